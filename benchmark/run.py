@@ -23,8 +23,10 @@ FILE_1 = YAMLS / "config.yaml"
 FILE_2 = YAMLS / "UGM3.yaml"
 # file from `https://examplefile.com`
 FILE_3 = YAMLS / "bench.yaml"
+# JSON API response, loaded as YAML
+FILE_4 = YAMLS / "api.json"
 
-FILES = [FILE_1, FILE_2, FILE_3]
+FILES = [FILE_1, FILE_2, FILE_3, FILE_4]
 
 CPU = cpuinfo.get_cpu_info()["brand_raw"]
 PY_VERSION = f"{platform.python_version()} ({platform.system()} {platform.release()})"
@@ -112,6 +114,7 @@ def run(run_count: int) -> None:
                 d, parse_datetime=False,
             ),
             "ryaml": lambda d=data: ryaml.loads_all(d),
+            "ryaml (saphyr)": lambda d=data: ryaml.loads_all(d, backend="saphyr"),
             "PyYAML": lambda d=data: list(pyyaml.safe_load_all(d)),
             "PyYAML (CLoader)": lambda d=data: list(pyyaml.load_all(
                 d, Loader=pyyaml.CLoader,
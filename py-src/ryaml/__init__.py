@@ -1,5 +1,66 @@
-from ._ryaml import InvalidYamlError, loads, loads_all, dumps
+from ._ryaml import (
+    InvalidYamlError,
+    loads,
+    loads_sharing_gil as _loads_sharing_gil,
+    loads_all,
+    loads_iter,
+    loads_lazy,
+    loads_into,
+    LazyMapping,
+    LazySequence,
+    load_iter as _load_iter,
+    DocumentIterator,
+    DocumentReader,
+    Document,
+    load_files,
+    select as _select,
+    select_all as _select_all,
+    map_file as _map_file,
+    read_path as _read_path,
+    read_fd as _read_fd,
+    write_path as _write_path,
+    MappedFile,
+    dumps,
+    dumps_all,
+    dumps_many,
+    dumps_fragment,
+    DocumentWriter,
+    from_json,
+    compose,
+    compose_all,
+    construct,
+    merge,
+    merge_nodes,
+    diff,
+    patch,
+    normalize,
+    content_hash,
+    serialize,
+    serialize_all,
+    scalar_kind,
+    quote_if_needed,
+    add_representer,
+    add_constructor,
+    add_implicit_resolver,
+    add_yaml_object,
+    TaggedValue,
+    Diagnostic,
+    Change,
+    Edit,
+    apply_edits,
+    check,
+    check_file,
+    parse,
+    emit,
+    PushParser,
+    tokenize_spans,
+    format,
+    BACKENDS,
+    COMPRESSIONS,
+)
 from .error import (
+    YAMLError,
+    MarkedYAMLError,
     ScannerError,
     ParserError,
     ComposerError,
@@ -10,36 +71,299 @@ from .error import (
     ReaderError,
 )
 from .compat import RSafeDumper
+from .containers import Comments, StyledList, StyledDict, StyledStr
+from .objects import YAMLObject
+from .features import MissingFeatureError, available, require
 
-from typing import IO, AnyStr, Any
+from typing import IO, AnyStr, Any, Iterable
+import asyncio
+import codecs
+import inspect
 import io
+import os
+import sys
+
+# A file given by its path
+_Path = str | bytes | os.PathLike[str] | os.PathLike[bytes]
+
+
+def _map_rest(fp: IO[Any]) -> MappedFile | None:
+    """The rest of ``fp`` mapped into memory instead of read, if it is a large
+    regular file of UTF-8 text, leaving ``fp`` at its end as reading would."""
+    if isinstance(fp, io.TextIOWrapper):
+        # Text is decoded by the loader, so only UTF-8 files can be mapped, and
+        # only from the start, before the wrapper holds any of it
+        if codecs.lookup(fp.encoding).name not in ("utf-8", "utf-8-sig"):
+            return None
+    elif not isinstance(fp, (io.BufferedReader, io.FileIO)):
+        return None
+    try:
+        fd = fp.fileno()
+        offset = fp.tell()
+    except (OSError, ValueError):
+        return None
+    if isinstance(fp, io.TextIOWrapper) and offset != 0:
+        return None
+    mapped = _map_file(fd, offset)
+    if mapped is not None:
+        fp.seek(0, io.SEEK_END)
+    return mapped
+
+
+def _read_descriptor(
+    fd: int, max_bytes: int | None, mmap: bool = False
+) -> bytes | MappedFile:
+    """The rest of the file open as ``fd``, mapped if ``mmap`` asks, or
+    read natively."""
+    try:
+        offset = os.lseek(fd, 0, os.SEEK_CUR)
+    except OSError:
+        # Pipes and terminals can't seek, nor be mapped
+        offset = None
+    if mmap and offset is not None:
+        mapped = _map_file(fd, offset)
+        if mapped is not None:
+            os.lseek(fd, 0, os.SEEK_END)
+            return mapped
+    if os.name != "posix":
+        with os.fdopen(fd, "rb", closefd=False) as fp:
+            return _read_file(fp, max_bytes)
+    return _read_fd(fd, b"", max_bytes)
+
 
+def _read_native(fp: IO[Any], max_bytes: int | None) -> bytes | None:
+    """The rest of a binary file read straight from its descriptor, rather
+    than through its read() calls, if it has one."""
+    if os.name != "posix" or not isinstance(fp, (io.BufferedReader, io.FileIO)):
+        return None
+    try:
+        fd = fp.fileno()
+    except (OSError, ValueError):
+        return None
+    # What the buffer holds has been read from the descriptor already
+    prefix = fp.read(len(fp.peek())) if isinstance(fp, io.BufferedReader) else b""
+    return _read_fd(fd, prefix, max_bytes)
 
-def _read_file(fp: IO[AnyStr]) -> str:
-    data = fp.read()
-    if isinstance(data, str):
+
+def _read_file(
+    fp: IO[AnyStr], max_bytes: int | None = None, mmap: bool = False
+) -> str | bytes | MappedFile:
+    mapped = _map_rest(fp) if mmap else None
+    if mapped is not None:
+        return mapped
+    native = _read_native(fp, max_bytes)
+    if native is not None:
+        return native
+    # Bytes are decoded by loads(), which detects their encoding. Past
+    # max_bytes, one more is enough for loads() to reject the stream.
+    data = fp.read() if max_bytes is None else fp.read(max_bytes + 1)
+    if isinstance(data, (str, bytes)):
         return data
-    elif isinstance(data, bytes):
-        return data.decode("utf8")
     else:
-        return bytes(data).decode('utf8')
+        return bytes(data)
 
 
-def load(fp: IO[AnyStr]) -> Any:
-    if not isinstance(fp, io.IOBase):
-        raise TypeError("fp must be a file-like object")
-    return loads(_read_file(fp))
+def _source_name(fp: IO[AnyStr], kwargs: dict[str, Any]) -> None:
+    name = getattr(fp, "name", None)
+    if isinstance(name, str):
+        kwargs.setdefault("name", name)
+
 
+def _stdin() -> IO[Any]:
+    # Decode the raw bytes as UTF-8 like any other YAML source, rather than
+    # with the locale encoding the text layer was set up with
+    buffer = getattr(sys.stdin, "buffer", None)
+    if buffer is not None:
+        return buffer
+    return sys.stdin
 
-def load_all(fp: IO[AnyStr]) -> list[Any]:
+
+def _load_file(fp: IO[AnyStr] | str, kwargs: dict[str, Any]) -> IO[Any]:
+    if fp == "-" or fp is sys.stdin:
+        kwargs.setdefault("name", "<stdin>")
+        return _stdin()
     if not isinstance(fp, io.IOBase):
-        raise TypeError("fp must be a file-like object")
-    return loads_all(_read_file(fp))
+        raise TypeError("fp must be a file-like object or '-'")
+    _source_name(fp, kwargs)
+    return fp
+
+
+def _is_path(fp: Any) -> bool:
+    return isinstance(fp, (str, bytes, os.PathLike)) and fp != "-"
+
+
+def _load_source(
+    fp: IO[AnyStr] | _Path | int, kwargs: dict[str, Any], mmap: bool = False
+) -> str | bytes | MappedFile:
+    if isinstance(fp, int) and not isinstance(fp, bool):
+        kwargs.setdefault("name", f"<fd {fp}>")
+        return _read_descriptor(fp, kwargs.get("max_bytes"), mmap)
+    if _is_path(fp):
+        path = os.fsdecode(fp)  # type: ignore
+        kwargs.setdefault("name", path)
+        return _read_path(path, mmap)
+    return _read_file(_load_file(fp, kwargs), kwargs.get("max_bytes"), mmap)  # type: ignore
+
+
+def _write_stdout(yaml: str | bytes) -> None:
+    buffer = getattr(sys.stdout, "buffer", None)
+    if buffer is None:
+        if isinstance(yaml, bytes):
+            raise TypeError("stdout can't take encoded output")
+        sys.stdout.write(yaml)
+        return
+    # Anything already printed must come out first
+    sys.stdout.flush()
+    buffer.write(yaml.encode("utf8") if isinstance(yaml, str) else yaml)
+    buffer.flush()
+
+
+def load(fp: IO[AnyStr] | _Path | int, *, mmap: bool = False, **kwargs: Any) -> Any:
+    """Load the document of ``fp``, a file, path or file descriptor.
+
+    With ``mmap=True``, a large regular file of UTF-8 text is mapped into
+    memory instead of read. Nothing may truncate or rewrite the file while
+    it loads: the mapping shows such changes, which can crash the process."""
+    return loads(_load_source(fp, kwargs, mmap), **kwargs)
+
+
+def load_all(
+    fp: IO[AnyStr] | _Path | int, *, mmap: bool = False, **kwargs: Any
+) -> list[Any]:
+    """Load every document of ``fp``, mapping it with ``mmap`` as
+    ``load()`` does."""
+    return loads_all(_load_source(fp, kwargs, mmap), **kwargs)
+
+
+def load_lazy(fp: IO[AnyStr] | _Path | int, *, mmap: bool = False, **kwargs: Any) -> Any:
+    """Load ``fp`` as ``loads_lazy()`` does: its mappings and sequences
+    construct their values only when they are first accessed."""
+    return loads_lazy(_load_source(fp, kwargs, mmap), **kwargs)
+
 
+def load_into(
+    fp: IO[AnyStr] | _Path | int, type: Any, *, mmap: bool = False, **kwargs: Any
+) -> Any:
+    """Load ``fp`` into ``type``, as ``loads_into()`` does."""
+    return loads_into(_load_source(fp, kwargs, mmap), type, **kwargs)
 
-def dump(fp: IO[AnyStr], obj: Any) -> None:
-    yaml = dumps(obj)
-    if isinstance(fp, io.TextIOBase):
+
+def load_iter(fp: IO[AnyStr] | str, **kwargs: Any) -> DocumentIterator:
+    """Iterate over the documents of ``fp``, reading it only as far as the
+    parser needs, so large files, pipes and sockets load in bounded memory.
+
+    Error marks don't quote the source, which isn't kept, and ``roundtrip``
+    keeps no comments."""
+    return _load_iter(_load_file(fp, kwargs), **kwargs)
+
+
+def _select_source(
+    source: str | bytes | MappedFile | IO[AnyStr] | os.PathLike[str],
+    kwargs: dict[str, Any],
+) -> str | bytes | MappedFile:
+    # A str is YAML text here, not a path
+    if isinstance(source, (str, bytes, bytearray, memoryview, MappedFile)):
+        return source  # type: ignore
+    return _load_source(source, kwargs)
+
+
+def select(
+    source: str | bytes | MappedFile | IO[AnyStr] | os.PathLike[str],
+    path: str,
+    **kwargs: Any,
+) -> Any:
+    """The first value at ``path``, such as ``spec.containers[0].image``, in
+    the documents of ``source``: YAML text, a file or a path. Only the value
+    is constructed, and parsing stops once it is found. Raises KeyError if
+    there is none.
+
+    Paths are keys separated by dots, with ``[N]`` for sequence items,
+    ``["key"]`` for keys holding dots or brackets, and ``*`` or ``[*]`` for
+    every value or item."""
+    return _select(_select_source(source, kwargs), path, **kwargs)
+
+
+def select_all(
+    source: str | bytes | MappedFile | IO[AnyStr] | os.PathLike[str],
+    path: str,
+    **kwargs: Any,
+) -> list[Any]:
+    """Every value at ``path`` in the documents of ``source``, as ``select()``
+    finds them."""
+    return _select_all(_select_source(source, kwargs), path, **kwargs)
+
+
+def _dump_output(fp: IO[AnyStr] | _Path, yaml: str | bytes, atomic: bool = False) -> None:
+    if _is_path(fp):
+        _write_path(os.fsdecode(fp), yaml, atomic)  # type: ignore
+    elif atomic:
+        raise TypeError("atomic=True needs fp to be a path")
+    elif fp == "-" or fp is sys.stdout:
+        _write_stdout(yaml)
+    elif isinstance(fp, io.TextIOBase) or isinstance(yaml, bytes):
+        # Output encoded as asked goes to the file as it is
         fp.write(yaml) # type: ignore
     else:
         fp.write(yaml.encode('utf8')) # type: ignore
+
+
+def dump(fp: IO[AnyStr] | _Path, obj: Any, *, atomic: bool = False, **kwargs: Any) -> None:
+    """Dump ``obj`` to ``fp``. With ``atomic``, ``fp`` must be a path, which
+    is replaced in one step: a crash mid-dump leaves the old file whole."""
+    _dump_output(fp, dumps(obj, **kwargs), atomic)
+
+
+def dump_all(
+    fp: IO[AnyStr] | _Path, objs: Iterable[Any], *, atomic: bool = False, **kwargs: Any
+) -> None:
+    _dump_output(fp, dumps_all(objs, **kwargs), atomic)
+
+
+async def _aread(fp: Any, max_bytes: int | None) -> str | bytes:
+    if max_bytes is None:
+        data = await fp.read()
+    else:
+        # Reads may return less than asked, as a StreamReader's do
+        chunks = []
+        size = 0
+        while size <= max_bytes:
+            chunk = await fp.read(max_bytes + 1 - size)
+            if not chunk:
+                break
+            chunks.append(chunk)
+            size += len(chunk)
+        data = "".join(chunks) if chunks and isinstance(chunks[0], str) else b"".join(chunks)
+    if isinstance(data, (str, bytes)):
+        return data
+    else:
+        return bytes(data)
+
+
+async def aload(fp: Any, **kwargs: Any) -> Any:
+    """Load ``fp``, an asyncio file such as a ``StreamReader`` or an aiofiles
+    file, awaiting its reads and loading on a worker thread so the event loop
+    goes on meanwhile: the text is parsed with the GIL released, then
+    constructed letting the event loop's thread take the GIL every
+    millisecond."""
+    _source_name(fp, kwargs)
+    data = await _aread(fp, kwargs.get("max_bytes"))
+    return await asyncio.to_thread(_loads_sharing_gil, data, **kwargs)
+
+
+async def adump(fp: Any, obj: Any, **kwargs: Any) -> None:
+    """Dump ``obj`` to ``fp``, an asyncio file such as a ``StreamWriter`` or
+    an aiofiles file, dumping on a worker thread and awaiting the write.
+
+    The dump holds the GIL throughout, as ``obj`` must not change while it
+    is read, so the event loop only runs while representers written in
+    Python do; a large ``obj`` holds it up until the dump is done."""
+    yaml = await asyncio.to_thread(dumps, obj, **kwargs)
+    binary = isinstance(fp, asyncio.StreamWriter) or "b" in getattr(fp, "mode", "")
+    if binary and isinstance(yaml, str):
+        yaml = yaml.encode("utf8")
+    written = fp.write(yaml)
+    if inspect.isawaitable(written):
+        await written
+    # A StreamWriter writes without waiting, then drains its buffer
+    if isinstance(fp, asyncio.StreamWriter):
+        await fp.drain()
@@ -1,16 +1,960 @@
 # pyright: strict
-from typing import Any
+import os
+import re
+from typing import IO, Any, Callable, ClassVar, Iterable, Iterator, Literal, Sequence, TypeVar, overload
+
+_T = TypeVar("_T")
 
 class InvalidYamlError(ValueError): ...
 
-def loads(s: str) -> Any: ...
-def loads_all(s: str) -> list[Any]: ...
-def dumps(obj: Any) -> str: ...
+class YAMLError(InvalidYamlError): ...
+
+class MarkedYAMLError(YAMLError):
+    context: str | None
+    context_mark: Mark | None
+    problem: str | None
+    problem_mark: Mark | None
+    note: str | None
+    line: int | None
+    column: int | None
+    snippet: str | None
+
+class ScannerError(MarkedYAMLError): ...
+class ParserError(MarkedYAMLError): ...
+class ComposerError(MarkedYAMLError): ...
+class ConstructorError(MarkedYAMLError): ...
+class EmitterError(YAMLError): ...
+class SerializerError(YAMLError): ...
+class RepresenterError(YAMLError): ...
+class ReaderError(YAMLError): ...
+
+class Mark:
+    name: str
+    index: int
+    line: int
+    column: int
+    buffer: str | None
+    pointer: int
+    def __init__(
+        self,
+        index: int,
+        line: int,
+        column: int,
+        name: str | None = None,
+        buffer: str | None = None,
+        pointer: int | None = None,
+    ) -> None: ...
+    def get_snippet(self, indent: int = 4, max_length: int = 75) -> str | None: ...
+
+class ScalarNode:
+    tag: str
+    value: str
+    start_mark: Mark | None
+    end_mark: Mark | None
+    style: str | None
+    id: str
+    def __init__(
+        self,
+        tag: str,
+        value: str,
+        start_mark: Mark | None = None,
+        end_mark: Mark | None = None,
+        style: str | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def find(self, path: str) -> Node | None: ...
+    def to_python(self, *, schema: Literal["1.1", "core", "json", "failsafe"] | None = None) -> Any: ...
+
+class SequenceNode:
+    tag: str
+    value: list[Node]
+    start_mark: Mark | None
+    end_mark: Mark | None
+    flow_style: bool | None
+    id: str
+    def __init__(
+        self,
+        tag: str,
+        value: list[Node],
+        start_mark: Mark | None = None,
+        end_mark: Mark | None = None,
+        flow_style: bool | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def find(self, path: str) -> Node | None: ...
+    def set(self, path: str, value: Any) -> None: ...
+    def delete(self, path: str) -> None: ...
+    def insert(self, index: int, value: Any) -> None: ...
+    def to_python(self, *, schema: Literal["1.1", "core", "json", "failsafe"] | None = None) -> Any: ...
+
+class MappingNode:
+    tag: str
+    value: list[tuple[Node, Node]]
+    start_mark: Mark | None
+    end_mark: Mark | None
+    flow_style: bool | None
+    id: str
+    def __init__(
+        self,
+        tag: str,
+        value: list[tuple[Node, Node]],
+        start_mark: Mark | None = None,
+        end_mark: Mark | None = None,
+        flow_style: bool | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    __hash__: ClassVar[None]  # type: ignore[assignment]
+    def find(self, path: str) -> Node | None: ...
+    def set(self, path: str, value: Any) -> None: ...
+    def delete(self, path: str) -> None: ...
+    def to_python(self, *, schema: Literal["1.1", "core", "json", "failsafe"] | None = None) -> Any: ...
+
+Node = ScalarNode | SequenceNode | MappingNode
+
+BACKENDS: list[str]
+COMPRESSIONS: list[str]
+
+@overload
+def loads(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    explain: Literal[False] = False,
+    return_anchors: Literal[False] = False,
+    stats: Literal[False] = False,
+    allow_multiple: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> Any: ...
+@overload
+def loads(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    explain: Literal[True],
+    return_anchors: Literal[False] = False,
+    stats: Literal[False] = False,
+    allow_multiple: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> tuple[Any, list[Resolution]]: ...
+@overload
+def loads(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    explain: Literal[False] = False,
+    return_anchors: Literal[True],
+    stats: Literal[False] = False,
+    allow_multiple: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> tuple[Any, dict[str, Any]]: ...
+@overload
+def loads(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    explain: Literal[True],
+    return_anchors: Literal[True],
+    stats: Literal[False] = False,
+    allow_multiple: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> tuple[Any, list[Resolution], dict[str, Any]]: ...
+@overload
+def loads(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    explain: Literal[False] = False,
+    return_anchors: Literal[False] = False,
+    stats: Literal[True],
+    allow_multiple: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> tuple[Any, ParseStats]: ...
+@overload
+def loads(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    explain: bool = False,
+    return_anchors: bool = False,
+    stats: Literal[True],
+    allow_multiple: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> tuple[Any, ...]: ...
+def loads_sharing_gil(s: str | bytes | bytearray | memoryview | MappedFile, **kwargs: Any) -> Any: ...
+def load_files(
+    paths: Sequence[str | os.PathLike[str]],
+    *,
+    max_workers: int | None = None,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    name: str | None = None,
+) -> dict[Any, Any]: ...
+def loads_all(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    progress: Callable[[int, int], object] | None = None,
+    max_workers: int | None = None,
+    on_error: Callable[[Exception], object] | None = None,
+    with_meta: bool = False,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    global_anchors: bool = False,
+    name: str | None = None,
+) -> list[Any]: ...
+def loads_iter(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    global_anchors: bool = False,
+    name: str | None = None,
+) -> DocumentIterator: ...
+def load_iter(
+    fp: IO[Any],
+    *,
+    backend: str = "libyaml",
+    allow_nan_keys: bool = True,
+    parse_datetimes: bool = True,
+    preserve_flow_style: bool = False,
+    roundtrip: bool = False,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    float_precision: Literal["ignore", "warn", "error"] = "ignore",
+    preserve_unknown_tags: bool = False,
+    string_keys: bool = False,
+    merge_keys: bool | None = None,
+    max_depth: int | None = None,
+    max_nodes: int | None = None,
+    max_bytes: int | None = None,
+    expand_env: bool = False,
+    env_allowlist: Iterable[str] | None = None,
+    include_dir: str | os.PathLike[str] | None = None,
+    max_include_depth: int = 8,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    global_anchors: bool = False,
+    name: str | None = None,
+) -> DocumentIterator: ...
+@overload
+def from_json(
+    json: str,
+    *,
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: None = None,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+) -> str: ...
+@overload
+def from_json(
+    json: str,
+    *,
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: Literal["utf-8", "utf-16-le", "utf-16-be"],
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+) -> bytes: ...
+@overload
+def dumps(
+    obj: Any,
+    *,
+    dry_run: Literal[False] = False,
+    third_party_datetimes: bool = False,
+    timezone: Literal["preserve", "utc", "naive"] = "preserve",
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: None = None,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    stringify_keys: bool = False,
+    sort_keys: bool | Literal["natural"] | Callable[[Any], Any] = False,
+    compact_threshold: int | None = None,
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    dump_dataclasses: bool = False,
+    dump_numpy: bool = False,
+    iterable_as_sequence: bool = False,
+    coerce_unknown: Literal["error", "str", "repr"] = "error",
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+    aliases: bool = True,
+    default: Callable[[Any], Any] | None = None,
+) -> str: ...
+@overload
+def dumps(
+    obj: Any,
+    *,
+    dry_run: Literal[False] = False,
+    third_party_datetimes: bool = False,
+    timezone: Literal["preserve", "utc", "naive"] = "preserve",
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: Literal["utf-8", "utf-16-le", "utf-16-be"],
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    stringify_keys: bool = False,
+    sort_keys: bool | Literal["natural"] | Callable[[Any], Any] = False,
+    compact_threshold: int | None = None,
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    dump_dataclasses: bool = False,
+    dump_numpy: bool = False,
+    iterable_as_sequence: bool = False,
+    coerce_unknown: Literal["error", "str", "repr"] = "error",
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+    aliases: bool = True,
+    default: Callable[[Any], Any] | None = None,
+) -> bytes: ...
+@overload
+def dumps(
+    obj: Any,
+    *,
+    dry_run: Literal[True],
+    third_party_datetimes: bool = False,
+    timezone: Literal["preserve", "utc", "naive"] = "preserve",
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: Literal["utf-8", "utf-16-le", "utf-16-be"] | None = None,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    stringify_keys: bool = False,
+    sort_keys: bool | Literal["natural"] | Callable[[Any], Any] = False,
+    compact_threshold: int | None = None,
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    dump_dataclasses: bool = False,
+    dump_numpy: bool = False,
+    iterable_as_sequence: bool = False,
+    coerce_unknown: Literal["error", "str", "repr"] = "error",
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+    aliases: bool = True,
+    default: Callable[[Any], Any] | None = None,
+) -> DumpPlan: ...
+def dumps_all(
+    objs: Iterable[Any],
+    *,
+    third_party_datetimes: bool = False,
+    timezone: Literal["preserve", "utc", "naive"] = "preserve",
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: Literal["utf-8", "utf-16-le", "utf-16-be"] | None = None,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    stringify_keys: bool = False,
+    sort_keys: bool | Literal["natural"] | Callable[[Any], Any] = False,
+    compact_threshold: int | None = None,
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    dump_dataclasses: bool = False,
+    dump_numpy: bool = False,
+    iterable_as_sequence: bool = False,
+    coerce_unknown: Literal["error", "str", "repr"] = "error",
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+    aliases: bool = True,
+    default: Callable[[Any], Any] | None = None,
+) -> str | bytes: ...
+def dumps_many(
+    objs: Sequence[Any],
+    *,
+    max_workers: int | None = None,
+    third_party_datetimes: bool = False,
+    timezone: Literal["preserve", "utc", "naive"] = "preserve",
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    encoding: Literal["utf-8", "utf-16-le", "utf-16-be"] | None = None,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    document_end: Literal["auto", "always", "never"] = "auto",
+    final_newline: bool = True,
+    stringify_keys: bool = False,
+    sort_keys: bool | Literal["natural"] | Callable[[Any], Any] = False,
+    compact_threshold: int | None = None,
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    dump_dataclasses: bool = False,
+    dump_numpy: bool = False,
+    iterable_as_sequence: bool = False,
+    coerce_unknown: Literal["error", "str", "repr"] = "error",
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+    aliases: bool = True,
+    default: Callable[[Any], Any] | None = None,
+) -> list[str] | list[bytes]: ...
+def compat_load(stream: str | bytes | IO[Any], Loader: type | None = None) -> Any: ...
+def compat_load_all(stream: str | bytes | IO[Any], Loader: type | None = None) -> list[Any]: ...
+def compat_dump_all(
+    documents: Iterable[Any], stream: IO[Any] | None = None, Dumper: type | None = None, **kwargs: Any
+) -> Any: ...
+def dumps_fragment(
+    obj: Any,
+    indent_level: int = 0,
+    *,
+    third_party_datetimes: bool = False,
+    timezone: Literal["preserve", "utc", "naive"] = "preserve",
+    styles: dict[str, str] | None = None,
+    default_style: Literal["literal", "folded", "single", "double", "|", ">", "'", '"'] | None = None,
+    canonical: bool = False,
+    line_break: Literal["\n", "\r\n", "\r"] = "\n",
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+    max_items: int | None = None,
+    allow_nan: bool = True,
+    stringify_keys: bool = False,
+    sort_keys: bool | Literal["natural"] | Callable[[Any], Any] = False,
+    compact_threshold: int | None = None,
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    dump_dataclasses: bool = False,
+    dump_numpy: bool = False,
+    iterable_as_sequence: bool = False,
+    coerce_unknown: Literal["error", "str", "repr"] = "error",
+    quote_ambiguous: bool = False,
+    literal_multiline: bool = False,
+    allow_unicode: bool = True,
+    aliases: bool = True,
+    default: Callable[[Any], Any] | None = None,
+) -> str: ...
+def loads_lazy(s: str | bytes | bytearray | memoryview | MappedFile, **kwargs: Any) -> Any: ...
+@overload
+def loads_into(
+    s: str | bytes | bytearray | memoryview | MappedFile, type: type[_T], **kwargs: Any
+) -> _T: ...
+@overload
+def loads_into(s: str | bytes | bytearray | memoryview | MappedFile, type: Any, **kwargs: Any) -> Any: ...
+def compose(s: str | bytes | bytearray | memoryview | MappedFile, **kwargs: Any) -> Node | None: ...
+def compose_all(s: str | bytes | bytearray | memoryview | MappedFile, **kwargs: Any) -> list[Node]: ...
+def construct(node: Any) -> Any: ...
+def merge(
+    documents: Iterable[str | bytes | bytearray | memoryview | MappedFile | Node],
+    *,
+    strategy: Literal["deep", "override"] | Callable[[list[str | int], Node, Node], Node | None] | None = None,
+    lists: Literal["replace", "append", "merge-by-key"] = "replace",
+    list_key: str | None = None,
+    **kwargs: Any,
+) -> Any: ...
+def merge_nodes(
+    documents: Iterable[str | bytes | bytearray | memoryview | MappedFile | Node],
+    *,
+    strategy: Literal["deep", "override"] | Callable[[list[str | int], Node, Node], Node | None] | None = None,
+    lists: Literal["replace", "append", "merge-by-key"] = "replace",
+    list_key: str | None = None,
+    **kwargs: Any,
+) -> Node | None: ...
+def diff(
+    a: str | bytes | bytearray | memoryview | MappedFile | Node,
+    b: str | bytes | bytearray | memoryview | MappedFile | Node,
+    **kwargs: Any,
+) -> list[Change]: ...
+def patch(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    patch: list[dict[str, Any]] | dict[Any, Any],
+    *,
+    comments: bool = True,
+    **kwargs: Any,
+) -> str: ...
+def normalize(s: str | bytes | bytearray | memoryview | MappedFile, **kwargs: Any) -> str: ...
+def content_hash(
+    data: Any,
+    *,
+    algorithm: Literal["blake3", "sha256"] = "blake3",
+    sort_keys: bool = True,
+    **kwargs: Any,
+) -> str: ...
+def serialize(node: Node) -> str: ...
+def serialize_all(nodes: list[Node]) -> str: ...
+def scalar_kind(
+    value: str,
+    *,
+    schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+) -> Literal["null", "bool", "int", "float", "timestamp", "merge", "value", "str"] | None: ...
+def quote_if_needed(value: str) -> str: ...
+def apply_edits(text: str, edits: Iterable[Edit]) -> str: ...
+def select(
+    s: str | bytes | bytearray | memoryview | MappedFile, path: str, **kwargs: Any
+) -> Any: ...
+def select_all(
+    s: str | bytes | bytearray | memoryview | MappedFile, path: str, **kwargs: Any
+) -> list[Any]: ...
+def check(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    name: str | None = None,
+    backend: str = "libyaml",
+    recover: bool = False,
+) -> list[Diagnostic]: ...
+def check_file(
+    path: str | os.PathLike[str],
+    *,
+    name: str | None = None,
+    backend: str = "libyaml",
+    recover: bool = False,
+) -> list[Diagnostic]: ...
+def add_representer(data_type: type[Any], representer: Callable[[Any], Node]) -> None: ...
+def add_constructor(tag: str, constructor: Callable[[Constructor, Node], Any]) -> None: ...
+def add_implicit_resolver(
+    tag: str, pattern: str | re.Pattern[str], first: Iterable[str] | None = None
+) -> None: ...
+def add_yaml_object(cls: type[Any]) -> None: ...
+def parse(s: str | bytes | bytearray | memoryview | MappedFile, *, backend: str = "libyaml") -> list[Any]: ...
+def emit(events: Iterable[Any], stream: IO[str] | None = None) -> str | None: ...
+def format(
+    s: str | bytes | bytearray | memoryview | MappedFile,
+    *,
+    indent: int = 2,
+    width: int = 100,
+    preserve_flow_style: bool = False,
+    comments: bool = True,
+) -> str: ...
+def tokenize_spans(s: str | bytes | bytearray | memoryview | MappedFile) -> list[tuple[str, int, int]]: ...
+
+class PushParser:
+    def __init__(self, *, backend: str = "libyaml") -> None: ...
+    def feed(self, chunk: bytes) -> list[Any]: ...
+    def finish(self) -> list[Any]: ...
+
+class TaggedValue:
+    tag: str
+    value: Any
+    def __init__(self, tag: str, value: Any) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class DumpPlan:
+    scalars: int
+    sequences: int
+    mappings: int
+    anchors: list[str]
+    estimated_size: int
+    needs_quoting: list[str]
+    explicit_tags: list[str]
+
+class Constructor:
+    def construct_scalar(self, node: Node) -> str: ...
+    def construct_sequence(self, node: Node, deep: bool = False) -> list[Any]: ...
+    def construct_mapping(self, node: Node, deep: bool = False) -> dict[Any, Any]: ...
+    def construct_object(self, node: Node, deep: bool = False) -> Any: ...
+
+class Resolution:
+    value: str
+    tag: str
+    mark: Mark
+
+class ParseStats:
+    documents: int
+    scalars: int
+    sequences: int
+    mappings: int
+    anchors: int
+    aliases: int
+    max_depth: int
+    parse_time: float
+    load_time: float
+
+class Diagnostic:
+    severity: Literal["error", "warning", "info"]
+    code: str
+    message: str
+    mark: Mark | None
+    end_mark: Mark | None
+    path: tuple[Any, ...]
+    def __init__(
+        self,
+        severity: Literal["error", "warning", "info"],
+        code: str,
+        message: str,
+        mark: Mark | None = None,
+        end_mark: Mark | None = None,
+        path: Sequence[Any] = (),
+    ) -> None: ...
+
+class Change:
+    kind: Literal["added", "removed", "changed"]
+    path: tuple[Any, ...]
+    old: Any
+    new: Any
+    old_mark: Mark | None
+    new_mark: Mark | None
+
+class Edit:
+    span: tuple[int, int]
+    replacement: str
+    def __init__(self, span: tuple[int, int], replacement: str) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class DocumentWriter:
+    def __init__(self, stream: IO[Any], **kwargs: Any) -> None: ...
+    def write(self, obj: Any) -> None: ...
+    def flush(self) -> None: ...
+    def close(self) -> None: ...
+    def __enter__(self) -> DocumentWriter: ...
+    def __exit__(self, exc_type: object, exc_value: object, traceback: object) -> bool: ...
+
+class DocumentIterator:
+    def __iter__(self) -> DocumentIterator: ...
+    def __next__(self) -> Any: ...
+
+class DocumentReader:
+    def __init__(
+        self, stream: IO[Any], follow: bool = False, poll_interval: float = 0.1, **kwargs: Any
+    ) -> None: ...
+    def __iter__(self) -> DocumentReader: ...
+    def __next__(self) -> Any: ...
+
+class Document:
+    data: Any
+    version: tuple[int, int] | None
+    tags: dict[str, str]
+    start_mark: Mark
+    end_mark: Mark
+
+class MappedFile:
+    def __len__(self) -> int: ...
+
+class LazyMapping:
+    def __len__(self) -> int: ...
+    def __getitem__(self, key: Any) -> Any: ...
+    def __contains__(self, key: object) -> bool: ...
+    def __iter__(self) -> Iterator[Any]: ...
+    def get(self, key: Any, default: Any = None) -> Any: ...
+    def keys(self) -> list[Any]: ...
+    def values(self) -> list[Any]: ...
+    def items(self) -> list[tuple[Any, Any]]: ...
+    def materialize(self) -> dict[Any, Any]: ...
+
+class LazySequence:
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int | slice) -> Any: ...
+    def __contains__(self, item: object) -> bool: ...
+    def __iter__(self) -> Iterator[Any]: ...
+    def materialize(self) -> list[Any]: ...
+
+def map_file(fd: int, offset: int) -> MappedFile | None: ...
+def read_path(path: str | os.PathLike[str], mmap: bool = False) -> bytes | MappedFile: ...
+def read_fd(fd: int, prefix: bytes, max_bytes: int | None = None) -> bytes: ...
+def write_path(path: str | os.PathLike[str], data: str | bytes, atomic: bool = False) -> None: ...
 
 class _RSafeLoader:
-    # Note that this class only takes str | bytes because we want to do all I/O
-    # at the Python layer
-    def __init__(self, stream: str | bytes) -> None: ...
+    # Note that this class only takes text or binary buffers because we want to
+    # do all I/O at the Python layer
+    def __init__(
+        self,
+        stream: str | bytes | bytearray | memoryview | MappedFile,
+        *,
+        backend: str = "libyaml",
+        allow_nan_keys: bool = True,
+        parse_datetimes: bool = True,
+        preserve_flow_style: bool = False,
+        roundtrip: bool = False,
+        schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+        float_precision: Literal["ignore", "warn", "error"] = "ignore",
+        preserve_unknown_tags: bool = False,
+        string_keys: bool = False,
+        merge_keys: bool | None = None,
+        max_depth: int | None = None,
+        max_nodes: int | None = None,
+        max_bytes: int | None = None,
+        expand_env: bool = False,
+        env_allowlist: Iterable[str] | None = None,
+        include_dir: str | os.PathLike[str] | None = None,
+        max_include_depth: int = 8,
+        warn_ambiguous: bool = False,
+        intern_values: bool = False,
+        allow_duplicate_anchors: bool = True,
+    allow_duplicate_anchors: bool = True,
+    warn_ambiguous: bool = False,
+    intern_values: bool = False,
+    allow_duplicate_anchors: bool = True,
+        object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+        mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+        object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+    object_pairs_hook: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    mapping_type: Callable[[list[tuple[Any, Any]]], Any] | None = None,
+    object_hook: Callable[[dict[Any, Any]], Any] | None = None,
+        global_anchors: bool = False,
+        name: str | None = None,
+    ) -> None: ...
+    @classmethod
+    def add_constructor(cls, tag: str, constructor: Callable[[Constructor, Node], Any]) -> None: ...
+    @classmethod
+    def add_implicit_resolver(
+        cls, tag: str, pattern: str | re.Pattern[str], first: Iterable[str] | None = None
+    ) -> None: ...
+    def check_node(self) -> bool: ...
+    def get_node(self) -> Node | None: ...
+    def get_single_node(self) -> Node | None: ...
+    def construct_scalar(self, node: Node) -> str: ...
+    def construct_sequence(self, node: Node, deep: bool = False) -> list[Any]: ...
+    def construct_mapping(self, node: Node, deep: bool = False) -> dict[Any, Any]: ...
+    def construct_object(self, node: Node, deep: bool = False) -> Any: ...
+    def resolve(
+        self, kind: type[Node], value: str | list[Any], implicit: tuple[bool, bool] | bool
+    ) -> str: ...
+    def construct_yaml_null(self, node: Node) -> None: ...
+    def construct_yaml_bool(self, node: Node) -> bool: ...
+    def construct_yaml_int(self, node: Node) -> int: ...
+    def construct_yaml_float(self, node: Node) -> float: ...
+    def construct_yaml_binary(self, node: Node) -> bytes: ...
+    def construct_yaml_timestamp(self, node: Node) -> Any: ...
+    def construct_yaml_str(self, node: Node) -> str: ...
+    def construct_yaml_seq(self, node: Node) -> Any: ...
+    def construct_yaml_map(self, node: Node) -> Any: ...
+    def construct_yaml_set(self, node: Node) -> Any: ...
+    @property
+    def tag_handles(self) -> dict[str, str]: ...
+    def set_input(self, source: str | bytes | bytearray | memoryview | MappedFile) -> None: ...
+    def reset(self, source: str | bytes | bytearray | memoryview | MappedFile) -> None: ...
+    def reset_state(self) -> None: ...
+    @property
+    def cache_sizes(self) -> dict[str, int]: ...
+
+class _RBaseLoader(_RSafeLoader): ...
+
+class _RFullLoader(_RSafeLoader): ...
+
+class _RUnsafeLoader(_RSafeLoader): ...
 
 class _RSafeDumper:
     def __init__(
@@ -28,5 +972,73 @@ class _RSafeDumper:
         explicit_end: bool | None = None,
         version: tuple[int, int] | None = None,
         tags: dict[str, str] | None = None,
-        sort_keys: bool = True,
+        sort_keys: bool | Literal["natural"] | Callable[[Any], Any] | None = None,
+        third_party_datetimes: bool = False,
+        timezone: Literal["preserve", "utc", "naive"] = "preserve",
+        styles: dict[str, str] | None = None,
+        schema: Literal["1.1", "core", "json", "failsafe"] = "1.1",
+        max_items: int | None = None,
+        allow_nan: bool = True,
+        document_end: Literal["auto", "always", "never"] = "auto",
+        final_newline: bool = True,
+        stringify_keys: bool = False,
+        dump_dataclasses: bool = False,
+        dump_numpy: bool = False,
+        iterable_as_sequence: bool = False,
+        coerce_unknown: Literal["error", "str", "repr"] = "error",
+        quote_ambiguous: bool = False,
+        literal_multiline: bool = False,
+        aliases: bool = True,
+        default: Callable[[Any], Any] | None = None,
+        compact_threshold: int | None = None,
+        set_style: Literal["block", "flow", "sequence"] = "block",
+    set_style: Literal["block", "flow", "sequence"] = "block",
+    ) -> None: ...
+    @classmethod
+    def add_implicit_resolver(
+        cls, tag: str, pattern: str | re.Pattern[str], first: Iterable[str] | None = None
+    ) -> None: ...
+    def open(self) -> None: ...
+    def represent(self, data: Any) -> None: ...
+    def represent_data(self, data: Any) -> Node: ...
+    def ignore_aliases(self, data: Any) -> bool: ...
+    def represent_scalar(self, tag: str, value: str, style: str | None = None) -> ScalarNode: ...
+    def represent_sequence(
+        self, tag: str, sequence: Iterable[Any], flow_style: bool | None = None
+    ) -> SequenceNode: ...
+    def represent_mapping(
+        self,
+        tag: str,
+        mapping: dict[Any, Any] | Iterable[tuple[Any, Any]],
+        flow_style: bool | None = None,
+    ) -> MappingNode: ...
+    def represent_none(self, data: None) -> Node: ...
+    def represent_bool(self, data: bool) -> Node: ...
+    def represent_int(self, data: int) -> Node: ...
+    def represent_float(self, data: float) -> Node: ...
+    def represent_str(self, data: str) -> Node: ...
+    def represent_binary(self, data: bytes) -> Node: ...
+    def represent_date(self, data: Any) -> Node: ...
+    def represent_datetime(self, data: Any) -> Node: ...
+    def represent_list(self, data: list[Any] | tuple[Any, ...]) -> Node: ...
+    def represent_dict(self, data: dict[Any, Any]) -> Node: ...
+    def represent_set(self, data: set[Any] | frozenset[Any]) -> Node: ...
+    def serialize(self, node: Node) -> None: ...
+    def serialize_all(self, nodes: list[Node]) -> None: ...
+    def close(self) -> None: ...
+    def dispose(self) -> None: ...
+    def reset(self, stream: Any) -> None: ...
+
+class _REmitter:
+    def __init__(
+        self,
+        stream: IO[str] | IO[bytes],
+        canonical: bool | None = None,
+        indent: int | None = None,
+        width: int | None = None,
+        allow_unicode: bool | None = None,
+        line_break: str | None = None,
+        encoding: str | None = None,
     ) -> None: ...
+    def emit(self, event: Any) -> None: ...
+    def dispose(self) -> None: ...
@@ -1,12 +1,114 @@
-"""Compatibilty layer with pyyaml to load YAML documents with Rust code."""
+"""Compatibilty layer with pyyaml to load YAML documents with Rust code.
 
-from typing import Protocol, TypeAlias, TypeVar
+Its ``load()``, ``dump()`` and other functions take the arguments pyyaml's do,
+so ``import ryaml.compat as yaml`` runs most code written for pyyaml as it is.
+It needs no pyyaml, so ``sys.modules["yaml"] = ryaml.compat`` stands in for
+pyyaml in dependencies which import it. Multi-constructors, multi-representers,
+path resolvers and ``scan()`` are not provided.
+"""
 
-from ryaml._ryaml import _RSafeLoader, _RSafeDumper
+import io
+from typing import Any, Callable, Iterable, Protocol, TypeAlias, TypeVar
 
-from yaml import BaseLoader
+from ryaml._ryaml import (
+    _RSafeLoader,
+    _RBaseLoader,
+    _RFullLoader,
+    _RUnsafeLoader,
+    _RSafeDumper,
+    compat_load as _load,
+    compat_load_all as _load_all,
+    compat_dump_all as _dump_all,
+    add_constructor as _add_constructor,
+    add_representer as _add_representer,
+    add_implicit_resolver as _add_implicit_resolver,
+    parse as _parse,
+    compose as _compose,
+    compose_all as _compose_all,
+    emit as _emit,
+    Mark,
+    ScalarNode,
+    SequenceNode,
+    MappingNode,
+    StreamStartEvent,
+    StreamEndEvent,
+    DocumentStartEvent,
+    DocumentEndEvent,
+    AliasEvent,
+    ScalarEvent,
+    SequenceStartEvent,
+    SequenceEndEvent,
+    MappingStartEvent,
+    MappingEndEvent,
+)
+from ryaml.error import (
+    YAMLError,
+    MarkedYAMLError,
+    ScannerError,
+    ParserError,
+    ComposerError,
+    ConstructorError,
+    EmitterError,
+    SerializerError,
+    RepresenterError,
+    ReaderError,
+)
+from ryaml.objects import YAMLObject, YAMLObjectMetaclass
 
-__all__ = ["RSafeLoader", "RSafeDumper"]
+try:
+    # Loaders pass for pyyaml's where it is installed
+    from yaml import BaseLoader as _BaseLoader
+except ImportError:
+    _BaseLoader = object  # type: ignore
+
+__all__ = [
+    "RSafeLoader",
+    "RBaseLoader",
+    "RFullLoader",
+    "RUnsafeLoader",
+    "RSafeDumper",
+    "SafeLoader",
+    "BaseLoader",
+    "FullLoader",
+    "UnsafeLoader",
+    "SafeDumper",
+    "Loader",
+    "Dumper",
+    "CSafeLoader",
+    "CBaseLoader",
+    "CFullLoader",
+    "CUnsafeLoader",
+    "CLoader",
+    "CSafeDumper",
+    "CDumper",
+    "load",
+    "load_all",
+    "safe_load",
+    "safe_load_all",
+    "full_load",
+    "full_load_all",
+    "unsafe_load",
+    "unsafe_load_all",
+    "dump",
+    "dump_all",
+    "safe_dump",
+    "safe_dump_all",
+    "YAMLError",
+    "MarkedYAMLError",
+    "ScannerError",
+    "ParserError",
+    "ComposerError",
+    "ConstructorError",
+    "EmitterError",
+    "SerializerError",
+    "RepresenterError",
+    "ReaderError",
+]
+
+# The pyyaml version whose API this module follows
+__version__ = "6.0"
+# Code choosing pyyaml's libyaml classes when they exist gets the Rust ones
+__with_libyaml__ = True
 
 # SupportsRead Protocol from the definition in typeshed
 _T_co = TypeVar("_T_co", covariant=True)
@@ -17,16 +119,193 @@ class SupportsRead(Protocol[_T_co]):
 # From the pyyaml type defintions in typeshed
 Readable: TypeAlias = SupportsRead[str | bytes]
 
-class RSafeLoader(_RSafeLoader, BaseLoader):
+def _read_stream(stream: str | bytes | Readable) -> tuple[str | bytes, str | None]:
+    # Binary data is decoded by the loader, which detects its encoding
+    try:
+        data = stream.read() # type: ignore
+    except AttributeError:
+        data = stream
+    name = getattr(stream, 'name', None)
+    if not isinstance(name, str):
+        name = None
+    return data, name # type: ignore
+
+class RSafeLoader(_RSafeLoader, _BaseLoader):
     def __new__(cls, stream: str | bytes | Readable) -> "RSafeLoader":
-        try:
-            data = stream.read() # type: ignore
-        except AttributeError:
-            data = stream
-        if isinstance(data, bytes):
-            data = data.decode('utf8')
-        return super().__new__(cls, data) # type: ignore
+        data, name = _read_stream(stream)
+        return super().__new__(cls, data, name=name) # type: ignore
+
+class RBaseLoader(_RBaseLoader, _BaseLoader):
+    """Like pyyaml's BaseLoader: every scalar is a string, sequences are lists
+    and mappings are dicts, whatever their tags."""
+    def __new__(cls, stream: str | bytes | Readable) -> "RBaseLoader":
+        data, name = _read_stream(stream)
+        return super().__new__(cls, data, name=name) # type: ignore
+
+class RFullLoader(_RFullLoader, _BaseLoader):
+    """Like pyyaml's FullLoader: also constructs !!python/tuple, !!python/complex,
+    !!python/name: and !!python/module: and the other builtin python tags, but
+    never instantiates arbitrary objects or imports modules."""
+    def __new__(cls, stream: str | bytes | Readable) -> "RFullLoader":
+        data, name = _read_stream(stream)
+        return super().__new__(cls, data, name=name) # type: ignore
+
+class RUnsafeLoader(_RUnsafeLoader, _BaseLoader):
+    """Like pyyaml's UnsafeLoader: also constructs !!python/object,
+    !!python/object/new and !!python/object/apply, importing any module they
+    name. Only use it on trusted input."""
+    def __new__(cls, stream: str | bytes | Readable) -> "RUnsafeLoader":
+        data, name = _read_stream(stream)
+        return super().__new__(cls, data, name=name) # type: ignore
 
 class RSafeDumper(_RSafeDumper):
     """pyyaml-compatible safe YAML dumper backed by Rust."""
     pass
+
+SafeLoader = RSafeLoader
+BaseLoader = RBaseLoader
+FullLoader = RFullLoader
+UnsafeLoader = RUnsafeLoader
+SafeDumper = RSafeDumper
+# ryaml represents no python objects, so the default dumper is the safe one
+Loader = UnsafeLoader
+Dumper = SafeDumper
+CSafeLoader = SafeLoader
+CBaseLoader = BaseLoader
+CFullLoader = FullLoader
+CUnsafeLoader = UnsafeLoader
+CLoader = Loader
+CSafeDumper = SafeDumper
+CDumper = Dumper
+
+def load(stream: str | bytes | Readable, Loader: type | None = None) -> Any:
+    """Load the single document of ``stream`` as pyyaml's ``load()`` does;
+    without a ``Loader``, as safely as ``SafeLoader``."""
+    return _load(stream, Loader)
+
+def load_all(stream: str | bytes | Readable, Loader: type | None = None) -> list[Any]:
+    """Load every document of ``stream`` as pyyaml's ``load_all()`` does, but
+    into a list rather than a generator."""
+    return _load_all(stream, Loader)
+
+def safe_load(stream: str | bytes | Readable) -> Any:
+    return _load(stream)
+
+def safe_load_all(stream: str | bytes | Readable) -> list[Any]:
+    return _load_all(stream)
+
+def full_load(stream: str | bytes | Readable) -> Any:
+    return _load(stream, FullLoader)
+
+def full_load_all(stream: str | bytes | Readable) -> list[Any]:
+    return _load_all(stream, FullLoader)
+
+def unsafe_load(stream: str | bytes | Readable) -> Any:
+    return _load(stream, UnsafeLoader)
+
+def unsafe_load_all(stream: str | bytes | Readable) -> list[Any]:
+    return _load_all(stream, UnsafeLoader)
+
+def dump_all(
+    documents: Iterable[Any], stream: Any = None, Dumper: type | None = None, **kwds: Any
+) -> Any:
+    """Dump ``documents`` as pyyaml's ``dump_all()`` does, returning the YAML
+    when no ``stream`` is given. ``Dumper`` defaults to ``SafeDumper``, as
+    ryaml represents no python objects pyyaml's ``Dumper`` would."""
+    return _dump_all(documents, stream, Dumper, **kwds)
+
+def dump(data: Any, stream: Any = None, Dumper: type | None = None, **kwds: Any) -> Any:
+    return _dump_all([data], stream, Dumper, **kwds)
+
+def safe_dump_all(documents: Iterable[Any], stream: Any = None, **kwds: Any) -> Any:
+    return _dump_all(documents, stream, SafeDumper, **kwds)
+
+def safe_dump(data: Any, stream: Any = None, **kwds: Any) -> Any:
+    return _dump_all([data], stream, SafeDumper, **kwds)
+
+def parse(stream: str | bytes | Readable, Loader: type | None = None) -> list[Any]:
+    """The events of ``stream``, in a list rather than a generator."""
+    data, _ = _read_stream(stream)
+    return _parse(data)
+
+def compose(stream: str | bytes | Readable, Loader: type | None = None) -> Any:
+    data, name = _read_stream(stream)
+    return _compose(data, name=name)
+
+def compose_all(stream: str | bytes | Readable, Loader: type | None = None) -> list[Any]:
+    data, name = _read_stream(stream)
+    return _compose_all(data, name=name)
+
+def emit(events: Iterable[Any], stream: Any = None, Dumper: type | None = None) -> Any:
+    return _emit(events, stream)
+
+def serialize_all(
+    nodes: Iterable[Any], stream: Any = None, Dumper: type | None = None, **kwds: Any
+) -> Any:
+    """Serialize ``nodes`` as pyyaml's ``serialize_all()`` does, with an
+    instance of ``Dumper`` made with ``kwds``."""
+    if stream is None:
+        output = io.BytesIO() if kwds.get("encoding") is not None else io.StringIO()
+    else:
+        output = stream
+    dumper = (Dumper or SafeDumper)(output, **kwds)
+    try:
+        dumper.open()
+        for node in nodes:
+            dumper.serialize(node)
+        dumper.close()
+    finally:
+        dumper.dispose()
+    if stream is None:
+        return output.getvalue()
+    return None
+
+def serialize(node: Any, stream: Any = None, Dumper: type | None = None, **kwds: Any) -> Any:
+    return serialize_all([node], stream, Dumper, **kwds)
+
+def _is_pyyaml(cls: type | None, base: type) -> bool:
+    return cls is not None and not issubclass(cls, base)
+
+def add_constructor(
+    tag: str, constructor: Callable[[Any, Any], Any], Loader: type | None = None
+) -> None:
+    """Register ``constructor(loader, node)`` for nodes tagged ``tag``. It
+    applies to every ryaml loader; a pyyaml ``Loader`` gets it instead."""
+    if _is_pyyaml(Loader, _RSafeLoader):
+        Loader.add_constructor(tag, constructor)  # type: ignore
+    else:
+        _add_constructor(tag, constructor)
+
+def add_representer(
+    data_type: type, representer: Callable[[Any, Any], Any], Dumper: type | None = None
+) -> None:
+    """Register ``representer(dumper, data)`` for instances of ``data_type``.
+    It applies to every ryaml dumper, and is given a dumper of its own to
+    build nodes with; a pyyaml ``Dumper`` gets it instead."""
+    if _is_pyyaml(Dumper, _RSafeDumper):
+        Dumper.add_representer(data_type, representer)  # type: ignore
+        return
+    dumper_class = Dumper or SafeDumper
+
+    def represent(data: Any) -> Any:
+        return representer(dumper_class(None), data)
+
+    _add_representer(data_type, represent)
+
+def add_implicit_resolver(
+    tag: str,
+    regexp: Any,
+    first: Iterable[str] | None = None,
+    Loader: type | None = None,
+    Dumper: type | None = None,
+) -> None:
+    """Resolve plain scalars matching ``regexp`` to ``tag``, in every ryaml
+    loader and dumper; pyyaml's ``Loader`` and ``Dumper`` get it instead."""
+    pyyaml_loader = _is_pyyaml(Loader, _RSafeLoader)
+    pyyaml_dumper = _is_pyyaml(Dumper, _RSafeDumper)
+    if pyyaml_loader:
+        Loader.add_implicit_resolver(tag, regexp, first)  # type: ignore
+    if pyyaml_dumper:
+        Dumper.add_implicit_resolver(tag, regexp, first)  # type: ignore
+    if not (pyyaml_loader and pyyaml_dumper):
+        _add_implicit_resolver(tag, regexp, first)
@@ -0,0 +1,51 @@
+"""Types recording how a value was written in the source.
+
+Loading with ``preserve_flow_style=True`` produces ``StyledList`` and
+``StyledDict`` instead of plain ``list``/``dict``, and the dumper writes them
+back in the same style. ``roundtrip=True`` also records the anchors of
+collections, the comments around block mapping entries, and loads quoted and
+block strings as ``StyledStr``, so dumping what was loaded reproduces the
+source, short of indentation, comments inside sequences and flow collections,
+and other formatting YAML doesn't keep.
+"""
+
+from typing import Any, NamedTuple
+
+
+class Comments(NamedTuple):
+    """Comments written around a mapping entry.
+
+    ``before`` holds the lines above the key, each a comment starting with
+    ``#`` or ``""`` for a blank line; ``inline`` is the comment ending the
+    entry's line, written two spaces after it.
+    """
+
+    before: tuple[str, ...] = ()
+    inline: str | None = None
+
+
+class StyledList(list[Any]):
+    """A list remembering whether it was a flow (``[a, b]``) or block sequence."""
+
+    flow_style: bool | None = None
+    anchor: str | None = None
+
+
+class StyledDict(dict[Any, Any]):
+    """A dict remembering whether it was a flow (``{a: b}``) or block mapping,
+    and the comments around its entries, by key."""
+
+    flow_style: bool | None = None
+    anchor: str | None = None
+    comments: dict[Any, Comments]
+
+    def __init__(self, *args: Any, **kwargs: Any) -> None:
+        super().__init__(*args, **kwargs)
+        self.comments = {}
+
+
+class StyledStr(str):
+    """A str remembering the style it was written in: ``"'"`` or ``'"'`` when
+    quoted, ``"|"`` or ``">"`` for a literal or folded block."""
+
+    style: str | None = None
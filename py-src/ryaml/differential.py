@@ -0,0 +1,126 @@
+"""Differential checks of ryaml against ruamel.yaml's safe loader.
+
+Run a corpus through both libraries to find where they disagree::
+
+    python -m ryaml.differential configs/*.yaml
+
+Each document is loaded by both, and ryaml's dump of what it loaded is read
+back by ruamel.yaml, so a divergence is either a load mismatch, an error only
+one side raises, or output ruamel.yaml doesn't read back as the same data.
+ruamel.yaml is only needed to run the checks, not to import ryaml.
+"""
+
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Iterable, Literal
+import io
+import math
+import os
+import sys
+
+from ._ryaml import dumps, loads_all
+from .features import require
+
+# ruamel.yaml version used to mirror each ryaml schema
+_VERSIONS = {"1.1": (1, 1), "core": (1, 2)}
+
+
+@dataclass
+class Divergence:
+    """One disagreement between ryaml and ruamel.yaml."""
+
+    source: str
+    # "load": both loaded different data; "error": only one side raised;
+    # "dump": ruamel.yaml reads ryaml's output back as different data
+    kind: Literal["load", "error", "dump"]
+    ryaml: Any
+    ruamel: Any
+
+    def __str__(self) -> str:
+        return f"{self.source}: {self.kind}: ryaml={self.ryaml!r} ruamel={self.ruamel!r}"
+
+
+def _ruamel_loader(schema: str) -> Any:
+    require("differential")
+    from ruamel.yaml import YAML
+    if schema not in _VERSIONS:
+        raise ValueError(f"unsupported schema '{schema}' (expected '1.1' or 'core')")
+    yaml = YAML(typ="safe", pure=True)
+    yaml.version = _VERSIONS[schema]
+    return yaml
+
+
+def _normalize(value: Any) -> Any:
+    """Make NaN compare equal to itself so it doesn't count as a divergence."""
+    if isinstance(value, float) and math.isnan(value):
+        return "<nan>"
+    if isinstance(value, list):
+        return [_normalize(item) for item in value]
+    if isinstance(value, dict):
+        return {_normalize(k): _normalize(v) for k, v in value.items()}
+    return value
+
+
+def _load(load: Any, text: str) -> tuple[list[Any] | None, Exception | None]:
+    try:
+        return [_normalize(doc) for doc in load(text)], None
+    except Exception as e:
+        return None, e
+
+
+def compare_text(text: str, *, source: str = "<string>", schema: str = "1.1") -> list[Divergence]:
+    """Compare how ryaml and ruamel.yaml load (and ryaml dumps) ``text``."""
+    yaml = _ruamel_loader(schema)
+
+    def ruamel_load(s: str) -> list[Any]:
+        return list(yaml.load_all(io.StringIO(s)))
+
+    ours, our_error = _load(lambda s: loads_all(s, schema=schema), text)
+    theirs, their_error = _load(ruamel_load, text)
+    if our_error is not None or their_error is not None:
+        if (our_error is None) == (their_error is None):
+            return []
+        return [Divergence(source, "error", our_error or ours, their_error or theirs)]
+    assert ours is not None and theirs is not None
+    if ours != theirs:
+        return [Divergence(source, "load", ours, theirs)]
+
+    divergences = []
+    for doc in loads_all(text, schema=schema):
+        output = dumps(doc, schema=schema)
+        reloaded, error = _load(ruamel_load, output)
+        if error is not None or reloaded != [_normalize(doc)]:
+            divergences.append(Divergence(source, "dump", output, error or reloaded))
+    return divergences
+
+
+def compare_files(
+    paths: Iterable[str | os.PathLike[str]], *, schema: str = "1.1"
+) -> list[Divergence]:
+    """Compare ryaml and ruamel.yaml on every file in ``paths``."""
+    divergences = []
+    for path in paths:
+        text = Path(path).read_text(encoding="utf-8")
+        divergences.extend(compare_text(text, source=os.fspath(path), schema=schema))
+    return divergences
+
+
+def report(divergences: Iterable[Divergence]) -> str:
+    """A fix list: the divergences grouped by kind, one per line."""
+    lines = []
+    by_kind: dict[str, list[Divergence]] = {}
+    for divergence in divergences:
+        by_kind.setdefault(divergence.kind, []).append(divergence)
+    for kind in ("load", "error", "dump"):
+        found = by_kind.get(kind, [])
+        if found:
+            lines.append(f"{kind} ({len(found)}):")
+            lines.extend(f"  {divergence}" for divergence in found)
+    return "\n".join(lines)
+
+
+if __name__ == "__main__":
+    found = compare_files(sys.argv[1:])
+    if found:
+        print(report(found))
+    sys.exit(1 if found else 0)
@@ -0,0 +1,84 @@
+"""Checks for the optional parts of ryaml, so code depending on one fails up
+front with a message saying how to get it, rather than with an
+AttributeError or ImportError deep inside a call::
+
+    import ryaml
+
+    ryaml.require("testing")
+
+Optional parts are either compiled into the native extension (the parser
+backends in ``BACKENDS`` and the decompressors in ``COMPRESSIONS``) or need a
+third-party package installed.
+"""
+
+import importlib.util
+
+from ._ryaml import BACKENDS, COMPRESSIONS
+
+
+class MissingFeatureError(ImportError):
+    """An optional part of ryaml was asked for but isn't available."""
+
+    def __init__(self, feature: str, message: str) -> None:
+        super().__init__(message)
+        self.feature = feature
+
+
+# Module each feature needing a third-party package imports; the ryaml
+# extra installing it has the feature's name
+_PACKAGES = {
+    "testing": "hypothesis",
+    "differential": "ruamel.yaml",
+}
+
+# Compressed input formats, each decompressed if ryaml is built with the
+# cargo feature of the same name
+_COMPRESSIONS = ("gzip", "zstd", "xz")
+
+
+def features() -> list[str]:
+    """Names of every optional feature, available or not."""
+    return sorted([*_PACKAGES, *BACKENDS, *_COMPRESSIONS])
+
+
+def _installed(module: str) -> bool:
+    try:
+        return importlib.util.find_spec(module) is not None
+    except ImportError:
+        # The parent package of a dotted name is missing
+        return False
+
+
+def _problem(feature: str) -> str | None:
+    """Why ``feature`` isn't available, or None if it is."""
+    if feature in BACKENDS or feature in COMPRESSIONS:
+        return None
+    if feature in _COMPRESSIONS:
+        return (
+            f"ryaml was built without its '{feature}' feature; rebuild it "
+            f"from source with: maturin build --features {feature}"
+        )
+    module = _PACKAGES.get(feature)
+    if module is None:
+        raise ValueError(
+            f"unknown ryaml feature '{feature}' (expected one of {', '.join(features())})"
+        )
+    if _installed(module):
+        return None
+    return (
+        f"ryaml's '{feature}' feature needs {module} installed; "
+        f"install it with: pip install 'ryaml[{feature}]'"
+    )
+
+
+def available(feature: str) -> bool:
+    """Whether ``feature`` can be used."""
+    return _problem(feature) is None
+
+
+def require(feature: str) -> None:
+    """Raise ``MissingFeatureError`` saying how to enable ``feature`` if it
+    can't be used."""
+    problem = _problem(feature)
+    if problem is not None:
+        raise MissingFeatureError(feature, problem)
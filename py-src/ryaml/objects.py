@@ -0,0 +1,34 @@
+"""Base class for objects loaded from and dumped as tagged YAML mappings.
+
+Subclasses of ``YAMLObject`` declaring a ``yaml_tag`` are registered with
+``add_yaml_object()`` when the class is created, like pyyaml's ``YAMLObject``.
+"""
+
+from typing import Any
+
+from ._ryaml import add_yaml_object
+
+
+class YAMLObjectMetaclass(type):
+    """Registers every class whose body sets ``yaml_tag``."""
+
+    def __init__(cls, name: str, bases: tuple[type, ...], kwds: dict[str, Any]) -> None:
+        super().__init__(name, bases, kwds)
+        if kwds.get("yaml_tag") is not None:
+            add_yaml_object(cls)
+
+
+class YAMLObject(metaclass=YAMLObjectMetaclass):
+    """An object loaded from a mapping tagged ``yaml_tag`` and dumped back as one.
+
+    The mapping is the instance's state: it is restored with ``__setstate__``
+    or into ``__dict__`` when loading, and taken from ``__getstate__`` or
+    ``__dict__`` when dumping. Define the classmethods
+    ``from_yaml(constructor, node)`` and ``to_yaml(data)`` to convert
+    differently.
+    """
+
+    __slots__ = ()
+
+    yaml_tag: str | None = None
+    yaml_flow_style: bool | None = None
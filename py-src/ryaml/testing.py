@@ -0,0 +1,95 @@
+"""Property-based round-trip checks, built on hypothesis.
+
+``roundtrip()`` dumps values drawn from a strategy and asserts they load back
+equal. With no strategy it draws from ``values()``, every kind of data the
+safe dumper and loader handle; libraries registering their own representers
+and constructors pass a strategy building their objects instead::
+
+    from hypothesis import strategies as st
+    from ryaml.testing import roundtrip
+
+    def test_points_round_trip():
+        roundtrip(st.builds(Point, st.integers(), st.integers()))
+
+hypothesis is only needed to import this module, not ryaml.
+"""
+
+from typing import Any
+import math
+
+from .features import require
+
+require("testing")
+
+from hypothesis import given, settings, strategies as st
+
+from ._ryaml import dumps, loads
+
+
+def scalars() -> st.SearchStrategy[Any]:
+    """None, bools, ints, floats (NaN and infinities included), strings,
+    bytes and dates."""
+    return st.one_of(
+        st.none(),
+        st.booleans(),
+        st.integers(),
+        st.floats(),
+        # Surrogates can't be encoded; control characters are written escaped
+        st.text(st.characters(blacklist_categories=("Cs",))),
+        st.binary(),
+        st.dates(),
+    )
+
+
+def values(max_leaves: int = 50) -> st.SearchStrategy[Any]:
+    """Scalars nested in lists and in dicts keyed by strings and ints."""
+    keys = st.one_of(st.text(), st.integers())
+    return st.recursive(
+        scalars(),
+        lambda children: st.one_of(
+            st.lists(children), st.dictionaries(keys, children)
+        ),
+        max_leaves=max_leaves,
+    )
+
+
+def _same(a: Any, b: Any) -> bool:
+    """Equality that takes NaN to equal itself and tells bools from ints."""
+    if isinstance(a, float) and isinstance(b, float) and math.isnan(a):
+        return math.isnan(b)
+    if type(a) is not type(b) and (isinstance(a, bool) or isinstance(b, bool)):
+        return False
+    if isinstance(a, list) and isinstance(b, list):
+        return len(a) == len(b) and all(_same(x, y) for x, y in zip(a, b))
+    if isinstance(a, dict) and isinstance(b, dict):
+        return a.keys() == b.keys() and all(_same(v, b[k]) for k, v in a.items())
+    return bool(a == b)
+
+
+def assert_roundtrips(
+    value: Any,
+    dumps_kwargs: dict[str, Any] | None = None,
+    loads_kwargs: dict[str, Any] | None = None,
+) -> None:
+    """Assert ``value`` loads back equal after being dumped."""
+    text = dumps(value, **(dumps_kwargs or {}))
+    loaded = loads(text, **(loads_kwargs or {}))
+    assert _same(value, loaded), f"{value!r} loaded back as {loaded!r} from:\n{text}"
+
+
+def roundtrip(
+    strategy: st.SearchStrategy[Any] | None = None,
+    *,
+    dumps_kwargs: dict[str, Any] | None = None,
+    loads_kwargs: dict[str, Any] | None = None,
+    max_examples: int = 100,
+) -> None:
+    """Check that every value ``strategy`` draws round-trips, raising the
+    ``AssertionError`` of the smallest failing example hypothesis finds."""
+
+    @settings(max_examples=max_examples, deadline=None)
+    @given(values() if strategy is None else strategy)
+    def check(value: Any) -> None:
+        assert_roundtrips(value, dumps_kwargs, loads_kwargs)
+
+    check()
@@ -0,0 +1,74 @@
+//! Files written whole or not at all: the output goes to a temporary file
+//! beside the target, which is synced and then renamed over it, so a crash
+//! mid-write leaves the old file in place rather than a truncated one.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts temporary files made by this process, to keep their names apart
+static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Replace the file at `path` with `data` in one step
+pub fn write(path: &Path, data: &[u8]) -> io::Result<()> {
+    let (temporary, file) = create_temporary(path)?;
+    if let Err(e) = replace_with(file, &temporary, path, data) {
+        let _ = std::fs::remove_file(&temporary);
+        return Err(e);
+    }
+    sync_directory(path);
+    Ok(())
+}
+
+/// Fill `file`, open as `temporary`, with `data` and rename it to `path`
+fn replace_with(mut file: File, temporary: &Path, path: &Path, data: &[u8]) -> io::Result<()> {
+    file.write_all(data)?;
+    // A replaced file keeps its permissions
+    if let Ok(metadata) = std::fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(temporary, path)
+}
+
+/// A new file in the directory of `path`, named after it
+fn create_temporary(path: &Path) -> io::Result<(PathBuf, File)> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path names no file"))?;
+    loop {
+        let count = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+        let mut temporary_name = std::ffi::OsString::from(".");
+        temporary_name.push(name);
+        temporary_name.push(format!(".{}.{count}.tmp", std::process::id()));
+        let temporary = path.with_file_name(temporary_name);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temporary)
+        {
+            Ok(file) => return Ok((temporary, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Make the rename itself durable. Best effort, as not every file system
+/// lets a directory be synced.
+fn sync_directory(path: &Path) {
+    #[cfg(unix)]
+    {
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        if let Ok(directory) = File::open(directory) {
+            let _ = directory.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
@@ -0,0 +1,576 @@
+//! Parser backends feeding events to the loader.
+//!
+//! Every backend produces libyaml-safer `Event`s so the resolver and
+//! constructor layers are shared regardless of which parser is selected.
+//! saphyr-parser's events are converted as they are parsed; it doesn't report
+//! `%YAML` and `%TAG` directives, and numbers anchors instead of naming them,
+//! so their names are read back from the source text where there is one.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Cursor, Read};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use libyaml_safer::{
+    Encoding, ErrorKind, Event, EventData, MappingStyle, Mark, Parser, ScalarStyle, SequenceStyle,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use saphyr_parser::{BufferedInput, Marker, Span};
+
+use crate::encoding::SourceText;
+
+/// Names of the backends compiled into this build
+pub const BACKENDS: &[&str] = &["libyaml", "saphyr"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Libyaml,
+    Saphyr,
+}
+
+impl Backend {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "libyaml" => Ok(Backend::Libyaml),
+            "saphyr" => Ok(Backend::Saphyr),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported parser backend '{}' (available: {})",
+                name,
+                BACKENDS.join(", ")
+            ))),
+        }
+    }
+}
+
+/// Number of bytes or characters asked of a file object per `read()` call
+const READ_SIZE: usize = 64 * 1024;
+
+/// Input read from a Python file object a chunk at a time, as the parser asks
+/// for it. Text files give str, encoded to UTF-8; binary files give bytes,
+/// whose encoding the parser detects from the byte order mark.
+pub struct PyReader {
+    file: Py<PyAny>,
+    chunk: Vec<u8>,
+    /// Bytes of `chunk` the parser has consumed
+    offset: usize,
+    /// Exception raised by `read()`, kept to be raised in place of the
+    /// reader error the parser turns it into
+    error: Arc<Mutex<Option<PyErr>>>,
+    /// Bytes read so far, and the most that may be, from `max_bytes`
+    read: usize,
+    max_bytes: Option<usize>,
+    /// With `follow`, how long to wait before reading again at the end of
+    /// the file, which is then never treated as the end of the input
+    follow: Option<Duration>,
+}
+
+impl PyReader {
+    fn read_chunk(&self, py: Python) -> PyResult<Vec<u8>> {
+        let data = self.file.bind(py).call_method1("read", (READ_SIZE,))?;
+        if let Ok(text) = data.downcast::<PyString>() {
+            return Ok(text.to_str()?.as_bytes().to_vec());
+        }
+        let bytes = match data.downcast_into::<PyBytes>() {
+            Ok(bytes) => bytes,
+            // bytearray, memoryview and other buffers
+            Err(e) => py
+                .get_type::<PyBytes>()
+                .call1((e.into_inner(),))?
+                .downcast_into::<PyBytes>()?,
+        };
+        Ok(bytes.as_bytes().to_vec())
+    }
+
+    /// The next chunk, waiting for the file to grow at its end when following
+    fn next_chunk(&self, py: Python) -> PyResult<Vec<u8>> {
+        loop {
+            let chunk = self.read_chunk(py)?;
+            match self.follow {
+                Some(interval) if chunk.is_empty() => {
+                    py.detach(|| std::thread::sleep(interval));
+                    py.check_signals()?;
+                }
+                _ => return Ok(chunk),
+            }
+        }
+    }
+
+    /// Fail once more than `max_bytes` have been read
+    fn check_size(&self, py: Python) -> PyResult<()> {
+        match self.max_bytes {
+            Some(max_bytes) if self.read > max_bytes => Err(crate::exception::reader_error(
+                py,
+                format!("stream is longer than max_bytes={max_bytes}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Read for PyReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for PyReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.offset == self.chunk.len() {
+            self.chunk = Python::attach(|py| {
+                let chunk = self.next_chunk(py)?;
+                self.read += chunk.len();
+                self.check_size(py)?;
+                Ok(chunk)
+            })
+            .map_err(|e: PyErr| {
+                let message = e.to_string();
+                *self.error.lock().unwrap() = Some(e);
+                std::io::Error::other(message)
+            })?;
+            self.offset = 0;
+        }
+        Ok(&self.chunk[self.offset..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.offset = (self.offset + amount).min(self.chunk.len());
+    }
+}
+
+/// A syntax error found by either backend
+#[derive(Debug)]
+pub enum ParseError {
+    Libyaml(libyaml_safer::Error),
+    /// saphyr-parser's error, or its input failing to read or decode, as
+    /// `read` tells
+    Saphyr {
+        problem: String,
+        mark: Option<Mark>,
+        read: bool,
+    },
+}
+
+impl ParseError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ParseError::Libyaml(error) => error.kind(),
+            ParseError::Saphyr { read: true, .. } => ErrorKind::Reader,
+            // saphyr's scanner and parser share one error type
+            ParseError::Saphyr { read: false, .. } => ErrorKind::Parser,
+        }
+    }
+
+    pub fn problem(&self) -> &str {
+        match self {
+            ParseError::Libyaml(error) => error.problem(),
+            ParseError::Saphyr { problem, .. } => problem,
+        }
+    }
+
+    pub fn problem_mark(&self) -> Option<Mark> {
+        match self {
+            ParseError::Libyaml(error) => error.problem_mark(),
+            ParseError::Saphyr { mark, .. } => *mark,
+        }
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        match self {
+            ParseError::Libyaml(error) => error.context(),
+            ParseError::Saphyr { .. } => None,
+        }
+    }
+
+    pub fn context_mark(&self) -> Option<Mark> {
+        match self {
+            ParseError::Libyaml(error) => error.context_mark(),
+            ParseError::Saphyr { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Libyaml(error) => error.fmt(f),
+            ParseError::Saphyr {
+                problem,
+                mark: Some(mark),
+                ..
+            } => write!(
+                f,
+                "{problem} at line {} column {}",
+                mark.line + 1,
+                mark.column + 1
+            ),
+            ParseError::Saphyr { problem, .. } => f.write_str(problem),
+        }
+    }
+}
+
+/// UTF-8 characters read from `input` for saphyr-parser, which takes an
+/// iterator and so can't be told why it ended
+struct Chars<R> {
+    input: R,
+    /// Why reading `input` stopped before its end, once it has
+    failed: Arc<OnceLock<String>>,
+    started: bool,
+}
+
+impl<R: BufRead> Chars<R> {
+    fn next_char(&mut self) -> Result<Option<char>, String> {
+        let first = match self.input.fill_buf().map_err(|e| e.to_string())? {
+            [] => return Ok(None),
+            [byte, ..] if byte.is_ascii() => {
+                let c = char::from(*byte);
+                self.input.consume(1);
+                return Ok(Some(c));
+            }
+            [byte, ..] => *byte,
+        };
+        let width = match first {
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => return Err("invalid leading UTF-8 octet".to_string()),
+        };
+        // The bytes of a character may be split between chunks of the input
+        let mut bytes = [0; 4];
+        for byte in &mut bytes[..width] {
+            *byte = match self.input.fill_buf().map_err(|e| e.to_string())? {
+                [] => return Err("incomplete UTF-8 octet sequence".to_string()),
+                [next, ..] => *next,
+            };
+            self.input.consume(1);
+        }
+        match std::str::from_utf8(&bytes[..width]) {
+            Ok(text) => Ok(text.chars().next()),
+            Err(_) => Err("invalid trailing UTF-8 octet".to_string()),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Chars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self.next_char() {
+            // saphyr doesn't skip the byte order mark itself
+            Ok(Some('\u{feff}')) if !self.started => {
+                self.started = true;
+                self.next()
+            }
+            Ok(c) => {
+                self.started = true;
+                c
+            }
+            Err(problem) => {
+                let _ = self.failed.set(problem);
+                None
+            }
+        }
+    }
+}
+
+/// saphyr-parser over the UTF-8 text of `R`, its events converted to
+/// libyaml's
+pub struct Saphyr<R: BufRead + 'static> {
+    parser: saphyr_parser::Parser<'static, BufferedInput<Chars<R>>>,
+    failed: Arc<OnceLock<String>>,
+    /// Text being parsed from byte `origin` on, when it is in memory, which
+    /// anchor names are read back from
+    source: Option<SourceText>,
+    origin: usize,
+    /// Byte offset in `source` of a character, counted from `origin`, as far
+    /// as the search for anchor names has got
+    cursor: (usize, usize),
+    /// Character the last event ended at
+    last_end: usize,
+    /// Names of the anchors defined so far, by saphyr's number for them
+    anchors: HashMap<usize, String>,
+}
+
+impl<R: BufRead + 'static> Saphyr<R> {
+    pub fn new(input: R, source: Option<SourceText>, origin: usize) -> Self {
+        let failed = Arc::new(OnceLock::new());
+        let chars = Chars {
+            input,
+            failed: Arc::clone(&failed),
+            started: false,
+        };
+        // Marks don't count a byte order mark, which `Chars` skips
+        let bom = match &source {
+            Some(source) if source[origin..].starts_with('\u{feff}') => '\u{feff}'.len_utf8(),
+            _ => 0,
+        };
+        Self {
+            parser: saphyr_parser::Parser::new_from_iter(chars),
+            failed,
+            source,
+            origin: origin + bom,
+            cursor: (origin + bom, 0),
+            last_end: 0,
+            anchors: HashMap::new(),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Event, ParseError> {
+        loop {
+            let next = self.parser.next_event();
+            if let Some(problem) = self.failed.get() {
+                return Err(ParseError::Saphyr {
+                    problem: problem.clone(),
+                    mark: None,
+                    read: true,
+                });
+            }
+            match next {
+                // The stream end was already returned
+                None => return Ok(Event::stream_end()),
+                Some(Ok((event, span))) => {
+                    if let Some(event) = self.convert(event, span) {
+                        return Ok(event);
+                    }
+                }
+                Some(Err(error)) => {
+                    return Err(ParseError::Saphyr {
+                        problem: error.info().to_string(),
+                        mark: Some(saphyr_mark(*error.marker())),
+                        read: false,
+                    });
+                }
+            }
+        }
+    }
+
+    fn convert(&mut self, event: saphyr_parser::Event<'static>, span: Span) -> Option<Event> {
+        use saphyr_parser::Event as Saphyr;
+        let (start, end) = (span.start.index(), span.end.index());
+        let mut converted = match event {
+            Saphyr::Nothing => return None,
+            Saphyr::StreamStart => Event::stream_start(Encoding::Utf8),
+            Saphyr::StreamEnd => Event::stream_end(),
+            Saphyr::DocumentStart(explicit) => Event::document_start(None, &[], !explicit),
+            // Only an explicit end, `...`, spans any text
+            Saphyr::DocumentEnd => Event::document_end(start == end),
+            Saphyr::Alias(id) => {
+                let name = self.anchors.get(&id).cloned();
+                Event::alias(&name.unwrap_or_else(|| id.to_string()))
+            }
+            Saphyr::Scalar(value, style, id, tag) => {
+                let anchor = self.define_anchor(id, start);
+                let tag = tag.map(|tag| format!("{}{}", tag.handle, tag.suffix));
+                let plain = style == saphyr_parser::ScalarStyle::Plain;
+                let style = match style {
+                    saphyr_parser::ScalarStyle::Plain => ScalarStyle::Plain,
+                    saphyr_parser::ScalarStyle::SingleQuoted => ScalarStyle::SingleQuoted,
+                    saphyr_parser::ScalarStyle::DoubleQuoted => ScalarStyle::DoubleQuoted,
+                    saphyr_parser::ScalarStyle::Literal => ScalarStyle::Literal,
+                    saphyr_parser::ScalarStyle::Folded => ScalarStyle::Folded,
+                };
+                let implicit = tag.is_none();
+                Event::scalar(
+                    anchor.as_deref(),
+                    tag.as_deref(),
+                    &value,
+                    implicit && plain,
+                    implicit && !plain,
+                    style,
+                )
+            }
+            // A flow collection starts at its bracket, a block one at nothing
+            Saphyr::SequenceStart(id, tag) => {
+                let anchor = self.define_anchor(id, start);
+                let tag = tag.map(|tag| format!("{}{}", tag.handle, tag.suffix));
+                let style = if start == end {
+                    SequenceStyle::Block
+                } else {
+                    SequenceStyle::Flow
+                };
+                Event::sequence_start(anchor.as_deref(), tag.as_deref(), tag.is_none(), style)
+            }
+            Saphyr::SequenceEnd => Event::sequence_end(),
+            Saphyr::MappingStart(id, tag) => {
+                let anchor = self.define_anchor(id, start);
+                let tag = tag.map(|tag| format!("{}{}", tag.handle, tag.suffix));
+                let style = if start == end {
+                    MappingStyle::Block
+                } else {
+                    MappingStyle::Flow
+                };
+                Event::mapping_start(anchor.as_deref(), tag.as_deref(), tag.is_none(), style)
+            }
+            Saphyr::MappingEnd => Event::mapping_end(),
+        };
+        converted.start_mark = saphyr_mark(span.start);
+        converted.end_mark = saphyr_mark(span.end);
+        self.last_end = self.last_end.max(end);
+        Some(converted)
+    }
+
+    /// Name the anchor saphyr numbered `id`, if it isn't 0 for none, after the
+    /// `&name` before the node starting at character `start`, or after its
+    /// number if there is no source text to read that from
+    fn define_anchor(&mut self, id: usize, start: usize) -> Option<String> {
+        if id == 0 {
+            return None;
+        }
+        let name = self.source.as_ref().and_then(|source| {
+            let from = byte_at(source, self.origin, &mut self.cursor, self.last_end);
+            let to = byte_at(source, self.origin, &mut self.cursor, start);
+            anchor_in(&source[from..to])
+        });
+        let name = name.unwrap_or_else(|| id.to_string());
+        self.anchors.insert(id, name.clone());
+        Some(name)
+    }
+}
+
+/// Byte offset in `source` of character `index`, counted from byte `origin`,
+/// moving `cursor` on to it
+fn byte_at(source: &str, origin: usize, cursor: &mut (usize, usize), index: usize) -> usize {
+    if index < cursor.1 {
+        *cursor = (origin, 0);
+    }
+    let (mut byte, mut chars) = *cursor;
+    for c in source[byte..].chars() {
+        if chars == index {
+            break;
+        }
+        byte += c.len_utf8();
+        chars += 1;
+    }
+    *cursor = (byte, chars);
+    byte
+}
+
+/// The name of the last anchor in `text`, the properties and indicators
+/// between a node and the event before it
+fn anchor_in(text: &str) -> Option<String> {
+    text.lines()
+        .flat_map(|line| {
+            // Anchor names hold no flow indicators, and a comment ends the line
+            line.split(|c: char| c.is_whitespace() || ",[]{}".contains(c))
+                .take_while(|token| !token.starts_with('#'))
+        })
+        .filter_map(|token| token.strip_prefix('&'))
+        .last()
+        .map(str::to_string)
+}
+
+fn saphyr_mark(marker: Marker) -> Mark {
+    Mark {
+        index: marker.index() as u64,
+        // saphyr counts lines from 1
+        line: marker.line().saturating_sub(1) as u64,
+        column: marker.col() as u64,
+    }
+}
+
+/// Event source dispatching to the selected backend
+pub enum EventParser {
+    Libyaml(Parser<Cursor<SourceText>>),
+    /// Events parsed from a file object as it is read
+    Stream(Parser<PyReader>, Arc<Mutex<Option<PyErr>>>),
+    Saphyr(Box<Saphyr<Cursor<SourceText>>>),
+    SaphyrStream(Box<Saphyr<PyReader>>, Arc<Mutex<Option<PyErr>>>),
+    /// Events parsed ahead of time, such as on a worker thread by
+    /// `load_files()`, ending with the parse error if there was one
+    Parsed(std::vec::IntoIter<Result<Event, ParseError>>),
+}
+
+impl EventParser {
+    pub fn new(backend: Backend, source: SourceText) -> Self {
+        Self::starting_at(backend, source, 0)
+    }
+
+    /// A parser of `source` from byte `start` on, which must begin a line.
+    /// Its marks count from there.
+    pub fn starting_at(backend: Backend, source: SourceText, start: usize) -> Self {
+        let mut input = Cursor::new(source.clone());
+        input.set_position(start as u64);
+        match backend {
+            Backend::Libyaml => {
+                let mut parser = Parser::new();
+                parser.set_input(input);
+                EventParser::Libyaml(parser)
+            }
+            Backend::Saphyr => {
+                EventParser::Saphyr(Box::new(Saphyr::new(input, Some(source), start)))
+            }
+        }
+    }
+
+    /// A parser reading `file` as it goes, so only the part being parsed is
+    /// held in memory, failing once more than `max_bytes` are read. With
+    /// `follow`, the end of the file is waited out, polling at that interval,
+    /// so input cut off mid-document is parsed once the rest is written.
+    pub fn from_file(
+        backend: Backend,
+        file: Py<PyAny>,
+        max_bytes: Option<usize>,
+        follow: Option<Duration>,
+    ) -> Self {
+        let error = Arc::new(Mutex::new(None));
+        let reader = PyReader {
+            file,
+            chunk: Vec::new(),
+            offset: 0,
+            error: Arc::clone(&error),
+            read: 0,
+            max_bytes,
+            follow,
+        };
+        match backend {
+            Backend::Libyaml => {
+                let mut parser = Parser::new();
+                parser.set_input(reader);
+                EventParser::Stream(parser, error)
+            }
+            Backend::Saphyr => {
+                EventParser::SaphyrStream(Box::new(Saphyr::new(reader, None, 0)), error)
+            }
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Event, ParseError> {
+        match self {
+            EventParser::Libyaml(parser) => parser.parse().map_err(ParseError::Libyaml),
+            EventParser::Stream(parser, _) => parser.parse().map_err(ParseError::Libyaml),
+            EventParser::Saphyr(parser) => parser.parse(),
+            EventParser::SaphyrStream(parser, _) => parser.parse(),
+            EventParser::Parsed(events) => events.next().unwrap_or_else(|| Ok(Event::stream_end())),
+        }
+    }
+
+    /// The exception reading the file object raised, if the last parse error
+    /// came from one
+    pub fn take_read_error(&mut self) -> Option<PyErr> {
+        match self {
+            EventParser::Stream(_, error) | EventParser::SaphyrStream(_, error) => {
+                error.lock().unwrap().take()
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse every event of `source` up to the end of the stream or the first error
+    pub fn parse_all(backend: Backend, source: SourceText) -> Vec<Result<Event, ParseError>> {
+        let mut parser = Self::new(backend, source);
+        let mut events = Vec::new();
+        loop {
+            let event = parser.parse();
+            let done = !matches!(&event, Ok(event) if event.data != EventData::StreamEnd);
+            events.push(event);
+            if done {
+                return events;
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Loading many files, or the many documents of one stream, at once, reading
+//! and parsing them on worker threads.
+//!
+//! Workers only produce events, which need no Python objects, so they run
+//! with the GIL released. The documents are then constructed from those
+//! events one file at a time, in the calling thread.
+
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use libyaml_safer::{Event, EventData};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::backend::{Backend, EventParser, ParseError};
+use crate::encoding::SourceText;
+use crate::loader::{LoaderOptions, RSafeLoader};
+
+/// Streams smaller than this are parsed on the calling thread, where starting
+/// workers would cost more than it saves
+const PARALLEL_MIN_SIZE: usize = 256 * 1024;
+
+/// A file's text and its events, or the error reading it
+type ParsedFile = std::io::Result<(SourceText, Vec<Result<Event, ParseError>>)>;
+
+/// Number of worker threads to use, given as `max_workers` by Python
+pub fn worker_count(max_workers: Option<usize>) -> PyResult<usize> {
+    match max_workers {
+        Some(0) => Err(PyValueError::new_err("max_workers must be at least 1")),
+        Some(workers) => Ok(workers),
+        None => Ok(std::thread::available_parallelism().map_or(1, NonZeroUsize::get)),
+    }
+}
+
+/// Load each file of `paths` as a single document, mapping every path to its
+/// data, or to the exception loading it raised
+pub fn load_files<'py>(
+    py: Python<'py>,
+    paths: Vec<Bound<'py, PyAny>>,
+    max_workers: Option<usize>,
+    options: LoaderOptions,
+    named: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let workers = worker_count(max_workers)?;
+    let fs_paths = paths
+        .iter()
+        .map(|path| path.extract::<PathBuf>())
+        .collect::<PyResult<Vec<_>>>()?;
+    let workers = workers.min(fs_paths.len());
+    let parsed = py.detach(|| parse_files(&fs_paths, workers, options.backend));
+
+    let results = PyDict::new(py);
+    for ((path, fs_path), file) in paths.iter().zip(&fs_paths).zip(parsed) {
+        let result = match file {
+            Ok((source, events)) => {
+                let mut options = options.clone();
+                if !named {
+                    options.name = fs_path.display().to_string();
+                }
+                let parser = EventParser::Parsed(events.into_iter());
+                RSafeLoader::with_parser(py, source, options, parser)?.get_single_data(py)
+            }
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(data) => results.set_item(path, data)?,
+            Err(e) => results.set_item(path, e.into_value(py))?,
+        }
+    }
+    Ok(results)
+}
+
+/// Read and parse `paths` on `workers` threads, returning the files in order
+fn parse_files(paths: &[PathBuf], workers: usize, backend: Backend) -> Vec<ParsedFile> {
+    let next = AtomicUsize::new(0);
+    let mut parsed: Vec<(usize, ParsedFile)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(i) else {
+                            return done;
+                        };
+                        let file = crate::mapped::read_file(path).map(|source| {
+                            let events = EventParser::parse_all(backend, source.clone());
+                            (source, events)
+                        });
+                        done.push((i, file));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("parser thread panicked"))
+            .collect()
+    });
+    parsed.sort_unstable_by_key(|(i, _)| *i);
+    parsed.into_iter().map(|(_, file)| file).collect()
+}
+
+/// Where a run of documents starts in the stream
+#[derive(Debug, Clone, Copy, Default)]
+struct Part {
+    /// Byte offset of the run
+    start: usize,
+    /// Character offset and line, as parser marks count them
+    index: u64,
+    line: u64,
+}
+
+/// Parse the documents of `source` on up to `workers` threads, each taking a
+/// run of whole documents, and join their events into one stream. None when
+/// the stream is too small to split, or a run fails to parse: parsing the
+/// stream whole then reports the error where it is in the full text.
+pub fn parse_documents(
+    py: Python,
+    source: &SourceText,
+    backend: Backend,
+    workers: usize,
+) -> Option<Vec<Result<Event, ParseError>>> {
+    if workers < 2 || source.len() < PARALLEL_MIN_SIZE {
+        return None;
+    }
+    let parts = split_documents(source, workers);
+    if parts.len() < 2 {
+        return None;
+    }
+    let text: &str = source;
+    let runs: Vec<Option<Vec<Event>>> = py.detach(|| {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = parts
+                .iter()
+                .enumerate()
+                .map(|(i, part)| {
+                    let end = parts.get(i + 1).map_or(text.len(), |next| next.start);
+                    let (first, last) = (i == 0, i == parts.len() - 1);
+                    scope.spawn(move || {
+                        parse_run(backend, &text[part.start..end], *part, first, last)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("parser thread panicked"))
+                .collect()
+        })
+    });
+    let mut events = Vec::new();
+    for run in runs {
+        events.extend(run?.into_iter().map(Ok));
+    }
+    Some(events)
+}
+
+/// Events of the documents in `text`, which starts at `part` of the stream,
+/// with their marks placed in the full stream. Only the first run starts the
+/// stream and only the last ends it.
+fn parse_run(
+    backend: Backend,
+    text: &str,
+    part: Part,
+    first: bool,
+    last: bool,
+) -> Option<Vec<Event>> {
+    let mut events = Vec::new();
+    for event in EventParser::parse_all(backend, SourceText::from(text.to_string())) {
+        let mut event = event.ok()?;
+        match event.data {
+            EventData::StreamStart { .. } if !first => continue,
+            EventData::StreamEnd if !last => continue,
+            _ => {}
+        }
+        for mark in [&mut event.start_mark, &mut event.end_mark] {
+            mark.index += part.index;
+            mark.line += part.line;
+        }
+        events.push(event);
+    }
+    Some(events)
+}
+
+/// Whether `rest` starts with the document marker `marker` alone on its line
+/// or followed by whitespace
+fn starts_with_marker(rest: &str, marker: &str) -> bool {
+    rest.strip_prefix(marker).is_some_and(|after| {
+        after.is_empty()
+            || after.starts_with([' ', '\t', '\r', '\n', '\u{85}', '\u{2028}', '\u{2029}'])
+    })
+}
+
+/// Split `text` into at most about `parts` runs of whole documents, each
+/// starting at a `---` line, or at the directives before it. A `---` at the
+/// start of a line always starts a document (or is an error), whatever it
+/// follows. `%` lines are only directives after a `...` line; elsewhere they
+/// may continue a plain scalar, so no run starts at the `---` after them.
+fn split_documents(text: &str, parts: usize) -> Vec<Part> {
+    let target = text.len().div_ceil(parts);
+    let mut runs = vec![Part::default()];
+    let mut after_end = false;
+    let mut directives: Option<Part> = None;
+    let mut stray_directive = false;
+    let mut line = Part::default();
+    let mut chars = text.char_indices().peekable();
+    // The byte order mark isn't counted in marks
+    chars.next_if(|&(_, c)| c == '\u{feff}');
+    loop {
+        let rest = &text[line.start..];
+        let content = rest
+            .split(['\r', '\n', '\u{85}', '\u{2028}', '\u{2029}'])
+            .next()
+            .unwrap_or("")
+            .trim();
+        if starts_with_marker(rest, "---") {
+            let run_start = runs.last().map_or(0, |run| run.start);
+            if line.start >= run_start + target && !stray_directive {
+                runs.push(directives.unwrap_or(line));
+            }
+            (after_end, directives, stray_directive) = (false, None, false);
+        } else if starts_with_marker(rest, "...") {
+            (after_end, directives, stray_directive) = (true, None, false);
+        } else if rest.starts_with('%') {
+            if after_end {
+                directives.get_or_insert(line);
+            } else {
+                stray_directive = true;
+            }
+        } else if !content.is_empty() && !content.starts_with('#') {
+            (after_end, directives, stray_directive) = (false, None, false);
+        }
+
+        // Move to the next line, counting breaks as the parser does
+        let mut index = line.index;
+        let next = loop {
+            let Some((i, c)) = chars.next() else {
+                break None;
+            };
+            index += 1;
+            match c {
+                '\n' | '\u{85}' | '\u{2028}' | '\u{2029}' => break Some(i + c.len_utf8()),
+                '\r' => {
+                    if chars.next_if(|&(_, c)| c == '\n').is_some() {
+                        index += 1;
+                        break Some(i + 2);
+                    }
+                    break Some(i + 1);
+                }
+                _ => {}
+            }
+        };
+        let Some(start) = next else {
+            return runs;
+        };
+        line = Part {
+            start,
+            index,
+            line: line.line + 1,
+        };
+    }
+}
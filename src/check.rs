@@ -0,0 +1,190 @@
+//! Checking YAML text for problems without loading it. Only the parser runs,
+//! with aliases matched to the anchors before them; no Python object is
+//! constructed, so linting many files costs a fraction of loading them.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use libyaml_safer::{ErrorKind, EventData, Mark};
+use pyo3::prelude::*;
+
+use crate::backend::{Backend, EventParser};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::encoding::SourceText;
+use crate::mark::PyMark;
+
+/// A problem found while parsing, before it is given its source name
+struct Problem {
+    code: &'static str,
+    message: String,
+    mark: Option<Mark>,
+    end_mark: Option<Mark>,
+}
+
+/// Where parsing starts in the source: at its beginning, or at the line it
+/// resynchronizes on after a syntax error
+#[derive(Debug, Clone, Copy, Default)]
+struct Start {
+    byte: usize,
+    index: u64,
+    line: u64,
+}
+
+impl Start {
+    /// `mark` of the text parsed from here, as a mark of the whole source.
+    /// Parsing starts at the start of a line, so columns are unchanged.
+    fn shift(self, mut mark: Mark) -> Mark {
+        mark.index += self.index;
+        mark.line += self.line;
+        mark
+    }
+}
+
+/// The problems of `source`: aliases to anchors not defined before them, and
+/// the syntax error ending the stream, if there is one. With `recover`,
+/// parsing starts over after each syntax error at the next line starting a
+/// document or a top-level entry, so every such error is found.
+fn find_problems(backend: Backend, source: &SourceText, recover: bool) -> Vec<Problem> {
+    let mut anchors = HashSet::new();
+    let mut problems = Vec::new();
+    let mut start = Some(Start::default());
+    while let Some(from) = start.take() {
+        let text = &source[from.byte..];
+        // A document picked up midway keeps the anchors defined before
+        let mut resumed = from.byte > 0 && !text.starts_with("---");
+        let input = match from.byte {
+            0 => source.clone(),
+            _ => SourceText::from(text.to_string()),
+        };
+        let mut parser = EventParser::new(backend, input);
+        loop {
+            let event = match parser.parse() {
+                Ok(event) => event,
+                Err(error) => {
+                    let mark = error
+                        .problem_mark()
+                        .or(error.context_mark())
+                        .map(|mark| from.shift(mark));
+                    problems.push(Problem {
+                        code: match error.kind() {
+                            ErrorKind::Reader | ErrorKind::Io => "reader-error",
+                            _ => "syntax-error",
+                        },
+                        message: match error.context() {
+                            Some(context) => format!("{}, {context}", error.problem()),
+                            None => error.problem().to_string(),
+                        },
+                        mark,
+                        end_mark: None,
+                    });
+                    if recover && let Some(mark) = mark {
+                        start = resync(source, from, mark.line);
+                    }
+                    break;
+                }
+            };
+            match event.data {
+                EventData::StreamEnd => break,
+                EventData::DocumentStart { .. } if resumed => resumed = false,
+                EventData::DocumentStart { .. } => anchors.clear(),
+                EventData::Alias { anchor } if !anchors.contains(&anchor) => {
+                    problems.push(Problem {
+                        code: "undefined-alias",
+                        message: format!("found undefined alias '{anchor}'"),
+                        mark: Some(from.shift(event.start_mark)),
+                        end_mark: Some(from.shift(event.end_mark)),
+                    });
+                }
+                EventData::Scalar {
+                    anchor: Some(anchor),
+                    ..
+                }
+                | EventData::SequenceStart {
+                    anchor: Some(anchor),
+                    ..
+                }
+                | EventData::MappingStart {
+                    anchor: Some(anchor),
+                    ..
+                } => {
+                    anchors.insert(anchor);
+                }
+                _ => {}
+            }
+        }
+    }
+    problems
+}
+
+/// The first line after `line` where parsing can start over: one starting a
+/// document, or an entry of a top-level collection, at its first column
+fn resync(source: &str, from: Start, line: u64) -> Option<Start> {
+    let mut start = from;
+    for text in source[from.byte..].split_inclusive('\n') {
+        let first = text.chars().next();
+        if start.line > line
+            && !text.starts_with("...")
+            && first.is_some_and(|c| !matches!(c, ' ' | '\t' | '\r' | '\n' | '#' | ']' | '}' | ','))
+        {
+            return Some(start);
+        }
+        start.byte += text.len();
+        start.index += text.chars().count() as u64;
+        start.line += 1;
+    }
+    None
+}
+
+/// Diagnostics for the problems of `source`, whose marks are named `name`
+pub fn check(
+    py: Python,
+    source: SourceText,
+    name: &str,
+    backend: Backend,
+    recover: bool,
+) -> PyResult<Vec<Py<Diagnostic>>> {
+    let problems = py.detach(|| find_problems(backend, &source, recover));
+    let mark = |mark: Mark| PyMark::with_source(mark, name, &source);
+    problems
+        .into_iter()
+        .map(|problem| {
+            Py::new(
+                py,
+                Diagnostic {
+                    severity: Severity::Error,
+                    code: problem.code.to_string(),
+                    message: problem.message,
+                    mark: problem.mark.map(mark),
+                    end_mark: problem.end_mark.map(mark),
+                    path: Vec::new(),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Diagnostics for the problems of the file at `path`. Files which can't be
+/// decoded get a `reader-error` diagnostic; other I/O errors are raised.
+pub fn check_file(
+    py: Python,
+    path: &Path,
+    name: &str,
+    backend: Backend,
+    recover: bool,
+) -> PyResult<Vec<Py<Diagnostic>>> {
+    match py.detach(|| crate::mapped::read_file(path)) {
+        Ok(source) => check(py, source, name, backend, recover),
+        Err(error) if error.kind() == std::io::ErrorKind::InvalidData => {
+            let diagnostic = Diagnostic {
+                severity: Severity::Error,
+                code: "reader-error".to_string(),
+                message: error.to_string(),
+                mark: None,
+                end_mark: None,
+                path: Vec::new(),
+            };
+            Ok(vec![Py::new(py, diagnostic)?])
+        }
+        Err(error) => Err(error.into()),
+    }
+}
@@ -0,0 +1,217 @@
+//! Comments kept by `roundtrip` loading. The parser drops comments, so they
+//! are read from the source text around each block mapping entry, and written
+//! back by reparsing the dump output to find where each entry landed.
+
+use libyaml_safer::{EventData, MappingStyle, Mark, Parser, ScalarStyle, SequenceStyle};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use crate::encoding::SourceText;
+
+/// Comments written around one mapping entry, as held by `ryaml.Comments`
+#[derive(Debug, Clone, Default, FromPyObject)]
+pub struct EntryComments {
+    /// Lines above the key: comments from their `#`, and "" for blank lines
+    #[pyo3(item(0))]
+    pub before: Vec<String>,
+    /// Comment ending the line the entry's value starts on, or ends on for
+    /// values on a single line
+    #[pyo3(item(1))]
+    pub inline: Option<String>,
+}
+
+/// Lines of a source text, for finding the comments between its events
+pub struct SourceLines {
+    text: SourceText,
+    /// Byte range of each line, without its line break
+    lines: Vec<(usize, usize)>,
+}
+
+impl SourceLines {
+    pub fn new(text: SourceText) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (i, byte) in text.bytes().enumerate() {
+            if byte == b'\n' {
+                let end = if text[start..i].ends_with('\r') {
+                    i - 1
+                } else {
+                    i
+                };
+                lines.push((start, end));
+                start = i + 1;
+            }
+        }
+        lines.push((start, text.len()));
+        Self { text, lines }
+    }
+
+    fn line(&self, line: u64) -> &str {
+        usize::try_from(line)
+            .ok()
+            .and_then(|line| self.lines.get(line))
+            .map_or("", |&(start, end)| &self.text[start..end])
+    }
+
+    /// The blank and comment-only lines directly above `line`, stopping at
+    /// `floor`, the last line holding content
+    pub fn comments_above(&self, line: u64, floor: Option<u64>) -> Vec<String> {
+        let mut before = Vec::new();
+        let mut current = line;
+        while current > 0 && floor.is_none_or(|floor| current - 1 > floor) {
+            current -= 1;
+            let text = self.line(current).trim();
+            if !text.is_empty() && !text.starts_with('#') {
+                break;
+            }
+            before.push(text.to_string());
+        }
+        before.reverse();
+        before
+    }
+
+    /// The comment ending the line `mark` is on, if one follows it
+    pub fn comment_after(&self, mark: Mark) -> Option<String> {
+        let rest: String = self
+            .line(mark.line)
+            .chars()
+            .skip(usize::try_from(mark.column).unwrap_or(usize::MAX))
+            .collect();
+        // A comment is separated from what it follows by whitespace
+        let mut previous = ' ';
+        for (i, c) in rest.char_indices() {
+            if c == '#' && previous.is_whitespace() {
+                return Some(rest[i..].trim_end().to_string());
+            }
+            previous = c;
+        }
+        None
+    }
+}
+
+/// Open collection while reparsing dump output
+struct Frame {
+    /// Whether a mapping expects a key next; None for sequences
+    key_next: Option<bool>,
+    /// Comments are only written into block mappings
+    block: bool,
+    /// Inline comment to write after the collection, when it is a flow value
+    inline: Option<String>,
+}
+
+/// Write `comments`, each given with the position of its entry's key among all
+/// mapping keys of the stream, around the entries of `output`
+pub fn insert_comments(
+    output: &str,
+    eol: &str,
+    comments: &[(usize, EntryComments)],
+) -> Result<String, String> {
+    let mut parser = Parser::new();
+    parser.set_input(Cursor::new(output.to_string()));
+    let mut comments = comments.iter().peekable();
+    let mut ordinal = 0;
+    let mut stack: Vec<Frame> = Vec::new();
+    // Entry whose value comes next, with the line its key ends on
+    let mut pending: Option<(&EntryComments, u64)> = None;
+    // Lines to write above a line, indented like the key on it
+    let mut before: HashMap<u64, (u64, &[String])> = HashMap::new();
+    let mut inline: HashMap<u64, String> = HashMap::new();
+    loop {
+        let event = parser.parse().map_err(|e| e.to_string())?;
+        let (start, end) = (event.start_mark, event.end_mark);
+        let flow = match &event.data {
+            EventData::StreamEnd => break,
+            EventData::Scalar { .. } | EventData::Alias { .. } => None,
+            EventData::SequenceStart { style, .. } => Some(*style == SequenceStyle::Flow),
+            EventData::MappingStart { style, .. } => Some(*style == MappingStyle::Flow),
+            EventData::SequenceEnd | EventData::MappingEnd => {
+                if let Some(Frame {
+                    inline: Some(comment),
+                    ..
+                }) = stack.pop()
+                {
+                    inline.entry(end.line).or_insert(comment);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let parent = stack.last_mut();
+        let in_block_mapping = parent.as_ref().is_some_and(|frame| frame.block);
+        let is_key = match parent {
+            Some(Frame {
+                key_next: Some(key_next),
+                ..
+            }) => {
+                *key_next = !*key_next;
+                !*key_next
+            }
+            _ => false,
+        };
+        let mut flow_inline = None;
+        if is_key {
+            let entry = comments.next_if(|(position, _)| *position == ordinal);
+            ordinal += 1;
+            // Complex keys spanning lines are left alone
+            if let Some((_, entry)) = entry
+                && in_block_mapping
+                && flow.is_none()
+            {
+                before.insert(start.line, (start.column, &entry.before));
+                pending = Some((entry, end.line));
+            }
+        } else if let Some((entry, key_line)) = pending.take()
+            && let Some(comment) = entry.inline.as_deref()
+        {
+            let line = match (&event.data, flow) {
+                (
+                    EventData::Scalar {
+                        style: ScalarStyle::Literal | ScalarStyle::Folded,
+                        ..
+                    },
+                    _,
+                ) => Some(start.line),
+                (_, None) => Some(end.line),
+                (_, Some(false)) => Some(key_line),
+                (_, Some(true)) => {
+                    flow_inline = Some(comment.to_string());
+                    None
+                }
+            };
+            if let Some(line) = line {
+                inline.entry(line).or_insert_with(|| comment.to_string());
+            }
+        }
+        if let Some(flow) = flow {
+            let mapping = matches!(&event.data, EventData::MappingStart { .. });
+            stack.push(Frame {
+                key_next: mapping.then_some(true),
+                block: mapping && !flow,
+                inline: flow_inline,
+            });
+        }
+    }
+
+    let mut text = String::with_capacity(output.len());
+    for (number, line) in (0u64..).zip(output.split(eol)) {
+        if number > 0 {
+            text.push_str(eol);
+        }
+        if let Some(&(indent, lines)) = before.get(&number) {
+            for comment in lines {
+                if !comment.is_empty() {
+                    text.extend(std::iter::repeat_n(' ', indent as usize));
+                    text.push_str(comment);
+                }
+                text.push_str(eol);
+            }
+        }
+        text.push_str(line);
+        if let Some(comment) = inline.get(&number) {
+            text.push_str("  ");
+            text.push_str(comment);
+        }
+    }
+    Ok(text)
+}
@@ -0,0 +1,129 @@
+//! pyyaml's `load()`, `load_all()` and `dump_all()`, behind `ryaml.compat`.
+//! Loader and dumper classes are driven through the methods pyyaml calls, so
+//! pyyaml's own classes and subclasses of the compat ones work as they do
+//! with pyyaml. Without a loader class, documents load straight from the
+//! stream.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyType};
+
+use crate::dumper::RSafeDumper;
+use crate::encoding::{Source, SourceText};
+use crate::loader::{LoaderOptions, RSafeLoader};
+
+/// The text of `stream`, YAML text or a file to read, and the loader options
+/// naming it after the file
+fn read(py: Python, stream: &Bound<'_, PyAny>) -> PyResult<(SourceText, LoaderOptions)> {
+    let mut options = LoaderOptions::default();
+    if !stream.hasattr("read")? {
+        return Ok((stream.extract::<Source>()?.into_text(py)?, options));
+    }
+    if let Ok(name) = stream.getattr("name").and_then(|name| name.extract()) {
+        options.name = name;
+    }
+    let text = stream
+        .call_method0("read")?
+        .extract::<Source>()?
+        .into_text(py)?;
+    Ok((text, options))
+}
+
+/// The single document of `stream`, loaded by an instance of `loader`
+pub fn load(
+    py: Python,
+    stream: &Bound<'_, PyAny>,
+    loader: Option<&Bound<'_, PyType>>,
+) -> PyResult<Py<PyAny>> {
+    let Some(loader) = loader else {
+        let (text, options) = read(py, stream)?;
+        if let Some(data) = crate::json::try_loads(py, &text, &options)? {
+            return Ok(data);
+        }
+        let mut loader = RSafeLoader::pooled(py, text, options)?;
+        let data = loader.get_single_data(py)?;
+        loader.release(py);
+        return Ok(data.unwrap_or_else(|| py.None()));
+    };
+    let loader = loader.call1((stream,))?;
+    let data = loader.call_method0("get_single_data");
+    let disposed = loader.call_method0("dispose");
+    let data = data?.unbind();
+    disposed?;
+    Ok(data)
+}
+
+/// Every document of `stream`, loaded by an instance of `loader`
+pub fn load_all(
+    py: Python,
+    stream: &Bound<'_, PyAny>,
+    loader: Option<&Bound<'_, PyType>>,
+) -> PyResult<Vec<Py<PyAny>>> {
+    let mut documents = Vec::new();
+    let Some(loader) = loader else {
+        let (text, options) = read(py, stream)?;
+        let mut loader = RSafeLoader::new(py, text, options)?;
+        while let Some(document) = loader.get_data(py)? {
+            documents.push(document);
+        }
+        return Ok(documents);
+    };
+    let loader = loader.call1((stream,))?;
+    let loaded = (|| {
+        while loader.call_method0("check_data")?.is_truthy()? {
+            documents.push(loader.call_method0("get_data")?.unbind());
+        }
+        PyResult::Ok(())
+    })();
+    let disposed = loader.call_method0("dispose");
+    loaded?;
+    disposed?;
+    Ok(documents)
+}
+
+/// `documents` dumped to `stream` by an instance of `dumper`, made with
+/// `kwargs`, or returned as str (bytes with an `encoding`) without a stream.
+/// Keys are sorted unless `sort_keys=False` is given, as with pyyaml.
+pub fn dump_all(
+    py: Python,
+    documents: &Bound<'_, PyAny>,
+    stream: Option<&Bound<'_, PyAny>>,
+    dumper: Option<&Bound<'_, PyType>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Option<Py<PyAny>>> {
+    let kwargs = match kwargs {
+        Some(kwargs) => kwargs.copy()?,
+        None => PyDict::new(py),
+    };
+    if !kwargs.contains("sort_keys")? {
+        kwargs.set_item("sort_keys", true)?;
+    }
+    let output = match stream {
+        Some(stream) => stream.clone(),
+        None => {
+            let encoded = kwargs
+                .get_item("encoding")?
+                .is_some_and(|encoding| !encoding.is_none());
+            let buffer = if encoded { "BytesIO" } else { "StringIO" };
+            py.import("io")?.call_method0(buffer)?
+        }
+    };
+    let dumper = match dumper {
+        Some(dumper) => dumper.clone(),
+        None => py.get_type::<RSafeDumper>(),
+    };
+    let dumper = dumper.call((&output,), Some(&kwargs))?;
+    let dumped = (|| {
+        dumper.call_method0("open")?;
+        for document in documents.try_iter()? {
+            dumper.call_method1("represent", (document?,))?;
+        }
+        dumper.call_method0("close")
+    })();
+    let disposed = dumper.call_method0("dispose");
+    dumped?;
+    disposed?;
+    match stream {
+        Some(_) => Ok(None),
+        None => Ok(Some(output.call_method0("getvalue")?.unbind())),
+    }
+}
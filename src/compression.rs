@@ -0,0 +1,76 @@
+//! Decompression of YAML given compressed, recognized by the magic bytes the
+//! data starts with. Each format is compiled in with its cargo feature:
+//! `gzip`, `zstd` or `xz`.
+
+#[cfg(any(feature = "gzip", feature = "xz"))]
+use std::io::Read;
+
+/// Names of the compression formats compiled into this build
+pub const COMPRESSIONS: &[&str] = &[
+    #[cfg(feature = "gzip")]
+    "gzip",
+    #[cfg(feature = "zstd")]
+    "zstd",
+    #[cfg(feature = "xz")]
+    "xz",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Format {
+    /// The format of data starting with `bytes`, none of which can start YAML
+    /// text in any encoding
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x1F, 0x8B, ..] => Some(Format::Gzip),
+            [0x28, 0xB5, 0x2F, 0xFD, ..] => Some(Format::Zstd),
+            [0xFD, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Format::Xz),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Format::Gzip => "gzip",
+            Format::Zstd => "zstd",
+            Format::Xz => "xz",
+        }
+    }
+}
+
+/// `bytes` decompressed, or None if they aren't compressed
+pub fn decompress(bytes: &[u8]) -> Option<Result<Vec<u8>, String>> {
+    let format = Format::detect(bytes)?;
+    Some(match format {
+        #[cfg(feature = "gzip")]
+        Format::Gzip => read_all(flate2::read::MultiGzDecoder::new(bytes), format),
+        #[cfg(feature = "zstd")]
+        Format::Zstd => zstd::stream::decode_all(bytes).map_err(|e| corrupt(format, e)),
+        #[cfg(feature = "xz")]
+        Format::Xz => read_all(xz2::read::XzDecoder::new_multi_decoder(bytes), format),
+        #[allow(unreachable_patterns)]
+        _ => Err(format!(
+            "{0}-compressed input needs ryaml built with its '{0}' feature",
+            format.name()
+        )),
+    })
+}
+
+#[cfg(any(feature = "gzip", feature = "xz"))]
+fn read_all(mut reader: impl Read, format: Format) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    reader
+        .read_to_end(&mut output)
+        .map_err(|e| corrupt(format, e))?;
+    Ok(output)
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "xz"))]
+fn corrupt(format: Format, error: std::io::Error) -> String {
+    format!("invalid {} data: {error}", format.name())
+}
@@ -0,0 +1,382 @@
+//! Constructor Python class handed to registered constructors, building Python
+//! objects from node trees with the pyyaml `construct_*` methods.
+//!
+//! It also constructs documents for loader subclasses overriding those methods,
+//! dispatching each node to the overriding method first.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::exception;
+use crate::loader::{construct_scalar_value, make_hashable};
+use crate::nodes::PyNode;
+use crate::registry::Snapshot;
+use crate::state::{self, ModuleState};
+
+/// Methods of `_RSafeLoader` which a Python subclass may override
+pub const OVERRIDABLE: &[&str] = &[
+    "construct_scalar",
+    "construct_sequence",
+    "construct_mapping",
+    "construct_yaml_null",
+    "construct_yaml_bool",
+    "construct_yaml_int",
+    "construct_yaml_float",
+    "construct_yaml_binary",
+    "construct_yaml_timestamp",
+    "construct_yaml_str",
+    "construct_yaml_seq",
+    "construct_yaml_map",
+    "construct_yaml_set",
+];
+
+/// The `construct_yaml_*` method constructing nodes tagged `tag`, and the
+/// generic method it builds on
+fn yaml_method(tag: &str) -> Option<(&'static str, &'static str)> {
+    Some(match tag {
+        crate::TAG_NULL => ("construct_yaml_null", "construct_scalar"),
+        crate::TAG_BOOL => ("construct_yaml_bool", "construct_scalar"),
+        crate::TAG_INT => ("construct_yaml_int", "construct_scalar"),
+        crate::TAG_FLOAT => ("construct_yaml_float", "construct_scalar"),
+        crate::TAG_BINARY => ("construct_yaml_binary", "construct_scalar"),
+        crate::TAG_TIMESTAMP => ("construct_yaml_timestamp", "construct_scalar"),
+        crate::TAG_STR => ("construct_yaml_str", "construct_scalar"),
+        crate::TAG_SEQ => ("construct_yaml_seq", "construct_sequence"),
+        crate::TAG_MAP => ("construct_yaml_map", "construct_mapping"),
+        crate::TAG_SET => ("construct_yaml_set", "construct_mapping"),
+        _ => return None,
+    })
+}
+
+/// A loader instance whose class overrides some of the `OVERRIDABLE` methods
+pub struct Overrides {
+    pub loader: Py<PyAny>,
+    pub methods: Vec<&'static str>,
+}
+
+#[pyclass(name = "Constructor", frozen)]
+pub struct PyConstructor {
+    /// Registered constructors in effect for the document being loaded
+    constructors: Snapshot<String>,
+    /// State of the module the constructors were registered with
+    state: Py<ModuleState>,
+    /// Loader whose overriding methods nodes are dispatched to
+    overrides: Option<Overrides>,
+    /// Objects constructed so far, by node address, so shared nodes stay shared
+    constructed: Mutex<HashMap<usize, Py<PyAny>>>,
+    /// Nodes whose registered constructor is still running
+    in_progress: Mutex<HashSet<usize>>,
+    /// Whether two-step constructors run to the end at once, inside a
+    /// `deep=True` call, rather than once the document is constructed
+    deep: Mutex<bool>,
+    /// Two-step constructors left to finish once the document is constructed
+    pending: Mutex<Vec<Py<PyAny>>>,
+}
+
+#[pymethods]
+impl PyConstructor {
+    /// The string value of a scalar node
+    fn construct_scalar(&self, py: Python, node: PyNode) -> PyResult<String> {
+        match node {
+            PyNode::Scalar(node) => Ok(node.borrow(py).value.clone()),
+            _ => Err(expected(py, "a scalar", &node)),
+        }
+    }
+
+    /// Construct the items of a sequence node, even if its own tag has a registered
+    /// constructor. With `deep`, two-step constructors of the items run to
+    /// the end before this returns.
+    #[pyo3(signature = (node, deep=false))]
+    fn construct_sequence(slf: &Bound<'_, Self>, node: PyNode, deep: bool) -> PyResult<Py<PyAny>> {
+        match node {
+            PyNode::Sequence(_) => {
+                Self::constructing(slf, deep, || Self::construct_builtin(slf, &node))
+            }
+            _ => Err(expected(slf.py(), "a sequence", &node)),
+        }
+    }
+
+    /// Construct the entries of a mapping node, applying merge keys, even if its
+    /// own tag has a registered constructor
+    #[pyo3(signature = (node, deep=false))]
+    fn construct_mapping(slf: &Bound<'_, Self>, node: PyNode, deep: bool) -> PyResult<Py<PyAny>> {
+        match node {
+            PyNode::Mapping(_) => {
+                Self::constructing(slf, deep, || Self::construct_builtin(slf, &node))
+            }
+            _ => Err(expected(slf.py(), "a mapping", &node)),
+        }
+    }
+
+    /// Construct any node, dispatching on its tag
+    #[pyo3(signature = (node, deep=false))]
+    fn construct_object(slf: &Bound<'_, Self>, node: PyNode, deep: bool) -> PyResult<Py<PyAny>> {
+        Self::constructing(slf, deep, || Self::construct(slf, &node))
+    }
+}
+
+impl PyConstructor {
+    pub fn new(state: Py<ModuleState>, constructors: Snapshot<String>) -> Self {
+        Self::with_overrides(state, constructors, None)
+    }
+
+    pub fn with_overrides(
+        state: Py<ModuleState>,
+        constructors: Snapshot<String>,
+        overrides: Option<Overrides>,
+    ) -> Self {
+        Self {
+            constructors,
+            state,
+            overrides,
+            constructed: Mutex::new(HashMap::new()),
+            in_progress: Mutex::new(HashSet::new()),
+            deep: Mutex::new(false),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Construct the document `node`, then finish the two-step constructors
+    /// left pending, as pyyaml does at the end of each document
+    pub fn construct_document(slf: &Bound<'_, Self>, node: &PyNode) -> PyResult<Py<PyAny>> {
+        let obj = Self::construct(slf, node)?;
+        Self::finish_pending(slf)?;
+        Ok(obj)
+    }
+
+    /// Run the two-step constructors left pending to the end, including any
+    /// they leave pending in turn
+    pub fn finish_pending(slf: &Bound<'_, Self>) -> PyResult<()> {
+        loop {
+            let pending = std::mem::take(&mut *slf.get().pending.lock().unwrap());
+            if pending.is_empty() {
+                return Ok(());
+            }
+            for generator in pending {
+                for step in generator.bind(slf.py()).try_iter()? {
+                    step?;
+                }
+            }
+        }
+    }
+
+    /// Run `construct`, finishing the two-step constructors it reaches before
+    /// returning if `deep`
+    pub fn constructing<T>(
+        slf: &Bound<'_, Self>,
+        deep: bool,
+        construct: impl FnOnce() -> PyResult<T>,
+    ) -> PyResult<T> {
+        if !deep {
+            return construct();
+        }
+        let outer = std::mem::replace(&mut *slf.get().deep.lock().unwrap(), true);
+        let result = construct();
+        *slf.get().deep.lock().unwrap() = outer;
+        result
+    }
+
+    /// Construct `node`, through its registered constructor if its tag has one
+    pub fn construct(slf: &Bound<'_, Self>, node: &PyNode) -> PyResult<Py<PyAny>> {
+        let py = slf.py();
+        let this = slf.get();
+        let key = node.as_ptr() as usize;
+        if let Some(obj) = this.constructed.lock().unwrap().get(&key) {
+            return Ok(obj.clone_ref(py));
+        }
+        let tag = node.get_tag(py)?;
+        if let Some(constructor) = this.constructors.get(&tag) {
+            if !this.in_progress.lock().unwrap().insert(key) {
+                return Err(exception::constructor_error(
+                    py,
+                    "found unconstructable recursive node".to_string(),
+                ));
+            }
+            let result = constructor.bind(py).call1((slf, node.clone()));
+            this.in_progress.lock().unwrap().remove(&key);
+            let obj = result?.unbind();
+            this.remember(py, key, &obj);
+            return Ok(obj);
+        }
+        if let Some(overrides) = &this.overrides
+            && let Some((method, generic)) = yaml_method(&tag)
+            && overrides
+                .methods
+                .iter()
+                .any(|m| *m == method || *m == generic)
+        {
+            if !this.in_progress.lock().unwrap().insert(key) {
+                return Err(exception::constructor_error(
+                    py,
+                    "found unconstructable recursive node".to_string(),
+                ));
+            }
+            let result = overrides
+                .loader
+                .bind(py)
+                .call_method1(method, (node.clone(),));
+            this.in_progress.lock().unwrap().remove(&key);
+            return this.finish_constructed(py, key, result?);
+        }
+        Self::construct_builtin(slf, node)
+    }
+
+    /// Remember what a method constructed for the node `key`. Generators, as
+    /// pyyaml's two-step constructors return, yield the object and then fill it
+    /// in, so recursive aliases can refer to it. The filling in waits for the
+    /// end of the document unless a `deep=True` call is under way.
+    fn finish_constructed(
+        &self,
+        py: Python,
+        key: usize,
+        result: Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        let generator_type =
+            self.state
+                .get()
+                .generator_type
+                .import(py, "types", "GeneratorType")?;
+        if !result.is_instance(generator_type)? {
+            let obj = result.unbind();
+            self.remember(py, key, &obj);
+            return Ok(obj);
+        }
+        let obj = result.call_method0("__next__")?.unbind();
+        self.remember(py, key, &obj);
+        if *self.deep.lock().unwrap() {
+            for step in result.try_iter()? {
+                step?;
+            }
+        } else {
+            self.pending.lock().unwrap().push(result.unbind());
+        }
+        Ok(obj)
+    }
+
+    /// Construct `node` as the scalar, list or dict it is, ignoring registered
+    /// constructors and overriding methods for its own tag
+    pub fn construct_builtin(slf: &Bound<'_, Self>, node: &PyNode) -> PyResult<Py<PyAny>> {
+        let py = slf.py();
+        let this = slf.get();
+        let key = node.as_ptr() as usize;
+        let tag = node.get_tag(py)?;
+        match node {
+            PyNode::Scalar(scalar) => {
+                let obj = construct_scalar_value(py, &tag, &scalar.borrow(py).value)?;
+                this.remember_first(py, key, &obj);
+                Ok(obj)
+            }
+            PyNode::Sequence(sequence) => {
+                let items: Vec<PyNode> = sequence.borrow(py).value.clone();
+                // Made at its final size and filled in place
+                let list = PyList::new(py, std::iter::repeat_n(py.None(), items.len()))?;
+                // Remember before recursing, so recursive aliases resolve, unless a
+                // two-step constructor already did
+                this.remember_first(py, key, list.as_any().as_unbound());
+                for (i, item) in items.iter().enumerate() {
+                    list.set_item(i, Self::construct(slf, item)?)?;
+                }
+                Ok(list.into_any().unbind())
+            }
+            PyNode::Mapping(mapping) => {
+                let pairs: Vec<(PyNode, PyNode)> = mapping.borrow(py).value.clone();
+                let dict = presized_dict(py, pairs.len())?;
+                this.remember_first(py, key, dict.as_any().as_unbound());
+                let is_set = tag == crate::TAG_SET;
+                let mut merge_sources = Vec::new();
+                for (key_node, value_node) in &pairs {
+                    if key_node.get_tag(py)? == crate::TAG_MERGE {
+                        let value = Self::construct(slf, value_node)?.into_bound(py);
+                        match value.downcast::<PyList>() {
+                            Ok(sources) => merge_sources.extend(sources.iter()),
+                            Err(_) => merge_sources.push(value),
+                        }
+                        continue;
+                    }
+                    let key = make_hashable(py, Self::construct(slf, key_node)?)?;
+                    if is_set {
+                        dict.set_item(key, py.None())?;
+                    } else {
+                        dict.set_item(key, Self::construct(slf, value_node)?)?;
+                    }
+                }
+                // Explicit keys take precedence, then the first merge source wins
+                for source in &merge_sources {
+                    if let Ok(source) = source.downcast::<PyDict>() {
+                        for (k, v) in source.iter() {
+                            if !dict.contains(&k)? {
+                                dict.set_item(&k, v)?;
+                            }
+                        }
+                    }
+                }
+                Ok(dict.into_any().unbind())
+            }
+        }
+    }
+
+    fn remember(&self, py: Python, key: usize, obj: &Py<PyAny>) {
+        self.constructed
+            .lock()
+            .unwrap()
+            .insert(key, obj.clone_ref(py));
+    }
+
+    /// Remember `obj` for the node `key` unless something already was
+    fn remember_first(&self, py: Python, key: usize, obj: &Py<PyAny>) {
+        self.constructed
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| obj.clone_ref(py));
+    }
+}
+
+/// An empty dict with room for `len` entries, so filling it never resizes it
+fn presized_dict(py: Python<'_>, len: usize) -> PyResult<Bound<'_, PyDict>> {
+    // The limited API has no way to presize a dict
+    #[cfg(feature = "abi3")]
+    {
+        let _ = len;
+        Ok(PyDict::new(py))
+    }
+    #[cfg(not(feature = "abi3"))]
+    {
+        let len = pyo3::ffi::Py_ssize_t::try_from(len).unwrap_or(0);
+        // SAFETY: _PyDict_NewPresized returns a new reference to a dict, or
+        // null with an exception set
+        unsafe {
+            Ok(
+                Bound::from_owned_ptr_or_err(py, pyo3::ffi::_PyDict_NewPresized(len))?
+                    .downcast_into_unchecked(),
+            )
+        }
+    }
+}
+
+/// Construct a node tree, native or from pyyaml, with the registered constructors
+pub fn construct_node(py: Python, node: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+    let node = PyNode::from_any(node)?;
+    let state = state::get(py)?;
+    let constructors = state.get().registries.constructors.snapshot();
+    let constructor = Bound::new(py, PyConstructor::new(state.unbind(), constructors))?;
+    PyConstructor::construct_document(&constructor, &node)
+}
+
+/// Error for a node of the wrong kind passed to a `construct_*` method
+pub fn expected(py: Python, kind: &str, node: &PyNode) -> PyErr {
+    let found = match node {
+        PyNode::Scalar(_) => "scalar",
+        PyNode::Sequence(_) => "sequence",
+        PyNode::Mapping(_) => "mapping",
+    };
+    exception::constructor_error(py, format!("expected {kind} node, but found {found}"))
+}
+
+pub fn register_constructor(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyConstructor>()?;
+    Ok(())
+}
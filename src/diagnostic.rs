@@ -0,0 +1,250 @@
+//! Diagnostic, Change and Edit Python classes: the results shared by the APIs
+//! which check, compare or rewrite YAML text, so tools handle them all the
+//! same way.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+
+use crate::mark::PyMark;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "error" => Ok(Severity::Error),
+            "warning" => Ok(Severity::Warning),
+            "info" => Ok(Severity::Info),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported severity '{name}' (expected 'error', 'warning' or 'info')"
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A problem found in a document, with where it is in the text and in the data
+#[pyclass(name = "Diagnostic", frozen)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Short stable identifier of the kind of problem, e.g. `duplicate-key`
+    #[pyo3(get)]
+    pub code: String,
+    #[pyo3(get)]
+    pub message: String,
+    /// Where the problem starts, if it has a location in the text
+    #[pyo3(get)]
+    pub mark: Option<PyMark>,
+    /// Where the problem ends
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    /// Keys and indices leading to the node the problem is about
+    pub path: Vec<Py<PyAny>>,
+}
+
+#[pymethods]
+impl Diagnostic {
+    #[new]
+    #[pyo3(signature = (severity, code, message, mark=None, end_mark=None, path=Vec::new()))]
+    fn py_new(
+        severity: &str,
+        code: String,
+        message: String,
+        mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        path: Vec<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            severity: Severity::from_name(severity)?,
+            code,
+            message,
+            mark,
+            end_mark,
+            path,
+        })
+    }
+
+    /// "error", "warning" or "info"
+    #[getter]
+    fn severity(&self) -> &'static str {
+        self.severity.name()
+    }
+
+    #[getter]
+    fn path<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        PyTuple::new(py, &self.path)
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        Ok(format!(
+            "Diagnostic({:?}, {:?}, {:?}, path={})",
+            self.severity.name(),
+            self.code,
+            self.message,
+            self.path(py)?.repr()?
+        ))
+    }
+
+    /// `name:line:column: severity[code]: message`, as compilers and linters
+    /// report problems
+    fn __str__(&self) -> String {
+        let location = match &self.mark {
+            Some(mark) => format!("{}:{}:{}: ", mark.name, mark.line + 1, mark.column + 1),
+            None => String::new(),
+        };
+        format!(
+            "{location}{}[{}]: {}",
+            self.severity.name(),
+            self.code,
+            self.message
+        )
+    }
+}
+
+/// What a change did to the value at its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// A value added, removed or changed between two documents, with where it
+/// is in each
+#[pyclass(name = "Change", frozen)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Keys and indices leading to the value
+    pub path: Vec<Py<PyAny>>,
+    /// The value before, None when it was added
+    #[pyo3(get)]
+    pub old: Option<Py<PyAny>>,
+    /// The value after, None when it was removed
+    #[pyo3(get)]
+    pub new: Option<Py<PyAny>>,
+    #[pyo3(get)]
+    pub old_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub new_mark: Option<PyMark>,
+}
+
+#[pymethods]
+impl Change {
+    /// "added", "removed" or "changed"
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.kind.name()
+    }
+
+    #[getter]
+    fn path<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        PyTuple::new(py, &self.path)
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let repr = |value: &Option<Py<PyAny>>| match value {
+            Some(value) => Ok(value.bind(py).repr()?.to_string()),
+            None => PyResult::Ok("None".to_string()),
+        };
+        Ok(format!(
+            "Change({:?}, path={}, old={}, new={})",
+            self.kind.name(),
+            self.path(py)?.repr()?,
+            repr(&self.old)?,
+            repr(&self.new)?
+        ))
+    }
+}
+
+/// A replacement of the text between two character offsets
+#[pyclass(name = "Edit", frozen, eq, hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edit {
+    /// Start and end offsets of the replaced text
+    #[pyo3(get)]
+    pub span: (usize, usize),
+    #[pyo3(get)]
+    pub replacement: String,
+}
+
+#[pymethods]
+impl Edit {
+    #[new]
+    fn py_new(span: (usize, usize), replacement: String) -> PyResult<Self> {
+        if span.0 > span.1 {
+            return Err(PyValueError::new_err(format!(
+                "edit span ({}, {}) ends before it starts",
+                span.0, span.1
+            )));
+        }
+        Ok(Self { span, replacement })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Edit({:?}, {:?})", self.span, self.replacement)
+    }
+}
+
+/// Apply non-overlapping `edits` to `text`, whatever order they are given in
+pub fn apply_edits(text: &str, edits: &[Edit]) -> PyResult<String> {
+    let mut edits: Vec<&Edit> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.span);
+    // Spans are character offsets, as Python indexes strings
+    let offsets: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([text.len()])
+        .collect();
+    let byte_offset = |offset: usize| {
+        offsets.get(offset).copied().ok_or_else(|| {
+            PyValueError::new_err(format!("edit offset {offset} is past the end of the text"))
+        })
+    };
+
+    let mut output = String::with_capacity(text.len());
+    let mut copied = 0;
+    for edit in edits {
+        let (start, end) = edit.span;
+        if start < copied {
+            return Err(PyValueError::new_err(format!(
+                "edit span ({start}, {end}) overlaps an earlier edit"
+            )));
+        }
+        output.push_str(&text[byte_offset(copied)?..byte_offset(start)?]);
+        output.push_str(&edit.replacement);
+        copied = end;
+    }
+    output.push_str(&text[byte_offset(copied)?..]);
+    Ok(output)
+}
+
+pub fn register_diagnostics(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Diagnostic>()?;
+    m.add_class::<Change>()?;
+    m.add_class::<Edit>()?;
+    Ok(())
+}
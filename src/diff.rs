@@ -0,0 +1,184 @@
+//! `diff()`: the changes from one document to another, found by walking their
+//! node trees side by side. Mapping entries are matched by key and sequence
+//! items by index; scalars differ when their tags or constructed values do.
+//! Each change carries the marks of its values in both texts.
+
+use std::collections::{HashMap, HashSet};
+
+use pyo3::prelude::*;
+
+use crate::constructor::PyConstructor;
+use crate::diagnostic::{Change, ChangeKind};
+use crate::merge::scalar_key;
+use crate::nodes::{PyMappingNode, PyNode, PySequenceNode};
+use crate::state;
+
+pub struct Differ<'py> {
+    py: Python<'py>,
+    constructor: Bound<'py, PyConstructor>,
+    changes: Vec<Change>,
+    /// Pairs of nodes being compared, so recursive nodes end
+    comparing: HashSet<(usize, usize)>,
+}
+
+impl<'py> Differ<'py> {
+    pub fn new(py: Python<'py>) -> PyResult<Self> {
+        let state = state::get(py)?;
+        let constructors = state.get().registries.constructors.snapshot();
+        let constructor = PyConstructor::new(state.unbind(), constructors);
+        Ok(Self {
+            py,
+            constructor: Bound::new(py, constructor)?,
+            changes: Vec::new(),
+            comparing: HashSet::new(),
+        })
+    }
+
+    /// The changes from the document `old` to the document `new`, either of
+    /// which may be empty
+    pub fn diff(mut self, old: Option<&PyNode>, new: Option<&PyNode>) -> PyResult<Vec<Change>> {
+        let mut path = Vec::new();
+        match (old, new) {
+            (Some(old), Some(new)) => self.node(old, new, &mut path)?,
+            (Some(old), None) => self.change(ChangeKind::Removed, &path, Some(old), None)?,
+            (None, Some(new)) => self.change(ChangeKind::Added, &path, None, Some(new))?,
+            (None, None) => {}
+        }
+        Ok(self.changes)
+    }
+
+    fn node(&mut self, old: &PyNode, new: &PyNode, path: &mut Vec<Py<PyAny>>) -> PyResult<()> {
+        let py = self.py;
+        let pair = (old.as_ptr() as usize, new.as_ptr() as usize);
+        if pair.0 == pair.1 || self.comparing.contains(&pair) {
+            return Ok(());
+        }
+        if old.get_tag(py)? != new.get_tag(py)? {
+            return self.change(ChangeKind::Changed, path, Some(old), Some(new));
+        }
+        self.comparing.insert(pair);
+        let compared = match (old, new) {
+            (PyNode::Mapping(old), PyNode::Mapping(new)) => {
+                self.mapping(&old.borrow(py), &new.borrow(py), path)
+            }
+            (PyNode::Sequence(old), PyNode::Sequence(new)) => {
+                self.sequence(&old.borrow(py), &new.borrow(py), path)
+            }
+            (PyNode::Scalar(old_scalar), PyNode::Scalar(new_scalar)) => {
+                let same_text = old_scalar.borrow(py).value == new_scalar.borrow(py).value;
+                if same_text || self.construct(old)?.bind(py).eq(self.construct(new)?)? {
+                    Ok(())
+                } else {
+                    self.change(ChangeKind::Changed, path, Some(old), Some(new))
+                }
+            }
+            _ => self.change(ChangeKind::Changed, path, Some(old), Some(new)),
+        };
+        self.comparing.remove(&pair);
+        compared
+    }
+
+    fn mapping(
+        &mut self,
+        old: &PyMappingNode,
+        new: &PyMappingNode,
+        path: &mut Vec<Py<PyAny>>,
+    ) -> PyResult<()> {
+        let py = self.py;
+        let mut positions = HashMap::new();
+        for (position, (key, _)) in new.value.iter().enumerate() {
+            if let Some(key) = scalar_key(py, key) {
+                positions.entry(key).or_insert(position);
+            }
+        }
+        let mut matched = vec![false; new.value.len()];
+        for (key, value) in &old.value {
+            let position = match scalar_key(py, key) {
+                Some(key) => positions.get(&key).copied(),
+                // Complex keys match keys without changes between them
+                None => {
+                    let mut found = None;
+                    for (position, (new_key, _)) in new.value.iter().enumerate() {
+                        if !matched[position]
+                            && scalar_key(py, new_key).is_none()
+                            && Differ::new(py)?.diff(Some(key), Some(new_key))?.is_empty()
+                        {
+                            found = Some(position);
+                            break;
+                        }
+                    }
+                    found
+                }
+            };
+            path.push(self.construct(key)?);
+            let compared = match position.filter(|&position| !matched[position]) {
+                Some(position) => {
+                    matched[position] = true;
+                    self.node(value, &new.value[position].1, path)
+                }
+                None => self.change(ChangeKind::Removed, path, Some(value), None),
+            };
+            path.pop();
+            compared?;
+        }
+        for ((key, value), matched) in new.value.iter().zip(matched) {
+            if !matched {
+                path.push(self.construct(key)?);
+                let added = self.change(ChangeKind::Added, path, None, Some(value));
+                path.pop();
+                added?;
+            }
+        }
+        Ok(())
+    }
+
+    fn sequence(
+        &mut self,
+        old: &PySequenceNode,
+        new: &PySequenceNode,
+        path: &mut Vec<Py<PyAny>>,
+    ) -> PyResult<()> {
+        let py = self.py;
+        for index in 0..old.value.len().max(new.value.len()) {
+            path.push(index.into_pyobject(py)?.into_any().unbind());
+            let compared = match (old.value.get(index), new.value.get(index)) {
+                (Some(old), Some(new)) => self.node(old, new, path),
+                (Some(old), None) => self.change(ChangeKind::Removed, path, Some(old), None),
+                (None, Some(new)) => self.change(ChangeKind::Added, path, None, Some(new)),
+                (None, None) => unreachable!("index is within one of the sequences"),
+            };
+            path.pop();
+            compared?;
+        }
+        Ok(())
+    }
+
+    fn construct(&self, node: &PyNode) -> PyResult<Py<PyAny>> {
+        PyConstructor::construct(&self.constructor, node)
+    }
+
+    fn change(
+        &mut self,
+        kind: ChangeKind,
+        path: &[Py<PyAny>],
+        old: Option<&PyNode>,
+        new: Option<&PyNode>,
+    ) -> PyResult<()> {
+        let py = self.py;
+        let value = |node: Option<&PyNode>| node.map(|node| self.construct(node)).transpose();
+        let mark = |node: Option<&PyNode>| match node {
+            Some(node) => node.get_start_mark(py),
+            None => Ok(None),
+        };
+        let change = Change {
+            kind,
+            path: path.iter().map(|segment| segment.clone_ref(py)).collect(),
+            old: value(old)?,
+            new: value(new)?,
+            old_mark: mark(old)?,
+            new_mark: mark(new)?,
+        };
+        self.changes.push(change);
+        Ok(())
+    }
+}
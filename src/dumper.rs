@@ -1,88 +1,875 @@
 //! Full RSafeDumper implementation: emitter + serializer + SafeRepresenter + resolver.
 //! All in Rust, matching the RSafeLoader pattern.
 
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
 
 use base64::Engine as _;
-use libyaml_safer::{Emitter, Encoding, Event, MappingStyle, ScalarStyle, SequenceStyle};
+use libyaml_safer::{
+    Break, Emitter, Encoding, Event, EventData, MappingStyle, ScalarStyle, SequenceStyle,
+    TagDirective, VersionDirective,
+};
+use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{
-    PyBool, PyBytes, PyDict, PyFloat, PyFrozenSet, PyInt, PyList, PySet, PyString, PyTuple,
+    PyBool, PyByteArray, PyBytes, PyDict, PyFloat, PyFrozenSet, PyInt, PyList, PyMemoryView, PySet,
+    PyString, PyTuple, PyType,
 };
 
+use crate::comments::{EntryComments, insert_comments};
 use crate::exception;
-use crate::resolver;
+use crate::exclusive::Exclusive;
+use crate::json::JsonValue;
+use crate::nodes::{PyMappingNode, PyNode, PyScalarNode, PySequenceNode};
+use crate::registry;
+use crate::resolver::{self, ResolverCache, Resolvers, Schema};
+use crate::state::{self, ModuleState};
+use crate::tagged::PyTaggedValue;
+
+/// Plain nodes for None and bools, shared by every document of every dump
+static NULL_NODE: LazyLock<Arc<RepNode>> = LazyLock::new(|| static_scalar(crate::TAG_NULL, "null"));
+static TRUE_NODE: LazyLock<Arc<RepNode>> = LazyLock::new(|| static_scalar(crate::TAG_BOOL, "true"));
+static FALSE_NODE: LazyLock<Arc<RepNode>> =
+    LazyLock::new(|| static_scalar(crate::TAG_BOOL, "false"));
+
+fn static_scalar(tag: &'static str, value: &str) -> Arc<RepNode> {
+    Arc::new(RepNode::Scalar {
+        tag: Cow::Borrowed(tag),
+        value: value.to_string(),
+        style: None,
+        shared: true,
+    })
+}
+
+/// Options accepted as keyword arguments by `dumps()`
+#[derive(Debug, Clone)]
+pub struct DumperOptions {
+    /// Dump datetime-like objects from third-party libraries (arrow, numpy
+    /// `datetime64`, ...) as timestamps instead of raising
+    pub third_party_datetimes: bool,
+    /// How timezone-aware datetimes are written
+    pub timezone: TimezoneMode,
+    /// Scalar styles forced for string values at matching key paths
+    pub styles: Vec<StyleOverride>,
+    /// Scalar style of every scalar no other rule styles
+    pub default_style: Option<char>,
+    /// Write the fully tagged, fully quoted canonical form
+    pub canonical: bool,
+    /// Line break written at the end of each line
+    pub line_break: Break,
+    /// Return bytes in this encoding instead of a str
+    pub encoding: Option<Encoding>,
+    /// Write strings containing line breaks in literal block style
+    pub literal_multiline: bool,
+    /// Write non-ASCII characters as they are rather than as escapes
+    pub allow_unicode: bool,
+    /// Rules deciding which strings must be quoted to load back as strings
+    pub schema: Schema,
+    /// Also quote strings that another YAML 1.1 or 1.2 parser would read as
+    /// something other than a string
+    pub quote_ambiguous: bool,
+    /// Largest number of items a list, dict or set may have
+    pub max_items: Option<usize>,
+    /// Write NaN and infinite floats as `.nan`/`.inf` instead of raising
+    pub allow_nan: bool,
+    /// When `...` is written after a document
+    pub document_end: DocumentEnd,
+    /// End the output with a line break
+    pub final_newline: bool,
+    /// Write None, bool, int and float keys as strings
+    pub stringify_keys: bool,
+    /// Represent dataclass and attrs instances as mappings of their fields
+    pub dump_dataclasses: bool,
+    /// Represent numpy scalars as numbers and arrays as nested sequences
+    pub dump_numpy: bool,
+    /// Represent any other iterable, such as a generator or `range`, as a sequence
+    pub iterable_as_sequence: bool,
+    /// Anchor objects appearing more than once and write aliases to them;
+    /// when false each occurrence is written out in full
+    pub aliases: bool,
+    /// Fallback for objects no representer, protocol or `default` handles
+    pub coerce_unknown: CoerceUnknown,
+    /// Called with objects that can't otherwise be represented, to get an
+    /// object to represent in their place
+    pub default: Option<Py<PyAny>>,
+    /// Order mapping keys are written in
+    pub sort_keys: SortKeys,
+    /// How sets are written
+    pub set_style: SetStyle,
+    /// Write nested sequences and mappings of fewer than this many short
+    /// plain scalars in flow style, whatever `default_flow_style` says
+    pub compact_threshold: Option<usize>,
+}
+
+impl Default for DumperOptions {
+    fn default() -> Self {
+        Self {
+            third_party_datetimes: false,
+            timezone: TimezoneMode::Preserve,
+            styles: Vec::new(),
+            default_style: None,
+            canonical: false,
+            line_break: Break::Any,
+            encoding: None,
+            literal_multiline: false,
+            allow_unicode: true,
+            schema: Schema::default(),
+            quote_ambiguous: false,
+            max_items: None,
+            allow_nan: true,
+            document_end: DocumentEnd::Auto,
+            final_newline: true,
+            stringify_keys: false,
+            dump_dataclasses: false,
+            dump_numpy: false,
+            iterable_as_sequence: false,
+            aliases: true,
+            coerce_unknown: CoerceUnknown::Error,
+            default: None,
+            sort_keys: SortKeys::Unsorted,
+            set_style: SetStyle::Block,
+            compact_threshold: None,
+        }
+    }
+}
+
+/// Scalar style forced for string values whose key path matches a pattern
+#[derive(Debug, Clone)]
+pub struct StyleOverride {
+    /// Dotted key path segments, where `*` matches any single key or index
+    pattern: Vec<String>,
+    style: char,
+}
+
+impl StyleOverride {
+    /// Parse a `{path pattern: style}` mapping, e.g. `{"data.*": "literal"}`.
+    /// Styles are given by name or by their indicator character.
+    pub fn from_dict(styles: &Bound<'_, PyDict>) -> PyResult<Vec<Self>> {
+        let mut overrides = Vec::with_capacity(styles.len());
+        for (pattern, style) in styles.iter() {
+            let pattern: String = pattern.extract()?;
+            let style: String = style.extract()?;
+            let Some(style) = style_char(&style) else {
+                return Err(PyValueError::new_err(format!(
+                    "unsupported scalar style '{style}' for '{pattern}' \
+                     (expected 'literal', 'folded', 'single' or 'double')"
+                )));
+            };
+            overrides.push(StyleOverride {
+                pattern: pattern.split('.').map(str::to_string).collect(),
+                style,
+            });
+        }
+        Ok(overrides)
+    }
+
+    fn matches(&self, path: &[String]) -> bool {
+        self.pattern.len() == path.len()
+            && self
+                .pattern
+                .iter()
+                .zip(path)
+                .all(|(pattern, key)| pattern == "*" || pattern == key)
+    }
+}
+
+/// The emitter setting for an encoding given by its Python codec name
+fn parse_encoding(name: &str) -> Option<Encoding> {
+    match name {
+        "utf-8" | "utf8" => Some(Encoding::Utf8),
+        "utf-16-le" | "utf-16le" => Some(Encoding::Utf16Le),
+        "utf-16-be" | "utf-16be" => Some(Encoding::Utf16Be),
+        _ => None,
+    }
+}
+
+/// `trim_output_end` only knows how UTF-8 output ends
+fn check_trimmed_encoding(
+    document_end: DocumentEnd,
+    final_newline: bool,
+    encoding: Encoding,
+) -> PyResult<()> {
+    if (document_end == DocumentEnd::Never || !final_newline) && encoding != Encoding::Utf8 {
+        return Err(PyValueError::new_err(
+            "document_end='never' and final_newline=False need UTF-8 output",
+        ));
+    }
+    Ok(())
+}
+
+/// The emitter setting for a `line_break` given as the break itself
+fn parse_line_break(line_break: &str) -> Option<Break> {
+    match line_break {
+        "\n" => Some(Break::Ln),
+        "\r" => Some(Break::Cr),
+        "\r\n" => Some(Break::CrLn),
+        _ => None,
+    }
+}
+
+/// The indicator character of a scalar style given by name or by indicator
+fn style_char(style: &str) -> Option<char> {
+    match style {
+        "literal" | "|" => Some('|'),
+        "folded" | ">" => Some('>'),
+        "single" | "'" => Some('\''),
+        "double" | "\"" => Some('"'),
+        _ => None,
+    }
+}
+
+/// Normalization applied to timezone-aware datetimes when dumping
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimezoneMode {
+    /// Keep the original UTC offset
+    #[default]
+    Preserve,
+    /// Convert to UTC and mark with `Z`
+    Utc,
+    /// Drop the offset, keeping the local wall-clock time
+    Naive,
+}
+
+impl TimezoneMode {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "preserve" => Ok(TimezoneMode::Preserve),
+            "utc" => Ok(TimezoneMode::Utc),
+            "naive" => Ok(TimezoneMode::Naive),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported timezone mode '{name}' (expected 'preserve', 'utc' or 'naive')"
+            ))),
+        }
+    }
+}
+
+/// When the `...` document end marker is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DocumentEnd {
+    /// Only where the emitter needs it, after an open-ended scalar such as a
+    /// plain scalar at the top level
+    #[default]
+    Auto,
+    /// After every document
+    Always,
+    /// Never after the last document
+    Never,
+}
+
+impl DocumentEnd {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "auto" => Ok(DocumentEnd::Auto),
+            "always" => Ok(DocumentEnd::Always),
+            "never" => Ok(DocumentEnd::Never),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported document end '{name}' (expected 'auto', 'always' or 'never')"
+            ))),
+        }
+    }
+}
+
+/// What becomes of objects nothing else knows how to represent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoerceUnknown {
+    /// Raise a `RepresenterError`
+    #[default]
+    Error,
+    /// Write the object's `str()` as a string
+    Str,
+    /// Write the object's `repr()` as a string
+    Repr,
+}
+
+impl CoerceUnknown {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "error" => Ok(CoerceUnknown::Error),
+            "str" => Ok(CoerceUnknown::Str),
+            "repr" => Ok(CoerceUnknown::Repr),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported coerce_unknown mode '{name}' (expected 'error', 'str' or 'repr')"
+            ))),
+        }
+    }
+}
+
+/// How sets are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SetStyle {
+    /// A `!!set` block mapping of the items to nulls, as pyyaml writes them
+    #[default]
+    Block,
+    /// A `!!set` flow mapping, `!!set {a: null, b: null}`
+    Flow,
+    /// A plain sequence of the items, sorted so every run writes them in the
+    /// same order
+    Sequence,
+}
+
+impl SetStyle {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "block" => Ok(SetStyle::Block),
+            "flow" => Ok(SetStyle::Flow),
+            "sequence" => Ok(SetStyle::Sequence),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported set_style '{name}' (expected 'block', 'flow' or 'sequence')"
+            ))),
+        }
+    }
+}
+
+/// Order mapping keys are written in; lists of pairs keep theirs
+#[derive(Debug, Clone, Default)]
+pub enum SortKeys {
+    /// As the mapping holds them
+    #[default]
+    Unsorted,
+    /// By Python's comparisons, keeping the order of keys that don't compare
+    Sorted,
+    /// By the text of the keys, with runs of digits compared by their value,
+    /// so `item2` comes before `item10`
+    Natural,
+    /// By what the callable returns for each key, as `sorted(key=...)` orders
+    Key(Py<PyAny>),
+}
+
+impl SortKeys {
+    /// `sort_keys` given as a bool, `"natural"` or a key function
+    pub fn from_value(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if value.is_none() {
+            Ok(SortKeys::Unsorted)
+        } else if let Ok(sorted) = value.downcast::<PyBool>() {
+            Ok(match sorted.is_true() {
+                true => SortKeys::Sorted,
+                false => SortKeys::Unsorted,
+            })
+        } else if let Ok(mode) = value.extract::<&str>() {
+            match mode {
+                "natural" => Ok(SortKeys::Natural),
+                _ => Err(PyValueError::new_err(format!(
+                    "unsupported sort_keys mode '{mode}' (expected 'natural', a bool or a callable)"
+                ))),
+            }
+        } else if value.is_callable() {
+            Ok(SortKeys::Key(value.clone().unbind()))
+        } else {
+            Err(PyTypeError::new_err(
+                "sort_keys must be a bool, 'natural' or a callable",
+            ))
+        }
+    }
+
+    /// Sort `pairs` by key. Keys that don't compare are left in their order,
+    /// as pyyaml leaves them; errors from a key function are raised.
+    fn sort(&self, py: Python, pairs: &mut [(Py<PyAny>, Py<PyAny>)]) -> PyResult<()> {
+        match self {
+            SortKeys::Unsorted => Ok(()),
+            SortKeys::Sorted => {
+                let keys: Vec<_> = pairs.iter().map(|(key, _)| key.clone_ref(py)).collect();
+                let _ = sort_by_keys(py, pairs, keys);
+                Ok(())
+            }
+            SortKeys::Natural => {
+                let keys = pairs
+                    .iter()
+                    .map(|(key, _)| Ok(natural_key(&key.bind(py).str()?.to_cow()?)))
+                    .collect::<PyResult<Vec<_>>>()?;
+                let mut indexed: Vec<_> = keys.into_iter().zip(pairs.iter().cloned()).collect();
+                indexed.sort_by(|a, b| a.0.cmp(&b.0));
+                for (slot, (_, pair)) in pairs.iter_mut().zip(indexed) {
+                    *slot = pair;
+                }
+                Ok(())
+            }
+            SortKeys::Key(function) => {
+                let keys = pairs
+                    .iter()
+                    .map(|(key, _)| function.call1(py, (key,)))
+                    .collect::<PyResult<Vec<_>>>()?;
+                sort_by_keys(py, pairs, keys)
+            }
+        }
+    }
+}
+
+/// The items of the set `data`, sorted with Python's comparisons, or by
+/// their repr when they don't compare, as a mix of ints and strs doesn't
+fn sorted_set_items(py: Python, data: &Bound<'_, PyAny>) -> PyResult<Vec<Py<PyAny>>> {
+    let mut items = data
+        .try_iter()?
+        .map(|item| item.map(Bound::unbind))
+        .collect::<PyResult<Vec<_>>>()?;
+    let keys = items.clone();
+    if sort_by_keys(py, &mut items, keys).is_err() {
+        let reprs = items
+            .iter()
+            .map(|item| Ok(item.bind(py).repr()?.into_any().unbind()))
+            .collect::<PyResult<Vec<_>>>()?;
+        sort_by_keys(py, &mut items, reprs)?;
+    }
+    Ok(items)
+}
+
+/// A run of a key sorted naturally: digits, by their value, before text
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Chunk {
+    /// Length and digits of a number without its leading zeros
+    Number(usize, String),
+    Text(String),
+}
+
+/// `key` split into runs of digits and runs of other characters
+fn natural_key(key: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut rest = key;
+    while let Some(first) = rest.chars().next() {
+        let digits = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(rest.len());
+        let (run, after) = rest.split_at(end);
+        chunks.push(match digits {
+            true => {
+                let number = run.trim_start_matches('0');
+                Chunk::Number(number.len(), number.to_string())
+            }
+            false => Chunk::Text(run.to_string()),
+        });
+        rest = after;
+    }
+    chunks
+}
+
+/// Sort `pairs` by `keys`, one for each pair, with Python's comparisons.
+/// Keys that don't compare fail the sort, leaving `pairs` as they were.
+fn sort_by_keys<T: Clone>(py: Python, pairs: &mut [T], keys: Vec<Py<PyAny>>) -> PyResult<()> {
+    let mut order: Vec<usize> = (0..pairs.len()).collect();
+    let mut error = None;
+    order.sort_by(|&a, &b| {
+        if error.is_some() {
+            return std::cmp::Ordering::Equal;
+        }
+        let (a, b) = (keys[a].bind(py), keys[b].bind(py));
+        let ordering = a.lt(b).and_then(|lt| match lt {
+            true => Ok(std::cmp::Ordering::Less),
+            false => b.lt(a).map(|gt| match gt {
+                true => std::cmp::Ordering::Greater,
+                false => std::cmp::Ordering::Equal,
+            }),
+        });
+        ordering.unwrap_or_else(|e| {
+            error = Some(e);
+            std::cmp::Ordering::Equal
+        })
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+    let sorted: Vec<_> = order.iter().map(|&i| pairs[i].clone()).collect();
+    for (slot, pair) in pairs.iter_mut().zip(sorted) {
+        *slot = pair;
+    }
+    Ok(())
+}
+
+impl DumperOptions {
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut options = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok(options);
+        };
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "third_party_datetimes" => options.third_party_datetimes = value.extract()?,
+                "timezone" => {
+                    options.timezone = TimezoneMode::from_name(&value.extract::<String>()?)?
+                }
+                "styles" => {
+                    if !value.is_none() {
+                        options.styles = StyleOverride::from_dict(value.downcast()?)?;
+                    }
+                }
+                "default_style" => {
+                    options.default_style = match value.extract::<Option<String>>()? {
+                        Some(style) => Some(style_char(&style).ok_or_else(|| {
+                            PyValueError::new_err(format!(
+                                "unsupported default_style '{style}' \
+                                 (expected 'literal', 'folded', 'single' or 'double')"
+                            ))
+                        })?),
+                        None => None,
+                    }
+                }
+                "canonical" => options.canonical = value.extract()?,
+                "encoding" => {
+                    options.encoding = match value.extract::<Option<String>>()? {
+                        Some(name) => Some(parse_encoding(&name).ok_or_else(|| {
+                            PyValueError::new_err(format!(
+                                "unsupported encoding '{name}' \
+                                 (expected 'utf-8', 'utf-16-le' or 'utf-16-be')"
+                            ))
+                        })?),
+                        None => None,
+                    }
+                }
+                "line_break" => {
+                    let line_break = value.extract::<String>()?;
+                    options.line_break = parse_line_break(&line_break).ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "unsupported line_break {line_break:?} (expected '\\n', '\\r\\n' or '\\r')"
+                        ))
+                    })?;
+                }
+                "literal_multiline" => options.literal_multiline = value.extract()?,
+                "allow_unicode" => options.allow_unicode = value.extract()?,
+                "schema" => options.schema = Schema::from_name(&value.extract::<String>()?)?,
+                "quote_ambiguous" => options.quote_ambiguous = value.extract()?,
+                "max_items" => options.max_items = value.extract()?,
+                "allow_nan" => options.allow_nan = value.extract()?,
+                "document_end" => {
+                    options.document_end = DocumentEnd::from_name(&value.extract::<String>()?)?
+                }
+                "final_newline" => options.final_newline = value.extract()?,
+                "stringify_keys" => options.stringify_keys = value.extract()?,
+                "sort_keys" => options.sort_keys = SortKeys::from_value(&value)?,
+                "compact_threshold" => options.compact_threshold = value.extract()?,
+                "dump_dataclasses" => options.dump_dataclasses = value.extract()?,
+                "dump_numpy" => options.dump_numpy = value.extract()?,
+                "iterable_as_sequence" => options.iterable_as_sequence = value.extract()?,
+                "aliases" => options.aliases = value.extract()?,
+                "coerce_unknown" => {
+                    options.coerce_unknown = CoerceUnknown::from_name(&value.extract::<String>()?)?
+                }
+                "set_style" => {
+                    options.set_style = SetStyle::from_name(&value.extract::<String>()?)?
+                }
+                "default" => {
+                    if !value.is_none() && !value.is_callable() {
+                        return Err(PyValueError::new_err("default must be callable"));
+                    }
+                    options.default = (!value.is_none()).then(|| value.unbind());
+                }
+                _ => {
+                    return Err(PyTypeError::new_err(format!(
+                        "unexpected keyword argument '{key}'"
+                    )));
+                }
+            }
+        }
+        check_trimmed_encoding(
+            options.document_end,
+            options.final_newline,
+            options.encoding.unwrap_or(Encoding::Utf8),
+        )?;
+        Ok(options)
+    }
+}
+
+/// Key of the representer's scalar cache: tag, style and value
+type ScalarKey = (&'static str, Option<char>, String);
+
+/// Tag of a representation node, borrowed for the built-in tags
+type Tag = Cow<'static, str>;
 
 /// Internal representation node used by the representer/serializer.
 /// Uses Rc for alias detection via pointer identity.
 #[derive(Debug)]
 enum RepNode {
     Scalar {
-        tag: String,
+        tag: Tag,
         value: String,
         style: Option<char>,
+        /// Taken from the scalar cache and shared by every equal value, so
+        /// never anchored
+        shared: bool,
     },
     Sequence {
-        tag: String,
+        tag: Tag,
         value: Vec<Arc<RepNode>>,
         flow_style: Option<bool>,
     },
     Mapping {
-        tag: String,
+        tag: Tag,
         value: Vec<(Arc<RepNode>, Arc<RepNode>)>,
         flow_style: Option<bool>,
     },
 }
 
+impl RepNode {
+    /// A rough length for the text of this node, to reserve output for it:
+    /// its scalars plus a few bytes of indentation and punctuation around
+    /// each node. Collections met again are aliases, so are counted once.
+    fn estimated_len(self: &Arc<Self>, seen: &mut HashSet<usize>) -> usize {
+        if !seen.insert(Arc::as_ptr(self) as usize) {
+            return ESTIMATED_NODE_OVERHEAD;
+        }
+        ESTIMATED_NODE_OVERHEAD
+            + match self.as_ref() {
+                RepNode::Scalar { value, .. } => value.len(),
+                RepNode::Sequence { value, .. } => {
+                    value.iter().map(|item| item.estimated_len(seen)).sum()
+                }
+                RepNode::Mapping { value, .. } => value
+                    .iter()
+                    .map(|(k, v)| k.estimated_len(seen) + v.estimated_len(seen))
+                    .sum(),
+            }
+    }
+
+    /// A copy of this node with a different tag
+    fn with_tag(&self, tag: &str) -> RepNode {
+        match self {
+            RepNode::Scalar { value, style, .. } => RepNode::Scalar {
+                tag: Cow::Owned(tag.to_string()),
+                value: value.clone(),
+                style: *style,
+                shared: false,
+            },
+            RepNode::Sequence {
+                value, flow_style, ..
+            } => RepNode::Sequence {
+                tag: Cow::Owned(tag.to_string()),
+                value: value.clone(),
+                flow_style: *flow_style,
+            },
+            RepNode::Mapping {
+                value, flow_style, ..
+            } => RepNode::Mapping {
+                tag: Cow::Owned(tag.to_string()),
+                value: value.clone(),
+                flow_style: *flow_style,
+            },
+        }
+    }
+
+    /// Name of this node when used as a key in a style override path
+    fn path_segment(&self) -> String {
+        match self {
+            RepNode::Scalar { value, .. } => value.clone(),
+            _ => "?".to_string(),
+        }
+    }
+}
+
+/// Emitter settings, kept to set up a fresh emitter on `restart()`
+#[derive(Debug, Clone)]
+struct EmitterSettings {
+    encoding: Encoding,
+    canonical: bool,
+    indent: Option<i32>,
+    width: Option<i32>,
+    unicode: bool,
+    line_break: Break,
+}
+
+impl EmitterSettings {
+    fn for_options(options: &DumperOptions) -> Self {
+        Self {
+            encoding: options.encoding.unwrap_or(Encoding::Utf8),
+            canonical: options.canonical,
+            indent: None,
+            width: None,
+            unicode: options.allow_unicode,
+            line_break: options.line_break,
+        }
+    }
+}
+
+/// Output of an emitter, shared between the `Sink` it writes to and the
+/// wrapper reading and draining it
+type SharedOutput = Arc<Mutex<Vec<u8>>>;
+
+/// Writer handed to the emitter, appending to the shared output. The
+/// wrapper never touches it, so the emitter's reference to it is the only one.
+struct Sink(SharedOutput);
+
+impl std::io::Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        lock_output(&self.0).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn lock_output(output: &SharedOutput) -> MutexGuard<'_, Vec<u8>> {
+    output.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 /// Wraps libyaml Emitter with a self-owned output buffer.
 ///
-/// Safety: `emitter` borrows from `output` via an unsafe lifetime cast.
-/// The `output` is heap-allocated (Box) so it has a stable address.
-/// We guarantee emitter is always dropped before output by setting it
-/// to None in dispose()/Drop.
-struct EmitterWrapper {
-    #[allow(clippy::box_collection)]
-    output: Box<Vec<u8>>,
+/// Safety: `emitter` borrows `sink` via an unsafe lifetime cast. The `sink`
+/// is heap-allocated (Box) so it has a stable address, and nothing else
+/// borrows it: the output is read and drained through `output`, a handle of
+/// its own, even while the emitter writes. We guarantee emitter is always
+/// dropped before sink by setting it to None in restart()/dispose()/Drop.
+pub struct EmitterWrapper {
+    output: SharedOutput,
+    sink: Box<Sink>,
     emitter: Option<Emitter<'static>>,
+    settings: EmitterSettings,
+    /// Events kept instead of emitted, to be emitted later on another thread
+    recorded: Option<Vec<Event>>,
 }
 
 impl EmitterWrapper {
-    fn new() -> Self {
-        let output = Box::new(Vec::new());
-        let emitter = Emitter::new();
-        EmitterWrapper {
+    fn with_settings(settings: EmitterSettings) -> Self {
+        let output = SharedOutput::default();
+        let mut ew = EmitterWrapper {
+            sink: Box::new(Sink(Arc::clone(&output))),
             output,
-            emitter: Some(emitter),
+            emitter: None,
+            settings,
+            recorded: None,
+        };
+        ew.restart();
+        ew
+    }
+
+    /// Create an emitter configured from pyyaml-style dumper options
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_options(
+        py: Python,
+        encoding: Option<&str>,
+        canonical: Option<bool>,
+        indent: Option<i32>,
+        width: Option<i32>,
+        allow_unicode: Option<bool>,
+        line_break: Option<&str>,
+    ) -> PyResult<Self> {
+        let encoding = match encoding {
+            None => Encoding::Utf8,
+            Some(name) => parse_encoding(name)
+                .ok_or_else(|| exception::emitter_error(py, format!("unknown encoding: {name}")))?,
+        };
+        Ok(Self::with_settings(EmitterSettings {
+            encoding,
+            canonical: canonical.unwrap_or(false),
+            indent,
+            width,
+            unicode: allow_unicode.unwrap_or(false),
+            line_break: line_break
+                .map_or(Break::Any, |lb| parse_line_break(lb).unwrap_or(Break::Ln)),
+        }))
+    }
+
+    /// An emitter writing into its own buffer as `options` ask
+    fn for_options(options: &DumperOptions) -> Self {
+        Self::with_settings(EmitterSettings::for_options(options))
+    }
+
+    /// Start over with a fresh emitter and an empty buffer, keeping the
+    /// buffer's allocation
+    pub fn restart(&mut self) {
+        // Drop the emitter before touching the sink it borrows
+        self.emitter = None;
+        lock_output(&self.output).clear();
+        self.recorded = None;
+        // SAFETY: sink lives in a Box (stable heap address), is only reached
+        // through this reference, and we guarantee the emitter is dropped
+        // before sink (see dispose/Drop).
+        let sink_ref: &'static mut Sink = unsafe { &mut *(self.sink.as_mut() as *mut Sink) };
+        let settings = &self.settings;
+        let mut emitter = Emitter::new();
+        emitter.set_encoding(settings.encoding);
+        emitter.set_output(sink_ref);
+        emitter.set_canonical(settings.canonical);
+        if let Some(indent) = settings.indent {
+            emitter.set_indent(indent);
+        }
+        if let Some(width) = settings.width {
+            emitter.set_width(width);
         }
+        emitter.set_unicode(settings.unicode);
+        emitter.set_break(settings.line_break);
+        self.emitter = Some(emitter);
     }
 
-    fn configure(&mut self, encoding: Encoding) {
-        // SAFETY: output lives in a Box (stable heap address) and we guarantee
-        // the emitter is dropped before output (see dispose/Drop).
-        let output_ref: &'static mut Vec<u8> =
-            unsafe { &mut *(self.output.as_mut() as *mut Vec<u8>) };
-        let emitter = self.emitter.as_mut().unwrap();
-        emitter.set_encoding(encoding);
-        emitter.set_output_string(output_ref);
+    /// Start over as `options` ask, as `restart()` does
+    fn restart_for(&mut self, options: &DumperOptions) {
+        self.settings = EmitterSettings::for_options(options);
+        self.restart();
     }
 
-    fn emitter_mut(&mut self) -> &mut Emitter<'static> {
+    pub fn emitter_mut(&mut self) -> &mut Emitter<'static> {
         self.emitter.as_mut().expect("emitter already disposed")
     }
 
-    fn emit(&mut self, event: Event) -> Result<(), String> {
+    pub fn emit(&mut self, event: Event) -> Result<(), String> {
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(event);
+            return Ok(());
+        }
         self.emitter_mut()
             .emit(event)
             .map_err(|e| format!("emitter error: {e}"))
     }
 
-    fn take_output(&mut self) -> Vec<u8> {
-        std::mem::take(self.output.as_mut())
+    /// Keep the events passed to `emit()` instead of emitting them
+    pub fn record(&mut self) {
+        self.recorded = Some(Vec::new());
     }
 
-    fn dispose(&mut self) {
-        // Drop emitter first (releases borrow on output)
+    pub fn take_recorded(&mut self) -> Vec<Event> {
+        self.recorded.take().unwrap_or_default()
+    }
+
+    /// The output emitted so far; the emitter waits for it to be released
+    /// before writing more
+    pub fn output_mut(&mut self) -> MutexGuard<'_, Vec<u8>> {
+        lock_output(&self.output)
+    }
+
+    /// Make room for `additional` more bytes of output up front, unless
+    /// events are being recorded rather than emitted
+    pub fn reserve(&mut self, additional: usize) {
+        if self.recorded.is_none() {
+            self.output_mut().reserve(additional);
+        }
+    }
+
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut *self.output_mut())
+    }
+
+    /// Write the buffered output to a Python stream, as text or bytes, except
+    /// for its last `keep` bytes
+    pub fn flush_to(
+        &mut self,
+        py: Python,
+        stream: &Bound<'_, PyAny>,
+        dump_unicode: bool,
+        keep: usize,
+    ) -> PyResult<()> {
+        let mut buffer = lock_output(&self.output);
+        let mut split = buffer.len().saturating_sub(keep);
+        // Text goes out in whole characters
+        while dump_unicode && split > 0 && split < buffer.len() && buffer[split] & 0xC0 == 0x80 {
+            split -= 1;
+        }
+        if split == 0 {
+            return Ok(());
+        }
+        // Copied straight from the buffer into the object written
+        let output = if dump_unicode {
+            output_str(py, &buffer[..split])?.into_any()
+        } else {
+            PyBytes::new(py, &buffer[..split]).into_any()
+        };
+        buffer.drain(..split);
+        drop(buffer);
+        stream.call_method1("write", (output,))?;
+        Ok(())
+    }
+
+    pub fn dispose(&mut self) {
+        // Drop emitter first (releases borrow on sink)
         self.emitter = None;
     }
 }
@@ -93,6 +880,18 @@ impl Drop for EmitterWrapper {
     }
 }
 
+/// Output a dumper writes to its stream at a time while serializing a document
+const FLUSH_SIZE: usize = 64 * 1024;
+
+/// Bytes of indentation, indicators and line breaks counted for each node
+/// when estimating the length of a document
+const ESTIMATED_NODE_OVERHEAD: usize = 4;
+
+/// Longest scalar, in characters, a `compact_threshold` collection may hold
+const COMPACT_SCALAR_LEN: usize = 20;
+
+/// Dumper writing documents to one stream. It is used by one thread at a time;
+/// others calling it while a document is represented get a RuntimeError.
 #[pyclass(name = "_RSafeDumper", subclass)]
 pub struct RSafeDumper {
     // Emitter
@@ -101,24 +900,58 @@ pub struct RSafeDumper {
     dump_unicode: bool,
     // -1 = not opened, 0 = open, 1 = closed
     closed: i8,
+    /// Thread representing a document, across calls to Python overrides
+    exclusive: Exclusive,
 
     // Serializer config
     document_start_implicit: bool,
     document_end_implicit: bool,
+    // `%YAML` and `%TAG` directives written before each document
+    version: Option<VersionDirective>,
+    tags: Vec<TagDirective>,
 
     // Serializer state (reset per document)
     serialized_nodes: HashSet<usize>,
     anchors: HashMap<usize, Option<String>>,
+    // Mapping keys serialized in the stream so far, and the recorded comments
+    // to write around them, by their position among those keys
+    keys_serialized: usize,
+    key_comments: Vec<(usize, EntryComments)>,
     last_alias_id: i32,
 
     // Representer config
-    default_style: Option<char>,
     default_flow_style: Option<bool>,
-    sort_keys: bool,
 
     // Representer state (reset per represent() call)
     represented_objects: HashMap<usize, Arc<RepNode>>,
+    // Node objects for the same, when a subclass overrides representation
+    represented_nodes: HashMap<usize, PyNode>,
     object_keeper: Vec<Py<PyAny>>,
+    // Objects whose representation is under way, to catch one containing itself
+    in_progress: HashSet<usize>,
+    // Anchors of collections loaded with `roundtrip`, by node
+    recorded_anchors: HashMap<usize, String>,
+    // Comments of mapping entries loaded with `roundtrip`, by node and index
+    recorded_comments: HashMap<(usize, usize), EntryComments>,
+    // Nodes for None, bools, ints, floats and strings, so each distinct value
+    // is allocated once however often it repeats
+    scalar_cache: HashMap<ScalarKey, Arc<RepNode>>,
+    // Keys and indices leading to the object being represented, tracked only
+    // when style overrides or errors about sizes, NaNs and recursion need them
+    path: Vec<String>,
+    // Registered representers and implicit resolvers, snapshotted when a dump
+    // starts so concurrent registrations can't change dispatch halfway through
+    // a document
+    representers: registry::Snapshot<usize>,
+    yaml_object_tags: registry::Snapshot<usize>,
+    implicit_resolvers: Resolvers,
+    /// State of the module in the interpreter the dumper was made in
+    state: Py<ModuleState>,
+    /// What the implicit resolvers made of scalars written so far
+    resolved: ResolverCache,
+
+    // Behavior switches set at construction
+    options: DumperOptions,
 }
 
 #[pymethods]
@@ -128,8 +961,12 @@ impl RSafeDumper {
     #[pyo3(signature = (stream, default_style=None, default_flow_style=Some(false),
         canonical=None, indent=None, width=None, allow_unicode=None,
         line_break=None, encoding=None, explicit_start=None, explicit_end=None,
-        version=None, tags=None, sort_keys=false))]
-    #[allow(unused_variables)]
+        version=None, tags=None, sort_keys=None, third_party_datetimes=false,
+        timezone="preserve", styles=None, schema="1.1", max_items=None, allow_nan=true, document_end="auto",
+        final_newline=true, stringify_keys=false, dump_dataclasses=false,
+        dump_numpy=false, iterable_as_sequence=false, coerce_unknown="error", quote_ambiguous=false,
+        literal_multiline=false, aliases=true, default=None, compact_threshold=None,
+        set_style="block"))]
     fn new(
         py: Python,
         stream: Py<PyAny>,
@@ -145,68 +982,143 @@ impl RSafeDumper {
         explicit_end: Option<bool>,
         version: Option<(i32, i32)>,
         tags: Option<HashMap<String, String>>,
-        sort_keys: bool,
+        sort_keys: Option<&Bound<'_, PyAny>>,
+        third_party_datetimes: bool,
+        timezone: &str,
+        styles: Option<&Bound<'_, PyDict>>,
+        schema: &str,
+        max_items: Option<usize>,
+        allow_nan: bool,
+        document_end: &str,
+        final_newline: bool,
+        stringify_keys: bool,
+        dump_dataclasses: bool,
+        dump_numpy: bool,
+        iterable_as_sequence: bool,
+        coerce_unknown: &str,
+        quote_ambiguous: bool,
+        literal_multiline: bool,
+        aliases: bool,
+        default: Option<Py<PyAny>>,
+        compact_threshold: Option<usize>,
+        set_style: &str,
     ) -> PyResult<Self> {
-        let mut ew = EmitterWrapper::new();
-
-        // Configure emitter
-        let enc = match encoding {
-            None | Some("utf-8") | Some("utf8") => Encoding::Utf8,
-            Some("utf-16-le") | Some("utf-16le") => Encoding::Utf16Le,
-            Some("utf-16-be") | Some("utf-16be") => Encoding::Utf16Be,
-            Some(other) => {
-                return Err(exception::emitter_error(
-                    py,
-                    format!("unknown encoding: {other}"),
-                ));
-            }
+        let document_end = match explicit_end {
+            Some(true) => DocumentEnd::Always,
+            _ => DocumentEnd::from_name(document_end)?,
         };
-        ew.configure(enc);
-
-        if let Some(true) = canonical {
-            ew.emitter_mut().set_canonical(true);
-        }
-        if let Some(i) = indent {
-            ew.emitter_mut().set_indent(i);
-        }
-        if let Some(w) = width {
-            ew.emitter_mut().set_width(w);
-        }
-        if let Some(true) = allow_unicode {
-            ew.emitter_mut().set_unicode(true);
-        }
-        if let Some(lb) = line_break {
-            let brk = match lb {
-                "\n" => libyaml_safer::Break::Ln,
-                "\r" => libyaml_safer::Break::Cr,
-                "\r\n" => libyaml_safer::Break::CrLn,
-                _ => libyaml_safer::Break::Ln,
-            };
-            ew.emitter_mut().set_break(brk);
-        }
+        check_trimmed_encoding(
+            document_end,
+            final_newline,
+            encoding.and_then(parse_encoding).unwrap_or(Encoding::Utf8),
+        )?;
+        let ew = EmitterWrapper::with_options(
+            py,
+            encoding,
+            canonical,
+            indent,
+            width,
+            allow_unicode,
+            line_break,
+        )?;
 
         let dump_unicode = encoding.is_none();
 
+        // Sorted by handle, as pyyaml writes them
+        let mut tag_directives: Vec<TagDirective> = tags
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(handle, prefix)| TagDirective { handle, prefix })
+            .collect();
+        tag_directives.sort_by(|a, b| a.handle.cmp(&b.handle));
+
         let default_style_char = default_style.and_then(|s| s.chars().next());
 
+        let state = state::get(py)?;
+        let registries = &state.get().registries;
         Ok(RSafeDumper {
             emitter: ew,
             stream,
             dump_unicode,
             closed: -1,
+            exclusive: Exclusive::default(),
             document_start_implicit: !explicit_start.unwrap_or(false),
-            document_end_implicit: !explicit_end.unwrap_or(false),
+            document_end_implicit: document_end != DocumentEnd::Always,
+            version: version.map(|(major, minor)| VersionDirective { major, minor }),
+            tags: tag_directives,
             serialized_nodes: HashSet::new(),
             anchors: HashMap::new(),
+            keys_serialized: 0,
+            key_comments: Vec::new(),
             last_alias_id: 0,
-            default_style: default_style_char,
             default_flow_style,
-            sort_keys,
             represented_objects: HashMap::new(),
+            represented_nodes: HashMap::new(),
             object_keeper: Vec::new(),
+            in_progress: HashSet::new(),
+            recorded_anchors: HashMap::new(),
+            recorded_comments: HashMap::new(),
+            scalar_cache: HashMap::new(),
+            path: Vec::new(),
+            representers: registries.representers.snapshot(),
+            yaml_object_tags: registries.yaml_object_tags.snapshot(),
+            implicit_resolvers: registries.implicit_resolvers(),
+            state: state.clone().unbind(),
+            resolved: ResolverCache::default(),
+            options: DumperOptions {
+                third_party_datetimes,
+                timezone: TimezoneMode::from_name(timezone)?,
+                styles: match styles {
+                    Some(styles) => StyleOverride::from_dict(styles)?,
+                    None => Vec::new(),
+                },
+                default_style: default_style_char,
+                canonical: canonical.unwrap_or(false),
+                encoding: encoding.and_then(parse_encoding),
+                line_break: line_break
+                    .map_or(Break::Any, |lb| parse_line_break(lb).unwrap_or(Break::Ln)),
+                literal_multiline,
+                allow_unicode: allow_unicode.unwrap_or(false),
+                schema: Schema::from_name(schema)?,
+                quote_ambiguous,
+                max_items,
+                allow_nan,
+                document_end,
+                final_newline,
+                stringify_keys,
+                dump_dataclasses,
+                dump_numpy,
+                iterable_as_sequence,
+                aliases,
+                coerce_unknown: CoerceUnknown::from_name(coerce_unknown)?,
+                default,
+                sort_keys: match sort_keys {
+                    Some(sort_keys) => SortKeys::from_value(sort_keys)?,
+                    None => SortKeys::Unsorted,
+                },
+                compact_threshold,
+                set_style: SetStyle::from_name(set_style)?,
+            },
         })
     }
 
+    /// Resolve plain scalars matching `pattern` to `tag`. Resolvers are shared by
+    /// all loaders and dumpers, as with the module-level `add_implicit_resolver()`.
+    #[classmethod]
+    #[pyo3(signature = (tag, pattern, first=None))]
+    fn add_implicit_resolver(
+        cls: &Bound<'_, PyType>,
+        tag: &str,
+        pattern: &Bound<'_, PyAny>,
+        first: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let py = cls.py();
+        state::get(py)?
+            .get()
+            .registries
+            .add_implicit_resolver(py, tag, pattern, first)
+    }
+
     fn open(&mut self, py: Python) -> PyResult<()> {
         if self.closed != -1 {
             return Err(exception::serializer_error(
@@ -226,11 +1138,287 @@ impl RSafeDumper {
         Ok(())
     }
 
-    fn represent(&mut self, py: Python, data: Py<PyAny>) -> PyResult<()> {
-        let node = self.represent_data(py, data.bind(py))?;
-        self.serialize(py, &node)?;
-        self.represented_objects.clear();
-        self.object_keeper.clear();
+    fn represent(slf: &Bound<'_, Self>, data: Py<PyAny>) -> PyResult<()> {
+        let py = slf.py();
+        let _guard = slf.try_borrow()?.exclusive.enter("dumper")?;
+        {
+            slf.try_borrow_mut()?.snapshot_registries();
+        }
+        let node = if Self::has_overrides(slf)? {
+            Self::represent_overridden(slf, data.bind(py))
+        } else {
+            slf.try_borrow_mut()?.represent_data(py, data.bind(py))
+        };
+        let mut this = slf.try_borrow_mut()?;
+        let result = node.and_then(|node| this.serialize(py, &node));
+        this.clear_represented();
+        result
+    }
+
+    /// Represent any object as a node, dispatching on its type to the
+    /// `represent_*` method for it
+    #[pyo3(name = "represent_data")]
+    fn py_represent_data(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let py = slf.py();
+        let alias_key = if slf.call_method1("ignore_aliases", (data,))?.is_truthy()? {
+            None
+        } else {
+            let key = data.as_ptr() as usize;
+            let mut this = slf.try_borrow_mut()?;
+            if let Some(node) = this.represented_nodes.get(&key) {
+                return Ok(node.clone());
+            }
+            if !this.in_progress.insert(key) {
+                return Err(this.recursion_error(py));
+            }
+            this.object_keeper.push(data.clone().unbind());
+            Some(key)
+        };
+        let node = match Self::represent_method(slf, data)? {
+            Some(method) => returned_node(py, slf.call_method1(method, (data,))?, method)?,
+            None => {
+                let mut this = slf.try_borrow_mut()?;
+                // Native representation tracks recursion itself
+                if let Some(key) = alias_key {
+                    this.in_progress.remove(&key);
+                }
+                let rep = this.represent_data(py, data)?;
+                rep_to_node(py, &rep, &mut HashMap::new())?
+            }
+        };
+        if let Some(key) = alias_key {
+            let mut this = slf.try_borrow_mut()?;
+            this.in_progress.remove(&key);
+            this.represented_nodes.insert(key, node.clone());
+        }
+        Ok(node)
+    }
+
+    /// Whether `data` is written again in full wherever it repeats, instead of
+    /// through an anchor and aliases
+    #[pyo3(name = "ignore_aliases")]
+    fn py_ignore_aliases(&self, py: Python, data: &Bound<'_, PyAny>) -> bool {
+        self.ignore_aliases(py, data)
+    }
+
+    /// A scalar node, in the default style unless `style` is given
+    #[pyo3(name = "represent_scalar", signature = (tag, value, style=None))]
+    fn py_represent_scalar(
+        &self,
+        py: Python,
+        tag: String,
+        value: String,
+        style: Option<char>,
+    ) -> PyResult<PyNode> {
+        let style = style.or(self.options.default_style);
+        Ok(PyNode::Scalar(Py::new(
+            py,
+            PyScalarNode::new(tag, value, None, None, style),
+        )?))
+    }
+
+    /// A sequence node of the nodes representing each item of `sequence`
+    #[pyo3(name = "represent_sequence", signature = (tag, sequence, flow_style=None))]
+    fn py_represent_sequence(
+        slf: &Bound<'_, Self>,
+        tag: String,
+        sequence: &Bound<'_, PyAny>,
+        flow_style: Option<bool>,
+    ) -> PyResult<PyNode> {
+        let py = slf.py();
+        let mut value = Vec::new();
+        let mut best_style = true;
+        let mut short = true;
+        for (index, item) in sequence.try_iter()?.enumerate() {
+            let node = Self::represent_item(slf, index.to_string(), &item?)?;
+            best_style &= is_plain_node(py, &node);
+            short &= is_short_node(py, &node);
+            value.push(node);
+        }
+        let flow_style = match flow_style.or(recorded_flow_style(sequence)?) {
+            Some(flow_style) => Some(flow_style),
+            None => {
+                let slf = slf.try_borrow()?;
+                slf.choose_flow_style(best_style, slf.is_compact(value.len(), short))
+            }
+        };
+        Ok(PyNode::Sequence(Py::new(
+            py,
+            PySequenceNode::new(tag, value, None, None, flow_style),
+        )?))
+    }
+
+    /// A mapping node of the nodes representing each key and value of
+    /// `mapping` (a dict or a list of pairs), sorted by key when `sort_keys` is set
+    #[pyo3(name = "represent_mapping", signature = (tag, mapping, flow_style=None))]
+    fn py_represent_mapping(
+        slf: &Bound<'_, Self>,
+        tag: String,
+        mapping: &Bound<'_, PyAny>,
+        flow_style: Option<bool>,
+    ) -> PyResult<PyNode> {
+        let py = slf.py();
+        // Only real mappings are sorted; a list of pairs keeps its order
+        let is_mapping = mapping.hasattr("items")?;
+        let items = if is_mapping {
+            mapping.call_method0("items")?
+        } else {
+            mapping.clone()
+        };
+        let mut pairs = items
+            .try_iter()?
+            .map(|item| item?.extract::<(Py<PyAny>, Py<PyAny>)>())
+            .collect::<PyResult<Vec<_>>>()?;
+        if is_mapping {
+            let sort_keys = slf.try_borrow()?.options.sort_keys.clone();
+            sort_keys.sort(py, &mut pairs)?;
+        }
+        let mut value = Vec::with_capacity(pairs.len());
+        let mut best_style = true;
+        let mut short = true;
+        for (k, v) in &pairs {
+            // Keys are never styled by path, so represent them outside it
+            let path = std::mem::take(&mut slf.try_borrow_mut()?.path);
+            let key_node = slf.call_method1("represent_data", (k,));
+            slf.try_borrow_mut()?.path = path;
+            let mut key_node = returned_node(py, key_node?, "represent_data")?;
+            if let PyNode::Scalar(node) = &key_node
+                && slf.try_borrow()?.options.stringify_keys
+                && is_stringified_tag(&node.borrow(py).tag)
+            {
+                let node = node.borrow(py);
+                let str_node = PyScalarNode::new(
+                    crate::TAG_STR.to_string(),
+                    node.value.clone(),
+                    None,
+                    None,
+                    node.style,
+                );
+                drop(node);
+                key_node = PyNode::Scalar(Py::new(py, str_node)?);
+            }
+            let segment = match &key_node {
+                PyNode::Scalar(node) => node.borrow(py).value.clone(),
+                _ => "?".to_string(),
+            };
+            let val_node = Self::represent_item(slf, segment, v.bind(py))?;
+            best_style &= is_plain_node(py, &key_node) && is_plain_node(py, &val_node);
+            short &= is_short_node(py, &key_node) && is_short_node(py, &val_node);
+            value.push((key_node, val_node));
+        }
+        let flow_style = match flow_style.or(recorded_flow_style(mapping)?) {
+            Some(flow_style) => Some(flow_style),
+            None => {
+                let slf = slf.try_borrow()?;
+                slf.choose_flow_style(best_style, slf.is_compact(value.len(), short))
+            }
+        };
+        Ok(PyNode::Mapping(Py::new(
+            py,
+            PyMappingNode::new(tag, value, None, None, flow_style),
+        )?))
+    }
+
+    #[pyo3(name = "represent_none")]
+    #[allow(unused_variables)]
+    fn py_represent_none(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow_mut()?.represent_none();
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_bool")]
+    fn py_represent_bool(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow_mut()?.represent_bool(data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_int")]
+    fn py_represent_int(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow_mut()?.represent_int(data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_float")]
+    fn py_represent_float(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow_mut()?.represent_float(data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_str")]
+    fn py_represent_str(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow_mut()?.represent_str(data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_binary")]
+    fn py_represent_binary(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow()?.represent_binary(slf.py(), data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_date")]
+    fn py_represent_date(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow()?.represent_date(slf.py(), data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_datetime")]
+    fn py_represent_datetime(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let rep = slf.try_borrow()?.represent_datetime(slf.py(), data)?;
+        Self::scalar_via_python(slf, &rep)
+    }
+
+    #[pyo3(name = "represent_list")]
+    fn py_represent_list(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let py = slf.py();
+        slf.try_borrow()?.check_size(py, "sequence", data)?;
+        let node = slf.call_method1("represent_sequence", (crate::TAG_SEQ, data))?;
+        returned_node(py, node, "represent_sequence")
+    }
+
+    #[pyo3(name = "represent_dict")]
+    fn py_represent_dict(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let py = slf.py();
+        slf.try_borrow()?.check_size(py, "mapping", data)?;
+        let node = slf.call_method1("represent_mapping", (crate::TAG_MAP, data))?;
+        returned_node(py, node, "represent_mapping")
+    }
+
+    #[pyo3(name = "represent_set")]
+    fn py_represent_set(slf: &Bound<'_, Self>, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+        let py = slf.py();
+        let set_style = {
+            let slf = slf.try_borrow()?;
+            slf.check_size(py, "set", data)?;
+            slf.options.set_style
+        };
+        if set_style == SetStyle::Sequence {
+            let items = PyList::new(py, sorted_set_items(py, data)?)?;
+            let node = slf.call_method1("represent_sequence", (crate::TAG_SEQ, items))?;
+            return returned_node(py, node, "represent_sequence");
+        }
+        let value = PyDict::new(py);
+        for item in data.try_iter()? {
+            value.set_item(item?, py.None())?;
+        }
+        let flow_style = set_style == SetStyle::Flow;
+        let node = slf.call_method1("represent_mapping", (crate::TAG_SET, value, flow_style))?;
+        returned_node(py, node, "represent_mapping")
+    }
+
+    /// Serialize a node tree (e.g. from `compose()`) as a document
+    #[pyo3(name = "serialize")]
+    fn serialize_node_tree(&mut self, py: Python, node: PyNode) -> PyResult<()> {
+        self.check_opened(py)?;
+        let rep = self.node_to_rep(py, &node, &mut HashMap::new(), &mut HashSet::new())?;
+        self.serialize(py, &rep)
+    }
+
+    /// Serialize each node tree as its own document
+    fn serialize_all(&mut self, py: Python, nodes: Vec<PyNode>) -> PyResult<()> {
+        for node in nodes {
+            self.serialize_node_tree(py, node)?;
+        }
         Ok(())
     }
 
@@ -250,26 +1438,153 @@ impl RSafeDumper {
         self.closed = 1;
 
         // Flush output to stream
-        let output = self.emitter.take_output();
-        let stream = self.stream.bind(py);
-        if self.dump_unicode {
-            let s = String::from_utf8(output)
-                .map_err(|e| exception::emitter_error(py, format!("invalid utf8 output: {e}")))?;
-            stream.call_method1("write", (s,))?;
-        } else {
-            stream.call_method1("write", (PyBytes::new(py, &output),))?;
-        }
-        Ok(())
+        trim_output_end(&mut self.emitter.output_mut(), &self.options);
+        self.emitter
+            .flush_to(py, self.stream.bind(py), self.dump_unicode, 0)
     }
 
     fn dispose(&mut self) {
         self.emitter.dispose();
     }
+
+    /// Start a new stream written to `stream`, with the same settings. Tables
+    /// and buffers keep their allocations, which saves time when dumping many
+    /// small documents. Output not yet written by `close()` is dropped.
+    fn reset(&mut self, stream: Py<PyAny>) {
+        self.emitter.restart();
+        self.stream = stream;
+        self.clear_stream();
+    }
 }
 
 // ── Representer ──────────────────────────────────────────────────────────────
 
+/// Methods of `_RSafeDumper` which a Python subclass may override
+const OVERRIDABLE: &[&str] = &[
+    "ignore_aliases",
+    "represent_data",
+    "represent_scalar",
+    "represent_sequence",
+    "represent_mapping",
+    "represent_none",
+    "represent_bool",
+    "represent_int",
+    "represent_float",
+    "represent_str",
+    "represent_binary",
+    "represent_date",
+    "represent_datetime",
+    "represent_list",
+    "represent_dict",
+    "represent_set",
+];
+
 impl RSafeDumper {
+    /// Whether the dumper's class overrides any of the `OVERRIDABLE` methods
+    fn has_overrides(slf: &Bound<'_, Self>) -> PyResult<bool> {
+        let base = slf.py().get_type::<RSafeDumper>();
+        let class = slf.get_type();
+        if class.is(&base) {
+            return Ok(false);
+        }
+        for name in OVERRIDABLE {
+            if !class.getattr(name)?.is(&base.getattr(name)?) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Represent `data` through `represent_data()`, so the methods a subclass
+    /// overrides are called for every object in it
+    fn represent_overridden(
+        slf: &Bound<'_, Self>,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<Arc<RepNode>> {
+        let py = slf.py();
+        let node = returned_node(
+            py,
+            slf.call_method1("represent_data", (data,))?,
+            "represent_data",
+        )?;
+        slf.try_borrow()?
+            .node_to_rep(py, &node, &mut HashMap::new(), &mut HashSet::new())
+    }
+
+    /// The `represent_*` method for a builtin type, or None for objects always
+    /// represented natively: registered types, YAML objects, tagged values and
+    /// third-party datetimes
+    fn represent_method(
+        slf: &Bound<'_, Self>,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<Option<&'static str>> {
+        let py = slf.py();
+        let state = {
+            let this = slf.try_borrow()?;
+            if registry::find_representer(&this.representers, data).is_some()
+                || this
+                    .yaml_object_tags
+                    .contains_key(&(data.get_type().as_ptr() as usize))
+            {
+                return Ok(None);
+            }
+            this.state.clone_ref(py)
+        };
+        let state = state.get();
+        Ok(Some(if data.is_instance_of::<PyTaggedValue>() {
+            return Ok(None);
+        } else if data.is_none() {
+            "represent_none"
+        } else if data.is_instance_of::<PyBool>() {
+            "represent_bool"
+        } else if data.is_instance_of::<PyInt>() {
+            "represent_int"
+        } else if data.is_instance_of::<PyFloat>() {
+            "represent_float"
+        } else if data.is_instance_of::<PyString>() {
+            "represent_str"
+        } else if data.is_instance_of::<PyBytes>() {
+            "represent_binary"
+        } else if Self::is_datetime(state, py, data)? {
+            "represent_datetime"
+        } else if Self::is_date(state, py, data)? {
+            "represent_date"
+        } else if data.is_instance_of::<PyList>() || data.is_instance_of::<PyTuple>() {
+            "represent_list"
+        } else if data.is_instance_of::<PyDict>() {
+            "represent_dict"
+        } else if data.is_instance_of::<PySet>() || data.is_instance_of::<PyFrozenSet>() {
+            "represent_set"
+        } else {
+            return Ok(None);
+        }))
+    }
+
+    /// Represent `data` found under the key or index named by `segment`,
+    /// through `represent_data()`
+    fn represent_item(
+        slf: &Bound<'_, Self>,
+        segment: String,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<PyNode> {
+        slf.try_borrow_mut()?.path.push(segment);
+        let node = slf.call_method1("represent_data", (data,));
+        slf.try_borrow_mut()?.path.pop();
+        returned_node(slf.py(), node?, "represent_data")
+    }
+
+    /// Pass a natively represented scalar through `represent_scalar()`
+    fn scalar_via_python(slf: &Bound<'_, Self>, rep: &RepNode) -> PyResult<PyNode> {
+        let RepNode::Scalar {
+            tag, value, style, ..
+        } = rep
+        else {
+            unreachable!("builtin scalars are represented as scalar nodes")
+        };
+        let node = slf.call_method1("represent_scalar", (tag, value, *style))?;
+        returned_node(slf.py(), node, "represent_scalar")
+    }
+
     fn ignore_aliases(&self, _py: Python, data: &Bound<'_, PyAny>) -> bool {
         data.is_none()
             || (data.is_instance_of::<PyTuple>() && data.len().is_ok_and(|l| l == 0))
@@ -289,12 +1604,27 @@ impl RSafeDumper {
             if let Some(node) = self.represented_objects.get(&key) {
                 return Ok(Arc::clone(node));
             }
+            // Nodes are built bottom-up, so an object met again before its own
+            // node exists contains itself
+            if !self.in_progress.insert(key) {
+                return Err(self.recursion_error(py));
+            }
             self.object_keeper.push(data.clone().unbind());
             Some(key)
         };
 
         // Type dispatch (order matters: bool before int, datetime before date)
-        let node = if data.is_none() {
+        let node = if let Some(node) = self.represent_registered(py, data)? {
+            node
+        } else if let Some(tag) = self
+            .yaml_object_tags
+            .get(&(data.get_type().as_ptr() as usize))
+        {
+            let tag: String = tag.extract(py)?;
+            self.represent_yaml_object(py, data, tag)?
+        } else if let Ok(tagged) = data.downcast::<PyTaggedValue>() {
+            self.represent_tagged(py, tagged)?
+        } else if data.is_none() {
             self.represent_none()
         } else if data.is_instance_of::<PyBool>() {
             self.represent_bool(data)?
@@ -304,11 +1634,14 @@ impl RSafeDumper {
             self.represent_float(data)?
         } else if data.is_instance_of::<PyString>() {
             self.represent_str(data)?
-        } else if data.is_instance_of::<PyBytes>() {
+        } else if data.is_instance_of::<PyBytes>()
+            || data.is_instance_of::<PyByteArray>()
+            || data.is_instance_of::<PyMemoryView>()
+        {
             self.represent_binary(py, data)?
-        } else if Self::is_datetime(py, data)? {
+        } else if Self::is_datetime(self.state.get(), py, data)? {
             self.represent_datetime(py, data)?
-        } else if Self::is_date(py, data)? {
+        } else if Self::is_date(self.state.get(), py, data)? {
             self.represent_date(py, data)?
         } else if data.is_instance_of::<PyList>() || data.is_instance_of::<PyTuple>() {
             self.represent_list(py, data)?
@@ -316,6 +1649,34 @@ impl RSafeDumper {
             self.represent_dict(py, data)?
         } else if data.is_instance_of::<PySet>() || data.is_instance_of::<PyFrozenSet>() {
             self.represent_set(py, data)?
+        } else if let Some(node) = self.represent_datetime_like(py, data)? {
+            node
+        } else if data.hasattr("__yaml_repr__")? {
+            let replacement = data.call_method0("__yaml_repr__")?;
+            self.represent_replacement(py, data, &replacement, "__yaml_repr__")?
+        } else if self.options.dump_numpy && is_numpy_like(data)? {
+            // numpy scalars and arrays convert to Python numbers and lists
+            let converted = data.call_method0("tolist")?;
+            self.represent_replacement(py, data, &converted, "tolist")?
+        } else if let Some(fields) = self.dataclass_fields(py, data)? {
+            self.represent_dict(py, &fields)?
+        } else if self.options.iterable_as_sequence
+            && !data.is_instance_of::<PyType>()
+            && data.hasattr("__iter__")?
+        {
+            // Collected first, as generators have no length to check
+            let items = PyList::empty(py);
+            for item in data.try_iter()? {
+                items.append(item?)?;
+            }
+            self.represent_list(py, items.as_any())?
+        } else if let Some(default) = &self.options.default {
+            let replacement = default.bind(py).call1((data,))?;
+            self.represent_replacement(py, data, &replacement, "default")?
+        } else if self.options.coerce_unknown == CoerceUnknown::Str {
+            self.represent_str(data.str()?.as_any())?
+        } else if self.options.coerce_unknown == CoerceUnknown::Repr {
+            self.represent_str(data.repr()?.as_any())?
         } else {
             return Err(exception::representer_error(
                 py,
@@ -324,41 +1685,166 @@ impl RSafeDumper {
         };
 
         if let Some(key) = alias_key {
+            self.in_progress.remove(&key);
             self.represented_objects.insert(key, Arc::clone(&node));
         }
 
         Ok(node)
     }
 
-    fn represent_none(&self) -> Arc<RepNode> {
-        self.make_scalar(crate::TAG_NULL, "null", None)
+    fn represent_none(&mut self) -> Arc<RepNode> {
+        if self.options.default_style.is_none() {
+            return Arc::clone(&NULL_NODE);
+        }
+        self.cached_scalar(crate::TAG_NULL, "null".to_string(), None)
+    }
+
+    fn represent_bool(&mut self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        Ok(self.bool_node(data.extract()?))
     }
 
-    fn represent_bool(&self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
-        let b: bool = data.extract()?;
+    fn bool_node(&mut self, b: bool) -> Arc<RepNode> {
+        if self.options.default_style.is_none() {
+            return Arc::clone(if b { &TRUE_NODE } else { &FALSE_NODE });
+        }
         let value = if b { "true" } else { "false" };
-        Ok(self.make_scalar(crate::TAG_BOOL, value, None))
+        self.cached_scalar(crate::TAG_BOOL, value.to_string(), None)
     }
 
-    fn represent_int(&self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
-        let s = data.str()?.to_string();
-        Ok(self.make_scalar(crate::TAG_INT, &s, None))
+    fn represent_int(&mut self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        // Never call the object's own __str__, which an int subclass may
+        // override to raise or to return something that isn't a number
+        let s = match data.extract::<i64>() {
+            Ok(i) => i.to_string(),
+            Err(_) => {
+                let int_repr = self.state.get().int_repr.get_or_try_init(data.py(), || {
+                    PyResult::Ok(data.py().get_type::<PyInt>().getattr("__repr__")?.unbind())
+                })?;
+                int_repr.bind(data.py()).call1((data,))?.extract()?
+            }
+        };
+        Ok(self.cached_scalar(crate::TAG_INT, s, None))
+    }
+
+    fn represent_float(&mut self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        self.float_node(data.py(), data.extract()?)
     }
 
-    fn represent_float(&self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
-        let f: f64 = data.extract()?;
+    fn float_node(&mut self, py: Python, f: f64) -> PyResult<Arc<RepNode>> {
+        if !f.is_finite() && !self.options.allow_nan {
+            return Err(exception::representer_error(
+                py,
+                format!(
+                    "float {f} {} is not allowed with allow_nan=False",
+                    self.location()
+                ),
+            ));
+        }
         let value = format_float(f);
-        Ok(self.make_scalar(crate::TAG_FLOAT, &value, None))
+        Ok(self.cached_scalar(crate::TAG_FLOAT, value, None))
     }
 
-    fn represent_str(&self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+    fn represent_str(&mut self, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
         let s: String = data.extract()?;
-        Ok(self.make_scalar(crate::TAG_STR, &s, None))
+        let style = match self.options.styles.iter().find(|o| o.matches(&self.path)) {
+            Some(o) => Some(o.style),
+            None => recorded_scalar_style(data)?,
+        };
+        Ok(self.str_node(s, style))
+    }
+
+    /// A string scalar in `style`, or a literal block for multiline strings
+    /// with `literal_multiline`
+    fn str_node(&mut self, s: String, style: Option<char>) -> Arc<RepNode> {
+        let style =
+            style.or_else(|| (self.options.literal_multiline && s.contains('\n')).then_some('|'));
+        self.cached_scalar(crate::TAG_STR, s, style)
+    }
+
+    /// Represent a parsed JSON value as `represent_data()` would the objects
+    /// `json.loads()` makes of it
+    fn represent_json(&mut self, py: Python, value: &JsonValue) -> PyResult<Arc<RepNode>> {
+        Ok(match value {
+            JsonValue::Null => self.represent_none(),
+            JsonValue::Bool(b) => self.bool_node(*b),
+            JsonValue::Int(text) => self.cached_scalar(crate::TAG_INT, text.clone(), None),
+            JsonValue::Float(f) => self.float_node(py, *f)?,
+            JsonValue::String(s) => {
+                let style = self
+                    .options
+                    .styles
+                    .iter()
+                    .find(|o| o.matches(&self.path))
+                    .map(|o| o.style);
+                self.str_node(s.clone(), style)
+            }
+            JsonValue::Array(items) => {
+                self.check_len(py, "sequence", items.len())?;
+                let mut nodes = Vec::with_capacity(items.len());
+                let mut best_style = true;
+                let mut short = true;
+                for (i, item) in items.iter().enumerate() {
+                    self.path.push(i.to_string());
+                    let node = self.represent_json(py, item);
+                    self.path.pop();
+                    let node = node?;
+                    best_style &= is_plain_scalar(&node);
+                    short &= is_short_scalar(&node);
+                    nodes.push(node);
+                }
+                let compact = self.is_compact(nodes.len(), short);
+                Arc::new(RepNode::Sequence {
+                    tag: Cow::Borrowed(crate::TAG_SEQ),
+                    value: nodes,
+                    flow_style: self.choose_flow_style(best_style, compact),
+                })
+            }
+            JsonValue::Object(entries) => {
+                self.check_len(py, "mapping", entries.len())?;
+                let mut pairs = Vec::with_capacity(entries.len());
+                let mut best_style = true;
+                let mut short = true;
+                for (key, value) in entries {
+                    let key_node = self.str_node(key.clone(), None);
+                    self.path.push(key.clone());
+                    let value_node = self.represent_json(py, value);
+                    self.path.pop();
+                    let value_node = value_node?;
+                    best_style &= is_plain_scalar(&key_node) && is_plain_scalar(&value_node);
+                    short &= is_short_scalar(&key_node) && is_short_scalar(&value_node);
+                    pairs.push((key_node, value_node));
+                }
+                let compact = self.is_compact(pairs.len(), short);
+                Arc::new(RepNode::Mapping {
+                    tag: Cow::Borrowed(crate::TAG_MAP),
+                    value: pairs,
+                    flow_style: self.choose_flow_style(best_style, compact),
+                })
+            }
+        })
+    }
+
+    /// Represent the wrapped value, then give its node the wrapper's tag
+    fn represent_tagged(
+        &mut self,
+        py: Python,
+        tagged: &Bound<'_, PyTaggedValue>,
+    ) -> PyResult<Arc<RepNode>> {
+        let tagged = tagged.get();
+        let node = self.represent_data(py, tagged.value.bind(py))?;
+        Ok(Arc::new(node.with_tag(&tagged.tag)))
     }
 
-    fn represent_binary(&self, _py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
-        let bytes: &[u8] = data.downcast::<PyBytes>()?.as_bytes();
-        let encoded = base64::prelude::BASE64_STANDARD.encode(bytes);
+    fn represent_binary(&self, py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        let encoded = if let Ok(bytes) = data.downcast::<PyBytes>() {
+            base64::prelude::BASE64_STANDARD.encode(bytes.as_bytes())
+        } else if let Ok(bytes) = data.downcast::<PyByteArray>() {
+            base64::prelude::BASE64_STANDARD.encode(bytes.to_vec())
+        } else {
+            // Views may be strided or of wider items; bytes() lays them out flat
+            let bytes = py.get_type::<PyBytes>().call1((data,))?;
+            base64::prelude::BASE64_STANDARD.encode(bytes.downcast::<PyBytes>()?.as_bytes())
+        };
 
         // Add line breaks every 76 characters to match Python's base64.encodebytes()
         let mut result = String::new();
@@ -378,66 +1864,180 @@ impl RSafeDumper {
         Ok(self.make_scalar(crate::TAG_TIMESTAMP, &value, None))
     }
 
-    fn represent_datetime(&self, _py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
-        let value: String = data.call_method1("isoformat", (" ",))?.extract()?;
+    fn represent_datetime(&self, py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        let aware = !data.call_method0("utcoffset")?.is_none();
+        let value: String = match self.options.timezone {
+            TimezoneMode::Utc if aware => {
+                let utc = self.state.get().utc.get_or_try_init(py, || {
+                    PyResult::Ok(
+                        py.import("datetime")?
+                            .getattr("timezone")?
+                            .getattr("utc")?
+                            .unbind(),
+                    )
+                })?;
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("tzinfo", py.None())?;
+                let naive = data.call_method1("astimezone", (utc,))?.call_method(
+                    "replace",
+                    (),
+                    Some(&kwargs),
+                )?;
+                let value: String = naive.call_method1("isoformat", (" ",))?.extract()?;
+                value + "Z"
+            }
+            TimezoneMode::Naive if aware => {
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("tzinfo", py.None())?;
+                data.call_method("replace", (), Some(&kwargs))?
+                    .call_method1("isoformat", (" ",))?
+                    .extract()?
+            }
+            _ => data.call_method1("isoformat", (" ",))?.extract()?,
+        };
         Ok(self.make_scalar(crate::TAG_TIMESTAMP, &value, None))
     }
 
+    /// Represent a datetime-like object from a third-party library as a timestamp.
+    /// Only duck typing is used, so none of those libraries are ever imported.
+    fn represent_datetime_like(
+        &self,
+        py: Python,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<Option<Arc<RepNode>>> {
+        if !self.options.third_party_datetimes {
+            return Ok(None);
+        }
+        let converted = if data.get_type().name()? == "datetime64" {
+            // numpy.datetime64 converts to date/datetime at day..microsecond units
+            Some(data.call_method0("item")?)
+        } else if data.hasattr("to_pydatetime")? {
+            Some(data.call_method0("to_pydatetime")?)
+        } else if data.hasattr("datetime")? && data.hasattr("isoformat")? {
+            // arrow.Arrow wraps a stdlib datetime
+            Some(data.getattr("datetime")?)
+        } else {
+            None
+        };
+        if let Some(converted) = converted {
+            if Self::is_datetime(self.state.get(), py, &converted)? {
+                return Ok(Some(self.represent_datetime(py, &converted)?));
+            }
+            if Self::is_date(self.state.get(), py, &converted)? {
+                return Ok(Some(self.represent_date(py, &converted)?));
+            }
+            return Ok(None);
+        }
+        // Anything else implementing the date protocol
+        if data.hasattr("timetuple")? && data.hasattr("isoformat")? {
+            let value: String = data.call_method0("isoformat")?.extract()?;
+            return Ok(Some(self.make_scalar(crate::TAG_TIMESTAMP, &value, None)));
+        }
+        Ok(None)
+    }
+
     fn represent_list(&mut self, py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        self.check_size(py, "sequence", data)?;
         // Get iterator by calling __iter__
         let iter_obj = data.call_method0("__iter__")?;
         let mut items = Vec::new();
         let mut best_style = true;
+        let mut short = true;
         loop {
             match iter_obj.call_method0("__next__") {
                 Ok(item) => {
-                    let node = self.represent_data(py, &item)?;
+                    let node = self.represent_at(py, || items.len().to_string(), &item)?;
                     if !is_plain_scalar(&node) {
                         best_style = false;
                     }
+                    short &= is_short_scalar(&node);
                     items.push(node);
                 }
                 Err(e) if e.is_instance_of::<pyo3::exceptions::PyStopIteration>(py) => break,
                 Err(e) => return Err(e),
             }
         }
-        let flow_style = self.choose_flow_style(best_style);
-        Ok(Arc::new(RepNode::Sequence {
-            tag: crate::TAG_SEQ.to_string(),
+        let flow_style = match recorded_flow_style(data)? {
+            Some(flow_style) => Some(flow_style),
+            None => self.choose_flow_style(best_style, self.is_compact(items.len(), short)),
+        };
+        let node = Arc::new(RepNode::Sequence {
+            tag: Cow::Borrowed(crate::TAG_SEQ),
             value: items,
             flow_style,
-        }))
+        });
+        self.record_anchor(data, &node)?;
+        Ok(node)
     }
 
     fn represent_dict(&mut self, py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        self.check_size(py, "mapping", data)?;
         let dict = data.downcast::<PyDict>()?;
         let mut pairs: Vec<(Py<PyAny>, Py<PyAny>)> =
             dict.iter().map(|(k, v)| (k.unbind(), v.unbind())).collect();
 
-        if self.sort_keys {
-            // Sort by key, ignoring errors (matching pyyaml which wraps in try/except TypeError)
-            let _ = try_sort_pairs(py, &mut pairs);
-        }
+        self.options.sort_keys.sort(py, &mut pairs)?;
 
+        let comments = recorded_comments(data)?;
+        let mut entry_comments = Vec::new();
         let mut items = Vec::new();
         let mut best_style = true;
+        let mut short = true;
         for (k, v) in &pairs {
-            let key_node = self.represent_data(py, k.bind(py))?;
-            let val_node = self.represent_data(py, v.bind(py))?;
+            if let Some(comments) = &comments
+                && let Some(entry) = comments.get_item(k.bind(py))?
+            {
+                entry_comments.push((items.len(), entry.extract::<EntryComments>()?));
+            }
+            // Keys are never styled by path, so represent them outside it
+            let path = std::mem::take(&mut self.path);
+            let key_node = self.represent_data(py, k.bind(py));
+            self.path = path;
+            let key_node = self.stringify_key(key_node?);
+            let val_node = self.represent_at(py, || key_node.path_segment(), v.bind(py))?;
             if !is_plain_scalar(&key_node) || !is_plain_scalar(&val_node) {
                 best_style = false;
             }
+            short &= is_short_scalar(&key_node) && is_short_scalar(&val_node);
             items.push((key_node, val_node));
         }
-        let flow_style = self.choose_flow_style(best_style);
-        Ok(Arc::new(RepNode::Mapping {
-            tag: crate::TAG_MAP.to_string(),
+        let flow_style = match recorded_flow_style(data)? {
+            Some(flow_style) => Some(flow_style),
+            None => self.choose_flow_style(best_style, self.is_compact(items.len(), short)),
+        };
+        let node = Arc::new(RepNode::Mapping {
+            tag: Cow::Borrowed(crate::TAG_MAP),
             value: items,
             flow_style,
-        }))
+        });
+        self.record_anchor(data, &node)?;
+        let key = Arc::as_ptr(&node) as usize;
+        for (i, entry) in entry_comments {
+            self.recorded_comments.insert((key, i), entry);
+        }
+        Ok(node)
+    }
+
+    /// Keep the anchor a container loaded with `roundtrip` was defined with, to
+    /// write it under the same name
+    fn record_anchor(&mut self, data: &Bound<'_, PyAny>, node: &Arc<RepNode>) -> PyResult<()> {
+        if data.is_exact_instance_of::<PyList>() || data.is_exact_instance_of::<PyDict>() {
+            return Ok(());
+        }
+        if let Some(anchor) = data.getattr_opt("anchor")?
+            && let Some(anchor) = anchor.extract::<Option<String>>()?
+        {
+            self.recorded_anchors
+                .insert(Arc::as_ptr(node) as usize, anchor);
+        }
+        Ok(())
     }
 
     fn represent_set(&mut self, py: Python, data: &Bound<'_, PyAny>) -> PyResult<Arc<RepNode>> {
+        self.check_size(py, "set", data)?;
+        if self.options.set_style == SetStyle::Sequence {
+            return self.represent_set_items(py, data);
+        }
         // Get iterator by calling __iter__
         let iter_obj = data.call_method0("__iter__")?;
         let mut items = Vec::new();
@@ -445,7 +2045,7 @@ impl RSafeDumper {
             match iter_obj.call_method0("__next__") {
                 Ok(item) => {
                     let key_node = self.represent_data(py, &item)?;
-                    // Create a fresh null node for each value (don't share Arc to avoid aliases)
+                    // Null nodes come from the scalar cache, so are never aliased
                     let null_node = self.represent_none();
                     items.push((key_node, null_node));
                 }
@@ -454,62 +2054,487 @@ impl RSafeDumper {
             }
         }
         Ok(Arc::new(RepNode::Mapping {
-            tag: crate::TAG_SET.to_string(),
+            tag: Cow::Borrowed(crate::TAG_SET),
             value: items,
-            flow_style: Some(false),
+            flow_style: Some(self.options.set_style == SetStyle::Flow),
+        }))
+    }
+
+    /// Represent the set `data` as a sequence of its sorted items
+    fn represent_set_items(
+        &mut self,
+        py: Python,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<Arc<RepNode>> {
+        let mut nodes = Vec::new();
+        let mut best_style = true;
+        let mut short = true;
+        for item in sorted_set_items(py, data)? {
+            let node = self.represent_at(py, || nodes.len().to_string(), item.bind(py))?;
+            best_style &= is_plain_scalar(&node);
+            short &= is_short_scalar(&node);
+            nodes.push(node);
+        }
+        let compact = self.is_compact(nodes.len(), short);
+        Ok(Arc::new(RepNode::Sequence {
+            tag: Cow::Borrowed(crate::TAG_SEQ),
+            value: nodes,
+            flow_style: self.choose_flow_style(best_style, compact),
+        }))
+    }
+
+    /// Represent `data` with a registered representer for its type, if any
+    fn represent_registered(
+        &self,
+        py: Python,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<Option<Arc<RepNode>>> {
+        if self.representers.is_empty() {
+            return Ok(None);
+        }
+        let Some(representer) = registry::find_representer(&self.representers, data) else {
+            return Ok(None);
+        };
+        let node: PyNode = representer.call1((data,))?.extract().map_err(|_| {
+            exception::representer_error(
+                py,
+                format!(
+                    "representer for {:?} did not return a node",
+                    data.get_type()
+                        .name()
+                        .map(|n| n.to_string())
+                        .unwrap_or_default()
+                ),
+            )
+        })?;
+        self.node_to_rep(py, &node, &mut HashMap::new(), &mut HashSet::new())
+            .map(Some)
+    }
+
+    /// With `dump_dataclasses`, the fields of a dataclass or attrs instance as
+    /// a dict in definition order, or None for any other object
+    fn dataclass_fields<'py>(
+        &self,
+        py: Python<'py>,
+        data: &Bound<'py, PyAny>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        if !self.options.dump_dataclasses || data.is_instance_of::<PyType>() {
+            return Ok(None);
+        }
+        let class = data.get_type();
+        let names: Vec<Bound<'py, PyAny>> = if class.hasattr("__dataclass_fields__")? {
+            let fields = self
+                .state
+                .get()
+                .dataclass_fields
+                .import(py, "dataclasses", "fields")?;
+            fields
+                .call1((data,))?
+                .try_iter()?
+                .map(|field| field?.getattr("name"))
+                .collect::<PyResult<_>>()?
+        } else if let Some(attributes) = class.getattr_opt("__attrs_attrs__")? {
+            attributes
+                .try_iter()?
+                .map(|attribute| attribute?.getattr("name"))
+                .collect::<PyResult<_>>()?
+        } else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        for name in names {
+            dict.set_item(&name, data.getattr(name.downcast::<PyString>()?)?)?;
+        }
+        Ok(Some(dict.into_any()))
+    }
+
+    /// Represent what `__yaml_repr__()` or `default()` returned for `data`:
+    /// either a node, or another object to represent in its place
+    fn represent_replacement(
+        &mut self,
+        py: Python,
+        data: &Bound<'_, PyAny>,
+        replacement: &Bound<'_, PyAny>,
+        source: &str,
+    ) -> PyResult<Arc<RepNode>> {
+        if let Ok(node) = replacement.extract::<PyNode>() {
+            return self.node_to_rep(py, &node, &mut HashMap::new(), &mut HashSet::new());
+        }
+        if replacement.get_type().is(data.get_type()) {
+            return Err(exception::representer_error(
+                py,
+                format!(
+                    "{source}() returned another {:?} object",
+                    data.get_type().name()?
+                ),
+            ));
+        }
+        self.represent_data(py, replacement)
+    }
+
+    /// Represent an instance of a class registered with `add_yaml_object()`:
+    /// through its `to_yaml()` if it has one, otherwise as a mapping of its
+    /// state tagged `tag`
+    fn represent_yaml_object(
+        &mut self,
+        py: Python,
+        data: &Bound<'_, PyAny>,
+        tag: String,
+    ) -> PyResult<Arc<RepNode>> {
+        let class = data.get_type();
+        if class.hasattr("to_yaml")? {
+            let node: PyNode = class
+                .call_method1("to_yaml", (data,))?
+                .extract()
+                .map_err(|_| {
+                    exception::representer_error(
+                        py,
+                        format!(
+                            "to_yaml() of {:?} did not return a node",
+                            class.name().map(|n| n.to_string()).unwrap_or_default()
+                        ),
+                    )
+                })?;
+            return self.node_to_rep(py, &node, &mut HashMap::new(), &mut HashSet::new());
+        }
+        let state = if data.hasattr("__getstate__")? {
+            data.call_method0("__getstate__")?
+        } else {
+            data.getattr("__dict__")?
+        };
+        let state = if state.is_none() {
+            PyDict::new(py).into_any()
+        } else {
+            state
+        };
+        if !state.is_instance_of::<PyDict>() {
+            return Err(exception::representer_error(
+                py,
+                format!("cannot represent the state of an object: {state}"),
+            ));
+        }
+        let node = self.represent_dict(py, &state)?;
+        let RepNode::Mapping {
+            value, flow_style, ..
+        } = &*node
+        else {
+            unreachable!("dicts are represented as mappings")
+        };
+        let flow_style = match class.getattr("yaml_flow_style") {
+            Ok(style) if !style.is_none() => Some(style.extract()?),
+            _ => *flow_style,
+        };
+        Ok(Arc::new(RepNode::Mapping {
+            tag: Cow::Owned(tag),
+            value: value.clone(),
+            flow_style,
         }))
     }
 
     // ── Helpers ──
 
-    fn make_scalar(&self, tag: &str, value: &str, style: Option<char>) -> Arc<RepNode> {
-        let style = style.or(self.default_style);
+    /// With `stringify_keys`, a str node in place of a key node represented
+    /// as a null, bool, int or float, whatever the type of the key: numpy
+    /// scalars and objects with representers of their own are written as
+    /// strings too
+    fn stringify_key(&mut self, node: Arc<RepNode>) -> Arc<RepNode> {
+        if !self.options.stringify_keys {
+            return node;
+        }
+        match node.as_ref() {
+            RepNode::Scalar {
+                tag, value, style, ..
+            } if is_stringified_tag(tag) => {
+                self.cached_scalar(crate::TAG_STR, value.clone(), *style)
+            }
+            _ => node,
+        }
+    }
+
+    /// Fail before representing a collection with more items than `max_items`
+    fn check_size(&self, py: Python, kind: &str, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        if self.options.max_items.is_none() {
+            return Ok(());
+        }
+        self.check_len(py, kind, data.len()?)
+    }
+
+    /// Fail if a collection of `size` items has more than `max_items`
+    fn check_len(&self, py: Python, kind: &str, size: usize) -> PyResult<()> {
+        let Some(max_items) = self.options.max_items else {
+            return Ok(());
+        };
+        if size <= max_items {
+            return Ok(());
+        }
+        Err(exception::representer_error(
+            py,
+            format!(
+                "{kind} {} has {size} items, more than max_items={max_items}",
+                self.location()
+            ),
+        ))
+    }
+
+    fn recursion_error(&self, py: Python) -> PyErr {
+        let message = if self.options.aliases {
+            "cannot dump recursive data".to_string()
+        } else {
+            format!(
+                "cannot dump recursive data without aliases {}",
+                self.location()
+            )
+        };
+        exception::representer_error(py, message)
+    }
+
+    /// Where the object being represented is, for error messages
+    fn location(&self) -> String {
+        if self.path.is_empty() {
+            "at the top level".to_string()
+        } else {
+            format!("at '{}'", self.path.join("."))
+        }
+    }
+
+    /// Represent `data` found under the key or index named by `segment`
+    fn represent_at(
+        &mut self,
+        py: Python,
+        segment: impl FnOnce() -> String,
+        data: &Bound<'_, PyAny>,
+    ) -> PyResult<Arc<RepNode>> {
+        if self.options.styles.is_empty()
+            && self.options.max_items.is_none()
+            && self.options.allow_nan
+            && self.options.aliases
+            && self.options.compact_threshold.is_none()
+        {
+            return self.represent_data(py, data);
+        }
+        self.path.push(segment());
+        let node = self.represent_data(py, data);
+        self.path.pop();
+        node
+    }
+
+    fn make_scalar(&self, tag: &'static str, value: &str, style: Option<char>) -> Arc<RepNode> {
+        let style = style.or(self.options.default_style);
         Arc::new(RepNode::Scalar {
-            tag: tag.to_string(),
+            tag: Cow::Borrowed(tag),
             value: value.to_string(),
             style,
+            shared: false,
         })
     }
 
-    fn choose_flow_style(&self, best_style: bool) -> Option<bool> {
-        if let Some(dfs) = self.default_flow_style {
+    /// A scalar node for a value that is never aliased, reusing the node of an
+    /// equal value represented earlier in the document
+    fn cached_scalar(
+        &mut self,
+        tag: &'static str,
+        value: String,
+        style: Option<char>,
+    ) -> Arc<RepNode> {
+        let style = style.or(self.options.default_style);
+        let key = (tag, style, value);
+        if let Some(node) = self.scalar_cache.get(&key) {
+            return Arc::clone(node);
+        }
+        let node = Arc::new(RepNode::Scalar {
+            tag: Cow::Borrowed(tag),
+            value: key.2.clone(),
+            style,
+            shared: true,
+        });
+        self.scalar_cache.insert(key, Arc::clone(&node));
+        node
+    }
+
+    /// Whether a collection of `len` items, all short plain scalars when
+    /// `short` is set, is small enough for `compact_threshold`. The document
+    /// root stays in block style.
+    fn is_compact(&self, len: usize, short: bool) -> bool {
+        short
+            && !self.path.is_empty()
+            && self
+                .options
+                .compact_threshold
+                .is_some_and(|threshold| len < threshold)
+    }
+
+    /// Flow style of a collection whose items are all plain scalars when
+    /// `best_style` is set, and which `compact_threshold` keeps in flow style
+    /// when `compact` is set
+    fn choose_flow_style(&self, best_style: bool, compact: bool) -> Option<bool> {
+        if compact {
+            Some(true)
+        } else if let Some(dfs) = self.default_flow_style {
             Some(dfs)
         } else {
             Some(best_style)
         }
     }
 
-    fn is_datetime(py: Python, data: &Bound<'_, PyAny>) -> PyResult<bool> {
-        let datetime_mod = py.import("datetime")?;
-        let datetime_cls = datetime_mod.getattr("datetime")?;
-        data.is_instance(&datetime_cls)
+    /// Whether `data` is a `datetime.datetime`, including subclasses such as
+    /// pandas' `Timestamp` and freezegun's `FakeDatetime`
+    fn is_datetime(state: &ModuleState, py: Python, data: &Bound<'_, PyAny>) -> PyResult<bool> {
+        data.is_instance(state.datetime_type.import(py, "datetime", "datetime")?)
+    }
+
+    /// Whether `data` is a `datetime.date` (or datetime), including subclasses
+    fn is_date(state: &ModuleState, py: Python, data: &Bound<'_, PyAny>) -> PyResult<bool> {
+        data.is_instance(state.date_type.import(py, "datetime", "date")?)
+    }
+}
+
+// ── Serializer ───────────────────────────────────────────────────────────────
+
+impl RSafeDumper {
+    fn check_opened(&self, py: Python) -> PyResult<()> {
+        match self.closed {
+            -1 => Err(exception::serializer_error(
+                py,
+                "serializer is not opened".to_string(),
+            )),
+            1 => Err(exception::serializer_error(
+                py,
+                "serializer is closed".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Convert a Python node tree into representation nodes.
+    ///
+    /// Nodes shared by identity map to the same `Arc` so they are emitted as aliases;
+    /// recursive node graphs cannot be expressed this way and are rejected.
+    fn node_to_rep(
+        &self,
+        py: Python,
+        node: &PyNode,
+        converted: &mut HashMap<usize, Arc<RepNode>>,
+        in_progress: &mut HashSet<usize>,
+    ) -> PyResult<Arc<RepNode>> {
+        let key = node.as_ptr() as usize;
+        if let Some(rep) = converted.get(&key) {
+            return Ok(Arc::clone(rep));
+        }
+        if !in_progress.insert(key) {
+            return Err(exception::serializer_error(
+                py,
+                "cannot serialize a recursive node graph".to_string(),
+            ));
+        }
+        let rep = match node {
+            PyNode::Scalar(node) => {
+                let node = node.borrow(py);
+                Arc::new(RepNode::Scalar {
+                    tag: Cow::Owned(node.tag.clone()),
+                    value: node.value.clone(),
+                    style: node.style,
+                    shared: false,
+                })
+            }
+            PyNode::Sequence(node) => {
+                let node = node.borrow(py);
+                let mut value = Vec::with_capacity(node.value.len());
+                for item in &node.value {
+                    value.push(self.node_to_rep(py, item, converted, in_progress)?);
+                }
+                Arc::new(RepNode::Sequence {
+                    tag: Cow::Owned(node.tag.clone()),
+                    value,
+                    flow_style: node.flow_style,
+                })
+            }
+            PyNode::Mapping(node) => {
+                let node = node.borrow(py);
+                let mut value = Vec::with_capacity(node.value.len());
+                for (k, v) in &node.value {
+                    value.push((
+                        self.node_to_rep(py, k, converted, in_progress)?,
+                        self.node_to_rep(py, v, converted, in_progress)?,
+                    ));
+                }
+                Arc::new(RepNode::Mapping {
+                    tag: Cow::Owned(node.tag.clone()),
+                    value,
+                    flow_style: node.flow_style,
+                })
+            }
+        };
+        in_progress.remove(&key);
+        converted.insert(key, Arc::clone(&rep));
+        Ok(rep)
+    }
+
+    /// Whether a scalar tagged `tag` can be written without its tag when plain,
+    /// and when quoted
+    fn implicit_flags(&self, py: Python, tag: &str, value: &str) -> PyResult<(bool, bool)> {
+        let detected_tag = self.resolved.resolve(
+            py,
+            value,
+            true,
+            self.options.schema,
+            &self.implicit_resolvers,
+        )?;
+        let default_tag = resolver::resolve_scalar_tag(value, false, self.options.schema);
+        let plain_implicit = detected_tag == Some(tag)
+            && !(self.options.quote_ambiguous
+                && tag == resolver::DEFAULT_SCALAR_TAG
+                && is_ambiguous(value));
+        Ok((plain_implicit, default_tag == Some(tag)))
+    }
+
+    /// Forget the objects of the last document, so the next can't alias them
+    /// Forget the stream dumped so far, keeping the tables' allocations
+    fn clear_stream(&mut self) {
+        self.closed = -1;
+        self.serialized_nodes.clear();
+        self.anchors.clear();
+        self.keys_serialized = 0;
+        self.key_comments.clear();
+        self.last_alias_id = 0;
+        self.clear_represented();
     }
 
-    fn is_date(py: Python, data: &Bound<'_, PyAny>) -> PyResult<bool> {
-        let datetime_mod = py.import("datetime")?;
-        let date_cls = datetime_mod.getattr("date")?;
-        data.is_instance(&date_cls)
+    fn clear_represented(&mut self) {
+        self.represented_objects.clear();
+        self.represented_nodes.clear();
+        self.object_keeper.clear();
+        self.in_progress.clear();
+        self.recorded_anchors.clear();
+        self.recorded_comments.clear();
+        self.scalar_cache.clear();
+        self.path.clear();
     }
-}
-
-// ── Serializer ───────────────────────────────────────────────────────────────
 
-impl RSafeDumper {
     fn serialize(&mut self, py: Python, node: &Arc<RepNode>) -> PyResult<()> {
         self.emitter
             .emit(Event::document_start(
-                None,
-                &[],
+                self.version,
+                &self.tags,
                 self.document_start_implicit,
             ))
             .map_err(|e| exception::emitter_error(py, e))?;
 
         self.anchor_node(node);
+        // Streamed output is written out every FLUSH_SIZE bytes, so needs no
+        // more room than that
+        let estimate = node.estimated_len(&mut HashSet::new());
+        if self.stream.is_none(py) {
+            self.emitter.reserve(estimate);
+        } else {
+            self.emitter
+                .reserve(estimate.min(FLUSH_SIZE + TRIMMED_END_SIZE));
+        }
         self.serialize_node(py, node)?;
 
         self.emitter
             .emit(Event::document_end(self.document_end_implicit))
             .map_err(|e| exception::emitter_error(py, e))?;
+        self.flush_output(py, 0)?;
 
         // Reset serializer state
         self.serialized_nodes.clear();
@@ -519,15 +2544,26 @@ impl RSafeDumper {
     }
 
     fn anchor_node(&mut self, node: &Arc<RepNode>) {
+        if !self.options.aliases || matches!(node.as_ref(), RepNode::Scalar { shared: true, .. }) {
+            return;
+        }
         let key = Arc::as_ptr(node) as usize;
         if let Some(anchor) = self.anchors.get_mut(&key) {
             // Seen before with None → assign anchor name
             if anchor.is_none() {
-                self.last_alias_id += 1;
-                *anchor = Some(format!("id{:03}", self.last_alias_id));
+                // Generated names steer clear of the recorded ones
+                *anchor = loop {
+                    self.last_alias_id += 1;
+                    let name = format!("id{:03}", self.last_alias_id);
+                    if !self.recorded_anchors.values().any(|a| *a == name) {
+                        break Some(name);
+                    }
+                };
             }
         } else {
-            self.anchors.insert(key, None);
+            // Recorded anchors are written even if nothing aliases them
+            let recorded = self.recorded_anchors.get(&key).cloned();
+            self.anchors.insert(key, recorded);
             match node.as_ref() {
                 RepNode::Sequence { value, .. } => {
                     for item in value {
@@ -545,11 +2581,31 @@ impl RSafeDumper {
         }
     }
 
+    /// Write the output emitted so far to the stream once there is at least
+    /// `min_size` of it, so large dumps don't build up in memory. The end of
+    /// the output is kept back for `close()` to trim.
+    fn flush_output(&mut self, py: Python, min_size: usize) -> PyResult<()> {
+        // Dumpers returning their output have no stream
+        if self.stream.is_none(py) || self.emitter.output_mut().len() < min_size {
+            return Ok(());
+        }
+        self.emitter.flush_to(
+            py,
+            self.stream.bind(py),
+            self.dump_unicode,
+            TRIMMED_END_SIZE,
+        )
+    }
+
     fn serialize_node(&mut self, py: Python, node: &Arc<RepNode>) -> PyResult<()> {
+        self.flush_output(py, FLUSH_SIZE)?;
         let key = Arc::as_ptr(node) as usize;
         let anchor = self.anchors.get(&key).cloned().flatten();
 
-        if self.serialized_nodes.contains(&key) {
+        // Without aliases shared nodes are serialized again each time
+        let shared =
+            !self.options.aliases || matches!(node.as_ref(), RepNode::Scalar { shared: true, .. });
+        if !shared && self.serialized_nodes.contains(&key) {
             // Emit alias
             let anchor_str = anchor.as_deref().unwrap_or("");
             self.emitter
@@ -557,16 +2613,17 @@ impl RSafeDumper {
                 .map_err(|e| exception::emitter_error(py, e))?;
             return Ok(());
         }
-        self.serialized_nodes.insert(key);
+        if !shared {
+            self.serialized_nodes.insert(key);
+        }
 
         let anchor_ref = anchor.as_deref();
 
         match node.as_ref() {
-            RepNode::Scalar { tag, value, style } => {
-                let detected_tag = resolver::resolve_scalar_tag(value, true);
-                let default_tag = resolver::resolve_scalar_tag(value, false);
-                let plain_implicit = tag == detected_tag;
-                let quoted_implicit = tag == default_tag;
+            RepNode::Scalar {
+                tag, value, style, ..
+            } => {
+                let (plain_implicit, quoted_implicit) = self.implicit_flags(py, tag, value)?;
                 let scalar_style = char_to_scalar_style(*style);
 
                 self.emitter
@@ -620,7 +2677,12 @@ impl RSafeDumper {
                 self.emitter
                     .emit(Event::mapping_start(anchor_ref, Some(tag), implicit, style))
                     .map_err(|e| exception::emitter_error(py, e))?;
-                for (k, v) in value {
+                for (i, (k, v)) in value.iter().enumerate() {
+                    if let Some(comments) = self.recorded_comments.get(&(key, i)) {
+                        self.key_comments
+                            .push((self.keys_serialized, comments.clone()));
+                    }
+                    self.keys_serialized += 1;
                     self.serialize_node(py, k)?;
                     self.serialize_node(py, v)?;
                 }
@@ -635,11 +2697,158 @@ impl RSafeDumper {
 
 // ── Free helpers ─────────────────────────────────────────────────────────────
 
+/// Comments recorded around the entries of a mapping loaded with `roundtrip`
+fn recorded_comments<'py>(data: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    if data.is_exact_instance_of::<PyDict>() {
+        return Ok(None);
+    }
+    match data.getattr_opt("comments")? {
+        Some(comments) if !comments.is_none() => Ok(Some(comments.downcast_into()?)),
+        _ => Ok(None),
+    }
+}
+
+/// Style recorded on a string loaded with `roundtrip`, if any
+fn recorded_scalar_style(data: &Bound<'_, PyAny>) -> PyResult<Option<char>> {
+    if data.is_exact_instance_of::<PyString>() {
+        return Ok(None);
+    }
+    match data.getattr_opt("style")? {
+        Some(style) => style.extract(),
+        None => Ok(None),
+    }
+}
+
+/// Flow style recorded on a container loaded with `preserve_flow_style`, if any
+fn recorded_flow_style(data: &Bound<'_, PyAny>) -> PyResult<Option<bool>> {
+    if data.is_exact_instance_of::<PyList>() || data.is_exact_instance_of::<PyDict>() {
+        return Ok(None);
+    }
+    match data.getattr_opt("flow_style")? {
+        Some(flow_style) => flow_style.extract(),
+        None => Ok(None),
+    }
+}
+
 fn is_plain_scalar(node: &Arc<RepNode>) -> bool {
     matches!(node.as_ref(), RepNode::Scalar { style: None, .. })
 }
 
-fn char_to_scalar_style(style: Option<char>) -> ScalarStyle {
+/// Whether `node` is a plain scalar short enough to sit in a compact flow
+/// collection
+fn is_short_scalar(node: &Arc<RepNode>) -> bool {
+    matches!(node.as_ref(), RepNode::Scalar { style: None, value, .. } if is_short(value))
+}
+
+/// Whether `value` fits a compact flow collection: at most
+/// `COMPACT_SCALAR_LEN` characters on one line
+fn is_short(value: &str) -> bool {
+    !value.contains(['\n', '\r']) && value.chars().count() <= COMPACT_SCALAR_LEN
+}
+
+/// Whether `data` looks like a numpy scalar or array. Only duck typing is
+/// used, so numpy is never imported.
+fn is_numpy_like(data: &Bound<'_, PyAny>) -> PyResult<bool> {
+    Ok(!data.is_instance_of::<PyType>()
+        && data.hasattr("__array__")?
+        && data.hasattr("dtype")?
+        && data.hasattr("tolist")?)
+}
+
+/// Whether a YAML 1.1 or 1.2 core schema parser would read plain `value` as
+/// something other than a string
+fn is_ambiguous(value: &str) -> bool {
+    [Schema::Yaml11, Schema::Core].into_iter().any(|schema| {
+        resolver::resolve_scalar_tag(value, true, schema) != Some(resolver::DEFAULT_SCALAR_TAG)
+    })
+}
+
+/// Whether `stringify_keys` writes keys represented with `tag` as strings
+fn is_stringified_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        crate::TAG_NULL | crate::TAG_BOOL | crate::TAG_INT | crate::TAG_FLOAT
+    )
+}
+
+fn is_plain_node(py: Python, node: &PyNode) -> bool {
+    matches!(node, PyNode::Scalar(node) if node.borrow(py).style.is_none())
+}
+
+/// Whether `node` is a short plain scalar node, as `is_short_scalar()`
+fn is_short_node(py: Python, node: &PyNode) -> bool {
+    matches!(node, PyNode::Scalar(node) if {
+        let node = node.borrow(py);
+        node.style.is_none() && is_short(&node.value)
+    })
+}
+
+/// Extract the node returned by a `represent_*` method
+fn returned_node(py: Python, node: Bound<'_, PyAny>, method: &str) -> PyResult<PyNode> {
+    node.extract()
+        .map_err(|_| exception::representer_error(py, format!("{method}() did not return a node")))
+}
+
+/// A node object for a natively represented node. Shared collections stay
+/// shared; cached scalars get a node per use, so they are never anchored.
+fn rep_to_node(
+    py: Python,
+    rep: &Arc<RepNode>,
+    converted: &mut HashMap<usize, PyNode>,
+) -> PyResult<PyNode> {
+    let key = Arc::as_ptr(rep) as usize;
+    if let Some(node) = converted.get(&key) {
+        return Ok(node.clone());
+    }
+    let node = match rep.as_ref() {
+        RepNode::Scalar {
+            tag,
+            value,
+            style,
+            shared,
+        } => {
+            let node = PyScalarNode::new(tag.to_string(), value.clone(), None, None, *style);
+            let node = PyNode::Scalar(Py::new(py, node)?);
+            if *shared {
+                return Ok(node);
+            }
+            node
+        }
+        RepNode::Sequence {
+            tag,
+            value,
+            flow_style,
+        } => {
+            let value = value
+                .iter()
+                .map(|item| rep_to_node(py, item, converted))
+                .collect::<PyResult<_>>()?;
+            let node = PySequenceNode::new(tag.to_string(), value, None, None, *flow_style);
+            PyNode::Sequence(Py::new(py, node)?)
+        }
+        RepNode::Mapping {
+            tag,
+            value,
+            flow_style,
+        } => {
+            let value = value
+                .iter()
+                .map(|(k, v)| {
+                    Ok((
+                        rep_to_node(py, k, converted)?,
+                        rep_to_node(py, v, converted)?,
+                    ))
+                })
+                .collect::<PyResult<_>>()?;
+            let node = PyMappingNode::new(tag.to_string(), value, None, None, *flow_style);
+            PyNode::Mapping(Py::new(py, node)?)
+        }
+    };
+    converted.insert(key, node.clone());
+    Ok(node)
+}
+
+pub fn char_to_scalar_style(style: Option<char>) -> ScalarStyle {
     match style {
         None => ScalarStyle::Any,
         Some('\'') => ScalarStyle::SingleQuoted,
@@ -663,91 +2872,785 @@ fn format_float(f: f64) -> String {
         }
         .to_string();
     }
-    // Use Python's repr-like formatting
-    let mut value = format!("{}", f);
-    // Ensure lower case for scientific notation
-    value = value.to_lowercase();
-    // If there's no decimal point but there's an 'e', add '.0' before it
-    if !value.contains('.') && value.contains('e') {
-        value = value.replacen('e', ".0e", 1);
-    }
-    // If there's no decimal point and no 'e', it's an integer-looking float
-    // Python repr would show e.g. "1.0", but Rust format! shows "1" for 1.0f64
-    // Actually Rust shows "1" only for integers; for f64 it shows e.g. "1.5"
-    // But for whole numbers like 1.0, Rust shows "1" with {} formatter
-    if !value.contains('.') && !value.contains('e') {
-        value.push_str(".0");
-    }
-    value
-}
-
-/// Try to sort (key, value) pairs by key. Silently fails on TypeError (matching pyyaml).
-fn try_sort_pairs(py: Python, pairs: &mut [(Py<PyAny>, Py<PyAny>)]) -> PyResult<()> {
-    // Use Python's comparison to sort keys
-    pairs.sort_by(|a, b| {
-        a.0.bind(py)
-            .lt(b.0.bind(py))
-            .and_then(|lt| {
-                if lt {
-                    Ok(std::cmp::Ordering::Less)
-                } else {
-                    a.0.bind(py).gt(b.0.bind(py)).map(|gt| {
-                        if gt {
-                            std::cmp::Ordering::Greater
-                        } else {
-                            std::cmp::Ordering::Equal
-                        }
-                    })
-                }
-            })
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-    Ok(())
+    // Shortest digits that read back as `f`, laid out as Python's repr lays
+    // them out: positional for exponents from -4 to 15, scientific otherwise
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("floats format with an exponent");
+    let exponent: i32 = exponent.parse().expect("exponents are integers");
+    let digits = mantissa.replace('.', "");
+    let sign = if f.is_sign_negative() { "-" } else { "" };
+    if (-4..16).contains(&exponent) {
+        let point = exponent + 1;
+        if point <= 0 {
+            let zeros = "0".repeat(point.unsigned_abs() as usize);
+            return format!("{sign}0.{zeros}{digits}");
+        }
+        let point = point as usize;
+        if digits.len() > point {
+            return format!("{sign}{}.{}", &digits[..point], &digits[point..]);
+        }
+        let zeros = "0".repeat(point - digits.len());
+        return format!("{sign}{digits}{zeros}.0");
+    }
+    // YAML 1.1 floats need a fraction, so "1e+16" is written "1.0e+16", as
+    // pyyaml writes it
+    let fraction = if digits.len() > 1 { &digits[1..] } else { "0" };
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+    format!(
+        "{sign}{}.{fraction}e{exponent_sign}{:02}",
+        &digits[..1],
+        exponent.unsigned_abs()
+    )
 }
 
 // ── Fast-path for dumps() ────────────────────────────────────────────────────
 
 /// Dump a Python object to a YAML string, bypassing the pyyaml stream protocol.
-pub fn dumps_to_string(py: Python, obj: &Bound<'_, PyAny>) -> PyResult<String> {
-    let mut ew = EmitterWrapper::new();
-    ew.configure(Encoding::Utf8);
-
-    let mut dumper = RSafeDumper {
-        emitter: ew,
-        stream: py.None(),
-        dump_unicode: true,
-        closed: -1,
-        document_start_implicit: true,
-        document_end_implicit: true,
-        serialized_nodes: HashSet::new(),
-        anchors: HashMap::new(),
-        last_alias_id: 0,
-        default_style: None,
-        default_flow_style: Some(false),
-        sort_keys: false,
-        represented_objects: HashMap::new(),
-        object_keeper: Vec::new(),
+pub fn dumps(py: Python, obj: &Bound<'_, PyAny>, options: DumperOptions) -> PyResult<Py<PyAny>> {
+    let encoded = options.encoding.is_some();
+    let output = RSafeDumper::for_string(py, options)?.emit_stream_to_bytes(py, |dumper| {
+        let node = dumper.represent_data(py, obj)?;
+        dumper.serialize(py, &node)
+    })?;
+    output_object(py, output, encoded)
+}
+
+/// Dump every object `objs` yields as its own document of one stream
+pub fn dumps_all(
+    py: Python,
+    objs: &Bound<'_, PyAny>,
+    options: DumperOptions,
+) -> PyResult<Py<PyAny>> {
+    let encoded = options.encoding.is_some();
+    let output = RSafeDumper::for_string(py, options)?.emit_stream_to_bytes(py, |dumper| {
+        for obj in objs.try_iter()? {
+            let node = dumper.represent_data(py, &obj?)?;
+            dumper.serialize(py, &node)?;
+            dumper.clear_represented();
+        }
+        Ok(())
+    })?;
+    output_object(py, output, encoded)
+}
+
+/// Dump a parsed JSON value as a YAML document, with no Python object made
+/// for it
+pub fn from_json(py: Python, value: &JsonValue, options: DumperOptions) -> PyResult<Py<PyAny>> {
+    let encoded = options.encoding.is_some();
+    let output = RSafeDumper::for_string(py, options)?.emit_stream_to_bytes(py, |dumper| {
+        let node = dumper.represent_json(py, value)?;
+        dumper.serialize(py, &node)
+    })?;
+    output_object(py, output, encoded)
+}
+
+/// Dump output as bytes when an encoding was asked for, else as str
+fn output_object(py: Python, output: Vec<u8>, encoded: bool) -> PyResult<Py<PyAny>> {
+    if encoded {
+        return Ok(PyBytes::new(py, &output).into_any().unbind());
+    }
+    Ok(output_str(py, &output)?.into_any().unbind())
+}
+
+/// UTF-8 output as a str, checked by Python as it copies it in rather than
+/// in a pass of its own beforehand
+fn output_str<'py>(py: Python<'py>, output: &[u8]) -> PyResult<Bound<'py, PyString>> {
+    let len = pyo3::ffi::Py_ssize_t::try_from(output.len())
+        .map_err(|_| exception::emitter_error(py, "output too large".to_string()))?;
+    // SAFETY: the pointer and length describe `output`, which outlives the
+    // call; PyUnicode_DecodeUTF8 returns a new reference to a str, or null
+    // with an exception set
+    let decoded = unsafe {
+        Bound::from_owned_ptr_or_err(
+            py,
+            pyo3::ffi::PyUnicode_DecodeUTF8(output.as_ptr().cast(), len, std::ptr::null()),
+        )
     };
+    match decoded {
+        // SAFETY: PyUnicode_DecodeUTF8 only returns str objects
+        Ok(decoded) => Ok(unsafe { decoded.downcast_into_unchecked() }),
+        Err(e) => Err(exception::emitter_error(
+            py,
+            format!("invalid utf8 output: {e}"),
+        )),
+    }
+}
+
+/// Dump each object to its own string. Objects are represented one after the
+/// other, as that needs the GIL; their events are then emitted as text on
+/// worker threads with the GIL released.
+pub fn dumps_many(
+    py: Python,
+    objs: &[Bound<'_, PyAny>],
+    max_workers: Option<usize>,
+    options: DumperOptions,
+) -> PyResult<Vec<Py<PyAny>>> {
+    if max_workers == Some(0) {
+        return Err(PyValueError::new_err("max_workers must be at least 1"));
+    }
+    let mut streams = Vec::with_capacity(objs.len());
+    for obj in objs {
+        let mut dumper = RSafeDumper::for_string(py, options.clone())?;
+        dumper.emitter.record();
+        let events = dumper.emit_stream_events(py, |dumper| {
+            let node = dumper.represent_data(py, obj)?;
+            dumper.serialize(py, &node)
+        })?;
+        streams.push(std::sync::Mutex::new((events, dumper.key_comments)));
+    }
+    let workers = max_workers
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        })
+        .min(streams.len());
+    let outputs = py.detach(|| {
+        let next = std::sync::atomic::AtomicUsize::new(0);
+        let mut outputs: Vec<(usize, Result<Vec<u8>, String>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            let Some(stream) = streams.get(i) else {
+                                return done;
+                            };
+                            let (events, comments) = std::mem::take(&mut *stream.lock().unwrap());
+                            done.push((i, emit_events(events, &comments, &options)));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("emitter thread panicked"))
+                .collect()
+        });
+        outputs.sort_unstable_by_key(|(i, _)| *i);
+        outputs
+    });
+    let encoded = options.encoding.is_some();
+    outputs
+        .into_iter()
+        .map(|(_, output)| {
+            let output = output.map_err(|e| exception::emitter_error(py, e))?;
+            output_object(py, output, encoded)
+        })
+        .collect()
+}
+
+/// Emit a recorded stream of events, with the comments recorded alongside
+fn emit_events(
+    events: Vec<Event>,
+    comments: &[(usize, EntryComments)],
+    options: &DumperOptions,
+) -> Result<Vec<u8>, String> {
+    let mut emitter = EmitterWrapper::for_options(options);
+    emitter.reserve(
+        events
+            .iter()
+            .map(|event| match &event.data {
+                EventData::Scalar { value, .. } => value.len() + ESTIMATED_NODE_OVERHEAD,
+                _ => ESTIMATED_NODE_OVERHEAD,
+            })
+            .sum(),
+    );
+    for event in events {
+        emitter.emit(event)?;
+    }
+    if !comments.is_empty() {
+        let mut output = emitter.output_mut();
+        *output = with_comments(&output, comments, options)?;
+    }
+    trim_output_end(&mut emitter.output_mut(), options);
+    Ok(emitter.take_output())
+}
+
+/// What `dumps()` would write for an object, worked out without emitting it
+#[pyclass(name = "DumpPlan", frozen)]
+#[derive(Default)]
+pub struct DumpPlan {
+    #[pyo3(get)]
+    pub scalars: usize,
+    #[pyo3(get)]
+    pub sequences: usize,
+    #[pyo3(get)]
+    pub mappings: usize,
+    /// Anchor names, in the order they are assigned
+    #[pyo3(get)]
+    pub anchors: Vec<String>,
+    /// Approximate length of the output; line folding and multi-line scalar
+    /// indentation are not accounted for
+    #[pyo3(get)]
+    pub estimated_size: usize,
+    /// Distinct string values which have to be quoted to be written
+    #[pyo3(get)]
+    pub needs_quoting: Vec<String>,
+    /// Distinct tags which have to be written explicitly
+    #[pyo3(get)]
+    pub explicit_tags: Vec<String>,
+    seen_quoted: HashSet<String>,
+}
+
+#[pymethods]
+impl DumpPlan {
+    fn __repr__(&self) -> String {
+        format!(
+            "DumpPlan(scalars={}, sequences={}, mappings={}, anchors={}, estimated_size={})",
+            self.scalars,
+            self.sequences,
+            self.mappings,
+            self.anchors.len(),
+            self.estimated_size
+        )
+    }
+}
+
+impl DumpPlan {
+    /// Record a node needing an explicit tag, returning the length it adds
+    fn add_tag(&mut self, tag: &str) -> usize {
+        if !self.explicit_tags.iter().any(|t| t == tag) {
+            self.explicit_tags.push(tag.to_string());
+        }
+        if let Some(suffix) = tag.strip_prefix("tag:yaml.org,2002:") {
+            suffix.len() + 3
+        } else if tag.starts_with('!') {
+            tag.len() + 1
+        } else {
+            tag.len() + 4
+        }
+    }
 
-    dumper
-        .emitter
-        .emit(Event::stream_start(Encoding::Utf8))
-        .map_err(|e| exception::emitter_error(py, e))?;
+    fn add_quoted(&mut self, value: &str) {
+        if self.seen_quoted.insert(value.to_string()) {
+            self.needs_quoting.push(value.to_string());
+        }
+    }
+}
 
+/// Represent a Python object and summarize the document `dumps()` would write
+pub fn dump_plan(py: Python, obj: &Bound<'_, PyAny>, options: DumperOptions) -> PyResult<DumpPlan> {
+    let mut dumper = RSafeDumper::for_string(py, options)?;
     let node = dumper.represent_data(py, obj)?;
-    dumper.serialize(py, &node)?;
+    dumper.anchor_node(&node);
+
+    let mut plan = DumpPlan::default();
+    let mut anchors: Vec<&String> = dumper.anchors.values().flatten().collect();
+    anchors.sort();
+    plan.anchors = anchors.into_iter().cloned().collect();
+
+    let (size, block) = dumper.plan_node(py, &node, 0, false, &mut plan, &mut HashSet::new())?;
+    plan.estimated_size = size + usize::from(!block);
+    Ok(plan)
+}
+
+impl RSafeDumper {
+    /// Add `node` to `plan`, returning the length of its text and whether it is
+    /// a block collection. Block collections are measured as whole lines
+    /// indented by `indent`; anything else is measured inline.
+    fn plan_node(
+        &self,
+        py: Python,
+        node: &Arc<RepNode>,
+        indent: usize,
+        flow: bool,
+        plan: &mut DumpPlan,
+        visited: &mut HashSet<usize>,
+    ) -> PyResult<(usize, bool)> {
+        let key = Arc::as_ptr(node) as usize;
+        let anchor = self.anchors.get(&key).cloned().flatten();
+        let shared =
+            !self.options.aliases || matches!(node.as_ref(), RepNode::Scalar { shared: true, .. });
+        if !shared && !visited.insert(key) {
+            // Alias
+            return Ok((anchor.map_or(0, |a| a.len()) + 1, false));
+        }
+        let mut size = anchor.map_or(0, |a| a.len() + 2);
+
+        match node.as_ref() {
+            RepNode::Scalar {
+                tag, value, style, ..
+            } => {
+                plan.scalars += 1;
+                let (plain_implicit, quoted_implicit) = self.implicit_flags(py, tag, value)?;
+                if !plain_implicit && !quoted_implicit {
+                    size += plan.add_tag(tag);
+                } else if style.is_none() && (!plain_implicit || !plain_allowed(value, flow)) {
+                    plan.add_quoted(value);
+                    size += 2;
+                }
+                if matches!(style, Some('\'' | '"')) {
+                    size += 2;
+                }
+                Ok((size + value.len(), false))
+            }
+            RepNode::Sequence {
+                tag,
+                value,
+                flow_style,
+            } => {
+                plan.sequences += 1;
+                if tag != resolver::DEFAULT_SEQUENCE_TAG {
+                    size += plan.add_tag(tag);
+                }
+                if flow || value.is_empty() || *flow_style == Some(true) {
+                    size += 2 + 2 * value.len().saturating_sub(1);
+                    for item in value {
+                        size += self.plan_node(py, item, 0, true, plan, visited)?.0;
+                    }
+                    return Ok((size, false));
+                }
+                for item in value {
+                    let (item_size, block) =
+                        self.plan_node(py, item, indent + 2, false, plan, visited)?;
+                    // A block item starts on the "- " line, in place of its indentation
+                    size += if block {
+                        item_size
+                    } else {
+                        indent + 2 + item_size + 1
+                    };
+                }
+                Ok((size, true))
+            }
+            RepNode::Mapping {
+                tag,
+                value,
+                flow_style,
+            } => {
+                plan.mappings += 1;
+                if tag != resolver::DEFAULT_MAPPING_TAG {
+                    size += plan.add_tag(tag);
+                }
+                if flow || value.is_empty() || *flow_style == Some(true) {
+                    size += 2 + 2 * value.len().saturating_sub(1);
+                    for (k, v) in value {
+                        size += self.plan_node(py, k, 0, true, plan, visited)?.0 + 2;
+                        size += self.plan_node(py, v, 0, true, plan, visited)?.0;
+                    }
+                    return Ok((size, false));
+                }
+                for (k, v) in value {
+                    let key_size = self.plan_node(py, k, indent, false, plan, visited)?.0;
+                    // Sequences under a key are not indented further
+                    let value_indent = match v.as_ref() {
+                        RepNode::Sequence { .. } => indent,
+                        _ => indent + 2,
+                    };
+                    let (value_size, block) =
+                        self.plan_node(py, v, value_indent, false, plan, visited)?;
+                    size += indent + key_size + 2 + value_size + usize::from(!block);
+                }
+                Ok((size, true))
+            }
+        }
+    }
+}
+
+/// Whether the emitter can write `value` as a plain scalar. A simplified
+/// version of libyaml's scalar analysis.
+fn plain_allowed(value: &str, flow: bool) -> bool {
+    let Some(first) = value.chars().next() else {
+        return false;
+    };
+    let second = value.chars().nth(1);
+    if value.starts_with("---") || value.starts_with("...") {
+        return false;
+    }
+    match first {
+        ',' | '[' | ']' | '{' | '}' | '#' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%'
+        | '@' | '`' => return false,
+        '-' | '?' | ':' if second.is_none_or(|c| c == ' ') => return false,
+        _ => {}
+    }
+    !(value.starts_with(' ')
+        || value.ends_with(' ')
+        || value.ends_with(':')
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.chars().any(|c| c.is_control())
+        || (flow && value.contains([',', '?', '[', ']', '{', '}'])))
+}
+
+/// Dump a Python object as a fragment for textual insertion into a document.
+///
+/// Block collections are returned with every line indented by `indent_level`
+/// spaces and a trailing newline, to follow a `key:` line. Scalars and flow or
+/// empty collections are returned inline, without a trailing newline, with only
+/// their continuation lines indented.
+pub fn dumps_fragment(
+    py: Python,
+    obj: &Bound<'_, PyAny>,
+    indent_level: usize,
+    options: DumperOptions,
+) -> PyResult<String> {
+    if options.encoding.is_some() {
+        return Err(PyValueError::new_err(
+            "dumps_fragment() returns str and doesn't take an encoding",
+        ));
+    }
+    let mut inline = true;
+    let line_break = options.line_break;
+    let output = RSafeDumper::for_string(py, options)?.emit_stream_to_string(py, |dumper| {
+        let node = dumper.represent_data(py, obj)?;
+        inline = match &*node {
+            RepNode::Scalar { .. } => true,
+            RepNode::Sequence {
+                value, flow_style, ..
+            } => value.is_empty() || *flow_style == Some(true),
+            RepNode::Mapping {
+                value, flow_style, ..
+            } => value.is_empty() || *flow_style == Some(true),
+        };
+        dumper.serialize(py, &node)
+    })?;
+    let eol = match line_break {
+        Break::Cr => "\r",
+        Break::CrLn => "\r\n",
+        _ => "\n",
+    };
+    let output = output.strip_suffix(&format!("...{eol}")).unwrap_or(&output);
+    // Only the final line break; more belong to a kept block scalar
+    let output = output.strip_suffix(eol).unwrap_or(output);
+
+    let indent = " ".repeat(indent_level);
+    let mut fragment = String::with_capacity(output.len());
+    for (i, line) in output.split(eol).enumerate() {
+        if i > 0 {
+            fragment.push_str(eol);
+        }
+        if (i > 0 || !inline) && !line.is_empty() {
+            fragment.push_str(&indent);
+        }
+        fragment.push_str(line);
+    }
+    if !inline {
+        fragment.push_str(eol);
+    }
+    Ok(fragment)
+}
+
+/// Serialize node trees to a YAML string, one document per node.
+pub fn serialize_to_string(py: Python, nodes: &[PyNode]) -> PyResult<String> {
+    RSafeDumper::for_string(py, DumperOptions::default())?.emit_stream_to_string(py, |dumper| {
+        for node in nodes {
+            let rep = dumper.node_to_rep(py, node, &mut HashMap::new(), &mut HashSet::new())?;
+            dumper.serialize(py, &rep)?;
+        }
+        Ok(())
+    })
+}
+
+/// `data` represented as a node tree, as `dumps()` would represent it
+pub fn represent_to_node(py: Python, data: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+    let mut dumper = RSafeDumper::for_string(py, DumperOptions::default())?;
+    let node = dumper
+        .represent_data(py, data)
+        .and_then(|rep| rep_to_node(py, &rep, &mut HashMap::new()));
+    dumper.release(py);
+    node
+}
+
+/// Write `value` the way the emitter would to keep it a string in block context,
+/// always on a single line: plain when safe, otherwise quoted and escaped.
+pub fn quote_if_needed(py: Python, value: &str) -> PyResult<String> {
+    let render = |style: Option<char>| {
+        let mut dumper = RSafeDumper::for_string(py, DumperOptions::default())?;
+        let emitter = dumper.emitter.emitter_mut();
+        emitter.set_width(-1);
+        emitter.set_unicode(true);
+        dumper.emit_stream_to_string(py, |dumper| {
+            let node = dumper.make_scalar(crate::TAG_STR, value, style);
+            dumper.serialize(py, &node)
+        })
+    };
+    let rendered = render(None)?;
+    let rendered = rendered.trim_end_matches('\n');
+    let rendered = rendered.strip_suffix("\n...").unwrap_or(rendered);
+    if !rendered.contains('\n') {
+        return Ok(rendered.to_string());
+    }
+    // Multi-line output can't be interpolated inline, and double quotes escape breaks
+    let rendered = render(Some('"'))?;
+    Ok(rendered.trim_end_matches('\n').to_string())
+}
+
+impl RSafeDumper {
+    /// A dumper writing into its own buffer, with the module-level defaults.
+    /// The one the last dump released is reused.
+    fn for_string(py: Python, options: DumperOptions) -> PyResult<Self> {
+        let state = state::get(py)?;
+        let Some(mut dumper) = state.get().take_dumper() else {
+            return Self::new_for_string(py, options);
+        };
+        dumper.emitter.restart_for(&options);
+        dumper.stream = py.None();
+        dumper.dump_unicode = true;
+        dumper.clear_stream();
+        dumper.document_start_implicit = true;
+        dumper.document_end_implicit = options.document_end != DocumentEnd::Always;
+        dumper.version = None;
+        dumper.tags.clear();
+        dumper.default_flow_style = Some(false);
+        dumper.snapshot_registries();
+        dumper.options = options;
+        Ok(dumper)
+    }
+
+    /// Keep this dumper for the next `for_string()`, without the Python
+    /// objects it holds
+    fn release(mut self, py: Python) {
+        self.clear_stream();
+        self.options = DumperOptions::default();
+        let state = self.state.clone_ref(py);
+        state.get().put_dumper(self);
+    }
+
+    fn new_for_string(py: Python, options: DumperOptions) -> PyResult<Self> {
+        let state = state::get(py)?;
+        let registries = &state.get().registries;
+        Ok(RSafeDumper {
+            emitter: EmitterWrapper::for_options(&options),
+            stream: py.None(),
+            dump_unicode: true,
+            closed: -1,
+            exclusive: Exclusive::default(),
+            document_start_implicit: true,
+            document_end_implicit: options.document_end != DocumentEnd::Always,
+            version: None,
+            tags: Vec::new(),
+            serialized_nodes: HashSet::new(),
+            anchors: HashMap::new(),
+            keys_serialized: 0,
+            key_comments: Vec::new(),
+            last_alias_id: 0,
+            default_flow_style: Some(false),
+            represented_objects: HashMap::new(),
+            represented_nodes: HashMap::new(),
+            object_keeper: Vec::new(),
+            in_progress: HashSet::new(),
+            recorded_anchors: HashMap::new(),
+            recorded_comments: HashMap::new(),
+            scalar_cache: HashMap::new(),
+            path: Vec::new(),
+            representers: registries.representers.snapshot(),
+            yaml_object_tags: registries.yaml_object_tags.snapshot(),
+            implicit_resolvers: registries.implicit_resolvers(),
+            state: state.clone().unbind(),
+            resolved: ResolverCache::default(),
+            options,
+        })
+    }
+
+    /// State of the module in the interpreter this was made in
+    pub fn state(&self) -> &Py<ModuleState> {
+        &self.state
+    }
+
+    /// Take up the registrations made since the last document
+    fn snapshot_registries(&mut self) {
+        let registries = &self.state.get().registries;
+        self.representers = registries.representers.snapshot();
+        self.yaml_object_tags = registries.yaml_object_tags.snapshot();
+        self.implicit_resolvers = registries.implicit_resolvers();
+    }
+
+    /// Emit a stream around the documents produced by `body` and return the output
+    fn emit_stream_to_string(
+        self,
+        py: Python,
+        body: impl FnOnce(&mut Self) -> PyResult<()>,
+    ) -> PyResult<String> {
+        let output = self.emit_stream_to_bytes(py, body)?;
+        String::from_utf8(output)
+            .map_err(|e| exception::emitter_error(py, format!("invalid utf8 output: {e}")))
+    }
+
+    /// Emit a stream around the documents produced by `body` and return the
+    /// output in the encoding of the options
+    fn emit_stream_to_bytes(
+        mut self,
+        py: Python,
+        body: impl FnOnce(&mut Self) -> PyResult<()>,
+    ) -> PyResult<Vec<u8>> {
+        self.emit_stream(py, body)?;
+        if !self.key_comments.is_empty() {
+            let mut output = self.emitter.output_mut();
+            *output = with_comments(&output, &self.key_comments, &self.options)
+                .map_err(|e| exception::emitter_error(py, e))?;
+        }
+        trim_output_end(&mut self.emitter.output_mut(), &self.options);
+        let output = self.emitter.take_output();
+        self.release(py);
+        Ok(output)
+    }
+
+    /// Emit the stream start, the documents produced by `body` and the stream end
+    fn emit_stream(
+        &mut self,
+        py: Python,
+        body: impl FnOnce(&mut Self) -> PyResult<()>,
+    ) -> PyResult<()> {
+        let encoding = self.options.encoding.unwrap_or(Encoding::Utf8);
+        self.emitter
+            .emit(Event::stream_start(encoding))
+            .map_err(|e| exception::emitter_error(py, e))?;
+        body(self)?;
+        self.emitter
+            .emit(Event::stream_end())
+            .map_err(|e| exception::emitter_error(py, e))
+    }
+
+    /// Record the events of a stream around the documents produced by `body`
+    fn emit_stream_events(
+        &mut self,
+        py: Python,
+        body: impl FnOnce(&mut Self) -> PyResult<()>,
+    ) -> PyResult<Vec<Event>> {
+        self.emit_stream(py, body)?;
+        Ok(self.emitter.take_recorded())
+    }
+}
+
+/// Write recorded comments into emitted `output`, in the encoding of the options
+fn with_comments(
+    output: &[u8],
+    comments: &[(usize, EntryComments)],
+    options: &DumperOptions,
+) -> Result<Vec<u8>, String> {
+    let text = crate::encoding::decode(output)?;
+    let eol = match options.line_break {
+        Break::Cr => "\r",
+        Break::CrLn => "\r\n",
+        _ => "\n",
+    };
+    let text = insert_comments(&text, eol, comments)?;
+    let from_units = match options.encoding {
+        Some(Encoding::Utf16Le) => u16::to_le_bytes,
+        Some(Encoding::Utf16Be) => u16::to_be_bytes,
+        _ => return Ok(text.into_bytes()),
+    };
+    // UTF-16 output starts with a byte order mark, as the emitter writes it
+    Ok(std::iter::once('\u{feff}')
+        .chain(text.chars())
+        .collect::<String>()
+        .encode_utf16()
+        .flat_map(from_units)
+        .collect())
+}
+
+/// Longest end of the output `trim_output_end` looks at: a line break, `...`
+/// and a CRLF
+const TRIMMED_END_SIZE: usize = 6;
+
+/// Drop the `...` after the last document and the final line break of UTF-8
+/// output, as `document_end="never"` and `final_newline=False` ask
+fn trim_output_end(output: &mut Vec<u8>, options: &DumperOptions) {
+    if options.document_end == DocumentEnd::Never {
+        for marker in [&b"...\r\n"[..], b"...\n", b"...\r"] {
+            if let Some(rest) = output.strip_suffix(marker)
+                && (rest.is_empty() || rest.ends_with(b"\n") || rest.ends_with(b"\r"))
+            {
+                output.truncate(rest.len());
+                break;
+            }
+        }
+    }
+    if !options.final_newline {
+        for line_break in [&b"\r\n"[..], b"\n", b"\r"] {
+            if output.ends_with(line_break) {
+                output.truncate(output.len() - line_break.len());
+                break;
+            }
+        }
+    }
+}
+
+/// Writer appending documents to one stream as they are produced. The
+/// stream stays open between them, so each document costs only itself.
+#[pyclass(name = "DocumentWriter")]
+pub struct DocumentWriter {
+    dumper: RSafeDumper,
+}
+
+#[pymethods]
+impl DocumentWriter {
+    /// Start a stream on `stream`, dumping with the keyword arguments of
+    /// `dumps()`
+    #[new]
+    #[pyo3(signature = (stream, **kwargs))]
+    fn new(py: Python, stream: Py<PyAny>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let options = DumperOptions::from_kwargs(kwargs)?;
+        let encoding = options.encoding.unwrap_or(Encoding::Utf8);
+        let mut dumper = RSafeDumper::new_for_string(py, options)?;
+        dumper.dump_unicode = dumper.options.encoding.is_none();
+        dumper.stream = stream;
+        dumper
+            .emitter
+            .emit(Event::stream_start(encoding))
+            .map_err(|e| exception::emitter_error(py, e))?;
+        dumper.closed = 0;
+        Ok(Self { dumper })
+    }
 
-    dumper
-        .emitter
-        .emit(Event::stream_end())
-        .map_err(|e| exception::emitter_error(py, e))?;
+    /// Append `obj` to the stream as a document of its own
+    fn write(&mut self, py: Python, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+        let dumper = &mut self.dumper;
+        if dumper.closed == 1 {
+            return Err(exception::serializer_error(
+                py,
+                "writer is closed".to_string(),
+            ));
+        }
+        dumper.snapshot_registries();
+        let result = dumper
+            .represent_data(py, obj)
+            .and_then(|node| dumper.serialize(py, &node));
+        dumper.clear_represented();
+        result
+    }
+
+    /// Write everything given so far to the stream and flush it. The end of
+    /// the output is written as it is, so `final_newline=False` and
+    /// `document_end="never"` can't trim it afterwards.
+    fn flush(&mut self, py: Python) -> PyResult<()> {
+        let dumper = &mut self.dumper;
+        let stream = dumper.stream.bind(py);
+        dumper
+            .emitter
+            .flush_to(py, stream, dumper.dump_unicode, 0)?;
+        if stream.hasattr("flush")? {
+            stream.call_method0("flush")?;
+        }
+        Ok(())
+    }
+
+    /// End the stream, writing what is left of it. The stream itself stays
+    /// open.
+    fn close(&mut self, py: Python) -> PyResult<()> {
+        self.dumper.close(py)
+    }
 
-    let output = dumper.emitter.take_output();
-    String::from_utf8(output)
-        .map_err(|e| exception::emitter_error(py, format!("invalid utf8 output: {e}")))
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        py: Python,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
 }
 
 pub fn register_dumper(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
     m.add_class::<RSafeDumper>()?;
+    m.add_class::<DumpPlan>()?;
+    m.add_class::<DocumentWriter>()?;
     Ok(())
 }
@@ -0,0 +1,83 @@
+//! REmitter: write a stream of event objects back out as YAML.
+//! Pairs with `parse()` to build parse -> filter -> emit pipelines.
+
+use pyo3::prelude::*;
+
+use crate::dumper::EmitterWrapper;
+use crate::event::PyEvent;
+use crate::exception;
+
+#[pyclass(name = "_REmitter", subclass)]
+pub struct REmitter {
+    emitter: EmitterWrapper,
+    stream: Py<PyAny>,
+    dump_unicode: bool,
+}
+
+#[pymethods]
+impl REmitter {
+    #[allow(clippy::too_many_arguments)]
+    #[new]
+    #[pyo3(signature = (stream, canonical=None, indent=None, width=None,
+        allow_unicode=None, line_break=None, encoding=None))]
+    fn new(
+        py: Python,
+        stream: Py<PyAny>,
+        canonical: Option<bool>,
+        indent: Option<i32>,
+        width: Option<i32>,
+        allow_unicode: Option<bool>,
+        line_break: Option<&str>,
+        encoding: Option<&str>,
+    ) -> PyResult<Self> {
+        let emitter = EmitterWrapper::with_options(
+            py,
+            encoding,
+            canonical,
+            indent,
+            width,
+            allow_unicode,
+            line_break,
+        )?;
+        Ok(REmitter {
+            emitter,
+            stream,
+            dump_unicode: encoding.is_none(),
+        })
+    }
+
+    /// Emit a single event, writing the output to the stream once the stream ends
+    fn emit(&mut self, py: Python, event: PyEvent) -> PyResult<()> {
+        let is_stream_end = matches!(event, PyEvent::StreamEnd(_));
+        self.emitter
+            .emit(event.to_event(py))
+            .map_err(|e| exception::emitter_error(py, e))?;
+        if is_stream_end {
+            self.emitter
+                .flush_to(py, self.stream.bind(py), self.dump_unicode, 0)?;
+        }
+        Ok(())
+    }
+
+    fn dispose(&mut self) {
+        self.emitter.dispose();
+    }
+}
+
+/// Emit an iterable of event objects into a YAML string
+pub fn emit_to_string(py: Python, events: &Bound<'_, PyAny>) -> PyResult<String> {
+    let mut emitter = EmitterWrapper::with_options(py, None, None, None, None, None, None)?;
+    for event in events.try_iter()? {
+        let event: PyEvent = event?.extract()?;
+        emitter
+            .emit(event.to_event(py))
+            .map_err(|e| exception::emitter_error(py, e))?;
+    }
+    String::from_utf8(emitter.take_output())
+        .map_err(|e| exception::emitter_error(py, format!("invalid utf8 output: {e}")))
+}
+
+pub fn register_emitter(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    m.add_class::<REmitter>()?;
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Detection of the character encoding of YAML given as bytes, following the
+//! byte order mark and null byte patterns in YAML 1.2 §5.2, and the source text
+//! the loader parses, borrowed from the Python str when given one.
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyBytes, PyMemoryView, PyString};
+
+use crate::exception;
+use crate::mapped::{MappedFile, Mapping};
+
+/// YAML source text, given as a str, as a binary buffer in a supported
+/// encoding, or as a mapped file
+#[derive(FromPyObject)]
+pub enum Source<'py> {
+    Text(Bound<'py, PyString>),
+    Bytes(Bound<'py, PyBytes>),
+    ByteArray(Bound<'py, PyByteArray>),
+    View(Bound<'py, PyMemoryView>),
+    Mapped(Bound<'py, MappedFile>),
+}
+
+impl Source<'_> {
+    /// The source as text, decoding binary buffers by their detected encoding.
+    /// A str is borrowed rather than copied.
+    pub fn into_text(self, py: Python) -> PyResult<SourceText> {
+        let decoded = match self {
+            Source::Text(text) => return SourceText::borrowed(text),
+            Source::Mapped(file) => return Ok(file.get().0.clone()),
+            Source::Bytes(bytes) => decode_source(bytes.as_bytes()),
+            Source::ByteArray(bytes) => decode_source(&bytes.to_vec()),
+            // Views may be strided or of wider items; bytes() lays them out flat
+            Source::View(view) => {
+                let bytes = py.get_type::<PyBytes>().call1((view,))?;
+                decode_source(bytes.downcast::<PyBytes>()?.as_bytes())
+            }
+        };
+        decoded
+            .map(SourceText::from)
+            .map_err(|e| exception::reader_error(py, e))
+    }
+}
+
+/// Source text shared by the parser and every mark pointing into it
+#[derive(Clone)]
+pub struct SourceText(Arc<Storage>);
+
+enum Storage {
+    Owned(String),
+    /// The UTF-8 form of a Python str, which lives as long as the str
+    Borrowed {
+        _owner: Py<PyString>,
+        text: &'static str,
+    },
+    Mapped(Mapping),
+}
+
+impl SourceText {
+    pub fn borrowed(text: Bound<'_, PyString>) -> PyResult<Self> {
+        let utf8 = text.to_str()?;
+        // SAFETY: Python caches a str's UTF-8 form on the object until it is
+        // freed, and strs are immutable; `_owner` keeps the object alive as
+        // long as the reference is held.
+        let utf8 = unsafe { std::mem::transmute::<&str, &'static str>(utf8) };
+        Ok(Self(Arc::new(Storage::Borrowed {
+            _owner: text.unbind(),
+            text: utf8,
+        })))
+    }
+}
+
+impl From<String> for SourceText {
+    fn from(text: String) -> Self {
+        Self(Arc::new(Storage::Owned(text)))
+    }
+}
+
+impl From<Mapping> for SourceText {
+    fn from(mapping: Mapping) -> Self {
+        Self(Arc::new(Storage::Mapped(mapping)))
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match &*self.0 {
+            Storage::Owned(text) => text,
+            Storage::Borrowed { text, .. } => text,
+            Storage::Mapped(mapping) => mapping.text(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for SourceText {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl std::fmt::Debug for SourceText {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// Decode YAML bytes like `decode`, decompressing them first if they are
+/// compressed
+pub fn decode_source(bytes: &[u8]) -> Result<String, String> {
+    match crate::compression::decompress(bytes) {
+        Some(decompressed) => decode(&decompressed?),
+        None => decode(bytes),
+    }
+}
+
+/// Decode YAML bytes as UTF-8, UTF-16 or UTF-32, as told by a byte order mark
+/// or, failing that, by where the first character's null bytes fall
+pub fn decode(bytes: &[u8]) -> Result<String, String> {
+    match bytes {
+        [0, 0, 0xFE, 0xFF, rest @ ..] => decode_utf32(rest, 4, u32::from_be_bytes),
+        [0xFF, 0xFE, 0, 0, rest @ ..] => decode_utf32(rest, 4, u32::from_le_bytes),
+        [0, 0, 0, _, ..] => decode_utf32(bytes, 0, u32::from_be_bytes),
+        [_, 0, 0, 0, ..] => decode_utf32(bytes, 0, u32::from_le_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => decode_utf8(rest, 3),
+        [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, 2, u16::from_be_bytes),
+        [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, 2, u16::from_le_bytes),
+        [0, _, ..] => decode_utf16(bytes, 0, u16::from_be_bytes),
+        [_, 0, ..] => decode_utf16(bytes, 0, u16::from_le_bytes),
+        _ => decode_utf8(bytes, 0),
+    }
+}
+
+/// Decode `bytes`, which start `offset` bytes into the input
+fn decode_utf8(bytes: &[u8], offset: usize) -> Result<String, String> {
+    std::str::from_utf8(bytes).map(str::to_string).map_err(|e| {
+        let position = e.valid_up_to();
+        format!(
+            "invalid UTF-8 byte #x{:02x} at position {}",
+            bytes[position],
+            offset + position
+        )
+    })
+}
+
+fn decode_utf16(
+    bytes: &[u8],
+    offset: usize,
+    from_bytes: fn([u8; 2]) -> u16,
+) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err("truncated UTF-16 input: odd number of bytes".to_string());
+    }
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]));
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut position = offset;
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => {
+                text.push(c);
+                position += 2 * c.len_utf16();
+            }
+            Err(e) => {
+                return Err(format!(
+                    "invalid UTF-16 surrogate #x{:04x} at position {position}",
+                    e.unpaired_surrogate()
+                ));
+            }
+        }
+    }
+    Ok(text)
+}
+
+fn decode_utf32(
+    bytes: &[u8],
+    offset: usize,
+    from_bytes: fn([u8; 4]) -> u32,
+) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err("truncated UTF-32 input: byte count not a multiple of 4".to_string());
+    }
+    let mut text = String::with_capacity(bytes.len() / 4);
+    for (i, unit) in bytes.chunks_exact(4).enumerate() {
+        let code = from_bytes([unit[0], unit[1], unit[2], unit[3]]);
+        match char::from_u32(code) {
+            Some(c) => text.push(c),
+            None => {
+                return Err(format!(
+                    "invalid UTF-32 code point #x{code:x} at position {}",
+                    offset + 4 * i
+                ));
+            }
+        }
+    }
+    Ok(text)
+}
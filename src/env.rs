@@ -0,0 +1,91 @@
+//! `expand_env`: `${VAR}` references in scalars replaced with the values of
+//! environment variables as the stream is parsed, before tags are resolved,
+//! so `port: ${PORT}` loads an int. Values come from `os.environ`, so changes
+//! made to it from Python are seen.
+
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+
+use crate::state;
+
+/// `text` with its `${VAR}`, `${VAR:-default}` and `${VAR-default}`
+/// references replaced, or None when it has none. `:-` takes the default when
+/// the variable is unset or empty, `-` only when it is unset; `$${` stands for
+/// a literal `${`. Only variables in `allowlist`, when given, may be read.
+/// Errors are problems for the caller to mark.
+pub fn expand(
+    py: Python,
+    text: &str,
+    allowlist: Option<&HashSet<String>>,
+) -> PyResult<Result<Option<String>, String>> {
+    if !text.contains("${") {
+        return Ok(Ok(None));
+    }
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        expanded.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Ok(Err(format!("found unterminated '${{' in '{text}'")));
+        };
+        let reference = &rest[start + 2..start + end];
+        rest = &rest[start + end + 1..];
+
+        let (name, default) = match reference.find(['-', ':']) {
+            Some(at) if reference[at..].starts_with(":-") => {
+                (&reference[..at], Some((&reference[at + 2..], true)))
+            }
+            Some(at) if reference[at..].starts_with('-') => {
+                (&reference[..at], Some((&reference[at + 1..], false)))
+            }
+            _ => (reference, None),
+        };
+        if !is_name(name) {
+            return Ok(Err(format!(
+                "found invalid environment variable name '{name}'"
+            )));
+        }
+        if allowlist.is_some_and(|allowlist| !allowlist.contains(name)) {
+            return Ok(Err(format!(
+                "environment variable '{name}' is not in env_allowlist"
+            )));
+        }
+        let value = lookup(py, name)?;
+        match (value, default) {
+            (Some(value), Some((_, true))) if !value.is_empty() => expanded.push_str(&value),
+            (Some(value), Some((_, false)) | None) => expanded.push_str(&value),
+            (_, Some((default, _))) => expanded.push_str(default),
+            (None, None) => {
+                return Ok(Err(format!("environment variable '{name}' is not set")));
+            }
+        }
+    }
+    expanded.push_str(rest);
+    Ok(Ok(Some(expanded)))
+}
+
+/// The value of the environment variable `name` in `os.environ`
+fn lookup(py: Python, name: &str) -> PyResult<Option<String>> {
+    let state = state::get(py)?;
+    let environ = state.get().environ.get_or_try_init(py, || {
+        PyResult::Ok(py.import("os")?.getattr("environ")?.unbind())
+    })?;
+    environ.bind(py).call_method1("get", (name,))?.extract()
+}
+
+/// Whether `name` is a shell variable name: a letter or `_`, then letters,
+/// digits and `_`
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
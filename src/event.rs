@@ -0,0 +1,595 @@
+//! Events emitted by the parser, corresponding to PyYAML's events classes
+
+use std::collections::HashMap;
+
+use libyaml_safer::{
+    Encoding, Event, EventData, MappingStyle, ScalarStyle, SequenceStyle, TagDirective,
+    VersionDirective,
+};
+use pyo3::prelude::*;
+
+use crate::dumper::char_to_scalar_style;
+use crate::mark::PyMark;
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "StreamStartEvent")]
+pub struct PyStreamStartEvent {
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub encoding: Option<String>,
+}
+
+#[pymethods]
+impl PyStreamStartEvent {
+    #[new]
+    #[pyo3(signature = (start_mark=None, end_mark=None, encoding=None))]
+    pub fn new(
+        start_mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        encoding: Option<String>,
+    ) -> Self {
+        Self {
+            start_mark,
+            end_mark,
+            encoding,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        "StreamStartEvent()".to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "StreamEndEvent")]
+pub struct PyStreamEndEvent {
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+}
+
+#[pymethods]
+impl PyStreamEndEvent {
+    #[new]
+    #[pyo3(signature = (start_mark=None, end_mark=None))]
+    pub fn new(start_mark: Option<PyMark>, end_mark: Option<PyMark>) -> Self {
+        Self {
+            start_mark,
+            end_mark,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        "StreamEndEvent()".to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "DocumentStartEvent")]
+pub struct PyDocumentStartEvent {
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub explicit: Option<bool>,
+    #[pyo3(get)]
+    pub version: Option<(i32, i32)>,
+    #[pyo3(get)]
+    pub tags: Option<HashMap<String, String>>,
+}
+
+#[pymethods]
+impl PyDocumentStartEvent {
+    #[new]
+    #[pyo3(signature = (start_mark=None, end_mark=None, explicit=None, version=None, tags=None))]
+    pub fn new(
+        start_mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        explicit: Option<bool>,
+        version: Option<(i32, i32)>,
+        tags: Option<HashMap<String, String>>,
+    ) -> Self {
+        Self {
+            start_mark,
+            end_mark,
+            explicit,
+            version,
+            tags,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DocumentStartEvent(explicit={:?})", self.explicit)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "DocumentEndEvent")]
+pub struct PyDocumentEndEvent {
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub explicit: Option<bool>,
+}
+
+#[pymethods]
+impl PyDocumentEndEvent {
+    #[new]
+    #[pyo3(signature = (start_mark=None, end_mark=None, explicit=None))]
+    pub fn new(
+        start_mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        explicit: Option<bool>,
+    ) -> Self {
+        Self {
+            start_mark,
+            end_mark,
+            explicit,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DocumentEndEvent(explicit={:?})", self.explicit)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "AliasEvent")]
+pub struct PyAliasEvent {
+    #[pyo3(get)]
+    pub anchor: String,
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+}
+
+#[pymethods]
+impl PyAliasEvent {
+    #[new]
+    #[pyo3(signature = (anchor, start_mark=None, end_mark=None))]
+    pub fn new(anchor: String, start_mark: Option<PyMark>, end_mark: Option<PyMark>) -> Self {
+        Self {
+            anchor,
+            start_mark,
+            end_mark,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("AliasEvent(anchor={:?})", self.anchor)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "ScalarEvent")]
+pub struct PyScalarEvent {
+    #[pyo3(get)]
+    pub anchor: Option<String>,
+    #[pyo3(get)]
+    pub tag: Option<String>,
+    #[pyo3(get)]
+    pub implicit: (bool, bool),
+    #[pyo3(get)]
+    pub value: String,
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub style: Option<char>,
+}
+
+#[pymethods]
+impl PyScalarEvent {
+    #[new]
+    #[pyo3(signature = (anchor, tag, implicit, value, start_mark=None, end_mark=None, style=None))]
+    pub fn new(
+        anchor: Option<String>,
+        tag: Option<String>,
+        implicit: (bool, bool),
+        value: String,
+        start_mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        style: Option<char>,
+    ) -> Self {
+        Self {
+            anchor,
+            tag,
+            implicit,
+            value,
+            start_mark,
+            end_mark,
+            style,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScalarEvent(anchor={:?}, tag={:?}, implicit={:?}, value={:?})",
+            self.anchor, self.tag, self.implicit, self.value
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "SequenceStartEvent")]
+pub struct PySequenceStartEvent {
+    #[pyo3(get)]
+    pub anchor: Option<String>,
+    #[pyo3(get)]
+    pub tag: Option<String>,
+    #[pyo3(get)]
+    pub implicit: bool,
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub flow_style: Option<bool>,
+}
+
+#[pymethods]
+impl PySequenceStartEvent {
+    #[new]
+    #[pyo3(signature = (anchor, tag, implicit, start_mark=None, end_mark=None, flow_style=None))]
+    pub fn new(
+        anchor: Option<String>,
+        tag: Option<String>,
+        implicit: bool,
+        start_mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        flow_style: Option<bool>,
+    ) -> Self {
+        Self {
+            anchor,
+            tag,
+            implicit,
+            start_mark,
+            end_mark,
+            flow_style,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SequenceStartEvent(anchor={:?}, tag={:?}, implicit={:?})",
+            self.anchor, self.tag, self.implicit
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "SequenceEndEvent")]
+pub struct PySequenceEndEvent {
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+}
+
+#[pymethods]
+impl PySequenceEndEvent {
+    #[new]
+    #[pyo3(signature = (start_mark=None, end_mark=None))]
+    pub fn new(start_mark: Option<PyMark>, end_mark: Option<PyMark>) -> Self {
+        Self {
+            start_mark,
+            end_mark,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        "SequenceEndEvent()".to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "MappingStartEvent")]
+pub struct PyMappingStartEvent {
+    #[pyo3(get)]
+    pub anchor: Option<String>,
+    #[pyo3(get)]
+    pub tag: Option<String>,
+    #[pyo3(get)]
+    pub implicit: bool,
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub flow_style: Option<bool>,
+}
+
+#[pymethods]
+impl PyMappingStartEvent {
+    #[new]
+    #[pyo3(signature = (anchor, tag, implicit, start_mark=None, end_mark=None, flow_style=None))]
+    pub fn new(
+        anchor: Option<String>,
+        tag: Option<String>,
+        implicit: bool,
+        start_mark: Option<PyMark>,
+        end_mark: Option<PyMark>,
+        flow_style: Option<bool>,
+    ) -> Self {
+        Self {
+            anchor,
+            tag,
+            implicit,
+            start_mark,
+            end_mark,
+            flow_style,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MappingStartEvent(anchor={:?}, tag={:?}, implicit={:?})",
+            self.anchor, self.tag, self.implicit
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "MappingEndEvent")]
+pub struct PyMappingEndEvent {
+    #[pyo3(get)]
+    pub start_mark: Option<PyMark>,
+    #[pyo3(get)]
+    pub end_mark: Option<PyMark>,
+}
+
+#[pymethods]
+impl PyMappingEndEvent {
+    #[new]
+    #[pyo3(signature = (start_mark=None, end_mark=None))]
+    pub fn new(start_mark: Option<PyMark>, end_mark: Option<PyMark>) -> Self {
+        Self {
+            start_mark,
+            end_mark,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        "MappingEndEvent()".to_string()
+    }
+}
+
+/// Any Python event object; the payload of marker-only events is unused
+#[allow(dead_code)]
+#[derive(Debug, Clone, FromPyObject)]
+pub enum PyEvent {
+    StreamStart(Py<PyStreamStartEvent>),
+    StreamEnd(Py<PyStreamEndEvent>),
+    DocumentStart(Py<PyDocumentStartEvent>),
+    DocumentEnd(Py<PyDocumentEndEvent>),
+    Alias(Py<PyAliasEvent>),
+    Scalar(Py<PyScalarEvent>),
+    SequenceStart(Py<PySequenceStartEvent>),
+    SequenceEnd(Py<PySequenceEndEvent>),
+    MappingStart(Py<PyMappingStartEvent>),
+    MappingEnd(Py<PyMappingEndEvent>),
+}
+
+impl PyEvent {
+    /// Convert to the libyaml event consumed by the emitter
+    pub fn to_event(&self, py: Python) -> Event {
+        match self {
+            PyEvent::StreamStart(_) => Event::stream_start(Encoding::Utf8),
+            PyEvent::StreamEnd(_) => Event::stream_end(),
+            PyEvent::DocumentStart(event) => {
+                let event = event.borrow(py);
+                let version = event
+                    .version
+                    .map(|(major, minor)| VersionDirective { major, minor });
+                let tags: Vec<TagDirective> = event
+                    .tags
+                    .iter()
+                    .flatten()
+                    .map(|(handle, prefix)| TagDirective {
+                        handle: handle.clone(),
+                        prefix: prefix.clone(),
+                    })
+                    .collect();
+                Event::document_start(version, &tags, !event.explicit.unwrap_or(false))
+            }
+            PyEvent::DocumentEnd(event) => {
+                Event::document_end(!event.borrow(py).explicit.unwrap_or(false))
+            }
+            PyEvent::Alias(event) => Event::alias(&event.borrow(py).anchor),
+            PyEvent::Scalar(event) => {
+                let event = event.borrow(py);
+                Event::scalar(
+                    event.anchor.as_deref(),
+                    event.tag.as_deref(),
+                    &event.value,
+                    event.implicit.0,
+                    event.implicit.1,
+                    char_to_scalar_style(event.style),
+                )
+            }
+            PyEvent::SequenceStart(event) => {
+                let event = event.borrow(py);
+                let style = match event.flow_style {
+                    Some(true) => SequenceStyle::Flow,
+                    Some(false) => SequenceStyle::Block,
+                    None => SequenceStyle::Any,
+                };
+                Event::sequence_start(
+                    event.anchor.as_deref(),
+                    event.tag.as_deref(),
+                    event.implicit,
+                    style,
+                )
+            }
+            PyEvent::SequenceEnd(_) => Event::sequence_end(),
+            PyEvent::MappingStart(event) => {
+                let event = event.borrow(py);
+                let style = match event.flow_style {
+                    Some(true) => MappingStyle::Flow,
+                    Some(false) => MappingStyle::Block,
+                    None => MappingStyle::Any,
+                };
+                Event::mapping_start(
+                    event.anchor.as_deref(),
+                    event.tag.as_deref(),
+                    event.implicit,
+                    style,
+                )
+            }
+            PyEvent::MappingEnd(_) => Event::mapping_end(),
+        }
+    }
+}
+
+/// Convert a libyaml event into the matching Python event object, with its
+/// marks made by `mark`
+pub fn event_to_py(
+    py: Python,
+    event: Event,
+    mark: impl Fn(libyaml_safer::Mark) -> PyMark,
+) -> PyResult<Py<PyAny>> {
+    let (start, end) = (Some(mark(event.start_mark)), Some(mark(event.end_mark)));
+    let obj = match event.data {
+        EventData::StreamStart { encoding } => {
+            let encoding = match encoding {
+                Encoding::Any => None,
+                Encoding::Utf8 => Some("utf-8".to_string()),
+                Encoding::Utf16Le => Some("utf-16-le".to_string()),
+                Encoding::Utf16Be => Some("utf-16-be".to_string()),
+            };
+            Py::new(py, PyStreamStartEvent::new(start, end, encoding))?.into_any()
+        }
+        EventData::StreamEnd => Py::new(py, PyStreamEndEvent::new(start, end))?.into_any(),
+        EventData::DocumentStart {
+            version_directive,
+            tag_directives,
+            implicit,
+        } => {
+            let version = version_directive.map(|v| (v.major, v.minor));
+            let tags = if tag_directives.is_empty() {
+                None
+            } else {
+                Some(
+                    tag_directives
+                        .into_iter()
+                        .map(|t| (t.handle, t.prefix))
+                        .collect(),
+                )
+            };
+            Py::new(
+                py,
+                PyDocumentStartEvent::new(start, end, Some(!implicit), version, tags),
+            )?
+            .into_any()
+        }
+        EventData::DocumentEnd { implicit } => {
+            Py::new(py, PyDocumentEndEvent::new(start, end, Some(!implicit)))?.into_any()
+        }
+        EventData::Alias { anchor } => {
+            Py::new(py, PyAliasEvent::new(anchor, start, end))?.into_any()
+        }
+        EventData::Scalar {
+            anchor,
+            tag,
+            value,
+            plain_implicit,
+            quoted_implicit,
+            style,
+        } => Py::new(
+            py,
+            PyScalarEvent::new(
+                anchor,
+                tag,
+                (plain_implicit, quoted_implicit),
+                value,
+                start,
+                end,
+                scalar_style_to_char(style),
+            ),
+        )?
+        .into_any(),
+        EventData::SequenceStart {
+            anchor,
+            tag,
+            implicit,
+            style,
+        } => Py::new(
+            py,
+            PySequenceStartEvent::new(
+                anchor,
+                tag,
+                implicit,
+                start,
+                end,
+                sequence_flow_style(style),
+            ),
+        )?
+        .into_any(),
+        EventData::SequenceEnd => Py::new(py, PySequenceEndEvent::new(start, end))?.into_any(),
+        EventData::MappingStart {
+            anchor,
+            tag,
+            implicit,
+            style,
+        } => Py::new(
+            py,
+            PyMappingStartEvent::new(anchor, tag, implicit, start, end, mapping_flow_style(style)),
+        )?
+        .into_any(),
+        EventData::MappingEnd => Py::new(py, PyMappingEndEvent::new(start, end))?.into_any(),
+    };
+    Ok(obj)
+}
+
+pub fn scalar_style_to_char(style: ScalarStyle) -> Option<char> {
+    match style {
+        ScalarStyle::SingleQuoted => Some('\''),
+        ScalarStyle::DoubleQuoted => Some('"'),
+        ScalarStyle::Literal => Some('|'),
+        ScalarStyle::Folded => Some('>'),
+        ScalarStyle::Any | ScalarStyle::Plain => None,
+    }
+}
+
+/// `flow_style` of a sequence: True for flow, False for block, None if unspecified
+pub fn sequence_flow_style(style: SequenceStyle) -> Option<bool> {
+    flow_style_of(style == SequenceStyle::Flow, style == SequenceStyle::Block)
+}
+
+/// `flow_style` of a mapping: True for flow, False for block, None if unspecified
+pub fn mapping_flow_style(style: MappingStyle) -> Option<bool> {
+    flow_style_of(style == MappingStyle::Flow, style == MappingStyle::Block)
+}
+
+fn flow_style_of(flow: bool, block: bool) -> Option<bool> {
+    if flow {
+        Some(true)
+    } else if block {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+pub fn register_events(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyStreamStartEvent>()?;
+    m.add_class::<PyStreamEndEvent>()?;
+    m.add_class::<PyDocumentStartEvent>()?;
+    m.add_class::<PyDocumentEndEvent>()?;
+    m.add_class::<PyAliasEvent>()?;
+    m.add_class::<PyScalarEvent>()?;
+    m.add_class::<PySequenceStartEvent>()?;
+    m.add_class::<PySequenceEndEvent>()?;
+    m.add_class::<PyMappingStartEvent>()?;
+    m.add_class::<PyMappingEndEvent>()?;
+    Ok(())
+}
@@ -1,42 +1,345 @@
+use libyaml_safer::ErrorKind;
+use pyo3::PyTypeInfo;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyType;
-
-pyo3::create_exception!(_ryaml, InvalidYamlError, pyo3::exceptions::PyValueError);
-
-/// Raise one of the exception classes defined in ``ryaml.error``.
-///
-/// Falls back to ``InvalidYamlError`` if the import fails (e.g. the pure-Python
-/// package has not been installed alongside the native extension).
-pub fn yaml_error(py: Python, class_name: &str, message: String) -> PyErr {
-    if let Ok(module) = py.import("ryaml.error")
-        && let Ok(attr) = module.getattr(class_name)
-        && let Ok(tp) = attr.downcast_into::<PyType>()
+use pyo3::types::{PyModule, PyType};
+
+use crate::backend::ParseError;
+use crate::mark::PyMark;
+
+pyo3::create_exception!(ryaml, InvalidYamlError, PyValueError);
+pyo3::create_exception!(
+    ryaml,
+    YAMLError,
+    InvalidYamlError,
+    "Base class of the errors raised for invalid YAML and objects that can't be dumped."
+);
+pyo3::create_exception!(
+    ryaml,
+    MarkedYAMLError,
+    YAMLError,
+    "An error at a place in a YAML stream, with pyyaml's ``context``, ``context_mark``, \
+     ``problem``, ``problem_mark`` and ``note`` attributes, and the ``line``, ``column`` \
+     and ``snippet`` of the problem."
+);
+pyo3::create_exception!(ryaml, ScannerError, MarkedYAMLError);
+pyo3::create_exception!(ryaml, ParserError, MarkedYAMLError);
+pyo3::create_exception!(ryaml, ComposerError, MarkedYAMLError);
+pyo3::create_exception!(ryaml, ConstructorError, MarkedYAMLError);
+pyo3::create_exception!(ryaml, EmitterError, YAMLError);
+pyo3::create_exception!(ryaml, SerializerError, YAMLError);
+pyo3::create_exception!(ryaml, RepresenterError, YAMLError);
+pyo3::create_exception!(ryaml, ReaderError, YAMLError);
+
+/// Attributes of marked errors, None until an error sets them
+const MARKED_ATTRIBUTES: &[&str] = &[
+    "context",
+    "context_mark",
+    "problem",
+    "problem_mark",
+    "note",
+    "line",
+    "column",
+    "snippet",
+];
+
+pub fn register_exceptions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("InvalidYamlError", py.get_type::<InvalidYamlError>())?;
+    m.add("YAMLError", py.get_type::<YAMLError>())?;
+    m.add("MarkedYAMLError", py.get_type::<MarkedYAMLError>())?;
+    m.add("ScannerError", py.get_type::<ScannerError>())?;
+    m.add("ParserError", py.get_type::<ParserError>())?;
+    m.add("ComposerError", py.get_type::<ComposerError>())?;
+    m.add("ConstructorError", py.get_type::<ConstructorError>())?;
+    m.add("EmitterError", py.get_type::<EmitterError>())?;
+    m.add("SerializerError", py.get_type::<SerializerError>())?;
+    m.add("RepresenterError", py.get_type::<RepresenterError>())?;
+    m.add("ReaderError", py.get_type::<ReaderError>())?;
+    // Errors raised from Python with only a message have the attributes too
+    let marked = py.get_type::<MarkedYAMLError>();
+    for attribute in MARKED_ATTRIBUTES {
+        marked.setattr(*attribute, py.None())?;
+    }
+    Ok(())
+}
+
+/// Raise a `T`, a ``MarkedYAMLError``, carrying ``context``,
+/// ``context_mark``, ``problem`` and ``problem_mark`` attributes.
+pub fn marked_error<T: PyTypeInfo>(
+    py: Python,
+    context: Option<String>,
+    context_mark: Option<PyMark>,
+    problem: String,
+    problem_mark: Option<PyMark>,
+) -> PyErr {
+    marked_error_with_note::<T>(py, context, context_mark, problem, problem_mark, None)
+}
+
+/// Like `marked_error`, also setting the ``note`` shown after the location
+pub fn marked_error_with_note<T: PyTypeInfo>(
+    py: Python,
+    context: Option<String>,
+    context_mark: Option<PyMark>,
+    problem: String,
+    problem_mark: Option<PyMark>,
+    note: Option<String>,
+) -> PyErr {
+    new_marked_error(
+        py,
+        T::type_object(py),
+        context,
+        context_mark,
+        problem,
+        problem_mark,
+        note,
+    )
+}
+
+fn new_marked_error(
+    py: Python,
+    class: Bound<'_, PyType>,
+    context: Option<String>,
+    context_mark: Option<PyMark>,
+    problem: String,
+    problem_mark: Option<PyMark>,
+    note: Option<String>,
+) -> PyErr {
+    let error = PyErr::from_type(class, ());
+    let value = error.value(py);
+    let set = || {
+        value.setattr("context", context)?;
+        value.setattr("context_mark", context_mark)?;
+        value.setattr("problem", problem)?;
+        value.setattr("problem_mark", problem_mark)?;
+        value.setattr("note", note)?;
+        describe(value)
+    };
+    match set() {
+        Ok(()) => error,
+        Err(e) => e,
+    }
+}
+
+/// Set the message and the ``line``, ``column`` and ``snippet`` of the
+/// marked error `value` from its other attributes, in the layout of pyyaml's
+/// ``MarkedYAMLError``. A message given without a problem becomes the problem.
+fn describe(value: &Bound<'_, PyAny>) -> PyResult<()> {
+    let mut problem = value.getattr("problem")?;
+    let args = value.getattr("args")?;
+    if problem.is_none() && args.len()? == 1 && value.getattr("context")?.is_none() {
+        problem = args.get_item(0)?;
+        value.setattr("problem", &problem)?;
+    }
+    let context = value.getattr("context")?;
+    let context_mark = value.getattr("context_mark")?;
+    let problem_mark = value.getattr("problem_mark")?;
+    let note = value.getattr("note")?;
+
+    let mut lines = Vec::new();
+    if !context.is_none() {
+        lines.push(context.str()?.to_string());
+    }
+    if !context_mark.is_none()
+        && (problem.is_none()
+            || problem_mark.is_none()
+            || !same_place(&context_mark, &problem_mark)?)
     {
-        return PyErr::from_type(tp, (message,));
+        lines.push(context_mark.str()?.to_string());
+    }
+    for part in [&problem, &problem_mark, &note] {
+        if !part.is_none() {
+            lines.push(part.str()?.to_string());
+        }
+    }
+    value.setattr("args", (lines.join("\n"),))?;
+
+    let (line, column, snippet) = match problem_mark.is_none() {
+        true => (None, None, None),
+        false => (
+            Some(problem_mark.getattr("line")?.extract::<u64>()? + 1),
+            Some(problem_mark.getattr("column")?.extract::<u64>()? + 1),
+            match problem_mark.hasattr("get_snippet")? {
+                true => problem_mark
+                    .call_method1("get_snippet", (0,))?
+                    .extract::<Option<String>>()?,
+                false => None,
+            },
+        ),
+    };
+    value.setattr("line", line)?;
+    value.setattr("column", column)?;
+    value.setattr("snippet", snippet)
+}
+
+/// Whether marks `a` and `b` point at the same place of the same stream
+fn same_place(a: &Bound<'_, PyAny>, b: &Bound<'_, PyAny>) -> PyResult<bool> {
+    for attribute in ["name", "line", "column"] {
+        if !a.getattr(attribute)?.eq(b.getattr(attribute)?)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Convert a libyaml error into the matching ``ryaml.error`` exception,
+/// translating its marks with `mark`
+pub fn libyaml_error(
+    py: Python,
+    error: &libyaml_safer::Error,
+    mark: impl Fn(libyaml_safer::Mark) -> PyMark,
+) -> PyErr {
+    let class = match error.kind() {
+        ErrorKind::Scanner => py.get_type::<ScannerError>(),
+        ErrorKind::Parser => py.get_type::<ParserError>(),
+        ErrorKind::Composer => py.get_type::<ComposerError>(),
+        ErrorKind::Emitter => return emitter_error(py, error.to_string()),
+        ErrorKind::Reader | ErrorKind::Io => return reader_error(py, error.to_string()),
+    };
+    let problem_mark = error.problem_mark().map(&mark);
+    let note = hint(error.problem(), problem_mark.as_ref());
+    new_marked_error(
+        py,
+        class,
+        error.context().map(str::to_string),
+        error.context_mark().map(&mark),
+        error.problem().to_string(),
+        problem_mark,
+        note,
+    )
+}
+
+/// Convert the syntax error of either parser backend into the matching
+/// ``ryaml.error`` exception, translating its marks with `mark`
+pub fn parse_error(
+    py: Python,
+    error: &ParseError,
+    mark: impl Fn(libyaml_safer::Mark) -> PyMark,
+) -> PyErr {
+    match error {
+        ParseError::Libyaml(error) => libyaml_error(py, error, mark),
+        ParseError::Saphyr { read: true, .. } => reader_error(py, error.to_string()),
+        ParseError::Saphyr {
+            problem, mark: at, ..
+        } => {
+            let problem_mark = at.map(&mark);
+            let note = hint(problem, problem_mark.as_ref());
+            new_marked_error(
+                py,
+                py.get_type::<ParserError>(),
+                None,
+                None,
+                problem.clone(),
+                problem_mark,
+                note,
+            )
+        }
     }
-    InvalidYamlError::new_err(message)
 }
 
-pub fn scanner_error(py: Python, message: String) -> PyErr {
-    yaml_error(py, "ScannerError", message)
+/// Likely causes of common libyaml problems, keyed by the start of the problem
+/// text. `{line}` is replaced with the 1-based line of the problem.
+const HINTS: &[(&str, &str)] = &[
+    (
+        "did not find expected key",
+        "check for a missing ':' after a key or inconsistent indentation at line {line}",
+    ),
+    (
+        "could not find expected ':'",
+        "a key must be followed by ':' on the same line; check line {line}",
+    ),
+    (
+        "mapping values are not allowed",
+        "a ':' followed by a space starts a mapping value; quote the value on line {line} \
+         if it contains ': ', or put nested mappings on their own indented line",
+    ),
+    (
+        "did not find expected '-' indicator",
+        "items of a sequence must all start with '-' at the same indentation; check line {line}",
+    ),
+    (
+        "block sequence entries are not allowed",
+        "a '-' entry can't follow a value on the same line; start the sequence on a new line \
+         after line {line}",
+    ),
+    (
+        "did not find expected ',' or ']'",
+        "check for a missing ',' between items or an unclosed '[' before line {line}",
+    ),
+    (
+        "did not find expected ',' or '}'",
+        "check for a missing ',' between entries or an unclosed '{' before line {line}",
+    ),
+    (
+        "found character that cannot start any token",
+        "YAML does not allow tabs for indentation, and values starting with '@' or '`' \
+         must be quoted; check line {line}",
+    ),
+    (
+        "found a tab character",
+        "YAML does not allow tabs for indentation; replace them with spaces on line {line}",
+    ),
+    (
+        "found unexpected end of stream",
+        "check for an unclosed quote or bracket before line {line}",
+    ),
+    (
+        "found unknown escape character",
+        "only YAML escapes are allowed in double quotes; use single quotes or '\\\\' \
+         for a literal backslash on line {line}",
+    ),
+    (
+        "did not find expected comment or line break",
+        "check for text after a closing quote or block indicator on line {line}",
+    ),
+    (
+        "found undefined tag handle",
+        "declare the tag handle with a %TAG directive or use a full !<...> tag on line {line}",
+    ),
+];
+
+/// A hint for fixing `problem` at `mark`, if it is one of the common ones
+fn hint(problem: &str, mark: Option<&PyMark>) -> Option<String> {
+    let (_, hint) = HINTS
+        .iter()
+        .find(|(prefix, _)| problem.starts_with(prefix))?;
+    let line = mark?.line + 1;
+    Some(format!(
+        "hint: {}",
+        hint.replace("{line}", &line.to_string())
+    ))
 }
 
-pub fn composer_error(py: Python, message: String) -> PyErr {
-    yaml_error(py, "ComposerError", message)
+/// Point an already raised marked error at `mark`, unless it has a problem mark already
+pub fn with_problem_mark(py: Python, error: PyErr, mark: PyMark) -> PyErr {
+    let value = error.value(py);
+    if value
+        .getattr("problem_mark")
+        .is_ok_and(|problem_mark| problem_mark.is_none())
+        && value.setattr("problem_mark", mark).is_ok()
+        && value.is_instance_of::<MarkedYAMLError>()
+    {
+        let _ = describe(value);
+    }
+    error
 }
 
 pub fn constructor_error(py: Python, message: String) -> PyErr {
-    yaml_error(py, "ConstructorError", message)
+    marked_error::<ConstructorError>(py, None, None, message, None)
+}
+
+pub fn emitter_error(_py: Python, message: String) -> PyErr {
+    EmitterError::new_err(message)
 }
 
-pub fn emitter_error(py: Python, message: String) -> PyErr {
-    yaml_error(py, "EmitterError", message)
+pub fn serializer_error(_py: Python, message: String) -> PyErr {
+    SerializerError::new_err(message)
 }
 
-pub fn serializer_error(py: Python, message: String) -> PyErr {
-    yaml_error(py, "SerializerError", message)
+pub fn representer_error(_py: Python, message: String) -> PyErr {
+    RepresenterError::new_err(message)
 }
 
-pub fn representer_error(py: Python, message: String) -> PyErr {
-    yaml_error(py, "RepresenterError", message)
+pub fn reader_error(_py: Python, message: String) -> PyErr {
+    ReaderError::new_err(message)
 }
@@ -0,0 +1,52 @@
+//! Use of a loader or dumper by one thread at a time.
+//!
+//! Their methods hand state back and forth with Python overrides, releasing
+//! and retaking the object between steps. On free-threaded builds a second
+//! thread could step in between and interleave its document with the first
+//! one's, so it gets a RuntimeError instead. Calls back into the object from
+//! the thread already using it, as overrides make, are let through.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The thread using an object, if any
+#[derive(Debug, Default)]
+pub struct Exclusive(Arc<AtomicU64>);
+
+impl Exclusive {
+    /// Claim the object for the current thread until the guard is dropped
+    pub fn enter(&self, what: &str) -> PyResult<Guard> {
+        let thread = THREAD_ID.with(|id| *id);
+        match self
+            .0
+            .compare_exchange(0, thread, Ordering::Acquire, Ordering::Relaxed)
+        {
+            Ok(_) => Ok(Guard(Some(Arc::clone(&self.0)))),
+            // Already held by this thread further up the stack
+            Err(owner) if owner == thread => Ok(Guard(None)),
+            Err(_) => Err(PyRuntimeError::new_err(format!(
+                "{what} is in use by another thread; use one per thread"
+            ))),
+        }
+    }
+}
+
+/// Releases the object when dropped, unless an outer guard holds it
+pub struct Guard(Option<Arc<AtomicU64>>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(owner) = &self.0 {
+            owner.store(0, Ordering::Release);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! `format()`: YAML text parsed and emitted again in one consistent style,
+//! as `yamlfmt` does. Events go straight from the parser to the emitter, so
+//! tags, anchors and documents come through unchanged while indentation,
+//! quoting and collection styles are normalized. Comments around block
+//! mapping entries are kept as `roundtrip` keeps them; others are dropped.
+
+use std::io::Cursor;
+
+use libyaml_safer::{
+    Encoding, Event, EventData, MappingStyle, Mark, Parser, ScalarStyle, SequenceStyle,
+};
+use pyo3::prelude::*;
+
+use crate::comments::{EntryComments, SourceLines, insert_comments};
+use crate::dumper::EmitterWrapper;
+use crate::encoding::SourceText;
+use crate::exception;
+use crate::mark::PyMark;
+
+pub struct FormatOptions {
+    pub indent: i32,
+    pub width: i32,
+    /// Keep flow collections in flow style rather than making them block
+    pub preserve_flow_style: bool,
+    pub comments: bool,
+}
+
+struct Formatter<'a> {
+    source: &'a SourceText,
+    parser: Parser<Cursor<SourceText>>,
+    peeked: Option<Event>,
+    emitter: EmitterWrapper,
+    options: &'a FormatOptions,
+    /// Source lines searched for comments, with `comments`
+    lines: Option<SourceLines>,
+    /// Last line holding a scalar or alias parsed so far
+    content_line: Option<u64>,
+    /// End of the last event parsed
+    position: Mark,
+    /// Mapping keys of the stream met so far
+    keys: usize,
+    /// Comments of block mapping entries, by the position of their key
+    comments: Vec<(usize, EntryComments)>,
+}
+
+impl Formatter<'_> {
+    fn peek(&mut self, py: Python) -> PyResult<&Event> {
+        if self.peeked.is_none() {
+            let event = self.parser.parse().map_err(|e| {
+                exception::libyaml_error(py, &e, |m| {
+                    PyMark::with_source(m, crate::mark::DEFAULT_SOURCE_NAME, self.source)
+                })
+            })?;
+            self.peeked = Some(event);
+        }
+        Ok(self.peeked.as_ref().expect("an event was just parsed"))
+    }
+
+    /// Emit the next event in the normalized style, returning its data as
+    /// parsed
+    fn next(&mut self, py: Python) -> PyResult<EventData> {
+        self.peek(py)?;
+        let mut event = self.peeked.take().expect("an event was peeked");
+        self.position = event.end_mark;
+        let data = event.data.clone();
+        match &mut event.data {
+            EventData::StreamStart { encoding } => *encoding = Encoding::Utf8,
+            EventData::Scalar { style, .. } => {
+                self.content_line = Some(match style {
+                    // Block scalars end at the start of the line after their content
+                    ScalarStyle::Literal | ScalarStyle::Folded => event
+                        .end_mark
+                        .line
+                        .saturating_sub(1)
+                        .max(event.start_mark.line),
+                    _ => event.end_mark.line,
+                });
+                // Quoted scalars stay quoted, to keep resolving as strs, in
+                // single quotes unless they need double
+                if matches!(style, ScalarStyle::SingleQuoted | ScalarStyle::DoubleQuoted) {
+                    *style = ScalarStyle::Any;
+                }
+            }
+            EventData::Alias { .. } => self.content_line = Some(event.end_mark.line),
+            EventData::SequenceStart { style, .. } if !self.options.preserve_flow_style => {
+                *style = SequenceStyle::Block;
+            }
+            EventData::MappingStart { style, .. } if !self.options.preserve_flow_style => {
+                *style = MappingStyle::Block;
+            }
+            _ => {}
+        }
+        self.emitter
+            .emit(event)
+            .map_err(|e| exception::emitter_error(py, e))?;
+        Ok(data)
+    }
+
+    /// Pass on the events of every document of the stream
+    fn stream(&mut self, py: Python) -> PyResult<()> {
+        self.next(py)?;
+        loop {
+            match self.next(py)? {
+                EventData::StreamEnd => return Ok(()),
+                EventData::DocumentStart { .. } => {
+                    self.node(py)?;
+                    self.next(py)?;
+                }
+                _ => unreachable!("the parser gives documents between stream events"),
+            }
+        }
+    }
+
+    /// Pass on the events of the next node
+    fn node(&mut self, py: Python) -> PyResult<()> {
+        match self.next(py)? {
+            EventData::SequenceStart { .. } => {
+                while !matches!(self.peek(py)?.data, EventData::SequenceEnd) {
+                    self.node(py)?;
+                }
+                self.next(py)?;
+            }
+            EventData::MappingStart { style, .. } => {
+                let block = style != MappingStyle::Flow;
+                loop {
+                    let floor = self.content_line;
+                    let key = self.peek(py)?;
+                    if matches!(key.data, EventData::MappingEnd) {
+                        break;
+                    }
+                    let key_line = key.start_mark.line;
+                    let position = self.keys;
+                    self.keys += 1;
+                    self.node(py)?;
+                    let key_end = self.position;
+                    // Where the comment ending the entry's first line follows
+                    let value = self.peek(py)?;
+                    let comment_mark = match &value.data {
+                        EventData::SequenceStart {
+                            style: SequenceStyle::Block,
+                            ..
+                        }
+                        | EventData::MappingStart {
+                            style: MappingStyle::Block,
+                            ..
+                        } => Some(key_end),
+                        EventData::Scalar {
+                            style: ScalarStyle::Literal | ScalarStyle::Folded,
+                            ..
+                        } => Some(value.start_mark),
+                        _ => None,
+                    };
+                    self.node(py)?;
+                    if let Some(lines) = self.lines.as_ref().filter(|_| block) {
+                        let entry = EntryComments {
+                            before: lines.comments_above(key_line, floor),
+                            inline: lines.comment_after(comment_mark.unwrap_or(self.position)),
+                        };
+                        if !entry.before.is_empty() || entry.inline.is_some() {
+                            self.comments.push((position, entry));
+                        }
+                    }
+                }
+                self.next(py)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// `source` emitted again in the style `options` ask for
+pub fn format(py: Python, source: SourceText, options: &FormatOptions) -> PyResult<String> {
+    let mut parser = Parser::new();
+    parser.set_input(Cursor::new(source.clone()));
+    let emitter = EmitterWrapper::with_options(
+        py,
+        None,
+        None,
+        Some(options.indent),
+        Some(options.width),
+        Some(true),
+        None,
+    )?;
+    let mut formatter = Formatter {
+        source: &source,
+        parser,
+        peeked: None,
+        emitter,
+        options,
+        lines: options.comments.then(|| SourceLines::new(source.clone())),
+        content_line: None,
+        position: Mark::default(),
+        keys: 0,
+        comments: Vec::new(),
+    };
+    formatter.stream(py)?;
+    let output = String::from_utf8(formatter.emitter.take_output())
+        .map_err(|e| exception::emitter_error(py, format!("invalid utf8 output: {e}")))?;
+    if formatter.comments.is_empty() {
+        return Ok(output);
+    }
+    formatter.comments.sort_by_key(|(position, _)| *position);
+    insert_comments(&output, "\n", &formatter.comments).map_err(|e| exception::emitter_error(py, e))
+}
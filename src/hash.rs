@@ -0,0 +1,196 @@
+//! `content_hash()`: a digest of the data a document holds rather than of
+//! its text. Each value is hashed with a tag for its type, and each
+//! collection over the digests of its items, so mappings and sets can be
+//! hashed regardless of order by sorting those digests.
+
+use std::collections::HashSet;
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{
+    PyBool, PyByteArray, PyBytes, PyDict, PyFloat, PyFrozenSet, PyInt, PyList, PySet, PyString,
+    PyTuple,
+};
+use sha2::Digest as _;
+
+type Digest = [u8; 32];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Blake3,
+    Sha256,
+}
+
+impl Algorithm {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "blake3" => Ok(Algorithm::Blake3),
+            "sha256" => Ok(Algorithm::Sha256),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported hash algorithm '{name}' (expected 'blake3' or 'sha256')"
+            ))),
+        }
+    }
+
+    fn hasher(self) -> Hasher {
+        match self {
+            Algorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            Algorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+        }
+    }
+}
+
+enum Hasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(sha2::Sha256),
+}
+
+impl Hasher {
+    fn update(&mut self, bytes: &[u8]) -> &mut Self {
+        match self {
+            Hasher::Blake3(hasher) => {
+                hasher.update(bytes);
+            }
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+        }
+        self
+    }
+
+    /// `bytes` preceded by their length, so neighbouring values can't run
+    /// into each other
+    fn update_sized(&mut self, bytes: &[u8]) -> &mut Self {
+        self.update(&(bytes.len() as u64).to_le_bytes())
+            .update(bytes)
+    }
+
+    fn finalize(self) -> Digest {
+        match self {
+            Hasher::Blake3(hasher) => *hasher.finalize().as_bytes(),
+            Hasher::Sha256(hasher) => hasher.finalize().into(),
+        }
+    }
+}
+
+pub struct ContentHasher {
+    algorithm: Algorithm,
+    /// Hash mappings the same whatever the order of their keys
+    sort_keys: bool,
+    /// Collections being hashed, to refuse recursive data
+    open: HashSet<usize>,
+}
+
+impl ContentHasher {
+    pub fn new(algorithm: Algorithm, sort_keys: bool) -> Self {
+        Self {
+            algorithm,
+            sort_keys,
+            open: HashSet::new(),
+        }
+    }
+
+    /// The digest of `data`, as a hex string
+    pub fn hex_digest(&mut self, data: &Bound<'_, PyAny>) -> PyResult<String> {
+        let digest = self.digest(data)?;
+        Ok(digest.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
+    fn leaf(&self, tag: u8, bytes: &[u8]) -> Digest {
+        let mut hasher = self.algorithm.hasher();
+        hasher.update(&[tag]).update_sized(bytes);
+        hasher.finalize()
+    }
+
+    /// A collection tagged `tag` over the digests of its items, sorted
+    /// unless their order matters
+    fn collection(&self, tag: u8, mut items: Vec<Digest>, ordered: bool) -> Digest {
+        if !ordered {
+            items.sort_unstable();
+        }
+        let mut hasher = self.algorithm.hasher();
+        hasher
+            .update(&[tag])
+            .update(&(items.len() as u64).to_le_bytes());
+        for item in &items {
+            hasher.update(item);
+        }
+        hasher.finalize()
+    }
+
+    fn digest(&mut self, data: &Bound<'_, PyAny>) -> PyResult<Digest> {
+        if data.is_none() {
+            return Ok(self.leaf(b'n', b""));
+        }
+        if let Ok(value) = data.downcast::<PyBool>() {
+            return Ok(self.leaf(b'b', &[u8::from(value.is_true())]));
+        }
+        if data.is_instance_of::<PyInt>() {
+            return Ok(self.leaf(b'i', data.str()?.to_str()?.as_bytes()));
+        }
+        if let Ok(value) = data.downcast::<PyFloat>() {
+            let value = value.value();
+            // Every NaN, and both zeros, hash alike
+            let bits = if value.is_nan() {
+                f64::NAN.to_bits()
+            } else if value == 0.0 {
+                0
+            } else {
+                value.to_bits()
+            };
+            return Ok(self.leaf(b'f', &bits.to_le_bytes()));
+        }
+        if let Ok(value) = data.downcast::<PyString>() {
+            return Ok(self.leaf(b's', value.to_str()?.as_bytes()));
+        }
+        if let Ok(value) = data.downcast::<PyBytes>() {
+            return Ok(self.leaf(b'y', value.as_bytes()));
+        }
+        if let Ok(value) = data.downcast::<PyByteArray>() {
+            return Ok(self.leaf(b'y', &value.to_vec()));
+        }
+
+        let address = data.as_ptr() as usize;
+        if !self.open.insert(address) {
+            return Err(PyValueError::new_err("cannot hash recursive data"));
+        }
+        let digest = self.collection_digest(data);
+        self.open.remove(&address);
+        digest
+    }
+
+    fn collection_digest(&mut self, data: &Bound<'_, PyAny>) -> PyResult<Digest> {
+        if let Ok(dict) = data.downcast::<PyDict>() {
+            let mut entries = Vec::with_capacity(dict.len());
+            for (key, value) in dict.iter() {
+                let mut hasher = self.algorithm.hasher();
+                hasher
+                    .update(&self.digest(&key)?)
+                    .update(&self.digest(&value)?);
+                entries.push(hasher.finalize());
+            }
+            return Ok(self.collection(b'm', entries, !self.sort_keys));
+        }
+        if data.is_instance_of::<PyList>() || data.is_instance_of::<PyTuple>() {
+            let items = data
+                .try_iter()?
+                .map(|item| self.digest(&item?))
+                .collect::<PyResult<_>>()?;
+            return Ok(self.collection(b'l', items, true));
+        }
+        if data.is_instance_of::<PySet>() || data.is_instance_of::<PyFrozenSet>() {
+            let items = data
+                .try_iter()?
+                .map(|item| self.digest(&item?))
+                .collect::<PyResult<_>>()?;
+            return Ok(self.collection(b'S', items, false));
+        }
+        // Dates and datetimes, as the timestamps they were loaded from
+        if let Ok(isoformat) = data.getattr("isoformat") {
+            let text = isoformat.call0()?;
+            return Ok(self.leaf(b't', text.extract::<String>()?.as_bytes()));
+        }
+        let type_name = data.get_type().name()?;
+        Err(PyTypeError::new_err(format!(
+            "cannot hash an object of type {type_name}"
+        )))
+    }
+}
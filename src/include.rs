@@ -0,0 +1,59 @@
+//! `!include path` scalars, loaded as the document of the file at `path` in
+//! their place when `include_dir` is given. Paths are relative to the file
+//! including them, or to `include_dir` at the top, and must lead to a file
+//! inside `include_dir` once symlinks are followed.
+
+use std::path::{Path, PathBuf};
+
+/// Tag of the scalars naming a file to include
+pub const TAG_INCLUDE: &str = "!include";
+
+/// Default of `max_include_depth`
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+/// The file `path` refers to, included from the last file of `chain`, or
+/// from the top when it is empty. Fails with the problem to report when the
+/// file is outside `dir`, is already being included or is one too deep.
+pub fn resolve(
+    dir: &Path,
+    chain: &[PathBuf],
+    max_depth: usize,
+    path: &str,
+) -> Result<PathBuf, String> {
+    let root = dir
+        .canonicalize()
+        .map_err(|e| format!("can't use include_dir '{}': {e}", dir.display()))?;
+    let base = chain.last().and_then(|file| file.parent()).unwrap_or(&root);
+    let file = base
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("can't include '{path}': {e}"))?;
+    if !file.starts_with(&root) {
+        return Err(format!(
+            "can't include '{path}', which is outside include_dir"
+        ));
+    }
+    if chain.contains(&file) {
+        let cycle: Vec<_> = chain
+            .iter()
+            .skip_while(|included| **included != file)
+            .chain([&file])
+            .map(|included| display(&root, included))
+            .collect();
+        return Err(format!("found an include cycle: {}", cycle.join(" -> ")));
+    }
+    if chain.len() >= max_depth {
+        return Err(format!(
+            "includes are nested more than max_include_depth={max_depth} deep"
+        ));
+    }
+    Ok(file)
+}
+
+/// `file` relative to `root`, for messages
+fn display(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .display()
+        .to_string()
+}
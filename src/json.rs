@@ -0,0 +1,478 @@
+//! Fast path for loading documents which are plain JSON.
+//!
+//! Many YAML payloads are really JSON. Those are constructed here in a single
+//! pass over the bytes, skipping the YAML scanner and parser. Anything this
+//! constructor is not sure loads identically through the YAML path (exponents,
+//! integers beyond 64 bits, tabs, surrogate escapes, characters YAML forbids,
+//! ...) makes it give up, and the document is loaded as YAML instead.
+//!
+//! `from_json()` parses any JSON text into a `JsonValue` tree instead, with the
+//! same parser in strict mode, which the dumper writes out as YAML without
+//! making Python objects.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString};
+
+use crate::backend::Backend;
+use crate::loader::{FloatPrecision, LoaderOptions, is_exact_float};
+use crate::resolver::Schema;
+
+/// Deepest nesting handled here; deeper documents go through the YAML path
+const MAX_DEPTH: usize = 256;
+
+/// Construct `source` if it is a JSON document, or None if it must be loaded as YAML
+pub fn try_loads(py: Python, source: &str, options: &LoaderOptions) -> PyResult<Option<Py<PyAny>>> {
+    // Failsafe resolution, styled containers, mapping hooks and interned
+    // values differ from plain JSON values; limits and environment variables
+    // are handled as the YAML path parses. The saphyr backend is only asked
+    // for to parse with it.
+    if options.backend == Backend::Saphyr
+        || options.schema == Schema::Failsafe
+        || options.has_limits()
+        || options.expand_env
+        || options.preserve_flow_style
+        || options.roundtrip
+        || options.mapping_hook.is_some()
+        || options.object_hook.is_some()
+        || options.intern_values
+    {
+        return Ok(None);
+    }
+    let mut parser = JsonParser {
+        b: source.as_bytes(),
+        i: 0,
+        depth: 0,
+        strict: false,
+        check_floats: options.float_precision != FloatPrecision::Ignore,
+    };
+    parser.whitespace();
+    if !matches!(parser.peek(), Some(b'{' | b'[')) {
+        return Ok(None);
+    }
+    let Some(value) = parser.py_value(py)? else {
+        return Ok(None);
+    };
+    parser.whitespace();
+    Ok((parser.i == parser.b.len()).then_some(value))
+}
+
+/// Reads JSON, for the fast path only as far as YAML reads it the same way,
+/// or for `from_json()` strictly as `json.loads()` accepts it
+struct JsonParser<'a> {
+    b: &'a [u8],
+    i: usize,
+    depth: usize,
+    /// Accept all of JSON, NaN and Infinity included, instead of failing on
+    /// what YAML would load differently
+    strict: bool,
+    /// Leave inexact floats to the YAML path, which reports them
+    check_floats: bool,
+}
+
+impl<'a> JsonParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.b.get(self.i).copied()
+    }
+
+    /// Skip spaces and line breaks. Tabs are only skipped when strict, and
+    /// otherwise left to fail the fast path, as YAML only accepts them in
+    /// some positions.
+    fn whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            match byte {
+                b' ' | b'\n' | b'\r' => self.i += 1,
+                b'\t' if self.strict => self.i += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.i += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8, problem: &'static str) -> Result<(), &'static str> {
+        match self.eat(byte) {
+            true => Ok(()),
+            false => Err(problem),
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> Result<(), &'static str> {
+        if !self.b[self.i..].starts_with(word.as_bytes()) {
+            return Err("expected a value");
+        }
+        self.i += word.len();
+        Ok(())
+    }
+
+    /// Step into the object or array opening here
+    fn nested(&mut self) -> Result<(), &'static str> {
+        if self.depth == MAX_DEPTH {
+            return Err("too deeply nested");
+        }
+        self.depth += 1;
+        self.i += 1;
+        self.whitespace();
+        Ok(())
+    }
+
+    /// Read a string starting at the opening quote. Unless strict, strings
+    /// with characters YAML forbids or with escaped surrogates, which libyaml
+    /// combines differently, are rejected too.
+    fn string(&mut self) -> Result<String, &'static str> {
+        self.i += 1;
+        let mut s = String::new();
+        loop {
+            let run_start = self.i;
+            while let Some(byte) = self.peek() {
+                match byte {
+                    b'"' | b'\\' => break,
+                    0..0x20 => return Err("invalid control character in string"),
+                    0x7f if !self.strict => return Err("invalid control character in string"),
+                    _ => self.i += 1,
+                }
+            }
+            // Runs start and end at ASCII bytes, so they are whole characters
+            s.push_str(
+                std::str::from_utf8(&self.b[run_start..self.i]).map_err(|_| "invalid UTF-8")?,
+            );
+            match self.peek() {
+                None => return Err("unterminated string"),
+                Some(b'"') => {
+                    self.i += 1;
+                    if !self.strict && !printable(&s) {
+                        return Err("character YAML does not accept in string");
+                    }
+                    return Ok(s);
+                }
+                Some(_) => {
+                    self.i += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let unit = self.hex4()?;
+                            if self.strict
+                                && (0xD800..0xDC00).contains(&unit)
+                                && self.b[self.i + 1..].starts_with(b"\\u")
+                            {
+                                self.i += 2;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err("invalid surrogate pair");
+                                }
+                                let code = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+                                char::from_u32(code).ok_or("invalid surrogate pair")?
+                            } else {
+                                char::from_u32(unit).ok_or("lone surrogate in string")?
+                            }
+                        }
+                        _ => return Err("invalid escape"),
+                    };
+                    self.i += 1;
+                    s.push(escaped);
+                }
+            }
+        }
+    }
+
+    /// The four hex digits after the `u` of a `\u` escape, leaving the
+    /// parser at the last one
+    fn hex4(&mut self) -> Result<u32, &'static str> {
+        let hex = self
+            .b
+            .get(self.i + 1..self.i + 5)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .ok_or("invalid \\u escape")?;
+        self.i += 4;
+        // Checked to be ASCII hex digits above
+        Ok(u32::from_str_radix(std::str::from_utf8(hex).unwrap_or("0"), 16).unwrap_or(0))
+    }
+
+    /// Read a number, giving its text and whether it is a float. Unless
+    /// strict, exponents are rejected: YAML 1.1 needs a '.' and a signed
+    /// exponent, so they are left to it.
+    fn number(&mut self) -> Result<(&'a str, bool), &'static str> {
+        let start = self.i;
+        let digits = |parser: &mut Self| {
+            let from = parser.i;
+            while matches!(parser.peek(), Some(b'0'..=b'9')) {
+                parser.i += 1;
+            }
+            parser.i > from
+        };
+        self.eat(b'-');
+        if !self.eat(b'0') && !digits(self) {
+            return Err("invalid number");
+        }
+        let mut is_float = false;
+        if self.eat(b'.') {
+            is_float = true;
+            if !digits(self) {
+                return Err("invalid number");
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            if !self.strict {
+                return Err("exponent YAML reads differently");
+            }
+            self.i += 1;
+            is_float = true;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.i += 1;
+            }
+            if !digits(self) {
+                return Err("invalid number");
+            }
+        }
+        // Only ASCII digits and signs were read
+        let text = std::str::from_utf8(&self.b[start..self.i]).unwrap_or_default();
+        Ok((text, is_float))
+    }
+}
+
+// ── Python objects for the fast path ─────────────────────────────────────────
+
+impl JsonParser<'_> {
+    fn py_value(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        let value = match self.peek() {
+            Some(b'{') => return self.py_object(py),
+            Some(b'[') => return self.py_array(py),
+            Some(b'"') => self
+                .string()
+                .map(|s| PyString::new(py, &s).into_any().unbind()),
+            Some(b't') => self
+                .keyword("true")
+                .map(|()| PyBool::new(py, true).to_owned().into_any().unbind()),
+            Some(b'f') => self
+                .keyword("false")
+                .map(|()| PyBool::new(py, false).to_owned().into_any().unbind()),
+            Some(b'n') => self.keyword("null").map(|()| py.None()),
+            _ => return Ok(self.py_number(py)),
+        };
+        Ok(value.ok())
+    }
+
+    fn py_object(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        if self.nested().is_err() {
+            return Ok(None);
+        }
+        let dict = PyDict::new(py);
+        if !self.eat(b'}') {
+            loop {
+                if self.peek() != Some(b'"') {
+                    return Ok(None);
+                }
+                let Ok(key) = self.string() else {
+                    return Ok(None);
+                };
+                self.whitespace();
+                if !self.eat(b':') {
+                    return Ok(None);
+                }
+                self.whitespace();
+                let Some(value) = self.py_value(py)? else {
+                    return Ok(None);
+                };
+                dict.set_item(key, value)?;
+                self.whitespace();
+                if self.eat(b'}') {
+                    break;
+                }
+                if !self.eat(b',') {
+                    return Ok(None);
+                }
+                self.whitespace();
+            }
+        }
+        self.depth -= 1;
+        Ok(Some(dict.into_any().unbind()))
+    }
+
+    fn py_array(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        if self.nested().is_err() {
+            return Ok(None);
+        }
+        let mut items = Vec::new();
+        if !self.eat(b']') {
+            loop {
+                let Some(item) = self.py_value(py)? else {
+                    return Ok(None);
+                };
+                items.push(item);
+                self.whitespace();
+                if self.eat(b']') {
+                    break;
+                }
+                if !self.eat(b',') {
+                    return Ok(None);
+                }
+                self.whitespace();
+            }
+        }
+        self.depth -= 1;
+        Ok(Some(PyList::new(py, items)?.into_any().unbind()))
+    }
+
+    /// Read a number which YAML resolves the same way in every schema
+    fn py_number(&mut self, py: Python) -> Option<Py<PyAny>> {
+        let (text, is_float) = self.number().ok()?;
+        if is_float {
+            let value = text.parse().ok()?;
+            if self.check_floats && !is_exact_float(text, value) {
+                return None;
+            }
+            Some(PyFloat::new(py, value).into_any().unbind())
+        } else {
+            Some(
+                PyInt::new(py, text.parse::<i64>().ok()?)
+                    .into_any()
+                    .unbind(),
+            )
+        }
+    }
+}
+
+/// Whether `s` only has characters YAML accepts in a document. ASCII control
+/// characters are rejected while scanning.
+fn printable(s: &str) -> bool {
+    s.is_ascii()
+        || s.chars().all(
+            |c| !matches!(c, '\u{7f}'..='\u{84}' | '\u{86}'..='\u{9f}' | '\u{fffe}' | '\u{ffff}'),
+        )
+}
+
+// ── JSON values for from_json() ──────────────────────────────────────────────
+
+/// A JSON value, parsed strictly
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    /// An integer, as written, so big ones keep every digit
+    Int(String),
+    Float(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Entries in order; a key given twice keeps its first position and its
+    /// last value, as `json.loads()` does
+    Object(Vec<(String, JsonValue)>),
+}
+
+/// The value of the JSON text `source`, or a message saying where it is invalid
+pub fn parse(source: &str) -> Result<JsonValue, String> {
+    let mut parser = JsonParser {
+        b: source.as_bytes(),
+        i: 0,
+        depth: 0,
+        strict: true,
+        check_floats: false,
+    };
+    parser.whitespace();
+    let value = parser.json_value().and_then(|value| {
+        parser.whitespace();
+        match parser.peek() {
+            None => Ok(value),
+            Some(_) => Err("extra data after the value"),
+        }
+    });
+    value.map_err(|problem| {
+        let before = &source.as_bytes()[..parser.i.min(source.len())];
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let column = before.iter().rev().take_while(|&&b| b != b'\n').count() + 1;
+        format!("invalid JSON: {problem} at line {line}, column {column}")
+    })
+}
+
+impl JsonParser<'_> {
+    fn json_value(&mut self) -> Result<JsonValue, &'static str> {
+        match self.peek() {
+            Some(b'{') => self.json_object(),
+            Some(b'[') => self.json_array(),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b't') => self.keyword("true").map(|()| JsonValue::Bool(true)),
+            Some(b'f') => self.keyword("false").map(|()| JsonValue::Bool(false)),
+            Some(b'n') => self.keyword("null").map(|()| JsonValue::Null),
+            Some(b'N') => self.keyword("NaN").map(|()| JsonValue::Float(f64::NAN)),
+            Some(b'I') => self
+                .keyword("Infinity")
+                .map(|()| JsonValue::Float(f64::INFINITY)),
+            Some(b'-') if self.b[self.i..].starts_with(b"-Infinity") => self
+                .keyword("-Infinity")
+                .map(|()| JsonValue::Float(f64::NEG_INFINITY)),
+            Some(b'-' | b'0'..=b'9') => {
+                let (text, is_float) = self.number()?;
+                Ok(if is_float {
+                    JsonValue::Float(text.parse().map_err(|_| "invalid number")?)
+                } else {
+                    JsonValue::Int(text.to_string())
+                })
+            }
+            None => Err("unexpected end of input"),
+            Some(_) => Err("expected a value"),
+        }
+    }
+
+    fn json_object(&mut self) -> Result<JsonValue, &'static str> {
+        self.nested()?;
+        let mut entries: Vec<(String, JsonValue)> = Vec::new();
+        let mut positions = std::collections::HashMap::new();
+        if !self.eat(b'}') {
+            loop {
+                if self.peek() != Some(b'"') {
+                    return Err("expected a string key");
+                }
+                let key = self.string()?;
+                self.whitespace();
+                self.expect(b':', "expected ':'")?;
+                self.whitespace();
+                let value = self.json_value()?;
+                match positions.get(&key) {
+                    Some(&position) => entries[position] = (key, value),
+                    None => {
+                        positions.insert(key.clone(), entries.len());
+                        entries.push((key, value));
+                    }
+                }
+                self.whitespace();
+                if self.eat(b'}') {
+                    break;
+                }
+                self.expect(b',', "expected ',' or '}'")?;
+                self.whitespace();
+            }
+        }
+        self.depth -= 1;
+        Ok(JsonValue::Object(entries))
+    }
+
+    fn json_array(&mut self) -> Result<JsonValue, &'static str> {
+        self.nested()?;
+        let mut items = Vec::new();
+        if !self.eat(b']') {
+            loop {
+                items.push(self.json_value()?);
+                self.whitespace();
+                if self.eat(b']') {
+                    break;
+                }
+                self.expect(b',', "expected ',' or ']'")?;
+                self.whitespace();
+            }
+        }
+        self.depth -= 1;
+        Ok(JsonValue::Array(items))
+    }
+}
@@ -0,0 +1,358 @@
+//! Lazy containers returned by `loads_lazy()`: mappings and sequences backed
+//! by the composed node tree, constructing a value only when it is first
+//! accessed and keeping it for later accesses. Nested mappings and sequences
+//! are lazy in turn, so reading a few keys of a large document constructs
+//! little more than those keys' values.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use pyo3::exceptions::{PyIndexError, PyKeyError};
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyDict, PyIterator, PyList, PySlice, PyTuple};
+
+use crate::constructor::PyConstructor;
+use crate::loader::make_hashable;
+use crate::nodes::PyNode;
+use crate::registry::Snapshot;
+use crate::state;
+
+/// What the lazy containers of one document share
+struct Document {
+    /// Registered constructors in effect when the document was loaded
+    constructors: Snapshot<String>,
+    /// Constructs the values which aren't lazy, so shared nodes stay shared
+    constructor: Py<PyConstructor>,
+    /// Lazy containers made so far, by node address, so aliases to a node get
+    /// the same container
+    containers: Mutex<HashMap<usize, Py<PyAny>>>,
+}
+
+/// The value of the document with the root `node`
+pub fn load(py: Python, node: PyNode) -> PyResult<Py<PyAny>> {
+    let state = state::get(py)?;
+    let constructors = state.get().registries.constructors.snapshot();
+    let constructor = Py::new(
+        py,
+        PyConstructor::new(state.unbind(), Arc::clone(&constructors)),
+    )?;
+    let document = Arc::new(Document {
+        constructors,
+        constructor,
+        containers: Mutex::new(HashMap::new()),
+    });
+    value(py, &document, &node)
+}
+
+/// The value of `node`: a lazy container for a plain mapping or sequence,
+/// otherwise the object constructed from it
+fn value(py: Python, document: &Arc<Document>, node: &PyNode) -> PyResult<Py<PyAny>> {
+    let tag = node.get_tag(py)?;
+    let lazy = match node {
+        PyNode::Scalar(_) => false,
+        PyNode::Sequence(_) => tag == crate::TAG_SEQ,
+        PyNode::Mapping(_) => tag == crate::TAG_MAP,
+    } && !document.constructors.contains_key(&tag);
+    if !lazy {
+        return PyConstructor::construct(document.constructor.bind(py), node);
+    }
+    let key = node.as_ptr() as usize;
+    if let Some(container) = document.containers.lock().unwrap().get(&key) {
+        return Ok(container.clone_ref(py));
+    }
+    let container = match node {
+        PyNode::Mapping(_) => Py::new(
+            py,
+            LazyMapping {
+                document: Arc::clone(document),
+                node: node.clone(),
+                index: PyOnceLock::new(),
+            },
+        )?
+        .into_any(),
+        PyNode::Sequence(sequence) => {
+            let items = sequence.borrow(py).value.clone();
+            Py::new(
+                py,
+                LazySequence {
+                    document: Arc::clone(document),
+                    node: node.clone(),
+                    values: Mutex::new(vec![None; items.len()]),
+                    items,
+                },
+            )?
+            .into_any()
+        }
+        PyNode::Scalar(_) => unreachable!("scalars are never lazy"),
+    };
+    document
+        .containers
+        .lock()
+        .unwrap()
+        .insert(key, container.clone_ref(py));
+    Ok(container)
+}
+
+/// The keys of a mapping, constructed when it is first used, and its values
+/// as they are constructed
+struct Index {
+    /// Each key, with the position of its value
+    positions: Py<PyDict>,
+    keys: Vec<Py<PyAny>>,
+    nodes: Vec<PyNode>,
+    values: Mutex<Vec<Option<Py<PyAny>>>>,
+}
+
+/// A mapping whose values are constructed as they are accessed
+#[pyclass(name = "LazyMapping", mapping, frozen)]
+pub struct LazyMapping {
+    document: Arc<Document>,
+    node: PyNode,
+    index: PyOnceLock<Index>,
+}
+
+#[pymethods]
+impl LazyMapping {
+    fn __len__(&self, py: Python) -> PyResult<usize> {
+        Ok(self.index(py)?.keys.len())
+    }
+
+    fn __getitem__(&self, py: Python, key: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        let index = self.index(py)?;
+        match index.positions.bind(py).get_item(key)? {
+            Some(position) => self.value(py, index, position.extract()?),
+            None => Err(PyKeyError::new_err(key.clone().unbind())),
+        }
+    }
+
+    fn __contains__(&self, py: Python, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.index(py)?.positions.bind(py).contains(key)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        self.keys(py)?.try_iter()
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn get(
+        &self,
+        py: Python,
+        key: &Bound<'_, PyAny>,
+        default: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let index = self.index(py)?;
+        match index.positions.bind(py).get_item(key)? {
+            Some(position) => self.value(py, index, position.extract()?),
+            None => Ok(default.unwrap_or_else(|| py.None())),
+        }
+    }
+
+    fn keys<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        PyList::new(py, &self.index(py)?.keys)
+    }
+
+    /// The values, constructing those not constructed yet
+    fn values<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let index = self.index(py)?;
+        let values = (0..index.keys.len())
+            .map(|position| self.value(py, index, position))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, values)
+    }
+
+    /// The `(key, value)` pairs, constructing values not constructed yet
+    fn items<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let index = self.index(py)?;
+        let items = index
+            .keys
+            .iter()
+            .enumerate()
+            .map(|(position, key)| {
+                PyTuple::new(py, [key.clone_ref(py), self.value(py, index, position)?])
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, items)
+    }
+
+    /// The whole mapping constructed as a dict, with nothing lazy left in it
+    fn materialize(&self, py: Python) -> PyResult<Py<PyAny>> {
+        PyConstructor::construct(self.document.constructor.bind(py), &self.node)
+    }
+
+    fn __eq__(&self, py: Python, other: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.materialize(py)?.bind(py).eq(other)
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        Ok(format!("<LazyMapping of {} keys>", self.__len__(py)?))
+    }
+}
+
+impl LazyMapping {
+    fn index(&self, py: Python) -> PyResult<&Index> {
+        self.index.get_or_try_init(py, || {
+            let entries = entries(py, &self.document, &self.node, &mut HashSet::new())?;
+            let positions = PyDict::new(py);
+            let mut keys = Vec::with_capacity(entries.len());
+            let mut nodes = Vec::with_capacity(entries.len());
+            for (position, (key, node)) in entries.into_iter().enumerate() {
+                positions.set_item(&key, position)?;
+                keys.push(key);
+                nodes.push(node);
+            }
+            Ok(Index {
+                positions: positions.unbind(),
+                values: Mutex::new(vec![None; keys.len()]),
+                keys,
+                nodes,
+            })
+        })
+    }
+
+    fn value(&self, py: Python, index: &Index, position: usize) -> PyResult<Py<PyAny>> {
+        if let Some(value) = &index.values.lock().unwrap()[position] {
+            return Ok(value.clone_ref(py));
+        }
+        let value = value(py, &self.document, &index.nodes[position])?;
+        index.values.lock().unwrap()[position] = Some(value.clone_ref(py));
+        Ok(value)
+    }
+}
+
+/// The keys of the mapping `node`, constructed, with their value nodes. A key
+/// given twice keeps its first position and its last value; keys merged in
+/// count where the mapping doesn't have them itself, the first mapping merged
+/// taking precedence. `merging` holds the mappings being merged, so merge
+/// cycles end.
+fn entries(
+    py: Python,
+    document: &Document,
+    node: &PyNode,
+    merging: &mut HashSet<usize>,
+) -> PyResult<Vec<(Py<PyAny>, PyNode)>> {
+    let PyNode::Mapping(mapping) = node else {
+        return Ok(Vec::new());
+    };
+    let pairs = mapping.borrow(py).value.clone();
+    let constructor = document.constructor.bind(py);
+    let positions = PyDict::new(py);
+    let mut indexed: Vec<(Py<PyAny>, PyNode)> = Vec::with_capacity(pairs.len());
+    let mut sources = Vec::new();
+    for (key_node, value_node) in pairs {
+        if key_node.get_tag(py)? == crate::TAG_MERGE {
+            match &value_node {
+                PyNode::Sequence(sequence) => sources.extend(sequence.borrow(py).value.clone()),
+                _ => sources.push(value_node),
+            }
+            continue;
+        }
+        let key = make_hashable(py, PyConstructor::construct(constructor, &key_node)?)?;
+        match positions.get_item(&key)? {
+            Some(position) => indexed[position.extract::<usize>()?].1 = value_node,
+            None => {
+                positions.set_item(&key, indexed.len())?;
+                indexed.push((key, value_node));
+            }
+        }
+    }
+    merging.insert(node.as_ptr() as usize);
+    for source in sources {
+        if merging.contains(&(source.as_ptr() as usize)) {
+            continue;
+        }
+        for (key, value_node) in entries(py, document, &source, merging)? {
+            if !positions.contains(&key)? {
+                positions.set_item(&key, indexed.len())?;
+                indexed.push((key, value_node));
+            }
+        }
+    }
+    merging.remove(&(node.as_ptr() as usize));
+    Ok(indexed)
+}
+
+/// A sequence whose items are constructed as they are accessed
+#[pyclass(name = "LazySequence", sequence, frozen)]
+pub struct LazySequence {
+    document: Arc<Document>,
+    node: PyNode,
+    items: Vec<PyNode>,
+    values: Mutex<Vec<Option<Py<PyAny>>>>,
+}
+
+#[pymethods]
+impl LazySequence {
+    fn __len__(&self) -> usize {
+        self.items.len()
+    }
+
+    fn __getitem__(&self, py: Python, index: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let indices = slice.indices(self.items.len() as isize)?;
+            let values = (0..indices.slicelength)
+                .map(|i| self.value(py, (indices.start + i as isize * indices.step) as usize))
+                .collect::<PyResult<Vec<_>>>()?;
+            return Ok(PyList::new(py, values)?.into_any().unbind());
+        }
+        let index: isize = index.extract()?;
+        let len = self.items.len() as isize;
+        let position = if index < 0 { index + len } else { index };
+        if !(0..len).contains(&position) {
+            return Err(PyIndexError::new_err("sequence index out of range"));
+        }
+        self.value(py, position as usize)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        let values = (0..self.items.len())
+            .map(|position| self.value(py, position))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, values)?.try_iter()
+    }
+
+    fn __contains__(&self, py: Python, item: &Bound<'_, PyAny>) -> PyResult<bool> {
+        for position in 0..self.items.len() {
+            if self.value(py, position)?.bind(py).eq(item)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The whole sequence constructed as a list, with nothing lazy left in it
+    fn materialize(&self, py: Python) -> PyResult<Py<PyAny>> {
+        PyConstructor::construct(self.document.constructor.bind(py), &self.node)
+    }
+
+    fn __eq__(&self, py: Python, other: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.materialize(py)?.bind(py).eq(other)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<LazySequence of {} items>", self.items.len())
+    }
+}
+
+impl LazySequence {
+    fn value(&self, py: Python, position: usize) -> PyResult<Py<PyAny>> {
+        if let Some(value) = &self.values.lock().unwrap()[position] {
+            return Ok(value.clone_ref(py));
+        }
+        let value = value(py, &self.document, &self.items[position])?;
+        self.values.lock().unwrap()[position] = Some(value.clone_ref(py));
+        Ok(value)
+    }
+}
+
+pub fn register_lazy(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<LazyMapping>()?;
+    m.add_class::<LazySequence>()?;
+    // Counted as a Mapping and a Sequence by isinstance()
+    let abc = m.py().import("collections.abc")?;
+    abc.getattr("Mapping")?
+        .call_method1("register", (m.getattr("LazyMapping")?,))?;
+    abc.getattr("Sequence")?
+        .call_method1("register", (m.getattr("LazySequence")?,))?;
+    Ok(())
+}
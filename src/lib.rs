@@ -1,9 +1,41 @@
+mod atomic;
+mod backend;
+mod batch;
+mod check;
+mod comments;
+mod compat;
+mod compression;
+mod constructor;
+mod diagnostic;
+mod diff;
 mod dumper;
+mod emitter;
+mod encoding;
+mod env;
+mod event;
 mod exception;
+mod exclusive;
+mod format;
+mod hash;
+mod include;
+mod json;
+mod lazy;
 mod loader;
+mod mapped;
 mod mark;
+mod merge;
 mod nodes;
+mod normalize;
+mod patch;
+mod push;
+mod python;
+mod query;
+mod registry;
 mod resolver;
+mod state;
+mod tagged;
+mod tokens;
+mod typed;
 
 const TAG_NULL: &str = "tag:yaml.org,2002:null";
 const TAG_BOOL: &str = "tag:yaml.org,2002:bool";
@@ -22,23 +54,53 @@ const TAG_VALUE: &str = "tag:yaml.org,2002:value";
 mod _ryaml {
 
     use pyo3::Python;
+    use pyo3::exceptions::{PyKeyError, PyValueError};
     use pyo3::prelude::*;
-    use pyo3::types::PyList;
+    use pyo3::types::{PyBytes, PyDict, PyList, PyString, PyTuple, PyType};
 
-    use crate::dumper::register_dumper;
-    use crate::loader::register_loader;
+    use libyaml_safer::EventData;
+
+    use crate::backend::{Backend, EventParser};
+    use crate::constructor::register_constructor;
+    use crate::diagnostic::{Change, Diagnostic, Edit, register_diagnostics};
+    use crate::dumper::{DumperOptions, register_dumper};
+    use crate::emitter::register_emitter;
+    use crate::encoding::Source;
+    use crate::event::register_events;
+    use crate::exception::register_exceptions;
+    use crate::lazy::register_lazy;
+    use crate::loader::{LoaderOptions, register_loader};
+    use crate::mapped::{MappedFile, register_mapped};
     use crate::mark::register_mark;
+    use crate::nodes::PyNode;
     use crate::nodes::register_nodes;
+    use crate::push::register_push;
+    use crate::tagged::register_tagged;
 
     #[pymodule_export]
-    use crate::exception::InvalidYamlError;
+    use crate::loader::RSafeLoader;
 
     #[pymodule_export]
-    use crate::loader::RSafeLoader;
+    use crate::loader::RBaseLoader;
+
+    #[pymodule_export]
+    use crate::loader::RFullLoader;
+
+    #[pymodule_export]
+    use crate::loader::RUnsafeLoader;
+
+    #[pymodule_export]
+    use crate::loader::{Document, DocumentIterator, DocumentReader};
+
+    #[pymodule_export]
+    use crate::loader::{ParseStats, Resolution};
 
     #[pymodule_export]
     use crate::dumper::RSafeDumper;
 
+    #[pymodule_export]
+    use crate::emitter::REmitter;
+
     #[pymodule_export]
     use crate::mark::PyMark;
 
@@ -51,36 +113,824 @@ mod _ryaml {
     #[pymodule_export]
     use crate::nodes::PyMappingNode;
 
+    #[pymodule_export]
+    use crate::event::PyStreamStartEvent;
+
+    #[pymodule_export]
+    use crate::event::PyStreamEndEvent;
+
+    #[pymodule_export]
+    use crate::event::PyDocumentStartEvent;
+
+    #[pymodule_export]
+    use crate::event::PyDocumentEndEvent;
+
+    #[pymodule_export]
+    use crate::event::PyAliasEvent;
+
+    #[pymodule_export]
+    use crate::event::PyScalarEvent;
+
+    #[pymodule_export]
+    use crate::event::PySequenceStartEvent;
+
+    #[pymodule_export]
+    use crate::event::PySequenceEndEvent;
+
+    #[pymodule_export]
+    use crate::event::PyMappingStartEvent;
+
+    #[pymodule_export]
+    use crate::event::PyMappingEndEvent;
+
+    /// The single document of `str`. A second document fails with a
+    /// ComposerError, unless `allow_multiple` is given to load the first one
+    /// and leave the rest unread.
+    #[pyfunction]
+    #[pyo3(signature = (
+        str, *, explain=false, return_anchors=false, stats=false, allow_multiple=false, **kwargs
+    ))]
+    fn loads(
+        py: Python,
+        str: Source<'_>,
+        explain: bool,
+        return_anchors: bool,
+        stats: bool,
+        allow_multiple: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        load_source(
+            py,
+            str,
+            explain,
+            return_anchors,
+            stats,
+            allow_multiple,
+            false,
+            kwargs,
+        )
+    }
+
+    /// `loads()` for `aload()` to run on a worker thread: `str` is parsed with
+    /// the GIL released, then constructed letting other threads take the GIL
+    /// every millisecond, so the event loop keeps running meanwhile
     #[pyfunction]
-    fn loads(py: Python, str: String) -> PyResult<Option<Py<PyAny>>> {
-        RSafeLoader::new(str).get_single_data(py)
+    #[pyo3(signature = (
+        str, *, explain=false, return_anchors=false, stats=false, allow_multiple=false, **kwargs
+    ))]
+    fn loads_sharing_gil(
+        py: Python,
+        str: Source<'_>,
+        explain: bool,
+        return_anchors: bool,
+        stats: bool,
+        allow_multiple: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        load_source(
+            py,
+            str,
+            explain,
+            return_anchors,
+            stats,
+            allow_multiple,
+            true,
+            kwargs,
+        )
+    }
+
+    /// `loads()`, parsing with the GIL released first with `share_gil`
+    #[allow(clippy::too_many_arguments)]
+    fn load_source(
+        py: Python,
+        str: Source<'_>,
+        explain: bool,
+        return_anchors: bool,
+        stats: bool,
+        allow_multiple: bool,
+        share_gil: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        let started = std::time::Instant::now();
+        let str = str.into_text(py)?;
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        let load = match allow_multiple {
+            true => RSafeLoader::get_data,
+            false => RSafeLoader::get_single_data,
+        };
+        if explain || return_anchors || stats || share_gil {
+            // The JSON fast path parses without recording anything, and the
+            // pooled loader parses holding the GIL
+            let mut loader = match share_gil {
+                true => {
+                    let events = py.detach(|| EventParser::parse_all(options.backend, str.clone()));
+                    let parser = EventParser::Parsed(events.into_iter());
+                    let mut loader = RSafeLoader::with_parser(py, str, options, parser)?;
+                    loader.share_gil();
+                    loader
+                }
+                false => RSafeLoader::new(py, str, options)?,
+            };
+            if explain {
+                loader.explain();
+            }
+            if return_anchors {
+                loader.keep_anchors();
+            }
+            if stats {
+                loader.collect_stats();
+            }
+            let data = load(&mut loader, py)?;
+            if !(explain || return_anchors || stats) {
+                return Ok(data);
+            }
+            let mut result = vec![data.into_pyobject(py)?.unbind()];
+            if explain {
+                result.push(loader.take_resolutions().into_pyobject(py)?.unbind());
+            }
+            if return_anchors {
+                result.push(loader.take_anchors().into_pyobject(py)?.into_any().unbind());
+            }
+            if stats {
+                let mut stats = loader.take_stats();
+                stats.load_time = started.elapsed().as_secs_f64();
+                result.push(Py::new(py, stats)?.into_any());
+            }
+            return Ok(Some(PyTuple::new(py, result)?.into_any().unbind()));
+        }
+        if let Some(data) = crate::json::try_loads(py, &str, &options)? {
+            return Ok(Some(data));
+        }
+        let mut loader = RSafeLoader::pooled(py, str, options)?;
+        let data = load(&mut loader, py)?;
+        loader.release(py);
+        Ok(data)
     }
 
+    /// The document `str`, with its mappings and sequences as `LazyMapping`s
+    /// and `LazySequence`s constructing their values when first accessed.
+    /// Values are constructed from the composed nodes, with the registered
+    /// constructors, as `construct()` does.
     #[pyfunction]
-    fn loads_all(py: Python, str: String) -> PyResult<Option<Py<PyAny>>> {
+    #[pyo3(signature = (str, **kwargs))]
+    fn loads_lazy(
+        py: Python,
+        str: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        let node = RSafeLoader::new(py, str.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)?
+            .get_single_node(py)?;
+        node.map(|node| crate::lazy::load(py, node)).transpose()
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (str, **kwargs))]
+    fn compose(
+        py: Python,
+        str: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<PyNode>> {
+        RSafeLoader::new(py, str.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)?
+            .get_single_node(py)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (str, **kwargs))]
+    fn compose_all(
+        py: Python,
+        str: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<PyNode>> {
+        let mut loader =
+            RSafeLoader::new(py, str.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)?;
+        let mut nodes = Vec::new();
+        while let Some(node) = loader.get_node(py)? {
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (
+        str, *, progress=None, max_workers=None, on_error=None, with_meta=false, **kwargs
+    ))]
+    fn loads_all(
+        py: Python,
+        str: Source<'_>,
+        progress: Option<Bound<'_, PyAny>>,
+        max_workers: Option<usize>,
+        on_error: Option<Bound<'_, PyAny>>,
+        with_meta: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        let workers = crate::batch::worker_count(max_workers)?;
+        let str = str.into_text(py)?;
+        let options = LoaderOptions::from_kwargs(kwargs)?;
         if str.is_empty() {
             Ok(Some(Python::None(py)))
+        } else if !with_meta && let Some(data) = crate::json::try_loads(py, &str, &options)? {
+            if let Some(progress) = &progress {
+                progress.call1((1, str.len()))?;
+            }
+            Ok(Some(PyList::new(py, [data])?.into()))
         } else {
-            let mut loader = RSafeLoader::new(str);
+            // Long streams are parsed on worker threads, then constructed here
+            let mut loader = match crate::batch::parse_documents(py, &str, options.backend, workers)
+            {
+                Some(events) => RSafeLoader::with_parser(
+                    py,
+                    str,
+                    options,
+                    EventParser::Parsed(events.into_iter()),
+                )?,
+                None => RSafeLoader::pooled(py, str, options)?,
+            };
             let mut docs = Vec::new();
-            while loader.check_data(py)? {
-                docs.push(loader.get_data(py)?)
+            loop {
+                let document = match loader.check_data(py) {
+                    Ok(false) => break,
+                    Ok(true) => loader.get_data(py),
+                    Err(error) => Err(error),
+                };
+                match (document, &on_error) {
+                    (Ok(document), _) if with_meta => {
+                        let data = document.unwrap_or_else(|| py.None());
+                        let document = Py::new(py, loader.document_with_meta(data))?;
+                        docs.push(Some(document.into_any()));
+                    }
+                    (Ok(document), _) => docs.push(document),
+                    // Documents failing to load are reported and left out
+                    (Err(error), Some(on_error)) => {
+                        on_error.call1((error.into_value(py),))?;
+                        if !loader.skip_document() {
+                            break;
+                        }
+                        continue;
+                    }
+                    (Err(error), None) => return Err(error),
+                }
+                // Report (documents_loaded, bytes_consumed) after each document
+                if let Some(progress) = &progress {
+                    progress.call1((docs.len(), loader.bytes_consumed()))?;
+                }
             }
+            loader.release(py);
             Ok(Some(PyList::new(py, docs)?.into()))
         }
     }
 
     #[pyfunction]
-    fn dumps(py: Python, obj: Py<PyAny>) -> PyResult<String> {
-        crate::dumper::dumps_to_string(py, obj.bind(py))
+    #[pyo3(signature = (str, **kwargs))]
+    fn loads_iter(
+        py: Python,
+        str: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<DocumentIterator> {
+        DocumentIterator::new(py, str.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (fp, **kwargs))]
+    fn load_iter(
+        py: Python,
+        fp: Py<PyAny>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<DocumentIterator> {
+        DocumentIterator::from_file(py, fp, LoaderOptions::from_kwargs(kwargs)?)
+    }
+
+    /// The rest of the file open as `fd`, from byte `offset`, mapped into
+    /// memory; None if it is better read
+    #[pyfunction]
+    fn map_file(py: Python, fd: i32, offset: u64) -> Option<MappedFile> {
+        crate::mapped::map_file(py, fd, offset)
+    }
+
+    /// The rest of the file open as `fd`, after `prefix`, to pass to `loads()`
+    #[pyfunction]
+    #[pyo3(signature = (fd, prefix, max_bytes=None))]
+    fn read_fd(py: Python, fd: i32, prefix: &[u8], max_bytes: Option<u64>) -> PyResult<Py<PyAny>> {
+        crate::mapped::read_fd(py, fd, prefix, max_bytes)
+    }
+
+    /// The file at `path`, to pass to `loads()`, mapped into memory if
+    /// `mmap` asks and it can be
+    #[pyfunction]
+    #[pyo3(signature = (path, mmap=false))]
+    fn read_path(py: Python, path: std::path::PathBuf, mmap: bool) -> PyResult<Py<PyAny>> {
+        crate::mapped::read_path(py, &path, mmap)
+    }
+
+    /// Write `data` to the file at `path`, replacing it; with `atomic`, by
+    /// renaming a complete copy over it
+    #[pyfunction]
+    #[pyo3(signature = (path, data, atomic=false))]
+    fn write_path(
+        py: Python,
+        path: std::path::PathBuf,
+        data: &Bound<'_, PyAny>,
+        atomic: bool,
+    ) -> PyResult<()> {
+        // Text is written as UTF-8, as dumps() encodes it by default
+        let data = match data.downcast::<PyString>() {
+            Ok(text) => text.to_str()?.as_bytes(),
+            Err(_) => data.downcast::<PyBytes>()?.as_bytes(),
+        };
+        if atomic {
+            py.detach(|| crate::atomic::write(&path, data))?;
+        } else {
+            py.detach(|| std::fs::write(&path, data))?;
+        }
+        Ok(())
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (paths, *, max_workers=None, **kwargs))]
+    fn load_files<'py>(
+        py: Python<'py>,
+        paths: Vec<Bound<'py, PyAny>>,
+        max_workers: Option<usize>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let named = match kwargs {
+            Some(kwargs) => kwargs.contains("name")?,
+            None => false,
+        };
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        crate::batch::load_files(py, paths, max_workers, options, named)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (value, *, schema="1.1"))]
+    fn scalar_kind(value: &str, schema: &str) -> PyResult<Option<&'static str>> {
+        Ok(crate::resolver::scalar_kind(
+            value,
+            crate::resolver::Schema::from_name(schema)?,
+        ))
+    }
+
+    /// The single document of `s` decoded into `type`: a dataclass, a
+    /// TypedDict, or a generic such as `list[int]`. Unknown and missing
+    /// fields, and scalars of the wrong type, raise a ConstructorError.
+    #[pyfunction]
+    #[pyo3(signature = (s, r#type, **kwargs))]
+    fn loads_into(
+        py: Python,
+        s: Source<'_>,
+        r#type: &Bound<'_, PyAny>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyAny>> {
+        let schema = crate::typed::Schema::new(r#type)?;
+        let mut loader =
+            RSafeLoader::new(py, s.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)?;
+        crate::typed::load_into(py, &mut loader, &schema)
+    }
+
+    /// The first value at `path` in the documents of `s`, constructing only
+    /// what it needs; KeyError if there is none
+    #[pyfunction]
+    #[pyo3(signature = (s, path, **kwargs))]
+    fn select(
+        py: Python,
+        s: Source<'_>,
+        path: &str,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyAny>> {
+        let steps = crate::query::parse_path(path)?;
+        let mut loader =
+            RSafeLoader::new(py, s.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)?;
+        crate::query::select(py, &mut loader, &steps, true)?
+            .pop()
+            .ok_or_else(|| PyKeyError::new_err(path.to_string()))
+    }
+
+    /// Every value at `path` in the documents of `s`, in document order
+    #[pyfunction]
+    #[pyo3(signature = (s, path, **kwargs))]
+    fn select_all(
+        py: Python,
+        s: Source<'_>,
+        path: &str,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<Py<PyAny>>> {
+        let steps = crate::query::parse_path(path)?;
+        let mut loader =
+            RSafeLoader::new(py, s.into_text(py)?, LoaderOptions::from_kwargs(kwargs)?)?;
+        crate::query::select(py, &mut loader, &steps, false)
+    }
+
+    /// The problems of the YAML text `s` as a list of `Diagnostic`s, found
+    /// by parsing it without constructing any object. With `recover`, parsing
+    /// goes on after syntax errors to find the ones after them.
+    #[pyfunction]
+    #[pyo3(signature = (s, *, name=None, backend="libyaml", recover=false))]
+    fn check(
+        py: Python,
+        s: Source<'_>,
+        name: Option<String>,
+        backend: &str,
+        recover: bool,
+    ) -> PyResult<Vec<Py<Diagnostic>>> {
+        let name = name.unwrap_or_else(|| crate::mark::DEFAULT_SOURCE_NAME.to_string());
+        let backend = Backend::from_name(backend)?;
+        crate::check::check(py, s.into_text(py)?, &name, backend, recover)
+    }
+
+    /// The problems of the file at `path`, as `check()` finds them; marks are
+    /// named after the path unless `name` is given
+    #[pyfunction]
+    #[pyo3(signature = (path, *, name=None, backend="libyaml", recover=false))]
+    fn check_file(
+        py: Python,
+        path: std::path::PathBuf,
+        name: Option<String>,
+        backend: &str,
+        recover: bool,
+    ) -> PyResult<Vec<Py<Diagnostic>>> {
+        let name = name.unwrap_or_else(|| path.to_string_lossy().into_owned());
+        let backend = Backend::from_name(backend)?;
+        crate::check::check_file(py, &path, &name, backend, recover)
+    }
+
+    #[pyfunction]
+    fn apply_edits(text: &str, edits: Vec<Edit>) -> PyResult<String> {
+        crate::diagnostic::apply_edits(text, &edits)
+    }
+
+    #[pyfunction]
+    fn quote_if_needed(py: Python, value: &str) -> PyResult<String> {
+        crate::dumper::quote_if_needed(py, value)
+    }
+
+    /// Register `representer` to dump instances of `data_type` and its subclasses.
+    /// It is called with the object and returns a node.
+    #[pyfunction]
+    fn add_representer(
+        py: Python,
+        data_type: &Bound<'_, PyType>,
+        representer: Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        crate::state::get(py)?
+            .get()
+            .registries
+            .add_representer(py, data_type, representer)
+    }
+
+    /// Register `constructor` to load nodes tagged `tag`, e.g. `"!Ref"` or `"!!python/none"`.
+    /// It is called with a `Constructor` and the node, and returns the value.
+    #[pyfunction]
+    fn add_constructor(py: Python, tag: &str, constructor: Bound<'_, PyAny>) -> PyResult<()> {
+        crate::state::get(py)?
+            .get()
+            .registries
+            .add_constructor(py, tag, constructor)
+    }
+
+    /// Resolve plain scalars matching `pattern` to `tag`, both when loading and
+    /// when dumping. `first` lists the characters matching values can start with.
+    #[pyfunction]
+    #[pyo3(signature = (tag, pattern, first=None))]
+    fn add_implicit_resolver(
+        py: Python,
+        tag: &str,
+        pattern: &Bound<'_, PyAny>,
+        first: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        crate::state::get(py)?
+            .get()
+            .registries
+            .add_implicit_resolver(py, tag, pattern, first)
+    }
+
+    /// Load mappings tagged `cls.yaml_tag` as instances of `cls` and dump its
+    /// instances with that tag, as `YAMLObject` subclasses do automatically.
+    #[pyfunction]
+    fn add_yaml_object(py: Python, cls: &Bound<'_, PyType>) -> PyResult<()> {
+        crate::state::get(py)?
+            .get()
+            .registries
+            .add_yaml_object(py, cls)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (str, *, backend="libyaml"))]
+    fn parse(py: Python, str: Source<'_>, backend: &str) -> PyResult<Vec<Py<PyAny>>> {
+        let text = str.into_text(py)?;
+        let mut parser = EventParser::new(Backend::from_name(backend)?, text.clone());
+        let mut events = Vec::new();
+        let mark = |m| PyMark::with_source(m, crate::mark::DEFAULT_SOURCE_NAME, &text);
+        loop {
+            let event = parser
+                .parse()
+                .map_err(|e| crate::exception::parse_error(py, &e, mark))?;
+            let is_stream_end = matches!(event.data, EventData::StreamEnd);
+            events.push(crate::event::event_to_py(py, event, mark)?);
+            if is_stream_end {
+                return Ok(events);
+            }
+        }
+    }
+
+    /// `str` emitted again in one consistent style: indented by `indent`,
+    /// wrapped at `width`, quoted in single quotes unless escapes are
+    /// needed, with block collections
+    /// unless `preserve_flow_style` is set. Comments around block mapping
+    /// entries are kept with `comments`.
+    #[pyfunction]
+    #[pyo3(signature = (str, *, indent=2, width=100, preserve_flow_style=false, comments=true))]
+    fn format(
+        py: Python,
+        str: Source<'_>,
+        indent: i32,
+        width: i32,
+        preserve_flow_style: bool,
+        comments: bool,
+    ) -> PyResult<String> {
+        let options = crate::format::FormatOptions {
+            indent,
+            width,
+            preserve_flow_style,
+            comments,
+        };
+        crate::format::format(py, str.into_text(py)?, &options)
+    }
+
+    /// `(kind, start, end)` of each token of `str`, with offsets counting
+    /// characters, for highlighting and folding in editors
+    #[pyfunction]
+    fn tokenize_spans<'py>(py: Python<'py>, str: Source<'_>) -> PyResult<Bound<'py, PyList>> {
+        crate::tokens::tokenize_spans(py, str.into_text(py)?)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (events, stream=None))]
+    fn emit(
+        py: Python,
+        events: Bound<'_, PyAny>,
+        stream: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Option<String>> {
+        let output = crate::emitter::emit_to_string(py, &events)?;
+        match stream {
+            Some(stream) => {
+                stream.call_method1("write", (output,))?;
+                Ok(None)
+            }
+            None => Ok(Some(output)),
+        }
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (obj, *, dry_run=false, **kwargs))]
+    fn dumps(
+        py: Python,
+        obj: Py<PyAny>,
+        dry_run: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyAny>> {
+        let options = DumperOptions::from_kwargs(kwargs)?;
+        if dry_run {
+            let plan = crate::dumper::dump_plan(py, obj.bind(py), options)?;
+            return Ok(Bound::new(py, plan)?.into_any().unbind());
+        }
+        crate::dumper::dumps(py, obj.bind(py), options)
+    }
+
+    /// The JSON text `json` written as YAML, parsed and dumped without making
+    /// Python objects of it. Takes the keyword arguments of `dumps()`.
+    #[pyfunction]
+    #[pyo3(signature = (json, **kwargs))]
+    fn from_json(
+        py: Python,
+        json: &str,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyAny>> {
+        let options = DumperOptions::from_kwargs(kwargs)?;
+        let value = py
+            .detach(|| crate::json::parse(json))
+            .map_err(PyValueError::new_err)?;
+        crate::dumper::from_json(py, &value, options)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (objs, **kwargs))]
+    fn dumps_all(
+        py: Python,
+        objs: &Bound<'_, PyAny>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyAny>> {
+        let options = DumperOptions::from_kwargs(kwargs)?;
+        crate::dumper::dumps_all(py, objs, options)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (objs, *, max_workers=None, **kwargs))]
+    fn dumps_many(
+        py: Python,
+        objs: Vec<Bound<'_, PyAny>>,
+        max_workers: Option<usize>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<Py<PyAny>>> {
+        let options = DumperOptions::from_kwargs(kwargs)?;
+        crate::dumper::dumps_many(py, &objs, max_workers, options)
+    }
+
+    /// `stream`, YAML text or a file, loaded as pyyaml's `load()` does, with
+    /// an instance of `Loader`; without one, it loads as `loads()` does
+    #[pyfunction]
+    #[pyo3(signature = (stream, Loader=None))]
+    #[allow(non_snake_case)]
+    fn compat_load(
+        py: Python,
+        stream: &Bound<'_, PyAny>,
+        Loader: Option<&Bound<'_, PyType>>,
+    ) -> PyResult<Py<PyAny>> {
+        crate::compat::load(py, stream, Loader)
+    }
+
+    /// Every document of `stream`, as pyyaml's `load_all()` gives them
+    #[pyfunction]
+    #[pyo3(signature = (stream, Loader=None))]
+    #[allow(non_snake_case)]
+    fn compat_load_all(
+        py: Python,
+        stream: &Bound<'_, PyAny>,
+        Loader: Option<&Bound<'_, PyType>>,
+    ) -> PyResult<Vec<Py<PyAny>>> {
+        crate::compat::load_all(py, stream, Loader)
+    }
+
+    /// `documents` dumped as pyyaml's `dump_all()` does, with an instance of
+    /// `Dumper`, `_RSafeDumper` by default
+    #[pyfunction]
+    #[pyo3(signature = (documents, stream=None, Dumper=None, **kwargs))]
+    #[allow(non_snake_case)]
+    fn compat_dump_all(
+        py: Python,
+        documents: &Bound<'_, PyAny>,
+        stream: Option<&Bound<'_, PyAny>>,
+        Dumper: Option<&Bound<'_, PyType>>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        crate::compat::dump_all(py, documents, stream, Dumper, kwargs)
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (obj, indent_level=0, **kwargs))]
+    fn dumps_fragment(
+        py: Python,
+        obj: Py<PyAny>,
+        indent_level: usize,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<String> {
+        let options = DumperOptions::from_kwargs(kwargs)?;
+        crate::dumper::dumps_fragment(py, obj.bind(py), indent_level, options)
+    }
+
+    #[pyfunction]
+    fn construct(py: Python, node: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        crate::constructor::construct_node(py, node)
+    }
+
+    /// The changes from document `a` to document `b`, YAML texts or nodes,
+    /// as `Change`s with the path and marks of each
+    #[pyfunction]
+    #[pyo3(signature = (a, b, **kwargs))]
+    fn diff(
+        py: Python,
+        a: &Bound<'_, PyAny>,
+        b: &Bound<'_, PyAny>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<Py<Change>>> {
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        let old = crate::merge::document_node(py, a, &options)?;
+        let new = crate::merge::document_node(py, b, &options)?;
+        crate::diff::Differ::new(py)?
+            .diff(old.as_ref(), new.as_ref())?
+            .into_iter()
+            .map(|change| Py::new(py, change))
+            .collect()
+    }
+
+    /// A hex digest of the data `data` holds, YAML text loaded with
+    /// `kwargs` or an object, ignoring formatting and, with `sort_keys`, the
+    /// order of mapping keys
+    #[pyfunction]
+    #[pyo3(signature = (data, *, algorithm="blake3", sort_keys=true, **kwargs))]
+    fn content_hash(
+        py: Python,
+        data: &Bound<'_, PyAny>,
+        algorithm: &str,
+        sort_keys: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<String> {
+        let algorithm = crate::hash::Algorithm::from_name(algorithm)?;
+        let mut hasher = crate::hash::ContentHasher::new(algorithm, sort_keys);
+        match data.extract::<Source>() {
+            Ok(source) => {
+                let options = LoaderOptions::from_kwargs(kwargs)?;
+                let value = RSafeLoader::new(py, source.into_text(py)?, options)?
+                    .get_single_data(py)?
+                    .unwrap_or_else(|| py.None());
+                hasher.hex_digest(value.bind(py))
+            }
+            Err(_) => hasher.hex_digest(data),
+        }
+    }
+
+    /// `str` in a canonical form for hashing and comparison: aliases
+    /// expanded, mapping keys sorted, block style and the emitter's quoting
+    /// throughout
+    #[pyfunction]
+    #[pyo3(signature = (str, **kwargs))]
+    fn normalize(
+        py: Python,
+        str: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<String> {
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        crate::normalize::normalize(py, str.into_text(py)?, options)
+    }
+
+    /// `str` with `patch` applied at the node level and serialized again:
+    /// a list of JSON Patch (RFC 6902) operations, or a dict applied as a
+    /// JSON Merge Patch (RFC 7386). Nodes left untouched keep their styles,
+    /// and with `comments` the comments around block mapping entries too.
+    #[pyfunction]
+    #[pyo3(signature = (str, patch, *, comments=true, **kwargs))]
+    fn patch(
+        py: Python,
+        str: Source<'_>,
+        patch: &Bound<'_, PyAny>,
+        comments: bool,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<String> {
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        crate::patch::patch(py, str.into_text(py)?, patch, options, comments)
+    }
+
+    /// `documents`, YAML texts or nodes, merged in order, each over the
+    /// ones before it, and constructed
+    #[pyfunction]
+    #[pyo3(signature = (documents, *, strategy=None, lists="replace", list_key=None, **kwargs))]
+    fn merge(
+        py: Python,
+        documents: &Bound<'_, PyAny>,
+        strategy: Option<&Bound<'_, PyAny>>,
+        lists: &str,
+        list_key: Option<String>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        let node = merge_nodes(py, documents, strategy, lists, list_key, kwargs)?;
+        node.map(|node| crate::constructor::construct_node(py, &node.into_pyobject(py)?))
+            .transpose()
+    }
+
+    /// `documents`, YAML texts or nodes, merged in order, each over the
+    /// ones before it, as a node
+    #[pyfunction]
+    #[pyo3(signature = (documents, *, strategy=None, lists="replace", list_key=None, **kwargs))]
+    fn merge_nodes(
+        py: Python,
+        documents: &Bound<'_, PyAny>,
+        strategy: Option<&Bound<'_, PyAny>>,
+        lists: &str,
+        list_key: Option<String>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Option<PyNode>> {
+        let merger = crate::merge::Merger::new(strategy, lists, list_key)?;
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        let nodes = crate::merge::document_nodes(py, documents, &options)?;
+        merger.merge(py, nodes)
+    }
+
+    #[pyfunction]
+    fn serialize(py: Python, node: PyNode) -> PyResult<String> {
+        crate::dumper::serialize_to_string(py, &[node])
+    }
+
+    #[pyfunction]
+    fn serialize_all(py: Python, nodes: Vec<PyNode>) -> PyResult<String> {
+        crate::dumper::serialize_to_string(py, &nodes)
     }
 
     #[pymodule_init]
     fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        m.add("BACKENDS", crate::backend::BACKENDS.to_vec())?;
+        m.add("COMPRESSIONS", crate::compression::COMPRESSIONS.to_vec())?;
+        crate::state::init(m)?;
+        register_exceptions(m)?;
         register_nodes(m)?;
+        register_events(m)?;
         register_loader(m)?;
+        register_constructor(m)?;
+        register_tagged(m)?;
         register_mark(m)?;
         register_dumper(m)?;
+        register_emitter(m)?;
+        register_diagnostics(m)?;
+        register_mapped(m)?;
+        register_lazy(m)?;
+        register_push(m)?;
         Ok(())
     }
 }
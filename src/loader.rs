@@ -1,38 +1,393 @@
 //! Module implement pyyaml compatibility layer for ryaml via libyaml
 //! Implements RLoader, which can load YAML 1.1
 
-use libyaml_safer::{Event, EventData, Parser};
-use pyo3::exceptions::PyNotImplementedError;
+use base64::Engine as _;
+use base64::prelude::BASE64_STANDARD;
+use libyaml_safer::{Event, EventData, MappingStyle, ScalarStyle, SequenceStyle};
+use pyo3::exceptions::{PyNotImplementedError, PyTypeError, PyUserWarning, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString};
+use pyo3::types::{
+    PyBool, PyBytes, PyDate, PyDateTime, PyDelta, PyDict, PyFloat, PyInt, PyList, PyString,
+    PyTuple, PyType, PyTzInfo,
+};
 use rustc_hash::FxBuildHasher;
-use std::collections::HashMap;
-use std::io::Cursor;
-
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::backend::{Backend, EventParser};
+use crate::comments::SourceLines;
+use crate::constructor::{self, Overrides, PyConstructor};
+use crate::encoding::{Source, SourceText};
+use crate::event::{mapping_flow_style, scalar_style_to_char, sequence_flow_style};
 use crate::exception;
-use crate::resolver;
+use crate::exclusive::Exclusive;
+use crate::include;
+use crate::mark::{DEFAULT_SOURCE_NAME, PyMark};
+use crate::nodes::{PyMappingNode, PyNode, PyScalarNode, PySequenceNode};
+use crate::python::{self, Content, PythonTags};
+use crate::registry::Snapshot;
+use crate::resolver::{self, ResolverCache, Resolvers, Schema};
+use crate::state::{self, ModuleState};
+use crate::tagged::PyTaggedValue;
+
+/// Longest mapping key, in bytes, shared between equal keys
+const MAX_SHARED_KEY_LEN: usize = 64;
+/// Most distinct mapping keys shared per loader; keys past it are made anew
+const MAX_SHARED_KEYS: usize = 4096;
+/// Longest str value, in bytes, shared between equal values with `intern_values`
+const MAX_INTERNED_VALUE_LEN: usize = 256;
+/// Most distinct str values shared per loader with `intern_values`
+const MAX_INTERNED_VALUES: usize = 65536;
+/// Longest the GIL is held in a row while constructing, after `share_gil()`
+const GIL_SLICE: Duration = Duration::from_millis(1);
+
+/// Options accepted as keyword arguments by `loads()`, `loads_all()` and `_RSafeLoader`
+#[derive(Debug, Clone)]
+pub struct LoaderOptions {
+    /// Parser backend producing events
+    pub backend: Backend,
+    /// Accept NaN mapping keys, which can never be looked up again
+    pub allow_nan_keys: bool,
+    /// Source name reported in marks, such as the path of the file being loaded
+    pub name: String,
+    /// Resolve plain date-like scalars as timestamps; when off they stay strings
+    pub parse_datetimes: bool,
+    /// Construct `StyledList`/`StyledDict` recording each container's flow style
+    pub preserve_flow_style: bool,
+    /// As `preserve_flow_style`, also recording container anchors and loading
+    /// quoted and block strings as `StyledStr`, so dumping the result keeps the
+    /// source's layout
+    pub roundtrip: bool,
+    /// Rules used to resolve the types of plain scalars
+    pub schema: Schema,
+    /// What to do with float literals which can't be represented exactly
+    pub float_precision: FloatPrecision,
+    /// Load nodes with tags no constructor knows as `TaggedValue`s instead of
+    /// their plain scalar, list or dict
+    pub preserve_unknown_tags: bool,
+    /// `!!python/...` tags to construct; only set by the loader classes for them
+    pub python_tags: PythonTags,
+    /// Construct tagged nodes as if they had no tag, so with the failsafe schema
+    /// every scalar stays a string; only set by `_RBaseLoader`
+    pub ignore_tags: bool,
+    /// Keep anchors defined in a document visible to aliases in the documents
+    /// after it, instead of resetting them at each document end
+    pub global_anchors: bool,
+    /// Let an anchor be defined again, rebinding it; when off a second
+    /// definition is an error
+    pub allow_duplicate_anchors: bool,
+    /// Load scalar mapping keys as strings of their source text, so `1: a`
+    /// gives `{"1": "a"}`
+    pub string_keys: bool,
+    /// Treat plain `<<` keys as merge keys; None follows the schema, merging
+    /// only with YAML 1.1 where `<<` is a type rather than a plain string
+    pub merge_keys: Option<bool>,
+    /// Called with the list of (key, value) pairs of each mapping to make the
+    /// object loaded for it, set by `object_pairs_hook` or `mapping_type`
+    pub mapping_hook: Option<Py<PyAny>>,
+    /// Called with each dict once constructed, to make the object loaded in
+    /// its place; `mapping_hook` takes precedence, as in the json module
+    pub object_hook: Option<Py<PyAny>>,
+    /// Deepest nesting of collections allowed before loading fails
+    pub max_depth: Option<usize>,
+    /// Most nodes (scalars, aliases and collections) a document may hold
+    pub max_nodes: Option<usize>,
+    /// Longest stream, in bytes of its text, allowed to be loaded
+    pub max_bytes: Option<usize>,
+    /// Replace `${VAR}` references in scalars with environment variables
+    pub expand_env: bool,
+    /// The only environment variables `expand_env` may read, if given
+    pub env_allowlist: Option<HashSet<String>>,
+    /// Directory `!include` scalars may load files from; None leaves them
+    /// as tagged scalars
+    pub include_dir: Option<PathBuf>,
+    /// Deepest nesting of included files allowed
+    pub max_include_depth: usize,
+    /// Files being included, from the outermost; only set for the loaders of
+    /// included files
+    pub include_chain: Vec<PathBuf>,
+    /// Warn about plain scalars resolved by YAML 1.1 rules which YAML 1.2
+    /// reads differently
+    pub warn_ambiguous: bool,
+    /// Share one str object between equal str values of a stream, cutting
+    /// memory for documents repeating the same values
+    pub intern_values: bool,
+}
+
+impl Default for LoaderOptions {
+    fn default() -> Self {
+        Self {
+            backend: Backend::Libyaml,
+            allow_nan_keys: true,
+            name: DEFAULT_SOURCE_NAME.to_string(),
+            parse_datetimes: true,
+            preserve_flow_style: false,
+            roundtrip: false,
+            schema: Schema::Yaml11,
+            float_precision: FloatPrecision::Ignore,
+            preserve_unknown_tags: false,
+            python_tags: PythonTags::Ignore,
+            ignore_tags: false,
+            global_anchors: false,
+            allow_duplicate_anchors: true,
+            string_keys: false,
+            merge_keys: None,
+            mapping_hook: None,
+            object_hook: None,
+            max_depth: None,
+            max_nodes: None,
+            max_bytes: None,
+            expand_env: false,
+            env_allowlist: None,
+            include_dir: None,
+            max_include_depth: include::DEFAULT_MAX_DEPTH,
+            warn_ambiguous: false,
+            intern_values: false,
+            include_chain: Vec::new(),
+        }
+    }
+}
+
+impl LoaderOptions {
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut options = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok(options);
+        };
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "backend" => options.backend = Backend::from_name(&value.extract::<String>()?)?,
+                "allow_nan_keys" => options.allow_nan_keys = value.extract()?,
+                "parse_datetimes" => options.parse_datetimes = value.extract()?,
+                "preserve_flow_style" => options.preserve_flow_style = value.extract()?,
+                "roundtrip" => options.roundtrip = value.extract()?,
+                "preserve_unknown_tags" => options.preserve_unknown_tags = value.extract()?,
+                "global_anchors" => options.global_anchors = value.extract()?,
+                "allow_duplicate_anchors" => options.allow_duplicate_anchors = value.extract()?,
+                "string_keys" => options.string_keys = value.extract()?,
+                "merge_keys" => options.merge_keys = value.extract()?,
+                "max_depth" => options.max_depth = value.extract()?,
+                "max_nodes" => options.max_nodes = value.extract()?,
+                "max_bytes" => options.max_bytes = value.extract()?,
+                "expand_env" => options.expand_env = value.extract()?,
+                "warn_ambiguous" => options.warn_ambiguous = value.extract()?,
+                "intern_values" => options.intern_values = value.extract()?,
+                "env_allowlist" if !value.is_none() => {
+                    let names = value
+                        .try_iter()?
+                        .map(|name| name?.extract())
+                        .collect::<PyResult<_>>()?;
+                    options.env_allowlist = Some(names);
+                }
+                "env_allowlist" => {}
+                "include_dir" => options.include_dir = value.extract()?,
+                "max_include_depth" => options.max_include_depth = value.extract()?,
+                "object_pairs_hook" | "mapping_type" if !value.is_none() => {
+                    if !value.is_callable() {
+                        return Err(PyValueError::new_err(format!("{key} must be callable")));
+                    }
+                    if options.mapping_hook.is_some() {
+                        return Err(PyTypeError::new_err(
+                            "object_pairs_hook and mapping_type can't be given together",
+                        ));
+                    }
+                    options.mapping_hook = Some(value.unbind());
+                }
+                "object_pairs_hook" | "mapping_type" => {}
+                "object_hook" => {
+                    if !value.is_none() && !value.is_callable() {
+                        return Err(PyValueError::new_err("object_hook must be callable"));
+                    }
+                    options.object_hook = (!value.is_none()).then(|| value.unbind());
+                }
+                "schema" => options.schema = Schema::from_name(&value.extract::<String>()?)?,
+                "float_precision" => {
+                    options.float_precision =
+                        FloatPrecision::from_name(&value.extract::<String>()?)?
+                }
+                "name" => {
+                    if !value.is_none() {
+                        options.name = value.extract()?;
+                    }
+                }
+                _ => {
+                    return Err(PyTypeError::new_err(format!(
+                        "unexpected keyword argument '{key}'"
+                    )));
+                }
+            }
+        }
+        if options.env_allowlist.is_some() && !options.expand_env {
+            return Err(PyValueError::new_err("env_allowlist needs expand_env=True"));
+        }
+        Ok(options)
+    }
+
+    /// Fail if options which only apply when constructing straight from
+    /// events are set for a loader whose overridden `methods` construct from
+    /// nodes instead
+    fn check_overrides(&self, methods: &[&str]) -> PyResult<()> {
+        let ignored: Vec<&str> = [
+            ("allow_nan_keys=False", !self.allow_nan_keys),
+            ("preserve_flow_style", self.preserve_flow_style),
+            ("roundtrip", self.roundtrip),
+            (
+                "float_precision",
+                self.float_precision != FloatPrecision::Ignore,
+            ),
+            ("preserve_unknown_tags", self.preserve_unknown_tags),
+            ("string_keys", self.string_keys),
+            ("object_pairs_hook", self.mapping_hook.is_some()),
+            ("object_hook", self.object_hook.is_some()),
+            ("intern_values", self.intern_values),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect();
+        if ignored.is_empty() {
+            return Ok(());
+        }
+        Err(PyTypeError::new_err(format!(
+            "{} can't be used with a loader overriding {}",
+            ignored.join(", "),
+            methods.join(", ")
+        )))
+    }
+
+    /// Whether plain `<<` keys merge mappings
+    pub fn merge_keys(&self) -> bool {
+        self.merge_keys.unwrap_or(self.schema == Schema::Yaml11)
+    }
+
+    /// Whether any of the `max_*` limits on the input is set
+    pub fn has_limits(&self) -> bool {
+        self.max_depth.is_some() || self.max_nodes.is_some() || self.max_bytes.is_some()
+    }
+}
+
+/// Handling of float literals whose exact value an f64 can't hold, such as `0.1`
+/// or monetary amounts like `19.99`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatPrecision {
+    /// Load the nearest float
+    #[default]
+    Ignore,
+    /// Load the nearest float, emitting a `UserWarning`
+    Warn,
+    /// Raise a `ConstructorError`
+    Error,
+}
+
+impl FloatPrecision {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "ignore" => Ok(FloatPrecision::Ignore),
+            "warn" => Ok(FloatPrecision::Warn),
+            "error" => Ok(FloatPrecision::Error),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported float precision mode '{name}' (expected 'ignore', 'warn' or 'error')"
+            ))),
+        }
+    }
+}
 
+/// Loader of the documents of one stream. It is used by one thread at a time;
+/// others calling it while a document loads get a RuntimeError.
 #[pyclass(name = "_RSafeLoader", subclass)]
 pub struct RSafeLoader {
-    /// Parser over an in-memory string passed by Python
-    parser: Parser<Cursor<String>>,
+    /// Parser over an in-memory string passed by Python, or over a file object
+    parser: EventParser,
+    /// Source text, shared with the marks attached to composed nodes; None
+    /// when reading a file object as it is parsed
+    source: Option<SourceText>,
     /// Event used by internal parser
     parsed_event: Option<Event>,
     /// Anchors mapping anchor name to constructed Python object
     anchors: HashMap<String, Py<PyAny>, FxBuildHasher>,
+    /// Anchors mapping anchor name to composed node
+    node_anchors: HashMap<String, PyNode, FxBuildHasher>,
+    /// Tag handles in effect for the current document, mapping handle to prefix
+    tag_handles: HashMap<String, String>,
+    /// `%YAML` version the current document declares
+    version_directive: Option<(i32, i32)>,
+    /// Tag handles the current document declares with `%TAG`, mapping handle
+    /// to prefix
+    tag_directives: HashMap<String, String>,
+    /// Where the current document starts, and where the last one ended
+    document_start: libyaml_safer::Mark,
+    document_end: libyaml_safer::Mark,
+    /// str mapping keys loaded so far, shared by later keys with the same text
+    keys: HashMap<String, Py<PyString>, FxBuildHasher>,
+    /// str values loaded so far with `intern_values`, shared by later equal values
+    values: HashMap<String, Py<PyString>, FxBuildHasher>,
+    /// Behavior switches set at construction
+    options: LoaderOptions,
+    /// Registered constructors, snapshotted at the start of each document
+    constructors: Snapshot<String>,
+    /// Classes registered with `add_yaml_object()`, snapshotted with the constructors
+    yaml_objects: Snapshot<String>,
+    /// Constructor of the document being loaded through overridden methods
+    active_constructor: Option<Py<PyConstructor>>,
+    /// Registered implicit resolvers, snapshotted at the start of each document
+    implicit_resolvers: Resolvers,
+    /// State of the module in the interpreter the loader was made in
+    state: Py<ModuleState>,
+    /// What the implicit resolvers made of plain scalars seen so far
+    resolved: ResolverCache,
+    /// Where the last parsed event ended
+    position: libyaml_safer::Mark,
+    /// Where in the source the parser started, after starting over past a
+    /// syntax error; the marks it gives are shifted by this
+    origin: libyaml_safer::Mark,
+    /// Where the syntax error the parser stopped at is, if it did
+    parse_failed: Option<libyaml_safer::Mark>,
+    /// Last (character, byte) offset pair resolved by `bytes_consumed()`
+    byte_cursor: (u64, usize),
+    /// Plain scalars resolved to a type other than str, recorded only when
+    /// `loads(explain=True)` asks for them
+    resolutions: Option<Vec<Resolution>>,
+    /// Objects constructed for the anchors of the documents loaded, kept only
+    /// when `loads(return_anchors=True)` asks for them
+    kept_anchors: Option<HashMap<String, Py<PyAny>>>,
+    /// Counts of what has been parsed, kept only when `loads(stats=True)`
+    /// asks for them
+    stats: Option<ParseStats>,
+    /// Source lines searched for comments, with `roundtrip`
+    comment_lines: Option<SourceLines>,
+    /// Last line holding a scalar or alias parsed so far, with `roundtrip`
+    content_line: Option<u64>,
+    /// Collections open around the event last parsed, counted when
+    /// `max_depth` or another limit is set
+    depth: usize,
+    /// Nodes parsed in the current document, counted as `depth` is
+    nodes: usize,
+    /// Where each anchor in scope was defined, recorded unless
+    /// `allow_duplicate_anchors` is set
+    anchor_marks: HashMap<String, libyaml_safer::Mark, FxBuildHasher>,
+    /// When the GIL was last let go to other threads, kept only after
+    /// `share_gil()`
+    gil_taken: Option<Instant>,
+    /// Thread loading a document, across calls to Python overrides
+    exclusive: Exclusive,
 }
 
 #[pymethods]
 impl RSafeLoader {
     #[new]
-    pub fn new(source: String) -> Self {
-        let mut parser = Parser::new();
-        parser.set_input(Cursor::new(source));
-        Self {
-            parser,
-            parsed_event: None,
-            anchors: HashMap::with_hasher(FxBuildHasher),
-        }
+    #[pyo3(signature = (source, **kwargs))]
+    pub fn py_new(
+        py: Python,
+        source: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Self> {
+        Self::new(
+            py,
+            source.into_text(py)?,
+            LoaderOptions::from_kwargs(kwargs)?,
+        )
     }
 
     pub fn peek_token(&self) -> PyResult<()> {
@@ -59,56 +414,140 @@ impl RSafeLoader {
     }
 
     /// Get the next document as a Python object
-    pub fn get_data(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
-        if self.check_node(py)? {
-            return self.construct_document(py);
+    #[pyo3(name = "get_data")]
+    fn py_get_data(slf: &Bound<'_, Self>) -> PyResult<Option<Py<PyAny>>> {
+        let _guard = slf.try_borrow()?.exclusive.enter("loader")?;
+        let methods = Self::overridden_methods(slf)?;
+        if methods.is_empty() {
+            return slf.try_borrow_mut()?.get_data(slf.py());
         }
-        Ok(None)
+        slf.try_borrow()?.options.check_overrides(&methods)?;
+        let node = slf.try_borrow_mut()?.get_node(slf.py())?;
+        node.map(|node| Self::construct_overridden(slf, &node))
+            .transpose()
     }
 
     /// Get a single document as a Python object
-    pub fn get_single_data(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
-        // Eat stream start event
-        self._parse_next_event(py)?;
-        self.parsed_event = None;
+    #[pyo3(name = "get_single_data")]
+    fn py_get_single_data(slf: &Bound<'_, Self>) -> PyResult<Option<Py<PyAny>>> {
+        let _guard = slf.try_borrow()?.exclusive.enter("loader")?;
+        let methods = Self::overridden_methods(slf)?;
+        if methods.is_empty() {
+            return slf.try_borrow_mut()?.get_single_data(slf.py());
+        }
+        slf.try_borrow()?.options.check_overrides(&methods)?;
+        let node = slf.try_borrow_mut()?.get_single_node(slf.py())?;
+        node.map(|node| Self::construct_overridden(slf, &node))
+            .transpose()
+    }
 
-        // Get document
-        self._parse_next_event(py)?;
-        let document = if !matches!(
-            &self.parsed_event,
-            Some(Event {
-                data: EventData::StreamEnd,
-                ..
-            })
-        ) {
-            self.construct_document(py)?
-        } else {
-            None
-        };
+    /// The string value of a scalar node
+    fn construct_scalar(&self, py: Python, node: PyNode) -> PyResult<String> {
+        match node {
+            PyNode::Scalar(node) => Ok(node.borrow(py).value.clone()),
+            _ => Err(constructor::expected(py, "a scalar", &node)),
+        }
+    }
 
-        // Make sure there are no more documents
-        self._parse_next_event(py)?;
-        if !matches!(
-            &self.parsed_event,
-            Some(Event {
-                data: EventData::StreamEnd,
-                ..
-            })
-        ) {
-            return Err(exception::composer_error(
-                py,
-                "expected a single document in the stream, but found another document".to_string(),
-            ));
+    /// Construct the items of a sequence node as a list. With `deep`,
+    /// two-step constructors of the items run to the end before this returns.
+    #[pyo3(signature = (node, deep=false))]
+    fn construct_sequence(slf: &Bound<'_, Self>, node: PyNode, deep: bool) -> PyResult<Py<PyAny>> {
+        match node {
+            PyNode::Sequence(_) => Self::with_constructor(slf, deep, |constructor| {
+                PyConstructor::construct_builtin(constructor, &node)
+            }),
+            _ => Err(constructor::expected(slf.py(), "a sequence", &node)),
+        }
+    }
+
+    /// Construct the entries of a mapping node as a dict, applying merge keys
+    #[pyo3(signature = (node, deep=false))]
+    fn construct_mapping(slf: &Bound<'_, Self>, node: PyNode, deep: bool) -> PyResult<Py<PyAny>> {
+        match node {
+            PyNode::Mapping(_) => Self::with_constructor(slf, deep, |constructor| {
+                PyConstructor::construct_builtin(constructor, &node)
+            }),
+            _ => Err(constructor::expected(slf.py(), "a mapping", &node)),
         }
+    }
 
-        Ok(document)
+    /// Construct any node, dispatching on its tag
+    #[pyo3(signature = (node, deep=false))]
+    fn construct_object(slf: &Bound<'_, Self>, node: PyNode, deep: bool) -> PyResult<Py<PyAny>> {
+        Self::with_constructor(slf, deep, |constructor| {
+            PyConstructor::construct(constructor, &node)
+        })
     }
 
-    pub fn dispose(&self) {}
-}
+    /// The tag a node of class `kind` holding `value` resolves to: plain
+    /// scalars by the schema and implicit resolvers in effect, anything else
+    /// by its kind. `implicit` is `(plain, quoted)` for scalars, as in pyyaml.
+    fn resolve(
+        &self,
+        py: Python,
+        kind: &Bound<'_, PyType>,
+        value: &Bound<'_, PyAny>,
+        implicit: &Bound<'_, PyAny>,
+    ) -> PyResult<String> {
+        if kind.is_subclass_of::<PyScalarNode>()? {
+            let (plain, _) = implicit.extract::<(bool, bool)>()?;
+            let value: String = value.extract()?;
+            Ok(self.resolve_scalar_tag(py, &value, plain)?.to_string())
+        } else if kind.is_subclass_of::<PySequenceNode>()? {
+            Ok(resolver::DEFAULT_SEQUENCE_TAG.to_string())
+        } else if kind.is_subclass_of::<PyMappingNode>()? {
+            Ok(resolver::DEFAULT_MAPPING_TAG.to_string())
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "expected a node class, got {}",
+                kind.name()?
+            )))
+        }
+    }
 
-impl RSafeLoader {
-    fn check_node(&mut self, py: Python) -> PyResult<bool> {
+    fn construct_yaml_null(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_NULL)
+    }
+
+    fn construct_yaml_bool(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_BOOL)
+    }
+
+    fn construct_yaml_int(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_INT)
+    }
+
+    fn construct_yaml_float(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_FLOAT)
+    }
+
+    fn construct_yaml_binary(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_BINARY)
+    }
+
+    fn construct_yaml_timestamp(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_TIMESTAMP)
+    }
+
+    fn construct_yaml_str(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Self::construct_yaml_scalar(slf, node, crate::TAG_STR)
+    }
+
+    fn construct_yaml_seq(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Ok(slf.call_method1("construct_sequence", (node,))?.unbind())
+    }
+
+    fn construct_yaml_map(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Ok(slf.call_method1("construct_mapping", (node,))?.unbind())
+    }
+
+    fn construct_yaml_set(slf: &Bound<'_, Self>, node: PyNode) -> PyResult<Py<PyAny>> {
+        Ok(slf.call_method1("construct_mapping", (node,))?.unbind())
+    }
+
+    /// Check if there's another document available
+    pub fn check_node(&mut self, py: Python) -> PyResult<bool> {
         self._parse_next_event(py)?;
         if matches!(
             &self.parsed_event,
@@ -132,73 +571,1466 @@ impl RSafeLoader {
         Ok(true)
     }
 
-    /// Parse the next event if needed
-    fn _parse_next_event(&mut self, py: Python) -> PyResult<()> {
-        if self.parsed_event.is_none() {
-            match self.parser.parse() {
-                Ok(event) => {
-                    self.parsed_event = Some(event);
-                }
-                Err(e) => return Err(exception::scanner_error(py, format!("{}", e))),
-            }
+    /// Get the next document as a node tree
+    pub fn get_node(&mut self, py: Python) -> PyResult<Option<PyNode>> {
+        if self.check_node(py)? {
+            return self.compose_document(py);
         }
+        Ok(None)
+    }
+
+    /// Get a single document as a node tree
+    pub fn get_single_node(&mut self, py: Python) -> PyResult<Option<PyNode>> {
+        self.single_document(py, Self::compose_document)
+    }
+
+    pub fn dispose(&self) {}
+
+    /// Register `constructor` for nodes tagged `tag`. Constructors are shared by
+    /// all loaders and `loads()`, as with the module-level `add_constructor()`.
+    #[classmethod]
+    pub fn add_constructor(
+        cls: &Bound<'_, PyType>,
+        tag: &str,
+        constructor: Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        let py = cls.py();
+        state::get(py)?
+            .get()
+            .registries
+            .add_constructor(py, tag, constructor)
+    }
+
+    /// Resolve plain scalars matching `pattern` to `tag`. Resolvers are shared by
+    /// all loaders and dumpers, as with the module-level `add_implicit_resolver()`.
+    #[classmethod]
+    #[pyo3(signature = (tag, pattern, first=None))]
+    pub fn add_implicit_resolver(
+        cls: &Bound<'_, PyType>,
+        tag: &str,
+        pattern: &Bound<'_, PyAny>,
+        first: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let py = cls.py();
+        state::get(py)?
+            .get()
+            .registries
+            .add_implicit_resolver(py, tag, pattern, first)
+    }
+
+    /// Load `source` from now on, with the same options, as a new loader
+    /// would. Tables keep their allocations, which saves time when loading
+    /// many small documents.
+    pub fn set_input(&mut self, py: Python, source: Source<'_>) -> PyResult<()> {
+        self.restart(source.into_text(py)?);
         Ok(())
     }
 
-    /// Construct a document directly from events
-    fn construct_document(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
-        // Eat document start event
+    /// Load `source` from now on, as `set_input()` does; named like the
+    /// dumper's `reset()`, for services reusing both
+    pub fn reset(&mut self, py: Python, source: Source<'_>) -> PyResult<()> {
+        self.set_input(py, source)
+    }
+
+    /// Drop all cached construction state and release its memory
+    pub fn reset_state(&mut self) {
+        self.anchors = HashMap::with_hasher(FxBuildHasher);
+        self.node_anchors = HashMap::with_hasher(FxBuildHasher);
+        self.keys = HashMap::with_hasher(FxBuildHasher);
+        self.values = HashMap::with_hasher(FxBuildHasher);
+        self.anchor_marks = HashMap::with_hasher(FxBuildHasher);
+        self.resolved.clear();
+    }
+
+    /// Tag handles declared for the current document, mapping handle to URI prefix.
+    /// Tags on events and nodes are already expanded using these.
+    #[getter]
+    pub fn tag_handles(&self) -> HashMap<String, String> {
+        self.tag_handles.clone()
+    }
+
+    /// Number of entries held in each internal cache
+    #[getter]
+    pub fn cache_sizes(&self) -> HashMap<&'static str, usize> {
+        HashMap::from([
+            ("anchors", self.anchors.len()),
+            ("node_anchors", self.node_anchors.len()),
+            ("keys", self.keys.len()),
+            ("values", self.values.len()),
+            ("anchor_marks", self.anchor_marks.len()),
+            ("resolved", self.resolved.remembered()),
+        ])
+    }
+}
+
+impl RSafeLoader {
+    /// Get the next document as a Python object
+    pub fn get_data(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        if self.check_node(py)? {
+            return self.construct_document(py);
+        }
+        Ok(None)
+    }
+
+    /// Get a single document as a Python object
+    pub fn get_single_data(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        self.single_document(py, Self::construct_document)
+    }
+
+    /// The `construct_*` methods the Python class of `slf` overrides
+    fn overridden_methods(slf: &Bound<'_, Self>) -> PyResult<Vec<&'static str>> {
+        let base = slf.py().get_type::<RSafeLoader>();
+        let class = slf.get_type();
+        let mut methods = Vec::new();
+        if class.is(&base) {
+            return Ok(methods);
+        }
+        for name in constructor::OVERRIDABLE {
+            if !class.getattr(name)?.is(&base.getattr(name)?) {
+                methods.push(*name);
+            }
+        }
+        Ok(methods)
+    }
+
+    /// Run `construct` with the constructor of the document being loaded, or
+    /// outside of one with a new constructor, finishing what it leaves pending
+    fn with_constructor(
+        slf: &Bound<'_, Self>,
+        deep: bool,
+        construct: impl FnOnce(&Bound<'_, PyConstructor>) -> PyResult<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let active = slf
+            .try_borrow()?
+            .active_constructor
+            .as_ref()
+            .map(|constructor| constructor.bind(slf.py()).clone());
+        if let Some(constructor) = active {
+            return PyConstructor::constructing(&constructor, deep, || construct(&constructor));
+        }
+        let constructor = Self::new_constructor(slf)?;
+        let obj = PyConstructor::constructing(&constructor, deep, || construct(&constructor))?;
+        PyConstructor::finish_pending(&constructor)?;
+        Ok(obj)
+    }
+
+    fn new_constructor<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyConstructor>> {
+        let methods = Self::overridden_methods(slf)?;
+        let overrides = (!methods.is_empty()).then(|| Overrides {
+            loader: slf.clone().into_any().unbind(),
+            methods,
+        });
+        let (state, constructors) = {
+            let this = slf.try_borrow()?;
+            (
+                this.state.clone_ref(slf.py()),
+                Arc::clone(&this.constructors),
+            )
+        };
+        Bound::new(
+            slf.py(),
+            PyConstructor::with_overrides(state, constructors, overrides),
+        )
+    }
+
+    /// Construct a composed document through the methods a Python subclass
+    /// overrides. Registered constructors still apply; loader options only
+    /// applying to construction from events are refused by `check_overrides()`.
+    fn construct_overridden(slf: &Bound<'_, Self>, node: &PyNode) -> PyResult<Py<PyAny>> {
+        let constructor = Self::new_constructor(slf)?;
+        slf.try_borrow_mut()?.active_constructor = Some(constructor.clone().unbind());
+        let result = PyConstructor::construct_document(&constructor, node);
+        slf.try_borrow_mut()?.active_constructor = None;
+        result
+    }
+
+    /// Default `construct_yaml_*` method for scalars tagged `tag`, reading the
+    /// value through a possibly overridden `construct_scalar()`
+    fn construct_yaml_scalar(
+        slf: &Bound<'_, Self>,
+        node: PyNode,
+        tag: &str,
+    ) -> PyResult<Py<PyAny>> {
+        let value: String = slf.call_method1("construct_scalar", (node,))?.extract()?;
+        construct_scalar_value(slf.py(), tag, &value)
+    }
+
+    pub fn new(
+        py: Python,
+        source: impl Into<SourceText>,
+        options: LoaderOptions,
+    ) -> PyResult<Self> {
+        let source = source.into();
+        let parser = EventParser::new(options.backend, source.clone());
+        Self::with_parser(py, source, options, parser)
+    }
+
+    /// A loader taking its events from `parser`, which must parse `source`
+    pub fn with_parser(
+        py: Python,
+        source: SourceText,
+        options: LoaderOptions,
+        parser: EventParser,
+    ) -> PyResult<Self> {
+        let mut loader = Self::with_events(py, options, parser)?;
+        loader.comment_lines = loader
+            .options
+            .roundtrip
+            .then(|| SourceLines::new(source.clone()));
+        loader.source = Some(source);
+        Ok(loader)
+    }
+
+    /// A loader reading the file object `file` as it parses. Marks carry no
+    /// snippet of the source, and `roundtrip` keeps no comments, as the text
+    /// isn't held. With `follow`, the end of the file is waited for more
+    /// input, polling at that interval, instead of ending the stream.
+    pub fn from_file(
+        py: Python,
+        file: Py<PyAny>,
+        options: LoaderOptions,
+        follow: Option<Duration>,
+    ) -> PyResult<Self> {
+        let parser = EventParser::from_file(options.backend, file, options.max_bytes, follow);
+        Self::with_events(py, options, parser)
+    }
+
+    fn with_events(py: Python, options: LoaderOptions, parser: EventParser) -> PyResult<Self> {
+        let state = state::get(py)?;
+        let registries = &state.get().registries;
+        Ok(Self {
+            comment_lines: None,
+            content_line: None,
+            exclusive: Exclusive::default(),
+            source: None,
+            parser,
+            parsed_event: None,
+            anchors: HashMap::with_hasher(FxBuildHasher),
+            node_anchors: HashMap::with_hasher(FxBuildHasher),
+            tag_handles: HashMap::new(),
+            version_directive: None,
+            tag_directives: HashMap::new(),
+            document_start: libyaml_safer::Mark::default(),
+            document_end: libyaml_safer::Mark::default(),
+            keys: HashMap::with_hasher(FxBuildHasher),
+            values: HashMap::with_hasher(FxBuildHasher),
+            options,
+            constructors: registries.constructors.snapshot(),
+            yaml_objects: registries.yaml_object_classes.snapshot(),
+            active_constructor: None,
+            implicit_resolvers: registries.implicit_resolvers(),
+            state: state.clone().unbind(),
+            resolved: ResolverCache::default(),
+            position: libyaml_safer::Mark::default(),
+            origin: libyaml_safer::Mark::default(),
+            parse_failed: None,
+            byte_cursor: (0, 0),
+            resolutions: None,
+            kept_anchors: None,
+            stats: None,
+            depth: 0,
+            nodes: 0,
+            anchor_marks: HashMap::with_hasher(FxBuildHasher),
+            gil_taken: None,
+        })
+    }
+
+    /// A loader for `source`, reusing the one the last load released
+    pub fn pooled(
+        py: Python,
+        source: impl Into<SourceText>,
+        options: LoaderOptions,
+    ) -> PyResult<Self> {
+        match state::get(py)?.get().take_loader() {
+            Some(mut loader) => {
+                loader.options = options;
+                loader.restart(source.into());
+                Ok(loader)
+            }
+            None => Self::new(py, source, options),
+        }
+    }
+
+    /// Keep this loader for the next `pooled()`, without the source and
+    /// Python objects it holds
+    pub fn release(mut self, py: Python) {
+        self.parser = EventParser::Parsed(Vec::new().into_iter());
+        self.source = None;
+        self.comment_lines = None;
+        self.parsed_event = None;
+        self.anchors.clear();
+        self.node_anchors.clear();
+        self.anchor_marks.clear();
+        self.tag_handles.clear();
+        self.tag_directives.clear();
+        self.keys.clear();
+        self.values.clear();
+        self.resolved.clear();
+        self.active_constructor = None;
+        self.resolutions = None;
+        self.kept_anchors = None;
+        self.stats = None;
+        self.gil_taken = None;
+        self.options = LoaderOptions::default();
+        let state = self.state.clone_ref(py);
+        state.get().put_loader(self);
+    }
+
+    /// Start over on `source`, keeping the options and the tables' allocations
+    fn restart(&mut self, source: SourceText) {
+        self.parser = EventParser::new(self.options.backend, source.clone());
+        self.comment_lines = self
+            .options
+            .roundtrip
+            .then(|| SourceLines::new(source.clone()));
+        self.source = Some(source);
+        self.content_line = None;
         self.parsed_event = None;
+        self.anchors.clear();
+        self.node_anchors.clear();
+        self.tag_handles.clear();
+        self.version_directive = None;
+        self.tag_directives.clear();
+        self.document_start = libyaml_safer::Mark::default();
+        self.document_end = libyaml_safer::Mark::default();
+        self.keys.clear();
+        self.values.clear();
+        self.resolved.clear();
+        self.snapshot_registries();
+        self.active_constructor = None;
+        self.position = libyaml_safer::Mark::default();
+        self.origin = libyaml_safer::Mark::default();
+        self.parse_failed = None;
+        self.byte_cursor = (0, 0);
+        self.resolutions = None;
+        self.kept_anchors = None;
+        self.stats = None;
+        self.depth = 0;
+        self.nodes = 0;
+        self.anchor_marks.clear();
+        self.gil_taken = None;
+    }
 
-        // Construct the root object directly from events
-        self._parse_next_event(py)?;
-        let result = self.construct_from_events(py)?;
+    /// Record every plain scalar resolved to a type other than str
+    pub fn explain(&mut self) {
+        self.resolutions = Some(Vec::new());
+    }
 
-        // Eat document end event
+    /// The resolutions recorded since `explain()`
+    pub fn take_resolutions(&mut self) -> Vec<Resolution> {
+        self.resolutions.take().unwrap_or_default()
+    }
+
+    /// Keep the objects constructed for anchors as each document ends
+    pub fn keep_anchors(&mut self) {
+        self.kept_anchors = Some(HashMap::new());
+    }
+
+    /// The objects constructed for anchors since `keep_anchors()`, by anchor
+    /// name; a name defined again maps to its last node
+    pub fn take_anchors(&mut self) -> HashMap<String, Py<PyAny>> {
+        self.kept_anchors.take().unwrap_or_default()
+    }
+
+    /// Count what is parsed from here on
+    pub fn collect_stats(&mut self) {
+        self.stats = Some(ParseStats::default());
+    }
+
+    /// The counts made since `collect_stats()`
+    pub fn take_stats(&mut self) -> ParseStats {
+        self.stats.take().unwrap_or_default()
+    }
+
+    /// Let other threads take the GIL every `GIL_SLICE` from here on, so a
+    /// load on a worker thread doesn't stop the one calling it
+    pub fn share_gil(&mut self) {
+        self.gil_taken = Some(Instant::now());
+    }
+
+    /// Let other threads take the GIL, if sharing it and it has been held
+    /// for a slice
+    fn yield_gil(&mut self, py: Python) {
+        if let Some(taken) = self.gil_taken
+            && taken.elapsed() >= GIL_SLICE
+        {
+            py.detach(|| {});
+            self.gil_taken = Some(Instant::now());
+        }
+    }
+
+    /// A record of `value` resolving to `tag`, if explaining and `tag` isn't str
+    fn resolution(&self, value: &str, tag: &str, mark: libyaml_safer::Mark) -> Option<Resolution> {
+        if self.resolutions.is_none() || tag == resolver::DEFAULT_SCALAR_TAG {
+            return None;
+        }
+        Some(Resolution {
+            value: value.to_string(),
+            tag: tag.to_string(),
+            mark: self.mark(mark),
+        })
+    }
+
+    /// Warn that plain `value`, resolved to `tag` at `mark`, changes meaning
+    /// with YAML 1.2, if asked to
+    fn check_ambiguous(
+        &self,
+        py: Python,
+        value: &str,
+        tag: &str,
+        mark: libyaml_safer::Mark,
+    ) -> PyResult<()> {
+        if !self.options.warn_ambiguous || self.options.schema != Schema::Yaml11 {
+            return Ok(());
+        }
+        let Some(change) = resolver::yaml12_change(value, tag) else {
+            return Ok(());
+        };
+        let message = format!(
+            "{change}\n  in \"{}\", line {}, column {}",
+            self.options.name,
+            mark.line + 1,
+            mark.column + 1
+        );
+        warn_user(py, message)
+    }
+
+    /// Number of bytes of the UTF-8 source the parser has consumed so far;
+    /// characters when reading a file object
+    pub fn bytes_consumed(&mut self) -> usize {
+        let target = self.position.index;
+        let Some(source) = &self.source else {
+            return target as usize;
+        };
+        let (chars, bytes) = if target < self.byte_cursor.0 {
+            (0, 0)
+        } else {
+            self.byte_cursor
+        };
+        // Walk forward from the last resolved offset so repeated calls stay linear
+        let offset = source[bytes..]
+            .char_indices()
+            .nth((target - chars) as usize)
+            .map_or(source.len(), |(offset, _)| bytes + offset);
+        self.byte_cursor = (target, offset);
+        offset
+    }
+
+    /// Convert a parser mark into a Python mark pointing into the source
+    pub fn mark(&self, mark: libyaml_safer::Mark) -> PyMark {
+        match &self.source {
+            Some(source) => PyMark::with_source(mark, &self.options.name, source),
+            None => PyMark {
+                name: self.options.name.clone(),
+                ..PyMark::from(mark)
+            },
+        }
+    }
+
+    /// Resolve the implicit tag of an untagged scalar, honoring the loader options.
+    /// Fails for plain scalars the schema doesn't allow; the caller adds the mark.
+    fn resolve_scalar_tag(&self, py: Python, value: &str, plain_implicit: bool) -> PyResult<&str> {
+        // `<<` is a merge key exactly when merge keys are on, whatever the schema
+        if plain_implicit && value == "<<" {
+            if self.options.merge_keys() {
+                return Ok(crate::TAG_MERGE);
+            }
+            if self.options.schema == Schema::Yaml11 {
+                return Ok(resolver::DEFAULT_SCALAR_TAG);
+            }
+        }
+        match self.resolved.resolve(
+            py,
+            value,
+            plain_implicit,
+            self.options.schema,
+            &self.implicit_resolvers,
+        )? {
+            Some(crate::TAG_TIMESTAMP) if !self.options.parse_datetimes => {
+                Ok(resolver::DEFAULT_SCALAR_TAG)
+            }
+            Some(tag) => Ok(tag),
+            None => Err(exception::marked_error::<exception::ComposerError>(
+                py,
+                None,
+                None,
+                format!("found plain scalar '{value}' which is not valid in the JSON schema"),
+                None,
+            )),
+        }
+    }
+
+    /// Raise a composer error pointing at `mark`
+    fn composer_error_at(&self, py: Python, problem: String, mark: libyaml_safer::Mark) -> PyErr {
+        exception::marked_error::<exception::ComposerError>(
+            py,
+            None,
+            None,
+            problem,
+            Some(self.mark(mark)),
+        )
+    }
+
+    /// Read exactly one document from the stream with `load`, rejecting any further documents
+    fn single_document<T>(
+        &mut self,
+        py: Python,
+        load: fn(&mut Self, Python) -> PyResult<Option<T>>,
+    ) -> PyResult<Option<T>> {
+        // Eat stream start event
         self._parse_next_event(py)?;
         self.parsed_event = None;
 
-        // Clear anchors for next document
-        self.anchors.clear();
+        // Get document
+        self._parse_next_event(py)?;
+        let document_mark = self.parsed_event.as_ref().map(|e| e.start_mark);
+        let document = if !matches!(
+            &self.parsed_event,
+            Some(Event {
+                data: EventData::StreamEnd,
+                ..
+            })
+        ) {
+            load(self, py)?
+        } else {
+            None
+        };
+
+        // Make sure there are no more documents
+        self._parse_next_event(py)?;
+        if !matches!(
+            &self.parsed_event,
+            Some(Event {
+                data: EventData::StreamEnd,
+                ..
+            })
+        ) {
+            return Err(exception::marked_error::<exception::ComposerError>(
+                py,
+                Some("expected a single document in the stream".to_string()),
+                document_mark.map(|m| self.mark(m)),
+                "but found another document".to_string(),
+                self.parsed_event.as_ref().map(|e| self.mark(e.start_mark)),
+            ));
+        }
+
+        Ok(document)
+    }
+
+    /// Parse the next event if needed
+    fn _parse_next_event(&mut self, py: Python) -> PyResult<()> {
+        if self.parsed_event.is_none() {
+            self.yield_gil(py);
+            let started = self.stats.is_some().then(Instant::now);
+            match self.parser.parse() {
+                Ok(mut event) => {
+                    event.start_mark = self.shift(event.start_mark);
+                    event.end_mark = self.shift(event.end_mark);
+                    if let (Some(stats), Some(started)) = (&mut self.stats, started) {
+                        stats.parse_time += started.elapsed().as_secs_f64();
+                        stats.record(&event);
+                    }
+                    if self.options.has_limits() {
+                        self.check_limits(py, &event)?;
+                    }
+                    if !self.options.allow_duplicate_anchors {
+                        self.check_anchor(py, &event)?;
+                    }
+                    if self.options.expand_env {
+                        self.expand_env(py, &mut event)?;
+                    }
+                    self.position = event.end_mark;
+                    if self.comment_lines.is_some() {
+                        self.track_content_line(&event);
+                    }
+                    self.parsed_event = Some(event);
+                }
+                Err(e) => {
+                    self.parse_failed =
+                        Some(e.problem_mark().map_or(self.position, |m| self.shift(m)));
+                    return Err(self.parser.take_read_error().unwrap_or_else(|| {
+                        exception::parse_error(py, &e, |m| self.mark(self.shift(m)))
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the environment variable references in the value of `event`,
+    /// if it is a scalar
+    fn expand_env(&self, py: Python, event: &mut Event) -> PyResult<()> {
+        let EventData::Scalar { value, .. } = &mut event.data else {
+            return Ok(());
+        };
+        match crate::env::expand(py, value, self.options.env_allowlist.as_ref())? {
+            Ok(Some(expanded)) => *value = expanded,
+            Ok(None) => {}
+            Err(problem) => {
+                return Err(exception::marked_error::<exception::ConstructorError>(
+                    py,
+                    Some("while expanding environment variables".to_string()),
+                    None,
+                    problem,
+                    Some(self.mark(event.start_mark)),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// A loader for the file named by the `!include` scalar `path`, found at `mark`
+    fn include(&self, py: Python, path: &str, mark: libyaml_safer::Mark) -> PyResult<Self> {
+        let error = |problem| {
+            exception::marked_error::<exception::ConstructorError>(
+                py,
+                Some("while including a file".to_string()),
+                None,
+                problem,
+                Some(self.mark(mark)),
+            )
+        };
+        let (dir, chain) = (&self.options.include_dir, &self.options.include_chain);
+        let max_depth = self.options.max_include_depth;
+        let file = match dir {
+            Some(dir) => py.detach(|| include::resolve(dir, chain, max_depth, path)),
+            None => unreachable!("only scalars are included with include_dir"),
+        }
+        .map_err(error)?;
+        let source = py
+            .detach(|| crate::mapped::read_file(&file))
+            .map_err(|e| error(format!("can't include '{path}': {e}")))?;
+        let mut options = self.options.clone();
+        options.name = file.display().to_string();
+        options.include_chain.push(file);
+        Self::new(py, source, options)
+    }
+
+    /// Whether the scalar tagged `tag` names a file to include
+    fn is_include(&self, tag: &Option<String>) -> bool {
+        self.options.include_dir.is_some() && tag.as_deref() == Some(include::TAG_INCLUDE)
+    }
+
+    /// `mark` given by the parser, as a mark of the whole source
+    fn shift(&self, mut mark: libyaml_safer::Mark) -> libyaml_safer::Mark {
+        mark.index += self.origin.index;
+        mark.line += self.origin.line;
+        mark
+    }
+
+    /// Skip the rest of the document whose loading failed, so the next one
+    /// can be loaded. After a syntax error the parser can't go on, so a new
+    /// one starts at the next line starting a document. False when there is
+    /// no document left to go on with.
+    pub fn skip_document(&mut self) -> bool {
+        self.active_constructor = None;
+        self.depth = 0;
+        if !self.options.global_anchors {
+            self.anchors.clear();
+            self.node_anchors.clear();
+        }
+        let mut event = self.parsed_event.take();
+        loop {
+            match event.as_ref().map(|event| &event.data) {
+                Some(EventData::DocumentEnd { .. }) => return true,
+                Some(EventData::StreamEnd) => {
+                    self.parsed_event = event;
+                    return true;
+                }
+                _ => {}
+            }
+            if let Some(mark) = self.parse_failed.take() {
+                return self.resync(mark);
+            }
+            match self.parser.parse() {
+                Ok(next) => {
+                    self.position = self.shift(next.end_mark);
+                    event = Some(next);
+                }
+                Err(e) => {
+                    self.parser.take_read_error();
+                    self.parse_failed =
+                        Some(e.problem_mark().map_or(self.position, |m| self.shift(m)));
+                }
+            }
+        }
+    }
+
+    /// Start a new parser at the next line starting a document after the
+    /// syntax error at `mark`, or at it, if the error is meeting that line
+    fn resync(&mut self, mark: libyaml_safer::Mark) -> bool {
+        // A file object is read as it is parsed, so the rest of it is gone
+        let Some(source) = &self.source else {
+            return false;
+        };
+        let mut origin = libyaml_safer::Mark::default();
+        let mut start = 0;
+        for text in source.split_inclusive('\n') {
+            let after = origin.line > mark.line
+                || (origin.line == mark.line && mark.column == 0 && origin.line > self.origin.line);
+            if after && text.starts_with("---") {
+                let rest = &text[3..];
+                if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+                    self.parser =
+                        EventParser::starting_at(self.options.backend, source.clone(), start);
+                    self.origin = origin;
+                    // The new parser starts with its own stream start event
+                    self.parsed_event = None;
+                    return true;
+                }
+            }
+            start += text.len();
+            origin.index += text.chars().count() as u64;
+            origin.line += 1;
+        }
+        false
+    }
+
+    /// Reject an anchor defined by `event` which is already defined, as
+    /// `allow_duplicate_anchors=False` asks
+    fn check_anchor(&mut self, py: Python, event: &Event) -> PyResult<()> {
+        let anchor = match &event.data {
+            EventData::DocumentStart { .. } if !self.options.global_anchors => {
+                self.anchor_marks.clear();
+                return Ok(());
+            }
+            EventData::Scalar { anchor, .. }
+            | EventData::SequenceStart { anchor, .. }
+            | EventData::MappingStart { anchor, .. } => anchor,
+            _ => return Ok(()),
+        };
+        let Some(anchor) = anchor else {
+            return Ok(());
+        };
+        match self.anchor_marks.get(anchor) {
+            Some(&first) => Err(exception::marked_error::<exception::ComposerError>(
+                py,
+                Some(format!(
+                    "found duplicate anchor '{anchor}'; first occurrence"
+                )),
+                Some(self.mark(first)),
+                "second occurrence".to_string(),
+                Some(self.mark(event.start_mark)),
+            )),
+            None => {
+                self.anchor_marks.insert(anchor.clone(), event.start_mark);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fail if parsing `event` takes the stream past one of the `max_*` limits
+    fn check_limits(&mut self, py: Python, event: &Event) -> PyResult<()> {
+        match &event.data {
+            EventData::StreamStart { .. } => {
+                // Streams read from a file are measured as they are read
+                if let (Some(max_bytes), Some(source)) = (self.options.max_bytes, &self.source)
+                    && source.len() > max_bytes
+                {
+                    return Err(exception::reader_error(
+                        py,
+                        format!(
+                            "stream is {} bytes long, more than max_bytes={max_bytes}",
+                            source.len()
+                        ),
+                    ));
+                }
+                return Ok(());
+            }
+            EventData::DocumentStart { .. } => {
+                self.nodes = 0;
+                return Ok(());
+            }
+            EventData::SequenceEnd | EventData::MappingEnd => {
+                self.depth = self.depth.saturating_sub(1);
+                return Ok(());
+            }
+            EventData::SequenceStart { .. } | EventData::MappingStart { .. } => {
+                self.depth += 1;
+                if let Some(max_depth) = self.options.max_depth
+                    && self.depth > max_depth
+                {
+                    return Err(self.composer_error_at(
+                        py,
+                        format!("collections are nested more than max_depth={max_depth} deep"),
+                        event.start_mark,
+                    ));
+                }
+            }
+            EventData::Scalar { .. } | EventData::Alias { .. } => {}
+            _ => return Ok(()),
+        }
+        self.nodes += 1;
+        match self.options.max_nodes {
+            Some(max_nodes) if self.nodes > max_nodes => Err(self.composer_error_at(
+                py,
+                format!("document has more than max_nodes={max_nodes} nodes"),
+                event.start_mark,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move `content_line` past a scalar or alias. The marks of collection
+    /// events can lie past the comments before their first or next entry,
+    /// so they are not counted.
+    fn track_content_line(&mut self, event: &Event) {
+        let line = match &event.data {
+            // Block scalars end at the start of the line after their content
+            EventData::Scalar {
+                style: ScalarStyle::Literal | ScalarStyle::Folded,
+                ..
+            } => event
+                .end_mark
+                .line
+                .saturating_sub(1)
+                .max(event.start_mark.line),
+            EventData::Scalar { .. } | EventData::Alias { .. } => event.end_mark.line,
+            _ => return,
+        };
+        self.content_line = Some(line);
+    }
+
+    /// State of the module in the interpreter this was made in
+    pub fn state(&self) -> &Py<ModuleState> {
+        &self.state
+    }
+
+    /// Take up the registrations made since the last document
+    fn snapshot_registries(&mut self) {
+        let registries = &self.state.get().registries;
+        self.constructors = registries.constructors.snapshot();
+        self.yaml_objects = registries.yaml_object_classes.snapshot();
+        self.implicit_resolvers = registries.implicit_resolvers();
+    }
+
+    /// Consume the document start event, recording the tag handles it declares
+    fn start_document(&mut self) {
+        self.snapshot_registries();
+        self.tag_handles.clear();
+        self.tag_handles.insert("!".to_string(), "!".to_string());
+        self.tag_handles
+            .insert("!!".to_string(), "tag:yaml.org,2002:".to_string());
+        self.version_directive = None;
+        self.tag_directives.clear();
+        if let Some(Event {
+            data:
+                EventData::DocumentStart {
+                    version_directive,
+                    tag_directives,
+                    ..
+                },
+            start_mark,
+            ..
+        }) = self.parsed_event.take()
+        {
+            self.document_start = start_mark;
+            self.version_directive =
+                version_directive.map(|version| (version.major, version.minor));
+            for directive in tag_directives {
+                self.tag_directives
+                    .insert(directive.handle.clone(), directive.prefix.clone());
+                self.tag_handles.insert(directive.handle, directive.prefix);
+            }
+        }
+    }
+
+    /// Consume the document end event, recording where the document ends
+    fn finish_document(&mut self) {
+        if let Some(event) = self.parsed_event.take() {
+            self.document_end = event.end_mark;
+        }
+    }
+
+    /// `data`, the document just loaded, with the directives it declares and
+    /// the marks it spans
+    pub fn document_with_meta(&self, data: Py<PyAny>) -> Document {
+        Document {
+            data,
+            version: self.version_directive,
+            tags: self.tag_directives.clone(),
+            start_mark: self.mark(self.document_start),
+            end_mark: self.mark(self.document_end),
+        }
+    }
+
+    // ── Walking ──
+
+    /// Start the next document, to walk its events; false at the end of the stream
+    pub fn begin_document(&mut self, py: Python) -> PyResult<bool> {
+        if !self.check_node(py)? {
+            return Ok(false);
+        }
+        self.start_document();
+        Ok(true)
+    }
+
+    /// The event the walk is at
+    pub fn peek_event(&mut self, py: Python) -> PyResult<&Event> {
+        self._parse_next_event(py)?;
+        Ok(self.parsed_event.as_ref().expect("event parsed"))
+    }
+
+    /// Move the walk past the event it is at
+    pub fn skip_event(&mut self) {
+        self.parsed_event = None;
+    }
+
+    /// Whether the walk is at a key merging mappings into the one it is in
+    pub fn at_merge_key(&self) -> bool {
+        is_merge_key(&self.parsed_event, self.options.merge_keys())
+    }
+
+    /// Construct the node the walk is at, moving past it
+    pub fn construct_node(&mut self, py: Python) -> PyResult<Py<PyAny>> {
+        self._parse_next_event(py)?;
+        self.construct_from_events(py)
+    }
+
+    /// Consume the end of the document the walk has been through
+    pub fn end_document(&mut self, py: Python) -> PyResult<()> {
+        self._parse_next_event(py)?;
+        self.finish_document();
+        if !self.options.global_anchors {
+            self.anchors.clear();
+            self.node_anchors.clear();
+        }
+        Ok(())
+    }
+
+    // ── Composer ──
+
+    /// Compose a document into a node tree
+    fn compose_document(&mut self, py: Python) -> PyResult<Option<PyNode>> {
+        self.start_document();
+
+        self._parse_next_event(py)?;
+        let node = self.compose_node(py)?;
+
+        // Eat document end event
+        self._parse_next_event(py)?;
+        self.finish_document();
+
+        if !self.options.global_anchors {
+            self.node_anchors.clear();
+        }
+
+        Ok(Some(node))
+    }
+
+    /// Consume the current event and compose the node it starts
+    fn compose_node(&mut self, py: Python) -> PyResult<PyNode> {
+        let event = self.parsed_event.take().unwrap();
+        let start_mark = self.mark(event.start_mark);
+        let node = match event.data {
+            EventData::Alias { anchor } => {
+                return match self.node_anchors.get(&anchor) {
+                    Some(node) => Ok(node.clone()),
+                    None => Err(self.composer_error_at(
+                        py,
+                        format!("found undefined alias '{}'", anchor),
+                        event.start_mark,
+                    )),
+                };
+            }
+            EventData::Scalar {
+                anchor,
+                tag,
+                value,
+                style,
+                ..
+            } if self.is_include(&tag) => {
+                let node = match self
+                    .include(py, &value, event.start_mark)?
+                    .get_single_node(py)?
+                {
+                    Some(node) => node,
+                    None => {
+                        let end_mark = self.mark(event.end_mark);
+                        let tag = crate::TAG_NULL.to_string();
+                        Self::compose_scalar_node(
+                            py,
+                            tag,
+                            String::new(),
+                            style,
+                            start_mark,
+                            end_mark,
+                        )?
+                    }
+                };
+                self.register_node_anchor(anchor, &node);
+                node
+            }
+            EventData::Scalar {
+                anchor,
+                tag,
+                value,
+                plain_implicit,
+                style,
+                ..
+            } => {
+                let end_mark = self.mark(event.end_mark);
+                let tag = match tag {
+                    None => {
+                        let tag = self
+                            .resolve_scalar_tag(py, &value, plain_implicit)
+                            .map_err(|e| exception::with_problem_mark(py, e, start_mark.clone()))?
+                            .to_string();
+                        self.check_ambiguous(py, &value, &tag, event.start_mark)?;
+                        if let Some(resolution) = self.resolution(&value, &tag, event.start_mark) {
+                            self.resolutions.get_or_insert_default().push(resolution);
+                        }
+                        tag
+                    }
+                    Some(tag) if tag == "!" => resolver::DEFAULT_SCALAR_TAG.to_string(),
+                    Some(tag) => tag,
+                };
+                let node = Self::compose_scalar_node(py, tag, value, style, start_mark, end_mark)?;
+                self.register_node_anchor(anchor, &node);
+                node
+            }
+            EventData::SequenceStart {
+                anchor, tag, style, ..
+            } => self.compose_sequence_node(py, anchor, tag, style, start_mark)?,
+            EventData::MappingStart {
+                anchor, tag, style, ..
+            } => self.compose_mapping_node(py, anchor, tag, style, start_mark)?,
+            _ => {
+                return Err(self.composer_error_at(
+                    py,
+                    format!("unexpected event: {:?}", event.data),
+                    event.start_mark,
+                ));
+            }
+        };
+        Ok(node)
+    }
+
+    fn register_node_anchor(&mut self, anchor: Option<String>, node: &PyNode) {
+        if let Some(anchor_name) = anchor {
+            self.node_anchors.insert(anchor_name, node.clone());
+        }
+    }
+
+    fn compose_scalar_node(
+        py: Python,
+        tag: String,
+        value: String,
+        style: ScalarStyle,
+        start_mark: PyMark,
+        end_mark: PyMark,
+    ) -> PyResult<PyNode> {
+        let node = PyScalarNode::new(
+            tag,
+            value,
+            Some(start_mark),
+            Some(end_mark),
+            scalar_style_to_char(style),
+        );
+        Ok(PyNode::Scalar(Py::new(py, node)?))
+    }
+
+    fn compose_sequence_node(
+        &mut self,
+        py: Python,
+        anchor: Option<String>,
+        tag: Option<String>,
+        style: SequenceStyle,
+        start_mark: PyMark,
+    ) -> PyResult<PyNode> {
+        let tag = collection_tag(tag, resolver::DEFAULT_SEQUENCE_TAG);
+        let flow_style = sequence_flow_style(style);
+        let node = Py::new(
+            py,
+            PySequenceNode::new(tag, Vec::new(), Some(start_mark), None, flow_style),
+        )?;
+        // Register the anchor before composing children so recursive aliases resolve
+        self.register_node_anchor(anchor, &PyNode::Sequence(node.clone_ref(py)));
+
+        loop {
+            self._parse_next_event(py)?;
+            if matches!(
+                &self.parsed_event,
+                Some(Event {
+                    data: EventData::SequenceEnd,
+                    ..
+                })
+            ) {
+                break;
+            }
+            let item = self.compose_node(py)?;
+            node.borrow_mut(py).value.push(item);
+        }
+
+        // The collection ends where its closing event does
+        let end_event = self.parsed_event.take().unwrap();
+        node.borrow_mut(py).end_mark = Some(self.mark(end_event.end_mark));
+        Ok(PyNode::Sequence(node))
+    }
+
+    fn compose_mapping_node(
+        &mut self,
+        py: Python,
+        anchor: Option<String>,
+        tag: Option<String>,
+        style: MappingStyle,
+        start_mark: PyMark,
+    ) -> PyResult<PyNode> {
+        let tag = collection_tag(tag, resolver::DEFAULT_MAPPING_TAG);
+        let flow_style = mapping_flow_style(style);
+        let node = Py::new(
+            py,
+            PyMappingNode::new(tag, Vec::new(), Some(start_mark), None, flow_style),
+        )?;
+        // Register the anchor before composing children so recursive aliases resolve
+        self.register_node_anchor(anchor, &PyNode::Mapping(node.clone_ref(py)));
+
+        loop {
+            self._parse_next_event(py)?;
+            if matches!(
+                &self.parsed_event,
+                Some(Event {
+                    data: EventData::MappingEnd,
+                    ..
+                })
+            ) {
+                break;
+            }
+            let key = self.compose_node(py)?;
+            self._parse_next_event(py)?;
+            let value = self.compose_node(py)?;
+            node.borrow_mut(py).value.push((key, value));
+        }
+
+        // The collection ends where its closing event does
+        let end_event = self.parsed_event.take().unwrap();
+        node.borrow_mut(py).end_mark = Some(self.mark(end_event.end_mark));
+        Ok(PyNode::Mapping(node))
+    }
+
+    // ── Constructor ──
+
+    /// Construct a document directly from events
+    fn construct_document(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        self.start_document();
+
+        // Construct the root object directly from events
+        self._parse_next_event(py)?;
+        let result = self.construct_from_events(py)?;
+
+        // Eat document end event
+        self._parse_next_event(py)?;
+        self.finish_document();
+
+        if let Some(kept) = &mut self.kept_anchors {
+            kept.extend(
+                self.anchors
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone_ref(py))),
+            );
+        }
+
+        // Clear anchors for next document
+        if !self.options.global_anchors {
+            self.anchors.clear();
+            self.node_anchors.clear();
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Core single-pass constructor: consume the current event and produce a Python object
+    fn construct_from_events(&mut self, py: Python) -> PyResult<Py<PyAny>> {
+        let mut event = self.parsed_event.take().unwrap();
+        if self.options.ignore_tags
+            && let EventData::Scalar { tag, .. }
+            | EventData::SequenceStart { tag, .. }
+            | EventData::MappingStart { tag, .. } = &mut event.data
+        {
+            *tag = None;
+        }
+        if let EventData::Scalar {
+            anchor, tag, value, ..
+        } = &event.data
+            && self.is_include(tag)
+        {
+            let included = self
+                .include(py, value, event.start_mark)?
+                .get_single_data(py)?;
+            let result = included.unwrap_or_else(|| py.None());
+            if let Some(anchor_name) = anchor {
+                self.anchors
+                    .insert(anchor_name.clone(), result.clone_ref(py));
+            }
+            return Ok(result);
+        }
+        let registered_anchor = match &event.data {
+            EventData::Scalar {
+                anchor,
+                tag: Some(tag),
+                ..
+            }
+            | EventData::SequenceStart {
+                anchor,
+                tag: Some(tag),
+                ..
+            }
+            | EventData::MappingStart {
+                anchor,
+                tag: Some(tag),
+                ..
+            } if self.constructors.contains_key(tag) => Some(anchor.clone()),
+            // Plain scalars which a registered implicit resolver claims
+            EventData::Scalar {
+                anchor,
+                tag: None,
+                value,
+                plain_implicit: true,
+                ..
+            } if !self.implicit_resolvers.is_empty()
+                && self
+                    .resolve_scalar_tag(py, value, true)
+                    .is_ok_and(|tag| self.constructors.contains_key(tag)) =>
+            {
+                Some(anchor.clone())
+            }
+            _ => None,
+        };
+        if let Some(anchor) = registered_anchor {
+            return self.construct_registered(py, event, anchor);
+        }
+        if let EventData::Scalar { tag: Some(tag), .. }
+        | EventData::SequenceStart { tag: Some(tag), .. }
+        | EventData::MappingStart { tag: Some(tag), .. } = &event.data
+            && let Some(class) = self.yaml_objects.get(tag)
+        {
+            let class = class.bind(py).clone();
+            return self.construct_yaml_object(py, event, class);
+        }
+        if self.options.python_tags != PythonTags::Ignore
+            && let EventData::Scalar { tag: Some(tag), .. }
+            | EventData::SequenceStart { tag: Some(tag), .. }
+            | EventData::MappingStart { tag: Some(tag), .. } = &event.data
+            && tag.starts_with(python::TAG_PREFIX)
+        {
+            return self.construct_python(py, event);
+        }
+        if let EventData::Scalar { tag: Some(tag), .. }
+        | EventData::SequenceStart { tag: Some(tag), .. }
+        | EventData::MappingStart { tag: Some(tag), .. } = &event.data
+            && let Some(suggestion) = suggest_tag(tag, &self.constructors)
+        {
+            return Err(exception::marked_error_with_note::<
+                exception::ConstructorError,
+            >(
+                py,
+                None,
+                None,
+                format!("could not determine a constructor for the tag '{tag}'"),
+                Some(self.mark(event.start_mark)),
+                Some(format!("did you mean '{suggestion}'?")),
+            ));
+        }
+        if self.options.preserve_unknown_tags
+            && let EventData::Scalar { tag: Some(tag), .. }
+            | EventData::SequenceStart { tag: Some(tag), .. }
+            | EventData::MappingStart { tag: Some(tag), .. } = &event.data
+            && !is_known_tag(tag)
+        {
+            return self.construct_tagged(py, event);
+        }
+        match event.data {
+            EventData::Alias { anchor } => {
+                if let Some(obj) = self.anchors.get(&anchor) {
+                    Ok(obj.clone_ref(py))
+                } else {
+                    Err(self.composer_error_at(
+                        py,
+                        format!("found undefined alias '{}'", anchor),
+                        event.start_mark,
+                    ))
+                }
+            }
+            EventData::Scalar {
+                anchor,
+                tag,
+                value,
+                plain_implicit,
+                style,
+                ..
+            } => self
+                .construct_scalar_direct(
+                    py,
+                    anchor,
+                    tag,
+                    value,
+                    plain_implicit,
+                    style,
+                    event.start_mark,
+                )
+                .map_err(|e| exception::with_problem_mark(py, e, self.mark(event.start_mark))),
+            EventData::SequenceStart {
+                anchor, tag, style, ..
+            } => self.construct_sequence_direct(py, anchor, tag, style),
+            EventData::MappingStart {
+                anchor, tag, style, ..
+            } => self.construct_mapping_direct(py, anchor, tag, style),
+            _ => Err(self.composer_error_at(
+                py,
+                format!("unexpected event: {:?}", event.data),
+                event.start_mark,
+            )),
+        }
+    }
+
+    /// Construct the node started by `event`, which has a `!!python/...` tag
+    fn construct_python(&mut self, py: Python, mut event: Event) -> PyResult<Py<PyAny>> {
+        let start_mark = self.mark(event.start_mark);
+        let (anchor, tag, content) = match &mut event.data {
+            EventData::Scalar { anchor, tag, .. } => (anchor.clone(), tag, "scalar"),
+            EventData::SequenceStart { anchor, tag, .. } => (anchor.clone(), tag, "sequence"),
+            EventData::MappingStart { anchor, tag, .. } => (anchor.clone(), tag, "mapping"),
+            _ => unreachable!("only nodes have tags"),
+        };
+        let full_tag = tag.clone().unwrap_or_default();
+        let suffix = &full_tag[python::TAG_PREFIX.len()..];
+        let Some(expected) = python::content(suffix, self.options.python_tags) else {
+            return Err(exception::marked_error::<exception::ConstructorError>(
+                py,
+                None,
+                None,
+                format!("could not determine a constructor for the tag '{full_tag}'"),
+                Some(start_mark),
+            ));
+        };
+        if !expected.accepts(content) {
+            return Err(exception::marked_error::<exception::ConstructorError>(
+                py,
+                None,
+                None,
+                format!(
+                    "expected a {} node, but found {content}",
+                    expected.describe()
+                ),
+                Some(start_mark),
+            ));
+        }
+        *tag = Some(match expected {
+            Content::Scalar(content_tag) => content_tag.to_string(),
+            _ => "!".to_string(),
+        });
+        self.parsed_event = Some(event);
+        let value = self.construct_from_events(py)?;
+        let result = python::finish(suffix, value.into_bound(py), self.options.python_tags)
+            .map_err(|e| exception::with_problem_mark(py, e, start_mark))?
+            .unbind();
+        if let Some(anchor_name) = anchor {
+            self.anchors.insert(anchor_name, result.clone_ref(py));
+        }
+        Ok(result)
+    }
+
+    /// Construct the node started by `event`, which has an unknown tag, as a
+    /// `TaggedValue` wrapping what it loads as without the tag
+    fn construct_tagged(&mut self, py: Python, mut event: Event) -> PyResult<Py<PyAny>> {
+        let (anchor, tag) = match &mut event.data {
+            EventData::Scalar { anchor, tag, .. }
+            | EventData::SequenceStart { anchor, tag, .. }
+            | EventData::MappingStart { anchor, tag, .. } => {
+                // Construct the content as if untagged, except that scalars stay strings
+                (anchor.clone(), tag.replace("!".to_string()))
+            }
+            _ => unreachable!("only nodes have tags"),
+        };
+        let tag = tag.unwrap_or_default();
+        self.parsed_event = Some(event);
+        let value = self.construct_from_events(py)?;
+        let tagged = Bound::new(py, PyTaggedValue::new(tag, value))?
+            .into_any()
+            .unbind();
+        // Aliases refer to the wrapper; recursive ones inside see the bare value
+        if let Some(anchor_name) = anchor {
+            self.anchors.insert(anchor_name, tagged.clone_ref(py));
+        }
+        Ok(tagged)
+    }
 
-        Ok(Some(result))
+    /// Compose the node started by `event` and construct it with the
+    /// constructor registered for its tag
+    fn construct_registered(
+        &mut self,
+        py: Python,
+        event: Event,
+        anchor: Option<String>,
+    ) -> PyResult<Py<PyAny>> {
+        let start_mark = self.mark(event.start_mark);
+        self.parsed_event = Some(event);
+        let node = self.compose_node(py)?;
+        let constructor = Bound::new(
+            py,
+            PyConstructor::new(self.state.clone_ref(py), Arc::clone(&self.constructors)),
+        )?;
+        let result = PyConstructor::construct(&constructor, &node)
+            .map_err(|e| exception::with_problem_mark(py, e, start_mark))?;
+        if let Some(anchor_name) = anchor {
+            self.anchors.insert(anchor_name, result.clone_ref(py));
+        }
+        Ok(result)
     }
 
-    /// Core single-pass constructor: consume the current event and produce a Python object
-    fn construct_from_events(&mut self, py: Python) -> PyResult<Py<PyAny>> {
-        let event = self.parsed_event.take().unwrap();
-        match event.data {
-            EventData::Alias { anchor } => {
-                if let Some(obj) = self.anchors.get(&anchor) {
-                    Ok(obj.clone_ref(py))
-                } else {
-                    Err(exception::composer_error(
-                        py,
-                        format!("found undefined alias '{}'", anchor),
-                    ))
-                }
-            }
-            EventData::Scalar {
-                anchor,
-                tag,
-                value,
-                plain_implicit,
-                ..
-            } => self.construct_scalar_direct(py, anchor, tag, value, plain_implicit),
-            EventData::SequenceStart { anchor, tag, .. } => {
-                self.construct_sequence_direct(py, anchor, tag)
-            }
-            EventData::MappingStart { anchor, tag, .. } => {
-                self.construct_mapping_direct(py, anchor, tag)
-            }
-            _ => Err(exception::composer_error(
+    /// Construct an instance of `class`, registered with `add_yaml_object()`, from
+    /// the node started by `event`: through `class.from_yaml()` if it has one,
+    /// otherwise restoring the constructed mapping as its state
+    fn construct_yaml_object(
+        &mut self,
+        py: Python,
+        mut event: Event,
+        class: Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        let start_mark = self.mark(event.start_mark);
+        let (anchor, tag, content) = match &mut event.data {
+            EventData::Scalar { anchor, tag, .. } => (anchor.clone(), tag, "scalar"),
+            EventData::SequenceStart { anchor, tag, .. } => (anchor.clone(), tag, "sequence"),
+            EventData::MappingStart { anchor, tag, .. } => (anchor.clone(), tag, "mapping"),
+            _ => unreachable!("only nodes have tags"),
+        };
+        let result = if class.hasattr("from_yaml")? {
+            self.parsed_event = Some(event);
+            let node = self.compose_node(py)?;
+            let constructor = Bound::new(
                 py,
-                format!("unexpected event: {:?}", event.data),
-            )),
+                PyConstructor::new(self.state.clone_ref(py), Arc::clone(&self.constructors)),
+            )?;
+            class
+                .call_method1("from_yaml", (constructor, node))
+                .map_err(|e| exception::with_problem_mark(py, e, start_mark))?
+        } else {
+            if content != "mapping" {
+                return Err(exception::marked_error::<exception::ConstructorError>(
+                    py,
+                    None,
+                    None,
+                    format!("expected a mapping node, but found {content}"),
+                    Some(start_mark),
+                ));
+            }
+            *tag = Some("!".to_string());
+            self.parsed_event = Some(event);
+            let state = self.construct_from_events(py)?;
+            let instance = class.call_method1("__new__", (&class,))?;
+            python::set_state(&instance, state.into_bound(py))
+                .map_err(|e| exception::with_problem_mark(py, e, start_mark))?;
+            instance
         }
+        .unbind();
+        if let Some(anchor_name) = anchor {
+            self.anchors.insert(anchor_name, result.clone_ref(py));
+        }
+        Ok(result)
     }
 
     /// Construct a Python object directly from a scalar event
+    #[allow(clippy::too_many_arguments)]
     fn construct_scalar_direct(
         &mut self,
         py: Python,
@@ -206,22 +2038,49 @@ impl RSafeLoader {
         tag: Option<String>,
         value: String,
         plain_implicit: bool,
+        style: ScalarStyle,
+        mark: libyaml_safer::Mark,
     ) -> PyResult<Py<PyAny>> {
         // Resolve tag inline — &'static str, no allocation for common case
         let resolved_tag: &str = if let Some(ref t) = tag {
             t.as_str()
         } else {
-            resolver::resolve_scalar_tag(&value, plain_implicit)
+            self.resolve_scalar_tag(py, &value, plain_implicit)?
         };
 
-        let result = match resolved_tag {
-            crate::TAG_NULL => py.None(),
-            crate::TAG_BOOL => construct_bool_direct(py, &value)?,
-            crate::TAG_INT => construct_int_direct(py, &value)?,
-            crate::TAG_FLOAT => construct_float_direct(py, &value)?,
-            // str, timestamp, value, merge, and unknown tags all produce strings
-            _ => PyString::new(py, &value).into_any().unbind(),
+        let resolution = match tag {
+            None => {
+                self.check_ambiguous(py, &value, resolved_tag, mark)?;
+                self.resolution(&value, resolved_tag, mark)
+            }
+            Some(_) => None,
+        };
+        let mut result = if self.options.intern_values && resolved_tag == crate::TAG_STR {
+            self.intern_value(py, &value)
+        } else {
+            construct_scalar_value(py, resolved_tag, &value)?
         };
+        if self.options.roundtrip
+            && resolved_tag == crate::TAG_STR
+            && let Some(style) = scalar_style_to_char(style)
+        {
+            let styled = self
+                .state
+                .get()
+                .styled_str
+                .import(py, "ryaml.containers", "StyledStr")?
+                .call1((result,))?;
+            styled.setattr("style", style)?;
+            result = styled.unbind();
+        }
+        if resolved_tag == crate::TAG_FLOAT
+            && self.options.float_precision != FloatPrecision::Ignore
+        {
+            self.check_float_precision(py, &value, result.extract(py)?, mark)?;
+        }
+        if let Some(resolution) = resolution {
+            self.resolutions.get_or_insert_default().push(resolution);
+        }
 
         if let Some(anchor_name) = anchor {
             self.anchors.insert(anchor_name, result.clone_ref(py));
@@ -230,22 +2089,88 @@ impl RSafeLoader {
         Ok(result)
     }
 
+    /// The str `value`, shared with the equal values loaded before it
+    fn intern_value(&mut self, py: Python, value: &str) -> Py<PyAny> {
+        if let Some(interned) = self.values.get(value) {
+            return interned.clone_ref(py).into_any();
+        }
+        let interned = PyString::new(py, value).unbind();
+        if value.len() <= MAX_INTERNED_VALUE_LEN && self.values.len() < MAX_INTERNED_VALUES {
+            self.values
+                .insert(value.to_string(), interned.clone_ref(py));
+        }
+        interned.into_any()
+    }
+
+    /// Warn about or reject a float literal `text` which `value` doesn't hold exactly
+    fn check_float_precision(
+        &self,
+        py: Python,
+        text: &str,
+        value: f64,
+        mark: libyaml_safer::Mark,
+    ) -> PyResult<()> {
+        if is_exact_float(text, value) {
+            return Ok(());
+        }
+        let problem = format!("float '{text}' cannot be represented exactly");
+        if self.options.float_precision == FloatPrecision::Warn {
+            let message = format!(
+                "{problem}, loading {value:?} instead\n  in \"{}\", line {}, column {}",
+                self.options.name,
+                mark.line + 1,
+                mark.column + 1
+            );
+            return warn_user(py, message);
+        }
+        Err(exception::marked_error_with_note::<
+            exception::ConstructorError,
+        >(
+            py,
+            None,
+            None,
+            problem,
+            None,
+            Some("quote it or tag it !!str to keep the exact digits".to_string()),
+        ))
+    }
+
     /// Construct a Python list directly from sequence events
     fn construct_sequence_direct(
         &mut self,
         py: Python,
         anchor: Option<String>,
         _tag: Option<String>,
+        style: SequenceStyle,
     ) -> PyResult<Py<PyAny>> {
-        let list = PyList::empty(py);
-        let list_obj: Py<PyAny> = list.clone().unbind().into_any();
+        let list = if self.options.preserve_flow_style || self.options.roundtrip {
+            let list = self
+                .state
+                .get()
+                .styled_list
+                .import(py, "ryaml.containers", "StyledList")?
+                .call0()?;
+            list.setattr("flow_style", sequence_flow_style(style))?;
+            if self.options.roundtrip {
+                list.setattr("anchor", anchor.as_deref())?;
+            }
+            Some(list.downcast_into::<PyList>()?)
+        } else if anchor.is_some() {
+            Some(PyList::empty(py))
+        } else {
+            // Nothing can refer to the list before its items are constructed,
+            // so it is made afterwards, at its final size
+            None
+        };
 
         // Store in anchors BEFORE recursing (handles circular references)
-        if let Some(anchor_name) = anchor {
-            self.anchors.insert(anchor_name, list_obj.clone_ref(py));
+        if let (Some(anchor_name), Some(list)) = (anchor, &list) {
+            self.anchors
+                .insert(anchor_name, list.clone().unbind().into_any());
         }
 
         // Consume child events until SequenceEnd
+        let mut items = Vec::new();
         loop {
             self._parse_next_event(py)?;
             if matches!(
@@ -257,12 +2182,60 @@ impl RSafeLoader {
             ) {
                 break;
             }
-            let item = self.construct_from_events(py)?;
-            list.append(item)?;
+            items.push(self.construct_from_events(py)?);
         }
+        self.parsed_event = None;
+
+        let list = match list {
+            Some(list) => {
+                for item in items {
+                    list.append(item)?;
+                }
+                list
+            }
+            None => PyList::new(py, items)?,
+        };
+        Ok(list.into_any().unbind())
+    }
 
+    /// Construct the mapping key just parsed when it is a short str, sharing
+    /// one object between equal keys. None leaves the key to be constructed
+    /// as any other node.
+    fn construct_str_key(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        let Some(Event {
+            data:
+                EventData::Scalar {
+                    anchor: None,
+                    tag: None,
+                    value,
+                    plain_implicit,
+                    ..
+                },
+            ..
+        }) = &self.parsed_event
+        else {
+            return Ok(None);
+        };
+        // Styled keys and registered str constructors make objects of their own
+        if value.len() > MAX_SHARED_KEY_LEN
+            || self.options.roundtrip
+            || self.constructors.contains_key(crate::TAG_STR)
+            || self.resolve_scalar_tag(py, value, *plain_implicit)? != crate::TAG_STR
+        {
+            return Ok(None);
+        }
+        let key = match self.keys.get(value.as_str()) {
+            Some(key) => key.clone_ref(py),
+            None => {
+                let key = PyString::new(py, value).unbind();
+                if self.keys.len() < MAX_SHARED_KEYS {
+                    self.keys.insert(value.clone(), key.clone_ref(py));
+                }
+                key
+            }
+        };
         self.parsed_event = None;
-        Ok(list_obj)
+        Ok(Some(key.into_any()))
     }
 
     /// Construct a Python dict directly from mapping events, with inline merge key handling
@@ -271,20 +2244,44 @@ impl RSafeLoader {
         py: Python,
         anchor: Option<String>,
         tag: Option<String>,
+        style: MappingStyle,
     ) -> PyResult<Py<PyAny>> {
         let is_set = tag.as_deref() == Some(crate::TAG_SET);
 
-        let dict = PyDict::new(py);
+        let dict = if (self.options.preserve_flow_style || self.options.roundtrip) && !is_set {
+            let dict = self
+                .state
+                .get()
+                .styled_dict
+                .import(py, "ryaml.containers", "StyledDict")?
+                .call0()?;
+            dict.setattr("flow_style", mapping_flow_style(style))?;
+            if self.options.roundtrip {
+                dict.setattr("anchor", anchor.as_deref())?;
+            }
+            dict.downcast_into::<PyDict>()?
+        } else {
+            PyDict::new(py)
+        };
         let dict_obj: Py<PyAny> = dict.clone().unbind().into_any();
 
         // Store in anchors BEFORE recursing (handles circular references)
-        if let Some(anchor_name) = anchor {
-            self.anchors.insert(anchor_name, dict_obj.clone_ref(py));
+        if let Some(anchor_name) = &anchor {
+            self.anchors
+                .insert(anchor_name.clone(), dict_obj.clone_ref(py));
         }
 
         let mut merge_sources: Vec<Py<PyAny>> = Vec::new();
+        // Comments around the entries of block mappings, with `roundtrip`
+        let comments = match &self.comment_lines {
+            Some(_) if style != MappingStyle::Flow && !is_set => {
+                Some(dict.getattr("comments")?.downcast_into::<PyDict>()?)
+            }
+            _ => None,
+        };
 
         loop {
+            let floor = self.content_line;
             self._parse_next_event(py)?;
             if matches!(
                 &self.parsed_event,
@@ -295,18 +2292,85 @@ impl RSafeLoader {
             ) {
                 break;
             }
+            let before = match (&comments, &self.comment_lines, &self.parsed_event) {
+                (Some(_), Some(lines), Some(event)) => {
+                    lines.comments_above(event.start_mark.line, floor)
+                }
+                _ => Vec::new(),
+            };
 
             // Check if the key is a merge key BEFORE constructing it
-            let is_merge = is_merge_key(&self.parsed_event);
-
-            let key = self.construct_from_events(py)?;
+            let is_merge = is_merge_key(&self.parsed_event, self.options.merge_keys());
+            let key_mark = self.parsed_event.as_ref().map(|e| self.mark(e.start_mark));
+            let key_text = match &self.parsed_event {
+                Some(Event {
+                    data: EventData::Scalar { value, .. },
+                    ..
+                }) if self.options.string_keys && !is_merge => Some(value.clone()),
+                _ => None,
+            };
+
+            let key = match self.construct_str_key(py)? {
+                Some(key) => key,
+                None => self.construct_from_events(py)?,
+            };
+            let key = match key_text {
+                Some(text) if !key.bind(py).is_instance_of::<PyString>() => {
+                    PyString::new(py, &text).into_any().unbind()
+                }
+                _ => key,
+            };
+            if !self.options.allow_nan_keys
+                && let Ok(float_key) = key.downcast_bound::<PyFloat>(py)
+                && float_key.value().is_nan()
+            {
+                return Err(exception::marked_error::<exception::ConstructorError>(
+                    py,
+                    Some("while constructing a mapping".to_string()),
+                    None,
+                    "found a NaN mapping key, which can never be looked up".to_string(),
+                    key_mark,
+                ));
+            }
 
             // Parse the value
+            let key_end = self.position;
             self._parse_next_event(py)?;
+            // Where the comment ending the entry's first line follows
+            let comment_mark = match &self.parsed_event {
+                Some(Event {
+                    data:
+                        EventData::SequenceStart {
+                            style: SequenceStyle::Block,
+                            ..
+                        }
+                        | EventData::MappingStart {
+                            style: MappingStyle::Block,
+                            ..
+                        },
+                    ..
+                }) => Some(key_end),
+                Some(Event {
+                    data:
+                        EventData::Scalar {
+                            style: ScalarStyle::Literal | ScalarStyle::Folded,
+                            ..
+                        },
+                    start_mark,
+                    ..
+                }) => Some(*start_mark),
+                _ => None,
+            };
             let value = self.construct_from_events(py)?;
+            let inline = match (&comments, &self.comment_lines) {
+                (Some(_), Some(lines)) => {
+                    lines.comment_after(comment_mark.unwrap_or(self.position))
+                }
+                _ => None,
+            };
 
             if is_set {
-                let hashable_key = self.make_hashable(py, key)?;
+                let hashable_key = make_hashable(py, key)?;
                 dict.set_item(hashable_key, py.None())?;
                 continue;
             }
@@ -323,7 +2387,18 @@ impl RSafeLoader {
                 continue;
             }
 
-            let hashable_key = self.make_hashable(py, key)?;
+            let hashable_key = make_hashable(py, key)?;
+            if let Some(comments) = &comments
+                && (!before.is_empty() || inline.is_some())
+            {
+                let entry = self
+                    .state
+                    .get()
+                    .entry_comments
+                    .import(py, "ryaml.containers", "Comments")?
+                    .call1((PyTuple::new(py, before)?, inline))?;
+                comments.set_item(hashable_key.bind(py), entry)?;
+            }
             dict.set_item(hashable_key, value)?;
         }
 
@@ -336,44 +2411,165 @@ impl RSafeLoader {
                             dict.set_item(&k, v)?;
                         }
                     }
+                } else if source.bind(py).hasattr("items")? {
+                    // Mappings made by hooks or constructors, which needn't be dicts
+                    for pair in source.bind(py).call_method0("items")?.try_iter()? {
+                        let (k, v): (Bound<'_, PyAny>, Bound<'_, PyAny>) = pair?.extract()?;
+                        if !dict.contains(&k)? {
+                            dict.set_item(&k, v)?;
+                        }
+                    }
                 }
             }
         }
 
         self.parsed_event = None;
+        let mapping = match (&self.options.mapping_hook, &self.options.object_hook) {
+            _ if is_set => None,
+            (Some(hook), _) => Some(hook.bind(py).call1((dict.items(),))?),
+            (None, Some(hook)) => Some(hook.bind(py).call1((&dict,))?),
+            (None, None) => None,
+        };
+        if let Some(mapping) = mapping {
+            // Aliases from inside the mapping still refer to the dict
+            let mapping = mapping.unbind();
+            if let Some(anchor_name) = anchor {
+                self.anchors.insert(anchor_name, mapping.clone_ref(py));
+            }
+            return Ok(mapping);
+        }
         Ok(dict_obj)
     }
+}
 
-    /// Convert unhashable types (dict, list) to tuples for use as dict keys
-    fn make_hashable(&self, py: Python, obj: Py<PyAny>) -> PyResult<Py<PyAny>> {
-        if let Ok(dict) = obj.downcast_bound::<PyDict>(py) {
-            let mut items = Vec::new();
-            for (key, value) in dict.iter() {
-                let hashable_key = self.make_hashable(py, key.unbind())?;
-                let hashable_value = self.make_hashable(py, value.unbind())?;
-                let pair = pyo3::types::PyTuple::new(py, &[hashable_key, hashable_value])?;
-                items.push(pair);
-            }
-            let tuple = pyo3::types::PyTuple::new(py, &items)?;
-            return Ok(tuple.unbind().into_any());
+/// Convert unhashable types (dict, list) to tuples for use as dict keys
+pub fn make_hashable(py: Python, obj: Py<PyAny>) -> PyResult<Py<PyAny>> {
+    if let Ok(dict) = obj.downcast_bound::<PyDict>(py) {
+        let mut items = Vec::new();
+        for (key, value) in dict.iter() {
+            let hashable_key = make_hashable(py, key.unbind())?;
+            let hashable_value = make_hashable(py, value.unbind())?;
+            let pair = pyo3::types::PyTuple::new(py, &[hashable_key, hashable_value])?;
+            items.push(pair);
         }
+        let tuple = pyo3::types::PyTuple::new(py, &items)?;
+        return Ok(tuple.unbind().into_any());
+    }
 
-        if let Ok(list) = obj.downcast_bound::<PyList>(py) {
-            let mut items = Vec::new();
-            for item in list.iter() {
-                let hashable_item = self.make_hashable(py, item.unbind())?;
-                items.push(hashable_item);
-            }
-            let tuple = pyo3::types::PyTuple::new(py, &items)?;
-            return Ok(tuple.unbind().into_any());
+    if let Ok(list) = obj.downcast_bound::<PyList>(py) {
+        let mut items = Vec::new();
+        for item in list.iter() {
+            let hashable_item = make_hashable(py, item.unbind())?;
+            items.push(hashable_item);
         }
+        let tuple = pyo3::types::PyTuple::new(py, &items)?;
+        return Ok(tuple.unbind().into_any());
+    }
 
-        Ok(obj)
+    Ok(obj)
+}
+
+/// Construct the Python value of a scalar with the given resolved tag
+pub fn construct_scalar_value(py: Python, tag: &str, value: &str) -> PyResult<Py<PyAny>> {
+    Ok(match tag {
+        crate::TAG_NULL => py.None(),
+        crate::TAG_BOOL => construct_bool_direct(py, value)?,
+        crate::TAG_INT => construct_int_direct(py, value)?,
+        crate::TAG_FLOAT => construct_float_direct(py, value)?,
+        crate::TAG_TIMESTAMP => construct_timestamp_direct(py, value)?,
+        crate::TAG_BINARY => construct_binary_direct(py, value)?,
+        // str, value, merge, and unknown tags all produce strings
+        _ => PyString::new(py, value).into_any().unbind(),
+    })
+}
+
+/// Resolve the tag of a collection node, falling back to `default` for untagged or `!` nodes
+/// Issue `message` as a `UserWarning`, spelling out any NUL in it, which
+/// the C string Python takes can't hold
+fn warn_user(py: Python, message: String) -> PyResult<()> {
+    let message = std::ffi::CString::new(message.replace('\0', "\\0")).expect("NULs were replaced");
+    PyErr::warn(py, &py.get_type::<PyUserWarning>(), &message, 1)
+}
+
+fn collection_tag(tag: Option<String>, default: &str) -> String {
+    match tag {
+        Some(t) if t != "!" => t,
+        _ => default.to_string(),
+    }
+}
+
+/// Standard tags, in the shorthand they're usually written with
+const KNOWN_TAGS: &[&str] = &[
+    "!!null",
+    "!!bool",
+    "!!int",
+    "!!float",
+    "!!str",
+    "!!binary",
+    "!!timestamp",
+    "!!seq",
+    "!!map",
+    "!!set",
+    "!!omap",
+    "!!pairs",
+    "!!merge",
+    "!!value",
+];
+
+/// For an unknown tag that looks like a typo of a standard or registered one,
+/// that tag. Other unknown tags are left alone, as they construct plain values.
+fn suggest_tag(tag: &str, registered: &Snapshot<String>) -> Option<String> {
+    if tag == "!" || registered.contains_key(tag) {
+        return None;
+    }
+    let shorthand = tag_shorthand(tag);
+    if KNOWN_TAGS.contains(&shorthand.as_str()) {
+        return None;
+    }
+    let lowered = shorthand.to_lowercase();
+    KNOWN_TAGS
+        .iter()
+        .map(|known| known.to_string())
+        .chain(registered.keys().map(|known| tag_shorthand(known)))
+        .map(|known| (edit_distance(&lowered, &known.to_lowercase()), known))
+        // Short names need a closer match to avoid suggesting a different type
+        .filter(|(distance, known)| *distance <= if known.len() > 6 { 2 } else { 1 })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+/// Whether `tag` is non-specific or one of the standard tags
+fn is_known_tag(tag: &str) -> bool {
+    tag == "!" || KNOWN_TAGS.contains(&tag_shorthand(tag).as_str())
+}
+
+/// `tag` written with the `!!` handle if it is a standard tag
+fn tag_shorthand(tag: &str) -> String {
+    match tag.strip_prefix("tag:yaml.org,2002:") {
+        Some(name) => format!("!!{name}"),
+        None => tag.to_string(),
+    }
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
     }
+    row[b.len()]
 }
 
-/// Check if the current event is a merge key (plain scalar "<<" or explicit merge tag)
-fn is_merge_key(event: &Option<Event>) -> bool {
+/// Check if the current event is a merge key (plain scalar "<<" when
+/// `merge_keys` is on, or explicit merge tag)
+fn is_merge_key(event: &Option<Event>, merge_keys: bool) -> bool {
     if let Some(Event {
         data:
             EventData::Scalar {
@@ -388,7 +2584,7 @@ fn is_merge_key(event: &Option<Event>) -> bool {
         if let Some(t) = tag {
             return t == crate::TAG_MERGE;
         }
-        return *plain_implicit && value == "<<";
+        return merge_keys && *plain_implicit && value == "<<";
     }
     false
 }
@@ -442,6 +2638,10 @@ fn construct_int_fallback(py: Python, value: &str) -> PyResult<Py<PyAny>> {
         parse_int_skip_underscores(hex, 16).map_err(|_| {
             exception::constructor_error(py, format!("invalid hex integer: {}", value))
         })?
+    } else if let Some(octal) = remaining.strip_prefix("0o") {
+        parse_int_skip_underscores(octal, 8).map_err(|_| {
+            exception::constructor_error(py, format!("invalid octal integer: {}", value))
+        })?
     } else if remaining.starts_with('0') && !remaining.contains(':') && remaining.len() > 1 {
         parse_int_skip_underscores(remaining, 8).map_err(|_| {
             exception::constructor_error(py, format!("invalid octal integer: {}", value))
@@ -501,6 +2701,116 @@ fn construct_float_fallback(py: Python, value: &str) -> PyResult<Py<PyAny>> {
     Ok(PyFloat::new(py, sign * result).into_any().unbind())
 }
 
+// --- Exact decimal detection ---
+
+/// Whether `value`, parsed from the float literal `text`, is exactly the number
+/// written. Special values and sexagesimal floats are taken as exact.
+pub fn is_exact_float(text: &str, value: f64) -> bool {
+    if !text.bytes().any(|b| b.is_ascii_digit()) || text.contains(':') {
+        return true;
+    }
+    if !value.is_finite() {
+        return false;
+    }
+    let Some((digits, exponent)) = decimal_digits(text) else {
+        return true;
+    };
+    // A binary fraction written in decimal always ends in 5
+    if exponent < 0 && digits.last() != Some(&5) {
+        return false;
+    }
+    (digits, exponent) == float_digits(value.abs())
+}
+
+/// Significant digits and power of ten of a decimal literal like `-1_000.25e3`
+fn decimal_digits(text: &str) -> Option<(Vec<u8>, i64)> {
+    let text = text.trim_start_matches(['+', '-']);
+    let (mantissa, mut exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], text[i + 1..].parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let mut digits = Vec::with_capacity(mantissa.len());
+    let mut fraction = false;
+    for b in mantissa.bytes() {
+        match b {
+            b'0'..=b'9' => {
+                digits.push(b - b'0');
+                if fraction {
+                    exponent -= 1;
+                }
+            }
+            b'.' => fraction = true,
+            b'_' => {}
+            _ => return None,
+        }
+    }
+    Some(strip_zeros(digits, exponent))
+}
+
+/// Significant digits and power of ten of the exact value of a finite float
+fn float_digits(value: f64) -> (Vec<u8>, i64) {
+    let bits = value.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exponent) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased - 1075)
+    };
+    // mantissa * 2^exponent, or mantissa * 5^-exponent * 10^exponent
+    let mut n = vec![
+        (mantissa % 1_000_000_000) as u32,
+        (mantissa / 1_000_000_000) as u32,
+    ];
+    if exponent >= 0 {
+        mul_pow(&mut n, 2, 31, exponent as u32);
+    } else {
+        mul_pow(&mut n, 5, 13, (-exponent) as u32);
+    }
+    let mut digits = Vec::new();
+    for (i, limb) in n.iter().rev().enumerate() {
+        let limb = if i == 0 {
+            limb.to_string()
+        } else {
+            format!("{limb:09}")
+        };
+        digits.extend(limb.bytes().map(|b| b - b'0'));
+    }
+    strip_zeros(digits, exponent.min(0))
+}
+
+/// Multiply a little-endian base 10^9 number by `base^power`, `chunk` powers at a time
+fn mul_pow(n: &mut Vec<u32>, base: u64, chunk: u32, mut power: u32) {
+    while power > 0 {
+        let step = power.min(chunk);
+        power -= step;
+        let factor = base.pow(step);
+        let mut carry = 0u64;
+        for limb in n.iter_mut() {
+            let product = *limb as u64 * factor + carry;
+            *limb = (product % 1_000_000_000) as u32;
+            carry = product / 1_000_000_000;
+        }
+        while carry > 0 {
+            n.push((carry % 1_000_000_000) as u32);
+            carry /= 1_000_000_000;
+        }
+    }
+}
+
+fn strip_zeros(mut digits: Vec<u8>, mut exponent: i64) -> (Vec<u8>, i64) {
+    let leading = digits.iter().take_while(|&&d| d == 0).count();
+    digits.drain(..leading);
+    while digits.last() == Some(&0) {
+        digits.pop();
+        exponent += 1;
+    }
+    if digits.is_empty() {
+        exponent = 0;
+    }
+    (digits, exponent)
+}
+
 // --- Zero-allocation parsing helpers ---
 
 /// Parse integer string in given radix, skipping '_' characters, without heap allocation.
@@ -544,6 +2854,51 @@ fn parse_sexagesimal_int(s: &str) -> Result<i64, ()> {
     Ok(result)
 }
 
+/// Decode base64 into `bytes`, ignoring the line breaks and indentation of block scalars
+fn construct_binary_direct(py: Python, value: &str) -> PyResult<Py<PyAny>> {
+    let data: Vec<u8> = value.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let decoded = BASE64_STANDARD.decode(&data).map_err(|e| {
+        exception::constructor_error(py, format!("failed to decode base64 data: {}", e))
+    })?;
+    Ok(PyBytes::new(py, &decoded).into_any().unbind())
+}
+
+/// Construct a `datetime.date` for date-only values, otherwise a `datetime.datetime`
+/// which is timezone-aware only when the value carries an offset
+fn construct_timestamp_direct(py: Python, value: &str) -> PyResult<Py<PyAny>> {
+    let Some(ts) = resolver::parse_timestamp(value) else {
+        return Err(exception::constructor_error(
+            py,
+            format!("invalid timestamp: {}", value),
+        ));
+    };
+    let Some(time) = ts.time else {
+        return Ok(PyDate::new(py, ts.year, ts.month, ts.day)?
+            .into_any()
+            .unbind());
+    };
+    let tzinfo = match time.offset {
+        None => None,
+        Some(0) if value.ends_with('Z') => Some(PyTzInfo::utc(py)?.to_owned()),
+        Some(minutes) => {
+            let delta = PyDelta::new(py, 0, minutes * 60, 0, true)?;
+            Some(PyTzInfo::fixed_offset(py, delta)?)
+        }
+    };
+    let datetime = PyDateTime::new(
+        py,
+        ts.year,
+        ts.month,
+        ts.day,
+        time.hour,
+        time.minute,
+        time.second,
+        time.microsecond,
+        tzinfo.as_ref(),
+    )?;
+    Ok(datetime.into_any().unbind())
+}
+
 /// Parse float string, skipping '_' characters, without heap allocation for typical values.
 fn parse_float_skip_underscores(s: &str) -> Result<f64, ()> {
     // Fast path: no underscores
@@ -579,7 +2934,321 @@ fn parse_sexagesimal_float(s: &str) -> Result<f64, ()> {
     Ok(result)
 }
 
+/// Loader also constructing the `!!python/...` tags of pyyaml's FullLoader:
+/// builtin types, and names and modules which are already imported
+#[pyclass(name = "_RFullLoader", extends = RSafeLoader, subclass)]
+pub struct RFullLoader {}
+
+#[pymethods]
+impl RFullLoader {
+    #[new]
+    #[pyo3(signature = (source, **kwargs))]
+    pub fn py_new(
+        py: Python,
+        source: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, RSafeLoader)> {
+        let mut options = LoaderOptions::from_kwargs(kwargs)?;
+        options.python_tags = PythonTags::Full;
+        Ok((
+            Self {},
+            RSafeLoader::new(py, source.into_text(py)?, options)?,
+        ))
+    }
+}
+
+/// Loader resolving nothing, like pyyaml's BaseLoader: tags are ignored and
+/// every scalar loads as a string, so values like `no`, `1.0` or `2023-01-01`
+/// come back exactly as written
+#[pyclass(name = "_RBaseLoader", extends = RSafeLoader, subclass)]
+pub struct RBaseLoader {}
+
+#[pymethods]
+impl RBaseLoader {
+    #[new]
+    #[pyo3(signature = (source, **kwargs))]
+    pub fn py_new(
+        py: Python,
+        source: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, RSafeLoader)> {
+        let mut options = LoaderOptions::from_kwargs(kwargs)?;
+        options.schema = Schema::Failsafe;
+        options.ignore_tags = true;
+        Ok((
+            Self {},
+            RSafeLoader::new(py, source.into_text(py)?, options)?,
+        ))
+    }
+}
+
+/// Loader constructing every `!!python/...` tag of pyyaml's UnsafeLoader,
+/// importing modules and instantiating arbitrary classes. Never use it on
+/// untrusted input; nothing else in ryaml constructs these tags.
+#[pyclass(name = "_RUnsafeLoader", extends = RSafeLoader, subclass)]
+pub struct RUnsafeLoader {}
+
+#[pymethods]
+impl RUnsafeLoader {
+    #[new]
+    #[pyo3(signature = (source, **kwargs))]
+    pub fn py_new(
+        py: Python,
+        source: Source<'_>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, RSafeLoader)> {
+        let mut options = LoaderOptions::from_kwargs(kwargs)?;
+        options.python_tags = PythonTags::Unsafe;
+        Ok((
+            Self {},
+            RSafeLoader::new(py, source.into_text(py)?, options)?,
+        ))
+    }
+}
+
+/// A plain scalar which implicit resolution gave a type other than str, as
+/// reported by `loads(explain=True)`
+#[pyclass(name = "Resolution", frozen)]
+pub struct Resolution {
+    /// The scalar as written
+    #[pyo3(get)]
+    pub value: String,
+    /// The tag it resolved to
+    #[pyo3(get)]
+    pub tag: String,
+    /// Where the scalar starts
+    #[pyo3(get)]
+    pub mark: PyMark,
+}
+
+#[pymethods]
+impl Resolution {
+    fn __repr__(&self) -> String {
+        format!(
+            "Resolution(value={:?}, tag={:?}, line={}, column={})",
+            self.value,
+            self.tag,
+            self.mark.line + 1,
+            self.mark.column + 1
+        )
+    }
+}
+
+/// Counts of what a load parsed, as reported by `loads(stats=True)`
+#[pyclass(name = "ParseStats", frozen)]
+#[derive(Debug, Clone, Default)]
+pub struct ParseStats {
+    #[pyo3(get)]
+    pub documents: usize,
+    #[pyo3(get)]
+    pub scalars: usize,
+    #[pyo3(get)]
+    pub sequences: usize,
+    #[pyo3(get)]
+    pub mappings: usize,
+    /// Nodes given an anchor
+    #[pyo3(get)]
+    pub anchors: usize,
+    #[pyo3(get)]
+    pub aliases: usize,
+    /// Deepest nesting of collections
+    #[pyo3(get)]
+    pub max_depth: usize,
+    /// Seconds spent parsing events
+    #[pyo3(get)]
+    pub parse_time: f64,
+    /// Seconds the whole load took, constructing included
+    #[pyo3(get)]
+    pub load_time: f64,
+    /// Collections open around the event last recorded
+    depth: usize,
+}
+
+impl ParseStats {
+    fn record(&mut self, event: &Event) {
+        let anchor = match &event.data {
+            EventData::DocumentStart { .. } => {
+                self.documents += 1;
+                return;
+            }
+            EventData::Alias { .. } => {
+                self.aliases += 1;
+                return;
+            }
+            EventData::SequenceEnd | EventData::MappingEnd => {
+                self.depth = self.depth.saturating_sub(1);
+                return;
+            }
+            EventData::Scalar { anchor, .. } => {
+                self.scalars += 1;
+                anchor
+            }
+            EventData::SequenceStart { anchor, .. } => {
+                self.sequences += 1;
+                self.depth += 1;
+                anchor
+            }
+            EventData::MappingStart { anchor, .. } => {
+                self.mappings += 1;
+                self.depth += 1;
+                anchor
+            }
+            _ => return,
+        };
+        self.max_depth = self.max_depth.max(self.depth);
+        self.anchors += usize::from(anchor.is_some());
+    }
+}
+
+#[pymethods]
+impl ParseStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "ParseStats(documents={}, scalars={}, sequences={}, mappings={}, anchors={}, \
+             aliases={}, max_depth={}, parse_time={:.6}, load_time={:.6})",
+            self.documents,
+            self.scalars,
+            self.sequences,
+            self.mappings,
+            self.anchors,
+            self.aliases,
+            self.max_depth,
+            self.parse_time,
+            self.load_time
+        )
+    }
+}
+
+/// Iterator over the documents of a string or file object, parsing each one
+/// only when requested. Anything after the last document pulled is never parsed.
+#[pyclass(name = "DocumentIterator")]
+pub struct DocumentIterator {
+    loader: RSafeLoader,
+}
+
+impl DocumentIterator {
+    pub fn new(py: Python, source: SourceText, options: LoaderOptions) -> PyResult<Self> {
+        Ok(Self {
+            loader: RSafeLoader::new(py, source, options)?,
+        })
+    }
+
+    /// Documents of the file object `file`, read only as far as they are pulled
+    pub fn from_file(py: Python, file: Py<PyAny>, options: LoaderOptions) -> PyResult<Self> {
+        Ok(Self {
+            loader: RSafeLoader::from_file(py, file, options, None)?,
+        })
+    }
+}
+
+#[pymethods]
+impl DocumentIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        if !self.loader.check_data(py)? {
+            return Ok(None);
+        }
+        Ok(Some(self.loader.get_data(py)?.unwrap_or_else(|| py.None())))
+    }
+}
+
+/// Iterator over the documents of a file object as they become available.
+/// With `follow`, the end of the file isn't the end of the stream: reading
+/// waits for more to be written, like `tail -f`, so the iterator never ends
+/// on its own. A document is yielded once the parser sees where it ends, at
+/// `...` or the `---` of the next one.
+#[pyclass(name = "DocumentReader")]
+pub struct DocumentReader {
+    loader: RSafeLoader,
+}
+
+#[pymethods]
+impl DocumentReader {
+    /// Read `stream`, loading with the keyword arguments of `load()`,
+    /// checking for more every `poll_interval` seconds when following
+    #[new]
+    #[pyo3(signature = (stream, follow=false, poll_interval=0.1, **kwargs))]
+    fn new(
+        py: Python,
+        stream: Py<PyAny>,
+        follow: bool,
+        poll_interval: f64,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Self> {
+        let interval = Duration::try_from_secs_f64(poll_interval)
+            .ok()
+            .filter(|interval| !interval.is_zero())
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "poll_interval must be a positive number of seconds, got {poll_interval}"
+                ))
+            })?;
+        let options = LoaderOptions::from_kwargs(kwargs)?;
+        Ok(Self {
+            loader: RSafeLoader::from_file(py, stream, options, follow.then_some(interval))?,
+        })
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        if !self.loader.check_data(py)? {
+            return Ok(None);
+        }
+        Ok(Some(self.loader.get_data(py)?.unwrap_or_else(|| py.None())))
+    }
+}
+
+/// A loaded document with the `%YAML` and `%TAG` directives it declares and
+/// the marks it spans, as `loads_all(with_meta=True)` gives them
+#[pyclass(name = "Document", frozen)]
+pub struct Document {
+    #[pyo3(get)]
+    data: Py<PyAny>,
+    /// `(major, minor)` of the `%YAML` directive, if any
+    #[pyo3(get)]
+    version: Option<(i32, i32)>,
+    /// Handles declared with `%TAG`, mapping handle to prefix
+    #[pyo3(get)]
+    tags: HashMap<String, String>,
+    /// Where the document starts, at its `---` if it has one
+    #[pyo3(get)]
+    start_mark: PyMark,
+    /// Where the document ends, after its `...` if it has one
+    #[pyo3(get)]
+    end_mark: PyMark,
+}
+
+#[pymethods]
+impl Document {
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let mut handles: Vec<_> = self.tags.iter().collect();
+        handles.sort();
+        let tags = PyDict::new(py);
+        for (handle, prefix) in handles {
+            tags.set_item(handle, prefix)?;
+        }
+        let version = match self.version {
+            Some((major, minor)) => format!("({major}, {minor})"),
+            None => "None".to_string(),
+        };
+        Ok(format!(
+            "Document({}, version={version}, tags={})",
+            self.data.bind(py).repr()?,
+            tags.repr()?
+        ))
+    }
+}
+
 pub fn register_loader(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
     m.add_class::<RSafeLoader>()?;
+    m.add_class::<DocumentIterator>()?;
+    m.add_class::<DocumentReader>()?;
+    m.add_class::<Document>()?;
     Ok(())
 }
@@ -0,0 +1,226 @@
+//! Files loaded through a read-only memory map rather than read into a
+//! string, so a large file is held once, by the page cache, instead of once
+//! more by the loader. Files are only mapped when `load(mmap=True)` asks,
+//! since the text is checked to be UTF-8 once: a file truncated or rewritten
+//! while it loads can crash the process. Only files of UTF-8 text are mapped;
+//! the rest are read and decoded as before.
+
+use std::path::Path;
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::encoding::SourceText;
+
+/// Smallest file worth mapping; smaller ones are read, which costs less
+const MIN_MAPPED_SIZE: u64 = 1 << 20;
+
+/// The UTF-8 text of a mapped file, passed to `loads()` like a str
+#[pyclass(name = "MappedFile", frozen)]
+pub struct MappedFile(pub SourceText);
+
+#[pymethods]
+impl MappedFile {
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Map the contents of the file open as `fd` from byte `offset` on, if it is a
+/// regular file large enough to be worth it and holding UTF-8 text. The file
+/// must not change while the mapping is in use.
+pub fn map_file(py: Python, fd: i32, offset: u64) -> Option<MappedFile> {
+    let mapping = py.detach(|| Mapping::new(fd, offset));
+    mapping.map(|mapping| MappedFile(SourceText::from(mapping)))
+}
+
+/// The text of the file at `path`, read and decoded
+pub fn read_file(path: &Path) -> std::io::Result<SourceText> {
+    match open(path, false)? {
+        Contents::Mapped(mapping) => Ok(SourceText::from(mapping)),
+        Contents::Read(bytes) => crate::encoding::decode_source(&bytes)
+            .map(SourceText::from)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+    }
+}
+
+/// The file at `path` as a source for `loads()`: mapped if `mmap` asks and
+/// it can be, otherwise its bytes, decoded by their detected encoding when
+/// loaded
+pub fn read_path(py: Python, path: &Path, mmap: bool) -> PyResult<Py<PyAny>> {
+    Ok(match py.detach(|| open(path, mmap))? {
+        Contents::Mapped(mapping) => Py::new(py, MappedFile(SourceText::from(mapping)))?.into_any(),
+        Contents::Read(bytes) => PyBytes::new(py, &bytes).into_any().unbind(),
+    })
+}
+
+/// Bytes read from a descriptor at a time, with the GIL released
+const READ_CHUNK_SIZE: usize = 1 << 16;
+
+/// The rest of the file open as `fd`, read without going through a Python
+/// file object, after the `prefix` already read from it. Past `max_bytes`,
+/// one more byte is read, which is enough for `loads()` to reject the stream.
+#[cfg(unix)]
+pub fn read_fd(py: Python, fd: i32, prefix: &[u8], max_bytes: Option<u64>) -> PyResult<Py<PyAny>> {
+    use std::io::Read;
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: the descriptor is open for the length of the call, and is left
+    // for its owner to close
+    let file = std::mem::ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(fd) });
+    let limit = max_bytes.map_or(usize::MAX, |max| {
+        usize::try_from(max.saturating_add(1)).unwrap_or(usize::MAX)
+    });
+    let mut bytes = prefix.to_vec();
+    while bytes.len() < limit {
+        let start = bytes.len();
+        bytes.resize(start + READ_CHUNK_SIZE.min(limit - start), 0);
+        let read = py.detach(|| (&*file).read(&mut bytes[start..]));
+        match read {
+            Ok(0) => {
+                bytes.truncate(start);
+                break;
+            }
+            Ok(count) => bytes.truncate(start + count),
+            // Let Ctrl-C stop a read waiting on a pipe
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+                bytes.truncate(start);
+                py.check_signals()?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(PyBytes::new(py, &bytes).into_any().unbind())
+}
+
+/// Descriptors are only read natively on Unix; elsewhere through Python
+#[cfg(not(unix))]
+pub fn read_fd(
+    _py: Python,
+    _fd: i32,
+    _prefix: &[u8],
+    _max_bytes: Option<u64>,
+) -> PyResult<Py<PyAny>> {
+    Err(pyo3::exceptions::PyOSError::new_err(
+        "file descriptors are only read natively on Unix",
+    ))
+}
+
+/// A file opened by path
+enum Contents {
+    Mapped(Mapping),
+    Read(Vec<u8>),
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn open(path: &Path, mmap: bool) -> std::io::Result<Contents> {
+    let file = std::fs::File::open(path)?;
+    #[cfg(unix)]
+    if mmap {
+        use std::os::fd::AsRawFd;
+        if let Some(mapping) = Mapping::new(file.as_raw_fd(), 0) {
+            return Ok(Contents::Mapped(mapping));
+        }
+    }
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut &file, &mut bytes)?;
+    Ok(Contents::Read(bytes))
+}
+
+/// A read-only private mapping of a file, unmapped when dropped
+#[cfg(unix)]
+pub struct Mapping {
+    start: *mut libc::c_void,
+    len: usize,
+    /// Bytes of the mapping before the text: the skipped part of the file and
+    /// any byte order mark
+    offset: usize,
+}
+
+// SAFETY: the mapping is read-only and owned by this value alone
+#[cfg(unix)]
+unsafe impl Send for Mapping {}
+#[cfg(unix)]
+unsafe impl Sync for Mapping {}
+
+#[cfg(unix)]
+impl Mapping {
+    fn new(fd: i32, offset: u64) -> Option<Self> {
+        use std::os::fd::FromRawFd;
+
+        // SAFETY: the descriptor is open; it belongs to the Python file object,
+        // so it isn't closed here
+        let file = std::mem::ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(fd) });
+        let metadata = file.metadata().ok()?;
+        if !metadata.is_file() || metadata.len() < offset.saturating_add(MIN_MAPPED_SIZE) {
+            return None;
+        }
+        let len = usize::try_from(metadata.len()).ok()?;
+        let offset = usize::try_from(offset).ok()?;
+        // SAFETY: a new private read-only mapping of a descriptor that is open;
+        // the result is checked before use
+        let start = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                fd,
+                0,
+            )
+        };
+        if start == libc::MAP_FAILED {
+            return None;
+        }
+        let mut mapping = Self { start, len, offset };
+        let bytes = &mapping.bytes()[offset..];
+        if bytes.starts_with(b"\xEF\xBB\xBF") {
+            mapping.offset += 3;
+        }
+        // Other encodings are left to be read and decoded
+        std::str::from_utf8(&mapping.bytes()[mapping.offset..]).ok()?;
+        Some(mapping)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: `start` points to `len` readable bytes until dropped
+        unsafe { std::slice::from_raw_parts(self.start.cast::<u8>(), self.len) }
+    }
+
+    pub fn text(&self) -> &str {
+        // SAFETY: checked to be UTF-8 when mapped. A private mapping still
+        // shows changes made to the file after that, which is why mapping is
+        // opt-in and documented as needing a file nothing else writes.
+        unsafe { std::str::from_utf8_unchecked(&self.bytes()[self.offset..]) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: the mapping made in `new`, which nothing borrows any more
+        unsafe {
+            libc::munmap(self.start, self.len);
+        }
+    }
+}
+
+/// Files are only mapped on Unix; elsewhere they are read
+#[cfg(not(unix))]
+pub enum Mapping {}
+
+#[cfg(not(unix))]
+impl Mapping {
+    fn new(_fd: i32, _offset: u64) -> Option<Self> {
+        None
+    }
+
+    pub fn text(&self) -> &str {
+        match *self {}
+    }
+}
+
+pub fn register_mapped(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<MappedFile>()?;
+    Ok(())
+}
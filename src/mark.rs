@@ -1,29 +1,134 @@
 //! Mark Python class which is duck-type compatible with pyyaml's Mark type.
 
 use pyo3::prelude::*;
+use pyo3::types::PyType;
+
+use crate::encoding::SourceText;
+
+/// Source name used when the input has no better name, matching pyyaml
+pub const DEFAULT_SOURCE_NAME: &str = "<unicode string>";
+
+/// Characters which end a line when rendering a snippet
+const LINE_BREAKS: &[char] = &['\0', '\r', '\n', '\u{85}', '\u{2028}', '\u{2029}'];
 
 #[derive(Debug, Clone)]
-#[pyclass(name = "Mark")]
+#[pyclass(name = "Mark", module = "ryaml._ryaml")]
 pub struct PyMark {
+    #[pyo3(get)]
+    pub name: String,
     #[pyo3(get)]
     pub index: u64,
     #[pyo3(get)]
     pub line: u64,
     #[pyo3(get)]
     pub column: u64,
+    /// Source text the mark points into, shared between all marks of a load
+    pub buffer: Option<SourceText>,
+    #[pyo3(get)]
+    pub pointer: u64,
 }
 
 #[pymethods]
 impl PyMark {
     #[new]
-    pub fn new(index: u64, line: u64, column: u64) -> Self {
+    #[pyo3(signature = (index, line, column, name=None, buffer=None, pointer=None))]
+    pub fn new(
+        index: u64,
+        line: u64,
+        column: u64,
+        name: Option<String>,
+        buffer: Option<String>,
+        pointer: Option<u64>,
+    ) -> Self {
         Self {
+            name: name.unwrap_or_else(|| DEFAULT_SOURCE_NAME.to_string()),
             index,
             line,
             column,
+            buffer: buffer.map(SourceText::from),
+            pointer: pointer.unwrap_or(index),
+        }
+    }
+
+    #[getter]
+    fn buffer(&self) -> Option<&str> {
+        self.buffer.as_deref()
+    }
+
+    /// Render the line around the mark with a caret under its column,
+    /// or None if the mark has no buffer
+    #[pyo3(signature = (indent=4, max_length=75))]
+    pub fn get_snippet(&self, indent: usize, max_length: usize) -> Option<String> {
+        let buffer: Vec<char> = self.buffer.as_deref()?.chars().collect();
+        let pointer = (self.pointer as usize).min(buffer.len());
+        let half = max_length as f64 / 2.0 - 1.0;
+
+        let mut head = "";
+        let mut start = pointer;
+        while start > 0 && !LINE_BREAKS.contains(&buffer[start - 1]) {
+            start -= 1;
+            if (pointer - start) as f64 > half {
+                head = " ... ";
+                start += 5;
+                break;
+            }
+        }
+
+        let mut tail = "";
+        let mut end = pointer;
+        while end < buffer.len() && !LINE_BREAKS.contains(&buffer[end]) {
+            end += 1;
+            if (end - pointer) as f64 > half {
+                tail = " ... ";
+                end -= 5;
+                break;
+            }
+        }
+
+        let snippet: String = buffer[start..end].iter().collect();
+        Some(format!(
+            "{}{head}{snippet}{tail}\n{}^",
+            " ".repeat(indent),
+            " ".repeat(indent + pointer - start + head.len()),
+        ))
+    }
+
+    fn __str__(&self) -> String {
+        let location = format!(
+            "  in \"{}\", line {}, column {}",
+            self.name,
+            self.line + 1,
+            self.column + 1
+        );
+        match self.get_snippet(4, 75) {
+            Some(snippet) => format!("{location}:\n{snippet}"),
+            None => location,
         }
     }
 
+    /// Pickled as the arguments rebuilding it, so errors holding marks can
+    /// be sent between processes
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (
+        Bound<'py, PyType>,
+        (u64, u64, u64, String, Option<String>, u64),
+    ) {
+        let mark = slf.borrow();
+        (
+            slf.get_type(),
+            (
+                mark.index,
+                mark.line,
+                mark.column,
+                mark.name.clone(),
+                mark.buffer.as_deref().map(str::to_string),
+                mark.pointer,
+            ),
+        )
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "<Mark index={} line={} column={}>",
@@ -32,12 +137,57 @@ impl PyMark {
     }
 }
 
+impl PyMark {
+    /// Build a mark pointing into `buffer`, which was read from `name`
+    pub fn with_source(mark: libyaml_safer::Mark, name: &str, buffer: &SourceText) -> Self {
+        Self {
+            name: name.to_string(),
+            buffer: Some(buffer.clone()),
+            ..Self::from(mark)
+        }
+    }
+}
+
+impl PyMark {
+    /// Copy a mark from pyyaml's `yaml.Mark`, or anything else with its
+    /// attributes, or None
+    pub fn from_any(mark: &Bound<'_, PyAny>) -> PyResult<Option<Self>> {
+        if mark.is_none() {
+            return Ok(None);
+        }
+        if let Ok(mark) = mark.extract::<PyMark>() {
+            return Ok(Some(mark));
+        }
+        let index: u64 = mark.getattr("index")?.extract()?;
+        let buffer: Option<String> = match mark.getattr_opt("buffer")? {
+            Some(buffer) => buffer.extract()?,
+            None => None,
+        };
+        let pointer: Option<u64> = match mark.getattr_opt("pointer")? {
+            Some(pointer) => pointer.extract()?,
+            None => None,
+        };
+        Ok(Some(Self::new(
+            index,
+            mark.getattr("line")?.extract()?,
+            mark.getattr("column")?.extract()?,
+            mark.getattr("name")?.extract()?,
+            // pyyaml ends its buffers with a NUL
+            buffer.map(|buffer| buffer.trim_end_matches('\0').to_string()),
+            pointer,
+        )))
+    }
+}
+
 impl From<libyaml_safer::Mark> for PyMark {
     fn from(mark: libyaml_safer::Mark) -> Self {
         Self {
+            name: DEFAULT_SOURCE_NAME.to_string(),
             index: mark.index,
             line: mark.line,
             column: mark.column,
+            buffer: None,
+            pointer: mark.index,
         }
     }
 }
@@ -0,0 +1,285 @@
+//! `merge()`: documents layered one over the other, as Helm and Ansible do
+//! with values files. Node trees are merged rather than loaded values, so
+//! tags, styles and shared nodes come through, and the inputs are left as
+//! they are: merged collections are new nodes.
+
+use std::collections::{HashMap, HashSet};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use crate::encoding::Source;
+use crate::loader::{LoaderOptions, RSafeLoader};
+use crate::nodes::{PyMappingNode, PyNode, PySequenceNode};
+
+/// How a value is merged over another
+pub enum Strategy {
+    /// Mappings are merged key by key, all the way down
+    Deep,
+    /// Top-level keys are replaced whole
+    Override,
+    /// Called with the path, the base node and the node over it; returns the
+    /// merged node, or None to merge them as `Deep` does
+    Custom(Py<PyAny>),
+}
+
+/// How a sequence is merged over another, with the `Deep` strategy
+pub enum Lists {
+    Replace,
+    Append,
+    /// Mappings with the same value at this key are merged; other items are
+    /// appended
+    MergeByKey(String),
+}
+
+pub struct Merger {
+    strategy: Strategy,
+    lists: Lists,
+}
+
+/// A step into a merged value, given to custom strategies
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Merger {
+    pub fn new(
+        strategy: Option<&Bound<'_, PyAny>>,
+        lists: &str,
+        list_key: Option<String>,
+    ) -> PyResult<Self> {
+        let strategy = if let Some(strategy) = strategy.filter(|strategy| strategy.is_callable()) {
+            Strategy::Custom(strategy.clone().unbind())
+        } else if let Some(strategy) = strategy {
+            match strategy.extract::<String>()?.as_str() {
+                "deep" => Strategy::Deep,
+                "override" => Strategy::Override,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unsupported strategy '{other}' (expected 'deep', 'override' or a callable)"
+                    )));
+                }
+            }
+        } else {
+            Strategy::Deep
+        };
+        let lists = match (lists, list_key) {
+            ("replace", _) => Lists::Replace,
+            ("append", _) => Lists::Append,
+            ("merge-by-key", Some(key)) => Lists::MergeByKey(key),
+            ("merge-by-key", None) => {
+                return Err(PyValueError::new_err(
+                    "lists='merge-by-key' needs the list_key to match items by",
+                ));
+            }
+            (other, _) => {
+                return Err(PyValueError::new_err(format!(
+                    "unsupported lists '{other}' (expected 'replace', 'append' or 'merge-by-key')"
+                )));
+            }
+        };
+        Ok(Self { strategy, lists })
+    }
+
+    /// `nodes` merged in order, each over the ones before it
+    pub fn merge(&self, py: Python, nodes: Vec<PyNode>) -> PyResult<Option<PyNode>> {
+        let mut merged: Option<PyNode> = None;
+        for node in nodes {
+            merged = Some(match merged {
+                Some(base) => self.node(py, &base, &node, &mut Vec::new(), &mut HashSet::new())?,
+                None => node,
+            });
+        }
+        Ok(merged)
+    }
+
+    /// `over` merged over `base`, found at `path`. `merging` holds the pairs
+    /// of nodes being merged, so recursive nodes end.
+    fn node(
+        &self,
+        py: Python,
+        base: &PyNode,
+        over: &PyNode,
+        path: &mut Vec<Segment>,
+        merging: &mut HashSet<(usize, usize)>,
+    ) -> PyResult<PyNode> {
+        if let Strategy::Custom(strategy) = &self.strategy {
+            let steps = PyList::empty(py);
+            for segment in path.iter() {
+                match segment {
+                    Segment::Key(key) => steps.append(key)?,
+                    Segment::Index(index) => steps.append(index)?,
+                }
+            }
+            let merged = strategy
+                .bind(py)
+                .call1((steps, base.clone(), over.clone()))?;
+            if !merged.is_none() {
+                return PyNode::from_any(&merged);
+            }
+        }
+        if matches!(self.strategy, Strategy::Override) && !path.is_empty() {
+            return Ok(over.clone());
+        }
+        let pair = (base.as_ptr() as usize, over.as_ptr() as usize);
+        if base.get_tag(py)? != over.get_tag(py)? || !merging.insert(pair) {
+            return Ok(over.clone());
+        }
+        let merged = match (base, over) {
+            (PyNode::Mapping(base), PyNode::Mapping(over)) => {
+                self.mapping(py, &base.borrow(py), &over.borrow(py), path, merging)
+            }
+            (PyNode::Sequence(base), PyNode::Sequence(over)) => {
+                self.sequence(py, &base.borrow(py), &over.borrow(py), path, merging)
+            }
+            _ => Ok(over.clone()),
+        };
+        merging.remove(&pair);
+        merged
+    }
+
+    fn mapping(
+        &self,
+        py: Python,
+        base: &PyMappingNode,
+        over: &PyMappingNode,
+        path: &mut Vec<Segment>,
+        merging: &mut HashSet<(usize, usize)>,
+    ) -> PyResult<PyNode> {
+        let mut pairs = base.value.clone();
+        let mut positions = HashMap::new();
+        for (position, (key, _)) in pairs.iter().enumerate() {
+            if let Some(key) = scalar_key(py, key) {
+                positions.entry(key).or_insert(position);
+            }
+        }
+        for (key, value) in &over.value {
+            let scalar = scalar_key(py, key);
+            match scalar.as_ref().and_then(|key| positions.get(key)) {
+                Some(&position) => {
+                    path.push(Segment::Key(scalar.expect("matched a scalar key").1));
+                    let merged = self.node(py, &pairs[position].1, value, path, merging);
+                    path.pop();
+                    pairs[position].1 = merged?;
+                }
+                None => {
+                    if let Some(scalar) = scalar {
+                        positions.insert(scalar, pairs.len());
+                    }
+                    pairs.push((key.clone(), value.clone()));
+                }
+            }
+        }
+        let node = PyMappingNode::new(
+            base.tag.clone(),
+            pairs,
+            base.start_mark.clone(),
+            base.end_mark.clone(),
+            base.flow_style,
+        );
+        Ok(PyNode::Mapping(Py::new(py, node)?))
+    }
+
+    fn sequence(
+        &self,
+        py: Python,
+        base: &PySequenceNode,
+        over: &PySequenceNode,
+        path: &mut Vec<Segment>,
+        merging: &mut HashSet<(usize, usize)>,
+    ) -> PyResult<PyNode> {
+        let items = match &self.lists {
+            Lists::Replace => over.value.clone(),
+            Lists::Append => base.value.iter().chain(&over.value).cloned().collect(),
+            Lists::MergeByKey(list_key) => {
+                let mut items = base.value.clone();
+                let mut positions = HashMap::new();
+                for (position, item) in items.iter().enumerate() {
+                    if let Some(id) = item_id(py, item, list_key) {
+                        positions.entry(id).or_insert(position);
+                    }
+                }
+                for item in &over.value {
+                    match item_id(py, item, list_key).and_then(|id| positions.get(&id)) {
+                        Some(&position) => {
+                            path.push(Segment::Index(position));
+                            let merged = self.node(py, &items[position], item, path, merging);
+                            path.pop();
+                            items[position] = merged?;
+                        }
+                        None => {
+                            if let Some(id) = item_id(py, item, list_key) {
+                                positions.insert(id, items.len());
+                            }
+                            items.push(item.clone());
+                        }
+                    }
+                }
+                items
+            }
+        };
+        let node = PySequenceNode::new(
+            base.tag.clone(),
+            items,
+            base.start_mark.clone(),
+            base.end_mark.clone(),
+            base.flow_style,
+        );
+        Ok(PyNode::Sequence(Py::new(py, node)?))
+    }
+}
+
+/// The root nodes of `documents`: YAML texts, composed with `options`, or
+/// nodes. Empty documents are left out.
+pub fn document_nodes(
+    py: Python,
+    documents: &Bound<'_, PyAny>,
+    options: &LoaderOptions,
+) -> PyResult<Vec<PyNode>> {
+    let mut nodes = Vec::new();
+    for document in documents.try_iter()? {
+        nodes.extend(document_node(py, &document?, options)?);
+    }
+    Ok(nodes)
+}
+
+/// The root node of `document`, YAML text composed with `options` or a
+/// node; None for an empty document
+pub fn document_node(
+    py: Python,
+    document: &Bound<'_, PyAny>,
+    options: &LoaderOptions,
+) -> PyResult<Option<PyNode>> {
+    match document.extract::<Source>() {
+        Ok(source) => {
+            RSafeLoader::new(py, source.into_text(py)?, options.clone())?.get_single_node(py)
+        }
+        Err(_) => Ok(Some(PyNode::from_any(document)?)),
+    }
+}
+
+/// The tag and value of a scalar key, which keys of the other mapping match
+pub fn scalar_key(py: Python, key: &PyNode) -> Option<(String, String)> {
+    match key {
+        PyNode::Scalar(scalar) => {
+            let scalar = scalar.borrow(py);
+            Some((scalar.tag.clone(), scalar.value.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// The scalar at `list_key` of a mapping item, identifying it among the items
+/// of a sequence
+fn item_id(py: Python, item: &PyNode, list_key: &str) -> Option<(String, String)> {
+    let PyNode::Mapping(mapping) = item else {
+        return None;
+    };
+    let mapping = mapping.borrow(py);
+    mapping.value.iter().find_map(|(key, value)| match key {
+        PyNode::Scalar(key) if key.borrow(py).value == list_key => scalar_key(py, value),
+        _ => None,
+    })
+}
@@ -1,8 +1,14 @@
 //! Nodes representing YAML data, corresponding to PyYAML's nodes classes
 
+use std::collections::{HashMap, HashSet};
+
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyString;
 
 use crate::mark::PyMark;
+use crate::query::{Step, parse_path};
+use crate::resolver::{self, Schema};
 
 #[derive(Debug, Clone)]
 #[pyclass(name = "ScalarNode")]
@@ -22,6 +28,7 @@ pub struct PyScalarNode {
 #[pymethods]
 impl PyScalarNode {
     #[new]
+    #[pyo3(signature = (tag, value, start_mark=None, end_mark=None, style=None))]
     pub fn new(
         tag: String,
         value: String,
@@ -42,6 +49,33 @@ impl PyScalarNode {
     fn id(&self) -> &'static str {
         "scalar"
     }
+
+    /// Equal to `other` when of the same kind, with the same tag and equal
+    /// values, whatever their marks and styles
+    fn __eq__(slf: &Bound<'_, Self>, other: PyNode) -> bool {
+        PyNode::Scalar(slf.clone().unbind()).equals(slf.py(), &other, &mut HashSet::new())
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        PyNode::Scalar(slf.clone().unbind()).repr(slf.py(), &mut HashSet::new())
+    }
+
+    /// The node at `path`, such as `spec.containers[0].image`, below this
+    /// one; None when nothing is there
+    fn find(slf: &Bound<'_, Self>, path: &str) -> PyResult<Option<PyNode>> {
+        let steps = edit_steps(path)?;
+        Ok(PyNode::Scalar(slf.clone().unbind()).find(slf.py(), &steps))
+    }
+
+    /// The Python data this node constructs to; with `schema`, plain scalars
+    /// are resolved again by its rules first
+    #[pyo3(signature = (*, schema=None))]
+    fn to_python(slf: &Bound<'_, Self>, schema: Option<&str>) -> PyResult<Py<PyAny>> {
+        PyNode::Scalar(slf.clone().unbind()).to_python(slf.py(), schema)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +96,7 @@ pub struct PySequenceNode {
 #[pymethods]
 impl PySequenceNode {
     #[new]
+    #[pyo3(signature = (tag, value, start_mark=None, end_mark=None, flow_style=None))]
     pub fn new(
         tag: String,
         value: Vec<PyNode>,
@@ -82,6 +117,60 @@ impl PySequenceNode {
     fn id(&self) -> &'static str {
         "sequence"
     }
+
+    /// Equal to `other` when of the same kind, with the same tag and equal
+    /// values, whatever their marks and styles
+    fn __eq__(slf: &Bound<'_, Self>, other: PyNode) -> bool {
+        PyNode::Sequence(slf.clone().unbind()).equals(slf.py(), &other, &mut HashSet::new())
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        PyNode::Sequence(slf.clone().unbind()).repr(slf.py(), &mut HashSet::new())
+    }
+
+    /// The node at `path`, such as `spec.containers[0].image`, below this
+    /// one; None when nothing is there
+    fn find(slf: &Bound<'_, Self>, path: &str) -> PyResult<Option<PyNode>> {
+        let steps = edit_steps(path)?;
+        Ok(PyNode::Sequence(slf.clone().unbind()).find(slf.py(), &steps))
+    }
+
+    /// Set the node at `path` to `value`, a node or data represented as
+    /// `dumps()` would; a missing last key is added to its mapping
+    fn set(slf: &Bound<'_, Self>, path: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let steps = edit_steps(path)?;
+        let value = node_of(value)?;
+        PyNode::Sequence(slf.clone().unbind()).edit(slf.py(), path, &steps, Some(value))
+    }
+
+    /// Remove the mapping entry or sequence item at `path`
+    fn delete(slf: &Bound<'_, Self>, path: &str) -> PyResult<()> {
+        let steps = edit_steps(path)?;
+        PyNode::Sequence(slf.clone().unbind()).edit(slf.py(), path, &steps, None)
+    }
+
+    /// Insert `value`, a node or data represented as `dumps()` would, before
+    /// the item at `index`, as `list.insert()` does
+    fn insert(&mut self, index: isize, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let len = self.value.len() as isize;
+        let index = if index < 0 {
+            (index + len).max(0)
+        } else {
+            index.min(len)
+        };
+        self.value.insert(index as usize, node_of(value)?);
+        Ok(())
+    }
+
+    /// The Python data this node constructs to; with `schema`, plain scalars
+    /// are resolved again by its rules first
+    #[pyo3(signature = (*, schema=None))]
+    fn to_python(slf: &Bound<'_, Self>, schema: Option<&str>) -> PyResult<Py<PyAny>> {
+        PyNode::Sequence(slf.clone().unbind()).to_python(slf.py(), schema)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -102,6 +191,7 @@ pub struct PyMappingNode {
 #[pymethods]
 impl PyMappingNode {
     #[new]
+    #[pyo3(signature = (tag, value, start_mark=None, end_mark=None, flow_style=None))]
     pub fn new(
         tag: String,
         value: Vec<(PyNode, PyNode)>,
@@ -122,6 +212,47 @@ impl PyMappingNode {
     fn id(&self) -> &'static str {
         "mapping"
     }
+
+    /// Equal to `other` when of the same kind, with the same tag and equal
+    /// values, whatever their marks and styles
+    fn __eq__(slf: &Bound<'_, Self>, other: PyNode) -> bool {
+        PyNode::Mapping(slf.clone().unbind()).equals(slf.py(), &other, &mut HashSet::new())
+    }
+
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        PyNode::Mapping(slf.clone().unbind()).repr(slf.py(), &mut HashSet::new())
+    }
+
+    /// The node at `path`, such as `spec.containers[0].image`, below this
+    /// one; None when nothing is there
+    fn find(slf: &Bound<'_, Self>, path: &str) -> PyResult<Option<PyNode>> {
+        let steps = edit_steps(path)?;
+        Ok(PyNode::Mapping(slf.clone().unbind()).find(slf.py(), &steps))
+    }
+
+    /// Set the node at `path` to `value`, a node or data represented as
+    /// `dumps()` would; a missing last key is added to its mapping
+    fn set(slf: &Bound<'_, Self>, path: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let steps = edit_steps(path)?;
+        let value = node_of(value)?;
+        PyNode::Mapping(slf.clone().unbind()).edit(slf.py(), path, &steps, Some(value))
+    }
+
+    /// Remove the mapping entry or sequence item at `path`
+    fn delete(slf: &Bound<'_, Self>, path: &str) -> PyResult<()> {
+        let steps = edit_steps(path)?;
+        PyNode::Mapping(slf.clone().unbind()).edit(slf.py(), path, &steps, None)
+    }
+
+    /// The Python data this node constructs to; with `schema`, plain scalars
+    /// are resolved again by its rules first
+    #[pyo3(signature = (*, schema=None))]
+    fn to_python(slf: &Bound<'_, Self>, schema: Option<&str>) -> PyResult<Py<PyAny>> {
+        PyNode::Mapping(slf.clone().unbind()).to_python(slf.py(), schema)
+    }
 }
 
 #[derive(Debug, Clone, FromPyObject)]
@@ -155,6 +286,15 @@ impl PyNode {
         Ok(())
     }
 
+    /// Pointer to the underlying Python object, identifying shared nodes
+    pub fn as_ptr(&self) -> *mut pyo3::ffi::PyObject {
+        match self {
+            PyNode::Scalar(node) => node.as_ptr(),
+            PyNode::Sequence(node) => node.as_ptr(),
+            PyNode::Mapping(node) => node.as_ptr(),
+        }
+    }
+
     pub fn get_start_mark(&self, py: Python) -> PyResult<Option<PyMark>> {
         match self {
             PyNode::Scalar(node) => Ok(node.borrow(py).start_mark.clone()),
@@ -162,6 +302,370 @@ impl PyNode {
             PyNode::Mapping(node) => Ok(node.borrow(py).start_mark.clone()),
         }
     }
+
+    /// Whether `self` and `other` hold the same tags and values; `open` holds
+    /// the pairs being compared, which recursive nodes compare equal again
+    fn equals(&self, py: Python, other: &PyNode, open: &mut HashSet<(usize, usize)>) -> bool {
+        let pair = (self.as_ptr() as usize, other.as_ptr() as usize);
+        if pair.0 == pair.1 || !open.insert(pair) {
+            return true;
+        }
+        let equal = match (self, other) {
+            (PyNode::Scalar(a), PyNode::Scalar(b)) => {
+                let (a, b) = (a.borrow(py), b.borrow(py));
+                a.tag == b.tag && a.value == b.value
+            }
+            (PyNode::Sequence(a), PyNode::Sequence(b)) => {
+                let (a, b) = (a.borrow(py), b.borrow(py));
+                a.tag == b.tag
+                    && a.value.len() == b.value.len()
+                    && a.value
+                        .iter()
+                        .zip(&b.value)
+                        .all(|(a, b)| a.equals(py, b, open))
+            }
+            (PyNode::Mapping(a), PyNode::Mapping(b)) => {
+                let (a, b) = (a.borrow(py), b.borrow(py));
+                a.tag == b.tag
+                    && a.value.len() == b.value.len()
+                    && a.value.iter().zip(&b.value).all(|((ak, av), (bk, bv))| {
+                        ak.equals(py, bk, open) && av.equals(py, bv, open)
+                    })
+            }
+            _ => false,
+        };
+        open.remove(&pair);
+        equal
+    }
+
+    /// `Kind(tag=..., value=...)`, as pyyaml writes nodes; `open` holds the
+    /// collections being written, which are written `...` when met again
+    fn repr(&self, py: Python, open: &mut HashSet<usize>) -> PyResult<String> {
+        let address = self.as_ptr() as usize;
+        if !open.insert(address) {
+            return Ok("...".to_string());
+        }
+        let quote =
+            |text: &str| -> PyResult<String> { Ok(PyString::new(py, text).repr()?.to_string()) };
+        let repr = match self {
+            PyNode::Scalar(node) => {
+                let node = node.borrow(py);
+                format!(
+                    "ScalarNode(tag={}, value={})",
+                    quote(&node.tag)?,
+                    quote(&node.value)?
+                )
+            }
+            PyNode::Sequence(node) => {
+                let node = node.borrow(py);
+                let items = node
+                    .value
+                    .iter()
+                    .map(|item| item.repr(py, open))
+                    .collect::<PyResult<Vec<_>>>()?;
+                format!(
+                    "SequenceNode(tag={}, value=[{}])",
+                    quote(&node.tag)?,
+                    items.join(", ")
+                )
+            }
+            PyNode::Mapping(node) => {
+                let node = node.borrow(py);
+                let pairs = node
+                    .value
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok(format!(
+                            "({}, {})",
+                            key.repr(py, open)?,
+                            value.repr(py, open)?
+                        ))
+                    })
+                    .collect::<PyResult<Vec<_>>>()?;
+                format!(
+                    "MappingNode(tag={}, value=[{}])",
+                    quote(&node.tag)?,
+                    pairs.join(", ")
+                )
+            }
+        };
+        open.remove(&address);
+        Ok(repr)
+    }
+
+    /// The child reached by `step`, if there is one
+    fn child(&self, py: Python, step: &Step) -> Option<PyNode> {
+        match (self, step) {
+            (PyNode::Mapping(node), Step::Key(key)) => {
+                let node = node.borrow(py);
+                entry_position(py, &node, key).map(|i| node.value[i].1.clone())
+            }
+            (PyNode::Sequence(node), Step::Index(index)) => {
+                node.borrow(py).value.get(*index).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    fn find(&self, py: Python, steps: &[Step]) -> Option<PyNode> {
+        let mut node = self.clone();
+        for step in steps {
+            node = node.child(py, step)?;
+        }
+        Some(node)
+    }
+
+    /// Set the node at `steps` to `value`, or remove it for None
+    fn edit(&self, py: Python, path: &str, steps: &[Step], value: Option<PyNode>) -> PyResult<()> {
+        let missing = || PyKeyError::new_err(path.to_string());
+        let (last, parents) = steps
+            .split_last()
+            .ok_or_else(|| PyValueError::new_err("an empty path has no parent to edit"))?;
+        match (self.find(py, parents).ok_or_else(missing)?, last) {
+            (PyNode::Mapping(node), Step::Key(key)) => {
+                let mut node = node.borrow_mut(py);
+                match (entry_position(py, &node, key), value) {
+                    (Some(i), Some(value)) => node.value[i].1 = value,
+                    (Some(i), None) => {
+                        node.value.remove(i);
+                    }
+                    (None, Some(value)) => {
+                        let key = PyScalarNode::new(
+                            crate::TAG_STR.to_string(),
+                            key.clone(),
+                            None,
+                            None,
+                            None,
+                        );
+                        node.value.push((PyNode::Scalar(Py::new(py, key)?), value));
+                    }
+                    (None, None) => return Err(missing()),
+                }
+            }
+            (PyNode::Sequence(node), Step::Index(index)) => {
+                let mut node = node.borrow_mut(py);
+                if *index >= node.value.len() {
+                    return Err(missing());
+                }
+                match value {
+                    Some(value) => node.value[*index] = value,
+                    None => {
+                        node.value.remove(*index);
+                    }
+                }
+            }
+            _ => return Err(missing()),
+        }
+        Ok(())
+    }
+
+    fn to_python(&self, py: Python, schema: Option<&str>) -> PyResult<Py<PyAny>> {
+        let node = match schema {
+            Some(schema) => {
+                self.resolved_by(py, Schema::from_name(schema)?, &mut HashMap::new())?
+            }
+            None => self.clone(),
+        };
+        crate::constructor::construct_node(py, &node.into_pyobject(py)?)
+    }
+
+    /// A copy of the tree with its plain scalars resolved by `schema` rather
+    /// than the YAML 1.1 rules, keeping shared and recursive nodes shared
+    fn resolved_by(
+        &self,
+        py: Python,
+        schema: Schema,
+        copied: &mut HashMap<usize, PyNode>,
+    ) -> PyResult<PyNode> {
+        let key = self.as_ptr() as usize;
+        if let Some(copy) = copied.get(&key) {
+            return Ok(copy.clone());
+        }
+        let copy = match self {
+            PyNode::Scalar(node) => {
+                let node = node.borrow(py);
+                let mut copy = PyScalarNode::clone(&node);
+                // Tags given in the source, or differing from what the plain
+                // text resolves to, are kept
+                if node.style.is_none()
+                    && resolver::resolve_scalar_tag(&node.value, true, Schema::Yaml11)
+                        == Some(node.tag.as_str())
+                {
+                    copy.tag = resolver::resolve_scalar_tag(&node.value, true, schema)
+                        .unwrap_or(crate::TAG_STR)
+                        .to_string();
+                }
+                PyNode::Scalar(Py::new(py, copy)?)
+            }
+            PyNode::Sequence(node) => {
+                let node = node.borrow(py);
+                let copy = Py::new(
+                    py,
+                    PySequenceNode::new(
+                        node.tag.clone(),
+                        Vec::new(),
+                        node.start_mark.clone(),
+                        node.end_mark.clone(),
+                        node.flow_style,
+                    ),
+                )?;
+                // Record the copy before its items, which may refer back to it
+                copied.insert(key, PyNode::Sequence(copy.clone_ref(py)));
+                let items = node
+                    .value
+                    .iter()
+                    .map(|item| item.resolved_by(py, schema, copied))
+                    .collect::<PyResult<_>>()?;
+                copy.borrow_mut(py).value = items;
+                PyNode::Sequence(copy)
+            }
+            PyNode::Mapping(node) => {
+                let node = node.borrow(py);
+                let copy = Py::new(
+                    py,
+                    PyMappingNode::new(
+                        node.tag.clone(),
+                        Vec::new(),
+                        node.start_mark.clone(),
+                        node.end_mark.clone(),
+                        node.flow_style,
+                    ),
+                )?;
+                copied.insert(key, PyNode::Mapping(copy.clone_ref(py)));
+                let pairs = node
+                    .value
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((
+                            key.resolved_by(py, schema, copied)?,
+                            value.resolved_by(py, schema, copied)?,
+                        ))
+                    })
+                    .collect::<PyResult<_>>()?;
+                copy.borrow_mut(py).value = pairs;
+                PyNode::Mapping(copy)
+            }
+        };
+        copied.insert(key, copy.clone());
+        Ok(copy)
+    }
+}
+
+impl PyNode {
+    /// Take a native node as it is, or copy a node tree from pyyaml's
+    /// `yaml.nodes` (or anything with the same attributes) into native nodes,
+    /// keeping shared and recursive nodes shared
+    pub fn from_any(node: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::copy_from(node, &mut HashMap::new())
+    }
+
+    fn copy_from(node: &Bound<'_, PyAny>, copied: &mut HashMap<usize, PyNode>) -> PyResult<Self> {
+        if let Ok(node) = node.extract::<PyNode>() {
+            return Ok(node);
+        }
+        let key = node.as_ptr() as usize;
+        if let Some(copy) = copied.get(&key) {
+            return Ok(copy.clone());
+        }
+        let py = node.py();
+        let id = node
+            .getattr("id")
+            .and_then(|id| id.extract::<String>())
+            .map_err(|_| not_a_node(node))?;
+        let tag: String = node.getattr("tag")?.extract()?;
+        let start_mark = PyMark::from_any(&node.getattr("start_mark")?)?;
+        let end_mark = PyMark::from_any(&node.getattr("end_mark")?)?;
+        match id.as_str() {
+            "scalar" => {
+                let style: Option<String> = node.getattr("style")?.extract()?;
+                let copy = PyNode::Scalar(Py::new(
+                    py,
+                    PyScalarNode::new(
+                        tag,
+                        node.getattr("value")?.extract()?,
+                        start_mark,
+                        end_mark,
+                        style.and_then(|style| style.chars().next()),
+                    ),
+                )?);
+                copied.insert(key, copy.clone());
+                Ok(copy)
+            }
+            "sequence" => {
+                let flow_style = node.getattr("flow_style")?.extract()?;
+                let copy = Py::new(
+                    py,
+                    PySequenceNode::new(tag, Vec::new(), start_mark, end_mark, flow_style),
+                )?;
+                // Record the copy before its items, which may refer back to it
+                copied.insert(key, PyNode::Sequence(copy.clone_ref(py)));
+                let mut items = Vec::new();
+                for item in node.getattr("value")?.try_iter()? {
+                    items.push(Self::copy_from(&item?, copied)?);
+                }
+                copy.borrow_mut(py).value = items;
+                Ok(PyNode::Sequence(copy))
+            }
+            "mapping" => {
+                let flow_style = node.getattr("flow_style")?.extract()?;
+                let copy = Py::new(
+                    py,
+                    PyMappingNode::new(tag, Vec::new(), start_mark, end_mark, flow_style),
+                )?;
+                copied.insert(key, PyNode::Mapping(copy.clone_ref(py)));
+                let mut pairs = Vec::new();
+                for pair in node.getattr("value")?.try_iter()? {
+                    let (key_node, value_node): (Bound<'_, PyAny>, Bound<'_, PyAny>) =
+                        pair?.extract()?;
+                    pairs.push((
+                        Self::copy_from(&key_node, copied)?,
+                        Self::copy_from(&value_node, copied)?,
+                    ));
+                }
+                copy.borrow_mut(py).value = pairs;
+                Ok(PyNode::Mapping(copy))
+            }
+            _ => Err(not_a_node(node)),
+        }
+    }
+}
+
+/// The steps of a path to edit or find a single node at
+fn edit_steps(path: &str) -> PyResult<Vec<Step>> {
+    let steps = parse_path(path)?;
+    if steps
+        .iter()
+        .any(|step| matches!(step, Step::AnyValue | Step::AnyItem))
+    {
+        return Err(PyValueError::new_err(format!(
+            "invalid path '{path}': '*' doesn't name a single node"
+        )));
+    }
+    Ok(steps)
+}
+
+/// Position of the entry of `mapping` whose key is the scalar `key`
+fn entry_position(py: Python, mapping: &PyMappingNode, key: &str) -> Option<usize> {
+    mapping.value.iter().position(|(k, _)| match k {
+        PyNode::Scalar(k) => k.borrow(py).value == key,
+        _ => false,
+    })
+}
+
+/// `value` if it is a node, else `value` represented as `dumps()` would
+fn node_of(value: &Bound<'_, PyAny>) -> PyResult<PyNode> {
+    match value.extract::<PyNode>() {
+        Ok(node) => Ok(node),
+        Err(_) => crate::dumper::represent_to_node(value.py(), value),
+    }
+}
+
+fn not_a_node(obj: &Bound<'_, PyAny>) -> PyErr {
+    let type_name = obj
+        .get_type()
+        .name()
+        .map_or_else(|_| "object".to_string(), |name| name.to_string());
+    PyTypeError::new_err(format!("expected a YAML node, but found {type_name}"))
 }
 
 impl<'py> IntoPyObject<'py> for PyNode {
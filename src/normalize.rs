@@ -0,0 +1,92 @@
+//! `normalize()`: a canonical form of a YAML stream, for hashing and
+//! comparing documents. Aliases are expanded, mapping keys sorted, and every
+//! node is written in block style with the quoting the emitter picks, so
+//! texts holding the same data come out the same.
+
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+
+use crate::dumper::serialize_to_string;
+use crate::encoding::SourceText;
+use crate::exception;
+use crate::loader::{LoaderOptions, RSafeLoader};
+use crate::nodes::{PyMappingNode, PyNode, PyScalarNode, PySequenceNode};
+
+/// Where a key sorts among the keys of its mapping: scalars by value, then
+/// tag, ahead of collections, which sort by their normalized text
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Scalar(String, String),
+    Collection(String),
+}
+
+impl SortKey {
+    fn of(py: Python, key: &PyNode) -> PyResult<Self> {
+        Ok(match key {
+            PyNode::Scalar(scalar) => {
+                let scalar = scalar.borrow(py);
+                Self::Scalar(scalar.value.clone(), scalar.tag.clone())
+            }
+            _ => Self::Collection(serialize_to_string(py, std::slice::from_ref(key))?),
+        })
+    }
+}
+
+/// A normalized copy of `node`, sharing none of its nodes; `open` holds the
+/// collections being copied, to refuse recursive aliases
+fn normalize_node(py: Python, node: &PyNode, open: &mut HashSet<usize>) -> PyResult<PyNode> {
+    let address = node.as_ptr() as usize;
+    if !open.insert(address) {
+        return Err(exception::serializer_error(
+            py,
+            "cannot normalize a recursive node graph".to_string(),
+        ));
+    }
+    let normalized = match node {
+        PyNode::Scalar(scalar) => {
+            let scalar = scalar.borrow(py);
+            let copy =
+                PyScalarNode::new(scalar.tag.clone(), scalar.value.clone(), None, None, None);
+            PyNode::Scalar(Py::new(py, copy)?)
+        }
+        PyNode::Sequence(sequence) => {
+            let sequence = sequence.borrow(py);
+            let items = sequence
+                .value
+                .iter()
+                .map(|item| normalize_node(py, item, open))
+                .collect::<PyResult<_>>()?;
+            let copy = PySequenceNode::new(sequence.tag.clone(), items, None, None, Some(false));
+            PyNode::Sequence(Py::new(py, copy)?)
+        }
+        PyNode::Mapping(mapping) => {
+            let mapping = mapping.borrow(py);
+            let mut entries = Vec::with_capacity(mapping.value.len());
+            for (key, value) in &mapping.value {
+                let key = normalize_node(py, key, open)?;
+                let value = normalize_node(py, value, open)?;
+                entries.push((SortKey::of(py, &key)?, key, value));
+            }
+            entries.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+            let entries = entries
+                .into_iter()
+                .map(|(_, key, value)| (key, value))
+                .collect();
+            let copy = PyMappingNode::new(mapping.tag.clone(), entries, None, None, Some(false));
+            PyNode::Mapping(Py::new(py, copy)?)
+        }
+    };
+    open.remove(&address);
+    Ok(normalized)
+}
+
+/// Every document of `source` in canonical form
+pub fn normalize(py: Python, source: SourceText, options: LoaderOptions) -> PyResult<String> {
+    let mut loader = RSafeLoader::new(py, source, options)?;
+    let mut documents = Vec::new();
+    while let Some(node) = loader.get_node(py)? {
+        documents.push(normalize_node(py, &node, &mut HashSet::new())?);
+    }
+    serialize_to_string(py, &documents)
+}
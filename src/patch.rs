@@ -0,0 +1,502 @@
+//! `patch()`: JSON Patch operations (RFC 6902), or a JSON Merge Patch
+//! (RFC 7386), applied to the node tree of a document, which is then
+//! serialized again. Nodes the patch doesn't reach are reused as they are,
+//! keeping their styles, and the comments around block mapping entries whose
+//! keys survive are written back as `format()` writes them.
+
+use std::collections::{HashMap, HashSet};
+
+use libyaml_safer::Mark;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+
+use crate::comments::{EntryComments, SourceLines, insert_comments};
+use crate::constructor::construct_node;
+use crate::dumper::{represent_to_node, serialize_to_string};
+use crate::encoding::SourceText;
+use crate::exception;
+use crate::loader::{LoaderOptions, RSafeLoader};
+use crate::mark::PyMark;
+use crate::merge::scalar_key;
+use crate::nodes::{PyMappingNode, PyNode, PySequenceNode};
+
+/// What an operation does at the end of its path
+enum Leaf {
+    /// Insert into a sequence, or set a mapping entry
+    Add(PyNode),
+    Replace(PyNode),
+    Remove,
+}
+
+/// A JSON Pointer, split into its unescaped reference tokens
+struct Pointer {
+    text: String,
+    tokens: Vec<String>,
+}
+
+impl Pointer {
+    fn parse(text: String) -> PyResult<Self> {
+        let tokens = match text.strip_prefix('/') {
+            Some(rest) => rest
+                .split('/')
+                .map(|token| token.replace("~1", "/").replace("~0", "~"))
+                .collect(),
+            None if text.is_empty() => Vec::new(),
+            None => {
+                return Err(PyValueError::new_err(format!(
+                    "patch path {text:?} does not start with '/'"
+                )));
+            }
+        };
+        Ok(Self { text, tokens })
+    }
+
+    fn missing(&self) -> PyErr {
+        PyValueError::new_err(format!("patch path {:?} does not exist", self.text))
+    }
+}
+
+/// Position in `sequence` of `token`, which may be one past the end, as "-"
+/// always is
+fn sequence_index(pointer: &Pointer, sequence: &PySequenceNode, token: &str) -> PyResult<usize> {
+    if token == "-" {
+        return Ok(sequence.value.len());
+    }
+    // Leading zeros and signs aren't indices in a JSON Pointer
+    let index = match token.parse::<usize>() {
+        Ok(index) if token == index.to_string() => index,
+        _ => return Err(pointer.missing()),
+    };
+    if index > sequence.value.len() {
+        return Err(pointer.missing());
+    }
+    Ok(index)
+}
+
+/// Position of the entry of `mapping` whose scalar key is written `token`
+fn entry_index(py: Python, mapping: &PyMappingNode, token: &str) -> Option<usize> {
+    mapping
+        .value
+        .iter()
+        .position(|(key, _)| scalar_key(py, key).is_some_and(|(_, value)| value == token))
+}
+
+/// The node `pointer` refers to
+fn get(py: Python, root: Option<&PyNode>, pointer: &Pointer) -> PyResult<PyNode> {
+    let mut node = root.ok_or_else(|| pointer.missing())?.clone();
+    for token in &pointer.tokens {
+        let child = match &node {
+            PyNode::Mapping(mapping) => {
+                let mapping = mapping.borrow(py);
+                entry_index(py, &mapping, token).map(|i| mapping.value[i].1.clone())
+            }
+            PyNode::Sequence(sequence) => {
+                let sequence = sequence.borrow(py);
+                let index = sequence_index(pointer, &sequence, token)?;
+                sequence.value.get(index).cloned()
+            }
+            PyNode::Scalar(_) => None,
+        };
+        node = child.ok_or_else(|| pointer.missing())?;
+    }
+    Ok(node)
+}
+
+/// `node` with `leaf` done at `tokens` below it, the collections on the way
+/// made new and everything else shared
+fn update(
+    py: Python,
+    node: Option<&PyNode>,
+    pointer: &Pointer,
+    tokens: &[String],
+    leaf: Leaf,
+) -> PyResult<Option<PyNode>> {
+    let Some((token, rest)) = tokens.split_first() else {
+        return match leaf {
+            Leaf::Add(value) => Ok(Some(value)),
+            Leaf::Replace(value) if node.is_some() => Ok(Some(value)),
+            Leaf::Remove if node.is_some() => Ok(None),
+            _ => Err(pointer.missing()),
+        };
+    };
+    match node {
+        Some(PyNode::Mapping(mapping)) => {
+            let mapping = mapping.borrow(py);
+            let mut entries = mapping.value.clone();
+            match (entry_index(py, &mapping, token), rest.is_empty(), leaf) {
+                (Some(i), false, leaf) => {
+                    entries[i].1 = update(py, Some(&entries[i].1), pointer, rest, leaf)?
+                        .ok_or_else(|| pointer.missing())?;
+                }
+                (Some(i), true, Leaf::Add(value) | Leaf::Replace(value)) => entries[i].1 = value,
+                (Some(i), true, Leaf::Remove) => {
+                    entries.remove(i);
+                }
+                (None, true, Leaf::Add(value)) => {
+                    let key = represent_to_node(py, PyString::new(py, token).as_any())?;
+                    entries.push((key, value));
+                }
+                (None, _, _) => return Err(pointer.missing()),
+            }
+            let updated = PyMappingNode::new(
+                mapping.tag.clone(),
+                entries,
+                mapping.start_mark.clone(),
+                mapping.end_mark.clone(),
+                mapping.flow_style,
+            );
+            Ok(Some(PyNode::Mapping(Py::new(py, updated)?)))
+        }
+        Some(PyNode::Sequence(sequence)) => {
+            let sequence = sequence.borrow(py);
+            let index = sequence_index(pointer, &sequence, token)?;
+            let mut items = sequence.value.clone();
+            let len = items.len();
+            match (rest.is_empty(), leaf) {
+                (false, leaf) if index < len => {
+                    items[index] = update(py, Some(&items[index]), pointer, rest, leaf)?
+                        .ok_or_else(|| pointer.missing())?;
+                }
+                (true, Leaf::Add(value)) => items.insert(index, value),
+                (true, Leaf::Replace(value)) if index < len => items[index] = value,
+                (true, Leaf::Remove) if index < len => {
+                    items.remove(index);
+                }
+                _ => return Err(pointer.missing()),
+            }
+            let updated = PySequenceNode::new(
+                sequence.tag.clone(),
+                items,
+                sequence.start_mark.clone(),
+                sequence.end_mark.clone(),
+                sequence.flow_style,
+            );
+            Ok(Some(PyNode::Sequence(Py::new(py, updated)?)))
+        }
+        _ => Err(pointer.missing()),
+    }
+}
+
+/// A copy of `node` sharing none of its collections, so a copied value
+/// isn't written as an alias of the original
+fn deep_copy(py: Python, node: &PyNode) -> PyResult<PyNode> {
+    Ok(match node {
+        PyNode::Scalar(scalar) => {
+            let scalar = scalar.borrow(py);
+            PyNode::Scalar(Py::new(py, scalar.clone())?)
+        }
+        PyNode::Sequence(sequence) => {
+            let sequence = sequence.borrow(py);
+            let items = sequence
+                .value
+                .iter()
+                .map(|item| deep_copy(py, item))
+                .collect::<PyResult<_>>()?;
+            let copy = PySequenceNode::new(
+                sequence.tag.clone(),
+                items,
+                sequence.start_mark.clone(),
+                sequence.end_mark.clone(),
+                sequence.flow_style,
+            );
+            PyNode::Sequence(Py::new(py, copy)?)
+        }
+        PyNode::Mapping(mapping) => {
+            let mapping = mapping.borrow(py);
+            let entries = mapping
+                .value
+                .iter()
+                .map(|(key, value)| Ok((deep_copy(py, key)?, deep_copy(py, value)?)))
+                .collect::<PyResult<_>>()?;
+            let copy = PyMappingNode::new(
+                mapping.tag.clone(),
+                entries,
+                mapping.start_mark.clone(),
+                mapping.end_mark.clone(),
+                mapping.flow_style,
+            );
+            PyNode::Mapping(Py::new(py, copy)?)
+        }
+    })
+}
+
+/// The member `name` of a JSON Patch operation
+fn member<'py>(operation: &Bound<'py, PyAny>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+    operation
+        .get_item(name)
+        .map_err(|_| PyValueError::new_err(format!("patch operation {operation} has no {name:?}")))
+}
+
+/// `root` with the JSON Patch `operations` applied in order
+fn apply_operations(
+    py: Python,
+    mut root: Option<PyNode>,
+    operations: &Bound<'_, PyAny>,
+) -> PyResult<Option<PyNode>> {
+    for operation in operations.try_iter()? {
+        let operation = operation?;
+        let member = |name: &str| member(&operation, name);
+        let op: String = member("op")?.extract()?;
+        let pointer = Pointer::parse(member("path")?.extract()?)?;
+        let value = || represent_to_node(py, &member("value")?);
+        let from = || Pointer::parse(member("from")?.extract()?);
+        let leaf = match op.as_str() {
+            "add" => Leaf::Add(value()?),
+            "replace" => Leaf::Replace(value()?),
+            "remove" => Leaf::Remove,
+            "copy" => Leaf::Add(deep_copy(py, &get(py, root.as_ref(), &from()?)?)?),
+            "move" => {
+                let from = from()?;
+                if pointer.text.starts_with(&format!("{}/", from.text)) {
+                    return Err(PyValueError::new_err(format!(
+                        "cannot move {:?} into itself",
+                        from.text
+                    )));
+                }
+                let moved = get(py, root.as_ref(), &from)?;
+                root = update(py, root.as_ref(), &from, &from.tokens, Leaf::Remove)?;
+                Leaf::Add(moved)
+            }
+            "test" => {
+                let actual =
+                    construct_node(py, &get(py, root.as_ref(), &pointer)?.into_pyobject(py)?)?;
+                if !actual.bind(py).eq(member("value")?)? {
+                    return Err(PyValueError::new_err(format!(
+                        "patch test failed: {:?} is {}",
+                        pointer.text,
+                        actual.bind(py).repr()?
+                    )));
+                }
+                continue;
+            }
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown patch operation {other:?}"
+                )));
+            }
+        };
+        root = update(py, root.as_ref(), &pointer, &pointer.tokens, leaf)?;
+    }
+    Ok(root)
+}
+
+/// `node` with the JSON Merge Patch `patch` applied: its mappings merged
+/// into mappings key by key, None removing keys, anything else replacing
+fn apply_merge_patch(
+    py: Python,
+    node: Option<&PyNode>,
+    patch: &Bound<'_, PyAny>,
+) -> PyResult<Option<PyNode>> {
+    let Ok(patch) = patch.downcast::<PyDict>() else {
+        if patch.is_none() {
+            return Ok(None);
+        }
+        return represent_to_node(py, patch).map(Some);
+    };
+    let base = match node {
+        Some(PyNode::Mapping(mapping)) => Some(mapping.borrow(py)),
+        _ => None,
+    };
+    let mut entries = base
+        .as_ref()
+        .map_or_else(Vec::new, |base| base.value.clone());
+    for (key, value) in patch.iter() {
+        let key = represent_to_node(py, &key)?;
+        let wanted = scalar_key(py, &key);
+        let position = entries
+            .iter()
+            .position(|(existing, _)| wanted.is_some() && scalar_key(py, existing) == wanted);
+        let merged = apply_merge_patch(py, position.map(|i| &entries[i].1), &value)?;
+        match (position, merged) {
+            (Some(i), Some(merged)) => entries[i].1 = merged,
+            (Some(i), None) => {
+                entries.remove(i);
+            }
+            (None, Some(merged)) => entries.push((key, merged)),
+            (None, None) => {}
+        }
+    }
+    let merged = match base {
+        Some(base) => PyMappingNode::new(
+            base.tag.clone(),
+            entries,
+            base.start_mark.clone(),
+            base.end_mark.clone(),
+            base.flow_style,
+        ),
+        None => PyMappingNode::new(crate::TAG_MAP.to_string(), entries, None, None, None),
+    };
+    Ok(Some(PyNode::Mapping(Py::new(py, merged)?)))
+}
+
+fn start_mark(py: Python, node: &PyNode) -> Option<PyMark> {
+    match node {
+        PyNode::Scalar(scalar) => scalar.borrow(py).start_mark.clone(),
+        PyNode::Sequence(sequence) => sequence.borrow(py).start_mark.clone(),
+        PyNode::Mapping(mapping) => mapping.borrow(py).start_mark.clone(),
+    }
+}
+
+fn end_mark(py: Python, node: &PyNode) -> Option<PyMark> {
+    match node {
+        PyNode::Scalar(scalar) => scalar.borrow(py).end_mark.clone(),
+        PyNode::Sequence(sequence) => sequence.borrow(py).end_mark.clone(),
+        PyNode::Mapping(mapping) => mapping.borrow(py).end_mark.clone(),
+    }
+}
+
+fn source_mark(mark: &PyMark) -> Mark {
+    Mark {
+        index: mark.index,
+        line: mark.line,
+        column: mark.column,
+    }
+}
+
+/// Comments around the block mapping entries of a composed document, by
+/// the address of the key node
+struct Comments<'a> {
+    lines: &'a SourceLines,
+    entries: HashMap<usize, EntryComments>,
+    seen: HashSet<usize>,
+}
+
+impl Comments<'_> {
+    /// Gather the comments of the entries below `node`, whose content comes
+    /// after the line `floor`; returns the last line holding content
+    fn gather(&mut self, py: Python, node: &PyNode, floor: Option<u64>) -> Option<u64> {
+        // Aliases in the source give no lines to go by
+        if !self.seen.insert(node.as_ptr() as usize) {
+            return floor;
+        }
+        match node {
+            PyNode::Scalar(scalar) => {
+                let scalar = scalar.borrow(py);
+                let (Some(start), Some(end)) = (&scalar.start_mark, &scalar.end_mark) else {
+                    return floor;
+                };
+                Some(match scalar.style {
+                    // Block scalars end at the start of the line after their content
+                    Some('|' | '>') => end.line.saturating_sub(1).max(start.line),
+                    _ => end.line,
+                })
+            }
+            PyNode::Sequence(sequence) => {
+                let mut floor = floor;
+                for item in &sequence.borrow(py).value {
+                    floor = self.gather(py, item, floor);
+                }
+                floor
+            }
+            PyNode::Mapping(mapping) => {
+                let mapping = mapping.borrow(py);
+                let block = mapping.flow_style != Some(true);
+                let mut floor = floor;
+                for (key, value) in &mapping.value {
+                    let key_floor = floor;
+                    floor = self.gather(py, key, floor);
+                    // Where the comment ending the entry's first line follows
+                    let comment_mark = match value {
+                        PyNode::Scalar(scalar) => match scalar.borrow(py).style {
+                            Some('|' | '>') => start_mark(py, value),
+                            _ => end_mark(py, value),
+                        },
+                        PyNode::Sequence(sequence)
+                            if sequence.borrow(py).flow_style == Some(true) =>
+                        {
+                            end_mark(py, value)
+                        }
+                        PyNode::Mapping(mapping) if mapping.borrow(py).flow_style == Some(true) => {
+                            end_mark(py, value)
+                        }
+                        _ => end_mark(py, key),
+                    };
+                    floor = self.gather(py, value, floor);
+                    if let (true, Some(key_start)) = (block, start_mark(py, key)) {
+                        let entry = EntryComments {
+                            before: self.lines.comments_above(key_start.line, key_floor),
+                            inline: comment_mark
+                                .and_then(|mark| self.lines.comment_after(source_mark(&mark))),
+                        };
+                        if !entry.before.is_empty() || entry.inline.is_some() {
+                            self.entries.insert(key.as_ptr() as usize, entry);
+                        }
+                    }
+                }
+                floor
+            }
+        }
+    }
+
+    /// Number the mapping keys below `node` in the order the serializer
+    /// writes them, taking the comments of the keys gathered from the source
+    fn place(
+        &mut self,
+        py: Python,
+        node: &PyNode,
+        written: &mut HashSet<usize>,
+        keys: &mut usize,
+        placed: &mut Vec<(usize, EntryComments)>,
+    ) {
+        match node {
+            PyNode::Scalar(_) => {}
+            // Collections written before are written as aliases
+            _ if !written.insert(node.as_ptr() as usize) => {}
+            PyNode::Sequence(sequence) => {
+                for item in &sequence.borrow(py).value {
+                    self.place(py, item, written, keys, placed);
+                }
+            }
+            PyNode::Mapping(mapping) => {
+                for (key, value) in &mapping.borrow(py).value {
+                    if let Some(entry) = self.entries.remove(&(key.as_ptr() as usize)) {
+                        placed.push((*keys, entry));
+                    }
+                    *keys += 1;
+                    self.place(py, key, written, keys, placed);
+                    self.place(py, value, written, keys, placed);
+                }
+            }
+        }
+    }
+}
+
+/// `source` with `patch` applied and serialized again: a list of JSON Patch
+/// operations, or a dict applied as a JSON Merge Patch. With `comments`,
+/// comments around the block mapping entries kept are written back.
+pub fn patch(
+    py: Python,
+    source: SourceText,
+    patch: &Bound<'_, PyAny>,
+    options: LoaderOptions,
+    comments: bool,
+) -> PyResult<String> {
+    let root = RSafeLoader::new(py, source.clone(), options)?.get_single_node(py)?;
+    let lines = SourceLines::new(source);
+    let mut gathered = Comments {
+        lines: &lines,
+        entries: HashMap::new(),
+        seen: HashSet::new(),
+    };
+    if let (true, Some(root)) = (comments, &root) {
+        gathered.gather(py, root, None);
+    }
+
+    let patched = if patch.is_instance_of::<PyDict>() {
+        apply_merge_patch(py, root.as_ref(), patch)?
+    } else {
+        apply_operations(py, root, patch)?
+    };
+    let Some(patched) = patched else {
+        return serialize_to_string(py, &[]);
+    };
+
+    let output = serialize_to_string(py, std::slice::from_ref(&patched))?;
+    let mut placed = Vec::new();
+    gathered.place(py, &patched, &mut HashSet::new(), &mut 0, &mut placed);
+    if placed.is_empty() {
+        return Ok(output);
+    }
+    insert_comments(&output, "\n", &placed).map_err(|e| exception::emitter_error(py, e))
+}
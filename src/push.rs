@@ -0,0 +1,193 @@
+//! `PushParser`: events parsed from YAML fed a chunk at a time, as it arrives
+//! over a socket or a pipe, without holding on to the whole stream.
+//!
+//! libyaml pulls its input, so the parser runs on a thread of its own, reading
+//! the chunks sent to it. Once it has used up every chunk fed so far it says
+//! so and waits for the next, and `feed()` returns the events parsed until
+//! then. Those events needed no more input, so later chunks can't change them.
+
+use std::io::{BufRead, Read};
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+use libyaml_safer::{Event, EventData, Parser};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::backend::{Backend, ParseError, Saphyr};
+use crate::mark::PyMark;
+
+/// What the parser thread sends back
+enum Parsed {
+    Event(Result<Event, ParseError>),
+    /// Every chunk fed so far is used up
+    Starved,
+}
+
+/// Input of the parser thread: the chunks given to `feed()`, then None from
+/// `finish()` to end the stream
+pub struct ChunkReader {
+    chunks: Receiver<Option<Vec<u8>>>,
+    parsed: Sender<Parsed>,
+    chunk: Vec<u8>,
+    /// Bytes of `chunk` the parser has consumed
+    offset: usize,
+    ended: bool,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for ChunkReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.offset == self.chunk.len() && !self.ended {
+            // Before the first chunk, nothing has been fed to wait on
+            if !self.chunk.is_empty() {
+                let _ = self.parsed.send(Parsed::Starved);
+            }
+            match self.chunks.recv() {
+                Ok(Some(chunk)) => self.chunk = chunk,
+                Ok(None) => {
+                    self.chunk.clear();
+                    self.ended = true;
+                }
+                Err(_) => return Err(std::io::Error::other("the push parser was dropped")),
+            }
+            self.offset = 0;
+        }
+        Ok(&self.chunk[self.offset..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.offset = (self.offset + amount).min(self.chunk.len());
+    }
+}
+
+/// Parses YAML fed to it a chunk at a time, returning the events each chunk
+/// completes
+#[pyclass(name = "PushParser")]
+pub struct PushParser {
+    /// None once the stream has ended, or failed to parse
+    chunks: Option<Sender<Option<Vec<u8>>>>,
+    parsed: Mutex<Receiver<Parsed>>,
+}
+
+#[pymethods]
+impl PushParser {
+    #[new]
+    #[pyo3(signature = (*, backend="libyaml"))]
+    fn new(backend: &str) -> PyResult<Self> {
+        let backend = Backend::from_name(backend)?;
+        let (chunks, chunk_receiver) = channel();
+        let (parsed_sender, parsed) = channel();
+        let reader = ChunkReader {
+            chunks: chunk_receiver,
+            parsed: parsed_sender.clone(),
+            chunk: Vec::new(),
+            offset: 0,
+            ended: false,
+        };
+        std::thread::spawn(move || match backend {
+            Backend::Libyaml => {
+                let mut parser = Parser::new();
+                parser.set_input(reader);
+                send_events(
+                    || parser.parse().map_err(ParseError::Libyaml),
+                    &parsed_sender,
+                );
+            }
+            Backend::Saphyr => {
+                let mut parser = Saphyr::new(reader, None, 0);
+                send_events(|| parser.parse(), &parsed_sender);
+            }
+        });
+        Ok(Self {
+            chunks: Some(chunks),
+            parsed: Mutex::new(parsed),
+        })
+    }
+
+    /// Parse `chunk`, the next bytes of the stream, returning the events it
+    /// completes
+    fn feed(&mut self, py: Python, chunk: Vec<u8>) -> PyResult<Vec<Py<PyAny>>> {
+        if chunk.is_empty() {
+            // An empty chunk would end the stream
+            self.sender()?;
+            return Ok(Vec::new());
+        }
+        self.send(py, Some(chunk))
+    }
+
+    /// End the stream, returning the rest of its events
+    fn finish(&mut self, py: Python) -> PyResult<Vec<Py<PyAny>>> {
+        self.send(py, None)
+    }
+}
+
+impl PushParser {
+    fn sender(&self) -> PyResult<&Sender<Option<Vec<u8>>>> {
+        self.chunks
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("the stream has already ended"))
+    }
+
+    /// Send `chunk` to the parser thread and wait for the events parsed until
+    /// it is used up, or the stream ends
+    fn send(&mut self, py: Python, chunk: Option<Vec<u8>>) -> PyResult<Vec<Py<PyAny>>> {
+        // The thread is gone once it has sent its last event
+        let _ = self.sender()?.send(chunk);
+        let parsed = &self.parsed;
+        let (events, ended) = py.detach(|| {
+            let parsed = parsed.lock().unwrap();
+            let mut events = Vec::new();
+            loop {
+                match parsed.recv() {
+                    Ok(Parsed::Event(event)) => {
+                        let done =
+                            !matches!(&event, Ok(event) if event.data != EventData::StreamEnd);
+                        events.push(event);
+                        if done {
+                            return (events, true);
+                        }
+                    }
+                    Ok(Parsed::Starved) => return (events, false),
+                    Err(_) => return (events, true),
+                }
+            }
+        });
+        if ended {
+            self.chunks = None;
+        }
+        events
+            .into_iter()
+            .map(|event| match event {
+                Ok(event) => crate::event::event_to_py(py, event, PyMark::from),
+                Err(e) => Err(crate::exception::parse_error(py, &e, PyMark::from)),
+            })
+            .collect()
+    }
+}
+
+pub fn register_push(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PushParser>()?;
+    Ok(())
+}
+
+/// Send the events `parse` returns to `parsed`, up to the end of the stream
+/// or the first error
+fn send_events(mut parse: impl FnMut() -> Result<Event, ParseError>, parsed: &Sender<Parsed>) {
+    loop {
+        let event = parse();
+        let done = !matches!(&event, Ok(event) if event.data != EventData::StreamEnd);
+        if parsed.send(Parsed::Event(event)).is_err() || done {
+            return;
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Construction of the `!!python/...` tags understood by pyyaml's FullLoader
+//! and UnsafeLoader.
+//!
+//! These tags are only honored by loaders created for them; `loads()` and
+//! `_RSafeLoader` never construct them.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString, PyTuple};
+
+use crate::exception;
+
+/// Prefix of the tags naming Python types and objects
+pub const TAG_PREFIX: &str = "tag:yaml.org,2002:python/";
+
+/// Which `!!python/...` tags a loader constructs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PythonTags {
+    /// None; they load like any other unknown tag
+    #[default]
+    Ignore,
+    /// Builtin types, and names and modules which are already imported
+    Full,
+    /// Everything, including importing modules and instantiating any class
+    Unsafe,
+}
+
+/// The kind of node a `!!python/...` tag applies to, and the tag its content
+/// is constructed with before `finish()` converts it
+#[derive(Debug, Clone, Copy)]
+pub enum Content {
+    Scalar(&'static str),
+    Sequence,
+    Mapping,
+    /// Sequence of arguments, or mapping of arguments and state
+    Collection,
+}
+
+impl Content {
+    /// Whether a node of `kind` ("scalar", "sequence" or "mapping") has this content
+    pub fn accepts(self, kind: &str) -> bool {
+        match self {
+            Content::Scalar(_) => kind == "scalar",
+            Content::Sequence => kind == "sequence",
+            Content::Mapping => kind == "mapping",
+            Content::Collection => kind != "scalar",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Content::Scalar(_) => "scalar",
+            Content::Sequence => "sequence",
+            Content::Mapping => "mapping",
+            Content::Collection => "sequence or mapping",
+        }
+    }
+}
+
+/// How to construct the content of a node tagged `!!python/<suffix>`, or None
+/// if the tag isn't supported in `mode`
+pub fn content(suffix: &str, mode: PythonTags) -> Option<Content> {
+    if mode == PythonTags::Unsafe {
+        if suffix.starts_with("object:") {
+            return Some(Content::Mapping);
+        }
+        if suffix.starts_with("object/new:") || suffix.starts_with("object/apply:") {
+            return Some(Content::Collection);
+        }
+    }
+    Some(match suffix {
+        "none" => Content::Scalar(crate::TAG_NULL),
+        "bool" => Content::Scalar(crate::TAG_BOOL),
+        "str" | "unicode" | "complex" => Content::Scalar(crate::TAG_STR),
+        "bytes" => Content::Scalar(crate::TAG_BINARY),
+        "int" | "long" => Content::Scalar(crate::TAG_INT),
+        "float" => Content::Scalar(crate::TAG_FLOAT),
+        "list" | "tuple" => Content::Sequence,
+        "dict" => Content::Mapping,
+        _ if suffix.starts_with("name:") || suffix.starts_with("module:") => {
+            Content::Scalar(crate::TAG_STR)
+        }
+        _ => return None,
+    })
+}
+
+/// Convert the constructed content of a node tagged `!!python/<suffix>`
+pub fn finish<'py>(
+    suffix: &str,
+    value: Bound<'py, PyAny>,
+    mode: PythonTags,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = value.py();
+    let name = suffix.split_once(':').map(|(_, name)| name);
+    if name == Some("") {
+        return Err(exception::constructor_error(
+            py,
+            "expected non-empty name appended to the tag".to_string(),
+        ));
+    }
+    if let Some(name) = suffix.strip_prefix("name:") {
+        expect_empty(&value)?;
+        return find_name(py, name, mode);
+    }
+    if let Some(name) = suffix.strip_prefix("module:") {
+        expect_empty(&value)?;
+        return find_module(py, name, mode);
+    }
+    if let Some(name) = suffix.strip_prefix("object:") {
+        let class = find_name(py, name, mode)?;
+        let instance = class.call_method1("__new__", (&class,))?;
+        set_state(&instance, value)?;
+        return Ok(instance);
+    }
+    if let Some(name) = suffix.strip_prefix("object/new:") {
+        let class = find_name(py, name, mode)?;
+        return make_instance(class, value, true);
+    }
+    if let Some(name) = suffix.strip_prefix("object/apply:") {
+        let callable = find_name(py, name, mode)?;
+        return make_instance(callable, value, false);
+    }
+    match suffix {
+        "complex" => py.import("builtins")?.getattr("complex")?.call1((value,)),
+        "tuple" => Ok(PyTuple::new(py, value.downcast::<PyList>()?)?.into_any()),
+        _ => Ok(value),
+    }
+}
+
+/// The module `name`, imported if `mode` allows it
+fn find_module<'py>(py: Python<'py>, name: &str, mode: PythonTags) -> PyResult<Bound<'py, PyAny>> {
+    if mode == PythonTags::Unsafe {
+        return py.import(name).map(Bound::into_any).map_err(|e| {
+            exception::constructor_error(py, format!("cannot find module '{name}' ({e})"))
+        });
+    }
+    match py.import("sys")?.getattr("modules")?.get_item(name) {
+        Ok(module) => Ok(module),
+        Err(_) => Err(exception::constructor_error(
+            py,
+            format!("module '{name}' is not imported"),
+        )),
+    }
+}
+
+/// The object `name` (`module.attribute`, or a builtin) of a module
+fn find_name<'py>(py: Python<'py>, name: &str, mode: PythonTags) -> PyResult<Bound<'py, PyAny>> {
+    let (module_name, object_name) = name.rsplit_once('.').unwrap_or(("builtins", name));
+    let module = find_module(py, module_name, mode)?;
+    module.getattr(object_name).map_err(|_| {
+        exception::constructor_error(
+            py,
+            format!("cannot find '{object_name}' in the module '{module_name}'"),
+        )
+    })
+}
+
+fn expect_empty(value: &Bound<'_, PyAny>) -> PyResult<()> {
+    let text: String = value.extract()?;
+    if text.is_empty() {
+        return Ok(());
+    }
+    Err(exception::constructor_error(
+        value.py(),
+        format!("expected the empty value, but found '{text}'"),
+    ))
+}
+
+/// Call `callable` (or its `__new__`, if `new`) with the arguments in `value`,
+/// either a list of positional arguments or a mapping with `args`, `kwds`,
+/// `state`, `listitems` and `dictitems`, like pyyaml's `make_python_instance`
+fn make_instance<'py>(
+    callable: Bound<'py, PyAny>,
+    value: Bound<'py, PyAny>,
+    new: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = value.py();
+    let (args, kwds, state, listitems, dictitems) = match value.downcast::<PyDict>() {
+        Ok(mapping) => (
+            mapping.get_item("args")?,
+            mapping.get_item("kwds")?,
+            mapping.get_item("state")?,
+            mapping.get_item("listitems")?,
+            mapping.get_item("dictitems")?,
+        ),
+        Err(_) => (Some(value), None, None, None, None),
+    };
+    let args = match args {
+        Some(args) => PyTuple::new(py, args.try_iter()?.collect::<PyResult<Vec<_>>>()?)?,
+        None => PyTuple::empty(py),
+    };
+    let kwds = match kwds {
+        Some(kwds) => Some(kwds.downcast_into::<PyDict>()?),
+        None => None,
+    };
+    let instance = if new {
+        let args: Vec<_> = std::iter::once(callable.clone())
+            .chain(args.iter())
+            .collect();
+        let args = PyTuple::new(py, args)?;
+        callable.getattr("__new__")?.call(args, kwds.as_ref())?
+    } else {
+        callable.call(args, kwds.as_ref())?
+    };
+    if let Some(state) = state {
+        set_state(&instance, state)?;
+    }
+    if let Some(listitems) = listitems {
+        instance.call_method1("extend", (listitems,))?;
+    }
+    if let Some(dictitems) = dictitems {
+        for (key, item) in dictitems.downcast::<PyDict>()?.iter() {
+            instance.set_item(key, item)?;
+        }
+    }
+    Ok(instance)
+}
+
+/// Restore `state` into `instance` like unpickling does, through
+/// `__setstate__`, `__dict__` or attributes for slots
+pub fn set_state(instance: &Bound<'_, PyAny>, state: Bound<'_, PyAny>) -> PyResult<()> {
+    if instance.hasattr("__setstate__")? {
+        instance.call_method1("__setstate__", (state,))?;
+        return Ok(());
+    }
+    let (state, slot_state) = match state.downcast::<PyTuple>() {
+        Ok(pair) if pair.len() == 2 => (pair.get_item(0)?, Some(pair.get_item(1)?)),
+        _ => (state, None),
+    };
+    let mut attributes = Vec::new();
+    if instance.hasattr("__dict__")? {
+        instance
+            .getattr("__dict__")?
+            .call_method1("update", (&state,))?;
+    } else if let Ok(state) = state.downcast::<PyDict>() {
+        attributes.extend(state.iter());
+    }
+    if let Some(slot_state) = slot_state
+        && !slot_state.is_none()
+    {
+        attributes.extend(slot_state.downcast::<PyDict>()?.iter());
+    }
+    for (name, value) in attributes {
+        instance.setattr(name.downcast_into::<PyString>()?, value)?;
+    }
+    Ok(())
+}
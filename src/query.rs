@@ -0,0 +1,330 @@
+//! `select()`: the values at a path such as `spec.containers[0].image`, found
+//! by walking the events of the documents. Only the nodes at the path are
+//! constructed, with the anchored nodes aliases in them may refer to, and
+//! `select()` stops parsing at the first match.
+
+use std::collections::HashSet;
+
+use libyaml_safer::EventData;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyList, PyMapping, PySequence, PyString};
+
+use crate::loader::RSafeLoader;
+
+/// One step of a path, into a mapping or a sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// The value of a key: `.name`, or `["name"]` for keys holding `.` or `[`
+    Key(String),
+    /// An item of a sequence: `[0]`
+    Index(usize),
+    /// Every value of a mapping: `.*`
+    AnyValue,
+    /// Every item of a sequence: `[*]`
+    AnyItem,
+}
+
+/// The steps of `path`. An empty path selects whole documents.
+pub fn parse_path(path: &str) -> PyResult<Vec<Step>> {
+    let invalid =
+        |problem: &str| PyValueError::new_err(format!("invalid path '{path}': {problem}"));
+    let mut steps = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(bracketed) = rest.strip_prefix('[') {
+            let end =
+                match bracketed.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let close = bracketed[1..]
+                            .find(quote)
+                            .ok_or_else(|| invalid("unclosed quote"))?;
+                        steps.push(Step::Key(bracketed[1..close + 1].to_string()));
+                        close + 2
+                    }
+                    _ => {
+                        let close = bracketed.find(']').ok_or_else(|| invalid("unclosed '['"))?;
+                        steps.push(match &bracketed[..close] {
+                            "*" => Step::AnyItem,
+                            index => Step::Index(index.parse().map_err(|_| {
+                                invalid(&format!("'{index}' is not an index or '*'"))
+                            })?),
+                        });
+                        close
+                    }
+                };
+            rest = bracketed[end..]
+                .strip_prefix(']')
+                .ok_or_else(|| invalid("expected ']'"))?;
+        } else {
+            // The first key of a path has no dot before it
+            let key = match rest.strip_prefix('.') {
+                Some(key) => key,
+                None if steps.is_empty() => rest,
+                None => return Err(invalid("expected '.' or '[' between steps")),
+            };
+            let end = key.find(['.', '[']).unwrap_or(key.len());
+            steps.push(match &key[..end] {
+                "" => return Err(invalid("empty key")),
+                "*" => Step::AnyValue,
+                name => Step::Key(name.to_string()),
+            });
+            rest = &key[end..];
+        }
+    }
+    Ok(steps)
+}
+
+/// The values at `steps` in the documents `loader` parses; only the first
+/// one, if `first`
+pub fn select(
+    py: Python,
+    loader: &mut RSafeLoader,
+    steps: &[Step],
+    first: bool,
+) -> PyResult<Vec<Py<PyAny>>> {
+    let mut walk = Walk {
+        loader,
+        first,
+        found: Vec::new(),
+    };
+    while !walk.done() && walk.loader.begin_document(py)? {
+        walk.node(py, steps)?;
+        if !walk.done() {
+            walk.loader.end_document(py)?;
+        }
+    }
+    Ok(walk.found)
+}
+
+struct Walk<'a> {
+    loader: &'a mut RSafeLoader,
+    first: bool,
+    found: Vec<Py<PyAny>>,
+}
+
+impl Walk<'_> {
+    /// Whether nothing more needs to be found; the rest isn't parsed
+    fn done(&self) -> bool {
+        self.first && !self.found.is_empty()
+    }
+
+    /// Take `steps` into the node the walk is at, moving past it
+    fn node(&mut self, py: Python, steps: &[Step]) -> PyResult<()> {
+        let Some((step, rest)) = steps.split_first() else {
+            let value = self.loader.construct_node(py)?;
+            self.found.push(value);
+            return Ok(());
+        };
+        let event = self.loader.peek_event(py)?;
+        match (&event.data, step) {
+            // Collections loaded as something else than a list or dict, and
+            // ones aliases may refer to, are constructed to be walked
+            (
+                EventData::MappingStart {
+                    anchor: None,
+                    tag: None,
+                    ..
+                },
+                Step::Key(_) | Step::AnyValue,
+            ) => self.mapping(py, step, rest),
+            (
+                EventData::SequenceStart {
+                    anchor: None,
+                    tag: None,
+                    ..
+                },
+                Step::Index(_) | Step::AnyItem,
+            ) => self.sequence(py, step, rest),
+            (
+                EventData::Alias { .. }
+                | EventData::MappingStart { .. }
+                | EventData::SequenceStart { .. },
+                _,
+            ) => {
+                let value = self.loader.construct_node(py)?;
+                self.object(value.bind(py), steps)
+            }
+            _ => self.skip(py),
+        }
+    }
+
+    /// Take `step` and then `rest` into the mapping the walk is at
+    fn mapping(&mut self, py: Python, step: &Step, rest: &[Step]) -> PyResult<()> {
+        self.loader.skip_event();
+        let mut seen = HashSet::new();
+        let mut merged = Vec::new();
+        loop {
+            if self.done() {
+                return Ok(());
+            }
+            if matches!(self.loader.peek_event(py)?.data, EventData::MappingEnd) {
+                self.loader.skip_event();
+                break;
+            }
+            if self.loader.at_merge_key() {
+                self.loader.skip_event();
+                merged.push(self.loader.construct_node(py)?);
+                continue;
+            }
+            // Complex and anchored keys are never matched
+            let key = match &self.loader.peek_event(py)?.data {
+                EventData::Scalar {
+                    anchor: None,
+                    value,
+                    ..
+                } => {
+                    let key = value.clone();
+                    self.loader.skip_event();
+                    Some(key)
+                }
+                _ => {
+                    self.skip(py)?;
+                    None
+                }
+            };
+            let selected = match (step, &key) {
+                (Step::AnyValue, _) => true,
+                (Step::Key(name), Some(key)) => name == key,
+                _ => false,
+            };
+            if let Some(key) = key {
+                seen.insert(key);
+            }
+            if selected {
+                self.node(py, rest)?;
+            } else {
+                self.skip(py)?;
+            }
+        }
+        // Keys merged in count where the mapping doesn't have them itself, the
+        // first mapping merged taking precedence
+        let mut sources = Vec::new();
+        for value in &merged {
+            match value.bind(py).downcast::<PyList>() {
+                Ok(list) => sources.extend(list.iter()),
+                Err(_) => sources.push(value.bind(py).clone()),
+            }
+        }
+        for source in sources {
+            let Ok(mapping) = source.downcast::<PyMapping>() else {
+                continue;
+            };
+            for item in mapping.items()?.iter() {
+                let (key, value): (Bound<'_, PyAny>, Bound<'_, PyAny>) = item.extract()?;
+                let Ok(key) = key.extract::<String>() else {
+                    continue;
+                };
+                let selected = match step {
+                    Step::AnyValue => true,
+                    Step::Key(name) => *name == key,
+                    _ => false,
+                };
+                if selected && seen.insert(key) {
+                    self.object(&value, rest)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Take `step` and then `rest` into the sequence the walk is at
+    fn sequence(&mut self, py: Python, step: &Step, rest: &[Step]) -> PyResult<()> {
+        self.loader.skip_event();
+        let mut index = 0;
+        loop {
+            if self.done() {
+                return Ok(());
+            }
+            if matches!(self.loader.peek_event(py)?.data, EventData::SequenceEnd) {
+                self.loader.skip_event();
+                return Ok(());
+            }
+            let selected = match step {
+                Step::AnyItem => true,
+                Step::Index(wanted) => index == *wanted,
+                _ => false,
+            };
+            if selected {
+                self.node(py, rest)?;
+            } else {
+                self.skip(py)?;
+            }
+            index += 1;
+        }
+    }
+
+    /// Take `steps` into an already constructed `value`
+    fn object(&mut self, value: &Bound<'_, PyAny>, steps: &[Step]) -> PyResult<()> {
+        let Some((step, rest)) = steps.split_first() else {
+            self.found.push(value.clone().unbind());
+            return Ok(());
+        };
+        let items: Vec<Bound<'_, PyAny>> = match step {
+            Step::Key(name) => match value.downcast::<PyMapping>() {
+                Ok(mapping) if mapping.contains(name)? => vec![mapping.get_item(name)?],
+                _ => Vec::new(),
+            },
+            Step::AnyValue => match value.downcast::<PyMapping>() {
+                Ok(mapping) => mapping.values()?.iter().collect(),
+                Err(_) => Vec::new(),
+            },
+            Step::Index(index) => match as_sequence(value) {
+                Some(sequence) if *index < sequence.len()? => vec![sequence.get_item(*index)?],
+                _ => Vec::new(),
+            },
+            Step::AnyItem => match as_sequence(value) {
+                Some(sequence) => sequence.try_iter()?.collect::<PyResult<_>>()?,
+                None => Vec::new(),
+            },
+        };
+        for item in items {
+            if self.done() {
+                break;
+            }
+            self.object(&item, rest)?;
+        }
+        Ok(())
+    }
+
+    /// Move past the node the walk is at. Anchored nodes are constructed, as
+    /// aliases in a selected node may refer to them.
+    fn skip(&mut self, py: Python) -> PyResult<()> {
+        let event = self.loader.peek_event(py)?;
+        let nested = match &event.data {
+            EventData::Scalar {
+                anchor: Some(_), ..
+            }
+            | EventData::SequenceStart {
+                anchor: Some(_), ..
+            }
+            | EventData::MappingStart {
+                anchor: Some(_), ..
+            } => {
+                self.loader.construct_node(py)?;
+                return Ok(());
+            }
+            EventData::SequenceStart { .. } | EventData::MappingStart { .. } => true,
+            _ => false,
+        };
+        self.loader.skip_event();
+        if nested {
+            while !matches!(
+                self.loader.peek_event(py)?.data,
+                EventData::SequenceEnd | EventData::MappingEnd
+            ) {
+                self.skip(py)?;
+            }
+            self.loader.skip_event();
+        }
+        Ok(())
+    }
+}
+
+/// `value` as a sequence to index, unless it is a string
+fn as_sequence<'a, 'py>(value: &'a Bound<'py, PyAny>) -> Option<&'a Bound<'py, PySequence>> {
+    if value.is_instance_of::<PyString>() || value.is_instance_of::<PyBytes>() {
+        return None;
+    }
+    value.downcast::<PySequence>().ok()
+}
@@ -0,0 +1,250 @@
+//! Registries of Python callbacks consulted while loading and dumping, one set
+//! per interpreter in the state of the `_ryaml` module.
+//!
+//! Registries are copy-on-write: registering builds a new map and swaps it in,
+//! while every load or dump takes a snapshot when it starts and dispatches from
+//! that snapshot only. A registration made from another thread mid-dump is
+//! therefore seen by the next call and never by half of the current one, which
+//! also holds on the free-threaded build where calls really run in parallel.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyString, PyType};
+
+use crate::resolver::{ImplicitResolver, Resolvers};
+
+/// An immutable view of a registry, as seen by one load or dump
+pub type Snapshot<K> = Arc<HashMap<K, Py<PyAny>>>;
+
+pub struct Registry<K> {
+    entries: RwLock<Snapshot<K>>,
+}
+
+impl<K: Eq + Hash + Clone> Registry<K> {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(Arc::new(HashMap::new())),
+        }
+    }
+
+    /// The current entries; later registrations don't affect the returned map
+    pub fn snapshot(&self) -> Snapshot<K> {
+        Arc::clone(&self.entries.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Register `callback` under `key`, replacing any earlier registration
+    pub fn insert(&self, py: Python, key: K, callback: Py<PyAny>) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let mut updated: HashMap<K, Py<PyAny>> = entries
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone_ref(py)))
+            .collect();
+        updated.insert(key, callback);
+        *entries = Arc::new(updated);
+    }
+
+    /// Put back the entries of an earlier `snapshot()`, undoing the
+    /// registrations made since
+    pub fn restore(&self, snapshot: &Snapshot<K>) {
+        *self.entries.write().unwrap_or_else(|e| e.into_inner()) = Arc::clone(snapshot);
+    }
+}
+
+/// The registries of one interpreter, kept in its module state
+pub struct Registries {
+    /// Constructors keyed by the full tag they construct
+    pub constructors: Registry<String>,
+    /// Classes registered with `add_yaml_object()`, keyed by the full tag they load
+    pub yaml_object_classes: Registry<String>,
+    /// Full tags of the classes registered with `add_yaml_object()`, keyed by
+    /// the address of the exact class they dump
+    pub yaml_object_tags: Registry<usize>,
+    /// Implicit resolvers, consulted in registration order
+    implicit_resolvers: RwLock<Resolvers>,
+    /// Representers keyed by the address of the exact type they handle.
+    /// Registered types are kept alive by `types`.
+    pub representers: Registry<usize>,
+    types: Registry<usize>,
+}
+
+/// The registrations of a `Registries` at one point, to go back to later
+#[pyclass(name = "_SavedRegistries", frozen)]
+pub struct SavedRegistries {
+    constructors: Snapshot<String>,
+    yaml_object_classes: Snapshot<String>,
+    yaml_object_tags: Snapshot<usize>,
+    implicit_resolvers: Resolvers,
+    representers: Snapshot<usize>,
+    types: Snapshot<usize>,
+}
+
+impl Registries {
+    pub fn new() -> Self {
+        Self {
+            constructors: Registry::new(),
+            yaml_object_classes: Registry::new(),
+            yaml_object_tags: Registry::new(),
+            implicit_resolvers: RwLock::new(Arc::new(Vec::new())),
+            representers: Registry::new(),
+            types: Registry::new(),
+        }
+    }
+
+    /// The registrations made so far
+    pub fn save(&self) -> SavedRegistries {
+        SavedRegistries {
+            constructors: self.constructors.snapshot(),
+            yaml_object_classes: self.yaml_object_classes.snapshot(),
+            yaml_object_tags: self.yaml_object_tags.snapshot(),
+            implicit_resolvers: self.implicit_resolvers(),
+            representers: self.representers.snapshot(),
+            types: self.types.snapshot(),
+        }
+    }
+
+    /// Undo the registrations made since `saved` was taken. Loads and dumps
+    /// under way keep the registrations they started with.
+    pub fn restore(&self, saved: &SavedRegistries) {
+        self.constructors.restore(&saved.constructors);
+        self.yaml_object_classes.restore(&saved.yaml_object_classes);
+        self.yaml_object_tags.restore(&saved.yaml_object_tags);
+        *self
+            .implicit_resolvers
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Arc::clone(&saved.implicit_resolvers);
+        self.representers.restore(&saved.representers);
+        self.types.restore(&saved.types);
+    }
+
+    /// Register `constructor` for nodes tagged `tag`, which may use the `!!`
+    /// shorthand. It is called with a `Constructor` and the node.
+    pub fn add_constructor(
+        &self,
+        py: Python,
+        tag: &str,
+        constructor: Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        if !constructor.is_callable() {
+            return Err(PyTypeError::new_err("constructor must be callable"));
+        }
+        self.constructors
+            .insert(py, expand_tag(tag), constructor.unbind());
+        Ok(())
+    }
+
+    /// Load mappings tagged `cls.yaml_tag` as instances of `cls`, and dump
+    /// instances of exactly `cls` with that tag. The class may define
+    /// `from_yaml(constructor, node)` and `to_yaml(data)` classmethods,
+    /// otherwise its state is the mapping.
+    pub fn add_yaml_object(&self, py: Python, cls: &Bound<'_, PyType>) -> PyResult<()> {
+        let tag = match cls.getattr("yaml_tag") {
+            Ok(tag) if !tag.is_none() => expand_tag(&tag.extract::<String>()?),
+            _ => return Err(PyTypeError::new_err("class must define yaml_tag")),
+        };
+        self.yaml_object_tags.insert(
+            py,
+            cls.as_ptr() as usize,
+            PyString::new(py, &tag).into_any().unbind(),
+        );
+        self.yaml_object_classes
+            .insert(py, tag, cls.clone().into_any().unbind());
+        Ok(())
+    }
+
+    /// The current implicit resolvers; later registrations don't affect the
+    /// returned list
+    pub fn implicit_resolvers(&self) -> Resolvers {
+        Arc::clone(
+            &self
+                .implicit_resolvers
+                .read()
+                .unwrap_or_else(|e| e.into_inner()),
+        )
+    }
+
+    /// Resolve plain scalars matching `pattern` (a regular expression string
+    /// or compiled pattern, matched against the whole value) to `tag`, when
+    /// loading and when deciding whether a dumped scalar needs its tag or
+    /// quotes. `first` lists the characters matching values can start with,
+    /// to skip the pattern for other values.
+    pub fn add_implicit_resolver(
+        &self,
+        py: Python,
+        tag: &str,
+        pattern: &Bound<'_, PyAny>,
+        first: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let pattern = py.import("re")?.call_method1("compile", (pattern,))?;
+        let first = match first {
+            Some(first) => Some(
+                first
+                    .try_iter()?
+                    .map(|c| c?.extract::<String>())
+                    .collect::<PyResult<String>>()?
+                    .chars()
+                    .collect(),
+            ),
+            None => None,
+        };
+        let mut resolvers = self
+            .implicit_resolvers
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut updated = Vec::clone(&resolvers);
+        updated.push(ImplicitResolver {
+            tag: expand_tag(tag),
+            pattern: pattern.unbind(),
+            first,
+        });
+        *resolvers = Arc::new(updated);
+        Ok(())
+    }
+
+    /// Register `representer` for instances of `data_type` (and its
+    /// subclasses). It is called with the object and must return a node.
+    pub fn add_representer(
+        &self,
+        py: Python,
+        data_type: &Bound<'_, PyType>,
+        representer: Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        if !representer.is_callable() {
+            return Err(PyTypeError::new_err("representer must be callable"));
+        }
+        let key = data_type.as_ptr() as usize;
+        self.types
+            .insert(py, key, data_type.clone().into_any().unbind());
+        self.representers.insert(py, key, representer.unbind());
+        Ok(())
+    }
+}
+
+/// Expand the `!!` shorthand of a tag
+fn expand_tag(tag: &str) -> String {
+    match tag.strip_prefix("!!") {
+        Some(name) => format!("tag:yaml.org,2002:{name}"),
+        None => tag.to_string(),
+    }
+}
+
+/// Find the representer for `data` in `representers`, walking the MRO of its type
+pub fn find_representer<'py>(
+    representers: &Snapshot<usize>,
+    data: &Bound<'py, PyAny>,
+) -> Option<Bound<'py, PyAny>> {
+    let py = data.py();
+    let data_type = data.get_type();
+    if let Some(representer) = representers.get(&(data_type.as_ptr() as usize)) {
+        return Some(representer.bind(py).clone());
+    }
+    for base in data_type.mro().iter() {
+        if let Some(representer) = representers.get(&(base.as_ptr() as usize)) {
+            return Some(representer.bind(py).clone());
+        }
+    }
+    None
+}
@@ -1,20 +1,209 @@
-//! Shared resolver for YAML 1.1 implicit tag resolution.
-//! Used by both the loader and dumper.
+//! Shared resolver for implicit tag resolution under the YAML 1.1 types and
+//! the YAML 1.2 core, JSON and failsafe schemas. Used by both the loader and dumper.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rustc_hash::FxBuildHasher;
 
 pub const DEFAULT_SCALAR_TAG: &str = crate::TAG_STR;
 pub const DEFAULT_SEQUENCE_TAG: &str = crate::TAG_SEQ;
 pub const DEFAULT_MAPPING_TAG: &str = crate::TAG_MAP;
 
+/// Set of implicit resolution rules for plain scalars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Schema {
+    /// YAML 1.1 types, as resolved by pyyaml
+    #[default]
+    Yaml11,
+    /// YAML 1.2 core schema: only `true`/`false` are booleans, no sexagesimals
+    /// or timestamps, and octals are written `0o17`
+    Core,
+    /// YAML 1.2 JSON schema: plain scalars must be JSON literals
+    Json,
+    /// YAML 1.2 failsafe schema: every plain scalar is a string
+    Failsafe,
+}
+
+impl Schema {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "1.1" => Ok(Schema::Yaml11),
+            "core" => Ok(Schema::Core),
+            "json" => Ok(Schema::Json),
+            "failsafe" => Ok(Schema::Failsafe),
+            _ => Err(PyValueError::new_err(format!(
+                "unsupported schema '{name}' (expected '1.1', 'core', 'json' or 'failsafe')"
+            ))),
+        }
+    }
+}
+
 /// Resolve the implicit tag for a scalar value.
 ///
 /// When `plain_implicit` is true, the value came from a plain (unquoted) scalar
-/// and we check all YAML 1.1 implicit patterns. When false, the value was quoted
-/// and defaults to `str`.
-pub fn resolve_scalar_tag(value: &str, plain_implicit: bool) -> &'static str {
+/// and we check all implicit patterns of `schema`. When false, the value was
+/// quoted and defaults to `str`. Returns None for plain scalars the JSON schema
+/// doesn't allow.
+pub fn resolve_scalar_tag(
+    value: &str,
+    plain_implicit: bool,
+    schema: Schema,
+) -> Option<&'static str> {
     if !plain_implicit {
-        return DEFAULT_SCALAR_TAG;
+        return Some(DEFAULT_SCALAR_TAG);
+    }
+    match schema {
+        Schema::Yaml11 => Some(resolve_yaml11_scalar_tag(value)),
+        Schema::Core => Some(resolve_core_scalar_tag(value)),
+        Schema::Json => resolve_json_scalar_tag(value),
+        Schema::Failsafe => Some(DEFAULT_SCALAR_TAG),
     }
+}
 
+/// A user-defined implicit rule, registered with `add_implicit_resolver()`
+#[derive(Clone)]
+pub struct ImplicitResolver {
+    pub tag: String,
+    /// Compiled `re` pattern the whole value must match
+    pub pattern: Py<PyAny>,
+    /// Characters a matching value can start with, or None for any
+    pub first: Option<Vec<char>>,
+}
+
+/// The implicit resolvers in effect for one load or dump, in registration order
+pub type Resolvers = Arc<Vec<ImplicitResolver>>;
+
+impl ImplicitResolver {
+    fn matches(&self, py: Python, value: &str) -> PyResult<bool> {
+        if let Some(first) = &self.first
+            && !value.chars().next().is_some_and(|c| first.contains(&c))
+        {
+            return Ok(false);
+        }
+        Ok(!self
+            .pattern
+            .bind(py)
+            .call_method1("fullmatch", (value,))?
+            .is_none())
+    }
+}
+
+/// Whether `resolvers` get a say on a scalar the schema resolved to `tag`
+fn claimable(
+    tag: Option<&str>,
+    plain_implicit: bool,
+    schema: Schema,
+    resolvers: &[ImplicitResolver],
+) -> bool {
+    plain_implicit
+        && !resolvers.is_empty()
+        && schema != Schema::Failsafe
+        && matches!(tag, None | Some(DEFAULT_SCALAR_TAG))
+}
+
+/// Index of the first of `resolvers` matching `value`
+fn claiming_resolver(
+    py: Python,
+    value: &str,
+    resolvers: &[ImplicitResolver],
+) -> PyResult<Option<usize>> {
+    for (index, resolver) in resolvers.iter().enumerate() {
+        if resolver.matches(py, value)? {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/// Longest value, in bytes, whose resolution `ResolverCache` remembers
+const MAX_CACHED_VALUE_LEN: usize = 64;
+/// Most values `ResolverCache` remembers per set of resolvers
+const MAX_CACHED_VALUES: usize = 1024;
+
+/// Index of the resolver which claimed each value, if any
+type Claims = HashMap<String, Option<usize>, FxBuildHasher>;
+
+/// Which implicit resolver claimed each short plain scalar seen so far, so
+/// repeated values don't run the resolvers' patterns again
+#[derive(Default)]
+pub struct ResolverCache(Mutex<Option<(Resolvers, Claims)>>);
+
+impl ResolverCache {
+    /// Resolve a scalar like `resolve_scalar_tag`, then let `resolvers` claim
+    /// the plain scalars the schema leaves as strings (or rejects, for JSON).
+    /// The built-in rules take precedence, as in pyyaml.
+    pub fn resolve<'a>(
+        &self,
+        py: Python,
+        value: &str,
+        plain_implicit: bool,
+        schema: Schema,
+        resolvers: &'a Resolvers,
+    ) -> PyResult<Option<&'a str>> {
+        let tag = resolve_scalar_tag(value, plain_implicit, schema);
+        if !claimable(tag, plain_implicit, schema, resolvers) {
+            return Ok(tag);
+        }
+        let cached = self.lookup(resolvers, value);
+        let claimed = match cached {
+            Some(claimed) => claimed,
+            // The lock isn't held while patterns run, as they are Python code
+            None => {
+                let claimed = claiming_resolver(py, value, resolvers)?;
+                self.remember(resolvers, value, claimed);
+                claimed
+            }
+        };
+        Ok(match claimed {
+            Some(index) => Some(&resolvers[index].tag),
+            None => tag,
+        })
+    }
+
+    /// Number of values whose resolution is remembered
+    pub fn remembered(&self) -> usize {
+        let cache = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        cache.as_ref().map_or(0, |(_, values)| values.len())
+    }
+
+    /// Forget every resolution, releasing their memory
+    pub fn clear(&self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn lookup(&self, resolvers: &Resolvers, value: &str) -> Option<Option<usize>> {
+        let cache = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match &*cache {
+            Some((cached, values)) if Arc::ptr_eq(cached, resolvers) => values.get(value).copied(),
+            _ => None,
+        }
+    }
+
+    fn remember(&self, resolvers: &Resolvers, value: &str, claimed: Option<usize>) {
+        if value.len() > MAX_CACHED_VALUE_LEN {
+            return;
+        }
+        let mut cache = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        // Indices only hold for the resolvers they were found in
+        if !cache
+            .as_ref()
+            .is_some_and(|(cached, _)| Arc::ptr_eq(cached, resolvers))
+        {
+            *cache = Some((Arc::clone(resolvers), HashMap::default()));
+        }
+        if let Some((_, values)) = cache.as_mut()
+            && values.len() < MAX_CACHED_VALUES
+        {
+            values.insert(value.to_string(), claimed);
+        }
+    }
+}
+
+/// Resolve a plain scalar with the YAML 1.1 types
+fn resolve_yaml11_scalar_tag(value: &str) -> &'static str {
     match value {
         "" | "~" | "null" | "Null" | "NULL" => crate::TAG_NULL,
         "yes" | "Yes" | "YES" | "no" | "No" | "NO" | "true" | "True" | "TRUE" | "false"
@@ -37,6 +226,160 @@ pub fn resolve_scalar_tag(value: &str, plain_implicit: bool) -> &'static str {
     }
 }
 
+/// Resolve a plain scalar with the YAML 1.2 core schema. YAML 1.2 has no
+/// merge or value types, so `<<` and `=` are plain strings; loaders can turn
+/// merge keys back on with `merge_keys=True`.
+fn resolve_core_scalar_tag(value: &str) -> &'static str {
+    match value {
+        "" | "~" | "null" | "Null" | "NULL" => crate::TAG_NULL,
+        "true" | "True" | "TRUE" | "false" | "False" | "FALSE" => crate::TAG_BOOL,
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" | "-.inf" | "-.Inf" | "-.INF"
+        | ".nan" | ".NaN" | ".NAN" => crate::TAG_FLOAT,
+        _ if is_core_int(value) => crate::TAG_INT,
+        _ if is_core_float(value) => crate::TAG_FLOAT,
+        _ => DEFAULT_SCALAR_TAG,
+    }
+}
+
+/// Resolve a plain scalar with the YAML 1.2 JSON schema
+fn resolve_json_scalar_tag(value: &str) -> Option<&'static str> {
+    match value {
+        "null" => Some(crate::TAG_NULL),
+        "true" | "false" => Some(crate::TAG_BOOL),
+        _ if is_json_int(value) => Some(crate::TAG_INT),
+        _ if is_json_float(value) => Some(crate::TAG_FLOAT),
+        _ => None,
+    }
+}
+
+/// Name of the type an unquoted scalar would load as: "null", "bool", "int",
+/// "float", "timestamp", "merge", "value" or "str". Anything other than "str"
+/// must be quoted to survive a round trip as a string. None if `schema`
+/// doesn't allow the value unquoted at all.
+pub fn scalar_kind(value: &str, schema: Schema) -> Option<&'static str> {
+    let tag = resolve_scalar_tag(value, true, schema)?;
+    Some(tag.strip_prefix("tag:yaml.org,2002:").unwrap_or(tag))
+}
+
+/// How plain `value`, which YAML 1.1 resolves to `tag`, changes meaning under
+/// the YAML 1.2 core schema, or None if it doesn't: a YAML 1.1 bool, int,
+/// float, null or timestamp of another type, or an int with a leading zero,
+/// octal in YAML 1.1 and decimal in YAML 1.2.
+pub fn yaml12_change(value: &str, tag: &str) -> Option<String> {
+    if !matches!(
+        tag,
+        crate::TAG_BOOL
+            | crate::TAG_INT
+            | crate::TAG_FLOAT
+            | crate::TAG_NULL
+            | crate::TAG_TIMESTAMP
+    ) {
+        return None;
+    }
+    let core = resolve_core_scalar_tag(value);
+    if core != tag {
+        let kind = |tag: &str| match tag.trim_start_matches("tag:yaml.org,2002:") {
+            "int" => "an int".to_string(),
+            kind => format!("a {kind}"),
+        };
+        return Some(format!(
+            "'{value}' is {} in YAML 1.1 but {} in YAML 1.2",
+            kind(tag),
+            kind(core)
+        ));
+    }
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    // Octal and decimal readings only differ from 8 on
+    let significant = digits.trim_start_matches('0');
+    if tag == crate::TAG_INT && digits.starts_with('0') && significant.len() > 1 {
+        return Some(format!(
+            "'{value}' is an octal int in YAML 1.1 but a decimal int in YAML 1.2"
+        ));
+    }
+    None
+}
+
+/// Match YAML 1.2 core integer: `[-+]?[0-9]+`, `0o[0-7]+` or `0x[0-9a-fA-F]+`
+fn is_core_int(value: &str) -> bool {
+    if let Some(octal) = value.strip_prefix("0o") {
+        return !octal.is_empty() && octal.bytes().all(|c| matches!(c, b'0'..=b'7'));
+    }
+    if let Some(hex) = value.strip_prefix("0x") {
+        return !hex.is_empty() && hex.bytes().all(|c| c.is_ascii_hexdigit());
+    }
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Match JSON integer: `-?(0|[1-9][0-9]*)`
+fn is_json_int(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    !digits.is_empty() && json_int_part_len(digits.as_bytes()) == digits.len()
+}
+
+/// Match JSON float: `-?(0|[1-9][0-9]*)(\.[0-9]*)?([eE][-+]?[0-9]+)?`
+fn is_json_float(value: &str) -> bool {
+    let b = value.strip_prefix('-').unwrap_or(value).as_bytes();
+    let mut i = json_int_part_len(b);
+    if i == 0 {
+        return false;
+    }
+    if i < b.len() && b[i] == b'.' {
+        i += 1;
+        i += b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    }
+    if i < b.len() && matches!(b[i], b'e' | b'E') {
+        i += 1;
+        if i < b.len() && matches!(b[i], b'-' | b'+') {
+            i += 1;
+        }
+        let exponent = b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if exponent == 0 {
+            return false;
+        }
+        i += exponent;
+    }
+    i == b.len()
+}
+
+/// Length of the leading `0|[1-9][0-9]*` in `b`, or 0 if there is none
+fn json_int_part_len(b: &[u8]) -> usize {
+    match b.first() {
+        Some(b'0') => 1,
+        Some(b'1'..=b'9') => 1 + b[1..].iter().take_while(|c| c.is_ascii_digit()).count(),
+        _ => 0,
+    }
+}
+
+/// Match YAML 1.2 core float (excluding inf/nan which are handled by the caller):
+/// `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+fn is_core_float(value: &str) -> bool {
+    let b = value.strip_prefix(['-', '+']).unwrap_or(value).as_bytes();
+    let leading = b.iter().take_while(|c| c.is_ascii_digit()).count();
+    let mut i = leading;
+    let mut fraction = 0;
+    if i < b.len() && b[i] == b'.' {
+        i += 1;
+        fraction = b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        i += fraction;
+    }
+    if leading == 0 && fraction == 0 {
+        return false;
+    }
+    if i < b.len() && matches!(b[i], b'e' | b'E') {
+        i += 1;
+        if i < b.len() && matches!(b[i], b'-' | b'+') {
+            i += 1;
+        }
+        let exponent = b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if exponent == 0 {
+            return false;
+        }
+        i += exponent;
+    }
+    i == b.len()
+}
+
 /// Match YAML 1.1 integer: binary (0b), octal (0), decimal, hex (0x), sexagesimal.
 fn is_int(value: &str) -> bool {
     let b = value.as_bytes();
@@ -191,127 +534,163 @@ fn is_exponent(b: &[u8]) -> bool {
 
 /// Match YAML 1.1 timestamp: date-only (YYYY-MM-DD) or full datetime.
 fn is_timestamp(value: &str) -> bool {
-    let b = value.as_bytes();
-    // Year: [0-9]{4}-
-    if b.len() < 8
-        || !b[0].is_ascii_digit()
-        || !b[1].is_ascii_digit()
-        || !b[2].is_ascii_digit()
-        || !b[3].is_ascii_digit()
-        || b[4] != b'-'
-    {
-        return false;
-    }
-    let mut i = 5;
+    // Implicit date-only values need two-digit months and days
+    parse_timestamp(value).is_some_and(|ts| ts.time.is_some() || value.len() == 10)
+}
 
-    // Month: [0-9][0-9]?
-    if !b[i].is_ascii_digit() {
-        return false;
-    }
-    i += 1;
-    let two_digit_month = i < b.len() && b[i].is_ascii_digit();
-    if two_digit_month {
-        i += 1;
-    }
-    if i >= b.len() || b[i] != b'-' {
-        return false;
-    }
-    i += 1;
+/// Fields of a YAML 1.1 timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    /// Time of day, absent for date-only values
+    pub time: Option<TimeOfDay>,
+}
 
-    // Day: [0-9][0-9]?
-    if i >= b.len() || !b[i].is_ascii_digit() {
-        return false;
-    }
-    i += 1;
-    let two_digit_day = i < b.len() && b[i].is_ascii_digit();
-    if two_digit_day {
-        i += 1;
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Fraction of a second truncated to microseconds
+    pub microsecond: u32,
+    /// Offset from UTC in minutes, absent for naive times
+    pub offset: Option<i32>,
+}
 
-    // Date-only form requires exactly YYYY-MM-DD
-    if i == b.len() {
-        return two_digit_month && two_digit_day;
+/// Parse a timestamp using the constructor's grammar, which unlike implicit
+/// resolution accepts single-digit months and days in date-only values:
+/// `YYYY-M[M]-D[D]([Tt]|[ \t]+)H[H]:MM:SS(.F*)?([ \t]*(Z|[-+]H[H](:MM)?))?`
+pub fn parse_timestamp(value: &str) -> Option<Timestamp> {
+    let mut cursor = TimestampCursor {
+        b: value.as_bytes(),
+        i: 0,
+    };
+
+    let year = cursor.digits(4, 4)? as i32;
+    cursor.expect(b'-')?;
+    let month = cursor.digits(1, 2)? as u8;
+    cursor.expect(b'-')?;
+    let day = cursor.digits(1, 2)? as u8;
+    if cursor.at_end() {
+        return Some(Timestamp {
+            year,
+            month,
+            day,
+            time: None,
+        });
     }
 
     // Separator: [Tt] or [ \t]+
-    if b[i] == b'T' || b[i] == b't' {
-        i += 1;
-    } else if b[i] == b' ' || b[i] == b'\t' {
-        while i < b.len() && (b[i] == b' ' || b[i] == b'\t') {
-            i += 1;
+    if !cursor.eat(b'T') && !cursor.eat(b't') && cursor.skip_blanks() == 0 {
+        return None;
+    }
+    let hour = cursor.digits(1, 2)? as u8;
+    cursor.expect(b':')?;
+    let minute = cursor.digits(2, 2)? as u8;
+    cursor.expect(b':')?;
+    let second = cursor.digits(2, 2)? as u8;
+
+    let mut microsecond = 0;
+    if cursor.eat(b'.') {
+        let start = cursor.i;
+        while cursor.peek().is_some_and(|c| c.is_ascii_digit()) {
+            cursor.i += 1;
+        }
+        // Keep the first six digits, right-padded with zeros
+        let fraction = &cursor.b[start..cursor.i];
+        for position in 0..6 {
+            let digit = fraction.get(position).map_or(0, |c| c - b'0');
+            microsecond = microsecond * 10 + u32::from(digit);
         }
-    } else {
-        return false;
     }
 
-    // Hour: [0-9][0-9]?
-    if i >= b.len() || !b[i].is_ascii_digit() {
-        return false;
-    }
-    i += 1;
-    if i < b.len() && b[i].is_ascii_digit() {
-        i += 1;
+    let mut offset = None;
+    if !cursor.at_end() {
+        cursor.skip_blanks();
+        if cursor.eat(b'Z') {
+            offset = Some(0);
+        } else {
+            let sign = match cursor.peek()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            cursor.i += 1;
+            let hours = cursor.digits(1, 2)? as i32;
+            let minutes = if cursor.eat(b':') {
+                cursor.digits(2, 2)? as i32
+            } else {
+                0
+            };
+            offset = Some(sign * (hours * 60 + minutes));
+        }
+        if !cursor.at_end() {
+            return None;
+        }
     }
 
-    // :MM
-    if i >= b.len() || b[i] != b':' {
-        return false;
-    }
-    i += 1;
-    if i + 1 >= b.len() || !b[i].is_ascii_digit() || !b[i + 1].is_ascii_digit() {
-        return false;
-    }
-    i += 2;
+    Some(Timestamp {
+        year,
+        month,
+        day,
+        time: Some(TimeOfDay {
+            hour,
+            minute,
+            second,
+            microsecond,
+            offset,
+        }),
+    })
+}
 
-    // :SS
-    if i >= b.len() || b[i] != b':' {
-        return false;
-    }
-    i += 1;
-    if i + 1 >= b.len() || !b[i].is_ascii_digit() || !b[i + 1].is_ascii_digit() {
-        return false;
-    }
-    i += 2;
+struct TimestampCursor<'a> {
+    b: &'a [u8],
+    i: usize,
+}
 
-    // Optional fractional seconds: \.[0-9]*
-    if i < b.len() && b[i] == b'.' {
-        i += 1;
-        while i < b.len() && b[i].is_ascii_digit() {
-            i += 1;
-        }
+impl TimestampCursor<'_> {
+    fn at_end(&self) -> bool {
+        self.i == self.b.len()
     }
 
-    if i == b.len() {
-        return true;
+    fn peek(&self) -> Option<u8> {
+        self.b.get(self.i).copied()
     }
 
-    // Optional timezone: [ \t]*(Z|[-+][0-9][0-9]?(:[0-9][0-9])?)
-    while i < b.len() && (b[i] == b' ' || b[i] == b'\t') {
-        i += 1;
-    }
-    if i >= b.len() {
-        return false;
-    }
-    if b[i] == b'Z' {
-        return i + 1 == b.len();
-    }
-    if b[i] != b'+' && b[i] != b'-' {
-        return false;
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.i += 1;
+            true
+        } else {
+            false
+        }
     }
-    i += 1;
 
-    // Offset hours: [0-9][0-9]?
-    if i >= b.len() || !b[i].is_ascii_digit() {
-        return false;
-    }
-    i += 1;
-    if i < b.len() && b[i].is_ascii_digit() {
-        i += 1;
-    }
-    if i == b.len() {
-        return true;
+    fn expect(&mut self, c: u8) -> Option<()> {
+        self.eat(c).then_some(())
     }
 
-    // Optional offset minutes: :[0-9][0-9]
-    b[i] == b':' && i + 3 == b.len() && b[i + 1].is_ascii_digit() && b[i + 2].is_ascii_digit()
+    fn skip_blanks(&mut self) -> usize {
+        let start = self.i;
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.i += 1;
+        }
+        self.i - start
+    }
+
+    /// Read between `min` and `max` ASCII digits as a number
+    fn digits(&mut self, min: usize, max: usize) -> Option<u32> {
+        let mut n = 0;
+        let mut count = 0;
+        while count < max
+            && let Some(c) = self.peek().filter(u8::is_ascii_digit)
+        {
+            n = n * 10 + u32::from(c - b'0');
+            self.i += 1;
+            count += 1;
+        }
+        (count >= min).then_some(n)
+    }
 }
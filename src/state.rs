@@ -0,0 +1,150 @@
+//! State of the `_ryaml` module: the registries, the Python objects looked up
+//! once and the pooled loader and dumper.
+//!
+//! None of it lives in Rust statics. Each interpreter importing the module gets
+//! its own `ModuleState`, stored on the module as `_state`, so that callbacks,
+//! types and cached objects of one interpreter are never seen from another.
+
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+use pyo3::pyclass::{PyTraverseError, PyVisit};
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyModule, PyString, PyType};
+
+use crate::dumper::RSafeDumper;
+use crate::loader::RSafeLoader;
+use crate::registry::{Registries, SavedRegistries};
+
+/// Name the module state is stored under on `_ryaml`
+const ATTRIBUTE: &str = "_state";
+
+#[pyclass(name = "_ModuleState", frozen)]
+pub struct ModuleState {
+    pub registries: Registries,
+    /// `types.GeneratorType`, for two-step constructors written for pyyaml
+    pub generator_type: PyOnceLock<Py<PyType>>,
+    /// `datetime.datetime` and `datetime.date`, imported once instead of on
+    /// every object
+    pub datetime_type: PyOnceLock<Py<PyType>>,
+    pub date_type: PyOnceLock<Py<PyType>>,
+    /// `dataclasses.fields`, imported the first time a dataclass is dumped
+    pub dataclass_fields: PyOnceLock<Py<PyAny>>,
+    /// `int.__repr__`, formatting ints too big for an i64 whatever their class
+    pub int_repr: PyOnceLock<Py<PyAny>>,
+    /// `datetime.timezone.utc`, used with `timezone="utc"`
+    pub utc: PyOnceLock<Py<PyAny>>,
+    /// `os.environ`, read by `expand_env`
+    pub environ: PyOnceLock<Py<PyAny>>,
+    /// Classes from `ryaml.containers`, used with `preserve_flow_style` and
+    /// `roundtrip`
+    pub styled_list: PyOnceLock<Py<PyType>>,
+    pub styled_dict: PyOnceLock<Py<PyType>>,
+    pub styled_str: PyOnceLock<Py<PyType>>,
+    pub entry_comments: PyOnceLock<Py<PyType>>,
+    /// Loader released by the last module-level load, taken by the next one
+    pooled_loader: Mutex<Option<RSafeLoader>>,
+    /// Dumper released by the last module-level dump, taken by the next one
+    pooled_dumper: Mutex<Option<RSafeDumper>>,
+}
+
+#[pymethods]
+impl ModuleState {
+    // The pooled loader and dumper refer back to this state, so the cycle is
+    // shown to the garbage collector
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Ok(pool) = self.pooled_loader.try_lock()
+            && let Some(loader) = pool.as_ref()
+        {
+            visit.call(loader.state())?;
+        }
+        if let Ok(pool) = self.pooled_dumper.try_lock()
+            && let Some(dumper) = pool.as_ref()
+        {
+            visit.call(dumper.state())?;
+        }
+        Ok(())
+    }
+
+    fn __clear__(&self) {
+        self.take_loader();
+        self.take_dumper();
+    }
+
+    /// The registrations made so far, for `restore_registries()`
+    fn save_registries(&self) -> SavedRegistries {
+        self.registries.save()
+    }
+
+    /// Undo the registrations made since `saved` was taken, as tests do to
+    /// leave no constructor or representer behind for the next one
+    fn restore_registries(&self, saved: &Bound<'_, SavedRegistries>) {
+        self.registries.restore(saved.get());
+    }
+}
+
+impl ModuleState {
+    fn new() -> Self {
+        Self {
+            registries: Registries::new(),
+            generator_type: PyOnceLock::new(),
+            datetime_type: PyOnceLock::new(),
+            date_type: PyOnceLock::new(),
+            dataclass_fields: PyOnceLock::new(),
+            int_repr: PyOnceLock::new(),
+            utc: PyOnceLock::new(),
+            environ: PyOnceLock::new(),
+            styled_list: PyOnceLock::new(),
+            styled_dict: PyOnceLock::new(),
+            styled_str: PyOnceLock::new(),
+            entry_comments: PyOnceLock::new(),
+            pooled_loader: Mutex::new(None),
+            pooled_dumper: Mutex::new(None),
+        }
+    }
+
+    /// Take the pooled loader, unless another thread holds the pool
+    pub fn take_loader(&self) -> Option<RSafeLoader> {
+        self.pooled_loader.try_lock().ok()?.take()
+    }
+
+    /// Pool `loader` for the next load, unless one is pooled already
+    pub fn put_loader(&self, loader: RSafeLoader) {
+        if let Ok(mut pool) = self.pooled_loader.try_lock() {
+            pool.get_or_insert(loader);
+        }
+    }
+
+    /// Take the pooled dumper, unless another thread holds the pool
+    pub fn take_dumper(&self) -> Option<RSafeDumper> {
+        self.pooled_dumper.try_lock().ok()?.take()
+    }
+
+    /// Pool `dumper` for the next dump, unless one is pooled already
+    pub fn put_dumper(&self, dumper: RSafeDumper) {
+        if let Ok(mut pool) = self.pooled_dumper.try_lock() {
+            pool.get_or_insert(dumper);
+        }
+    }
+}
+
+/// Give the module `m` a state of its own
+pub fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add(ATTRIBUTE, ModuleState::new())
+}
+
+/// The state of the `_ryaml` module imported by the running interpreter
+pub fn get(py: Python<'_>) -> PyResult<Bound<'_, ModuleState>> {
+    let name = PyString::new(py, "ryaml._ryaml");
+    // SAFETY: `name` is a valid str, and the result is a new reference or NULL
+    let module =
+        unsafe { Bound::from_owned_ptr_or_opt(py, pyo3::ffi::PyImport_GetModule(name.as_ptr())) };
+    let module = match module {
+        Some(module) => module,
+        None => match PyErr::take(py) {
+            Some(err) => return Err(err),
+            None => py.import(name)?.into_any(),
+        },
+    };
+    Ok(module.getattr(ATTRIBUTE)?.downcast_into()?)
+}
@@ -0,0 +1,51 @@
+//! TaggedValue Python class, holding a value loaded from a node with a tag no
+//! constructor knows, so it can be dumped back with that tag.
+
+use pyo3::prelude::*;
+
+#[pyclass(name = "TaggedValue", frozen)]
+pub struct PyTaggedValue {
+    /// Full tag of the node, e.g. `!Ref` or `tag:yaml.org,2002:python/tuple`
+    #[pyo3(get)]
+    pub tag: String,
+    /// The scalar string, list or dict the node loads as without its tag
+    #[pyo3(get)]
+    pub value: Py<PyAny>,
+}
+
+#[pymethods]
+impl PyTaggedValue {
+    #[new]
+    pub fn new(tag: String, value: Py<PyAny>) -> Self {
+        Self { tag, value }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        Ok(format!(
+            "TaggedValue({}, {})",
+            self.tag.as_str().into_pyobject(py)?.repr()?,
+            self.value.bind(py).repr()?
+        ))
+    }
+
+    fn __eq__(&self, py: Python, other: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match other.downcast::<PyTaggedValue>() {
+            Ok(other) => {
+                let other = other.get();
+                Ok(self.tag == other.tag && self.value.bind(py).eq(&other.value)?)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn __hash__(&self, py: Python) -> PyResult<isize> {
+        (self.tag.as_str(), self.value.bind(py))
+            .into_pyobject(py)?
+            .hash()
+    }
+}
+
+pub fn register_tagged(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyTaggedValue>()?;
+    Ok(())
+}
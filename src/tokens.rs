@@ -0,0 +1,105 @@
+//! `tokenize_spans()`: the kind and character offsets of every token of a
+//! stream, for editors highlighting and folding YAML. Only the spans reach
+//! Python, as tuples sharing one str per kind, rather than token objects.
+
+use std::io::Cursor;
+
+use libyaml_safer::{ScalarStyle, Scanner, TokenData};
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyString};
+
+use crate::encoding::SourceText;
+use crate::mark::PyMark;
+
+/// Names of the token kinds, which `kind()` indexes
+const KINDS: &[&str] = &[
+    "stream_start",
+    "stream_end",
+    "version_directive",
+    "tag_directive",
+    "document_start",
+    "document_end",
+    "block_sequence_start",
+    "block_mapping_start",
+    "block_end",
+    "flow_sequence_start",
+    "flow_sequence_end",
+    "flow_mapping_start",
+    "flow_mapping_end",
+    "block_entry",
+    "flow_entry",
+    "key",
+    "value",
+    "alias",
+    "anchor",
+    "tag",
+    "plain_scalar",
+    "single_quoted_scalar",
+    "double_quoted_scalar",
+    "literal_scalar",
+    "folded_scalar",
+];
+
+/// Index in `KINDS` of the kind of token `data` is
+fn kind(data: &TokenData) -> usize {
+    match data {
+        TokenData::StreamStart { .. } => 0,
+        TokenData::StreamEnd => 1,
+        TokenData::VersionDirective { .. } => 2,
+        TokenData::TagDirective { .. } => 3,
+        TokenData::DocumentStart => 4,
+        TokenData::DocumentEnd => 5,
+        TokenData::BlockSequenceStart => 6,
+        TokenData::BlockMappingStart => 7,
+        TokenData::BlockEnd => 8,
+        TokenData::FlowSequenceStart => 9,
+        TokenData::FlowSequenceEnd => 10,
+        TokenData::FlowMappingStart => 11,
+        TokenData::FlowMappingEnd => 12,
+        TokenData::BlockEntry => 13,
+        TokenData::FlowEntry => 14,
+        TokenData::Key => 15,
+        TokenData::Value => 16,
+        TokenData::Alias { .. } => 17,
+        TokenData::Anchor { .. } => 18,
+        TokenData::Tag { .. } => 19,
+        TokenData::Scalar { style, .. } => match style {
+            ScalarStyle::SingleQuoted => 21,
+            ScalarStyle::DoubleQuoted => 22,
+            ScalarStyle::Literal => 23,
+            ScalarStyle::Folded => 24,
+            ScalarStyle::Any | ScalarStyle::Plain => 20,
+        },
+    }
+}
+
+/// `(kind, start, end)` of every token of `source`, offsets counting
+/// characters. Zero-width tokens, such as the start and end of block
+/// collections, are included, which is what folding needs.
+pub fn tokenize_spans<'py>(py: Python<'py>, source: SourceText) -> PyResult<Bound<'py, PyList>> {
+    let scanned = py.detach(|| {
+        let mut scanner = Scanner::new();
+        scanner.set_input(Cursor::new(source.clone()));
+        let mut spans = Vec::new();
+        for token in scanner {
+            let token = token?;
+            spans.push((
+                kind(&token.data),
+                token.start_mark.index,
+                token.end_mark.index,
+            ));
+        }
+        Ok(spans)
+    });
+    let spans = scanned.map_err(|e| {
+        crate::exception::libyaml_error(py, &e, |m| {
+            PyMark::with_source(m, crate::mark::DEFAULT_SOURCE_NAME, &source)
+        })
+    })?;
+    let kinds: Vec<_> = KINDS.iter().map(|kind| PyString::new(py, kind)).collect();
+    let list = PyList::empty(py);
+    for (kind, start, end) in spans {
+        list.append((kinds[kind].clone(), start, end))?;
+    }
+    Ok(list)
+}
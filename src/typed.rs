@@ -0,0 +1,799 @@
+//! `loads_into()`: a document decoded straight into the type it is annotated
+//! with, walking its events and checking keys and scalars against the
+//! annotations on the way. Dataclasses, TypedDicts, `list`, `set`, `tuple`
+//! and `dict` generics, unions, `Literal` and plain classes are understood.
+//!
+//! Collections aliases may refer to, and tagged ones, are constructed as
+//! usual and then converted to the type.
+
+use std::collections::HashMap;
+
+use libyaml_safer::EventData;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{
+    PyBool, PyDict, PyFloat, PyFrozenSet, PyInt, PyList, PySet, PyString, PyTuple, PyType,
+};
+
+use crate::exception;
+use crate::loader::RSafeLoader;
+use crate::mark::PyMark;
+
+/// A type to decode values into
+enum Type {
+    Any,
+    None,
+    /// An int, but not a bool
+    Int,
+    /// A float; ints are converted
+    Float,
+    /// An instance of a class, constructed as usual: `str`, `datetime.date`...
+    Instance(Py<PyType>),
+    Literal(Vec<Py<PyAny>>),
+    List(Box<Type>),
+    Set {
+        item: Box<Type>,
+        frozen: bool,
+    },
+    Tuple(Vec<Type>),
+    /// `tuple[T, ...]`
+    VarTuple(Box<Type>),
+    Dict(Box<Type>, Box<Type>),
+    Union(Vec<Type>),
+    /// A dataclass or TypedDict, by its position in `Schema::classes`
+    Class(usize),
+}
+
+/// The fields of a dataclass or TypedDict
+struct Class {
+    name: String,
+    /// The dataclass to instantiate; None for a TypedDict, decoded as a dict
+    dataclass: Option<Py<PyType>>,
+    fields: Vec<Field>,
+    /// Position of each field, by name
+    positions: HashMap<String, usize>,
+}
+
+struct Field {
+    name: String,
+    ty: Type,
+    required: bool,
+}
+
+/// A type annotation, compiled to decode documents into
+pub struct Schema {
+    root: Type,
+    classes: Vec<Class>,
+}
+
+impl Schema {
+    pub fn new(ty: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let py = ty.py();
+        let mut compiler = Compiler {
+            builtins: py.import("builtins")?,
+            typing: py.import("typing")?,
+            abc: py.import("collections.abc")?,
+            dataclasses: py.import("dataclasses")?,
+            types: py.import("types")?,
+            classes: Vec::new(),
+            compiled: HashMap::new(),
+        };
+        let root = compiler.compile(ty)?;
+        Ok(Schema {
+            root,
+            classes: compiler.classes,
+        })
+    }
+
+    /// The type's name, as errors give it
+    fn describe(&self, py: Python, ty: &Type) -> String {
+        match ty {
+            Type::Any => "any value".to_string(),
+            Type::None => "None".to_string(),
+            Type::Int => "int".to_string(),
+            Type::Float => "float".to_string(),
+            Type::Instance(class) => class_name(class.bind(py)),
+            Type::Literal(values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| {
+                        value
+                            .bind(py)
+                            .repr()
+                            .map_or_else(|_| "?".to_string(), |repr| repr.to_string())
+                    })
+                    .collect();
+                format!("one of {}", values.join(", "))
+            }
+            Type::List(_) => "a list".to_string(),
+            Type::Set { frozen: false, .. } => "a set".to_string(),
+            Type::Set { frozen: true, .. } => "a frozenset".to_string(),
+            Type::Tuple(items) => format!("a tuple of {} items", items.len()),
+            Type::VarTuple(_) => "a tuple".to_string(),
+            Type::Dict(..) => "a dict".to_string(),
+            Type::Union(members) => members
+                .iter()
+                .map(|member| self.describe(py, member))
+                .collect::<Vec<_>>()
+                .join(" or "),
+            Type::Class(index) => self.classes[*index].name.clone(),
+        }
+    }
+}
+
+fn class_name(class: &Bound<'_, PyAny>) -> String {
+    class
+        .getattr("__qualname__")
+        .and_then(|name| name.extract())
+        .unwrap_or_else(|_| "object".to_string())
+}
+
+struct Compiler<'py> {
+    builtins: Bound<'py, PyModule>,
+    typing: Bound<'py, PyModule>,
+    abc: Bound<'py, PyModule>,
+    dataclasses: Bound<'py, PyModule>,
+    types: Bound<'py, PyModule>,
+    classes: Vec<Class>,
+    /// Classes compiled so far, by address, so recursive classes end
+    compiled: HashMap<usize, usize>,
+}
+
+impl<'py> Compiler<'py> {
+    fn compile(&mut self, ty: &Bound<'py, PyAny>) -> PyResult<Type> {
+        let py = ty.py();
+        let is = |module: &Bound<'py, PyModule>, name: &str, object: &Bound<'py, PyAny>| {
+            module
+                .getattr(name)
+                .is_ok_and(|attribute| attribute.is(object))
+        };
+        if ty.is_none() || ty.is(py.None().bind(py).get_type()) {
+            return Ok(Type::None);
+        }
+        if is(&self.typing, "Any", ty) || is(&self.builtins, "object", ty) {
+            return Ok(Type::Any);
+        }
+        let origin = self.typing.call_method1("get_origin", (ty,))?;
+        if !origin.is_none() {
+            let args = self
+                .typing
+                .call_method1("get_args", (ty,))?
+                .downcast_into::<PyTuple>()?;
+            let arg = |index: usize| -> PyResult<Option<Bound<'py, PyAny>>> {
+                Ok(if index < args.len() {
+                    Some(args.get_item(index)?)
+                } else {
+                    None
+                })
+            };
+            if is(&self.typing, "Union", &origin) || is(&self.types, "UnionType", &origin) {
+                let mut members = Vec::new();
+                for arg in args.iter() {
+                    // Nested unions are flattened by typing already
+                    members.push(self.compile(&arg)?);
+                }
+                return Ok(Type::Union(members));
+            }
+            if is(&self.typing, "Literal", &origin) {
+                return Ok(Type::Literal(args.iter().map(Bound::unbind).collect()));
+            }
+            if is(&self.typing, "Annotated", &origin) {
+                return self.compile(&args.get_item(0)?);
+            }
+            if is(&self.builtins, "list", &origin)
+                || is(&self.abc, "Sequence", &origin)
+                || is(&self.abc, "MutableSequence", &origin)
+            {
+                return Ok(Type::List(Box::new(self.compile_or_any(arg(0)?)?)));
+            }
+            if is(&self.builtins, "set", &origin)
+                || is(&self.abc, "Set", &origin)
+                || is(&self.abc, "MutableSet", &origin)
+                || is(&self.builtins, "frozenset", &origin)
+            {
+                return Ok(Type::Set {
+                    item: Box::new(self.compile_or_any(arg(0)?)?),
+                    frozen: is(&self.builtins, "frozenset", &origin),
+                });
+            }
+            if is(&self.builtins, "tuple", &origin) {
+                if args.len() == 2 && args.get_item(1)?.is(py.Ellipsis()) {
+                    return Ok(Type::VarTuple(Box::new(self.compile(&args.get_item(0)?)?)));
+                }
+                let items = args
+                    .iter()
+                    .map(|arg| self.compile(&arg))
+                    .collect::<PyResult<_>>()?;
+                return Ok(Type::Tuple(items));
+            }
+            if is(&self.builtins, "dict", &origin)
+                || is(&self.abc, "Mapping", &origin)
+                || is(&self.abc, "MutableMapping", &origin)
+            {
+                return Ok(Type::Dict(
+                    Box::new(self.compile_or_any(arg(0)?)?),
+                    Box::new(self.compile_or_any(arg(1)?)?),
+                ));
+            }
+            return Err(unsupported(ty));
+        }
+        let Ok(class) = ty.downcast::<PyType>() else {
+            return Err(unsupported(ty));
+        };
+        if class.is(py.get_type::<PyBool>()) {
+            return Ok(Type::Instance(class.clone().unbind()));
+        }
+        if class.is(py.get_type::<PyInt>()) {
+            return Ok(Type::Int);
+        }
+        if class.is(py.get_type::<PyFloat>()) {
+            return Ok(Type::Float);
+        }
+        if class.is(py.get_type::<PyList>()) {
+            return Ok(Type::List(Box::new(Type::Any)));
+        }
+        if class.is(py.get_type::<PyTuple>()) {
+            return Ok(Type::VarTuple(Box::new(Type::Any)));
+        }
+        if class.is(py.get_type::<PySet>()) || class.is(py.get_type::<PyFrozenSet>()) {
+            return Ok(Type::Set {
+                item: Box::new(Type::Any),
+                frozen: class.is(py.get_type::<PyFrozenSet>()),
+            });
+        }
+        if class.is(py.get_type::<PyDict>()) {
+            return Ok(Type::Dict(Box::new(Type::Any), Box::new(Type::Any)));
+        }
+        if self
+            .dataclasses
+            .call_method1("is_dataclass", (class,))?
+            .is_truthy()?
+        {
+            return self.class(class, true);
+        }
+        if self
+            .typing
+            .call_method1("is_typeddict", (class,))?
+            .is_truthy()?
+        {
+            return self.class(class, false);
+        }
+        Ok(Type::Instance(class.clone().unbind()))
+    }
+
+    fn compile_or_any(&mut self, ty: Option<Bound<'py, PyAny>>) -> PyResult<Type> {
+        match ty {
+            Some(ty) => self.compile(&ty),
+            None => Ok(Type::Any),
+        }
+    }
+
+    /// The fields of a dataclass, or of a TypedDict
+    fn class(&mut self, class: &Bound<'py, PyType>, dataclass: bool) -> PyResult<Type> {
+        let key = class.as_ptr() as usize;
+        if let Some(&index) = self.compiled.get(&key) {
+            return Ok(Type::Class(index));
+        }
+        // Registered before its fields, which may refer back to it
+        let index = self.classes.len();
+        self.compiled.insert(key, index);
+        self.classes.push(Class {
+            name: class_name(class),
+            dataclass: dataclass.then(|| class.clone().unbind()),
+            fields: Vec::new(),
+            positions: HashMap::new(),
+        });
+        let hints = self
+            .typing
+            .call_method1("get_type_hints", (class,))?
+            .downcast_into::<PyDict>()?;
+        let mut declared = Vec::new();
+        if dataclass {
+            let missing = self.dataclasses.getattr("MISSING")?;
+            for field in self
+                .dataclasses
+                .call_method1("fields", (class,))?
+                .try_iter()?
+            {
+                let field = field?;
+                if !field.getattr("init")?.is_truthy()? {
+                    continue;
+                }
+                let name: String = field.getattr("name")?.extract()?;
+                let hint = match hints.get_item(&name)? {
+                    Some(hint) => hint,
+                    None => field.getattr("type")?,
+                };
+                let required = field.getattr("default")?.is(&missing)
+                    && field.getattr("default_factory")?.is(&missing);
+                declared.push((name, hint, required));
+            }
+        } else {
+            let required_keys = class.getattr("__required_keys__")?;
+            for (name, hint) in hints.iter() {
+                let required = required_keys.contains(&name)?;
+                declared.push((name.extract()?, hint, required));
+            }
+        }
+        let mut fields = Vec::with_capacity(declared.len());
+        let mut positions = HashMap::with_capacity(declared.len());
+        for (name, hint, required) in declared {
+            positions.insert(name.clone(), fields.len());
+            fields.push(Field {
+                name,
+                ty: self.compile(&hint)?,
+                required,
+            });
+        }
+        let compiled = &mut self.classes[index];
+        compiled.fields = fields;
+        compiled.positions = positions;
+        Ok(Type::Class(index))
+    }
+}
+
+fn unsupported(ty: &Bound<'_, PyAny>) -> PyErr {
+    let repr = ty
+        .repr()
+        .map_or_else(|_| "?".to_string(), |repr| repr.to_string());
+    PyTypeError::new_err(format!("can't decode YAML into {repr}"))
+}
+
+/// The single document `loader` parses, decoded into the type of `schema`.
+/// An empty stream decodes as None.
+pub fn load_into(py: Python, loader: &mut RSafeLoader, schema: &Schema) -> PyResult<Py<PyAny>> {
+    let mut walk = Walk {
+        loader,
+        schema,
+        path: Vec::new(),
+    };
+    let value = if walk.loader.begin_document(py)? {
+        let value = walk.decode(py, &schema.root)?;
+        walk.loader.end_document(py)?;
+        value
+    } else {
+        walk.convert(py, py.None().bind(py), &schema.root, None)?
+    };
+    if walk.loader.check_node(py)? {
+        let mark = walk.loader.peek_event(py)?.start_mark;
+        return Err(exception::marked_error::<exception::ComposerError>(
+            py,
+            Some("expected a single document in the stream".to_string()),
+            None,
+            "but found another document".to_string(),
+            Some(walk.loader.mark(mark)),
+        ));
+    }
+    Ok(value)
+}
+
+/// A step from a value into one inside it, for the location errors give
+enum Segment {
+    Field(String),
+    Index(usize),
+    Key(String),
+}
+
+/// What kind of node the walk is at
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A mapping to walk
+    Mapping,
+    /// A sequence to walk
+    Sequence,
+    /// A scalar, an alias, or a collection to construct before converting it
+    Other,
+}
+
+impl Type {
+    /// Whether collections of `kind` are walked to decode this type
+    fn walks(&self, kind: Kind) -> bool {
+        match kind {
+            Kind::Mapping => matches!(self, Type::Dict(..) | Type::Class(_)),
+            Kind::Sequence => matches!(
+                self,
+                Type::List(_) | Type::Set { .. } | Type::Tuple(_) | Type::VarTuple(_)
+            ),
+            Kind::Other => false,
+        }
+    }
+}
+
+struct Walk<'a> {
+    loader: &'a mut RSafeLoader,
+    schema: &'a Schema,
+    path: Vec<Segment>,
+}
+
+impl Walk<'_> {
+    /// Decode the node the walk is at into `ty`, moving past it
+    fn decode(&mut self, py: Python, ty: &Type) -> PyResult<Py<PyAny>> {
+        let event = self.loader.peek_event(py)?;
+        let start_mark = event.start_mark;
+        let kind = match &event.data {
+            EventData::MappingStart {
+                anchor: None,
+                tag: None,
+                ..
+            } => Kind::Mapping,
+            EventData::SequenceStart {
+                anchor: None,
+                tag: None,
+                ..
+            } => Kind::Sequence,
+            _ => Kind::Other,
+        };
+        // A union is decoded as its first member walking such a collection
+        let walked = match ty {
+            Type::Union(members) => members.iter().find(|member| member.walks(kind)),
+            ty => Some(ty).filter(|ty| ty.walks(kind)),
+        };
+        let mark = self.loader.mark(start_mark);
+        match walked {
+            Some(Type::Dict(key_type, value_type)) => self.dict(py, key_type, value_type, mark),
+            Some(Type::Class(index)) => self.class(py, *index, mark),
+            Some(ty) => self.sequence(py, ty, mark),
+            None => {
+                let value = self.loader.construct_node(py)?;
+                self.convert(py, value.bind(py), ty, Some(mark))
+            }
+        }
+    }
+
+    /// Decode the mapping the walk is at into a dict
+    fn dict(
+        &mut self,
+        py: Python,
+        key_type: &Type,
+        value_type: &Type,
+        mark: PyMark,
+    ) -> PyResult<Py<PyAny>> {
+        self.loader.skip_event();
+        let dict = PyDict::new(py);
+        let mut merged = Vec::new();
+        while let Some(key_mark) = self.next_key(py, &mut merged)? {
+            let key = self.loader.construct_node(py)?;
+            let key = self.convert(py, key.bind(py), key_type, Some(key_mark))?;
+            self.path
+                .push(Segment::Key(key.bind(py).repr()?.to_string()));
+            let value = self.decode(py, value_type)?;
+            self.path.pop();
+            dict.set_item(key, value)?;
+        }
+        for source in merged {
+            for (key, value) in source.iter() {
+                let key = self.convert(py, &key, key_type, Some(mark.clone()))?;
+                if !dict.contains(&key)? {
+                    self.path
+                        .push(Segment::Key(key.bind(py).repr()?.to_string()));
+                    let value = self.convert(py, &value, value_type, Some(mark.clone()))?;
+                    self.path.pop();
+                    dict.set_item(key, value)?;
+                }
+            }
+        }
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Decode the mapping the walk is at into the fields of a class
+    fn class(&mut self, py: Python, index: usize, mark: PyMark) -> PyResult<Py<PyAny>> {
+        let schema = self.schema;
+        let class = &schema.classes[index];
+        self.loader.skip_event();
+        let values = PyDict::new(py);
+        let mut merged = Vec::new();
+        while let Some(key_mark) = self.next_key(py, &mut merged)? {
+            let key = self.loader.construct_node(py)?;
+            let field = self.field(py, class, key.bind(py), Some(key_mark))?;
+            self.path.push(Segment::Field(field.name.clone()));
+            let value = self.decode(py, &field.ty)?;
+            self.path.pop();
+            values.set_item(&field.name, value)?;
+        }
+        for source in merged {
+            self.fields(py, class, &source, &values, Some(mark.clone()))?;
+        }
+        self.finish(py, class, values, Some(mark))
+    }
+
+    /// Decode the sequence the walk is at into `ty`
+    fn sequence(&mut self, py: Python, ty: &Type, mark: PyMark) -> PyResult<Py<PyAny>> {
+        self.loader.skip_event();
+        let mut items = Vec::new();
+        loop {
+            let event = self.loader.peek_event(py)?;
+            if matches!(event.data, EventData::SequenceEnd) {
+                self.loader.skip_event();
+                break;
+            }
+            let item_mark = event.start_mark;
+            let Some(item_type) = item_type(ty, items.len()) else {
+                let mark = self.loader.mark(item_mark);
+                let problem = format!(
+                    "expected {}, but found more items",
+                    self.schema.describe(py, ty)
+                );
+                return Err(self.error(py, problem, Some(mark)));
+            };
+            self.path.push(Segment::Index(items.len()));
+            items.push(self.decode(py, item_type)?);
+            self.path.pop();
+        }
+        self.collect(py, ty, items, Some(mark))
+    }
+
+    /// Move to the next key of the mapping the walk is in, returning its
+    /// mark; None at the end of the mapping. The values of merge keys are
+    /// constructed into `merged` on the way.
+    fn next_key<'py>(
+        &mut self,
+        py: Python<'py>,
+        merged: &mut Vec<Bound<'py, PyDict>>,
+    ) -> PyResult<Option<PyMark>> {
+        loop {
+            let event = self.loader.peek_event(py)?;
+            let mark = event.start_mark;
+            if matches!(event.data, EventData::MappingEnd) {
+                self.loader.skip_event();
+                return Ok(None);
+            }
+            if !self.loader.at_merge_key() {
+                return Ok(Some(self.loader.mark(mark)));
+            }
+            self.loader.skip_event();
+            let source = self.loader.construct_node(py)?.into_bound(py);
+            // The first mapping merged takes precedence
+            let sources = match source.downcast::<PyList>() {
+                Ok(list) => list.iter().collect(),
+                Err(_) => vec![source],
+            };
+            merged.extend(
+                sources
+                    .into_iter()
+                    .filter_map(|source| source.downcast_into::<PyDict>().ok()),
+            );
+        }
+    }
+
+    /// Convert an already constructed `value` into `ty`
+    fn convert(
+        &mut self,
+        py: Python,
+        value: &Bound<'_, PyAny>,
+        ty: &Type,
+        mark: Option<PyMark>,
+    ) -> PyResult<Py<PyAny>> {
+        let matches = match ty {
+            Type::Any => true,
+            Type::None => value.is_none(),
+            Type::Int => value.is_instance_of::<PyInt>() && !value.is_instance_of::<PyBool>(),
+            Type::Float if value.is_instance_of::<PyInt>() && !value.is_instance_of::<PyBool>() => {
+                return Ok(PyFloat::new(py, value.extract()?).into_any().unbind());
+            }
+            Type::Float => value.is_instance_of::<PyFloat>(),
+            Type::Instance(class) => value.is_instance(class.bind(py))?,
+            Type::Literal(values) => {
+                let mut found = false;
+                for literal in values {
+                    let literal = literal.bind(py);
+                    if literal.get_type().is(value.get_type()) && literal.eq(value)? {
+                        found = true;
+                        break;
+                    }
+                }
+                found
+            }
+            Type::List(_) | Type::Tuple(_) | Type::VarTuple(_) | Type::Set { .. }
+                if value.is_instance_of::<PyList>()
+                    || value.is_instance_of::<PyTuple>()
+                    || (matches!(ty, Type::Set { .. })
+                        && (value.is_instance_of::<PySet>()
+                            || value.is_instance_of::<PyFrozenSet>())) =>
+            {
+                let mut items = Vec::new();
+                for (index, item) in value.try_iter()?.enumerate() {
+                    let Some(item_type) = item_type(ty, index) else {
+                        let problem = format!(
+                            "expected {}, but found more items",
+                            self.schema.describe(py, ty)
+                        );
+                        return Err(self.error(py, problem, mark));
+                    };
+                    self.path.push(Segment::Index(index));
+                    items.push(self.convert(py, &item?, item_type, mark.clone())?);
+                    self.path.pop();
+                }
+                return self.collect(py, ty, items, mark);
+            }
+            Type::Dict(key_type, value_type) if value.is_instance_of::<PyDict>() => {
+                let dict = PyDict::new(py);
+                for (key, item) in value.downcast::<PyDict>()?.iter() {
+                    let key = self.convert(py, &key, key_type, mark.clone())?;
+                    self.path
+                        .push(Segment::Key(key.bind(py).repr()?.to_string()));
+                    let item = self.convert(py, &item, value_type, mark.clone())?;
+                    self.path.pop();
+                    dict.set_item(key, item)?;
+                }
+                return Ok(dict.into_any().unbind());
+            }
+            Type::Class(index) => {
+                let schema = self.schema;
+                let class = &schema.classes[*index];
+                if let Some(dataclass) = &class.dataclass
+                    && value.is_instance(dataclass.bind(py))?
+                {
+                    return Ok(value.clone().unbind());
+                }
+                let Ok(source) = value.downcast::<PyDict>() else {
+                    return Err(self.mismatch(py, value, ty, mark));
+                };
+                let values = PyDict::new(py);
+                self.fields(py, class, source, &values, mark.clone())?;
+                return self.finish(py, class, values, mark);
+            }
+            Type::Union(members) => {
+                // The first member the value converts to
+                for member in members {
+                    let depth = self.path.len();
+                    match self.convert(py, value, member, mark.clone()) {
+                        Ok(converted) => return Ok(converted),
+                        Err(_) => self.path.truncate(depth),
+                    }
+                }
+                false
+            }
+            _ => false,
+        };
+        if matches {
+            Ok(value.clone().unbind())
+        } else {
+            Err(self.mismatch(py, value, ty, mark))
+        }
+    }
+
+    /// Convert the entries of `source` missing from `values` into the fields
+    /// of `class`
+    fn fields(
+        &mut self,
+        py: Python,
+        class: &Class,
+        source: &Bound<'_, PyDict>,
+        values: &Bound<'_, PyDict>,
+        mark: Option<PyMark>,
+    ) -> PyResult<()> {
+        for (key, value) in source.iter() {
+            let field = self.field(py, class, &key, mark.clone())?;
+            if values.contains(&field.name)? {
+                continue;
+            }
+            self.path.push(Segment::Field(field.name.clone()));
+            let value = self.convert(py, &value, &field.ty, mark.clone())?;
+            self.path.pop();
+            values.set_item(&field.name, value)?;
+        }
+        Ok(())
+    }
+
+    /// The field of `class` named `key`
+    fn field<'s>(
+        &self,
+        py: Python,
+        class: &'s Class,
+        key: &Bound<'_, PyAny>,
+        mark: Option<PyMark>,
+    ) -> PyResult<&'s Field> {
+        let Ok(name) = key.downcast::<PyString>() else {
+            let problem = format!(
+                "expected a field name, but found {}",
+                key.get_type().name()?
+            );
+            return Err(self.error(py, problem, mark));
+        };
+        let name = name.to_str()?;
+        match class.positions.get(name) {
+            Some(&position) => Ok(&class.fields[position]),
+            None => {
+                let problem = format!("found unknown field '{name}' for {}", class.name);
+                Err(self.error(py, problem, mark))
+            }
+        }
+    }
+
+    /// The instance of `class` with the field `values`, once the required
+    /// ones are all there
+    fn finish(
+        &self,
+        py: Python,
+        class: &Class,
+        values: Bound<'_, PyDict>,
+        mark: Option<PyMark>,
+    ) -> PyResult<Py<PyAny>> {
+        for field in &class.fields {
+            if field.required && !values.contains(&field.name)? {
+                let problem = format!("missing field '{}' for {}", field.name, class.name);
+                return Err(self.error(py, problem, mark));
+            }
+        }
+        match &class.dataclass {
+            Some(dataclass) => Ok(dataclass.bind(py).call((), Some(&values))?.unbind()),
+            None => Ok(values.into_any().unbind()),
+        }
+    }
+
+    /// The collection of type `ty` holding `items`
+    fn collect(
+        &self,
+        py: Python,
+        ty: &Type,
+        items: Vec<Py<PyAny>>,
+        mark: Option<PyMark>,
+    ) -> PyResult<Py<PyAny>> {
+        Ok(match ty {
+            Type::Tuple(types) if items.len() != types.len() => {
+                let problem = format!(
+                    "expected {}, but found {} items",
+                    self.schema.describe(py, ty),
+                    items.len()
+                );
+                return Err(self.error(py, problem, mark));
+            }
+            Type::Tuple(_) | Type::VarTuple(_) => PyTuple::new(py, items)?.into_any().unbind(),
+            Type::Set { frozen: false, .. } => PySet::new(py, items)?.into_any().unbind(),
+            Type::Set { frozen: true, .. } => PyFrozenSet::new(py, items)?.into_any().unbind(),
+            _ => PyList::new(py, items)?.into_any().unbind(),
+        })
+    }
+
+    fn mismatch(
+        &self,
+        py: Python,
+        value: &Bound<'_, PyAny>,
+        ty: &Type,
+        mark: Option<PyMark>,
+    ) -> PyErr {
+        let found = value
+            .get_type()
+            .name()
+            .map_or_else(|_| "object".to_string(), |name| name.to_string());
+        let problem = format!(
+            "expected {}, but found {found}",
+            self.schema.describe(py, ty)
+        );
+        self.error(py, problem, mark)
+    }
+
+    /// A constructor error at `mark`, giving where in the value it is
+    fn error(&self, py: Python, problem: String, mark: Option<PyMark>) -> PyErr {
+        let mut location = match &self.schema.root {
+            Type::Class(index) => self.schema.classes[*index].name.clone(),
+            _ if self.path.is_empty() => "the document".to_string(),
+            _ => String::new(),
+        };
+        for segment in &self.path {
+            match segment {
+                Segment::Field(name) if location.is_empty() => location.push_str(name),
+                Segment::Field(name) => location.push_str(&format!(".{name}")),
+                Segment::Index(index) => location.push_str(&format!("[{index}]")),
+                Segment::Key(key) => location.push_str(&format!("[{key}]")),
+            }
+        }
+        exception::marked_error::<exception::ConstructorError>(
+            py,
+            Some(format!("while decoding {location}")),
+            None,
+            problem,
+            mark,
+        )
+    }
+}
+
+/// The type of the item at `index` in a sequence of type `ty`; None past the
+/// end of a tuple
+fn item_type(ty: &Type, index: usize) -> Option<&Type> {
+    match ty {
+        Type::List(item) | Type::Set { item, .. } | Type::VarTuple(item) => Some(item),
+        Type::Tuple(items) => items.get(index),
+        _ => None,
+    }
+}
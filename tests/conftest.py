@@ -1,8 +1,18 @@
 import pytest
 import io
 
+from ryaml import _ryaml
+
 @pytest.fixture
 def yaml_file(tmp_path):
     with open(tmp_path / 'testfile.yaml', 'w+', encoding='utf8') as y:
         assert isinstance(y, io.TextIOBase)
         yield y
+
+
+@pytest.fixture
+def restore_registries():
+    """Undo the constructors, representers and resolvers a test registers"""
+    saved = _ryaml._state.save_registries()
+    yield
+    _ryaml._state.restore_registries(saved)
@@ -104,7 +104,7 @@ KNOWN_BAD = [
     "U99R",
 ]
 
-TIME_PARSE_TEST = ["U9NS"]
+TIME_PARSE_TEST = ["U9NS", "UGM3", "RZT7"]
 
 def _get_yamls():
     valid = []
@@ -0,0 +1,50 @@
+import pytest
+import yaml
+
+import ryaml
+from ryaml.compat import RSafeDumper
+
+
+def test_shared_objects_aliased_by_default():
+    shared = [1, 2]
+    assert ryaml.dumps({'a': shared, 'b': shared}) == 'a: &id001\n- 1\n- 2\nb: *id001\n'
+
+
+def test_aliases_false_expands_shared_objects():
+    shared = [1, 2]
+    data = {'a': shared, 'b': {'c': shared}}
+    assert ryaml.dumps(data, aliases=False) == 'a:\n- 1\n- 2\nb:\n  c:\n  - 1\n  - 2\n'
+    assert ryaml.loads(ryaml.dumps(data, aliases=False)) == data
+
+
+def test_aliases_false_dump_plan():
+    shared = {'x': 1}
+    plan = ryaml.dumps([shared, shared], aliases=False, dry_run=True)
+    assert plan.anchors == []
+    assert plan.mappings == 2
+
+
+def test_dumper_aliases_false():
+    shared = [1]
+    assert yaml.dump([shared, shared], Dumper=RSafeDumper, aliases=False) == '- - 1\n- - 1\n'
+
+
+def test_recursive_data_without_aliases():
+    loop = [1]
+    loop.append(loop)
+    with pytest.raises(ryaml.RepresenterError, match=r"without aliases at 'x\.1\.1'"):
+        ryaml.dumps({'x': [0, loop]}, aliases=False)
+
+
+def test_recursive_data_with_aliases():
+    loop = {}
+    loop['self'] = loop
+    with pytest.raises(ryaml.RepresenterError, match='cannot dump recursive data'):
+        ryaml.dumps(loop)
+    with pytest.raises(ryaml.RepresenterError, match='cannot dump recursive data'):
+        yaml.dump(loop, Dumper=RSafeDumper)
+
+
+def test_repeated_objects_are_not_recursive():
+    shared = [1]
+    assert ryaml.dumps([[shared], [shared]], aliases=False) == '- - - 1\n- - - 1\n'
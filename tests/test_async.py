@@ -0,0 +1,129 @@
+import asyncio
+
+import pytest
+
+import ryaml
+
+
+class AsyncFile:
+    """An asyncio file, as aiofiles gives"""
+
+    def __init__(self, data=b'', mode='rb', name=None):
+        self.data = data
+        self.mode = mode
+        self.written = []
+        if name is not None:
+            self.name = name
+
+    async def read(self, size=-1):
+        await asyncio.sleep(0)
+        if size < 0:
+            size = len(self.data)
+        data, self.data = self.data[:size], self.data[size:]
+        return data
+
+    async def write(self, data):
+        await asyncio.sleep(0)
+        self.written.append(data)
+        return len(data)
+
+
+def stream_reader(data):
+    reader = asyncio.StreamReader()
+    reader.feed_data(data)
+    reader.feed_eof()
+    return reader
+
+
+def test_aload():
+    async def main():
+        return await ryaml.aload(AsyncFile(b'a: [1, 2]\n'))
+    assert asyncio.run(main()) == {'a': [1, 2]}
+
+
+def test_aload_text():
+    async def main():
+        return await ryaml.aload(AsyncFile('a: é\n', mode='r'))
+    assert asyncio.run(main()) == {'a': 'é'}
+
+
+def test_aload_stream_reader():
+    async def main():
+        return await ryaml.aload(stream_reader(b'- x\n- y\n'))
+    assert asyncio.run(main()) == ['x', 'y']
+
+
+def test_aload_options_and_errors():
+    async def main():
+        with pytest.raises(ryaml.ParserError) as excinfo:
+            await ryaml.aload(AsyncFile(b'a: 1\n- b\n', name='config.yaml'))
+        assert excinfo.value.problem_mark.name == 'config.yaml'
+        return await ryaml.aload(AsyncFile(b'a: 1\n'), schema='json')
+    assert asyncio.run(main()) == {'a': 1}
+
+
+def test_aload_max_bytes():
+    async def main():
+        with pytest.raises(ryaml.ReaderError):
+            await ryaml.aload(stream_reader(b'a: 1\n' * 100), max_bytes=64)
+        return await ryaml.aload(stream_reader(b'a: 1\n'), max_bytes=64)
+    assert asyncio.run(main()) == {'a': 1}
+
+
+def test_adump():
+    async def main():
+        text, binary = AsyncFile(mode='w'), AsyncFile(mode='wb')
+        await ryaml.adump(text, {'a': [1, 2]})
+        await ryaml.adump(binary, {'a': 'é'})
+        return text.written, binary.written
+    assert asyncio.run(main()) == (['a:\n- 1\n- 2\n'], ['a: é\n'.encode()])
+
+
+def test_adump_stream_writer():
+    async def main():
+        written = []
+
+        async def serve(reader, writer):
+            await ryaml.adump(writer, {'a': 1})
+            writer.close()
+
+        server = await asyncio.start_server(serve, '127.0.0.1', 0)
+        port = server.sockets[0].getsockname()[1]
+        reader, writer = await asyncio.open_connection('127.0.0.1', port)
+        written.append(await ryaml.aload(reader))
+        writer.close()
+        server.close()
+        await server.wait_closed()
+        return written
+    assert asyncio.run(main()) == [{'a': 1}]
+
+
+def test_event_loop_not_blocked(monkeypatch):
+    # Count the event loop's turns while the worker thread loads, after the
+    # read is done
+    to_thread = asyncio.to_thread
+    turns = []
+
+    async def counting_to_thread(func, /, *args, **kwargs):
+        ticks = 0
+
+        async def tick():
+            nonlocal ticks
+            while True:
+                ticks += 1
+                await asyncio.sleep(0)
+
+        ticker = asyncio.create_task(tick())
+        await asyncio.sleep(0)
+        started = ticks
+        try:
+            return await to_thread(func, *args, **kwargs)
+        finally:
+            ticker.cancel()
+            turns.append(ticks - started)
+
+    monkeypatch.setattr(asyncio, 'to_thread', counting_to_thread)
+    payload = ryaml.dumps([{'id': i, 'name': f'item {i}'} for i in range(200000)]).encode()
+    data = asyncio.run(ryaml.aload(AsyncFile(payload)))
+    assert len(data) == 200000
+    assert turns[0] > 100
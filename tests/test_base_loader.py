@@ -0,0 +1,52 @@
+import yaml
+
+import ryaml
+from ryaml.compat import RBaseLoader
+
+
+def load(s):
+    return RBaseLoader(s).get_single_data()
+
+
+def test_scalars_stay_strings():
+    doc = 'a: no\nb: 1.0\nc: 2023-01-01\nd: ~\ne: 0o17\nf: "quoted"\n'
+    assert load(doc) == {
+        'a': 'no',
+        'b': '1.0',
+        'c': '2023-01-01',
+        'd': '~',
+        'e': '0o17',
+        'f': 'quoted',
+    }
+
+
+def test_collections():
+    assert load('- [1, 2]\n- {x: 3}\n- !!set {a: ~}\n') == [['1', '2'], {'x': '3'}, {'a': '~'}]
+
+
+def test_tags_are_ignored():
+    assert load('- !!int 12\n- !!binary aGk=\n- !Custom 3\n- !!python/tuple [1]\n') == [
+        '12', 'aGk=', '3', ['1'],
+    ]
+
+
+def test_merge_keys_are_kept():
+    assert load('base: &b {x: 1}\nchild: {<<: *b}\n') == {
+        'base': {'x': '1'},
+        'child': {'<<': {'x': '1'}},
+    }
+
+
+def test_registered_constructors_are_ignored():
+    ryaml.add_constructor('!base-loader-test', lambda constructor, node: 'constructed')
+    assert load('!base-loader-test value') == 'value'
+    assert ryaml.loads('!base-loader-test value') == 'constructed'
+
+
+def test_matches_pyyaml():
+    doc = 'a: [yes, 1_000, .inf]\nb: {c: 2001-12-14t21:59:43.10-05:00}\n'
+    assert yaml.load(doc, Loader=RBaseLoader) == yaml.load(doc, Loader=yaml.BaseLoader)
+
+
+def test_schema_cannot_be_overridden():
+    assert ryaml._ryaml._RBaseLoader('[1, true]', schema='core').get_single_data() == ['1', 'true']
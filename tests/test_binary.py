@@ -0,0 +1,49 @@
+import pytest
+import yaml
+
+import ryaml
+
+
+def test_binary():
+    assert ryaml.loads('!!binary aGVsbG8=') == b'hello'
+
+
+def test_binary_multiline():
+    source = (
+        'data: !!binary |\n'
+        '  cnlhbWwgYmluYXJ5IHBheWxvYWQgc3Bhbm5pbmcgbW9yZSB0aGFuIG9uZSBsaW5lIG9mIGJhc2U2\n'
+        '  NCBvdXRwdXQhIQ==\n'
+    )
+    assert ryaml.loads(source) == {
+        'data': b'ryaml binary payload spanning more than one line of base64 output!!'
+    }
+
+
+def test_binary_round_trip():
+    data = {'blob': bytes(range(256))}
+    assert ryaml.loads(ryaml.dumps(data)) == data
+
+
+def test_binary_invalid():
+    with pytest.raises(ryaml.ConstructorError):
+        ryaml.loads('!!binary not*base64')
+
+
+def test_dumps_binary():
+    assert ryaml.dumps({'data': b'\x00\x01'}) == 'data: !!binary |\n  AAE=\n'
+    assert ryaml.dumps(b'hi') == yaml.safe_dump(b'hi')
+
+
+@pytest.mark.parametrize('value', [bytearray(b'\x00\x01'), memoryview(b'\x00\x01')])
+def test_dumps_bytes_like(value):
+    assert ryaml.dumps({'data': value}) == 'data: !!binary |\n  AAE=\n'
+    assert ryaml.loads(ryaml.dumps(value)) == b'\x00\x01'
+
+
+def test_dumps_strided_memoryview():
+    view = memoryview(b'abcdef')[::2]
+    assert ryaml.loads(ryaml.dumps(view)) == b'ace'
+
+
+def test_dumps_many_binary():
+    assert ryaml.dumps_many([b'a', b'b']) == ['!!binary |\n  YQ==\n', '!!binary |\n  Yg==\n']
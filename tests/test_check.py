@@ -0,0 +1,96 @@
+import pytest
+
+import ryaml
+
+
+def test_valid():
+    assert ryaml.check('a: [1, 2]\nb: &x {c: 3}\nd: *x\n') == []
+
+
+def test_syntax_error():
+    [diagnostic] = ryaml.check('a: 1\nb: [2, 3\n', name='config.yaml')
+    assert diagnostic.severity == 'error'
+    assert diagnostic.code == 'syntax-error'
+    assert "did not find expected ',' or ']'" in diagnostic.message
+    assert diagnostic.mark.name == 'config.yaml'
+    assert str(diagnostic).startswith('config.yaml:3:1: error[syntax-error]: ')
+
+
+def test_undefined_aliases():
+    diagnostics = ryaml.check('a: *x\nb: &x 1\nc: *x\nd: *y\n')
+    assert [d.code for d in diagnostics] == ['undefined-alias', 'undefined-alias']
+    assert [d.message for d in diagnostics] == ["found undefined alias 'x'", "found undefined alias 'y'"]
+    assert [d.mark.line for d in diagnostics] == [0, 3]
+    assert diagnostics[1].end_mark.column == 5
+
+
+def test_anchors_are_per_document():
+    [diagnostic] = ryaml.check('--- &a 1\n--- *a\n')
+    assert diagnostic.mark.line == 1
+
+
+def test_keeps_problems_before_a_syntax_error():
+    diagnostics = ryaml.check('a: *missing\nb: "unclosed\n')
+    assert [d.code for d in diagnostics] == ['undefined-alias', 'syntax-error']
+
+
+def test_constructs_nothing():
+    # Tags no constructor knows and values that would fail to load are fine
+    assert ryaml.check('a: !unknown x\nb: !!int nope\n') == []
+
+
+def test_check_file(tmp_path):
+    path = tmp_path / 'bad.yaml'
+    path.write_text('a: [\n')
+    [diagnostic] = ryaml.check_file(path)
+    assert diagnostic.mark.name == str(path)
+    assert ryaml.check_file(str(path), name='other.yaml')[0].mark.name == 'other.yaml'
+
+
+def test_check_file_undecodable(tmp_path):
+    path = tmp_path / 'binary.yaml'
+    path.write_bytes(b'a: \xff\xfe\xfd\n')
+    [diagnostic] = ryaml.check_file(path)
+    assert diagnostic.code == 'reader-error'
+    assert diagnostic.mark is None
+
+
+def test_check_file_missing(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        ryaml.check_file(tmp_path / 'missing.yaml')
+
+
+def test_stops_at_first_syntax_error():
+    assert len(ryaml.check('a: b: c\nd: e: f\n')) == 1
+
+
+def test_recover_at_top_level_keys():
+    source = 'a: b: c\nd: 1\ne: x: y\nf: *missing\n'
+    diagnostics = ryaml.check(source, recover=True)
+    assert [(d.code, d.mark.line) for d in diagnostics] == [
+        ('syntax-error', 0),
+        ('syntax-error', 2),
+        ('undefined-alias', 3),
+    ]
+    assert diagnostics[2].mark.index == source.index('*missing')
+
+
+def test_recover_at_documents():
+    source = '--- &a 1\n--- x: y: z\n--- *a\n'
+    diagnostics = ryaml.check(source, recover=True)
+    # The alias is in a new document, where the anchor isn't defined
+    assert [(d.code, d.mark.line) for d in diagnostics] == [
+        ('syntax-error', 1),
+        ('undefined-alias', 2),
+    ]
+
+
+def test_recover_keeps_anchors_of_the_document():
+    diagnostics = ryaml.check('a: &x 1\nb: c: d\nc: *x\n', recover=True)
+    assert [d.code for d in diagnostics] == ['syntax-error']
+
+
+def test_recover_check_file(tmp_path):
+    path = tmp_path / 'bad.yaml'
+    path.write_text('a: b: c\nb: ok\nc: d: e\n')
+    assert len(ryaml.check_file(path, recover=True)) == 2
@@ -0,0 +1,39 @@
+import ipaddress
+import pathlib
+import uuid
+
+import pytest
+
+import ryaml
+
+
+def test_coerce_unknown_str():
+    data = {
+        pathlib.PurePosixPath('/srv'): pathlib.PurePosixPath('/srv/data'),
+        'id': uuid.UUID(int=2),
+        'ip': ipaddress.ip_address('10.0.0.1'),
+    }
+    assert ryaml.dumps(data, coerce_unknown='str') == (
+        '/srv: /srv/data\nid: 00000000-0000-0000-0000-000000000002\nip: 10.0.0.1\n'
+    )
+
+
+def test_coerce_unknown_repr():
+    path = pathlib.PurePosixPath('/srv')
+    assert ryaml.loads(ryaml.dumps([path], coerce_unknown='repr')) == [repr(path)]
+
+
+def test_coerce_unknown_error_is_default():
+    with pytest.raises(ryaml.RepresenterError, match='cannot represent an object'):
+        ryaml.dumps(pathlib.PurePosixPath('/srv'))
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(pathlib.PurePosixPath('/srv'), coerce_unknown='error')
+
+
+def test_default_takes_precedence():
+    assert ryaml.dumps(object(), default=lambda obj: 'from default', coerce_unknown='str') == 'from default\n'
+
+
+def test_unknown_mode():
+    with pytest.raises(ValueError, match="unsupported coerce_unknown mode 'json'"):
+        ryaml.dumps(1, coerce_unknown='json')
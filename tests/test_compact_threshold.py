@@ -0,0 +1,59 @@
+import pytest
+
+import ryaml
+from ryaml import compat
+from ryaml.compat import RSafeDumper
+
+
+def test_block_style_by_default():
+    assert ryaml.dumps({'ports': [80, 443]}) == 'ports:\n- 80\n- 443\n'
+
+
+def test_small_collections_in_flow_style():
+    data = {'ports': [80, 443], 'labels': {'app': 'web'}, 'hosts': ['a', 'b', 'c', 'd']}
+    assert ryaml.dumps(data, compact_threshold=4) == (
+        'ports: [80, 443]\nlabels: {app: web}\nhosts:\n- a\n- b\n- c\n- d\n'
+    )
+
+
+def test_matrix():
+    data = {'m': [[1, 0, 0], [0, 1, 0], [0, 0, 1]]}
+    assert ryaml.dumps(data, compact_threshold=4) == 'm:\n- [1, 0, 0]\n- [0, 1, 0]\n- [0, 0, 1]\n'
+
+
+def test_root_stays_in_block_style():
+    assert ryaml.dumps({'a': 1}, compact_threshold=4) == 'a: 1\n'
+    assert ryaml.dumps([1, 2], compact_threshold=4) == '- 1\n- 2\n'
+
+
+def test_long_or_styled_scalars_stay_in_block_style():
+    long = 'x' * 21
+    assert ryaml.dumps({'a': ['x' * 20]}, compact_threshold=4) == f"a: [{'x' * 20}]\n"
+    assert ryaml.dumps({'a': [long]}, compact_threshold=4) == f'a:\n- {long}\n'
+    assert ryaml.dumps({'a': ['two\nlines']}, compact_threshold=4).startswith('a:\n- ')
+    assert ryaml.dumps({'a': ['b']}, compact_threshold=4, default_style='double') == '"a":\n- "b"\n'
+
+
+def test_nested_collections_stay_in_block_style():
+    assert ryaml.dumps({'a': [[1]]}, compact_threshold=4) == 'a:\n- [1]\n'
+    assert ryaml.dumps({'a': {'b': {'c': 1}}}, compact_threshold=4) == 'a:\n  b: {c: 1}\n'
+
+
+def test_round_trip():
+    data = {'a': [1, 'b', None, 2.5], 'c': {'d': True}, 'e': [], 'f': {}}
+    assert ryaml.loads(ryaml.dumps(data, compact_threshold=8)) == data
+
+
+def test_from_json():
+    assert ryaml.from_json('{"a": [1, 2], "b": [1, 2, 3]}', compact_threshold=3) == (
+        'a: [1, 2]\nb:\n- 1\n- 2\n- 3\n'
+    )
+
+
+def test_compat_dumper():
+    assert compat.dump({'a': [1, 2]}, Dumper=RSafeDumper, compact_threshold=4) == 'a: [1, 2]\n'
+
+
+def test_invalid():
+    with pytest.raises(TypeError):
+        ryaml.dumps({'a': [1]}, compact_threshold='4')
@@ -0,0 +1,84 @@
+import io
+
+import pytest
+import yaml
+
+import ryaml
+import ryaml.compat as compat
+
+
+DOC = 'b: 1\na: [x, 2.5]\n'
+
+
+def test_load_text_and_bytes():
+    assert compat.load(DOC) == {'b': 1, 'a': ['x', 2.5]}
+    assert compat.load(DOC.encode('utf-16')) == {'b': 1, 'a': ['x', 2.5]}
+    assert compat.safe_load('{"json": true}') == {'json': True}
+    assert compat.safe_load('') is None
+
+
+def test_load_file(tmp_path):
+    path = tmp_path / 'doc.yaml'
+    path.write_text('a: [1\n')
+    with open(path) as f, pytest.raises(compat.YAMLError) as info:
+        compat.safe_load(f)
+    assert info.value.problem_mark.name == str(path)
+    with open(path, 'rb') as f, pytest.raises(compat.YAMLError):
+        compat.load(f)
+
+
+@pytest.mark.parametrize('loader', [compat.SafeLoader, compat.RSafeLoader, yaml.SafeLoader])
+def test_load_with_loader(loader):
+    assert compat.load(io.StringIO(DOC), Loader=loader) == yaml.safe_load(DOC)
+
+
+def test_loaders():
+    assert compat.load('a: 1', Loader=compat.BaseLoader) == {'a': '1'}
+    assert compat.full_load('!!python/tuple [1, 2]') == (1, 2)
+    assert compat.unsafe_load('!!python/name:builtins.len') is len
+
+
+def test_loader_subclass():
+    class PointLoader(compat.SafeLoader):
+        pass
+
+    PointLoader.add_constructor('!compat-api-point', lambda loader, node: tuple(loader.construct_sequence(node)))
+    assert compat.load('!compat-api-point [1, 2]', PointLoader) == (1, 2)
+
+
+def test_load_all():
+    assert compat.load_all('- 1\n---\n- 2\n') == [[1], [2]]
+    assert compat.safe_load_all(io.StringIO('a\n---\nb\n')) == ['a', 'b']
+    assert compat.load_all('1\n---\n2\n', Loader=yaml.SafeLoader) == [1, 2]
+    assert compat.load_all('') == []
+
+
+def test_dump_returns_text():
+    data = {'b': 1, 'a': [1, 'x']}
+    assert compat.dump(data) == yaml.safe_dump(data)
+    assert compat.safe_dump(data, sort_keys=False) == 'b: 1\na:\n- 1\n- x\n'
+    assert compat.dump(data, default_flow_style=True) == yaml.safe_dump(data, default_flow_style=True)
+    assert compat.dump(data, encoding='utf-8') == yaml.safe_dump(data, encoding='utf-8')
+
+
+def test_dump_to_stream():
+    stream = io.StringIO()
+    assert compat.dump({'a': 1}, stream, explicit_start=True) is None
+    assert stream.getvalue() == '---\na: 1\n'
+
+
+def test_dump_all():
+    assert compat.dump_all([1, {'a': 2}]) == yaml.safe_dump_all([1, {'a': 2}])
+    stream = io.StringIO()
+    compat.safe_dump_all([[1], [2]], stream, indent=4)
+    assert compat.safe_load_all(stream.getvalue()) == [[1], [2]]
+
+
+def test_dump_with_pyyaml_dumper():
+    assert compat.dump((1, 2), Dumper=yaml.Dumper) == yaml.dump((1, 2))
+
+
+def test_ryaml_dump_keeps_its_signature(tmp_path):
+    path = tmp_path / 'out.yaml'
+    ryaml.dump(path, {'a': 1})
+    assert compat.load(path.read_text()) == {'a': 1}
@@ -0,0 +1,87 @@
+import io
+import subprocess
+import sys
+
+import pytest
+
+import ryaml
+import ryaml.compat as compat
+
+
+@pytest.fixture
+def yaml(monkeypatch):
+    monkeypatch.setitem(sys.modules, 'yaml', compat)
+    import yaml
+    return yaml
+
+
+def test_stands_in_for_pyyaml(yaml):
+    assert yaml is compat
+    assert yaml.safe_load('a: [1, x]\n') == {'a': [1, 'x']}
+    assert yaml.safe_load_all('--- 1\n--- 2\n') == [1, 2]
+    assert yaml.safe_dump({'b': 1, 'a': 2}) == 'a: 2\nb: 1\n'
+    with pytest.raises(yaml.YAMLError):
+        yaml.safe_load('[')
+
+
+def test_libyaml_classes(yaml):
+    assert yaml.__with_libyaml__
+    loader = getattr(yaml, 'CSafeLoader', yaml.SafeLoader)
+    assert yaml.load('a: 1', Loader=loader) == {'a': 1}
+    assert yaml.dump({'a': 1}, Dumper=yaml.CSafeDumper) == 'a: 1\n'
+
+
+def test_add_constructor(yaml):
+    yaml.add_constructor('!dropin-point', lambda loader, node: tuple(loader.construct_sequence(node)))
+    assert yaml.safe_load('!dropin-point [1, 2]') == (1, 2)
+
+
+def test_add_representer(yaml):
+    class Money:
+        def __init__(self, amount):
+            self.amount = amount
+
+    def represent_money(dumper, data):
+        return dumper.represent_mapping('!dropin-money', {'amount': data.amount})
+
+    yaml.add_representer(Money, represent_money)
+    assert yaml.safe_dump({'price': Money(5)}) == 'price: !dropin-money\n  amount: 5\n'
+
+
+def test_compose_and_serialize(yaml):
+    node = yaml.compose('a: [1, 2]\n')
+    assert isinstance(node, yaml.MappingNode)
+    assert yaml.serialize(node) == 'a: [1, 2]\n'
+    assert ryaml.loads_all(yaml.serialize_all(yaml.compose_all('--- 1\n--- 2\n'))) == [1, 2]
+    stream = io.StringIO()
+    assert yaml.serialize(yaml.compose('x'), stream) is None
+    assert stream.getvalue() == 'x\n'
+
+
+def test_parse_and_emit(yaml):
+    events = yaml.parse(io.StringIO('a: 1\n'))
+    assert isinstance(events[0], yaml.StreamStartEvent)
+    assert yaml.safe_load(yaml.emit(events)) == {'a': 1}
+
+
+def test_yaml_object(yaml):
+    class Monster(yaml.YAMLObject):
+        yaml_tag = '!dropin-monster'
+
+        def __init__(self, name):
+            self.name = name
+
+    monster = yaml.safe_load('!dropin-monster {name: Dragon}')
+    assert isinstance(monster, Monster) and monster.name == 'Dragon'
+
+
+def test_without_pyyaml():
+    code = (
+        'import sys; sys.modules["yaml"] = None\n'
+        'import ryaml.compat as yaml\n'
+        'sys.modules["yaml"] = yaml\n'
+        'import yaml\n'
+        'print(yaml.safe_load("a: [1]"), yaml.load("b", Loader=yaml.SafeLoader))\n'
+    )
+    result = subprocess.run([sys.executable, '-c', code], capture_output=True, text=True, check=True)
+    assert result.stdout == "{'a': [1]} b\n"
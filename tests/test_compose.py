@@ -0,0 +1,65 @@
+import ryaml
+from ryaml._ryaml import ScalarNode, SequenceNode, MappingNode
+
+
+def test_compose_empty():
+    assert ryaml.compose('') is None
+
+def test_compose_scalar_tags():
+    node = ryaml.compose('[1, 1.5, "1", yes, ~, text]')
+    assert isinstance(node, SequenceNode)
+    assert node.flow_style is True
+    assert [item.tag for item in node.value] == [
+        'tag:yaml.org,2002:int',
+        'tag:yaml.org,2002:float',
+        'tag:yaml.org,2002:str',
+        'tag:yaml.org,2002:bool',
+        'tag:yaml.org,2002:null',
+        'tag:yaml.org,2002:str',
+    ]
+    assert node.value[2].style == '"'
+
+def test_compose_mapping():
+    node = ryaml.compose('key: !custom value\n')
+    assert isinstance(node, MappingNode)
+    assert node.tag == 'tag:yaml.org,2002:map'
+    [(key, value)] = node.value
+    assert isinstance(key, ScalarNode)
+    assert key.value == 'key'
+    assert value.tag == '!custom'
+
+def test_compose_alias_shares_node():
+    node = ryaml.compose('a: &x [1]\nb: *x\n')
+    (_, first), (_, second) = node.value
+    assert first is second
+
+def test_compose_all():
+    nodes = ryaml.compose_all('--- 1\n--- [2]\n')
+    assert [n.id for n in nodes] == ['scalar', 'sequence']
+
+def test_compose_expands_tag_handles():
+    node = ryaml.compose('%TAG !k8s! tag:k8s.io,2024:\n--- !k8s!Deployment {a: !!str 1}\n')
+    assert node.tag == 'tag:k8s.io,2024:Deployment'
+    [(_, value)] = node.value
+    assert value.tag == 'tag:yaml.org,2002:str'
+
+def test_loader_tag_handles():
+    from ryaml.compat import RSafeLoader
+    loader = RSafeLoader('%TAG !e! tag:example.com,2000:\n--- !e!thing x\n')
+    assert loader.get_single_node().tag == 'tag:example.com,2000:thing'
+    assert loader.tag_handles == {
+        '!': '!',
+        '!!': 'tag:yaml.org,2002:',
+        '!e!': 'tag:example.com,2000:',
+    }
+
+def test_compose_marks():
+    node = ryaml.compose('a: [1, 2]\nb: x\n')
+    assert (node.start_mark.line, node.start_mark.column) == (0, 0)
+    (_, seq), (key, scalar) = node.value
+    assert (seq.start_mark.line, seq.start_mark.column) == (0, 3)
+    assert (seq.end_mark.line, seq.end_mark.column) == (0, 9)
+    assert (key.start_mark.line, key.start_mark.column) == (1, 0)
+    assert (scalar.start_mark.line, scalar.start_mark.column) == (1, 3)
+    assert (scalar.end_mark.line, scalar.end_mark.column) == (1, 4)
+    assert node.end_mark.index >= scalar.end_mark.index
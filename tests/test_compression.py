@@ -0,0 +1,75 @@
+import gzip
+import lzma
+
+import pytest
+
+import ryaml
+
+DOCUMENT = b"name: ryaml\nitems: [1, 2, 3]\n"
+EXPECTED = {'name': 'ryaml', 'items': [1, 2, 3]}
+
+needs_gzip = pytest.mark.skipif(not ryaml.available('gzip'), reason="built without gzip")
+needs_xz = pytest.mark.skipif(not ryaml.available('xz'), reason="built without xz")
+
+
+@needs_gzip
+def test_loads_gzip_bytes():
+    assert ryaml.loads(gzip.compress(DOCUMENT)) == EXPECTED
+
+
+@needs_gzip
+def test_load_gzip_path(tmp_path):
+    path = tmp_path / 'data.yaml.gz'
+    path.write_bytes(gzip.compress(DOCUMENT))
+    assert ryaml.load(path) == EXPECTED
+
+
+@needs_gzip
+def test_load_gzip_binary_stream(tmp_path):
+    path = tmp_path / 'data.yaml.gz'
+    path.write_bytes(gzip.compress(DOCUMENT))
+    with open(path, 'rb') as f:
+        assert ryaml.load(f) == EXPECTED
+
+
+@needs_gzip
+def test_gzip_utf16_text():
+    text = "name: ☃\n".encode('utf-16')
+    assert ryaml.loads(gzip.compress(text)) == {'name': '☃'}
+
+
+@needs_gzip
+def test_corrupt_gzip():
+    data = gzip.compress(DOCUMENT)[:-8]
+    with pytest.raises(ryaml.ReaderError, match='invalid gzip data'):
+        ryaml.loads(data)
+
+
+@needs_xz
+def test_loads_xz_bytes():
+    assert ryaml.loads(lzma.compress(DOCUMENT)) == EXPECTED
+
+
+@needs_xz
+def test_load_files_xz(tmp_path):
+    path = tmp_path / 'data.yaml.xz'
+    path.write_bytes(lzma.compress(DOCUMENT))
+    assert ryaml.load_files([path]) == {path: EXPECTED}
+
+
+@pytest.mark.skipif(ryaml.available('gzip'), reason="built with gzip")
+def test_gzip_without_feature():
+    with pytest.raises(ryaml.ReaderError, match="needs ryaml built with its 'gzip' feature"):
+        ryaml.loads(gzip.compress(DOCUMENT))
+
+
+def test_compressions_are_features():
+    for name in ('gzip', 'zstd', 'xz'):
+        assert name in ryaml.features.features()
+        assert ryaml.available(name) == (name in ryaml.COMPRESSIONS)
+
+
+@pytest.mark.skipif(ryaml.available('zstd'), reason="built with zstd")
+def test_require_missing_compression():
+    with pytest.raises(ryaml.MissingFeatureError, match='--features zstd'):
+        ryaml.require('zstd')
@@ -0,0 +1,62 @@
+import datetime
+
+import pytest
+import yaml
+
+import ryaml
+
+DOC = '''\
+name: app
+ports: [80, 443]
+started: 2024-01-02
+blob: !!binary aGk=
+base: &base {a: 1}
+merged:
+  <<: *base
+  b: 2
+'''
+
+
+def test_construct_pyyaml_nodes():
+    node = yaml.compose(DOC, Loader=yaml.SafeLoader)
+    data = ryaml.construct(node)
+    assert data == yaml.safe_load(DOC)
+    assert data['started'] == datetime.date(2024, 1, 2)
+    assert data['merged'] == {'a': 1, 'b': 2}
+
+
+def test_construct_native_nodes():
+    assert ryaml.construct(ryaml.compose(DOC)) == ryaml.loads(DOC)
+
+
+def test_shared_and_recursive_nodes_stay_shared():
+    data = ryaml.construct(yaml.compose('a: &x [1]\nb: *x\n', Loader=yaml.SafeLoader))
+    assert data['a'] is data['b']
+    looped = ryaml.construct(yaml.compose('&a [*a]', Loader=yaml.SafeLoader))
+    assert looped[0] is looped
+
+
+def test_registered_constructors_apply():
+    ryaml.add_constructor('!Upper', lambda loader, node: loader.construct_scalar(node).upper())
+    node = yaml.compose('- !Upper abc\n', Loader=yaml.SafeLoader)
+    assert ryaml.construct(node) == ['ABC']
+
+
+def test_not_a_node():
+    with pytest.raises(TypeError, match='expected a YAML node, but found dict'):
+        ryaml.construct({'a': 1})
+
+
+def test_large_collections():
+    items = list(range(1000))
+    entries = {f'k{i}': i for i in range(1000)}
+    text = ryaml.dumps({'items': items, 'entries': entries})
+    assert ryaml.loads(text) == {'items': items, 'entries': entries}
+    assert ryaml.construct(ryaml.compose(text)) == {'items': items, 'entries': entries}
+
+
+def test_recursive_lists_load():
+    looped = ryaml.loads('&a [1, *a, [*a]]')
+    assert looped[0] == 1
+    assert looped[1] is looped
+    assert looped[2][0] is looped
@@ -0,0 +1,52 @@
+import pytest
+
+import ryaml
+from ryaml._ryaml import Mark
+
+
+def test_diagnostic_fields():
+    mark = Mark(5, 1, 0, name='config.yaml')
+    end = Mark(9, 1, 4, name='config.yaml')
+    diagnostic = ryaml.Diagnostic('warning', 'duplicate-key', 'duplicate key "a"', mark, end, ['spec', 0, 'a'])
+    assert diagnostic.severity == 'warning'
+    assert diagnostic.code == 'duplicate-key'
+    assert diagnostic.message == 'duplicate key "a"'
+    assert (diagnostic.mark.index, diagnostic.end_mark.index) == (5, 9)
+    assert diagnostic.path == ('spec', 0, 'a')
+    assert str(diagnostic) == 'config.yaml:2:1: warning[duplicate-key]: duplicate key "a"'
+
+
+def test_diagnostic_without_location():
+    diagnostic = ryaml.Diagnostic('error', 'bad', 'broken')
+    assert diagnostic.mark is None
+    assert diagnostic.path == ()
+    assert str(diagnostic) == 'error[bad]: broken'
+    assert repr(diagnostic) == "Diagnostic(\"error\", \"bad\", \"broken\", path=())"
+
+
+def test_unsupported_severity():
+    with pytest.raises(ValueError, match="unsupported severity 'fatal'"):
+        ryaml.Diagnostic('fatal', 'bad', 'broken')
+
+
+def test_edit():
+    edit = ryaml.Edit((3, 4), '2')
+    assert edit.span == (3, 4)
+    assert edit.replacement == '2'
+    assert edit == ryaml.Edit((3, 4), '2')
+    assert len({edit, ryaml.Edit((3, 4), '2')}) == 1
+    with pytest.raises(ValueError, match='ends before it starts'):
+        ryaml.Edit((4, 3), '')
+
+
+def test_apply_edits():
+    text = 'a: 1\nb: é\nc: 3\n'
+    edits = [ryaml.Edit((13, 14), '30'), ryaml.Edit((3, 4), '10'), ryaml.Edit((0, 0), '# top\n')]
+    assert ryaml.apply_edits(text, edits) == '# top\na: 10\nb: é\nc: 30\n'
+
+
+def test_apply_edits_rejects_bad_spans():
+    with pytest.raises(ValueError, match='overlaps an earlier edit'):
+        ryaml.apply_edits('abcdef', [ryaml.Edit((0, 3), 'x'), ryaml.Edit((2, 4), 'y')])
+    with pytest.raises(ValueError, match='past the end'):
+        ryaml.apply_edits('abc', [ryaml.Edit((2, 5), 'x')])
@@ -0,0 +1,78 @@
+import ryaml
+
+OLD = '''\
+image: web:1
+replicas: 1
+env: {MODE: dev, DEBUG: 'true'}
+ports: [80, 443]
+'''
+
+NEW = '''\
+image: web:1
+replicas: 3
+env: {MODE: dev, LEVEL: info}
+ports: [80]
+'''
+
+
+def summary(changes):
+    return [(c.kind, c.path, c.old, c.new) for c in changes]
+
+
+def test_changes():
+    assert summary(ryaml.diff(OLD, NEW)) == [
+        ('changed', ('replicas',), 1, 3),
+        ('removed', ('env', 'DEBUG'), 'true', None),
+        ('added', ('env', 'LEVEL'), None, 'info'),
+        ('removed', ('ports', 1), 443, None),
+    ]
+
+
+def test_identical():
+    assert ryaml.diff(OLD, OLD) == []
+
+
+def test_marks():
+    [change] = ryaml.diff(OLD, NEW.replace('replicas: 3', 'replicas: 5'))[:1]
+    assert change.old_mark.line == 1
+    assert change.old_mark.column == 10
+    assert change.new_mark.line == 1
+    added = ryaml.diff(OLD, NEW)[2]
+    assert added.old_mark is None
+    assert added.new_mark.line == 2
+
+
+def test_equal_values_written_differently():
+    assert ryaml.diff('a: 0x10\nb: yes\n', 'a: 16\nb: true\n') == []
+
+
+def test_tag_change():
+    assert summary(ryaml.diff('a: 1\n', "a: '1'\n")) == [('changed', ('a',), 1, '1')]
+
+
+def test_kind_change():
+    assert summary(ryaml.diff('a: [1]\n', 'a: {b: 1}\n')) == [
+        ('changed', ('a',), [1], {'b': 1}),
+    ]
+
+
+def test_empty_document():
+    assert summary(ryaml.diff('', 'a: 1\n')) == [('added', (), None, {'a': 1})]
+    assert summary(ryaml.diff('a: 1\n', '')) == [('removed', (), {'a': 1}, None)]
+    assert ryaml.diff('', '') == []
+
+
+def test_nodes():
+    assert summary(ryaml.diff(ryaml.compose('a: 1\n'), 'a: 2\n')) == [
+        ('changed', ('a',), 1, 2),
+    ]
+
+
+def test_recursive():
+    text = '&a [*a, 1]\n'
+    assert summary(ryaml.diff(text, '&a [*a, 2]\n')) == [('changed', (1,), 1, 2)]
+
+
+def test_repr():
+    [change] = ryaml.diff('a: 1\n', 'a: 2\n')
+    assert repr(change) == "Change(\"changed\", path=('a',), old=1, new=2)"
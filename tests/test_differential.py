@@ -0,0 +1,36 @@
+import pytest
+
+pytest.importorskip('ruamel.yaml')
+
+from ryaml import differential
+
+
+def test_agreeing_documents():
+    assert differential.compare_text('a: [1, 2.5, null]\nb: yes\n---\n- x\n') == []
+    assert differential.compare_text('b: yes\n', schema='core') == []
+
+
+def test_error_only_one_side_raises():
+    # ruamel.yaml rejects duplicate keys, ryaml keeps the last like pyyaml
+    [divergence] = differential.compare_text('a: 1\na: 2\n', source='dup.yaml')
+    assert divergence.source == 'dup.yaml'
+    assert divergence.kind == 'error'
+    assert divergence.ryaml == [{'a': 2}]
+    assert isinstance(divergence.ruamel, Exception)
+
+
+def test_compare_files(tmp_path):
+    good = tmp_path / 'good.yaml'
+    good.write_text('a: 1\n')
+    dup = tmp_path / 'dup.yaml'
+    dup.write_text('a: 1\na: 2\n')
+    divergences = differential.compare_files([good, dup])
+    assert [d.source for d in divergences] == [str(dup)]
+    report = differential.report(divergences)
+    assert report.startswith('error (1):\n  ')
+    assert str(dup) in report
+
+
+def test_unsupported_schema():
+    with pytest.raises(ValueError, match="unsupported schema 'json'"):
+        differential.compare_text('a: 1\n', schema='json')
@@ -0,0 +1,57 @@
+import io
+
+import pytest
+
+import ryaml
+
+
+def test_defaults():
+    assert ryaml.dumps({'a': 1}) == 'a: 1\n'
+    assert ryaml.dumps(1) == '1\n'
+
+
+def test_document_end_always():
+    assert ryaml.dumps({'a': 1}, document_end='always') == 'a: 1\n...\n'
+    assert ryaml.dumps(1, document_end='always') == '1\n...\n'
+
+
+def test_document_end_never():
+    assert ryaml.dumps({'a': 1}, document_end='never') == 'a: 1\n'
+    # A kept block scalar at the end is open-ended, so the emitter ends it
+    data = {'a': 'x\n\n'}
+    auto = ryaml.dumps(data, styles={'a': 'literal'})
+    never = ryaml.dumps(data, styles={'a': 'literal'}, document_end='never')
+    assert never == auto.removesuffix('...\n')
+    assert not never.endswith('...\n')
+    assert ryaml.loads(never) == data
+
+
+def test_final_newline():
+    assert ryaml.dumps({'a': [1, 2]}, final_newline=False) == 'a:\n- 1\n- 2'
+    assert ryaml.dumps(1, final_newline=False) == '1'
+    assert ryaml.dumps(1, document_end='always', final_newline=False) == '1\n...'
+
+
+def test_invalid_document_end():
+    with pytest.raises(ValueError):
+        ryaml.dumps(1, document_end='sometimes')
+
+
+def dump_with(data, **kwargs):
+    stream = io.StringIO()
+    dumper = ryaml.RSafeDumper(stream, **kwargs)
+    dumper.open()
+    dumper.represent(data)
+    dumper.close()
+    return stream.getvalue()
+
+
+def test_compat_dumper():
+    assert dump_with(1, final_newline=False) == '1'
+    assert dump_with({'a': 1}, document_end='always') == 'a: 1\n...\n'
+    assert dump_with({'a': 1}, explicit_end=True, document_end='never') == 'a: 1\n...\n'
+
+
+def test_compat_dumper_needs_utf8():
+    with pytest.raises(ValueError):
+        ryaml.RSafeDumper(io.BytesIO(), encoding='utf-16-le', final_newline=False)
@@ -0,0 +1,46 @@
+import ryaml
+
+def test_loads_all_with_meta():
+    first, second = ryaml.loads_all('%YAML 1.1\n%TAG !e! tag:example.com,2000:\n--- [1]\n--- 2\n', with_meta=True)
+    assert isinstance(first, ryaml.Document)
+    assert first.data == [1]
+    assert first.version == (1, 1)
+    assert first.tags == {'!e!': 'tag:example.com,2000:'}
+
+def test_directives_reset_per_document():
+    documents = ryaml.loads_all('%YAML 1.1\n--- a\n...\n%TAG !e! tag:example.com,2000:\n--- b\n', with_meta=True)
+    assert [(doc.data, doc.version, doc.tags) for doc in documents] == [
+        ('a', (1, 1), {}),
+        ('b', None, {'!e!': 'tag:example.com,2000:'}),
+    ]
+
+def test_default_tag_handles_are_not_reported():
+    [document] = ryaml.loads_all('--- !!str 1\n', with_meta=True)
+    assert document.data == '1'
+    assert document.version is None and document.tags == {}
+
+def test_with_meta_json_document():
+    [document] = ryaml.loads_all('{"a": 1}', with_meta=True)
+    assert document.data == {'a': 1}
+    assert document.version is None and document.tags == {}
+    assert (document.start_mark.index, document.end_mark.index) == (0, 8)
+
+def test_loads_all_without_meta_unchanged():
+    assert ryaml.loads_all('%YAML 1.1\n--- a\n--- b\n') == ['a', 'b']
+
+def test_document_repr():
+    [document] = ryaml.loads_all('%YAML 1.2\n--- 1\n', with_meta=True)
+    assert repr(document) == 'Document(1, version=(1, 2), tags={})'
+
+def test_document_marks():
+    source = 'a: 1\n---\nb: [2]\n...\n--- c\n'
+    documents = ryaml.loads_all(source, with_meta=True)
+    assert [document.data for document in documents] == [{'a': 1}, {'b': [2]}, 'c']
+    spans = [(document.start_mark.index, document.end_mark.index) for document in documents]
+    assert [source[start:end] for start, end in spans] == ['a: 1\n', '---\nb: [2]\n...', '--- c\n']
+    assert [document.start_mark.line for document in documents] == [0, 1, 4]
+
+def test_document_marks_point_into_source():
+    [document] = ryaml.loads_all('--- x\n', with_meta=True, name='config.yaml')
+    assert document.start_mark.name == 'config.yaml'
+    assert document.end_mark.buffer == '--- x\n'
@@ -0,0 +1,95 @@
+import io
+import threading
+import time
+
+import pytest
+
+import ryaml
+
+
+def test_reads_documents():
+    stream = io.StringIO('a: 1\n---\n- x\n- y\n---\ndone\n')
+    assert list(ryaml.DocumentReader(stream)) == [{'a': 1}, ['x', 'y'], 'done']
+
+
+def test_loader_options():
+    stream = io.BytesIO(b'a: 2001-12-14\n')
+    assert list(ryaml.DocumentReader(stream, parse_datetimes=False)) == [{'a': '2001-12-14'}]
+
+
+def test_ends_at_eof_without_follow():
+    reader = ryaml.DocumentReader(io.StringIO(''))
+    assert list(reader) == []
+
+
+def test_follow_waits_for_documents(tmp_path):
+    path = tmp_path / 'log.yaml'
+    path.write_text('')
+    with open(path) as stream, open(path, 'a') as log:
+        reader = ryaml.DocumentReader(stream, follow=True, poll_interval=0.01)
+
+        def append():
+            for n in range(3):
+                time.sleep(0.05)
+                log.write(f'n: {n}\n...\n')
+                log.flush()
+
+        writer = threading.Thread(target=append)
+        writer.start()
+        documents = [next(reader) for _ in range(3)]
+        writer.join()
+    assert documents == [{'n': 0}, {'n': 1}, {'n': 2}]
+
+
+def test_follow_tolerates_incomplete_document(tmp_path):
+    path = tmp_path / 'log.yaml'
+    path.write_text('first: 1\n---\nsecond: [1,\n')
+    with open(path) as stream:
+        reader = ryaml.DocumentReader(stream, follow=True, poll_interval=0.01)
+        assert next(reader) == {'first': 1}
+
+        def finish():
+            time.sleep(0.05)
+            with open(path, 'a') as log:
+                log.write('  2]\n...\n')
+
+        writer = threading.Thread(target=finish)
+        writer.start()
+        assert next(reader) == {'second': [1, 2]}
+        writer.join()
+
+
+def test_follow_reads_documents_from_writer(tmp_path):
+    path = tmp_path / 'log.yaml'
+    with open(path, 'w') as log, open(path) as stream:
+        reader = ryaml.DocumentReader(stream, follow=True, poll_interval=0.01)
+        with ryaml.DocumentWriter(log, document_end='always') as writer:
+            writer.write({'event': 'start'})
+            writer.flush()
+            assert next(reader) == {'event': 'start'}
+            writer.write({'event': 'stop'})
+            writer.flush()
+            assert next(reader) == {'event': 'stop'}
+
+
+def test_read_errors_are_raised():
+    class Broken(io.RawIOBase):
+        def readable(self):
+            return True
+
+        def read(self, size=-1):
+            raise OSError('disconnected')
+
+    with pytest.raises(OSError, match='disconnected'):
+        next(ryaml.DocumentReader(Broken(), follow=True))
+
+
+@pytest.mark.parametrize('interval', [0, -1, float('nan')])
+def test_invalid_poll_interval(interval):
+    with pytest.raises(ValueError, match='poll_interval'):
+        ryaml.DocumentReader(io.StringIO(''), follow=True, poll_interval=interval)
+
+
+def test_saphyr_backend():
+    stream = io.BytesIO('name: café\n---\n- &x 1\n- *x\n'.encode())
+    assert list(ryaml.DocumentReader(stream, backend='saphyr')) == [{'name': 'café'}, [1, 1]]
@@ -0,0 +1,71 @@
+import io
+
+import pytest
+
+import ryaml
+
+
+def test_writes_documents_as_one_stream():
+    stream = io.StringIO()
+    writer = ryaml.DocumentWriter(stream)
+    writer.write({'event': 'start', 'n': 1})
+    writer.write(['a', 'b'])
+    writer.write('done')
+    writer.close()
+    assert stream.getvalue() == ryaml.dumps_all([{'event': 'start', 'n': 1}, ['a', 'b'], 'done'])
+    assert stream.getvalue().startswith('event: start\nn: 1\n---\n- a\n- b\n---')
+
+
+def test_documents_are_written_as_they_come():
+    stream = io.StringIO()
+    writer = ryaml.DocumentWriter(stream)
+    writer.write({'a': 1})
+    writer.flush()
+    assert ryaml.loads_all(stream.getvalue()) == [{'a': 1}]
+    writer.write({'b': 2})
+    writer.flush()
+    assert ryaml.loads_all(stream.getvalue()) == [{'a': 1}, {'b': 2}]
+    writer.close()
+    assert ryaml.loads_all(stream.getvalue()) == [{'a': 1}, {'b': 2}]
+
+
+def test_dump_options():
+    options = {'encoding': 'utf-8', 'document_end': 'always', 'sort_keys': True}
+    stream = io.BytesIO()
+    with ryaml.DocumentWriter(stream, **options) as writer:
+        writer.write({'b': 1, 'a': 2})
+        writer.write({'c': 'é'})
+    assert stream.getvalue() == ryaml.dumps_all([{'b': 1, 'a': 2}, {'c': 'é'}], **options)
+    assert stream.getvalue().startswith(b'a: 2\nb: 1\n...\n')
+
+
+def test_anchors_are_per_document():
+    shared = [1]
+    stream = io.StringIO()
+    with ryaml.DocumentWriter(stream) as writer:
+        writer.write([shared, shared])
+        writer.write([shared, shared])
+    assert stream.getvalue() == '- &id001\n  - 1\n- *id001\n---\n- &id001\n  - 1\n- *id001\n'
+
+
+def test_failed_document_leaves_writer_usable():
+    stream = io.StringIO()
+    with ryaml.DocumentWriter(stream) as writer:
+        writer.write({'a': 1})
+        with pytest.raises(ryaml.RepresenterError):
+            writer.write(object())
+        writer.write({'b': 2})
+    assert ryaml.loads_all(stream.getvalue()) == [{'a': 1}, {'b': 2}]
+
+
+def test_write_after_close():
+    writer = ryaml.DocumentWriter(io.StringIO())
+    writer.close()
+    writer.close()
+    with pytest.raises(ryaml.SerializerError, match='writer is closed'):
+        writer.write(1)
+
+
+def test_invalid_options():
+    with pytest.raises(TypeError):
+        ryaml.DocumentWriter(io.StringIO(), not_an_option=True)
@@ -0,0 +1,45 @@
+import ryaml
+
+
+def test_counts_and_size():
+    data = {'a': 1, 'b': [1, 2], 'c': {'d': 'x'}}
+    plan = ryaml.dumps(data, dry_run=True)
+    assert (plan.scalars, plan.sequences, plan.mappings) == (8, 1, 2)
+    assert plan.estimated_size == len(ryaml.dumps(data)) == 26
+
+
+def test_anchors():
+    shared = [1]
+    data = {'a': shared, 'b': shared}
+    plan = ryaml.dumps(data, dry_run=True)
+    assert plan.anchors == ['id001']
+    assert plan.sequences == 1
+    assert plan.estimated_size == len(ryaml.dumps(data))
+
+
+def test_needs_quoting():
+    plan = ryaml.dumps(['yes', '1', 'plain', 'a: b', '', 'yes'], dry_run=True)
+    assert plan.needs_quoting == ['yes', '1', 'a: b', '']
+
+
+def test_needs_quoting_follows_schema():
+    assert ryaml.dumps(['yes'], dry_run=True, schema='core').needs_quoting == []
+
+
+def test_explicit_tags():
+    plan = ryaml.dumps({'s': {1, 2}, 'b': b'x'}, dry_run=True)
+    assert plan.explicit_tags == ['tag:yaml.org,2002:set', 'tag:yaml.org,2002:binary']
+    assert plan.needs_quoting == []
+
+
+def test_errors_are_raised():
+    try:
+        ryaml.dumps(object(), dry_run=True)
+    except ryaml.RepresenterError:
+        pass
+    else:
+        raise AssertionError('expected RepresenterError')
+
+
+def test_dry_run_false():
+    assert ryaml.dumps([1], dry_run=False) == '- 1\n'
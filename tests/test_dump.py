@@ -1,3 +1,8 @@
+import io
+import os
+
+import pytest
+
 import ryaml
 
 def test_dump_none(yaml_file):
@@ -19,3 +24,49 @@ def test_dump_key_sequence(yaml_file):
     ryaml.dump(yaml_file, { 'key': [4, 5] })
     yaml_file.seek(0)
     assert yaml_file.read() == 'key:\n- 4\n- 5\n'
+
+def test_dump_path(tmp_path):
+    path = tmp_path / 'out.yaml'
+    ryaml.dump(path, {'key': [4, 5]})
+    assert path.read_text(encoding='utf8') == 'key:\n- 4\n- 5\n'
+    ryaml.dump(str(path), 'replaced')
+    assert path.read_text(encoding='utf8') == 'replaced\n'
+
+def test_dump_all_path(tmp_path):
+    path = tmp_path / 'out.yaml'
+    ryaml.dump_all(path, [1, 2])
+    assert ryaml.load_all(path) == [1, 2]
+
+def test_dump_path_encoded(tmp_path):
+    path = tmp_path / 'out.yaml'
+    ryaml.dump(path, {'key': 'value'}, encoding='utf-16-le')
+    assert path.read_bytes() == '\ufeffkey: value\n'.encode('utf-16-le')
+    assert ryaml.load(path) == {'key': 'value'}
+
+def test_dump_atomic(tmp_path):
+    path = tmp_path / 'out.yaml'
+    path.write_text('old: true\n')
+    path.chmod(0o640)
+    ryaml.dump(path, {'key': [4, 5]}, atomic=True)
+    assert path.read_text(encoding='utf8') == 'key:\n- 4\n- 5\n'
+    assert path.stat().st_mode & 0o777 == 0o640
+    ryaml.dump_all(str(path), [1, 2], atomic=True)
+    assert ryaml.load_all(path) == [1, 2]
+    assert os.listdir(tmp_path) == ['out.yaml']
+
+def test_dump_atomic_new_file(tmp_path):
+    path = tmp_path / 'new.yaml'
+    ryaml.dump(path, 'x', atomic=True, encoding='utf-8')
+    assert path.read_bytes() == b'x\n'
+
+def test_dump_atomic_failure_keeps_old_file(tmp_path):
+    path = tmp_path / 'out.yaml'
+    path.write_text('old: true\n')
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dump(path, {'bad': object()}, atomic=True)
+    assert path.read_text() == 'old: true\n'
+    assert os.listdir(tmp_path) == ['out.yaml']
+
+def test_dump_atomic_needs_path():
+    with pytest.raises(TypeError, match='atomic=True needs fp to be a path'):
+        ryaml.dump(io.StringIO(), 1, atomic=True)
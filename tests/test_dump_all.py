@@ -0,0 +1,82 @@
+import io
+
+import ryaml
+
+
+def test_dumps_all():
+    assert ryaml.dumps_all([{'a': 1}, {'b': [1, 2]}]) == 'a: 1\n---\nb:\n- 1\n- 2\n'
+    assert ryaml.dumps_all(({'n': n} for n in range(3))) == 'n: 0\n---\nn: 1\n---\nn: 2\n'
+
+
+def test_dumps_all_empty():
+    assert ryaml.dumps_all([]) == ''
+
+
+def test_anchors_restart_each_document():
+    shared = [1]
+    dumped = ryaml.dumps_all([{'a': shared, 'b': shared}, {'c': shared}])
+    assert dumped == 'a: &id001\n- 1\nb: *id001\n---\nc:\n- 1\n'
+
+
+def test_options_apply():
+    assert ryaml.dumps_all([{'a': 1}, {'b': 2}], document_end='always') == 'a: 1\n...\n---\nb: 2\n...\n'
+    assert ryaml.dumps_all([{'a': 1}], encoding='utf-8') == b'a: 1\n'
+
+
+def test_round_trip():
+    docs = [{'a': [1, 2]}, ['x', None], {'b': {'c': 1.5}}]
+    assert ryaml.loads_all(ryaml.dumps_all(docs)) == docs
+
+
+def test_dump_all():
+    stream = io.StringIO()
+    ryaml.dump_all(stream, [{'a': 1}, {'b': 2}])
+    assert stream.getvalue() == 'a: 1\n---\nb: 2\n'
+    binary = io.BytesIO()
+    ryaml.dump_all(binary, [{'a': 1}, {'b': 2}])
+    assert binary.getvalue() == b'a: 1\n---\nb: 2\n'
+
+
+class RecordingStream(io.StringIO):
+    def __init__(self):
+        super().__init__()
+        self.writes = 0
+
+    def write(self, s):
+        self.writes += 1
+        return super().write(s)
+
+
+def test_compat_dumper_writes_each_document():
+    stream = RecordingStream()
+    dumper = ryaml.RSafeDumper(stream)
+    dumper.open()
+    dumper.represent({'a': 1, 'b': 'first document'})
+    # Output is written before the stream is closed, all but its very end
+    assert stream.getvalue().startswith('a: 1\n')
+    dumper.represent({'c': 2})
+    dumper.close()
+    assert stream.getvalue() == 'a: 1\nb: first document\n---\nc: 2\n'
+
+
+def test_compat_dumper_writes_large_documents_in_chunks():
+    # Keys sort as inserted, since the compat dumper sorts them
+    data = {f'key{i:05}': f'value {i}' for i in range(50000)}
+    stream = RecordingStream()
+    dumper = ryaml.RSafeDumper(stream, final_newline=False)
+    dumper.open()
+    dumper.represent(data)
+    dumper.close()
+    assert stream.writes > 2
+    assert stream.getvalue() == ryaml.dumps(data, final_newline=False)
+
+
+def test_compat_dumper_chunks_keep_characters_whole():
+    data = {f'key{i:05}': 'é😀' * (i % 7) for i in range(20000)}
+    stream = RecordingStream()
+    dumper = ryaml.RSafeDumper(stream, allow_unicode=True)
+    dumper.open()
+    dumper.represent(data)
+    dumper.close()
+    assert stream.writes > 2
+    assert ryaml.loads(stream.getvalue()) == data
@@ -0,0 +1,54 @@
+import dataclasses
+from typing import ClassVar
+
+import pytest
+
+import ryaml
+
+
+@dataclasses.dataclass
+class Port:
+    number: int
+    protocol: str = 'tcp'
+
+
+@dataclasses.dataclass
+class Service:
+    name: str
+    ports: list
+    kind: ClassVar[str] = 'service'
+
+
+def test_dataclass_dumped_as_mapping_in_field_order():
+    service = Service('web', [Port(80), Port(53, 'udp')])
+    assert ryaml.dumps(service, dump_dataclasses=True) == (
+        'name: web\nports:\n- number: 80\n  protocol: tcp\n- number: 53\n  protocol: udp\n'
+    )
+
+
+def test_shared_dataclass_is_aliased():
+    port = Port(80)
+    assert ryaml.dumps([port, port], dump_dataclasses=True) == (
+        '- &id001\n  number: 80\n  protocol: tcp\n- *id001\n'
+    )
+
+
+def test_dataclass_class_is_not_dumped_as_instance():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(Port, dump_dataclasses=True)
+
+
+def test_dataclasses_rejected_by_default():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(Port(80))
+
+
+def test_attrs_classes():
+    attr = pytest.importorskip('attr')
+
+    @attr.s
+    class Point:
+        x = attr.ib()
+        y = attr.ib()
+
+    assert ryaml.dumps(Point(1, 2), dump_dataclasses=True) == 'x: 1\ny: 2\n'
@@ -0,0 +1,119 @@
+import datetime
+
+import pytest
+
+import ryaml
+
+
+class ArrowLike:
+    """Mimics arrow.Arrow, which wraps a stdlib datetime"""
+
+    def __init__(self, dt):
+        self.datetime = dt
+
+    def isoformat(self):
+        return self.datetime.isoformat()
+
+
+class DateLike:
+    """Implements the date protocol without subclassing datetime.date"""
+
+    def timetuple(self):
+        return datetime.date(2024, 3, 1).timetuple()
+
+    def isoformat(self):
+        return '2024-03-01'
+
+
+def test_third_party_datetime_rejected_by_default():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(ArrowLike(datetime.datetime(2024, 3, 1, 12, 30)))
+
+
+def test_arrow_like():
+    obj = ArrowLike(datetime.datetime(2024, 3, 1, 12, 30))
+    assert ryaml.dumps([obj], third_party_datetimes=True) == '- 2024-03-01 12:30:00\n'
+
+
+def test_date_protocol():
+    assert ryaml.dumps([DateLike()], third_party_datetimes=True) == '- 2024-03-01\n'
+
+
+def test_round_trip():
+    dt = datetime.datetime(2024, 3, 1, 12, 30)
+    dumped = ryaml.dumps({'at': ArrowLike(dt)}, third_party_datetimes=True)
+    assert ryaml.loads(dumped) == {'at': dt}
+
+
+def test_numpy_datetime64():
+    np = pytest.importorskip('numpy')
+    dumped = ryaml.dumps(
+        [np.datetime64('2024-03-01'), np.datetime64('2024-03-01T12:30:00')],
+        third_party_datetimes=True,
+    )
+    assert dumped == '- 2024-03-01\n- 2024-03-01 12:30:00\n'
+
+
+def test_unknown_keyword():
+    with pytest.raises(TypeError):
+        ryaml.dumps(1, not_an_option=True)
+
+
+AWARE = datetime.datetime(
+    2024, 3, 1, 12, 30, tzinfo=datetime.timezone(datetime.timedelta(hours=2))
+)
+
+
+def test_timezone_preserve():
+    assert ryaml.dumps([AWARE]) == '- 2024-03-01 12:30:00+02:00\n'
+
+
+def test_timezone_utc():
+    assert ryaml.dumps([AWARE], timezone='utc') == '- 2024-03-01 10:30:00Z\n'
+
+
+def test_timezone_naive():
+    assert ryaml.dumps([AWARE], timezone='naive') == '- 2024-03-01 12:30:00\n'
+
+
+def test_timezone_leaves_naive_datetimes():
+    dt = datetime.datetime(2024, 3, 1, 12, 30)
+    assert ryaml.dumps([dt], timezone='utc') == '- 2024-03-01 12:30:00\n'
+
+
+def test_timezone_zoneinfo():
+    zoneinfo = pytest.importorskip('zoneinfo')
+    dt = datetime.datetime(2024, 7, 1, 12, 0, tzinfo=zoneinfo.ZoneInfo('Europe/Berlin'))
+    assert ryaml.dumps([dt], timezone='utc') == '- 2024-07-01 10:00:00Z\n'
+
+
+def test_timezone_invalid():
+    with pytest.raises(ValueError):
+        ryaml.dumps([AWARE], timezone='local')
+
+
+class MyDatetime(datetime.datetime):
+    pass
+
+
+class MyDate(datetime.date):
+    pass
+
+
+def test_subclasses():
+    dumped = ryaml.dumps([MyDatetime(2024, 3, 1, 12, 30), MyDate(2024, 3, 1)])
+    assert dumped == '- 2024-03-01 12:30:00\n- 2024-03-01\n'
+
+
+def test_freezegun_fakes():
+    freezegun = pytest.importorskip('freezegun')
+    with freezegun.freeze_time('2024-03-01 12:30:00'):
+        dumped = ryaml.dumps([datetime.datetime.now(), datetime.date.today()])
+    assert dumped == '- 2024-03-01 12:30:00\n- 2024-03-01\n'
+    # Real datetimes still dump once the clock is released
+    assert ryaml.dumps([datetime.date(2024, 3, 1)]) == '- 2024-03-01\n'
+
+
+def test_pandas_timestamp():
+    pd = pytest.importorskip('pandas')
+    assert ryaml.dumps([pd.Timestamp('2024-03-01 12:30')]) == '- 2024-03-01 12:30:00\n'
@@ -0,0 +1,61 @@
+import io
+import pathlib
+import uuid
+
+import pytest
+import ryaml
+from ryaml.compat import RSafeDumper
+
+
+def test_default_stringifies_unknown_objects():
+    data = {'path': pathlib.PurePosixPath('/etc/hosts'), 'id': uuid.UUID(int=1)}
+    assert ryaml.dumps(data, default=str) == (
+        'path: /etc/hosts\nid: 00000000-0000-0000-0000-000000000001\n'
+    )
+
+
+def test_default_result_is_represented():
+    class Point:
+        def __init__(self, x, y):
+            self.x = x
+            self.y = y
+
+    assert ryaml.dumps([Point(1, 2)], default=lambda p: {'x': p.x, 'y': p.y}) == '- x: 1\n  y: 2\n'
+
+
+def test_default_not_called_for_representable_objects():
+    calls = []
+    assert ryaml.dumps({'a': [1, 'b']}, default=calls.append) == 'a:\n- 1\n- b\n'
+    assert calls == []
+
+
+def test_default_errors_propagate():
+    def fail(obj):
+        raise TypeError(f'{type(obj).__name__} is not serializable')
+
+    with pytest.raises(TypeError, match='object is not serializable'):
+        ryaml.dumps(object(), default=fail)
+
+
+def test_default_returning_same_type():
+    with pytest.raises(ryaml.RepresenterError, match='default\\(\\) returned another'):
+        ryaml.dumps(object(), default=lambda obj: object())
+
+
+def test_default_must_be_callable():
+    with pytest.raises(ValueError, match='default must be callable'):
+        ryaml.dumps(object(), default='str')
+
+
+def test_without_default_still_raises():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(object())
+
+
+def test_default_with_dumper_class():
+    stream = io.StringIO()
+    dumper = RSafeDumper(stream, default=str)
+    dumper.open()
+    dumper.represent([pathlib.PurePosixPath('/tmp')])
+    dumper.close()
+    assert stream.getvalue() == '- /tmp\n'
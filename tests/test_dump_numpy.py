@@ -0,0 +1,44 @@
+import pytest
+
+import ryaml
+
+
+class FakeArray:
+    """Duck-types the parts of an ndarray the dumper looks at"""
+
+    dtype = 'int64'
+
+    def __init__(self, rows):
+        self.rows = rows
+
+    def __array__(self):
+        raise AssertionError('not used')
+
+    def tolist(self):
+        return self.rows
+
+
+def test_duck_typed_array():
+    assert ryaml.dumps({'m': FakeArray([[1, 2], [3, 4]])}, dump_numpy=True) == 'm:\n- - 1\n  - 2\n- - 3\n  - 4\n'
+
+
+def test_arrays_rejected_by_default():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(FakeArray([1]))
+
+
+def test_numpy_scalars_and_arrays():
+    np = pytest.importorskip('numpy')
+    data = {
+        'f': np.float64(1.5),
+        'i': np.int32(7),
+        'b': np.bool_(True),
+        'a': np.arange(3),
+    }
+    assert ryaml.dumps(data, dump_numpy=True) == 'f: 1.5\ni: 7\nb: true\na:\n- 0\n- 1\n- 2\n'
+
+
+def test_numpy_round_trip():
+    np = pytest.importorskip('numpy')
+    matrix = np.array([[1.0, 2.5], [3.0, 4.0]])
+    assert ryaml.loads(ryaml.dumps(matrix, dump_numpy=True)) == matrix.tolist()
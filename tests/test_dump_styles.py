@@ -0,0 +1,57 @@
+import pytest
+
+import ryaml
+
+
+def test_literal_by_path():
+    data = {'data': {'script': 'echo hi\nexit 0\n'}, 'name': 'job'}
+    dumped = ryaml.dumps(data, styles={'data.*': 'literal'})
+    assert dumped == 'data:\n  script: |\n    echo hi\n    exit 0\nname: job\n'
+
+
+def test_double_quoted_by_path():
+    data = {'metadata': {'annotations': {'a': 'x', 'b': 'y'}, 'name': 'n'}}
+    dumped = ryaml.dumps(data, styles={'metadata.annotations.*': '"'})
+    assert dumped == 'metadata:\n  annotations:\n    a: "x"\n    b: "y"\n  name: n\n'
+
+
+def test_sequence_index_wildcard():
+    dumped = ryaml.dumps({'args': ['a', 'b']}, styles={'args.*': 'single'})
+    assert dumped == "args:\n- 'a'\n- 'b'\n"
+
+
+def test_keys_are_not_styled():
+    dumped = ryaml.dumps({'data': {'data': 'v'}}, styles={'data': 'double'})
+    assert dumped == 'data:\n  data: v\n'
+
+
+def test_non_strings_are_not_styled():
+    assert ryaml.dumps({'port': 80}, styles={'port': 'double'}) == 'port: 80\n'
+
+
+def test_styles_round_trip():
+    data = {'data': {'a': 'one\ntwo\n'}}
+    assert ryaml.loads(ryaml.dumps(data, styles={'data.*': 'literal'})) == data
+
+
+def test_invalid_style():
+    with pytest.raises(ValueError):
+        ryaml.dumps({}, styles={'a': 'bold'})
+
+
+def test_literal_multiline():
+    dumped = ryaml.dumps({'script': 'echo hi\nexit 0\n', 'name': 'job'}, literal_multiline=True)
+    assert dumped == 'script: |\n  echo hi\n  exit 0\nname: job\n'
+    assert ryaml.dumps({'a': 'one\ntwo'}, literal_multiline=True) == 'a: |-\n  one\n  two\n'
+
+
+def test_literal_multiline_falls_back_when_content_needs_escapes():
+    data = {'a': 'trailing \nspace'}
+    dumped = ryaml.dumps(data, literal_multiline=True)
+    assert dumped.startswith('a: "')
+    assert ryaml.loads(dumped) == data
+
+
+def test_path_styles_take_precedence_over_literal_multiline():
+    dumped = ryaml.dumps({'a': 'one\ntwo'}, literal_multiline=True, styles={'a': 'double'})
+    assert dumped == 'a: "one\\ntwo"\n'
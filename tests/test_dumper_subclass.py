@@ -0,0 +1,79 @@
+import pytest
+import yaml
+
+import ryaml
+from ryaml._ryaml import MappingNode, ScalarNode
+from ryaml.compat import RSafeDumper
+
+
+class LiteralDumper(RSafeDumper):
+    def represent_str(self, data):
+        if '\n' in data:
+            return self.represent_scalar('tag:yaml.org,2002:str', data, style='|')
+        return super().represent_str(data)
+
+
+class NoAliasDumper(RSafeDumper):
+    def ignore_aliases(self, data):
+        return True
+
+
+class OrderedDumper(RSafeDumper):
+    def represent_dict(self, data):
+        return self.represent_mapping('tag:yaml.org,2002:map', data.items())
+
+
+class TildeDumper(RSafeDumper):
+    def represent_none(self, data):
+        return self.represent_scalar('tag:yaml.org,2002:null', '~')
+
+
+class BrokenDumper(RSafeDumper):
+    def represent_str(self, data):
+        return data
+
+
+def test_represent_str_override():
+    data = {'a': 'x\ny\n', 'b': 'z'}
+    assert yaml.dump(data, Dumper=LiteralDumper) == 'a: |\n  x\n  y\nb: z\n'
+
+
+def test_ignore_aliases_override():
+    shared = [1]
+    assert yaml.dump({'a': shared, 'b': shared}, Dumper=NoAliasDumper) == 'a:\n- 1\nb:\n- 1\n'
+
+
+def test_aliases_kept_without_override():
+    shared = [1]
+    assert yaml.dump({'a': shared, 'b': shared}, Dumper=TildeDumper) == 'a: &id001\n- 1\nb: *id001\n'
+
+
+def test_represent_mapping_keeps_order_of_pairs():
+    assert yaml.dump({'b': 1, 'a': {'d': 2, 'c': 3}}, Dumper=OrderedDumper) == 'b: 1\na:\n  d: 2\n  c: 3\n'
+
+
+def test_represent_scalar_override_reaches_nested_values():
+    assert yaml.dump({'a': [None, 1]}, Dumper=TildeDumper) == 'a:\n- ~\n- 1\n'
+
+
+def test_registered_representers_still_apply():
+    class Point:
+        pass
+
+    ryaml.add_representer(Point, lambda data: ScalarNode('!dumper-subclass-point', 'p'))
+    assert yaml.dump([Point(), None], Dumper=TildeDumper) == '- !dumper-subclass-point p\n- ~\n'
+
+
+def test_override_must_return_a_node():
+    with pytest.raises(ryaml.RepresenterError, match=r'represent_str\(\) did not return a node'):
+        yaml.dump('x', Dumper=BrokenDumper)
+
+
+def test_represent_methods_return_nodes():
+    dumper = RSafeDumper(None)
+    node = dumper.represent_data({'a': [1, 'x']})
+    assert isinstance(node, MappingNode)
+    key, value = node.value[0]
+    assert key.value == 'a'
+    assert [item.value for item in value.value] == ['1', 'x']
+    assert dumper.represent_scalar('tag:yaml.org,2002:str', 'x', style='"').style == '"'
@@ -1,4 +1,10 @@
+import io
+
+import pytest
+import yaml
+
 import ryaml
+from ryaml.compat import RSafeDumper
 
 def test_dumps_none():
     assert ryaml.dumps(None) == 'null\n'
@@ -11,3 +17,189 @@ def test_dumps_key_value():
 
 def test_dumps_key_sequence():
     assert ryaml.dumps({ 'key': [4, 5] }) == 'key:\n- 4\n- 5\n'
+
+def test_dumps_repeated_scalars_are_not_aliased():
+    image = ''.join(['nginx:', '1.25'])
+    data = [{'image': image, 'replicas': 3, 'debug': False} for _ in range(3)]
+    assert ryaml.dumps(data) == '- image: nginx:1.25\n  replicas: 3\n  debug: false\n' * 3
+
+def test_dumps_repeated_scalars_keep_their_styles():
+    dumped = ryaml.dumps({'a': 'x', 'b': 'x'}, styles={'b': 'double'})
+    assert dumped == 'a: x\nb: "x"\n'
+
+def test_dumps_repeated_containers_still_aliased():
+    shared = ['x']
+    assert ryaml.dumps(['x', shared, shared]) == '- x\n- &id001\n  - x\n- *id001\n'
+
+def test_dumps_flags_share_nodes_across_documents():
+    flags = {f'feature_{i}': i % 3 == 0 or None for i in range(4)}
+    expected = 'feature_0: true\nfeature_1: null\nfeature_2: null\nfeature_3: true\n'
+    assert ryaml.dumps(flags) == expected
+    assert ryaml.dumps([False, flags]) == '- false\n- ' + expected.replace('\n', '\n  ').rstrip(' ')
+
+def test_dumps_flags_with_default_style():
+    stream = io.StringIO()
+    dumper = RSafeDumper(stream, default_style='"')
+    dumper.open()
+    dumper.represent([True, None, True])
+    dumper.close()
+    assert '"true"' in stream.getvalue()
+    assert ryaml.loads(stream.getvalue()) == [True, None, True]
+
+def test_dumps_int_subclass_ignores_str():
+    class Weird(int):
+        def __str__(self):
+            raise RuntimeError('no __str__')
+
+        __repr__ = __str__
+
+    assert ryaml.dumps([Weird(3), Weird(-2**80)]) == f'- 3\n- {-2**80}\n'
+
+def test_dumps_big_ints():
+    assert ryaml.dumps(2**100) == f'{2**100}\n'
+
+def test_dumps_nan_allowed_by_default():
+    assert ryaml.dumps([float('nan'), float('inf'), -float('inf')]) == '- .nan\n- .inf\n- -.inf\n'
+
+@pytest.mark.parametrize('value', [float('nan'), float('inf'), -float('inf')])
+def test_dumps_allow_nan_false(value):
+    with pytest.raises(ryaml.RepresenterError, match="at 'a.1' is not allowed with allow_nan=False"):
+        ryaml.dumps({'a': [1.5, value]}, allow_nan=False)
+
+def test_dumps_allow_nan_false_finite_floats():
+    assert ryaml.dumps({'a': 1.5}, allow_nan=False) == 'a: 1.5\n'
+
+FLOATS = [0.1, 1.0, -0.0, 1e15, 1e16, 1e-4, 1e-5, 1.5e-7, 1e22, 5e-324, 1.7976931348623157e308,
+          123456789012345678.0, 0.30000000000000004, -2.5e-10]
+
+@pytest.mark.parametrize('value', FLOATS)
+def test_dumps_floats_match_pyyaml(value):
+    assert ryaml.dumps(value) == yaml.dump(value, Dumper=yaml.SafeDumper).replace('\n...\n', '\n')
+
+@pytest.mark.parametrize('value', FLOATS)
+def test_dumps_floats_round_trip(value):
+    loaded = ryaml.loads(ryaml.dumps(value))
+    assert loaded == value and str(loaded) == str(value)
+
+def test_nested_sequences_match_pyyaml():
+    # pyyaml, like libyaml, writes block sequences in mappings indentless
+    data = {'a': [1, [2, 3], {'b': ['c', 'd']}], 'e': [{'f': 1, 'g': [2]}]}
+    expected = yaml.dump(data, Dumper=yaml.SafeDumper)
+    assert yaml.dump(data, Dumper=RSafeDumper) == expected
+    assert ryaml.dumps(data) == expected
+
+def test_dumps_default_style():
+    assert ryaml.dumps('a', default_style='double') == '"a"\n'
+    assert ryaml.dumps({'a': 1}, default_style="'") == "'a': !!int '1'\n"
+
+def test_dumps_invalid_default_style():
+    with pytest.raises(ValueError, match="unsupported default_style 'bold'"):
+        ryaml.dumps('a', default_style='bold')
+
+def test_dumps_canonical():
+    data = {'a': [1, 'b', None]}
+    dumped = ryaml.dumps(data, canonical=True)
+    assert dumped.startswith('---\n!!map {\n')
+    assert '? !!str "a"' in dumped
+    assert '!!int "1"' in dumped
+    assert '!!null "null"' in dumped
+    assert ryaml.loads(dumped) == data
+    assert ryaml.dumps_many([data], canonical=True) == [dumped]
+
+def test_dumps_line_break():
+    data = {'a': [1, 2], 'b': 'x\ny\n'}
+    dumped = ryaml.dumps(data, line_break='\r\n', literal_multiline=True)
+    assert dumped == 'a:\r\n- 1\r\n- 2\r\nb: |\r\n  x\r\n  y\r\n'
+    assert ryaml.loads(dumped) == data
+    assert ryaml.dumps(1, line_break='\r\n', document_end='always') == '1\r\n...\r\n'
+    assert ryaml.dumps({'a': 1}, line_break='\r') == 'a: 1\r'
+
+def test_dumps_line_break_folded():
+    dumped = ryaml.dumps({'s': 'x\ny\n'}, line_break='\r\n', styles={'s': 'folded'})
+    assert dumped == 's: >\r\n  x\r\n\r\n  y\r\n'
+
+def test_dumps_line_break_trimmed_ends():
+    assert ryaml.dumps('a', line_break='\r\n', final_newline=False) == 'a'
+    assert ryaml.dumps_fragment({'a': 1, 'b': 2}, indent_level=2, line_break='\r\n') == '  a: 1\r\n  b: 2\r\n'
+
+def test_dumps_invalid_line_break():
+    with pytest.raises(ValueError, match='unsupported line_break'):
+        ryaml.dumps(1, line_break='\n\r')
+
+def test_dumps_encoding_returns_bytes():
+    assert ryaml.dumps({'a': 1}, encoding='utf-8') == b'a: 1\n'
+    assert ryaml.dumps({'a': 1}, encoding='utf-16-le') == '\ufeffa: 1\n'.encode('utf-16-le')
+    assert ryaml.dumps({'a': 1}, encoding='utf-16-be') == '\ufeffa: 1\n'.encode('utf-16-be')
+    assert ryaml.dumps_many([1, 'x'], encoding='utf-8') == [b'1\n', b'x\n']
+
+def test_dumps_encoding_round_trip():
+    data = {'a': [1, 'two'], 'b': None}
+    assert ryaml.loads(ryaml.dumps(data, encoding='utf-16-be')) == data
+
+def test_dump_encoding_writes_bytes():
+    stream = io.BytesIO()
+    ryaml.dump(stream, {'a': 1}, encoding='utf-16-le')
+    assert stream.getvalue() == '\ufeffa: 1\n'.encode('utf-16-le')
+
+def test_dumps_encoding_errors():
+    with pytest.raises(ValueError, match="unsupported encoding 'latin-1'"):
+        ryaml.dumps(1, encoding='latin-1')
+    with pytest.raises(ValueError, match='need UTF-8 output'):
+        ryaml.dumps(1, encoding='utf-16-le', final_newline=False)
+    with pytest.raises(ValueError, match="doesn't take an encoding"):
+        ryaml.dumps_fragment(1, encoding='utf-8')
+
+def test_dumper_version_directive():
+    assert yaml.dump({'a': 1}, Dumper=RSafeDumper, version=(1, 1)) == '%YAML 1.1\n---\na: 1\n'
+
+def test_dumper_tag_directives():
+    data = {'a': ryaml.TaggedValue('tag:example.com,2000:app/foo', 'x')}
+    tags = {'!e!': 'tag:example.com,2000:app/'}
+    assert yaml.dump(data, Dumper=RSafeDumper, tags=tags) == (
+        '%TAG !e! tag:example.com,2000:app/\n---\na: !e!foo x\n'
+    )
+
+
+def test_dumper_reset_starts_a_new_stream():
+    first, second = io.StringIO(), io.StringIO()
+    dumper = RSafeDumper(first)
+    dumper.open()
+    dumper.represent({'a': [1, 2]})
+    dumper.close()
+    dumper.reset(second)
+    dumper.open()
+    dumper.represent(['b'])
+    dumper.close()
+    assert ryaml.loads(first.getvalue()) == {'a': [1, 2]}
+    assert second.getvalue() == '- b\n'
+
+
+def test_repeated_dumps_are_independent():
+    shared = [1]
+    for i in range(50):
+        assert ryaml.loads(ryaml.dumps({'x': shared, 'y': shared, 'i': i})) == {
+            'x': [1], 'y': [1], 'i': i
+        }
+
+
+def test_dumps_called_from_default_hook():
+    class Point:
+        pass
+
+    text = ryaml.dumps({'p': Point()}, default=lambda value: ryaml.dumps([1, 2]))
+    assert ryaml.loads(text) == {'p': '- 1\n- 2\n'}
+
+def test_dumps_allow_unicode():
+    data = {'name': 'café', 'emoji': '😀'}
+    assert ryaml.dumps(data) == 'name: café\nemoji: 😀\n'
+    escaped = ryaml.dumps(data, allow_unicode=False)
+    assert escaped.isascii()
+    assert ryaml.loads(escaped) == data
+    assert ryaml.dumps(data, encoding='utf-8') == 'name: café\nemoji: 😀\n'.encode()
+
+def test_dumps_large_documents():
+    shared = {'name': 'x' * 100}
+    data = [{'i': i, 'shared': shared} for i in range(20000)]
+    dumped = ryaml.dumps(data)
+    assert dumped == yaml.dump(data, Dumper=yaml.SafeDumper, sort_keys=False)
+    assert ryaml.dumps_many([data, data[:2]]) == [dumped, ryaml.dumps(data[:2])]
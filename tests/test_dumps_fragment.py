@@ -0,0 +1,46 @@
+import ryaml
+
+
+def test_scalar_inline():
+    assert ryaml.dumps_fragment('web', indent_level=4) == 'web'
+    assert ryaml.dumps_fragment(80, indent_level=4) == '80'
+
+
+def test_scalar_needing_quotes():
+    assert ryaml.dumps_fragment('yes') == "'yes'"
+
+
+def test_block_mapping_indented():
+    fragment = ryaml.dumps_fragment({'a': 1, 'b': [1, 2]}, indent_level=2)
+    assert fragment == '  a: 1\n  b:\n  - 1\n  - 2\n'
+
+
+def test_block_sequence_indented():
+    assert ryaml.dumps_fragment(['x', 'y'], indent_level=4) == '    - x\n    - y\n'
+
+
+def test_empty_collections_inline():
+    assert ryaml.dumps_fragment({}, indent_level=2) == '{}'
+    assert ryaml.dumps_fragment([], indent_level=2) == '[]'
+
+
+def test_no_document_framing():
+    fragment = ryaml.dumps_fragment({'a': 1})
+    assert not fragment.startswith('---')
+    assert '...' not in fragment
+
+
+def test_fragment_inserts_into_document():
+    template = 'spec:\n  replicas: {replicas}\n  selector:\n{selector}  name: {name}\n'
+    text = template.format(
+        replicas=ryaml.dumps_fragment(3),
+        selector=ryaml.dumps_fragment({'app': 'web', 'tier': 'front'}, indent_level=4),
+        name=ryaml.dumps_fragment('on'),
+    )
+    assert ryaml.loads(text) == {
+        'spec': {
+            'replicas': 3,
+            'selector': {'app': 'web', 'tier': 'front'},
+            'name': 'on',
+        }
+    }
@@ -0,0 +1,27 @@
+import pytest
+
+import ryaml
+
+
+def test_dumps_many_matches_dumps():
+    objs = [{'index': i, 'items': list(range(i))} for i in range(20)] + [None, 'text', [1.5, True]]
+    assert ryaml.dumps_many(objs, max_workers=4) == [ryaml.dumps(obj) for obj in objs]
+
+
+def test_options_apply_to_every_object():
+    assert ryaml.dumps_many([1, 'a'], document_end='always') == ['1\n...\n', 'a\n...\n']
+    assert ryaml.dumps_many([{'a': 1}], final_newline=False) == ['a: 1']
+
+
+def test_empty():
+    assert ryaml.dumps_many([]) == []
+
+
+def test_represent_errors_raise():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps_many([1, object()])
+
+
+def test_zero_workers():
+    with pytest.raises(ValueError, match='max_workers'):
+        ryaml.dumps_many([1], max_workers=0)
@@ -0,0 +1,45 @@
+import pytest
+
+import ryaml
+
+SOURCE = 'a: &x 1\nb: *x\nc: &x 2\nd: *x\n'
+
+
+def test_rebound_by_default():
+    assert ryaml.loads(SOURCE) == {'a': 1, 'b': 1, 'c': 2, 'd': 2}
+
+
+def test_rejected():
+    with pytest.raises(ryaml.ComposerError) as info:
+        ryaml.loads(SOURCE, allow_duplicate_anchors=False)
+    error = info.value
+    assert error.context == "found duplicate anchor 'x'; first occurrence"
+    assert (error.context_mark.line, error.context_mark.column) == (0, 3)
+    assert error.problem == 'second occurrence'
+    assert (error.problem_mark.line, error.problem_mark.column) == (2, 3)
+
+
+@pytest.mark.parametrize('text', [
+    '- &x [1]\n- &x {a: 1}\n',
+    '&x a: &x b\n',
+])
+def test_collections_and_keys(text):
+    with pytest.raises(ryaml.ComposerError, match="duplicate anchor 'x'"):
+        ryaml.loads(text, allow_duplicate_anchors=False)
+
+
+def test_distinct_anchors():
+    assert ryaml.loads('a: &x 1\nb: &y 2\nc: *x\n', allow_duplicate_anchors=False) == {
+        'a': 1, 'b': 2, 'c': 1,
+    }
+
+
+def test_each_document_has_its_own_anchors():
+    assert ryaml.loads_all('--- &x 1\n--- &x 2\n', allow_duplicate_anchors=False) == [1, 2]
+    with pytest.raises(ryaml.ComposerError):
+        ryaml.loads_all('--- &x 1\n--- &x 2\n', allow_duplicate_anchors=False, global_anchors=True)
+
+
+def test_compose():
+    with pytest.raises(ryaml.ComposerError):
+        ryaml.compose(SOURCE, allow_duplicate_anchors=False)
@@ -0,0 +1,61 @@
+import io
+
+import pytest
+
+import ryaml
+
+DOC = 'name: café\nitems: [1, 2]\n'
+DATA = {'name': 'café', 'items': [1, 2]}
+
+
+@pytest.mark.parametrize('encoding', [
+    'utf-8', 'utf-8-sig', 'utf-16', 'utf-16-le', 'utf-16-be', 'utf-32', 'utf-32-le', 'utf-32-be',
+])
+def test_loads_detects_encoding(encoding):
+    assert ryaml.loads(DOC.encode(encoding)) == DATA
+    assert ryaml.loads_all(DOC.encode(encoding)) == [DATA]
+    assert list(ryaml.loads_iter(DOC.encode(encoding))) == [DATA]
+
+
+@pytest.mark.parametrize('encoding', ['utf-8-sig', 'utf-16', 'utf-16-be'])
+def test_load_binary_file(encoding):
+    assert ryaml.load(io.BytesIO(DOC.encode(encoding))) == DATA
+
+
+@pytest.mark.parametrize('wrap', [bytearray, memoryview])
+def test_loads_binary_buffers(wrap):
+    data = DOC.encode('utf-16')
+    assert ryaml.loads(wrap(data)) == DATA
+    assert ryaml.loads_all(wrap(data)) == [DATA]
+    assert ryaml.compose(wrap(data)) is not None
+
+
+def test_loads_memoryview_slice():
+    data = b'xx' + DOC.encode('utf-8') + b'xx'
+    assert ryaml.loads(memoryview(data)[2:-2]) == DATA
+
+
+def test_loader_takes_bytes():
+    from ryaml.compat import RSafeLoader
+
+    loader = RSafeLoader(DOC.encode('utf-16-be'))
+    assert loader.get_single_data() == DATA
+
+
+def test_invalid_utf8():
+    with pytest.raises(ryaml.ReaderError, match='invalid UTF-8 byte #xff at position 3'):
+        ryaml.loads(b'a: \xff\n')
+
+
+def test_invalid_utf16():
+    with pytest.raises(ryaml.ReaderError, match='odd number of bytes'):
+        ryaml.loads('a: 1\n'.encode('utf-16-le') + b'!')
+    with pytest.raises(ryaml.ReaderError, match='surrogate #xd800 at position 2'):
+        ryaml.loads(b'a\x00\x00\xd8')
+
+
+def test_invalid_utf32():
+    with pytest.raises(ryaml.ReaderError, match='not a multiple of 4'):
+        ryaml.loads('a: 1\n'.encode('utf-32-le') + b'!')
+    with pytest.raises(ryaml.ReaderError, match='code point #x110000 at position 4'):
+        ryaml.loads(b'a\x00\x00\x00\x00\x00\x11\x00')
@@ -0,0 +1,218 @@
+import pickle
+
+import pytest
+
+import ryaml
+
+
+def test_scanner_error_marks():
+    with pytest.raises(ryaml.ScannerError) as info:
+        ryaml.loads('a: 1\nb: "unterminated\n')
+    error = info.value
+    assert error.problem is not None
+    assert error.problem_mark is not None
+    assert error.problem_mark.line == 2
+    assert error.problem_mark.name == '<unicode string>'
+
+
+def test_parser_error_marks():
+    with pytest.raises(ryaml.ParserError) as info:
+        ryaml.loads('- a\nb: c\n')
+    error = info.value
+    assert error.context is not None
+    assert error.context_mark.line == 0
+    assert error.problem_mark.line == 1
+    assert '  in "<unicode string>", line 2, column 1:\n    b: c\n    ^' in str(error)
+
+
+def test_undefined_alias_mark():
+    with pytest.raises(ryaml.ComposerError) as info:
+        ryaml.loads('a: 1\nb: *missing\n')
+    error = info.value
+    assert error.problem == "found undefined alias 'missing'"
+    assert (error.problem_mark.line, error.problem_mark.column) == (1, 3)
+
+
+def test_single_document_marks():
+    with pytest.raises(ryaml.ComposerError) as info:
+        ryaml.loads('--- 1\n--- 2\n')
+    error = info.value
+    assert error.context == 'expected a single document in the stream'
+    assert error.problem == 'but found another document'
+    assert error.context_mark.line == 0
+    assert error.problem_mark.line == 1
+
+
+def test_constructor_error_mark():
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads('a: 1\nb: !!int nope\n')
+    assert (info.value.problem_mark.line, info.value.problem_mark.column) == (1, 3)
+
+
+def test_error_mark_uses_source_name():
+    with pytest.raises(ryaml.ComposerError) as info:
+        ryaml.compose('*missing\n', name='config.yaml')
+    assert info.value.problem_mark.name == 'config.yaml'
+
+
+def test_error_line_and_column():
+    with pytest.raises(ryaml.InvalidYamlError) as info:
+        ryaml.loads('name: demo\nitems: [1, 2\n')
+    error = info.value
+    assert error.line == 3
+    assert error.column == 1
+    assert error.snippet is not None and error.snippet.endswith('^')
+
+
+def test_error_snippet_points_at_problem():
+    with pytest.raises(ryaml.InvalidYamlError) as info:
+        ryaml.loads('a: 1\nb: *nope\n')
+    assert (info.value.line, info.value.column) == (2, 4)
+    assert info.value.snippet == 'b: *nope\n   ^'
+
+
+def test_error_hint_keeps_raw_problem():
+    with pytest.raises(ryaml.ParserError) as info:
+        ryaml.loads('a:\n  b: 1\n c: 2\n')
+    error = info.value
+    assert error.problem == 'did not find expected key'
+    assert error.note == (
+        "hint: check for a missing ':' after a key or inconsistent indentation at line 3"
+    )
+    assert str(error).endswith(error.note)
+
+
+def test_error_hint_for_scanner_error():
+    with pytest.raises(ryaml.ScannerError) as info:
+        ryaml.loads('a: b: c\n')
+    assert info.value.problem == 'mapping values are not allowed in this context'
+    assert info.value.note.startswith('hint: ')
+
+
+def test_uncommon_error_has_no_hint():
+    with pytest.raises(ryaml.ComposerError) as info:
+        ryaml.loads('*missing\n')
+    assert info.value.note is None
+
+
+@pytest.mark.parametrize('tag,suggestion', [
+    ('!!stR', '!!str'),
+    ('!!flaot', '!!float'),
+    ('!str', '!!str'),
+    ('!!timestmp', '!!timestamp'),
+])
+def test_tag_typo_suggestion(tag, suggestion):
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads(f'a: 1\nb: {tag} x\n')
+    error = info.value
+    assert error.problem.startswith('could not determine a constructor for the tag')
+    assert error.note == f"did you mean '{suggestion}'?"
+    assert (error.problem_mark.line, error.problem_mark.column) == (1, 3)
+
+
+def test_tag_typo_on_collection():
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads('!!mapp {a: 1}')
+    assert info.value.note == "did you mean '!!map'?"
+
+
+def test_unrelated_unknown_tag_still_loads():
+    assert ryaml.loads('!Environment prod') == 'prod'
+
+
+def test_native_error_classes():
+    import ryaml.error
+    from ryaml import _ryaml
+
+    assert ryaml.error.ScannerError is _ryaml.ScannerError is ryaml.ScannerError
+    for cls in (ryaml.ScannerError, ryaml.ParserError, ryaml.ComposerError, ryaml.ConstructorError):
+        assert issubclass(cls, ryaml.MarkedYAMLError)
+    for cls in (ryaml.EmitterError, ryaml.SerializerError, ryaml.RepresenterError, ryaml.ReaderError):
+        assert not issubclass(cls, ryaml.MarkedYAMLError)
+        assert issubclass(cls, ryaml.YAMLError)
+    assert issubclass(ryaml.YAMLError, ryaml.InvalidYamlError)
+    assert issubclass(ryaml.InvalidYamlError, ValueError)
+
+
+def test_scanner_error_context():
+    with pytest.raises(ryaml.ScannerError) as info:
+        ryaml.loads('a: 1\nb: "unterminated\n')
+    error = info.value
+    assert error.context == 'while scanning a quoted scalar'
+    assert (error.context_mark.line, error.context_mark.column) == (1, 3)
+    assert error.problem == 'found unexpected end of stream'
+
+
+@pytest.mark.parametrize('text, cls', [
+    ('a: b: c\n', ryaml.ScannerError),
+    ('key: @value\n', ryaml.ScannerError),
+    ('- a\nb: c\n', ryaml.ParserError),
+    ('[a, b\n', ryaml.ParserError),
+    ('a: 1\nb: *missing\n', ryaml.ComposerError),
+])
+def test_error_classes(text, cls):
+    with pytest.raises(cls) as info:
+        ryaml.loads(text)
+    assert type(info.value) is cls
+    assert info.value.problem_mark is not None
+
+
+def test_error_from_python_constructor():
+    def construct(loader, node):
+        raise ryaml.ConstructorError('bad value')
+
+    ryaml.add_constructor('!bad-native-error', construct)
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads('a: !bad-native-error x\n')
+    error = info.value
+    assert error.problem == 'bad value'
+    assert (error.line, error.column) == (1, 4)
+    assert str(error).startswith('bad value\n  in "<unicode string>", line 1, column 4')
+
+
+def test_compat_catches_errors():
+    from ryaml import compat
+
+    with pytest.raises(compat.YAMLError):
+        compat.safe_load('a: b: c\n')
+    with pytest.raises(compat.MarkedYAMLError):
+        compat.safe_load('- a\nb: c\n')
+
+
+def test_parse_error_marks():
+    with pytest.raises(ryaml.ParserError) as info:
+        ryaml.parse('- a\nb: c\n')
+    error = info.value
+    assert error.context_mark.line == 0
+    assert error.problem_mark.line == 1
+    assert error.snippet == 'b: c\n^'
+
+
+@pytest.mark.parametrize('text, error_type', [
+    ('a: "unterminated\n', ryaml.ScannerError),
+    ('- a\nb: c\n', ryaml.ParserError),
+    ('a: *missing\n', ryaml.ComposerError),
+    ('a: !!int x\n', ryaml.ConstructorError),
+])
+def test_errors_pickle(text, error_type):
+    with pytest.raises(error_type) as info:
+        ryaml.loads(text)
+    error = info.value
+    copy = pickle.loads(pickle.dumps(error))
+    assert type(copy) is error_type
+    assert str(copy) == str(error)
+    for attribute in ('context', 'problem', 'note', 'line', 'column', 'snippet'):
+        assert getattr(copy, attribute) == getattr(error, attribute)
+    assert (copy.problem_mark.line, copy.problem_mark.column) == (
+        error.problem_mark.line, error.problem_mark.column
+    )
+    assert str(copy.problem_mark) == str(error.problem_mark)
+
+
+def test_unmarked_errors_pickle():
+    with pytest.raises(ryaml.RepresenterError) as info:
+        ryaml.dumps(object())
+    copy = pickle.loads(pickle.dumps(info.value))
+    assert type(copy) is ryaml.RepresenterError
+    assert copy.args == info.value.args
+    assert isinstance(pickle.loads(pickle.dumps(ryaml.InvalidYamlError('bad'))), ryaml.InvalidYamlError)
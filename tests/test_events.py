@@ -0,0 +1,63 @@
+import io
+
+import ryaml
+from ryaml._ryaml import _REmitter, ScalarEvent, MappingStartEvent, DocumentStartEvent
+
+
+def test_scalar_event_attributes():
+    event = ScalarEvent(None, None, (True, False), 'value')
+    assert event.anchor is None
+    assert event.implicit == (True, False)
+    assert event.value == 'value'
+    assert event.start_mark is None
+    assert event.style is None
+
+def test_collection_event_attributes():
+    event = MappingStartEvent('a', 'tag:yaml.org,2002:map', True, flow_style=True)
+    assert event.anchor == 'a'
+    assert event.flow_style is True
+
+def test_document_event_attributes():
+    event = DocumentStartEvent(explicit=True, version=(1, 1), tags={'!e!': 'tag:example.com,2000:'})
+    assert event.version == (1, 1)
+    assert event.tags == {'!e!': 'tag:example.com,2000:'}
+
+def test_parse_emit_round_trip():
+    events = ryaml.parse('key: [1, 2]\n')
+    assert type(events[0]).__name__ == 'StreamStartEvent'
+    assert type(events[-1]).__name__ == 'StreamEndEvent'
+    assert ryaml.loads(ryaml.emit(events)) == {'key': [1, 2]}
+
+def test_parse_filter_emit():
+    events = [
+        e for e in ryaml.parse('a: 1\nb: 2\n')
+        if not (isinstance(e, ScalarEvent) and e.value in ('b', '2'))
+    ]
+    assert ryaml.loads(ryaml.emit(events)) == {'a': 1}
+
+def test_emitter_writes_stream():
+    stream = io.StringIO()
+    emitter = _REmitter(stream)
+    for event in ryaml.parse('- x\n'):
+        emitter.emit(event)
+    assert ryaml.loads(stream.getvalue()) == ['x']
+
+def test_parse_marks():
+    source = 'a: 1\n---\nb: [2, 3]\n'
+    events = ryaml.parse(source)
+    scalar = next(e for e in events if isinstance(e, ScalarEvent) and e.value == 'b')
+    assert (scalar.start_mark.line, scalar.start_mark.column) == (2, 0)
+    assert source[scalar.start_mark.index:scalar.end_mark.index] == 'b'
+    assert 'b: [2, 3]' in scalar.start_mark.get_snippet()
+    assert all(e.start_mark is not None and e.end_mark is not None for e in events)
+
+def test_document_source_from_marks():
+    source = 'a: 1\n---\nb: [2, 3]\n'
+    starts = [e for e in ryaml.parse(source) if isinstance(e, DocumentStartEvent)]
+    assert source[starts[1].start_mark.index:] == '---\nb: [2, 3]\n'
+
+def test_push_parser_marks():
+    parser = ryaml.PushParser()
+    events = parser.feed(b'a: 1\n') + parser.feed(b'b: 2\n') + parser.finish()
+    scalar = next(e for e in events if isinstance(e, ScalarEvent) and e.value == 'b')
+    assert (scalar.start_mark.line, scalar.start_mark.column) == (1, 0)
@@ -0,0 +1,96 @@
+import pytest
+
+import ryaml
+
+
+@pytest.fixture(autouse=True)
+def environ(monkeypatch):
+    monkeypatch.setenv('RYAML_TEST_HOST', 'db.internal')
+    monkeypatch.setenv('RYAML_TEST_PORT', '5432')
+    monkeypatch.setenv('RYAML_TEST_EMPTY', '')
+    monkeypatch.delenv('RYAML_TEST_UNSET', raising=False)
+
+
+def test_off_by_default():
+    assert ryaml.loads('host: ${RYAML_TEST_HOST}') == {'host': '${RYAML_TEST_HOST}'}
+
+
+def test_expand():
+    text = 'url: postgres://${RYAML_TEST_HOST}:${RYAML_TEST_PORT}/app\n'
+    assert ryaml.loads(text, expand_env=True) == {'url': 'postgres://db.internal:5432/app'}
+
+
+def test_resolved_after_expansion():
+    assert ryaml.loads('port: ${RYAML_TEST_PORT}', expand_env=True) == {'port': 5432}
+    assert ryaml.loads('port: "${RYAML_TEST_PORT}"', expand_env=True) == {'port': '5432'}
+
+
+def test_defaults():
+    text = '''\
+a: ${RYAML_TEST_UNSET:-fallback}
+b: ${RYAML_TEST_EMPTY:-fallback}
+c: ${RYAML_TEST_EMPTY-fallback}
+d: ${RYAML_TEST_HOST:-fallback}
+e: ${RYAML_TEST_UNSET:-}
+'''
+    assert ryaml.loads(text, expand_env=True) == {
+        'a': 'fallback',
+        'b': 'fallback',
+        'c': '',
+        'd': 'db.internal',
+        'e': None,
+    }
+
+
+def test_escape():
+    assert ryaml.loads('a: $${RYAML_TEST_HOST}', expand_env=True) == {'a': '${RYAML_TEST_HOST}'}
+
+
+def test_json_documents():
+    assert ryaml.loads('{"host": "${RYAML_TEST_HOST}"}', expand_env=True) == {'host': 'db.internal'}
+
+
+def test_keys_and_sequences():
+    text = '${RYAML_TEST_HOST}: [${RYAML_TEST_PORT}]'
+    assert ryaml.loads(text, expand_env=True) == {'db.internal': [5432]}
+
+
+def test_environ_changes(monkeypatch):
+    monkeypatch.setenv('RYAML_TEST_HOST', 'other')
+    assert ryaml.loads('${RYAML_TEST_HOST}', expand_env=True) == 'other'
+
+
+def test_unset():
+    with pytest.raises(ryaml.ConstructorError, match="'RYAML_TEST_UNSET' is not set") as info:
+        ryaml.loads('a: 1\nb: ${RYAML_TEST_UNSET}\n', expand_env=True)
+    assert info.value.problem_mark.line == 1
+
+
+def test_allowlist():
+    text = '[${RYAML_TEST_HOST}, ${RYAML_TEST_PORT}]'
+    assert ryaml.loads(text, expand_env=True, env_allowlist=['RYAML_TEST_HOST', 'RYAML_TEST_PORT']) == [
+        'db.internal',
+        5432,
+    ]
+    with pytest.raises(ryaml.ConstructorError, match="'RYAML_TEST_PORT' is not in env_allowlist"):
+        ryaml.loads(text, expand_env=True, env_allowlist={'RYAML_TEST_HOST'})
+
+
+def test_allowlist_needs_expand_env():
+    with pytest.raises(ValueError, match='needs expand_env=True'):
+        ryaml.loads('a', env_allowlist=['HOME'])
+
+
+@pytest.mark.parametrize('text, problem', [
+    ('${RYAML_TEST_HOST', "unterminated '\\$\\{'"),
+    ('${1ABC}', "invalid environment variable name '1ABC'"),
+    ('${}', "invalid environment variable name ''"),
+])
+def test_malformed(text, problem):
+    with pytest.raises(ryaml.ConstructorError, match=problem):
+        ryaml.loads(text, expand_env=True)
+
+
+def test_compose():
+    node = ryaml.compose('${RYAML_TEST_HOST}', expand_env=True)
+    assert node.value == 'db.internal'
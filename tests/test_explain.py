@@ -0,0 +1,34 @@
+import ryaml
+
+
+def test_explain_lists_non_str_resolutions():
+    doc = 'port: 8080\nname: web\ndebug: yes\nversion: "1.0"\ncountry: NO\n'
+    data, resolutions = ryaml.loads(doc, explain=True)
+    assert data == {'port': 8080, 'name': 'web', 'debug': True, 'version': '1.0', 'country': False}
+    assert [(r.value, r.tag) for r in resolutions] == [
+        ('8080', 'tag:yaml.org,2002:int'),
+        ('yes', 'tag:yaml.org,2002:bool'),
+        ('NO', 'tag:yaml.org,2002:bool'),
+    ]
+    assert [(r.mark.line, r.mark.column) for r in resolutions] == [(0, 6), (2, 7), (4, 9)]
+    assert repr(resolutions[1]) == "Resolution(value=\"yes\", tag=\"tag:yaml.org,2002:bool\", line=3, column=8)"
+
+
+def test_explain_skips_explicit_tags():
+    _, resolutions = ryaml.loads('[!!int 1, !!str 2, ~]', explain=True)
+    assert [(r.value, r.tag) for r in resolutions] == [('~', 'tag:yaml.org,2002:null')]
+
+
+def test_explain_json_documents():
+    data, resolutions = ryaml.loads('{"a": [1, true, null]}', explain=True)
+    assert data == {'a': [1, True, None]}
+    assert [r.value for r in resolutions] == ['1', 'true', 'null']
+
+
+def test_explain_follows_schema():
+    _, resolutions = ryaml.loads('[yes, 1]', schema='core', explain=True)
+    assert [r.value for r in resolutions] == ['1']
+
+
+def test_explain_empty_document():
+    assert ryaml.loads('', explain=True) == (None, [])
@@ -0,0 +1,30 @@
+import pytest
+
+import ryaml
+from ryaml import features
+
+
+def test_backends_are_available():
+    for backend in ryaml.BACKENDS:
+        assert ryaml.available(backend)
+        ryaml.require(backend)
+
+
+def test_missing_package(monkeypatch):
+    monkeypatch.setitem(features._PACKAGES, 'testing', 'not_a_real_module_xyz')
+    assert not ryaml.available('testing')
+    with pytest.raises(ryaml.MissingFeatureError, match=r"pip install 'ryaml\[testing\]'") as exc:
+        ryaml.require('testing')
+    assert exc.value.feature == 'testing'
+    assert isinstance(exc.value, ImportError)
+
+
+def test_missing_parent_package(monkeypatch):
+    monkeypatch.setitem(features._PACKAGES, 'differential', 'not_a_real_package_xyz.yaml')
+    assert not ryaml.available('differential')
+
+
+def test_unknown_feature():
+    with pytest.raises(ValueError, match="unknown ryaml feature 'query'"):
+        ryaml.require('query')
+    assert 'testing' in features.features()
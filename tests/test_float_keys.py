@@ -0,0 +1,29 @@
+import math
+
+import pytest
+
+import ryaml
+
+
+def test_load_float_keys():
+    assert ryaml.loads('1.5: a\n.inf: b\n-.inf: c\n') == {1.5: 'a', math.inf: 'b', -math.inf: 'c'}
+
+def test_load_nan_key():
+    [(key, value)] = ryaml.loads('.nan: a\n').items()
+    assert math.isnan(key)
+    assert value == 'a'
+
+def test_load_nan_key_rejected():
+    with pytest.raises(ryaml.ConstructorError, match='NaN mapping key'):
+        ryaml.loads('.nan: a\n', allow_nan_keys=False)
+    assert ryaml.loads('1.5: a\n', allow_nan_keys=False) == {1.5: 'a'}
+
+def test_float_keys_round_trip():
+    data = {1.5: 'a', 1e20: 'b', -0.0: 'c', math.inf: 'd', -math.inf: 'e'}
+    assert ryaml.loads(ryaml.dumps(data)) == data
+
+def test_nan_key_round_trip():
+    dumped = ryaml.dumps({math.nan: 1})
+    assert dumped == '.nan: 1\n'
+    [key] = ryaml.loads(dumped)
+    assert math.isnan(key)
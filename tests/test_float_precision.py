@@ -0,0 +1,54 @@
+import warnings
+
+import pytest
+
+import ryaml
+
+
+@pytest.mark.parametrize('value', ['1.5', '-2.25', '0.0', '1000.125', '1e3', '1.25e-1', '.inf', '9007199254740992.0'])
+def test_exact_floats_load(value):
+    assert ryaml.loads(f'- {value}', float_precision='error', schema='core') == [
+        float(value),
+    ]
+
+
+@pytest.mark.parametrize('value', ['0.1', '19.99', '9007199254740993.0', '1e400'])
+def test_inexact_floats_raise(value):
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads(f'price: {value}', float_precision='error', schema='core')
+    assert info.value.problem == f"float '{value}' cannot be represented exactly"
+    assert (info.value.problem_mark.line, info.value.problem_mark.column) == (0, 7)
+
+
+def test_inexact_floats_warn():
+    with pytest.warns(UserWarning, match="float '19.99' cannot be represented exactly"):
+        assert ryaml.loads('price: 19.99', float_precision='warn') == {'price': 19.99}
+
+
+def test_ignored_by_default():
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        assert ryaml.loads('price: 19.99') == {'price': 19.99}
+
+
+def test_quoted_values_are_not_checked():
+    assert ryaml.loads('price: "19.99"', float_precision='error') == {'price': '19.99'}
+
+
+def test_json_documents_are_checked():
+    with pytest.raises(ryaml.ConstructorError):
+        ryaml.loads('{"price": 19.99}', float_precision='error')
+    assert ryaml.loads('{"price": 19.5}', float_precision='error') == {'price': 19.5}
+
+
+def test_unknown_mode():
+    with pytest.raises(ValueError):
+        ryaml.loads('1.5', float_precision='strict')
+
+
+def test_warning_keeps_text_around_nul():
+    with pytest.warns(UserWarning) as record:
+        ryaml.loads('price: 19.99', float_precision='warn', name='prices\0.yaml')
+    message = str(record[0].message)
+    assert "float '19.99' cannot be represented exactly" in message
+    assert 'prices\\0.yaml' in message
@@ -0,0 +1,32 @@
+import ryaml
+from ryaml.containers import StyledDict, StyledList
+
+SOURCE = 'name: web\nports: [80, 443]\nlabels: {app: web}\nhosts:\n- a\n- b\n'
+
+
+def test_plain_containers_by_default():
+    data = ryaml.loads(SOURCE)
+    assert type(data) is dict
+    assert type(data['ports']) is list
+
+
+def test_styled_containers():
+    data = ryaml.loads(SOURCE, preserve_flow_style=True)
+    assert isinstance(data, StyledDict)
+    assert data.flow_style is False
+    assert isinstance(data['ports'], StyledList)
+    assert data['ports'].flow_style is True
+    assert data['labels'].flow_style is True
+    assert data['hosts'].flow_style is False
+    assert data == {'name': 'web', 'ports': [80, 443], 'labels': {'app': 'web'}, 'hosts': ['a', 'b']}
+
+
+def test_round_trip_keeps_flow_style():
+    data = ryaml.loads(SOURCE, preserve_flow_style=True)
+    assert ryaml.dumps(data) == SOURCE
+
+
+def test_round_trip_after_edit():
+    data = ryaml.loads(SOURCE, preserve_flow_style=True)
+    data['ports'].append(8080)
+    assert 'ports: [80, 443, 8080]\n' in ryaml.dumps(data)
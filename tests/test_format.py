@@ -0,0 +1,62 @@
+import pytest
+
+import ryaml
+
+
+def test_block_style():
+    assert ryaml.format('a:   {b: 1,   c: [1,2]}\n') == 'a:\n  b: 1\n  c:\n  - 1\n  - 2\n'
+
+
+def test_preserve_flow_style():
+    assert ryaml.format('a:   {b: 1,   c: [1,2]}\n', preserve_flow_style=True) == 'a: {b: 1, c: [1, 2]}\n'
+
+
+def test_indent():
+    assert ryaml.format('a:\n b:\n  c: 1\n', indent=4) == 'a:\n    b:\n        c: 1\n'
+
+
+def test_width():
+    text = 'a: ' + ' '.join(['word'] * 30) + '\n'
+    formatted = ryaml.format(text, width=40)
+    assert max(len(line) for line in formatted.splitlines()) <= 45
+    assert ryaml.loads(formatted) == ryaml.loads(text)
+
+
+def test_quotes():
+    assert ryaml.format('a: "x"\nb: \'yes\'\nc: "tab\\there"\nd: plain\n') == (
+        "a: 'x'\nb: 'yes'\nc: \"tab\\there\"\nd: plain\n"
+    )
+
+
+def test_comments():
+    text = '# top\na:   1 # one\n\nb:\n    c: 2   # two\n'
+    assert ryaml.format(text) == '# top\na: 1  # one\n\nb:\n  c: 2  # two\n'
+    assert ryaml.format(text, comments=False) == 'a: 1\nb:\n  c: 2\n'
+
+
+def test_documents_tags_and_anchors():
+    text = '--- !t\na: &x [1]\nb: *x\n...\n---\n- !!str 1\n'
+    formatted = ryaml.format(text)
+    assert '&x' in formatted and '*x' in formatted and '!t' in formatted
+    assert [repr(e) for e in ryaml.parse(formatted) if 'Scalar' in repr(e)] == [
+        repr(e) for e in ryaml.parse(text) if 'Scalar' in repr(e)
+    ]
+    assert len(ryaml.compose_all(formatted)) == 2
+
+
+def test_idempotent():
+    text = 'x:\n  - {a: 1}\n  - "q"\n# c\ny: |\n  block\n  text\n'
+    once = ryaml.format(text)
+    assert ryaml.format(once) == once
+
+
+def test_bytes():
+    assert ryaml.format('a: é\n'.encode('utf-16')) == 'a: é\n'
+
+
+def test_errors():
+    with pytest.raises(ryaml.ParserError):
+        ryaml.format('a: 1\n- b\n')
+    with pytest.raises(ryaml.ScannerError) as excinfo:
+        ryaml.format('a: "x\n')
+    assert excinfo.value.problem_mark is not None
@@ -0,0 +1,71 @@
+import json
+
+import pytest
+
+import ryaml
+
+
+def test_from_json():
+    assert ryaml.from_json('{"name": "web", "ports": [80, 443], "debug": false}') == (
+        'name: web\nports:\n- 80\n- 443\ndebug: false\n'
+    )
+
+
+def test_matches_dumps():
+    text = json.dumps({
+        'a': [1, 2.5, None, True, 'x'],
+        'b': {'nested': {'deep': []}, 'empty': {}},
+        'c': 'multi\nline',
+        'd': '',
+        'e': -0.0,
+    })
+    assert ryaml.from_json(text) == ryaml.dumps(json.loads(text))
+
+
+def test_round_trip():
+    text = '{"s": "true", "n": "1.5", "k": "null", "big": 123456789012345678901234567890}'
+    assert ryaml.loads(ryaml.from_json(text)) == json.loads(text)
+
+
+def test_exponents():
+    assert ryaml.loads(ryaml.from_json('[1e5, 2.5E-3, -1e400]')) == [1e5, 2.5e-3, float('-inf')]
+
+
+def test_escapes():
+    text = r'["a\"b", "é", "😀", "tab\there"]'
+    assert ryaml.loads(ryaml.from_json(text)) == json.loads(text)
+
+
+def test_duplicate_keys():
+    assert ryaml.from_json('{"a": 1, "b": 2, "a": 3}') == 'a: 3\nb: 2\n'
+
+
+def test_scalar_document():
+    assert ryaml.loads(ryaml.from_json('"text"')) == 'text'
+    assert ryaml.loads(ryaml.from_json('null')) is None
+
+
+def test_dump_options():
+    assert ryaml.from_json('{"a": "b"}', default_style='double') == '"a": "b"\n'
+    assert ryaml.from_json('{"a": 1}', encoding='utf-8') == b'a: 1\n'
+    with pytest.raises(ryaml.RepresenterError, match='max_items=1'):
+        ryaml.from_json('[1, 2]', max_items=1)
+
+
+def test_nan():
+    assert ryaml.from_json('[NaN, Infinity]') == '- .nan\n- .inf\n'
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.from_json('[NaN]', allow_nan=False)
+
+
+@pytest.mark.parametrize('text, problem', [
+    ('{"a": 1,}', "expected a string key at line 1, column 9"),
+    ('[1 2]', "expected ',' or ']' at line 1, column 4"),
+    ('{"a": 1}\n{}', 'extra data after the value at line 2, column 1'),
+    ('"abc', 'unterminated string'),
+    ('[01]', "expected ',' or ']'"),
+    ('', 'unexpected end of input'),
+])
+def test_invalid_json(text, problem):
+    with pytest.raises(ValueError, match=problem):
+        ryaml.from_json(text)
@@ -0,0 +1,91 @@
+import collections
+import os.path
+
+import pytest
+import yaml
+
+import ryaml
+from ryaml.compat import RFullLoader, RSafeLoader
+
+
+def load(s):
+    return RFullLoader(s).get_single_data()
+
+
+def test_builtin_types():
+    doc = '''
+tuple: !!python/tuple [1, [2, 3]]
+complex: !!python/complex 1+2j
+none: !!python/none ''
+bool: !!python/bool true
+str: !!python/str 12
+unicode: !!python/unicode héllo
+bytes: !!python/bytes aGk=
+int: !!python/int 7
+long: !!python/long 8
+float: !!python/float 1.5
+list: !!python/list [1]
+dict: !!python/dict {a: 1}
+'''
+    assert load(doc) == {
+        'tuple': (1, [2, 3]),
+        'complex': 1 + 2j,
+        'none': None,
+        'bool': True,
+        'str': '12',
+        'unicode': 'héllo',
+        'bytes': b'hi',
+        'int': 7,
+        'long': 8,
+        'float': 1.5,
+        'list': [1],
+        'dict': {'a': 1},
+    }
+
+
+def test_tuple_keys_and_anchors():
+    data = load('a: &t !!python/tuple [1, 2]\nb: *t\n')
+    assert data['a'] == (1, 2)
+    assert data['a'] is data['b']
+
+
+def test_names_and_modules():
+    data = load('- !!python/name:os.path.join\n- !!python/module:collections\n- !!python/name:len\n')
+    assert data == [os.path.join, collections, len]
+
+
+def test_name_requires_imported_module():
+    with pytest.raises(ryaml.ConstructorError, match="module 'this_module_does_not_exist' is not imported"):
+        load('!!python/name:this_module_does_not_exist.x')
+
+
+def test_name_must_exist():
+    with pytest.raises(ryaml.ConstructorError, match="cannot find 'nope' in the module 'os'"):
+        load('!!python/name:os.nope')
+
+
+def test_name_value_must_be_empty():
+    with pytest.raises(ryaml.ConstructorError, match='expected the empty value'):
+        load('!!python/name:len x')
+
+
+def test_objects_are_not_constructed():
+    with pytest.raises(ryaml.ConstructorError) as info:
+        load('!!python/object:collections.OrderedDict {}')
+    assert info.value.problem == (
+        "could not determine a constructor for the tag 'tag:yaml.org,2002:python/object:collections.OrderedDict'"
+    )
+
+
+def test_wrong_node_kind():
+    with pytest.raises(ryaml.ConstructorError, match='expected a sequence node, but found scalar'):
+        load('!!python/tuple abc')
+
+
+def test_safe_loading_is_unaffected():
+    assert ryaml.loads('!!python/tuple [1, 2]') == [1, 2]
+    assert RSafeLoader('!!python/tuple [1, 2]').get_single_data() == [1, 2]
+
+
+def test_pyyaml_full_load():
+    assert yaml.load('!!python/tuple [1, 2]', Loader=RFullLoader) == (1, 2)
@@ -0,0 +1,53 @@
+import datetime
+
+import pytest
+
+import ryaml
+
+
+def test_formatting_ignored():
+    a = ryaml.content_hash('a: 1\nb: [x, "y"]\n')
+    assert a == ryaml.content_hash("b:\n  - x\n  - 'y'\na:   1  # one\n")
+    assert len(a) == 64
+
+
+def test_objects_and_text_agree():
+    assert ryaml.content_hash('a: [1, 2.5, null, true]\n') == ryaml.content_hash(
+        {'a': [1, 2.5, None, True]}
+    )
+
+
+def test_values_differ():
+    assert ryaml.content_hash('a: 1\n') != ryaml.content_hash('a: "1"\n')
+    assert ryaml.content_hash('a: 1\n') != ryaml.content_hash('a: true\n')
+    assert ryaml.content_hash('[a, b]\n') != ryaml.content_hash('[b, a]\n')
+    assert ryaml.content_hash({'a': ['b']}) != ryaml.content_hash({'a': 'b'})
+
+
+def test_key_order():
+    assert ryaml.content_hash({'a': 1, 'b': 2}) == ryaml.content_hash({'b': 2, 'a': 1})
+    assert ryaml.content_hash({'a': 1, 'b': 2}, sort_keys=False) != ryaml.content_hash(
+        {'b': 2, 'a': 1}, sort_keys=False
+    )
+
+
+def test_algorithms():
+    blake3 = ryaml.content_hash('a: 1\n')
+    sha256 = ryaml.content_hash('a: 1\n', algorithm='sha256')
+    assert blake3 != sha256 and len(sha256) == 64
+    with pytest.raises(ValueError, match='unsupported hash algorithm'):
+        ryaml.content_hash('a: 1\n', algorithm='md5')
+
+
+def test_timestamps_and_sets():
+    assert ryaml.content_hash('d: 2024-01-02\n') == ryaml.content_hash({'d': datetime.date(2024, 1, 2)})
+    assert ryaml.content_hash('!!set {a, b}\n') == ryaml.content_hash({'b', 'a'})
+
+
+def test_unhashable():
+    recursive = []
+    recursive.append(recursive)
+    with pytest.raises(ValueError, match='recursive'):
+        ryaml.content_hash(recursive)
+    with pytest.raises(TypeError, match='cannot hash'):
+        ryaml.content_hash([object()])
@@ -0,0 +1,97 @@
+import os
+
+import pytest
+
+import ryaml
+
+
+@pytest.fixture
+def config(tmp_path):
+    (tmp_path / 'services').mkdir()
+    (tmp_path / 'services' / 'web.yaml').write_text('name: web\nports: !include ports.yaml\n')
+    (tmp_path / 'services' / 'ports.yaml').write_text('[80, 443]\n')
+    (tmp_path / 'empty.yaml').write_text('')
+    return tmp_path
+
+
+def test_include(config):
+    text = 'services:\n- !include services/web.yaml\n'
+    assert ryaml.loads(text, include_dir=config) == {
+        'services': [{'name': 'web', 'ports': [80, 443]}],
+    }
+
+
+def test_off_by_default(config):
+    assert ryaml.loads('!include services/web.yaml') == 'services/web.yaml'
+
+
+def test_str_include_dir(config):
+    assert ryaml.loads('!include services/ports.yaml', include_dir=str(config)) == [80, 443]
+
+
+def test_empty_file(config):
+    assert ryaml.loads('a: !include empty.yaml', include_dir=config) == {'a': None}
+
+
+def test_anchor(config):
+    text = 'a: &ports !include services/ports.yaml\nb: *ports\n'
+    loaded = ryaml.loads(text, include_dir=config)
+    assert loaded['b'] is loaded['a']
+
+
+def test_compose(config):
+    node = ryaml.compose('a: !include services/ports.yaml', include_dir=config)
+    included = node.value[0][1]
+    assert included.tag == 'tag:yaml.org,2002:seq'
+    assert included.start_mark.name.endswith('ports.yaml')
+
+
+def test_outside_include_dir(config):
+    (config.parent / 'secret.yaml').write_text('password: hunter2\n')
+    with pytest.raises(ryaml.ConstructorError, match='outside include_dir'):
+        ryaml.loads('!include ../secret.yaml', include_dir=config)
+    with pytest.raises(ryaml.ConstructorError, match='outside include_dir'):
+        ryaml.loads(f'!include {config.parent / "secret.yaml"}', include_dir=config)
+
+
+@pytest.mark.skipif(not hasattr(os, 'symlink'), reason='needs symlinks')
+def test_symlink_outside_include_dir(config):
+    (config.parent / 'secret.yaml').write_text('password: hunter2\n')
+    (config / 'link.yaml').symlink_to(config.parent / 'secret.yaml')
+    with pytest.raises(ryaml.ConstructorError, match='outside include_dir'):
+        ryaml.loads('!include link.yaml', include_dir=config)
+
+
+def test_missing_file(config):
+    with pytest.raises(ryaml.ConstructorError, match="can't include 'nope.yaml'") as info:
+        ryaml.loads('a: 1\nb: !include nope.yaml\n', include_dir=config)
+    assert info.value.problem_mark.line == 1
+
+
+def test_cycle(config):
+    (config / 'a.yaml').write_text('b: !include b.yaml\n')
+    (config / 'b.yaml').write_text('a: !include a.yaml\n')
+    with pytest.raises(ryaml.ConstructorError, match='include cycle: a.yaml -> b.yaml -> a.yaml'):
+        ryaml.loads('!include a.yaml', include_dir=config)
+
+
+def test_depth_limit(config):
+    for depth in range(4):
+        (config / f'level{depth}.yaml').write_text(f'!include level{depth + 1}.yaml\n')
+    (config / 'level4.yaml').write_text('bottom\n')
+    assert ryaml.loads('!include level0.yaml', include_dir=config) == 'bottom'
+    with pytest.raises(ryaml.ConstructorError, match='max_include_depth=3'):
+        ryaml.loads('!include level0.yaml', include_dir=config, max_include_depth=3)
+
+
+def test_error_in_included_file(config):
+    (config / 'broken.yaml').write_text('a: [1\n')
+    with pytest.raises(ryaml.InvalidYamlError) as info:
+        ryaml.loads('!include broken.yaml', include_dir=config)
+    assert 'broken.yaml' in str(info.value)
+
+
+def test_single_document(config):
+    (config / 'many.yaml').write_text('1\n---\n2\n')
+    with pytest.raises(ryaml.ComposerError, match='but found another document'):
+        ryaml.loads('!include many.yaml', include_dir=config)
@@ -0,0 +1,40 @@
+import ryaml
+
+DOC = '''\
+- image: nginx:1.25
+  tier: web
+- image: nginx:1.25
+  tier: 'web'
+- image: redis:7
+  tier: cache
+'''
+
+
+def test_equal_values_shared():
+    items = ryaml.loads(DOC, intern_values=True)
+    assert items[0]['image'] is items[1]['image']
+    assert items[0]['tier'] is items[1]['tier']
+    assert items[0]['image'] is not items[2]['image']
+
+
+def test_off_by_default():
+    items = ryaml.loads(DOC)
+    assert items[0]['image'] == items[1]['image']
+    assert items[0]['image'] is not items[1]['image']
+
+
+def test_only_strs():
+    items = ryaml.loads('- 1000000\n- 1000000\n- "1000000"\n- "1000000"\n', intern_values=True)
+    assert items == [1000000, 1000000, '1000000', '1000000']
+    assert items[2] is items[3]
+
+
+def test_across_documents():
+    first, second = ryaml.loads_all('a: shared value\n---\nb: shared value\n', intern_values=True)
+    assert first['a'] is second['b']
+
+
+def test_json_documents():
+    items = ryaml.loads('[{"a": "x"}, {"a": "x"}]', intern_values=True)
+    assert items == [{'a': 'x'}, {'a': 'x'}]
+    assert items[0]['a'] is items[1]['a']
@@ -0,0 +1,24 @@
+import pytest
+
+import ryaml
+
+
+def test_generators_and_ranges():
+    data = {'squares': (i * i for i in range(3)), 'ids': range(2), 'keys': {'a': 1}.keys()}
+    assert ryaml.dumps(data, iterable_as_sequence=True) == (
+        'squares:\n- 0\n- 1\n- 4\nids:\n- 0\n- 1\nkeys:\n- a\n'
+    )
+
+
+def test_nested_iterables():
+    assert ryaml.dumps(iter([range(2), 'x']), iterable_as_sequence=True) == '- - 0\n  - 1\n- x\n'
+
+
+def test_max_items_applies_to_generators():
+    with pytest.raises(ryaml.RepresenterError, match='has 3 items, more than max_items=2'):
+        ryaml.dumps((i for i in range(3)), iterable_as_sequence=True, max_items=2)
+
+
+def test_iterables_rejected_by_default():
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(range(3))
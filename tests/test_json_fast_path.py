@@ -0,0 +1,62 @@
+import json
+from pathlib import Path
+
+import pytest
+
+import ryaml
+
+API_JSON = Path(__file__).parent.parent / 'benchmark' / 'data' / 'api.json'
+
+
+def load_as_yaml(s, **kwargs):
+    # loads_iter always goes through the YAML parser
+    return list(ryaml.loads_iter(s, **kwargs))[0]
+
+
+@pytest.mark.parametrize('doc', [
+    '{}',
+    '[]',
+    '  {"a": 1, "b": [true, false, null], "c": {"d": -2.5}}\n',
+    '[0, -0, 12, 3.25, -0.5, "x"]',
+    '{"escapes": "q\\" b\\\\ s\\/ \\b\\f\\n\\r\\t \\u00e9 \\u2028"}',
+    '{"unicode": "héllo 世界"}',
+    '{"dup": 1, "dup": 2}',
+    '{"<<": {"a": 1}}',
+    '[\n  {"a": "2001-12-14"},\n  {"b": "yes"}\n]',
+])
+def test_matches_yaml_path(doc):
+    assert ryaml.loads(doc) == load_as_yaml(doc)
+
+
+@pytest.mark.parametrize('doc', [
+    '[1e3, 1.0e+3]',
+    '[.5, +1]',
+    '{"a": 1} # trailing comment',
+    '[1, 2,]',
+    '{a: 1}',
+    '[01]',
+    '--- [1]',
+])
+def test_falls_back_to_yaml(doc):
+    assert ryaml.loads(doc) == load_as_yaml(doc)
+
+
+def test_schema_is_respected():
+    assert ryaml.loads('["1", 1]', schema='failsafe') == ['1', '1']
+    assert ryaml.loads('[1.5]', schema='json') == [1.5]
+
+
+def test_preserve_flow_style():
+    assert ryaml.loads('[1]', preserve_flow_style=True).flow_style is True
+
+
+def test_loads_all():
+    progress = []
+    docs = ryaml.loads_all('{"a": [1]}', progress=lambda *args: progress.append(args))
+    assert docs == [{'a': [1]}]
+    assert progress == [(1, 10)]
+
+
+def test_benchmark_payload():
+    data = API_JSON.read_text(encoding='utf-8')
+    assert ryaml.loads(data) == json.loads(data) == load_as_yaml(data)
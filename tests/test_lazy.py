@@ -0,0 +1,114 @@
+import collections.abc
+import io
+
+import pytest
+
+import ryaml
+
+CONFIG = '''\
+name: bundle
+services:
+  web:
+    image: nginx
+    ports: [80, 443]
+  db:
+    image: postgres
+    env: {POSTGRES_DB: app}
+'''
+
+
+def test_lazy_mapping():
+    data = ryaml.loads_lazy(CONFIG)
+    assert isinstance(data, ryaml.LazyMapping)
+    assert isinstance(data, collections.abc.Mapping)
+    assert len(data) == 2
+    assert data['name'] == 'bundle'
+    assert 'services' in data
+    assert 'missing' not in data
+    assert list(data) == ['name', 'services']
+    assert data.get('missing', 1) == 1
+
+
+def test_nested_values_are_lazy():
+    data = ryaml.loads_lazy(CONFIG)
+    services = data['services']
+    assert isinstance(services, ryaml.LazyMapping)
+    ports = services['web']['ports']
+    assert isinstance(ports, ryaml.LazySequence)
+    assert isinstance(ports, collections.abc.Sequence)
+    assert ports[0] == 80
+    assert ports[-1] == 443
+    assert ports[:] == [80, 443]
+    assert 443 in ports
+    assert list(ports) == [80, 443]
+
+
+def test_values_are_cached():
+    data = ryaml.loads_lazy(CONFIG)
+    assert data['services'] is data['services']
+    assert data['services']['db']['env'] is data['services']['db']['env']
+
+
+def test_missing():
+    data = ryaml.loads_lazy(CONFIG)
+    with pytest.raises(KeyError):
+        data['missing']
+    with pytest.raises(IndexError):
+        data['services']['web']['ports'][2]
+
+
+def test_materialize():
+    data = ryaml.loads_lazy(CONFIG)
+    assert data.materialize() == ryaml.loads(CONFIG)
+    assert data == ryaml.loads(CONFIG)
+    assert data['services']['web']['ports'].materialize() == [80, 443]
+
+
+def test_items():
+    data = ryaml.loads_lazy('a: 1\nb: [2]\n')
+    assert data.keys() == ['a', 'b']
+    assert data.values()[0] == 1
+    assert [key for key, _ in data.items()] == ['a', 'b']
+
+
+def test_scalar_document():
+    assert ryaml.loads_lazy('42') == 42
+    assert ryaml.loads_lazy('') is None
+
+
+def test_duplicate_keys():
+    data = ryaml.loads_lazy('a: 1\nb: 2\na: 3\n')
+    assert list(data) == ['a', 'b']
+    assert data['a'] == 3
+
+
+def test_merge_keys():
+    data = ryaml.loads_lazy('base: &base {a: 1, b: 2}\nchild:\n  <<: *base\n  b: 3\n')
+    assert dict(data['child'].items()) == {'a': 1, 'b': 3}
+    assert data['child'] == {'a': 1, 'b': 3}
+
+
+def test_aliases_share_values():
+    data = ryaml.loads_lazy('a: &x [1, 2]\nb: *x\n')
+    assert data['a'] is data['b']
+
+
+def test_recursive_alias():
+    data = ryaml.loads_lazy('&a {self: *a}\n')
+    assert data['self'] is data
+
+
+def test_tagged_collections_are_constructed():
+    data = ryaml.loads_lazy('s: !!set {a, b}\n')
+    assert data['s'] == {'a', 'b'}
+
+
+def test_registered_constructor():
+    ryaml.add_constructor('!lazy-point', lambda c, node: tuple(c.construct_sequence(node)))
+    data = ryaml.loads_lazy('p: !lazy-point [1, 2]\n')
+    assert data['p'] == (1, 2)
+
+
+def test_load_lazy():
+    data = ryaml.load_lazy(io.StringIO(CONFIG))
+    assert data['services']['db']['image'] == 'postgres'
@@ -0,0 +1,96 @@
+import io
+
+import pytest
+
+import ryaml
+
+
+def test_within_limits():
+    assert ryaml.loads('a: [1, {b: 2}]\n', max_depth=3, max_nodes=7, max_bytes=16) == {'a': [1, {'b': 2}]}
+
+
+def test_max_depth():
+    with pytest.raises(ryaml.ComposerError, match='nested more than max_depth=3 deep') as exc:
+        ryaml.loads('a:\n  b:\n    c:\n      d: 1\n', max_depth=3)
+    assert exc.value.line == 4
+
+
+def test_max_depth_flow():
+    with pytest.raises(ryaml.ComposerError, match='max_depth=100'):
+        ryaml.loads('[' * 10_000 + ']' * 10_000, max_depth=100)
+
+
+def test_max_depth_counts_open_collections():
+    # Siblings don't add up
+    assert ryaml.loads('- [1]\n- [2]\n- [3]\n', max_depth=2) == [[1], [2], [3]]
+
+
+def test_max_nodes():
+    with pytest.raises(ryaml.ComposerError, match='more than max_nodes=10 nodes'):
+        ryaml.loads(str(list(range(100))), max_nodes=10)
+
+
+def test_max_nodes_counts_aliases():
+    source = 'a: &a [1, 2]\nb: [*a, *a, *a]\n'
+    with pytest.raises(ryaml.ComposerError, match='max_nodes=9'):
+        ryaml.loads(source, max_nodes=9)
+    assert ryaml.loads(source, max_nodes=10)['b'][2] == [1, 2]
+
+
+def test_max_nodes_per_document():
+    source = '--- [1, 2]\n--- [3, 4]\n'
+    assert ryaml.loads_all(source, max_nodes=3) == [[1, 2], [3, 4]]
+
+
+def test_max_bytes():
+    with pytest.raises(ryaml.ReaderError, match='stream is 12 bytes long, more than max_bytes=8'):
+        ryaml.loads('key: value\n\n', max_bytes=8)
+    # Counted in UTF-8
+    with pytest.raises(ryaml.ReaderError):
+        ryaml.loads('é: ü', max_bytes=5)
+
+
+def test_max_bytes_json():
+    # JSON documents skip the fast path when limited
+    with pytest.raises(ryaml.ReaderError):
+        ryaml.loads('{"a": [1, 2, 3]}', max_bytes=4)
+    with pytest.raises(ryaml.ComposerError):
+        ryaml.loads('[[[1]]]', max_depth=2)
+
+
+def test_load_file_reads_no_further():
+    stream = io.BytesIO(b'a: 1\n' * 1000)
+    with pytest.raises(ryaml.ReaderError):
+        ryaml.load(stream, max_bytes=100)
+    assert stream.tell() == 101
+
+
+def test_load_iter_max_bytes():
+    stream = io.StringIO('--- 1\n' * 100_000)
+    documents = ryaml.load_iter(stream, max_bytes=1000)
+    with pytest.raises(ryaml.ReaderError, match='longer than max_bytes=1000'):
+        list(documents)
+
+
+def test_load_iter_max_depth():
+    stream = io.StringIO('--- [1]\n--- [[2]]\n')
+    documents = ryaml.load_iter(stream, max_depth=1)
+    assert next(documents) == [1]
+    with pytest.raises(ryaml.ComposerError):
+        next(documents)
+
+
+def test_loader_option():
+    loader = ryaml._ryaml._RSafeLoader('[[1]]', max_depth=1)
+    with pytest.raises(ryaml.ComposerError):
+        loader.get_single_data()
+
+
+def test_compose_limits():
+    with pytest.raises(ryaml.ComposerError):
+        ryaml.compose('{a: {b: 1}}', max_depth=1)
+
+
+def test_invalid_limit():
+    with pytest.raises((OverflowError, TypeError)):
+        ryaml.loads('a', max_depth=-1)
@@ -1,3 +1,5 @@
+import pytest
+
 import ryaml
 
 def test_load_empty(yaml_file):
@@ -33,3 +35,27 @@ def test_load_non_ascii(yaml_file):
     yaml_file.write('key: 你好')
     yaml_file.seek(0)
     assert ryaml.load(yaml_file) == { 'key': '你好' }
+
+def test_load_path(tmp_path):
+    path = tmp_path / 'config.yaml'
+    path.write_text('key: [1, 2]\n', encoding='utf8')
+    assert ryaml.load(path) == {'key': [1, 2]}
+    assert ryaml.load(str(path)) == {'key': [1, 2]}
+    assert ryaml.load(bytes(path)) == {'key': [1, 2]}
+    assert ryaml.load_all(path) == [{'key': [1, 2]}]
+
+def test_load_path_detects_encoding(tmp_path):
+    path = tmp_path / 'config.yaml'
+    path.write_bytes('key: 你好\n'.encode('utf-16'))
+    assert ryaml.load(path) == {'key': '你好'}
+
+def test_load_path_error_names_file(tmp_path):
+    path = tmp_path / 'bad.yaml'
+    path.write_text('- a\nb: c\n', encoding='utf8')
+    with pytest.raises(ryaml.ParserError) as info:
+        ryaml.load(path)
+    assert info.value.problem_mark.name == str(path)
+
+def test_load_missing_path(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        ryaml.load(tmp_path / 'missing.yaml')
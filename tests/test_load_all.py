@@ -1,5 +1,11 @@
+import io
 import textwrap
+
+import pytest
+import yaml
+
 import ryaml
+from ryaml.compat import RSafeLoader
 
 def test_load_all_empty(yaml_file):
     yaml_file.write('')
@@ -16,3 +22,178 @@ def test_load_all_keys(yaml_file):
     '''))
     yaml_file.seek(0)
     assert ryaml.load_all(yaml_file) == [{ 'key': None }, { 'key2': None }]
+
+def test_loads_iter():
+    assert list(ryaml.loads_iter('--- 1\n--- [2]\n--- ~\n')) == [1, [2], None]
+
+def test_loads_iter_empty():
+    assert list(ryaml.loads_iter('')) == []
+
+def test_loads_iter_stops_parsing_early():
+    docs = ryaml.loads_iter('header: 1\n---\nbody: 2\n---\n[unclosed: {\n')
+    assert next(docs) == { 'header': 1 }
+    assert next(docs) == { 'body': 2 }
+    # The malformed third document is only reached when iteration continues
+    with pytest.raises(ryaml.InvalidYamlError):
+        next(docs)
+
+def test_loads_all_progress():
+    calls = []
+    source = '--- a\n--- é\n--- c\n'
+    ryaml.loads_all(source, progress=lambda docs, consumed: calls.append((docs, consumed)))
+    assert [docs for docs, _ in calls] == [1, 2, 3]
+    consumed = [consumed for _, consumed in calls]
+    assert consumed == sorted(consumed)
+    assert consumed[-1] <= len(source.encode('utf8'))
+
+def test_load_all_progress(yaml_file):
+    yaml_file.write('--- 1\n--- 2\n')
+    yaml_file.seek(0)
+    calls = []
+    assert ryaml.load_all(yaml_file, progress=lambda *args: calls.append(args)) == [1, 2]
+    assert len(calls) == 2
+
+SHARED_ANCHOR = '--- &a [1, 2]\n--- *a\n'
+
+@pytest.mark.parametrize('load', [
+    ryaml.loads_all,
+    lambda s: list(ryaml.loads_iter(s)),
+    ryaml.compose_all,
+    lambda s: list(yaml.load_all(s, Loader=RSafeLoader)),
+])
+def test_anchors_do_not_leak_across_documents(load):
+    with pytest.raises(ryaml.ComposerError, match="found undefined alias 'a'"):
+        load(SHARED_ANCHOR)
+
+def test_global_anchors():
+    first, second = ryaml.loads_all(SHARED_ANCHOR, global_anchors=True)
+    assert first == [1, 2]
+    assert second is first
+    assert list(ryaml.loads_iter(SHARED_ANCHOR, global_anchors=True)) == [[1, 2], [1, 2]]
+    assert [node.value[0].value for node in ryaml.compose_all(SHARED_ANCHOR, global_anchors=True)] == ['1', '1']
+
+def test_load_iter(yaml_file):
+    yaml_file.write('--- 1\n--- [2]\n--- {3: 4}\n')
+    yaml_file.seek(0)
+    assert list(ryaml.load_iter(yaml_file)) == [1, [2], {3: 4}]
+
+def test_load_iter_reads_as_needed():
+    doc = '---\n' + ''.join(f'key{i}: 值{i}\n' for i in range(5000))
+    data = ('--- first\n' + doc * 20).encode('utf8')
+    fp = io.BytesIO(data)
+    docs = ryaml.load_iter(fp)
+    assert next(docs) == 'first'
+    assert fp.tell() < len(data)
+    # Characters split between reads come out whole
+    assert next(docs)['key4999'] == '值4999'
+    assert sum(1 for _ in docs) == 19
+
+def test_load_iter_utf16():
+    fp = io.BytesIO('a: ü\n'.encode('utf-16'))
+    assert list(ryaml.load_iter(fp)) == [{'a': 'ü'}]
+
+def test_load_iter_read_error():
+    class Broken(io.RawIOBase):
+        def readable(self):
+            return True
+
+        def read(self, size=-1):
+            raise OSError('connection reset')
+
+    with pytest.raises(OSError, match='connection reset'):
+        list(ryaml.load_iter(Broken()))
+
+def test_load_iter_error_names_file(yaml_file):
+    yaml_file.write('a: [1\n')
+    yaml_file.seek(0)
+    with pytest.raises(ryaml.ParserError) as info:
+        list(ryaml.load_iter(yaml_file))
+    assert info.value.problem_mark.name == yaml_file.name
+
+def long_stream(docs=5000):
+    parts = []
+    for i in range(docs):
+        if i % 100 == 50:
+            parts.append('...\n%YAML 1.1\n')
+        parts.append(f'--- # document {i}\nkey: välue {i}\nitems: [1, 2]\ntext: |\n  first\n  ---not a marker\n')
+    return ''.join(parts)
+
+def test_loads_all_in_parallel():
+    source = long_stream()
+    docs = ryaml.loads_all(source, max_workers=4)
+    assert docs == ryaml.loads_all(source, max_workers=1)
+    assert len(docs) == 5000
+    assert docs[4999] == {'key': 'välue 4999', 'items': [1, 2], 'text': 'first\n---not a marker\n'}
+
+def test_loads_all_in_parallel_marks():
+    source = long_stream() + '---\na: 1\nb: *missing\n'
+    line = source.count('\n') - 1
+    for workers in (1, 4):
+        with pytest.raises(ryaml.ComposerError) as info:
+            ryaml.loads_all(source, max_workers=workers)
+        assert (info.value.problem_mark.line, info.value.problem_mark.column) == (line, 3)
+
+def test_loads_all_in_parallel_parse_error():
+    source = long_stream() + '---\na: [1\n'
+    errors = []
+    for workers in (1, 4):
+        with pytest.raises(ryaml.ParserError) as info:
+            ryaml.loads_all(source, max_workers=workers)
+        errors.append(str(info.value))
+    # The error is reported from the whole stream, as when parsing serially
+    assert errors[0] == errors[1]
+
+def test_loads_all_max_workers():
+    with pytest.raises(ValueError, match='max_workers must be at least 1'):
+        ryaml.loads_all('--- 1\n', max_workers=0)
+
+
+def test_on_error_skips_syntax_errors():
+    errors = []
+    source = '---\na: 1\n---\nb: c: d\n---\ne: 3\n'
+    assert ryaml.loads_all(source, on_error=errors.append) == [{'a': 1}, {'e': 3}]
+    [error] = errors
+    assert isinstance(error, ryaml.ScannerError)
+    assert error.problem_mark.line == 3
+
+
+def test_on_error_skips_construction_errors():
+    errors = []
+    source = '---\na: !!int x\nb: [1, 2]\n---\nc: *missing\n---\nd: 4\n'
+    assert ryaml.loads_all(source, on_error=errors.append) == [{'d': 4}]
+    assert [type(e) for e in errors] == [ryaml.ConstructorError, ryaml.ComposerError]
+    assert errors[1].problem_mark.line == 4
+
+
+def test_on_error_marks_after_resync():
+    errors = []
+    source = '--- [1\n--- 2\n--- [3\n--- &a 4\n--- *a\n'
+    assert ryaml.loads_all(source, on_error=errors.append) == [2, 4]
+    assert [e.problem_mark.line for e in errors] == [1, 3, 4]
+
+
+def test_on_error_at_end():
+    errors = []
+    assert ryaml.loads_all('--- 1\n--- [2\n', on_error=errors.append) == [1]
+    assert len(errors) == 1
+
+
+def test_on_error_raising_stops():
+    def fail(error):
+        raise RuntimeError('stop') from error
+
+    with pytest.raises(RuntimeError, match='stop'):
+        ryaml.loads_all('--- !!int x\n--- 1\n', on_error=fail)
+
+
+def test_without_on_error_raises():
+    with pytest.raises(ryaml.ConstructorError):
+        ryaml.loads_all('--- !!int x\n--- 1\n')
+
+
+def test_load_all_on_error(yaml_file):
+    yaml_file.write('---\nok: 1\n---\nbad: [\n---\nok: 2\n')
+    yaml_file.seek(0)
+    errors = []
+    assert ryaml.load_all(yaml_file, on_error=errors.append) == [{'ok': 1}, {'ok': 2}]
+    assert isinstance(errors[0], ryaml.ParserError)
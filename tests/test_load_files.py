@@ -0,0 +1,45 @@
+import pytest
+
+import ryaml
+
+
+def test_load_files(tmp_path):
+    paths = []
+    for i in range(20):
+        path = tmp_path / f'config{i}.yaml'
+        path.write_text(f'index: {i}\nname: file{i}\n')
+        paths.append(path)
+    results = ryaml.load_files(paths, max_workers=4)
+    assert list(results) == paths
+    assert [results[path] for path in paths] == [{'index': i, 'name': f'file{i}'} for i in range(20)]
+
+
+def test_errors_are_returned_per_path(tmp_path):
+    good = tmp_path / 'good.yaml'
+    good.write_text('a: 1\n')
+    bad = tmp_path / 'bad.yaml'
+    bad.write_text('a: [1\n')
+    missing = str(tmp_path / 'missing.yaml')
+    results = ryaml.load_files([str(good), str(bad), missing])
+    assert results[str(good)] == {'a': 1}
+    assert isinstance(results[str(bad)], ryaml.ParserError)
+    assert str(bad) in str(results[str(bad)])
+    assert isinstance(results[missing], FileNotFoundError)
+
+
+def test_options_apply_to_every_file(tmp_path):
+    path = tmp_path / 'a.yaml'
+    path.write_text('on: yes\n')
+    assert ryaml.load_files([path], schema='core') == {path: {'on': 'yes'}}
+
+
+def test_empty_file_and_no_files(tmp_path):
+    path = tmp_path / 'empty.yaml'
+    path.write_text('')
+    assert ryaml.load_files([path]) == {path: None}
+    assert ryaml.load_files([]) == {}
+
+
+def test_max_workers_must_be_positive():
+    with pytest.raises(ValueError, match='max_workers must be at least 1'):
+        ryaml.load_files([], max_workers=0)
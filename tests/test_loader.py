@@ -0,0 +1,85 @@
+import pytest
+
+import ryaml
+from ryaml.compat import RSafeLoader
+
+EMPTY = {'values': 0, 'anchor_marks': 0, 'resolved': 0}
+
+
+def test_loader_cache_sizes():
+    loader = RSafeLoader('a: &x 1\nb: *x\n')
+    assert loader.cache_sizes == {'anchors': 0, 'node_anchors': 0, 'keys': 0, **EMPTY}
+    assert loader.get_single_data() == {'a': 1, 'b': 1}
+    assert loader.cache_sizes == {'anchors': 0, 'node_anchors': 0, 'keys': 2, **EMPTY}
+
+def test_loader_reset_state():
+    loader = RSafeLoader('- &x [1]\n- *x\n')
+    loader.get_data()
+    loader.reset_state()
+    assert loader.cache_sizes == {'anchors': 0, 'node_anchors': 0, 'keys': 0, **EMPTY}
+
+def test_loader_reset_state_clears_every_cache():
+    loader = RSafeLoader(
+        '- &x [a, b]\n- *x\n- value\n- value\n',
+        intern_values=True,
+        allow_duplicate_anchors=False,
+    )
+    assert loader.get_data() == [['a', 'b'], ['a', 'b'], 'value', 'value']
+    sizes = loader.cache_sizes
+    assert sizes['values'] > 0
+    assert sizes['anchor_marks'] == 1
+    loader.reset_state()
+    assert loader.cache_sizes == {'anchors': 0, 'node_anchors': 0, 'keys': 0, **EMPTY}
+
+def test_equal_keys_share_one_string():
+    data = ryaml.loads('- name: a\n  image: b\n- name: c\n  "image": d\n')
+    first, second = (list(item) for item in data)
+    assert first[0] is second[0]
+    assert first[1] is second[1]
+
+def test_shared_keys_still_resolve_types():
+    assert ryaml.loads('- 1: a\n  true: b\n- 1: c\n  "1": d\n') == [
+        {1: 'a', True: 'b'},
+        {1: 'c', '1': 'd'},
+    ]
+
+def test_loader_set_input():
+    loader = RSafeLoader('a: &x [1]\nb: *x\n')
+    assert loader.get_single_data() == {'a': [1], 'b': [1]}
+    loader.set_input('- 1\n- two\n')
+    assert loader.get_single_data() == [1, 'two']
+    loader.set_input(b'c: 3\n')
+    assert loader.get_single_data() == {'c': 3}
+
+def test_loader_set_input_forgets_anchors():
+    loader = RSafeLoader('a: &x 1\n')
+    loader.get_single_data()
+    loader.set_input('b: *x\n')
+    with pytest.raises(ryaml.ComposerError, match="found undefined alias 'x'"):
+        loader.get_single_data()
+
+def test_loader_reset():
+    loader = RSafeLoader('a: &x [1]\nb: *x\n')
+    assert loader.get_single_data() == {'a': [1], 'b': [1]}
+    loader.reset('c: *x\n')
+    with pytest.raises(ryaml.ComposerError, match="found undefined alias 'x'"):
+        loader.get_single_data()
+    for i in range(3):
+        loader.reset(f'- &y {i}\n- *y\n')
+        assert loader.get_single_data() == [i, i]
+    assert loader.cache_sizes == {'anchors': 0, 'node_anchors': 0, 'keys': 0, **EMPTY}
+
+def test_pooled_loads_forget_directives():
+    first = ryaml.loads_all('%YAML 1.1\n%TAG !e! tag:example.com,2024:\n--- x\n', with_meta=True)
+    assert first[0].tags == {'!e!': 'tag:example.com,2024:'}
+    second = ryaml.loads_all('--- y\n', with_meta=True)
+    assert (second[0].version, second[0].tags) == (None, {})
+    assert ryaml.loads('%TAG !f! tag:other.org,2024:\n--- !!str z\n') == 'z'
+    with pytest.raises(ryaml.YAMLError, match='undefined tag handle'):
+        ryaml.loads('--- !e!thing w\n')
+
+def test_repeated_loads_are_independent():
+    for i in range(50):
+        assert ryaml.loads(f'a: &x {i}\nb: *x\n') == {'a': i, 'b': i}
+    assert ryaml.loads_all('- 1\n---\n- 2\n') == [[1], [2]]
+    assert ryaml.loads('[3]') == [3]
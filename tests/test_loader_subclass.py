@@ -0,0 +1,155 @@
+import pytest
+import yaml
+
+import ryaml
+from ryaml._ryaml import MappingNode, ScalarNode, SequenceNode, _RSafeLoader
+from ryaml.compat import RSafeLoader
+
+
+class SortedLoader(RSafeLoader):
+    def construct_mapping(self, node, deep=False):
+        mapping = super().construct_mapping(node, deep=deep)
+        return dict(sorted(mapping.items()))
+
+
+class UpperLoader(RSafeLoader):
+    def construct_yaml_str(self, node):
+        return super().construct_yaml_str(node).upper()
+
+
+class StrippingLoader(RSafeLoader):
+    def construct_scalar(self, node):
+        return super().construct_scalar(node).strip('_')
+
+
+class PlainLoader(RSafeLoader):
+    """Overrides nothing, so documents are constructed straight from events"""
+
+
+class OptionsLoader(_RSafeLoader):
+    """Takes loader options, which the pyyaml-style classes don't"""
+
+
+class SortedOptionsLoader(OptionsLoader):
+    def construct_mapping(self, node, deep=False):
+        mapping = super().construct_mapping(node, deep=deep)
+        return dict(sorted(mapping.items()))
+
+
+class TwoStepLoader(RSafeLoader):
+    """Written the pyyaml way, with a generator yielding the empty dict first"""
+
+    def construct_yaml_map(self, node):
+        data = {'two_step': True}
+        yield data
+        data.update(self.construct_mapping(node))
+
+
+def test_construct_mapping_override():
+    data = yaml.load('b: 1\na: {d: 2, c: 3}\n', Loader=SortedLoader)
+    assert data == {'a': {'c': 3, 'd': 2}, 'b': 1}
+    assert list(data) == ['a', 'b']
+    assert list(data['a']) == ['c', 'd']
+
+
+def test_construct_yaml_str_override():
+    assert yaml.load('a: [x, 1, "y"]', Loader=UpperLoader) == {'A': ['X', 1, 'Y']}
+
+
+def test_construct_scalar_override_applies_to_all_scalars():
+    assert yaml.load('[!!int _12_, _x_, !!bool _yes_]', Loader=StrippingLoader) == [12, 'x', True]
+
+
+def test_generator_constructor_keeps_recursive_aliases():
+    data = yaml.load('&a {self: *a}', Loader=TwoStepLoader)
+    assert data['two_step'] is True
+    assert data['self'] is data
+
+
+@pytest.mark.parametrize('deep, sizes', [(True, [1, 2]), (False, [0, 0])])
+def test_deep_finishes_two_step_constructors_first(deep, sizes):
+    class SizingLoader(RSafeLoader):
+        def construct_yaml_map(self, node):
+            data = {}
+            yield data
+            data.update(self.construct_mapping(node))
+
+        def construct_yaml_seq(self, node):
+            return [len(item) for item in self.construct_sequence(node, deep=deep)]
+
+    assert yaml.load('- {a: 1}\n- {b: 2, c: 3}\n', Loader=SizingLoader) == sizes
+
+
+def test_shallow_two_step_constructors_finish_with_the_document():
+    class KeepingLoader(TwoStepLoader):
+        def construct_yaml_seq(self, node):
+            return self.construct_sequence(node)
+
+    assert yaml.load('- {a: 1}\n', Loader=KeepingLoader) == [{'two_step': True, 'a': 1}]
+
+
+def test_load_all_and_registered_constructors():
+    ryaml.add_constructor('!loader-subclass-test', lambda constructor, node: 'registered')
+    docs = list(yaml.load_all('--- {b: !loader-subclass-test x, a: y}\n--- [z]\n', Loader=UpperLoader))
+    assert docs == [{'B': 'registered', 'A': 'Y'}, ['Z']]
+
+
+def test_base_class_is_unaffected():
+    assert yaml.load('b: x\na: y\n', Loader=RSafeLoader) == {'b': 'x', 'a': 'y'}
+    assert RSafeLoader('x').construct_scalar(ryaml.compose('x')) == 'x'
+
+
+def test_subclass_without_overrides_constructs_from_events():
+    assert OptionsLoader('1: [2]\n', string_keys=True).get_single_data() == {'1': [2]}
+    assert yaml.load('a: [1, x]\n', Loader=PlainLoader) == {'a': [1, 'x']}
+
+
+@pytest.mark.parametrize('option, value', [
+    ('string_keys', True),
+    ('intern_values', True),
+    ('allow_nan_keys', False),
+    ('roundtrip', True),
+    ('float_precision', 'error'),
+    ('object_hook', dict),
+])
+def test_event_only_options_refused_with_overrides(option, value):
+    loader = SortedOptionsLoader('1: [2]\n', **{option: value})
+    with pytest.raises(TypeError, match=f"{option}.* can't be used with a loader overriding construct_mapping"):
+        loader.get_single_data()
+    loader = SortedOptionsLoader('- 1\n', **{option: value})
+    with pytest.raises(TypeError, match='construct_mapping'):
+        loader.get_data()
+
+
+def test_options_applied_to_nodes_are_kept_with_overrides():
+    loader = SortedOptionsLoader('b: 2001-12-14\na: yes\n', parse_datetimes=False, schema='core')
+    assert loader.get_single_data() == {'a': 'yes', 'b': '2001-12-14'}
+
+
+def test_resolve():
+    loader = RSafeLoader('')
+    assert loader.resolve(ScalarNode, '12', (True, False)) == 'tag:yaml.org,2002:int'
+    assert loader.resolve(ScalarNode, '12', (False, True)) == 'tag:yaml.org,2002:str'
+    assert loader.resolve(ScalarNode, '~', (True, False)) == 'tag:yaml.org,2002:null'
+    assert loader.resolve(SequenceNode, [], True) == 'tag:yaml.org,2002:seq'
+    assert loader.resolve(MappingNode, [], True) == 'tag:yaml.org,2002:map'
+
+
+def test_resolve_follows_schema():
+    loader = RSafeLoader('', schema='core')
+    assert loader.resolve(ScalarNode, 'yes', (True, False)) == 'tag:yaml.org,2002:str'
+
+
+def test_resolve_rejects_other_classes():
+    with pytest.raises(TypeError, match='expected a node class, got int'):
+        RSafeLoader('').resolve(int, '1', (True, False))
+
+
+def test_resolve_in_constructor():
+    class TaggingLoader(RSafeLoader):
+        def construct_yaml_str(self, node):
+            return self.resolve(type(node), node.value, (True, False))
+
+    assert yaml.load('["1", "x"]', Loader=TaggingLoader) == [
+        'tag:yaml.org,2002:int', 'tag:yaml.org,2002:str'
+    ]
@@ -32,6 +32,45 @@ def test_loads_key_sequence():
 
     ''') == { 'key': [4, 5] }
 
+def test_loads_backend():
+    assert ryaml.BACKENDS == ['libyaml', 'saphyr']
+    assert ryaml.loads('key: 4', backend='libyaml') == { 'key': 4 }
+    assert ryaml.loads('key: 4', backend='saphyr') == { 'key': 4 }
+    with pytest.raises(ValueError, match='unsupported parser backend'):
+        ryaml.loads('key: 4', backend='nope')
+
+SWITCHED = '''\
+base: &base {name: ryaml, tags: [a, b]}
+copy: *base
+when: 2001-12-14
+on: yes
+quoted: 'yes'
+text: |
+  two
+  lines
+'''
+
+def test_backends_load_alike():
+    libyaml = ryaml.loads(SWITCHED, backend='libyaml')
+    saphyr = ryaml.loads(SWITCHED, backend='saphyr')
+    assert saphyr == libyaml
+    assert saphyr['copy'] is saphyr['base']
+    documents = 'a: 1\n---\n- &x [2]\n- *x\n...\n'
+    assert ryaml.loads_all(documents, backend='saphyr') == ryaml.loads_all(documents, backend='libyaml')
+
+def test_backends_parse_alike():
+    document = '# note &not\nkey: &first !!str 1\nlist: [&second x, *first]\n'
+    def describe(events):
+        return [(type(event).__name__, getattr(event, 'anchor', None), getattr(event, 'tag', None),
+                 getattr(event, 'value', None))
+                for event in events]
+    assert describe(ryaml.parse(document, backend='saphyr')) == describe(ryaml.parse(document, backend='libyaml'))
+
+def test_saphyr_syntax_error():
+    with pytest.raises(ryaml.ParserError) as info:
+        ryaml.loads('a: [1\n', backend='saphyr')
+    assert info.value.problem_mark.line == 1
+
 
 
 @pytest.mark.parametrize("input", VALID_YAMLS, ids=lambda val: f"{val.name[:-5]}")
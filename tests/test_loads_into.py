@@ -0,0 +1,138 @@
+import dataclasses
+import datetime
+import io
+from typing import Literal, Optional, TypedDict
+
+import pytest
+
+import ryaml
+
+
+@dataclasses.dataclass
+class Port:
+    number: int
+    protocol: Literal['tcp', 'udp'] = 'tcp'
+
+
+@dataclasses.dataclass
+class Service:
+    name: str
+    ports: list[Port]
+    replicas: int = 1
+    weight: float = 1.0
+    env: dict[str, str] = dataclasses.field(default_factory=dict)
+    image: Optional[str] = None
+
+
+class Limits(TypedDict, total=False):
+    cpu: float
+    memory: str
+
+
+@dataclasses.dataclass
+class Tree:
+    value: int
+    children: list['Tree'] = dataclasses.field(default_factory=list)
+
+
+def test_dataclass():
+    service = ryaml.loads_into('''\
+name: web
+ports:
+- number: 80
+- {number: 53, protocol: udp}
+weight: 2
+env: {MODE: prod}
+''', Service)
+    assert service == Service(
+        name='web',
+        ports=[Port(80), Port(53, 'udp')],
+        weight=2.0,
+        env={'MODE': 'prod'},
+    )
+    assert isinstance(service.weight, float)
+
+
+def test_typed_dict():
+    assert ryaml.loads_into('cpu: 1.5\n', Limits) == {'cpu': 1.5}
+
+
+def test_generics():
+    assert ryaml.loads_into('[1, 2]', list[int]) == [1, 2]
+    assert ryaml.loads_into('[1, 2]', tuple[int, ...]) == (1, 2)
+    assert ryaml.loads_into('[1, a]', tuple[int, str]) == (1, 'a')
+    assert ryaml.loads_into('[1, 1]', set[int]) == {1}
+    assert ryaml.loads_into('{a: [1]}', dict[str, list[int]]) == {'a': [1]}
+    assert ryaml.loads_into('2024-01-02', datetime.date) == datetime.date(2024, 1, 2)
+
+
+def test_unions():
+    assert ryaml.loads_into('null', Optional[int]) is None
+    assert ryaml.loads_into('3', int | str) == 3
+    assert ryaml.loads_into('x', int | str) == 'x'
+    assert ryaml.loads_into('{number: 1}', Port | None) == Port(1)
+
+
+def test_recursive_dataclass():
+    tree = ryaml.loads_into('value: 1\nchildren: [{value: 2}]\n', Tree)
+    assert tree == Tree(1, [Tree(2)])
+
+
+def test_wrong_scalar_type():
+    with pytest.raises(ryaml.ConstructorError, match='expected int, but found str') as info:
+        ryaml.loads_into('name: web\nports: [{number: http}]\n', Service)
+    assert 'Service.ports[0].number' in str(info.value)
+    assert info.value.problem_mark.line == 1
+
+
+def test_bool_is_not_int():
+    with pytest.raises(ryaml.ConstructorError, match='expected int, but found bool'):
+        ryaml.loads_into('true', int)
+
+
+def test_unknown_field():
+    with pytest.raises(ryaml.ConstructorError, match="unknown field 'replica' for Service"):
+        ryaml.loads_into('name: web\nports: []\nreplica: 2\n', Service)
+
+
+def test_missing_field():
+    with pytest.raises(ryaml.ConstructorError, match="missing field 'ports' for Service"):
+        ryaml.loads_into('name: web\n', Service)
+
+
+def test_literal():
+    with pytest.raises(ryaml.ConstructorError, match="expected one of 'tcp', 'udp'"):
+        ryaml.loads_into('{number: 1, protocol: sctp}', Port)
+
+
+def test_tuple_length():
+    with pytest.raises(ryaml.ConstructorError, match='expected a tuple of 2 items'):
+        ryaml.loads_into('[1, a, b]', tuple[int, str])
+
+
+def test_aliases_and_merge_keys():
+    ports = ryaml.loads_into('''\
+- &http {number: 80}
+- *http
+- <<: *http
+  protocol: udp
+''', list[Port])
+    assert ports == [Port(80), Port(80), Port(80, 'udp')]
+
+
+def test_single_document():
+    with pytest.raises(ryaml.ComposerError, match='but found another document'):
+        ryaml.loads_into('1\n---\n2\n', int)
+
+
+def test_empty_document():
+    assert ryaml.loads_into('', Optional[Port]) is None
+
+
+def test_unsupported_type():
+    with pytest.raises(TypeError, match="can't decode YAML into"):
+        ryaml.loads_into('1', 'int')
+
+
+def test_load_into():
+    assert ryaml.load_into(io.StringIO('number: 22\n'), Port) == Port(22)
@@ -0,0 +1,73 @@
+import os
+
+import pytest
+
+import ryaml
+from ryaml._ryaml import MappedFile, read_path
+
+# Large enough to be mapped rather than read, with mmap=True
+ITEMS = [f'item {i}' for i in range(100_000)]
+TEXT = ''.join(f'- {item}\n' for item in ITEMS)
+
+
+def write(tmp_path, data):
+    path = tmp_path / 'big.yaml'
+    path.write_bytes(data)
+    return path
+
+
+def test_load_large_binary_file(tmp_path):
+    with open(write(tmp_path, TEXT.encode()), 'rb') as fp:
+        assert ryaml.load(fp, mmap=True) == ITEMS
+        assert fp.read() == b''
+
+
+def test_load_large_text_file(tmp_path):
+    with open(write(tmp_path, TEXT.encode()), encoding='utf-8') as fp:
+        assert ryaml.load(fp, mmap=True) == ITEMS
+        assert fp.read() == ''
+
+
+def test_load_rest_of_large_file(tmp_path):
+    with open(write(tmp_path, b'skipped\n' + TEXT.encode()), 'rb') as fp:
+        fp.readline()
+        assert ryaml.load_all(fp, mmap=True) == [ITEMS]
+
+
+def test_load_large_file_with_bom(tmp_path):
+    with open(write(tmp_path, b'\xef\xbb\xbf' + TEXT.encode()), 'rb') as fp:
+        assert ryaml.load(fp, mmap=True) == ITEMS
+
+
+def test_load_large_utf16_file(tmp_path):
+    with open(write(tmp_path, TEXT.encode('utf-16')), 'rb') as fp:
+        assert ryaml.load(fp, mmap=True) == ITEMS
+
+
+def test_large_file_error_marks(tmp_path):
+    path = write(tmp_path, (TEXT + 'b: c\n').encode())
+    with open(path, 'rb') as fp, pytest.raises(ryaml.ParserError) as info:
+        ryaml.load(fp, mmap=True)
+    error = info.value
+    assert error.problem_mark.name == str(path)
+    assert error.problem_mark.line == len(ITEMS)
+    assert 'b: c\n    ^' in str(error)
+
+
+def test_load_files_large_file(tmp_path):
+    path = write(tmp_path, TEXT.encode())
+    assert ryaml.load_files([path]) == {path: ITEMS}
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='files are only mapped on Unix')
+def test_files_are_read_unless_mapping_is_asked_for(tmp_path):
+    path = write(tmp_path, TEXT.encode())
+    assert isinstance(read_path(path), bytes)
+    assert isinstance(read_path(path, mmap=True), MappedFile)
+
+
+def test_load_large_file_without_mmap(tmp_path):
+    path = write(tmp_path, TEXT.encode())
+    with open(path, 'rb') as fp:
+        assert ryaml.load(fp) == ITEMS
+    assert ryaml.load(path) == ITEMS
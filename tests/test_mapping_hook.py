@@ -0,0 +1,88 @@
+from collections import OrderedDict
+from types import MappingProxyType
+
+import pytest
+
+import ryaml
+
+SOURCE = 'b: 1\na:\n  c: [1, {d: 2}]\n'
+
+
+def test_mapping_type():
+    data = ryaml.loads(SOURCE, mapping_type=OrderedDict)
+    assert type(data) is OrderedDict
+    assert type(data['a']) is OrderedDict
+    assert type(data['a']['c'][1]) is OrderedDict
+    assert list(data) == ['b', 'a']
+
+
+def test_object_pairs_hook_gets_pairs():
+    seen = []
+
+    def hook(pairs):
+        seen.append(pairs)
+        return tuple(pairs)
+
+    assert ryaml.loads('a: 1\nb: {c: 2}\n', object_pairs_hook=hook) == (('a', 1), ('b', (('c', 2),)))
+    assert seen == [[('c', 2)], [('a', 1), ('b', (('c', 2),))]]
+
+
+def test_hook_result_is_aliased():
+    data = ryaml.loads('a: &x {k: 1}\nb: *x\n', mapping_type=OrderedDict)
+    assert type(data['b']) is OrderedDict
+    assert data['a'] is data['b']
+
+
+def test_merge_from_non_dict_mappings():
+    source = 'base: &base {x: 1, y: 2}\nchild:\n  <<: *base\n  y: 3\n'
+    data = ryaml.loads(source, mapping_type=lambda pairs: MappingProxyType(dict(pairs)))
+    assert dict(data['child']) == {'y': 3, 'x': 1}
+
+
+def test_sets_are_left_alone():
+    assert ryaml.loads('!!set {a, b}', mapping_type=OrderedDict) == {'a', 'b'}
+
+
+def test_json_documents_use_the_hook():
+    assert type(ryaml.loads('{"a": 1}', mapping_type=OrderedDict)) is OrderedDict
+
+
+def test_hook_errors():
+    with pytest.raises(ValueError, match='mapping_type must be callable'):
+        ryaml.loads('a: 1', mapping_type=1)
+    with pytest.raises(TypeError, match="can't be given together"):
+        ryaml.loads('a: 1', mapping_type=OrderedDict, object_pairs_hook=list)
+
+
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+
+def lift(d):
+    if d.keys() == {'x', 'y'}:
+        return Point(**d)
+    return d
+
+
+def test_object_hook_bottom_up():
+    data = ryaml.loads('points:\n- {x: 1, y: 2}\n- {x: 3, y: 4}\nname: p\n', object_hook=lift)
+    assert [(p.x, p.y) for p in data['points']] == [(1, 2), (3, 4)]
+    assert data['name'] == 'p'
+
+
+def test_object_hook_gets_dicts():
+    seen = []
+    ryaml.loads('a: {b: 1}', object_hook=lambda d: seen.append(dict(d)) or d)
+    assert seen == [{'b': 1}, {'a': {'b': 1}}]
+
+
+def test_object_pairs_hook_takes_precedence():
+    data = ryaml.loads('a: 1', object_hook=lambda d: 'object', object_pairs_hook=lambda p: 'pairs')
+    assert data == 'pairs'
+
+
+def test_object_hook_must_be_callable():
+    with pytest.raises(ValueError, match='object_hook must be callable'):
+        ryaml.loads('a: 1', object_hook='nope')
@@ -0,0 +1,53 @@
+import ryaml
+from ryaml._ryaml import Mark
+
+
+def test_mark_defaults():
+    mark = Mark(3, 0, 3)
+    assert mark.name == '<unicode string>'
+    assert mark.buffer is None
+    assert mark.pointer == 3
+    assert mark.get_snippet() is None
+    assert str(mark) == '  in "<unicode string>", line 1, column 4'
+
+
+def test_mark_snippet():
+    mark = Mark(8, 1, 3, buffer='a: 1\nb: [x\n', pointer=8)
+    assert mark.get_snippet() == '    b: [x\n       ^'
+    assert mark.get_snippet(indent=0) == 'b: [x\n   ^'
+
+
+def test_mark_snippet_truncates_long_lines():
+    line = 'x' * 100
+    mark = Mark(50, 0, 50, buffer=line, pointer=50)
+    snippet = mark.get_snippet(indent=0, max_length=20)
+    assert snippet == ' ... ' + 'x' * 10 + ' ... \n' + ' ' * 10 + '^'
+
+
+def test_composed_marks_carry_source():
+    node = ryaml.compose('a: 1\nb: 2\n', name='config.yaml')
+    (_, _), (_, value) = node.value
+    mark = value.start_mark
+    assert mark.name == 'config.yaml'
+    assert mark.buffer == 'a: 1\nb: 2\n'
+    assert mark.pointer == 8
+    assert str(mark) == '  in "config.yaml", line 2, column 4:\n    b: 2\n       ^'
+
+
+def test_compat_loader_uses_file_name(tmp_path):
+    from ryaml.compat import RSafeLoader
+    path = tmp_path / 'doc.yaml'
+    path.write_text('- x\n')
+    with open(path) as fp:
+        node = RSafeLoader(fp).get_single_node()
+    assert node.start_mark.name == str(path)
+
+
+def test_marks_outlive_source_str():
+    # Loading borrows the str's text, which the marks keep alive
+    source = ''.join(['a: é\n', 'b: ü\n'])
+    node = ryaml.compose(source)
+    del source
+    (_, _), (_, value) = node.value
+    assert value.start_mark.buffer == 'a: é\nb: ü\n'
+    assert value.start_mark.get_snippet() == '    b: ü\n       ^'
@@ -0,0 +1,35 @@
+import pytest
+
+import ryaml
+
+
+def test_within_limit():
+    assert ryaml.dumps({'a': [1, 2]}, max_items=2) == 'a:\n- 1\n- 2\n'
+
+
+def test_top_level_sequence():
+    with pytest.raises(ryaml.RepresenterError, match='sequence at the top level has 3 items, more than max_items=2'):
+        ryaml.dumps([1, 2, 3], max_items=2)
+
+
+def test_nested_path():
+    data = {'jobs': [{'name': 'a', 'args': list(range(1000))}]}
+    with pytest.raises(ryaml.RepresenterError, match="sequence at 'jobs.0.args' has 1000 items"):
+        ryaml.dumps(data, max_items=10)
+
+
+def test_mapping_and_set():
+    with pytest.raises(ryaml.RepresenterError, match="mapping at 'a' has 3 items"):
+        ryaml.dumps({'a': {1: 1, 2: 2, 3: 3}}, max_items=2)
+    with pytest.raises(ryaml.RepresenterError, match='set at the top level has 3 items'):
+        ryaml.dumps({1, 2, 3}, max_items=2)
+
+
+def test_dumper_option():
+    import io
+
+    stream = io.StringIO()
+    dumper = ryaml.RSafeDumper(stream, max_items=1)
+    dumper.open()
+    with pytest.raises(ryaml.RepresenterError):
+        dumper.represent([1, 2])
@@ -0,0 +1,107 @@
+import pytest
+
+import ryaml
+from ryaml._ryaml import ScalarNode
+
+BASE = '''\
+image: web:1
+replicas: 1
+env: {MODE: dev, DEBUG: 'true'}
+ports: [80]
+containers:
+- {name: app, cpu: 1}
+- {name: sidecar, cpu: 0.5}
+'''
+
+OVER = '''\
+replicas: 3
+env: {MODE: prod}
+ports: [443]
+containers:
+- {name: app, cpu: 2}
+- {name: proxy, cpu: 0.1}
+'''
+
+
+def test_deep():
+    assert ryaml.merge([BASE, OVER]) == {
+        'image': 'web:1',
+        'replicas': 3,
+        'env': {'MODE': 'prod', 'DEBUG': 'true'},
+        'ports': [443],
+        'containers': [{'name': 'app', 'cpu': 2}, {'name': 'proxy', 'cpu': 0.1}],
+    }
+
+
+def test_override():
+    merged = ryaml.merge([BASE, OVER], strategy='override')
+    assert merged['env'] == {'MODE': 'prod'}
+    assert merged['image'] == 'web:1'
+
+
+def test_append():
+    merged = ryaml.merge([BASE, OVER], lists='append')
+    assert merged['ports'] == [80, 443]
+    assert len(merged['containers']) == 4
+
+
+def test_merge_by_key():
+    merged = ryaml.merge([BASE, OVER], lists='merge-by-key', list_key='name')
+    assert merged['containers'] == [
+        {'name': 'app', 'cpu': 2},
+        {'name': 'sidecar', 'cpu': 0.5},
+        {'name': 'proxy', 'cpu': 0.1},
+    ]
+
+
+def test_many_documents():
+    assert ryaml.merge(['a: 1', 'b: 2', '', 'a: 3']) == {'a': 3, 'b': 2}
+    assert ryaml.merge([]) is None
+
+
+def test_tag_mismatch_replaces():
+    assert ryaml.merge(['a: {b: 1}', 'a: [1]']) == {'a': [1]}
+    assert ryaml.merge(['a: {b: 1}', 'a: !!set {c}']) == {'a': {'c'}}
+
+
+def test_custom():
+    def strategy(path, base, over):
+        if path == ['replicas']:
+            total = int(base.value) + int(over.value)
+            return ScalarNode('tag:yaml.org,2002:int', str(total))
+        return None
+
+    merged = ryaml.merge(['replicas: 2\nenv: {A: 1}', 'replicas: 3\nenv: {B: 2}'], strategy=strategy)
+    assert merged == {'replicas': 5, 'env': {'A': 1, 'B': 2}}
+
+
+def test_custom_path():
+    paths = []
+    ryaml.merge(
+        ['items: [{id: a, v: 1}]', 'items: [{id: a, v: 2}]'],
+        strategy=lambda path, base, over: paths.append(path),
+        lists='merge-by-key',
+        list_key='id',
+    )
+    assert ['items', 0, 'v'] in paths
+
+
+def test_merge_nodes():
+    base = ryaml.compose('a: 1\nb: [1]\n')
+    node = ryaml.merge_nodes([base, 'b: [2]\n'], lists='append')
+    assert ryaml.loads(ryaml.serialize(node)) == {'a': 1, 'b': [1, 2]}
+    assert ryaml.construct(base) == {'a': 1, 'b': [1]}
+
+
+def test_loader_options():
+    assert ryaml.merge(['a: 1', 'a: 2'], max_depth=10) == {'a': 2}
+
+
+@pytest.mark.parametrize('kwargs, message', [
+    ({'strategy': 'shallow'}, "unsupported strategy 'shallow'"),
+    ({'lists': 'prepend'}, "unsupported lists 'prepend'"),
+    ({'lists': 'merge-by-key'}, 'needs the list_key'),
+])
+def test_invalid_arguments(kwargs, message):
+    with pytest.raises(ValueError, match=message):
+        ryaml.merge(['a: 1'], **kwargs)
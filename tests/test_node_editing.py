@@ -0,0 +1,73 @@
+import pytest
+
+import ryaml
+from ryaml._ryaml import ScalarNode
+
+DOC = '''\
+spec:
+  image: !img "web:1"
+  ports: [80, 443]
+  env:
+    MODE: dev
+'''
+
+
+def test_find():
+    node = ryaml.compose(DOC)
+    image = node.find('spec.image')
+    assert image.tag == '!img' and image.style == '"'
+    assert node.find('spec.ports[1]').value == '443'
+    assert node.find('') is node
+    assert node.find('spec.missing') is None
+    assert node.find('spec.ports[5]') is None
+    assert node.find('spec.image.x') is None
+    with pytest.raises(ValueError, match="doesn't name a single node"):
+        node.find('spec.ports[*]')
+
+
+def test_set_keeps_the_rest():
+    node = ryaml.compose(DOC)
+    node.set('spec.env.MODE', 'prod')
+    node.set('spec.env.LEVEL', 3)
+    node.set('spec.ports[0]', ScalarNode('tag:yaml.org,2002:int', '8080'))
+    assert ryaml.serialize(node) == (
+        'spec:\n'
+        '  image: !img "web:1"\n'
+        '  ports: [8080, 443]\n'
+        '  env:\n'
+        '    MODE: prod\n'
+        '    LEVEL: 3\n'
+    )
+
+
+def test_set_data():
+    node = ryaml.compose('a: 1\n')
+    node.set('b', {'c': [1, 'x']})
+    assert node.to_python() == {'a': 1, 'b': {'c': [1, 'x']}}
+
+
+def test_delete():
+    node = ryaml.compose(DOC)
+    node.delete('spec.ports[0]')
+    node.delete('spec.env')
+    assert node.to_python() == {'spec': {'image': 'web:1', 'ports': [443]}}
+
+
+def test_insert():
+    node = ryaml.compose('[a, c]\n')
+    node.insert(1, 'b')
+    node.insert(-1, ScalarNode('tag:yaml.org,2002:str', 'b2'))
+    node.insert(10, 'd')
+    assert node.to_python() == ['a', 'b', 'b2', 'c', 'd']
+
+
+def test_missing_paths():
+    node = ryaml.compose(DOC)
+    with pytest.raises(KeyError):
+        node.delete('spec.missing')
+    with pytest.raises(KeyError):
+        node.set('nope.deeper', 1)
+    with pytest.raises(KeyError):
+        node.set('spec.ports[2]', 1)
+    with pytest.raises(ValueError):
+        node.set('', 1)
@@ -0,0 +1,55 @@
+import datetime
+
+import pytest
+
+import ryaml
+from ryaml._ryaml import ScalarNode, SequenceNode, MappingNode
+
+STR = 'tag:yaml.org,2002:str'
+
+
+def test_equality_ignores_marks_and_styles():
+    assert ryaml.compose('a: [1, "x"]\n') == ryaml.compose('{a: [1, x]}')
+    assert ryaml.compose('a: 1\n') != ryaml.compose('a: "1"\n')
+    assert ryaml.compose('[a, b]\n') != ryaml.compose('[a]\n')
+    assert ScalarNode(STR, 'a') == ScalarNode(STR, 'a', style='"')
+    assert ScalarNode(STR, 'a') != SequenceNode('tag:yaml.org,2002:seq', [])
+    assert ScalarNode(STR, 'a') != 'a'
+
+
+def test_recursive_equality():
+    a = ryaml.compose('&a [*a]\n')
+    b = ryaml.compose('&b [*b]\n')
+    assert a == b
+
+
+def test_unhashable():
+    with pytest.raises(TypeError):
+        hash(ScalarNode(STR, 'a'))
+
+
+def test_repr():
+    node = ryaml.compose('a: [x]\n')
+    assert repr(node) == (
+        "MappingNode(tag='tag:yaml.org,2002:map', value=["
+        "(ScalarNode(tag='tag:yaml.org,2002:str', value='a'), "
+        "SequenceNode(tag='tag:yaml.org,2002:seq', value=["
+        "ScalarNode(tag='tag:yaml.org,2002:str', value='x')]))])"
+    )
+    assert repr(ryaml.compose('&a [*a]\n')) == "SequenceNode(tag='tag:yaml.org,2002:seq', value=[...])"
+
+
+def test_to_python():
+    node = ryaml.compose('a: [1, 2.5, yes, ~, 2024-01-02]\nb: {c: d}\n')
+    assert node.to_python() == {'a': [1, 2.5, True, None, datetime.date(2024, 1, 2)], 'b': {'c': 'd'}}
+    [(_, items), _] = node.value
+    assert items.value[0].to_python() == 1
+
+
+def test_to_python_schema():
+    node = ryaml.compose('[yes, "yes", 0o17, !!str 1]\n')
+    assert node.to_python() == [True, 'yes', '0o17', '1']
+    assert node.to_python(schema='core') == ['yes', 'yes', 15, '1']
+    assert node.to_python(schema='failsafe') == ['yes', 'yes', '0o17', '1']
+    with pytest.raises(ValueError, match='unsupported schema'):
+        node.to_python(schema='2.0')
@@ -0,0 +1,32 @@
+import pytest
+
+import ryaml
+
+
+def test_sorted_keys():
+    assert ryaml.normalize('b: 1\na: {d: 2, c: 3}\n') == 'a:\n  c: 3\n  d: 2\nb: 1\n'
+
+
+def test_quoting_and_style():
+    assert ryaml.normalize('a: "x"\nb: [1, \'2\']\n') == ryaml.normalize("b:\n- 1\n- '2'\na: x\n")
+
+
+def test_aliases_expanded():
+    normalized = ryaml.normalize('base: &b {x: 1}\nother: *b\n')
+    assert normalized == 'base:\n  x: 1\nother:\n  x: 1\n'
+
+
+def test_deterministic():
+    a = 'z: 1\ny: [a, b]\nx: {k: v}\n'
+    b = 'x:\n  k: "v"\ny:\n  - a\n  - b\nz: 1\n'
+    assert ryaml.normalize(a) == ryaml.normalize(b)
+    assert ryaml.normalize(ryaml.normalize(a)) == ryaml.normalize(a)
+
+
+def test_documents():
+    assert len(ryaml.compose_all(ryaml.normalize('b: 1\n---\n- x\n'))) == 2
+
+
+def test_recursive():
+    with pytest.raises(ryaml.SerializerError):
+        ryaml.normalize('&a [*a]\n')
@@ -0,0 +1,52 @@
+import io
+
+import yaml
+
+import ryaml
+from ryaml.compat import RSafeDumper, RSafeLoader
+
+
+def test_tilde_key():
+    assert ryaml.loads('{~: value}') == {None: 'value'}
+
+
+def test_empty_flow_key():
+    assert ryaml.loads('{: value}') == {None: 'value'}
+
+
+def test_empty_block_key():
+    assert ryaml.loads('? \n: value\nother: 1\n') == {None: 'value', 'other': 1}
+
+
+def test_quoted_empty_key_is_string():
+    assert ryaml.loads("'': value") == {'': 'value'}
+
+
+def test_compat_loader_matches():
+    for source in ('{~: value}', '{: value}', '? \n: value\n'):
+        assert yaml.load(source, Loader=RSafeLoader) == ryaml.loads(source) == {None: 'value'}
+
+
+def test_dump_null_key():
+    assert ryaml.dumps({None: 'value'}) == 'null: value\n'
+    assert ryaml.loads(ryaml.dumps({None: 'value'})) == {None: 'value'}
+
+
+def test_dump_empty_string_key():
+    assert ryaml.dumps({'': 'value'}) == "'': value\n"
+    assert ryaml.loads(ryaml.dumps({'': 'value'})) == {'': 'value'}
+
+
+def test_dump_null_key_sorted():
+    # None can't be ordered against strings, so insertion order is kept
+    stream = io.StringIO()
+    dumper = RSafeDumper(stream, sort_keys=True)
+    dumper.open()
+    dumper.represent({'b': 1, None: 2})
+    dumper.close()
+    assert stream.getvalue() == 'b: 1\nnull: 2\n'
+
+
+def test_serialize_empty_key_round_trip():
+    node = ryaml.compose('{: value}')
+    assert ryaml.loads(ryaml.serialize(node)) == {None: 'value'}
@@ -0,0 +1,55 @@
+import ryaml
+
+
+def test_loads_stats():
+    data, stats = ryaml.loads('a: &x [1, {b: 2}]\nc: *x\nd: "e"\n', stats=True)
+    assert data == {'a': [1, {'b': 2}], 'c': [1, {'b': 2}], 'd': 'e'}
+    assert stats.documents == 1
+    assert (stats.scalars, stats.sequences, stats.mappings) == (7, 1, 2)
+    assert (stats.anchors, stats.aliases) == (1, 1)
+    assert stats.max_depth == 3
+    assert 0 <= stats.parse_time <= stats.load_time
+
+
+def test_loads_stats_json():
+    # JSON documents are counted too, rather than taking the fast path
+    data, stats = ryaml.loads('{"a": [1, 2]}', stats=True)
+    assert data == {'a': [1, 2]}
+    assert (stats.scalars, stats.sequences, stats.mappings, stats.max_depth) == (3, 1, 1, 2)
+
+
+def test_loads_stats_multiple_documents():
+    data, stats = ryaml.loads('--- 1\n--- [2]\n', stats=True, allow_multiple=True)
+    assert data == 1
+    assert stats.documents == 1
+
+
+def test_loads_stats_empty():
+    data, stats = ryaml.loads('', stats=True)
+    assert data is None
+    assert (stats.documents, stats.scalars, stats.max_depth) == (0, 0, 0)
+
+
+def test_loads_stats_with_explain_and_anchors():
+    data, resolutions, anchors, stats = ryaml.loads(
+        'a: &n 1\n', explain=True, return_anchors=True, stats=True
+    )
+    assert data == {'a': 1}
+    assert [r.value for r in resolutions] == ['1']
+    assert anchors == {'n': 1}
+    assert stats.anchors == 1
+
+
+def test_load_stats(tmp_path):
+    path = tmp_path / 'doc.yaml'
+    path.write_text('- a\n- b\n')
+    data, stats = ryaml.load(path, stats=True)
+    assert data == ['a', 'b']
+    assert (stats.scalars, stats.sequences) == (2, 1)
+
+
+def test_stats_repr():
+    _, stats = ryaml.loads('[1]', stats=True)
+    assert repr(stats).startswith(
+        'ParseStats(documents=1, scalars=1, sequences=1, mappings=0, anchors=0, aliases=0, max_depth=1, '
+    )
@@ -0,0 +1,80 @@
+import pytest
+
+import ryaml
+
+DOC = '''\
+# deployment
+image: web:1  # pinned
+replicas: 1
+env: {MODE: dev}
+ports:
+- 80
+- 443
+'''
+
+
+def test_replace():
+    patched = ryaml.patch(DOC, [{'op': 'replace', 'path': '/replicas', 'value': 3}])
+    assert patched == DOC.replace('replicas: 1', 'replicas: 3')
+
+
+def test_add_and_remove():
+    patched = ryaml.patch(DOC, [
+        {'op': 'add', 'path': '/env/LEVEL', 'value': 'info'},
+        {'op': 'add', 'path': '/ports/-', 'value': 8080},
+        {'op': 'remove', 'path': '/ports/0'},
+    ])
+    assert ryaml.loads(patched) == {
+        'image': 'web:1',
+        'replicas': 1,
+        'env': {'MODE': 'dev', 'LEVEL': 'info'},
+        'ports': [443, 8080],
+    }
+    assert 'env: {MODE: dev, LEVEL: info}' in patched
+    assert patched.startswith('# deployment\nimage: web:1  # pinned\n')
+
+
+def test_move_copy_and_test():
+    patched = ryaml.patch(DOC, [
+        {'op': 'test', 'path': '/image', 'value': 'web:1'},
+        {'op': 'copy', 'from': '/env', 'path': '/defaults'},
+        {'op': 'move', 'from': '/replicas', 'path': '/count'},
+    ])
+    data = ryaml.loads(patched)
+    assert data['defaults'] == {'MODE': 'dev'}
+    assert data['count'] == 1 and 'replicas' not in data
+    assert '&' not in patched
+
+
+def test_pointer_escapes():
+    patched = ryaml.patch('a/b: 1\n"m~n": 2\n', [
+        {'op': 'replace', 'path': '/a~1b', 'value': 10},
+        {'op': 'remove', 'path': '/m~0n'},
+    ])
+    assert patched == 'a/b: 10\n'
+
+
+def test_merge_patch():
+    patched = ryaml.patch(DOC, {'replicas': 2, 'env': {'MODE': None, 'LEVEL': 'info'}})
+    assert ryaml.loads(patched)['env'] == {'LEVEL': 'info'}
+    assert patched.startswith('# deployment\nimage: web:1  # pinned\nreplicas: 2\n')
+
+
+def test_comments():
+    text = 'a:\n  # about b\n  b: 1  # one\n  c: 2\n'
+    patched = ryaml.patch(text, [{'op': 'remove', 'path': '/a/c'}])
+    assert patched == 'a:\n  # about b\n  b: 1  # one\n'
+    assert ryaml.patch(text, [], comments=False) == 'a:\n  b: 1\n  c: 2\n'
+
+
+def test_errors():
+    with pytest.raises(ValueError, match='does not exist'):
+        ryaml.patch(DOC, [{'op': 'remove', 'path': '/missing'}])
+    with pytest.raises(ValueError, match='does not exist'):
+        ryaml.patch(DOC, [{'op': 'replace', 'path': '/ports/01', 'value': 1}])
+    with pytest.raises(ValueError, match='test failed'):
+        ryaml.patch(DOC, [{'op': 'test', 'path': '/replicas', 'value': 2}])
+    with pytest.raises(ValueError, match='unknown patch operation'):
+        ryaml.patch(DOC, [{'op': 'frob', 'path': '/image'}])
+    with pytest.raises(ValueError, match='into itself'):
+        ryaml.patch(DOC, [{'op': 'move', 'from': '/env', 'path': '/env/x'}])
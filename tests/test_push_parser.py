@@ -0,0 +1,89 @@
+import pytest
+
+import ryaml
+from ryaml import PushParser
+
+DOCUMENT = 'a: &x 1\nlist: [x, "y z"]\n---\n- café\n- !tag {b: c}\n...\n'
+
+
+def reprs(events):
+    return [repr(event) for event in events]
+
+
+def test_events_match_parse():
+    parser = PushParser()
+    events = parser.feed(DOCUMENT.encode()) + parser.finish()
+    assert reprs(events) == reprs(ryaml.parse(DOCUMENT))
+
+
+def test_saphyr_backend():
+    parser = PushParser(backend='saphyr')
+    data = DOCUMENT.encode()
+    events = parser.feed(data[:10]) + parser.feed(data[10:]) + parser.finish()
+    assert reprs(events) == reprs(ryaml.parse(DOCUMENT, backend='saphyr'))
+
+
+@pytest.mark.parametrize('size', [1, 2, 3, 7])
+def test_chunked(size):
+    data = DOCUMENT.encode()
+    parser = PushParser()
+    events = []
+    for start in range(0, len(data), size):
+        events += parser.feed(data[start:start + size])
+    events += parser.finish()
+    assert reprs(events) == reprs(ryaml.parse(DOCUMENT))
+
+
+def test_events_as_chunks_arrive():
+    parser = PushParser()
+    events = parser.feed(b'- a\n- b')
+    names = [type(event).__name__ for event in events]
+    assert names[:4] == ['StreamStartEvent', 'DocumentStartEvent', 'SequenceStartEvent', 'ScalarEvent']
+    # The last scalar may go on in the next chunk
+    assert [event.value for event in events if hasattr(event, 'value')] == ['a']
+    events = parser.feed(b'c\n')
+    assert events == []
+    events = parser.finish()
+    assert [event.value for event in events if hasattr(event, 'value')] == ['bc']
+    assert type(events[-1]).__name__ == 'StreamEndEvent'
+
+
+def test_utf16():
+    data = '- é\n'.encode('utf-16')
+    parser = PushParser()
+    events = []
+    for byte in data:
+        events += parser.feed(bytes([byte]))
+    events += parser.finish()
+    assert [event.value for event in events if hasattr(event, 'value')] == ['é']
+
+
+def test_empty_chunk():
+    parser = PushParser()
+    assert parser.feed(b'') == []
+    assert [type(event).__name__ for event in parser.finish()] == ['StreamStartEvent', 'StreamEndEvent']
+
+
+def test_error():
+    parser = PushParser()
+    parser.feed(b'a: 1\n')
+    with pytest.raises(ryaml.ParserError) as excinfo:
+        parser.feed(b'- b\n')
+    assert excinfo.value.problem_mark.line == 1
+    with pytest.raises(ValueError, match='already ended'):
+        parser.feed(b'c')
+
+
+def test_feed_after_finish():
+    parser = PushParser()
+    parser.feed(b'a')
+    parser.finish()
+    with pytest.raises(ValueError, match='already ended'):
+        parser.feed(b'b')
+    with pytest.raises(ValueError, match='already ended'):
+        parser.finish()
+
+
+def test_invalid_backend():
+    with pytest.raises(ValueError):
+        PushParser(backend='nope')
@@ -0,0 +1,26 @@
+import pytest
+
+import ryaml
+
+
+@pytest.mark.parametrize('value', ['1e3', '0o17'])
+def test_quotes_strings_the_core_schema_retypes(value):
+    assert ryaml.dumps(value) == f'{value}\n'
+    assert ryaml.dumps(value, quote_ambiguous=True) == f"'{value}'\n"
+
+
+@pytest.mark.parametrize('value', ['no', 'on', '2024-01-01', '<<'])
+def test_quotes_strings_yaml11_retypes(value):
+    assert ryaml.dumps(value, schema='core') == f'{value}\n'
+    assert ryaml.dumps(value, schema='core', quote_ambiguous=True) == f"'{value}'\n"
+
+
+def test_plain_strings_stay_plain():
+    assert ryaml.dumps({'name': 'hello world'}, quote_ambiguous=True) == 'name: hello world\n'
+
+
+def test_round_trips_under_both_schemas():
+    data = {'values': ['no', 'on', '1e3', '0o17', '2024-01-01', 'text']}
+    text = ryaml.dumps(data, quote_ambiguous=True)
+    assert ryaml.loads(text) == data
+    assert ryaml.loads(text, schema='core') == data
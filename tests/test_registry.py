@@ -0,0 +1,241 @@
+import re
+import threading
+
+import pytest
+
+import ryaml
+from ryaml import _ryaml
+from ryaml._ryaml import ScalarNode, SequenceNode
+
+pytestmark = pytest.mark.usefixtures('restore_registries')
+
+
+def test_add_representer():
+    class Point:
+        def __init__(self, x, y):
+            self.x = x
+            self.y = y
+
+    def represent_point(point):
+        return SequenceNode('tag:yaml.org,2002:seq', [
+            ScalarNode('tag:yaml.org,2002:int', str(point.x)),
+            ScalarNode('tag:yaml.org,2002:int', str(point.y)),
+        ], flow_style=True)
+
+    ryaml.add_representer(Point, represent_point)
+    assert ryaml.dumps({'p': Point(1, 2)}) == 'p: [1, 2]\n'
+
+
+def test_representer_applies_to_subclasses():
+    class Base:
+        pass
+
+    class Derived(Base):
+        pass
+
+    ryaml.add_representer(Base, lambda _: ScalarNode('tag:yaml.org,2002:str', 'base'))
+    assert ryaml.dumps(Derived()) == 'base\n'
+
+
+def test_representer_must_return_node():
+    class Opaque:
+        pass
+
+    ryaml.add_representer(Opaque, lambda _: 'not a node')
+    try:
+        ryaml.dumps(Opaque())
+    except ryaml.RepresenterError:
+        pass
+    else:
+        raise AssertionError('expected RepresenterError')
+
+
+def test_registration_mid_dump_applies_to_next_call():
+    class Item:
+        pass
+
+    def second(_):
+        return ScalarNode('tag:yaml.org,2002:str', 'second')
+
+    def first(_):
+        # The dump in progress keeps dispatching from its snapshot
+        ryaml.add_representer(Item, second)
+        return ScalarNode('tag:yaml.org,2002:str', 'first')
+
+    ryaml.add_representer(Item, first)
+    assert ryaml.dumps([Item(), Item(), Item()]) == '- first\n- first\n- first\n'
+    assert ryaml.dumps([Item(), Item()]) == '- second\n- second\n'
+
+
+def test_concurrent_registration_is_consistent():
+    class Item:
+        pass
+
+    def version(name):
+        return lambda _: ScalarNode('tag:yaml.org,2002:str', name)
+
+    ryaml.add_representer(Item, version('v0'))
+    data = [Item() for _ in range(200)]
+    stop = threading.Event()
+    outputs = []
+
+    def register():
+        i = 0
+        while not stop.is_set():
+            i += 1
+            ryaml.add_representer(Item, version(f'v{i}'))
+
+    def dump():
+        for _ in range(20):
+            outputs.append(ryaml.dumps(data))
+
+    registrar = threading.Thread(target=register)
+    dumpers = [threading.Thread(target=dump) for _ in range(4)]
+    registrar.start()
+    for thread in dumpers:
+        thread.start()
+    for thread in dumpers:
+        thread.join()
+    stop.set()
+    registrar.join()
+
+    assert len(outputs) == 80
+    for output in outputs:
+        # Every item of one dump went through the same representer
+        assert len(set(output.splitlines())) == 1
+
+
+def test_add_constructor_scalar():
+    ryaml.add_constructor('!Env', lambda loader, node: 'env:' + loader.construct_scalar(node))
+    assert ryaml.loads('a: !Env prod\nb: plain') == {'a': 'env:prod', 'b': 'plain'}
+
+
+def test_add_constructor_mapping_and_nested_tags():
+    class Point:
+        def __init__(self, x, y):
+            self.x = x
+            self.y = y
+
+    ryaml.add_constructor('!Point', lambda loader, node: Point(**loader.construct_mapping(node)))
+    ryaml.add_constructor('!Twice', lambda loader, node: 2 * int(loader.construct_scalar(node)))
+    point = ryaml.loads('!Point {x: 1, y: !Twice 3}')
+    assert (point.x, point.y) == (1, 6)
+
+
+def test_add_constructor_sequence():
+    ryaml.add_constructor('!Tuple', lambda loader, node: tuple(loader.construct_sequence(node)))
+    assert ryaml.loads('- !Tuple [1, [2, 3]]') == [(1, [2, 3])]
+
+
+def test_constructed_value_is_anchored():
+    ryaml.add_constructor('!Obj', lambda loader, node: object())
+    data = ryaml.loads('a: &o !Obj x\nb: *o')
+    assert data['a'] is data['b']
+
+
+def test_add_constructor_shorthand_tag():
+    ryaml.add_constructor('!!celsius', lambda loader, node: float(loader.construct_scalar(node)))
+    assert ryaml.loads('!!celsius 21') == 21.0
+
+
+def test_loader_add_constructor():
+    from ryaml._ryaml import _RSafeLoader
+
+    _RSafeLoader.add_constructor('!Upper', lambda loader, node: loader.construct_scalar(node).upper())
+    assert _RSafeLoader('!Upper abc').get_single_data() == 'ABC'
+    assert ryaml.loads('!Upper def') == 'DEF'
+
+
+def test_constructor_errors_propagate():
+    def fail(loader, node):
+        raise ValueError('bad value')
+
+    ryaml.add_constructor('!Fail', fail)
+    with pytest.raises(ValueError, match='bad value'):
+        ryaml.loads('!Fail x')
+
+
+def test_constructor_wrong_node_kind():
+    ryaml.add_constructor('!Scalar', lambda loader, node: loader.construct_scalar(node))
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads('a: !Scalar [1]')
+    assert info.value.problem == 'expected a scalar node, but found sequence'
+    assert (info.value.problem_mark.line, info.value.problem_mark.column) == (0, 3)
+
+
+def test_registered_tag_typo_suggestion():
+    ryaml.add_constructor('!Environment', lambda loader, node: None)
+    with pytest.raises(ryaml.ConstructorError) as info:
+        ryaml.loads('!Enviroment prod')
+    assert info.value.note == "did you mean '!Environment'?"
+
+
+def test_restore_registries():
+    saved = _ryaml._state.save_registries()
+    ryaml.add_constructor('!Temporary', lambda loader, node: 'constructed')
+    assert ryaml.loads('!Temporary x') == 'constructed'
+    _ryaml._state.restore_registries(saved)
+    with pytest.raises(ryaml.ConstructorError):
+        ryaml.loads('!Temporary x')
+
+
+def test_constructor_must_be_callable():
+    with pytest.raises(TypeError):
+        ryaml.add_constructor('!Nope', 42)
+
+
+def test_implicit_resolver_load():
+    ryaml.add_implicit_resolver('!semver', r'v\d+\.\d+\.\d+', first='v')
+    ryaml.add_constructor('!semver', lambda loader, node: tuple(
+        int(part) for part in loader.construct_scalar(node)[1:].split('.')
+    ))
+    assert ryaml.loads('version: v1.2.3\nquoted: "v1.2.3"\nname: v1') == {
+        'version': (1, 2, 3),
+        'quoted': 'v1.2.3',
+        'name': 'v1',
+    }
+
+
+def test_implicit_resolver_without_constructor_loads_str():
+    ryaml.add_implicit_resolver('!env', r'\$\{[A-Z_]+\}', first='$')
+    assert ryaml.loads('home: ${HOME}') == {'home': '${HOME}'}
+    assert ryaml.compose('${HOME}').tag == '!env'
+
+
+def test_implicit_resolver_dump():
+    ryaml.add_implicit_resolver('!ref', r'ref/[a-z]+', first=['r'])
+    # Strings which would load back as the custom tag are quoted
+    assert ryaml.dumps(['ref/home', 'home']) == "- 'ref/home'\n- home\n"
+    # Nodes with the custom tag are written plain, without their tag
+    assert ryaml.serialize(ScalarNode('!ref', 'ref/home')) == 'ref/home\n'
+
+
+def test_builtin_resolvers_take_precedence():
+    ryaml.add_implicit_resolver('!yes', 'yes|no')
+    assert ryaml.loads('[yes, no]') == [True, False]
+
+
+def test_restore_implicit_resolvers():
+    saved = _ryaml._state.save_registries()
+    ryaml.add_implicit_resolver('!temporary', r'tmp-\d+', first='t')
+    assert ryaml.compose('tmp-1').tag == '!temporary'
+    _ryaml._state.restore_registries(saved)
+    assert ryaml.compose('tmp-1').tag == 'tag:yaml.org,2002:str'
+    assert ryaml.dumps('tmp-1') == 'tmp-1\n'
+
+
+def test_implicit_resolver_bad_pattern():
+    with pytest.raises(re.error):
+        ryaml.add_implicit_resolver('!bad', '(')
+
+
+def test_implicit_resolvers_registered_later_apply_to_repeated_values():
+    assert ryaml.dumps(['chartreuse', 'chartreuse']) == '- chartreuse\n- chartreuse\n'
+    ryaml.add_implicit_resolver('!chartreuse', 'chartreuse', first='c')
+    assert ryaml.dumps(['chartreuse', 'chartreuse']) == "- 'chartreuse'\n- 'chartreuse'\n"
+    node = ryaml.compose('[chartreuse, chartreuse, "chartreuse"]')
+    assert [item.tag for item in node.value] == [
+        '!chartreuse',
+        '!chartreuse',
+        'tag:yaml.org,2002:str',
+    ]
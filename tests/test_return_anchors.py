@@ -0,0 +1,48 @@
+import io
+
+import ryaml
+
+
+def test_return_anchors():
+    text = '''\
+defaults: &defaults {replicas: 1, image: web}
+ports: &ports [80, 443]
+name: &name web
+service:
+  <<: *defaults
+  ports: *ports
+'''
+    data, anchors = ryaml.loads(text, return_anchors=True)
+    assert anchors == {
+        'defaults': {'replicas': 1, 'image': 'web'},
+        'ports': [80, 443],
+        'name': 'web',
+    }
+    assert anchors['defaults'] is data['defaults']
+    assert anchors['ports'] is data['service']['ports']
+
+
+def test_no_anchors():
+    assert ryaml.loads('{"a": 1}', return_anchors=True) == ({'a': 1}, {})
+    assert ryaml.loads('', return_anchors=True) == (None, {})
+
+
+def test_redefined_anchor():
+    _, anchors = ryaml.loads('[&a 1, *a, &a 2, *a]', return_anchors=True)
+    assert anchors == {'a': 2}
+
+
+def test_tagged_and_nested():
+    _, anchors = ryaml.loads('outer: &outer\n  inner: &inner !!set {x}\n', return_anchors=True)
+    assert anchors == {'outer': {'inner': {'x'}}, 'inner': {'x'}}
+
+
+def test_with_explain():
+    data, resolutions, anchors = ryaml.loads('a: &n 1', explain=True, return_anchors=True)
+    assert data == {'a': 1}
+    assert [resolution.value for resolution in resolutions] == ['1']
+    assert anchors == {'n': 1}
+
+
+def test_load():
+    assert ryaml.load(io.StringIO('- &x 1\n'), return_anchors=True) == ([1], {'x': 1})
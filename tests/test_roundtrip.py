@@ -0,0 +1,106 @@
+import ryaml
+from ryaml.containers import StyledDict, StyledList, StyledStr
+
+SOURCE = (
+    'defaults: &defaults\n'
+    "  name: 'web'\n"
+    '  ports: [80, 443]\n'
+    'prod: *defaults\n'
+    'note: |\n'
+    '  line one\n'
+    '  line two\n'
+    'quoted: "x"\n'
+)
+
+
+def test_roundtrip_reproduces_source():
+    assert ryaml.dumps(ryaml.loads(SOURCE, roundtrip=True)) == SOURCE
+
+
+def test_roundtrip_types():
+    data = ryaml.loads(SOURCE, roundtrip=True)
+    assert isinstance(data, StyledDict)
+    assert data['defaults'].anchor == 'defaults'
+    assert data['prod'] is data['defaults']
+    assert isinstance(data['defaults']['ports'], StyledList)
+    assert data['defaults']['ports'].anchor is None
+    assert isinstance(data['quoted'], StyledStr)
+    assert data['quoted'].style == '"'
+    assert data['note'].style == '|'
+    # Plain strings stay plain
+    assert type(data['defaults']['ports'][0]) is int
+    assert type(next(iter(data))) is str
+    assert data == ryaml.loads(SOURCE)
+
+
+def test_unaliased_anchor_kept():
+    source = 'base: &base {x: 1}\n'
+    assert ryaml.dumps(ryaml.loads(source, roundtrip=True)) == source
+
+
+def test_generated_anchors_avoid_recorded_names():
+    data = ryaml.loads('a: &id001 [1]\nb: *id001\n', roundtrip=True)
+    shared = [2]
+    data['c'] = shared
+    data['d'] = shared
+    assert ryaml.dumps(data) == 'a: &id001 [1]\nb: *id001\nc: &id002\n- 2\nd: *id002\n'
+
+
+def test_edited_strings_lose_their_style():
+    data = ryaml.loads(SOURCE, roundtrip=True)
+    data['quoted'] = 'y'
+    assert ryaml.dumps(data).endswith('quoted: y\n')
+
+
+COMMENTED = (
+    '# Service settings\n'
+    'name: web  # the service name\n'
+    '\n'
+    'server:\n'
+    '  # where to listen\n'
+    '  host: localhost\n'
+    '  ports: [80, 443]  # http and https\n'
+    'script: |  # run at start\n'
+    '  echo hi\n'
+)
+
+
+def test_comments_round_trip():
+    assert ryaml.dumps(ryaml.loads(COMMENTED, roundtrip=True)) == COMMENTED
+
+
+def test_comments_recorded_by_key():
+    data = ryaml.loads(COMMENTED, roundtrip=True)
+    assert data.comments['name'] == ryaml.Comments(('# Service settings',), '# the service name')
+    assert data.comments['server'] == ryaml.Comments(('',), None)
+    assert data['server'].comments['host'].before == ('# where to listen',)
+    assert data['server'].comments['ports'].inline == '# http and https'
+    assert data.comments['script'].inline == '# run at start'
+
+
+def test_comments_kept_after_edit():
+    data = ryaml.loads(COMMENTED, roundtrip=True)
+    data['name'] = 'api'
+    data['server']['host'] = '0.0.0.0'
+    data.comments['script'] = ryaml.Comments(before=('# startup',))
+    assert ryaml.dumps(data) == (
+        '# Service settings\n'
+        'name: api  # the service name\n'
+        '\n'
+        'server:\n'
+        '  # where to listen\n'
+        '  host: 0.0.0.0\n'
+        '  ports: [80, 443]  # http and https\n'
+        '# startup\n'
+        'script: |\n'
+        '  echo hi\n'
+    )
+
+
+def test_comments_in_encoded_output():
+    data = ryaml.loads('a: 1  # one\n', roundtrip=True)
+    assert ryaml.dumps(data, encoding='utf-16-le') == '\ufeffa: 1  # one\n'.encode('utf-16-le')
+
+
+def test_comments_without_roundtrip_are_dropped():
+    assert '#' not in ryaml.dumps(ryaml.loads(COMMENTED))
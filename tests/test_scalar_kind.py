@@ -0,0 +1,53 @@
+import pytest
+
+import ryaml
+
+
+@pytest.mark.parametrize('value, kind', [
+    ('', 'null'),
+    ('~', 'null'),
+    ('yes', 'bool'),
+    ('Off', 'bool'),
+    ('42', 'int'),
+    ('0x1F', 'int'),
+    ('1_000', 'int'),
+    ('1.5', 'float'),
+    ('.inf', 'float'),
+    ('2024-03-01', 'timestamp'),
+    ('<<', 'merge'),
+    ('=', 'value'),
+    ('hello', 'str'),
+    ('1.2.3', 'str'),
+])
+def test_scalar_kind(value, kind):
+    assert ryaml.scalar_kind(value) == kind
+
+
+@pytest.mark.parametrize('value', ['yes', '1.0', '2024-03-01', 'plain'])
+def test_scalar_kind_matches_dumper_quoting(value):
+    quoted = ryaml.dumps(value).startswith("'")
+    assert quoted == (ryaml.scalar_kind(value) != 'str')
+
+
+@pytest.mark.parametrize('value, expected', [
+    ('hello', 'hello'),
+    ("it's", "it's"),
+    ('héllo', 'héllo'),
+    ('yes', "'yes'"),
+    ('1.0', "'1.0'"),
+    ('', "''"),
+    ('a: b', "'a: b'"),
+    ('line\nbreak', '"line\\nbreak"'),
+])
+def test_quote_if_needed(value, expected):
+    assert ryaml.quote_if_needed(value) == expected
+
+
+@pytest.mark.parametrize('value', [
+    'plain', 'null', '~', '0o17', '2024-03-01', '- item', '#comment', '{x}',
+    ' padded ', 'tab\there', 'multi\nline\n', "quote ' and \" both", 'x' * 200,
+])
+def test_quote_if_needed_round_trip(value):
+    quoted = ryaml.quote_if_needed(value)
+    assert '\n' not in quoted
+    assert ryaml.loads(f'key: {quoted}\n') == {'key': value}
@@ -0,0 +1,106 @@
+import pytest
+
+import ryaml
+
+
+def test_core_booleans():
+    assert ryaml.loads('[yes, no, on, off, true, False]', schema='core') == [
+        'yes', 'no', 'on', 'off', True, False,
+    ]
+
+
+def test_core_integers():
+    assert ryaml.loads('[0o17, 0x1F, 017, -42, +7]', schema='core') == [15, 31, 17, -42, 7]
+
+
+def test_core_rejects_yaml11_numbers():
+    assert ryaml.loads('[1:30, 0b101, 1_000, 2001-12-14]', schema='core') == [
+        '1:30', '0b101', '1_000', '2001-12-14',
+    ]
+
+
+def test_core_floats():
+    assert ryaml.loads('[1e3, .5, -1.5E-2, .inf, 1.]', schema='core') == [
+        1000.0, 0.5, -0.015, float('inf'), 1.0,
+    ]
+
+
+def test_yaml11_is_default():
+    assert ryaml.loads('[yes, 1:30, 0o17]') == [True, 90, '0o17']
+
+
+def test_core_dump_quoting():
+    assert ryaml.dumps(['yes', '0o17', '1:30'], schema='core') == "- yes\n- '0o17'\n- 1:30\n"
+    assert ryaml.dumps(['yes', '0o17', '1:30']) == "- 'yes'\n- 0o17\n- '1:30'\n"
+
+
+@pytest.mark.parametrize('value', ['yes', '0o17', '0x1F', '1:30', '1e3', 'null', '017'])
+def test_core_round_trip(value):
+    dumped = ryaml.dumps({'v': value}, schema='core')
+    assert ryaml.loads(dumped, schema='core') == {'v': value}
+
+
+def test_scalar_kind_schema():
+    assert ryaml.scalar_kind('on', schema='core') == 'str'
+    assert ryaml.scalar_kind('0o17', schema='core') == 'int'
+
+
+def test_unknown_schema():
+    with pytest.raises(ValueError):
+        ryaml.loads('a', schema='1.3')
+
+
+def test_failsafe_everything_is_str():
+    assert ryaml.loads('a: [1, true, null, 1.5, 2001-12-14]\n<<: {}\n', schema='failsafe') == {
+        'a': ['1', 'true', 'null', '1.5', '2001-12-14'],
+        '<<': {},
+    }
+
+
+def test_json_scalars():
+    assert ryaml.loads('[null, true, false, 0, -12, 1.5, 1e3, -0.5E-2, "x"]', schema='json') == [
+        None, True, False, 0, -12, 1.5, 1000.0, -0.005, 'x',
+    ]
+
+
+@pytest.mark.parametrize('value', ['.inf', '.nan', 'yes', 'True', '~', '2001-12-14', '012', 'x'])
+def test_json_rejects_non_json_scalars(value):
+    with pytest.raises(ryaml.ComposerError) as info:
+        ryaml.loads(f'"a": [1, {value}]', schema='json')
+    assert (info.value.problem_mark.line, info.value.problem_mark.column) == (0, 9)
+
+
+def test_json_rejects_non_json_scalars_when_composing():
+    with pytest.raises(ryaml.ComposerError):
+        ryaml.compose('.inf', schema='json')
+
+
+def test_json_dump_quotes_non_json_strings():
+    assert ryaml.dumps(['x', 'null', '1'], schema='json') == "- 'x'\n- 'null'\n- '1'\n"
+
+
+def test_scalar_kind_json():
+    assert ryaml.scalar_kind('.inf', schema='json') is None
+    assert ryaml.scalar_kind('-1', schema='json') == 'int'
+
+
+MERGE_DOC = 'base: &b {x: 1}\nderived:\n  <<: *b\n  y: 2\n'
+
+
+def test_merge_keys_on_with_yaml11():
+    assert ryaml.loads(MERGE_DOC)['derived'] == {'x': 1, 'y': 2}
+
+
+def test_merge_keys_off_with_core():
+    assert ryaml.loads(MERGE_DOC, schema='core')['derived'] == {'<<': {'x': 1}, 'y': 2}
+    assert ryaml.loads('[<<, =]', schema='core') == ['<<', '=']
+    assert ryaml.scalar_kind('<<', schema='core') == 'str'
+
+
+def test_merge_keys_option_overrides_schema():
+    assert ryaml.loads(MERGE_DOC, schema='core', merge_keys=True)['derived'] == {'x': 1, 'y': 2}
+    assert ryaml.loads(MERGE_DOC, merge_keys=False)['derived'] == {'<<': {'x': 1}, 'y': 2}
+
+
+def test_core_dumps_merge_key_unquoted():
+    assert ryaml.dumps({'<<': 1}, schema='core') == '<<: 1\n'
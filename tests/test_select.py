@@ -0,0 +1,110 @@
+import io
+
+import pytest
+
+import ryaml
+
+MANIFEST = '''\
+apiVersion: v1
+kind: Pod
+metadata:
+  name: web
+  labels: {app: web, tier: "frontend"}
+spec:
+  containers:
+  - name: app
+    image: nginx:1.25
+    ports: [{containerPort: 80}]
+  - name: sidecar
+    image: envoy:1.29
+'''
+
+
+def test_select():
+    assert ryaml.select(MANIFEST, 'spec.containers[0].image') == 'nginx:1.25'
+    assert ryaml.select(MANIFEST, 'spec.containers[1].name') == 'sidecar'
+    assert ryaml.select(MANIFEST, 'spec.containers[0].ports[0].containerPort') == 80
+
+
+def test_select_collection():
+    assert ryaml.select(MANIFEST, 'metadata.labels') == {'app': 'web', 'tier': 'frontend'}
+
+
+def test_select_whole_document():
+    assert ryaml.select('a: 1', '') == {'a': 1}
+
+
+def test_select_missing():
+    with pytest.raises(KeyError):
+        ryaml.select(MANIFEST, 'spec.volumes')
+    with pytest.raises(KeyError):
+        ryaml.select(MANIFEST, 'spec.containers[5]')
+    with pytest.raises(KeyError):
+        ryaml.select(MANIFEST, 'kind.name')
+
+
+def test_select_all_wildcards():
+    assert ryaml.select_all(MANIFEST, 'spec.containers[*].image') == ['nginx:1.25', 'envoy:1.29']
+    assert ryaml.select_all(MANIFEST, 'metadata.labels.*') == ['web', 'frontend']
+    assert ryaml.select_all(MANIFEST, 'spec.containers[*].ports[*].containerPort') == [80]
+
+
+def test_select_all_documents():
+    source = '--- {name: a}\n--- {other: 1}\n--- {name: b}\n'
+    assert ryaml.select_all(source, 'name') == ['a', 'b']
+    assert ryaml.select(source, 'name') == 'a'
+
+
+def test_select_stops_at_first_match():
+    # The syntax error after the match is never parsed
+    assert ryaml.select('a: 1\nb: [\n', 'a') == 1
+    with pytest.raises(ryaml.ParserError):
+        ryaml.select_all('a: 1\nb: [\n', 'a')
+
+
+def test_quoted_keys():
+    source = '"a.b": {"[x]": 1}\n'
+    assert ryaml.select(source, '["a.b"]["[x]"]') == 1
+    assert ryaml.select(source, "['a.b']") == {'[x]': 1}
+
+
+def test_aliases():
+    source = 'base: &base {image: nginx, port: 80}\nweb: *base\nlist: &l [1, 2]\nfirst: *l\n'
+    assert ryaml.select(source, 'web.image') == 'nginx'
+    assert ryaml.select(source, 'first[1]') == 2
+
+
+def test_anchors_outside_the_path():
+    source = 'defaults: &d {retries: 3}\njobs:\n- name: a\n  settings: *d\n'
+    assert ryaml.select(source, 'jobs[0].settings') == {'retries': 3}
+
+
+def test_merge_keys():
+    source = 'base: &base {image: nginx, port: 80}\nweb:\n  <<: *base\n  port: 8080\n'
+    assert ryaml.select(source, 'web.image') == 'nginx'
+    assert ryaml.select(source, 'web.port') == 8080
+    assert ryaml.select_all(source, 'web.*') == [8080, 'nginx']
+
+
+def test_tagged_collections_are_constructed():
+    source = 'items: !!set {a, b}\n'
+    assert ryaml.select(source, 'items') == {'a', 'b'}
+
+
+def test_options_apply():
+    assert ryaml.select('a: {b: 1}', 'a.b', schema='failsafe') == '1'
+
+
+def test_select_file_and_path(tmp_path):
+    path = tmp_path / 'pod.yaml'
+    path.write_text(MANIFEST)
+    assert ryaml.select(path, 'metadata.name') == 'web'
+    with open(path) as f:
+        assert ryaml.select(f, 'kind') == 'Pod'
+    assert ryaml.select_all(io.StringIO(MANIFEST), 'spec.containers[*].name') == ['app', 'sidecar']
+
+
+@pytest.mark.parametrize('path', ['a..b', 'a[', 'a[x]', 'a["b]', 'a[0]b', '.'])
+def test_invalid_paths(path):
+    with pytest.raises(ValueError, match='invalid path'):
+        ryaml.select('a: 1', path)
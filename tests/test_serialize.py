@@ -0,0 +1,34 @@
+import io
+
+import pytest
+
+import ryaml
+from ryaml.compat import RSafeDumper
+
+
+def test_serialize_compose_round_trip():
+    text = 'key: [1, "2", three]\nother: !custom value\n'
+    assert ryaml.loads(ryaml.serialize(ryaml.compose(text))) == ryaml.loads(text)
+    assert '!custom value' in ryaml.serialize(ryaml.compose(text))
+
+def test_serialize_keeps_styles():
+    assert ryaml.serialize(ryaml.compose("a: 'x'\nb: [1, 2]\n")) == "a: 'x'\nb: [1, 2]\n"
+
+def test_serialize_shared_node_as_alias():
+    output = ryaml.serialize(ryaml.compose('a: &x [1]\nb: *x\n'))
+    assert '&id001' in output
+    assert '*id001' in output
+
+def test_serialize_all():
+    nodes = ryaml.compose_all('--- 1\n--- 2\n')
+    assert ryaml.loads_all(ryaml.serialize_all(nodes)) == [1, 2]
+
+def test_dumper_serialize():
+    stream = io.StringIO()
+    dumper = RSafeDumper(stream)
+    with pytest.raises(ryaml.SerializerError):
+        dumper.serialize(ryaml.compose('1'))
+    dumper.open()
+    dumper.serialize_all(ryaml.compose_all('--- a\n--- b\n'))
+    dumper.close()
+    assert ryaml.loads_all(stream.getvalue()) == ['a', 'b']
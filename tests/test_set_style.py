@@ -0,0 +1,52 @@
+import pytest
+
+import ryaml
+from ryaml import compat
+from ryaml.compat import RSafeDumper
+
+
+def test_block_by_default():
+    assert ryaml.dumps({'s': {'a'}}) == 's: !!set\n  a: null\n'
+
+
+def test_flow():
+    assert ryaml.dumps({'s': {'a'}}, set_style='flow') == 's: !!set {a: null}\n'
+    assert ryaml.loads(ryaml.dumps({'s': {'a', 'b'}}, set_style='flow')) == {'s': {'a', 'b'}}
+
+
+def test_sequence_is_sorted():
+    data = {'s': {'pear', 'apple', 'fig'}, 'n': frozenset({3, 1, 2})}
+    assert ryaml.dumps(data, set_style='sequence') == (
+        's:\n- apple\n- fig\n- pear\nn:\n- 1\n- 2\n- 3\n'
+    )
+
+
+def test_sequence_of_items_that_dont_compare():
+    # Ordered by repr: 'a', 1, None
+    assert ryaml.dumps({'s': {1, 'a', None}}, set_style='sequence') == 's:\n- a\n- 1\n- null\n'
+
+
+def test_sequence_of_nested_items():
+    assert ryaml.dumps({'s': {(1, 2)}}, set_style='sequence') == 's:\n- - 1\n  - 2\n'
+
+
+def test_compact_sequence():
+    assert ryaml.dumps({'s': {2, 1}}, set_style='sequence', compact_threshold=4) == 's: [1, 2]\n'
+
+
+def test_dumper():
+    assert compat.dump({'s': {'b', 'a'}}, Dumper=RSafeDumper, set_style='sequence') == (
+        's:\n- a\n- b\n'
+    )
+
+
+def test_dumper_subclass():
+    class Dumper(RSafeDumper):
+        pass
+
+    assert compat.dump({'s': {'a'}}, Dumper=Dumper, set_style='flow') == 's: !!set {a: null}\n'
+
+
+def test_unsupported():
+    with pytest.raises(ValueError, match="unsupported set_style 'list'"):
+        ryaml.dumps({1}, set_style='list')
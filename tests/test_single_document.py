@@ -0,0 +1,47 @@
+import io
+
+import pytest
+
+import ryaml
+
+
+@pytest.mark.parametrize('text', [
+    'a: 1\n---\nb: 2\n',
+    'a: 1\n...\nb: 2\n',
+    'a: 1\n--- \n',
+    '{"a": 1}\n---\n{"b": 2}\n',
+    '[1]\n...\n# comment\ntrailing\n',
+])
+def test_second_document(text):
+    with pytest.raises(ryaml.ComposerError, match='but found another document') as info:
+        ryaml.loads(text)
+    assert info.value.problem_mark is not None
+
+
+@pytest.mark.parametrize('text', [
+    'a: 1\n...\n',
+    'a: 1\n...\n# only a comment\n',
+    '--- {a: 1}\n# trailing comment\n',
+])
+def test_trailing_comments(text):
+    assert ryaml.loads(text) == {'a': 1}
+
+
+def test_allow_multiple():
+    assert ryaml.loads('a: 1\n---\nb: 2\n', allow_multiple=True) == {'a': 1}
+    assert ryaml.loads('{"a": 1}\n---\n{"b": 2}\n', allow_multiple=True) == {'a': 1}
+    assert ryaml.loads('', allow_multiple=True) is None
+
+
+def test_allow_multiple_leaves_the_rest_unread():
+    assert ryaml.loads('a: 1\n---\n[unclosed\n', allow_multiple=True) == {'a': 1}
+
+
+def test_allow_multiple_with_explain():
+    data, resolutions = ryaml.loads('1\n---\n2\n', allow_multiple=True, explain=True)
+    assert data == 1
+    assert len(resolutions) == 1
+
+
+def test_load_allow_multiple():
+    assert ryaml.load(io.StringIO('- 1\n---\n- 2\n'), allow_multiple=True) == [1]
@@ -0,0 +1,60 @@
+import pytest
+import yaml
+
+import ryaml
+from ryaml.compat import RSafeDumper
+
+
+def test_unsorted_by_default():
+    assert ryaml.dumps({'b': 1, 'a': 2}) == 'b: 1\na: 2\n'
+
+
+def test_sorted():
+    assert ryaml.dumps({'b': 1, 'a': {'d': 1, 'c': 2}}, sort_keys=True) == 'a:\n  c: 2\n  d: 1\nb: 1\n'
+
+
+def test_keys_that_dont_compare_keep_their_order():
+    assert ryaml.dumps({'b': 1, 1: 2, 'a': 3}, sort_keys=True) == 'b: 1\n1: 2\na: 3\n'
+
+
+def test_natural():
+    data = {'item10': 1, 'item2': 2, 'item1': 3, 'Item3': 4, 'item': 5}
+    assert list(ryaml.loads(ryaml.dumps(data, sort_keys='natural'))) == [
+        'Item3', 'item', 'item1', 'item2', 'item10',
+    ]
+
+
+def test_natural_numbers_and_leading_zeros():
+    data = {'v1.10': 0, 'v1.9': 0, 'v1.09b': 0, 100: 0, 20: 0}
+    assert list(ryaml.loads(ryaml.dumps(data, sort_keys='natural'))) == [20, 100, 'v1.9', 'v1.09b', 'v1.10']
+
+
+def test_key_function():
+    data = {'bb': 1, 'a': 2, 'ccc': 3}
+    assert ryaml.dumps(data, sort_keys=len) == 'a: 2\nbb: 1\nccc: 3\n'
+    assert ryaml.dumps(data, sort_keys=lambda key: -len(key)) == 'ccc: 3\nbb: 1\na: 2\n'
+
+
+def test_key_function_is_stable():
+    data = {'b': 1, 'a': 2, 'cc': 3}
+    assert ryaml.dumps(data, sort_keys=len) == 'b: 1\na: 2\ncc: 3\n'
+
+
+def test_key_function_errors_raise():
+    with pytest.raises(TypeError):
+        ryaml.dumps({'a': 1, 1: 2}, sort_keys=lambda key: key)
+    with pytest.raises(ZeroDivisionError):
+        ryaml.dumps({'a': 1, 'b': 2}, sort_keys=lambda key: 1 / 0)
+
+
+def test_compat_dumper():
+    data = {'x10': 1, 'x9': 2}
+    assert yaml.dump(data, Dumper=RSafeDumper, sort_keys='natural') == 'x9: 2\nx10: 1\n'
+    assert yaml.dump(data, Dumper=RSafeDumper, sort_keys=str.upper) == 'x10: 1\nx9: 2\n'
+    assert yaml.dump(data, Dumper=RSafeDumper, sort_keys=False) == 'x10: 1\nx9: 2\n'
+
+
+@pytest.mark.parametrize('sort_keys, error', [('alpha', ValueError), (3, TypeError)])
+def test_invalid(sort_keys, error):
+    with pytest.raises(error):
+        ryaml.dumps({}, sort_keys=sort_keys)
@@ -0,0 +1,123 @@
+import io
+import os
+import sys
+
+import pytest
+
+import ryaml
+
+
+def fake_stdin(monkeypatch, data):
+    # A locale encoding that would garble the UTF-8 input
+    monkeypatch.setattr(sys, 'stdin', io.TextIOWrapper(io.BytesIO(data), encoding='latin-1'))
+
+
+def fake_stdout(monkeypatch):
+    raw = io.BytesIO()
+    monkeypatch.setattr(sys, 'stdout', io.TextIOWrapper(raw, encoding='latin-1'))
+    return raw
+
+
+def test_load_dash(monkeypatch):
+    fake_stdin(monkeypatch, 'key: héllo\n'.encode('utf8'))
+    assert ryaml.load('-') == {'key': 'héllo'}
+
+
+def test_load_stdin_object(monkeypatch):
+    fake_stdin(monkeypatch, 'key: 世界\n'.encode('utf8'))
+    assert ryaml.load(sys.stdin) == {'key': '世界'}
+
+
+def test_load_all_dash(monkeypatch):
+    fake_stdin(monkeypatch, b'a: 1\n---\nb: 2\n')
+    assert ryaml.load_all('-') == [{'a': 1}, {'b': 2}]
+
+
+def test_stdin_error_name(monkeypatch):
+    fake_stdin(monkeypatch, b'a: [\n')
+    with pytest.raises(ryaml.ParserError) as info:
+        ryaml.load('-')
+    assert info.value.problem_mark.name == '<stdin>'
+
+
+def test_dump_dash(monkeypatch):
+    raw = fake_stdout(monkeypatch)
+    print('before')
+    ryaml.dump('-', {'key': 'héllo'})
+    assert raw.getvalue() == 'before\nkey: héllo\n'.encode('utf8')
+
+
+def test_dump_stdout_object(monkeypatch):
+    raw = fake_stdout(monkeypatch)
+    ryaml.dump(sys.stdout, [1])
+    assert raw.getvalue() == b'- 1\n'
+
+
+def test_stdout_without_buffer(monkeypatch):
+    out = io.StringIO()
+    monkeypatch.setattr(sys, 'stdout', out)
+    ryaml.dump('-', [1])
+    assert out.getvalue() == '- 1\n'
+
+
+def test_other_strings_rejected():
+    with pytest.raises(TypeError):
+        ryaml.load('file.yaml')
+
+
+def pipe(data):
+    read, write = os.pipe()
+    os.write(write, data)
+    os.close(write)
+    return read
+
+
+def test_load_file_descriptor():
+    fd = pipe('a: 世界\n---\nb: 2\n'.encode('utf8'))
+    try:
+        assert ryaml.load_all(fd) == [{'a': '世界'}, {'b': 2}]
+    finally:
+        os.close(fd)
+
+
+def test_load_file_descriptor_error_name():
+    fd = pipe(b'a: [\n')
+    try:
+        with pytest.raises(ryaml.ParserError) as info:
+            ryaml.load(fd)
+        assert info.value.problem_mark.name == f'<fd {fd}>'
+    finally:
+        os.close(fd)
+
+
+def test_load_regular_file_descriptor(tmp_path):
+    path = tmp_path / 'doc.yaml'
+    path.write_bytes(b'skipped\na: 1\n')
+    fd = os.open(path, os.O_RDONLY)
+    try:
+        os.lseek(fd, len('skipped\n'), os.SEEK_SET)
+        assert ryaml.load(fd) == {'a': 1}
+        assert os.lseek(fd, 0, os.SEEK_CUR) == path.stat().st_size
+    finally:
+        os.close(fd)
+
+
+def test_load_piped_stdin(monkeypatch):
+    fd = pipe('key: héllo\n'.encode('utf8'))
+    with open(fd, encoding='latin-1') as stdin:
+        monkeypatch.setattr(sys, 'stdin', stdin)
+        assert ryaml.load('-') == {'key': 'héllo'}
+
+
+def test_load_partly_read_pipe():
+    with open(pipe(b'# header\nitems: [1, 2]\n'), 'rb') as fp:
+        assert fp.readline() == b'# header\n'
+        # Whatever the file object buffered past that line is kept
+        assert ryaml.load(fp) == {'items': [1, 2]}
+        assert fp.read() == b''
+
+
+def test_load_pipe_max_bytes():
+    with open(pipe(b'a: 1\n' * 1000), 'rb') as fp:
+        with pytest.raises(ryaml.ReaderError):
+            ryaml.load(fp, max_bytes=100)
@@ -0,0 +1,25 @@
+import ryaml
+
+
+def test_scalar_keys_load_as_strings():
+    doc = '1: a\n2.5: b\ntrue: c\nnull: d\n0x10: e\nname: f\n'
+    assert ryaml.loads(doc, string_keys=True) == {
+        '1': 'a', '2.5': 'b', 'true': 'c', 'null': 'd', '0x10': 'e', 'name': 'f',
+    }
+
+
+def test_values_keep_their_types():
+    assert ryaml.loads('1: 2\nnested: {3: [4, null]}\n', string_keys=True) == {'1': 2, 'nested': {'3': [4, None]}}
+
+
+def test_merge_keys_still_merge():
+    doc = 'base: &b {1: x}\nderived:\n  <<: *b\n  2: y\n'
+    assert ryaml.loads(doc, string_keys=True)['derived'] == {'1': 'x', '2': 'y'}
+
+
+def test_keys_keep_their_types_by_default():
+    assert ryaml.loads('1: a\nnull: b\n') == {1: 'a', None: 'b'}
+
+
+def test_loads_all():
+    assert ryaml.loads_all('1: a\n---\n2: b\n', string_keys=True) == [{'1': 'a'}, {'2': 'b'}]
@@ -0,0 +1,53 @@
+import datetime
+
+import pytest
+
+import ryaml
+from ryaml._ryaml import ScalarNode
+from ryaml.compat import RSafeDumper
+
+
+def test_int_keys_are_quoted_strings():
+    assert ryaml.dumps({1: 'a', 'b': 2}, stringify_keys=True) == "'1': a\nb: 2\n"
+
+
+def test_scalar_keys_load_back_as_strings():
+    data = {1: 'int', 2.5: 'float', False: 'bool', None: 'null', 'x': 'str'}
+    dumped = ryaml.dumps(data, stringify_keys=True)
+    assert ryaml.loads(dumped) == {'1': 'int', '2.5': 'float', 'false': 'bool', 'null': 'null', 'x': 'str'}
+
+
+def test_nested_keys_and_values_untouched():
+    dumped = ryaml.dumps({'a': {10: [1, None]}}, stringify_keys=True)
+    assert ryaml.loads(dumped) == {'a': {'10': [1, None]}}
+
+
+def test_keys_kept_exact_by_default():
+    assert ryaml.dumps({1: 'a', None: 'b'}) == '1: a\nnull: b\n'
+
+
+class Port:
+    def __init__(self, number):
+        self.number = number
+
+
+@pytest.mark.usefixtures('restore_registries')
+def test_keys_represented_as_numbers():
+    ryaml.add_representer(Port, lambda port: ScalarNode('tag:yaml.org,2002:int', str(port.number)))
+    assert ryaml.dumps({Port(80): 'http'}, stringify_keys=True) == "'80': http\n"
+
+
+def test_numpy_scalar_keys():
+    np = pytest.importorskip('numpy')
+    data = {np.int64(1): 'a', np.float64(2.5): 'b'}
+    assert ryaml.loads(ryaml.dumps(data, dump_numpy=True, stringify_keys=True)) == {'1': 'a', '2.5': 'b'}
+
+
+def test_other_keys_untouched():
+    dumped = ryaml.dumps({datetime.date(2024, 1, 2): 'day', 'text': 1}, stringify_keys=True)
+    assert ryaml.loads(dumped) == {datetime.date(2024, 1, 2): 'day', 'text': 1}
+
+
+def test_compat_dumper():
+    yaml = pytest.importorskip('yaml')
+    assert yaml.dump({1: 'a', None: 'b'}, Dumper=RSafeDumper, stringify_keys=True) == "'1': a\n'null': b\n"
@@ -0,0 +1,58 @@
+import pytest
+
+import ryaml
+from ryaml import TaggedValue
+
+
+def test_unknown_tags_coerced_by_default():
+    assert ryaml.loads('!Ref name') == 'name'
+
+
+def test_scalar():
+    assert ryaml.loads('a: !Ref name\nb: !Ref 12', preserve_unknown_tags=True) == {
+        'a': TaggedValue('!Ref', 'name'),
+        'b': TaggedValue('!Ref', '12'),
+    }
+
+
+def test_collections():
+    data = ryaml.loads('- !Pair [1, 2]\n- !Point {x: 1}', preserve_unknown_tags=True)
+    assert data == [TaggedValue('!Pair', [1, 2]), TaggedValue('!Point', {'x': 1})]
+
+
+def test_standard_tags_are_not_wrapped():
+    assert ryaml.loads('[!!str 1, !!int "2", ! 3]', preserve_unknown_tags=True) == ['1', 2, '3']
+
+
+def test_full_tag():
+    data = ryaml.loads('!!python/tuple [1, 2]', preserve_unknown_tags=True)
+    assert data.tag == 'tag:yaml.org,2002:python/tuple'
+    assert data.value == [1, 2]
+
+
+def test_anchor_refers_to_wrapper():
+    data = ryaml.loads('a: &x !Ref name\nb: *x', preserve_unknown_tags=True)
+    assert data['a'] is data['b']
+
+
+def test_typos_still_raise():
+    with pytest.raises(ryaml.ConstructorError):
+        ryaml.loads('!!flaot 1.5', preserve_unknown_tags=True)
+
+
+@pytest.mark.parametrize('doc', [
+    'a: !Ref name\n',
+    'b: !Pair\n- 1\n- 2\n',
+    'c: !Point\n  x: 1\n',
+    '- !!python/tuple\n  - 1\n',
+    '- !Ref 12\n',
+])
+def test_round_trip(doc):
+    data = ryaml.loads(doc, preserve_unknown_tags=True)
+    assert ryaml.dumps(data) == doc
+
+
+def test_hashable():
+    assert {TaggedValue('!Ref', 'a'): 1}[TaggedValue('!Ref', 'a')] == 1
+    assert TaggedValue('!Ref', 'a') != TaggedValue('!Other', 'a')
+    assert repr(TaggedValue('!Ref', 'a')) == "TaggedValue('!Ref', 'a')"
@@ -0,0 +1,25 @@
+import pytest
+
+hypothesis = pytest.importorskip('hypothesis')
+from hypothesis import strategies as st
+
+from ryaml import testing
+
+
+def test_default_values_round_trip():
+    testing.roundtrip(max_examples=200)
+
+
+def test_custom_strategy_and_options():
+    testing.roundtrip(st.lists(st.text()), dumps_kwargs={'literal_multiline': True})
+
+
+def test_failing_round_trip_is_reported():
+    with pytest.raises(AssertionError, match="1 loaded back as '1'"):
+        testing.roundtrip(st.just(1), loads_kwargs={'schema': 'failsafe'})
+
+
+def test_assert_roundtrips_tells_bools_from_ints():
+    testing.assert_roundtrips([True, 1, float('nan')])
+    with pytest.raises(AssertionError):
+        testing.assert_roundtrips(True, loads_kwargs={'schema': 'failsafe'})
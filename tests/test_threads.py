@@ -0,0 +1,101 @@
+import io
+import threading
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+import yaml
+
+import ryaml
+from ryaml.compat import RSafeDumper, RSafeLoader
+
+THREADS = 8
+
+
+def document(i):
+    return {'id': i, 'name': f'item {i}', 'tags': ['a', 'b', i], 'nested': {'x': [i] * 20}}
+
+
+def test_concurrent_loads_and_dumps():
+    def roundtrip(i):
+        for _ in range(50):
+            data = document(i)
+            assert ryaml.loads(ryaml.dumps(data)) == data
+            assert ryaml.loads_all(ryaml.dumps_all([data, data])) == [data, data]
+        return i
+
+    with ThreadPoolExecutor(THREADS) as pool:
+        assert sorted(pool.map(roundtrip, range(THREADS * 4))) == list(range(THREADS * 4))
+
+
+def test_loader_and_dumper_per_thread():
+    def roundtrip(i):
+        for _ in range(50):
+            data = document(i)
+            text = yaml.dump(data, Dumper=RSafeDumper)
+            assert yaml.load(text, Loader=RSafeLoader) == data
+        return i
+
+    with ThreadPoolExecutor(THREADS) as pool:
+        assert sorted(pool.map(roundtrip, range(THREADS * 4))) == list(range(THREADS * 4))
+
+
+def blocking_in(method, base):
+    """A subclass of `base` whose `method` waits for the test to let it go"""
+    entered = threading.Event()
+    release = threading.Event()
+
+    def wait(self, *args):
+        entered.set()
+        release.wait(10)
+        return getattr(base, method)(self, *args)
+
+    return type('Blocking', (base,), {method: wait}), entered, release
+
+
+def run_blocked(call, entered, release):
+    """Start `call` on a thread, returning once it waits in an override"""
+    errors = []
+
+    def target():
+        try:
+            call()
+        except Exception as e:
+            errors.append(e)
+
+    thread = threading.Thread(target=target)
+    thread.start()
+    assert entered.wait(10)
+    return thread, errors
+
+
+def test_shared_loader_rejects_second_thread():
+    loader_class, entered, release = blocking_in('construct_yaml_str', RSafeLoader)
+    loader = loader_class('--- a\n--- b\n')
+    thread, errors = run_blocked(loader.get_data, entered, release)
+    try:
+        with pytest.raises(RuntimeError, match='loader is in use by another thread'):
+            loader.get_data()
+    finally:
+        release.set()
+        thread.join()
+    assert errors == []
+    # Free again once the first thread is done
+    assert loader.get_data() == 'b'
+
+
+def test_shared_dumper_rejects_second_thread():
+    dumper_class, entered, release = blocking_in('represent_str', RSafeDumper)
+    stream = io.StringIO()
+    dumper = dumper_class(stream)
+    dumper.open()
+    thread, errors = run_blocked(lambda: dumper.represent('first'), entered, release)
+    try:
+        with pytest.raises(RuntimeError, match='dumper is in use by another thread'):
+            dumper.represent('second')
+    finally:
+        release.set()
+        thread.join()
+    assert errors == []
+    dumper.represent(1)
+    dumper.close()
+    assert ryaml.loads_all(stream.getvalue()) == ['first', 1]
@@ -0,0 +1,68 @@
+import datetime
+
+import pytest
+
+import ryaml
+
+UTC = datetime.timezone.utc
+
+
+def test_date():
+    assert ryaml.loads('2002-12-14') == datetime.date(2002, 12, 14)
+
+
+def test_naive_datetime():
+    assert ryaml.loads('2001-12-14 21:59:43.10') == datetime.datetime(
+        2001, 12, 14, 21, 59, 43, 100000
+    )
+
+
+def test_canonical_utc():
+    value = ryaml.loads('2001-12-15T02:59:43.1Z')
+    assert value == datetime.datetime(2001, 12, 15, 2, 59, 43, 100000, tzinfo=UTC)
+    assert value.tzinfo is UTC
+
+
+def test_offset():
+    value = ryaml.loads('2001-12-14t21:59:43.10-05:00')
+    assert value.utcoffset() == datetime.timedelta(hours=-5)
+    assert value == datetime.datetime(2001, 12, 15, 2, 59, 43, 100000, tzinfo=UTC)
+
+
+def test_space_separated_offset():
+    value = ryaml.loads('2001-12-14 21:59:43.10 -5')
+    assert value.utcoffset() == datetime.timedelta(hours=-5)
+
+
+def test_fraction_truncated_to_microseconds():
+    value = ryaml.loads('2001-12-14 21:59:43.1234567')
+    assert value.microsecond == 123456
+
+
+def test_quoted_is_string():
+    assert ryaml.loads("'2002-12-14'") == '2002-12-14'
+
+
+def test_explicit_tag_single_digit_date():
+    assert ryaml.loads('!!timestamp 2002-1-2') == datetime.date(2002, 1, 2)
+
+
+def test_explicit_tag_invalid():
+    with pytest.raises(ryaml.ConstructorError):
+        ryaml.loads('!!timestamp yesterday')
+
+
+def test_parse_datetimes_disabled():
+    assert ryaml.loads('released: 2024-03-01\n', parse_datetimes=False) == {
+        'released': '2024-03-01'
+    }
+
+
+def test_parse_datetimes_disabled_keeps_explicit_tags():
+    value = ryaml.loads('!!timestamp 2024-03-01', parse_datetimes=False)
+    assert value == datetime.date(2024, 3, 1)
+
+
+def test_parse_datetimes_disabled_compose():
+    node = ryaml.compose('2024-03-01', parse_datetimes=False)
+    assert node.tag == 'tag:yaml.org,2002:str'
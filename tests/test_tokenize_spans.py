@@ -0,0 +1,53 @@
+import pytest
+
+import ryaml
+
+
+def test_spans():
+    source = 'a: [1, "x"]\n'
+    spans = ryaml.tokenize_spans(source)
+    assert [kind for kind, _, _ in spans] == [
+        'stream_start', 'block_mapping_start', 'key', 'plain_scalar', 'value',
+        'flow_sequence_start', 'plain_scalar', 'flow_entry', 'double_quoted_scalar',
+        'flow_sequence_end', 'block_end', 'stream_end',
+    ]
+    assert [source[start:end] for kind, start, end in spans if kind.endswith('scalar')] == [
+        'a', '1', '"x"',
+    ]
+
+
+def test_block_folding():
+    source = 'a:\n  - b\n  - c\nd: e\n'
+    spans = ryaml.tokenize_spans(source)
+    starts = [start for kind, start, _ in spans if kind == 'block_sequence_start']
+    ends = [start for kind, start, _ in spans if kind == 'block_end']
+    assert starts == [source.index('- b')]
+    assert ends[0] == source.index('d: e')
+
+
+def test_kinds():
+    source = "%YAML 1.1\n--- !t &x |\n  text\n...\n--- [*x, 'q', >\n  f\n]\n"
+    kinds = {kind for kind, _, _ in ryaml.tokenize_spans(source)}
+    assert {
+        'version_directive', 'document_start', 'document_end', 'tag', 'anchor', 'alias',
+        'literal_scalar', 'single_quoted_scalar', 'folded_scalar',
+    } <= kinds
+
+
+def test_non_ascii_offsets():
+    source = 'é: ü\n'
+    spans = ryaml.tokenize_spans(source)
+    assert [source[start:end] for kind, start, end in spans if kind == 'plain_scalar'] == ['é', 'ü']
+    assert ryaml.tokenize_spans(source.encode()) == spans
+
+
+def test_kinds_are_shared():
+    spans = ryaml.tokenize_spans('- a\n- b\n')
+    entries = [kind for kind, _, _ in spans if kind == 'block_entry']
+    assert entries[0] is entries[1]
+
+
+def test_scanner_error():
+    with pytest.raises(ryaml.ScannerError) as excinfo:
+        ryaml.tokenize_spans('a: "unterminated\n')
+    assert excinfo.value.problem_mark is not None
@@ -0,0 +1,80 @@
+import fractions
+import sys
+
+import pytest
+import yaml
+
+import ryaml
+from ryaml.compat import RFullLoader, RUnsafeLoader
+
+
+class Point:
+    def __init__(self, x, y=0):
+        self.x = x
+        self.y = y
+
+
+class Slotted:
+    __slots__ = ('a', 'b')
+
+
+class WithState:
+    def __setstate__(self, state):
+        self.restored = state
+
+
+def load(s):
+    return RUnsafeLoader(s).get_single_data()
+
+
+def test_object():
+    point = load(f'!!python/object:{__name__}.Point {{x: 1, y: 2}}')
+    assert isinstance(point, Point)
+    assert (point.x, point.y) == (1, 2)
+
+
+def test_object_setstate_and_slots():
+    assert load(f'!!python/object:{__name__}.WithState {{k: 1}}').restored == {'k': 1}
+    slotted = load(f'!!python/object:{__name__}.Slotted {{a: 1, b: 2}}')
+    assert (slotted.a, slotted.b) == (1, 2)
+
+
+def test_object_apply():
+    assert load('!!python/object/apply:fractions.Fraction [1, 3]') == fractions.Fraction(1, 3)
+    point = load(f'!!python/object/apply:{__name__}.Point {{args: [1], kwds: {{y: 5}}}}')
+    assert (point.x, point.y) == (1, 5)
+
+
+def test_object_new():
+    point = load(f'!!python/object/new:{__name__}.Point {{state: {{x: 3, y: 4}}}}')
+    assert (point.x, point.y) == (3, 4)
+    data = load('!!python/object/new:collections.OrderedDict {dictitems: {b: 1, a: 2}}')
+    assert list(data.items()) == [('b', 1), ('a', 2)]
+
+
+def test_name_imports_module(monkeypatch):
+    monkeypatch.delitem(sys.modules, 'colorsys', raising=False)
+    assert load('!!python/name:colorsys.rgb_to_hsv').__name__ == 'rgb_to_hsv'
+
+
+def test_missing_module():
+    with pytest.raises(ryaml.ConstructorError, match="cannot find module 'this_module_does_not_exist'"):
+        load('!!python/name:this_module_does_not_exist.x')
+
+
+def test_object_needs_collection():
+    with pytest.raises(ryaml.ConstructorError, match='expected a sequence or mapping node, but found scalar'):
+        load('!!python/object/apply:fractions.Fraction 1')
+
+
+def test_other_loaders_never_instantiate():
+    doc = f'!!python/object:{__name__}.Point {{x: 1}}'
+    assert ryaml.loads(doc) == {'x': 1}
+    with pytest.raises(ryaml.ConstructorError, match='could not determine a constructor'):
+        RFullLoader(doc).get_single_data()
+    with pytest.raises(TypeError):
+        ryaml.loads(doc, python_tags='unsafe')
+
+
+def test_pyyaml_load():
+    assert yaml.load('!!python/object/apply:fractions.Fraction [1, 2]', Loader=RUnsafeLoader) == fractions.Fraction(1, 2)
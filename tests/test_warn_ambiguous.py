@@ -0,0 +1,75 @@
+import warnings
+
+import pytest
+
+import ryaml
+
+
+@pytest.mark.parametrize('value, change', [
+    ('yes', "'yes' is a bool in YAML 1.1 but a str in YAML 1.2"),
+    ('Off', "'Off' is a bool in YAML 1.1 but a str in YAML 1.2"),
+    ('0755', "'0755' is an octal int in YAML 1.1 but a decimal int in YAML 1.2"),
+    ('1:30', "'1:30' is an int in YAML 1.1 but a str in YAML 1.2"),
+    ('1:30.5', "'1:30.5' is a float in YAML 1.1 but a str in YAML 1.2"),
+    ('0b101', "'0b101' is an int in YAML 1.1 but a str in YAML 1.2"),
+    ('1_000', "'1_000' is an int in YAML 1.1 but a str in YAML 1.2"),
+    ('2024-01-02', "'2024-01-02' is a timestamp in YAML 1.1 but a str in YAML 1.2"),
+])
+def test_warns(value, change):
+    with pytest.warns(UserWarning) as record:
+        ryaml.loads(f'a: 1\nkey: {value}\n', warn_ambiguous=True)
+    assert [str(w.message) for w in record] == [
+        f'{change}\n  in "<unicode string>", line 2, column 6',
+    ]
+
+
+@pytest.mark.parametrize('text', [
+    'a: true', 'a: 07', 'a: 0', 'a: 12', 'a: 1.5', 'a: null', 'a: "yes"', 'a: !!bool yes', 'a: text',
+])
+def test_no_warning(text):
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        ryaml.loads(text, warn_ambiguous=True)
+
+
+def test_off_by_default():
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        assert ryaml.loads('a: yes') == {'a': True}
+
+
+def test_only_with_yaml11_rules():
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        assert ryaml.loads('a: yes', warn_ambiguous=True, schema='core') == {'a': 'yes'}
+
+
+def test_timestamps_left_as_strings():
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        ryaml.loads('a: 2024-01-02', warn_ambiguous=True, parse_datetimes=False)
+
+
+def test_keys():
+    with pytest.warns(UserWarning, match="'on' is a bool"):
+        assert ryaml.loads('on: push', warn_ambiguous=True) == {True: 'push'}
+
+
+def test_values_still_load():
+    with pytest.warns(UserWarning):
+        assert ryaml.loads('[yes, 010]', warn_ambiguous=True) == [True, 8]
+
+
+def test_warnings_as_errors():
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        with pytest.raises(UserWarning, match="'no' is a bool"):
+            ryaml.loads('a: no', warn_ambiguous=True)
+
+
+def test_warning_keeps_text_around_nul():
+    with pytest.warns(UserWarning) as record:
+        ryaml.loads('a: no', warn_ambiguous=True, name='ci\0.yaml')
+    message = str(record[0].message)
+    assert "'no' is a bool" in message
+    assert 'ci\\0.yaml' in message
@@ -0,0 +1,103 @@
+import pytest
+
+import ryaml
+from ryaml._ryaml import ScalarNode
+
+
+class Monster(ryaml.YAMLObject):
+    yaml_tag = '!Monster'
+
+    def __init__(self, name, hp):
+        self.name = name
+        self.hp = hp
+
+
+class Point(ryaml.YAMLObject):
+    yaml_tag = '!yaml-object-point'
+    yaml_flow_style = True
+
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+
+class Celsius(ryaml.YAMLObject):
+    yaml_tag = '!yaml-object-celsius'
+
+    def __init__(self, degrees):
+        self.degrees = degrees
+
+    @classmethod
+    def from_yaml(cls, constructor, node):
+        return cls(float(constructor.construct_scalar(node).rstrip('C')))
+
+    @classmethod
+    def to_yaml(cls, data):
+        return ScalarNode('!yaml-object-celsius', f'{data.degrees}C')
+
+
+class Stateful(ryaml.YAMLObject):
+    yaml_tag = '!yaml-object-stateful'
+
+    def __init__(self, items):
+        self.items = items
+
+    def __getstate__(self):
+        return {'items': list(self.items)}
+
+    def __setstate__(self, state):
+        self.items = tuple(state['items'])
+
+
+def test_load():
+    monster = ryaml.loads('!Monster {name: Cave spider, hp: [2, 6]}')
+    assert isinstance(monster, Monster)
+    assert (monster.name, monster.hp) == ('Cave spider', [2, 6])
+
+
+def test_dump():
+    assert ryaml.dumps(Monster('Dragon', 16)) == '!Monster\nname: Dragon\nhp: 16\n'
+    assert ryaml.dumps([Point(1, 2)]) == '- !yaml-object-point {x: 1, y: 2}\n'
+
+
+def test_round_trip_keeps_aliases():
+    spider = Monster('Cave spider', 2)
+    data = ryaml.loads(ryaml.dumps([spider, spider]))
+    assert data[0] is data[1]
+    assert vars(data[0]) == vars(spider)
+
+
+def test_custom_from_yaml_and_to_yaml():
+    assert ryaml.dumps([Celsius(21.5)]) == '- !yaml-object-celsius 21.5C\n'
+    assert ryaml.loads('!yaml-object-celsius 21.5C').degrees == 21.5
+
+
+def test_getstate_and_setstate():
+    dumped = ryaml.dumps(Stateful((1, 2)))
+    assert dumped == '!yaml-object-stateful\nitems:\n- 1\n- 2\n'
+    assert ryaml.loads(dumped).items == (1, 2)
+
+
+def test_requires_mapping():
+    with pytest.raises(ryaml.ConstructorError, match='expected a mapping node, but found scalar'):
+        ryaml.loads('!Monster spider')
+
+
+def test_classes_without_tag_are_not_registered():
+    class Untagged(ryaml.YAMLObject):
+        pass
+
+    with pytest.raises(ryaml.RepresenterError):
+        ryaml.dumps(Untagged())
+
+
+def test_add_yaml_object():
+    class Plain:
+        yaml_tag = '!yaml-object-plain'
+
+    ryaml.add_yaml_object(Plain)
+    plain = ryaml.loads('!yaml-object-plain {a: 1}')
+    assert isinstance(plain, Plain) and plain.a == 1
+    assert ryaml.dumps(plain) == '!yaml-object-plain\na: 1\n'
+    with pytest.raises(TypeError):
+        ryaml.add_yaml_object(int)
@@ -0,0 +1,51 @@
+import pytest
+
+import ryaml
+from ryaml._ryaml import ScalarNode
+
+
+class Money:
+    def __init__(self, amount, currency):
+        self.amount = amount
+        self.currency = currency
+
+    def __yaml_repr__(self):
+        return {'amount': self.amount, 'currency': self.currency}
+
+
+class Version:
+    def __init__(self, version):
+        self.version = version
+
+    def __yaml_repr__(self):
+        return ScalarNode('!version', self.version)
+
+
+class Recursive:
+    def __yaml_repr__(self):
+        return Recursive()
+
+
+def test_yaml_repr_value_is_represented():
+    assert ryaml.dumps({'price': Money(3, 'EUR')}) == 'price:\n  amount: 3\n  currency: EUR\n'
+
+
+def test_yaml_repr_may_return_a_node():
+    assert ryaml.dumps([Version('1.2')]) == '- !version 1.2\n'
+
+
+def test_yaml_repr_takes_precedence_over_default():
+    assert ryaml.dumps(Money(1, 'USD'), default=str) == 'amount: 1\ncurrency: USD\n'
+
+
+def test_yaml_repr_returning_same_type():
+    with pytest.raises(ryaml.RepresenterError, match=r'__yaml_repr__\(\) returned another'):
+        ryaml.dumps(Recursive())
+
+
+def test_registered_representer_takes_precedence():
+    class Tagged(Money):
+        pass
+
+    ryaml.add_representer(Tagged, lambda m: ScalarNode('tag:yaml.org,2002:str', f'{m.amount} {m.currency}'))
+    assert ryaml.dumps(Tagged(2, 'GBP')) == '2 GBP\n'